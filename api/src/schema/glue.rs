@@ -31,6 +31,10 @@ pub struct Glue {
     pub out_of_range_behavior: Option<OutOfRangeBehavior>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub takeover_mode: Option<TakeoverMode>,
+    /// Only relevant for takeover mode `CatchUp`. Control values which are at most this far away
+    /// from the current target value are applied directly, values further away are scaled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub takeover_catch_range: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub round_target_value: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -45,10 +49,37 @@ pub struct Glue {
     pub interaction: Option<Interaction>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub target_value_sequence: Option<String>,
+    /// Lets each button press advance to the next value of the target value sequence.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_value_sequence_stepping: Option<ValueSequenceStepping>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub feedback_kind: Option<FeedbackKind>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub fire_mode: Option<FireMode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub velocity_threshold: Option<VelocityThreshold>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scale_quantization: Option<ScaleQuantization>,
+    /// Minimum time in milliseconds between two target hits. Target values arriving in between
+    /// are held back and the latest one is applied as soon as the interval has elapsed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_target_hit_interval: Option<u32>,
+    /// Absolute control values which differ less than this from the last processed one are
+    /// ignored.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dead_zone: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hysteresis: Option<Hysteresis>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ramp: Option<Ramp>,
+    /// Feedback values which differ less than this from the previously sent one are not sent.
+    /// Prevents motorized faders from chattering.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub feedback_deadband: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encoder_acceleration: Option<EncoderAcceleration>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_curve: Option<ResponseCurve>,
 }
 
 #[derive(Copy, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
@@ -125,6 +156,152 @@ pub struct OnSinglePressFireMode {
 #[serde(deny_unknown_fields)]
 pub struct OnDoublePressFireMode;
 
+/// Distinguishes soft from hard presses by comparing the incoming velocity with a threshold.
+#[derive(PartialEq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct VelocityThreshold {
+    pub threshold: f64,
+    /// Control value for soft presses. If not set, soft presses and their releases are ignored.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub below_value: Option<f64>,
+    /// Control value for hard presses. If not set, hard presses and their releases are ignored.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub above_value: Option<f64>,
+}
+
+/// Turns a noisy absolute source into a switch which turns on when reaching the upper threshold
+/// and off when reaching the lower threshold.
+#[derive(PartialEq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Hysteresis {
+    pub lower_threshold: f64,
+    pub upper_threshold: f64,
+}
+
+/// Sweeps the target value while a button is held instead of hitting the target directly.
+#[derive(PartialEq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Ramp {
+    /// Time in milliseconds for sweeping from the minimum to the maximum.
+    pub duration: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub curve: Option<RampCurve>,
+    /// Whether releasing the button sweeps back down to the minimum.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub return_on_release: Option<bool>,
+}
+
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub enum RampCurve {
+    Linear,
+    Exponential,
+    Logarithmic,
+}
+
+impl Default for RampCurve {
+    fn default() -> Self {
+        RampCurve::Linear
+    }
+}
+
+/// Multiplies relative increments depending on how fast an encoder is turned.
+#[derive(PartialEq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct EncoderAcceleration {
+    pub profile: EncoderAccelerationProfile,
+    /// Exponent of the exponential profile. Higher values keep moderately fast turns finer.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub curve: Option<f64>,
+}
+
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub enum EncoderAccelerationProfile {
+    Off,
+    Linear,
+    Exponential,
+}
+
+impl Default for EncoderAccelerationProfile {
+    fn default() -> Self {
+        EncoderAccelerationProfile::Off
+    }
+}
+
+/// Shapes absolute control values with a curve defined by anchor points.
+#[derive(PartialEq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ResponseCurve {
+    /// Anchor points. Control values between two points are interpolated, values outside of the
+    /// points take the value of the nearest point.
+    pub points: Vec<ResponseCurvePoint>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interpolation: Option<CurveInterpolation>,
+}
+
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ResponseCurvePoint {
+    /// Incoming control value (0.0 to 1.0).
+    pub x: f64,
+    /// Resulting control value (0.0 to 1.0).
+    pub y: f64,
+}
+
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub enum CurveInterpolation {
+    Linear,
+    Smooth,
+    Step,
+}
+
+impl Default for CurveInterpolation {
+    fn default() -> Self {
+        CurveInterpolation::Linear
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub enum ValueSequenceStepping {
+    Off,
+    Stop,
+    Wrap,
+    PingPong,
+}
+
+impl Default for ValueSequenceStepping {
+    fn default() -> Self {
+        ValueSequenceStepping::Off
+    }
+}
+
+/// Snaps notes generated by the "Send MIDI" target to a musical scale.
+#[derive(PartialEq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ScaleQuantization {
+    pub scale: MusicalScale,
+    /// Pitch class of the root note (0 = C, ..., 11 = B).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub root: Option<u8>,
+}
+
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub enum MusicalScale {
+    Major,
+    NaturalMinor,
+    HarmonicMinor,
+    Dorian,
+    Mixolydian,
+    MajorPentatonic,
+    MinorPentatonic,
+    Blues,
+}
+
+impl Default for MusicalScale {
+    fn default() -> Self {
+        Self::Major
+    }
+}
+
 #[derive(PartialEq, Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(untagged)]
 pub enum VirtualColor {
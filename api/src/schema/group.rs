@@ -19,6 +19,10 @@ pub struct Group {
     pub control_enabled: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub feedback_enabled: Option<bool>,
+    /// MIDI output device to which MIDI sources of this group send their feedback, unless they
+    /// choose a device themselves.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_device_id: Option<u8>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub activation_condition: Option<ActivationCondition>,
 }
@@ -25,6 +25,18 @@ pub struct Mapping {
     pub control_enabled: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub feedback_enabled: Option<bool>,
+    /// Maximum number of times per second this mapping may be controlled.
+    ///
+    /// Control values arriving faster are coalesced: The latest absolute value
+    /// wins, relative increments are summed up.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_control_rate: Option<u32>,
+    /// Layer in which this mapping can be controlled.
+    ///
+    /// Layer 0 is the base layer, which is active initially. If not set, the
+    /// mapping can be controlled no matter which layer is active.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub layer: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub activation_condition: Option<ActivationCondition>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -50,7 +62,13 @@ pub struct LifecycleHook {
 #[derive(PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "kind")]
 pub enum SendMidiFeedbackAction {
-    Raw { message: RawMidiMessage },
+    Raw {
+        message: RawMidiMessage,
+    },
+    /// Sends a MIDI macro defined in the active controller preset.
+    Macro {
+        name: String,
+    },
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
@@ -66,6 +84,8 @@ pub enum ActivationCondition {
     Modifier(ModifierActivationCondition),
     Bank(BankActivationCondition),
     Eel(EelActivationCondition),
+    Expression(ExpressionActivationCondition),
+    ReaperState(ReaperStateActivationCondition),
 }
 
 #[derive(PartialEq, Default, Serialize, Deserialize, JsonSchema)]
@@ -94,6 +114,29 @@ pub struct EelActivationCondition {
     pub condition: String,
 }
 
+#[derive(PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ExpressionActivationCondition {
+    /// Mapping is active if the result is greater than zero, e.g. `p1 > 0 && p2 < 0.5`.
+    pub condition: String,
+}
+
+#[derive(PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ReaperStateActivationCondition {
+    pub state: ReaperState,
+}
+
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub enum ReaperState {
+    Playing,
+    Recording,
+    Stopped,
+    AnyTrackArmed,
+    AnyTrackSelected,
+    AnyFxFocused,
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(untagged)]
 pub enum ParamRef {
@@ -129,6 +172,15 @@ pub struct OscArgument {
     pub kind: Option<OscArgKind>,
 }
 
+/// Lets only those messages through whose argument at the given index has the given value.
+#[derive(Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct OscArgumentFilter {
+    pub index: u32,
+    /// Compared numerically if the argument is a number, textually otherwise.
+    pub value: String,
+}
+
 #[derive(Copy, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub enum OscArgKind {
     Float,
@@ -76,6 +76,7 @@ mod tests {
                     controller_number: Some(64),
                     character: Some(SourceCharacter::Button),
                     fourteen_bit: Some(false),
+                    output_device_id: None,
                 },
             )),
             glue: Some(Glue {
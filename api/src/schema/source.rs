@@ -14,6 +14,9 @@ pub enum Source {
     // REAPER
     MidiDeviceChanges(MidiDeviceChangesSource),
     RealearnInstanceStart(RealearnInstanceStartSource),
+    GameController(GameControllerSource),
+    InstanceMessage(InstanceMessageSource),
+    MidiMachineControl(MidiMachineControlSource),
     // MIDI
     MidiNoteVelocity(MidiNoteVelocitySource),
     MidiNoteKeyNumber(MidiNoteKeyNumberSource),
@@ -70,6 +73,9 @@ mod midi {
         pub channel: Option<u8>,
         #[serde(skip_serializing_if = "Option::is_none")]
         pub key_number: Option<u8>,
+        /// MIDI output device to which feedback goes instead of the instance's feedback output.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub output_device_id: Option<u8>,
     }
 
     #[derive(Default, PartialEq, Serialize, Deserialize, JsonSchema)]
@@ -79,6 +85,9 @@ mod midi {
         pub feedback_behavior: Option<FeedbackBehavior>,
         #[serde(skip_serializing_if = "Option::is_none")]
         pub channel: Option<u8>,
+        /// MIDI output device to which feedback goes instead of the instance's feedback output.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub output_device_id: Option<u8>,
     }
 
     #[derive(Default, PartialEq, Serialize, Deserialize, JsonSchema)]
@@ -90,6 +99,9 @@ mod midi {
         pub channel: Option<u8>,
         #[serde(skip_serializing_if = "Option::is_none")]
         pub key_number: Option<u8>,
+        /// MIDI output device to which feedback goes instead of the instance's feedback output.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub output_device_id: Option<u8>,
     }
 
     #[derive(Default, PartialEq, Serialize, Deserialize, JsonSchema)]
@@ -105,6 +117,9 @@ mod midi {
         pub character: Option<SourceCharacter>,
         #[serde(skip_serializing_if = "Option::is_none")]
         pub fourteen_bit: Option<bool>,
+        /// MIDI output device to which feedback goes instead of the instance's feedback output.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub output_device_id: Option<u8>,
     }
 
     #[derive(Default, PartialEq, Serialize, Deserialize, JsonSchema)]
@@ -114,6 +129,9 @@ mod midi {
         pub feedback_behavior: Option<FeedbackBehavior>,
         #[serde(skip_serializing_if = "Option::is_none")]
         pub channel: Option<u8>,
+        /// MIDI output device to which feedback goes instead of the instance's feedback output.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub output_device_id: Option<u8>,
     }
 
     #[derive(Default, PartialEq, Serialize, Deserialize, JsonSchema)]
@@ -123,6 +141,9 @@ mod midi {
         pub feedback_behavior: Option<FeedbackBehavior>,
         #[serde(skip_serializing_if = "Option::is_none")]
         pub channel: Option<u8>,
+        /// MIDI output device to which feedback goes instead of the instance's feedback output.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub output_device_id: Option<u8>,
     }
 
     #[derive(Default, PartialEq, Serialize, Deserialize, JsonSchema)]
@@ -132,6 +153,9 @@ mod midi {
         pub feedback_behavior: Option<FeedbackBehavior>,
         #[serde(skip_serializing_if = "Option::is_none")]
         pub channel: Option<u8>,
+        /// MIDI output device to which feedback goes instead of the instance's feedback output.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub output_device_id: Option<u8>,
     }
 
     #[derive(Default, PartialEq, Serialize, Deserialize, JsonSchema)]
@@ -149,6 +173,9 @@ mod midi {
         pub registered: Option<bool>,
         #[serde(skip_serializing_if = "Option::is_none")]
         pub character: Option<SourceCharacter>,
+        /// MIDI output device to which feedback goes instead of the instance's feedback output.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub output_device_id: Option<u8>,
     }
 
     #[derive(Default, PartialEq, Serialize, Deserialize, JsonSchema)]
@@ -163,6 +190,90 @@ mod midi {
     #[serde(deny_unknown_fields)]
     pub struct RealearnInstanceStartSource;
 
+    #[derive(Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+    #[serde(deny_unknown_fields)]
+    pub struct GameControllerSource {
+        /// Index of the controller in the order in which the controllers got connected.
+        ///
+        /// If not set, events from any controller are accepted.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub controller_index: Option<u32>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub element: Option<GameControllerElement>,
+    }
+
+    #[derive(Copy, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+    pub enum GameControllerElement {
+        LeftStickX,
+        LeftStickY,
+        LeftZ,
+        RightStickX,
+        RightStickY,
+        RightZ,
+        DPadX,
+        DPadY,
+        South,
+        East,
+        North,
+        West,
+        LeftTrigger,
+        LeftTrigger2,
+        RightTrigger,
+        RightTrigger2,
+        Select,
+        Start,
+        Mode,
+        LeftThumb,
+        RightThumb,
+        DPadUp,
+        DPadDown,
+        DPadLeft,
+        DPadRight,
+    }
+
+    impl Default for GameControllerElement {
+        fn default() -> Self {
+            GameControllerElement::South
+        }
+    }
+
+    #[derive(Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+    #[serde(deny_unknown_fields)]
+    pub struct InstanceMessageSource {
+        /// Name of the message sent by an "Invoke instance" target of another instance.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub message: Option<String>,
+    }
+
+    #[derive(Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+    #[serde(deny_unknown_fields)]
+    pub struct MidiMachineControlSource {
+        /// If not set, commands addressed to any device are accepted.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub device_id: Option<u8>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub command: Option<MidiMachineControlCommand>,
+    }
+
+    #[derive(Copy, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+    pub enum MidiMachineControlCommand {
+        Stop,
+        Play,
+        DeferredPlay,
+        FastForward,
+        Rewind,
+        RecordStrobe,
+        RecordExit,
+        Pause,
+        Locate,
+    }
+
+    impl Default for MidiMachineControlCommand {
+        fn default() -> Self {
+            MidiMachineControlCommand::Play
+        }
+    }
+
     #[derive(Default, PartialEq, Serialize, Deserialize, JsonSchema)]
     #[serde(deny_unknown_fields)]
     pub struct MidiClockTransportSource {
@@ -179,6 +290,9 @@ mod midi {
         pub pattern: Option<String>,
         #[serde(skip_serializing_if = "Option::is_none")]
         pub character: Option<SourceCharacter>,
+        /// MIDI output device to which feedback goes instead of the instance's feedback output.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub output_device_id: Option<u8>,
     }
 
     #[derive(Default, PartialEq, Serialize, Deserialize, JsonSchema)]
@@ -186,6 +300,9 @@ mod midi {
     pub struct MidiScriptSource {
         #[serde(skip_serializing_if = "Option::is_none")]
         pub script: Option<String>,
+        /// MIDI output device to which feedback goes instead of the instance's feedback output.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub output_device_id: Option<u8>,
     }
 
     #[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize, JsonSchema)]
@@ -227,6 +344,9 @@ mod midi {
         pub channel: Option<u8>,
         #[serde(skip_serializing_if = "Option::is_none")]
         pub line: Option<u8>,
+        /// MIDI output device to which feedback goes instead of the instance's feedback output.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub output_device_id: Option<u8>,
     }
 
     #[derive(Copy, Clone, PartialEq, Default, Serialize, Deserialize, JsonSchema)]
@@ -234,6 +354,9 @@ mod midi {
     pub struct MackieSevenSegmentDisplaySource {
         #[serde(skip_serializing_if = "Option::is_none")]
         pub scope: Option<MackieSevenSegmentDisplayScope>,
+        /// MIDI output device to which feedback goes instead of the instance's feedback output.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub output_device_id: Option<u8>,
     }
 
     #[derive(Copy, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
@@ -260,15 +383,22 @@ mod midi {
         pub cell_index: Option<u8>,
         #[serde(skip_serializing_if = "Option::is_none")]
         pub item_index: Option<u8>,
+        /// MIDI output device to which feedback goes instead of the instance's feedback output.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub output_device_id: Option<u8>,
     }
 
     #[derive(Default, PartialEq, Serialize, Deserialize, JsonSchema)]
     #[serde(deny_unknown_fields)]
-    pub struct LaunchpadProScrollingTextDisplaySource;
+    pub struct LaunchpadProScrollingTextDisplaySource {
+        /// MIDI output device to which feedback goes instead of the instance's feedback output.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub output_device_id: Option<u8>,
+    }
 }
 
 mod osc {
-    use crate::schema::{FeedbackBehavior, OscArgument};
+    use crate::schema::{FeedbackBehavior, OscArgument, OscArgumentFilter};
     use schemars::JsonSchema;
     use serde::{Deserialize, Serialize};
 
@@ -281,8 +411,32 @@ mod osc {
         pub address: Option<String>,
         #[serde(skip_serializing_if = "Option::is_none")]
         pub argument: Option<OscArgument>,
+        /// Additional arguments which must have certain values, e.g. for devices which send the
+        /// note number as first and the velocity as second argument.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub argument_filters: Option<Vec<OscArgumentFilter>>,
         #[serde(skip_serializing_if = "Option::is_none")]
         pub relative: Option<bool>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub delta_mode: Option<OscDeltaMode>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub relative_feedback: Option<bool>,
+    }
+
+    #[derive(Copy, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+    pub enum OscDeltaMode {
+        // > 0 = increment; < 0 = decrement
+        Sign,
+        // Value = number of increments
+        Steps,
+        // 64 (int) or 0.5 (float) = none; above = increment; below = decrement
+        Centered,
+    }
+
+    impl Default for OscDeltaMode {
+        fn default() -> Self {
+            OscDeltaMode::Sign
+        }
     }
 }
 
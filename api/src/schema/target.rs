@@ -9,12 +9,20 @@ pub enum Target {
     AutomationModeOverride(AutomationModeOverrideTarget),
     ReaperAction(ReaperActionTarget),
     TransportAction(TransportActionTarget),
+    PunchAction(PunchActionTarget),
+    UndoRedo(UndoRedoTarget),
+    TimeSelection(TimeSelectionTarget),
+    MonitoringVolume(MonitoringVolumeTarget),
+    Metronome(MetronomeTarget),
     AnyOn(AnyOnTarget),
     CycleThroughTracks(CycleThroughTracksTarget),
     Seek(SeekTarget),
     PlayRate(PlayRateTarget),
     Tempo(TempoTarget),
+    SetExtState(SetExtStateTarget),
+    RunScript(RunScriptTarget),
     GoToBookmark(GoToBookmarkTarget),
+    NavigateBookmarks(NavigateBookmarksTarget),
     TrackArmState(TrackArmStateTarget),
     AllTrackFxOnOffState(AllTrackFxOnOffStateTarget),
     TrackMuteState(TrackMuteStateTarget),
@@ -24,18 +32,27 @@ pub enum Target {
     TrackAutomationMode(TrackAutomationModeTarget),
     TrackAutomationTouchState(TrackAutomationTouchStateTarget),
     TrackPan(TrackPanTarget),
+    TrackDualPan(TrackDualPanTarget),
     TrackWidth(TrackWidthTarget),
     TrackVolume(TrackVolumeTarget),
     #[serde(rename = "Track")]
     TrackTool(TrackToolTarget),
     TrackVisibility(TrackVisibilityTarget),
     TrackSoloState(TrackSoloStateTarget),
+    InsertTrack(InsertTrackTarget),
+    DuplicateTrack(DuplicateTrackTarget),
+    DeleteTrack(DeleteTrackTarget),
+    TrackCue(TrackCueTarget),
+    SpillFolder(SpillFolderTarget),
+    TrackRecordInput(TrackRecordInputTarget),
+    TrackInputMonitoring(TrackInputMonitoringTarget),
     CycleThroughFx(CycleThroughFxTarget),
     FxOnOffState(FxOnOffStateTarget),
     LoadFxSnapshot(LoadFxSnapshotTarget),
     CycleThroughFxPresets(CycleThroughFxPresetsTarget),
     FxVisibility(FxVisibilityTarget),
     FxParameterValue(FxParameterValueTarget),
+    FxParameterBank(FxParameterBankTarget),
     RouteAutomationMode(RouteAutomationModeTarget),
     RouteMonoState(RouteMonoStateTarget),
     RouteMuteState(RouteMuteStateTarget),
@@ -45,12 +62,26 @@ pub enum Target {
     ClipTransportAction(ClipTransportActionTarget),
     ClipSeek(ClipSeekTarget),
     ClipVolume(ClipVolumeTarget),
+    ClipColumnStop(ClipColumnStopTarget),
+    ClipSceneLaunch(ClipSceneLaunchTarget),
+    ClipCue(ClipCueTarget),
     SendMidi(SendMidiTarget),
+    SendMidiMacro(SendMidiMacroTarget),
+    SendProgramChange(SendProgramChangeTarget),
+    SendMidiClock(SendMidiClockTarget),
     SendOsc(SendOscTarget),
     EnableInstances(EnableInstancesTarget),
     EnableMappings(EnableMappingsTarget),
+    EnableGroup(EnableGroupTarget),
     LoadMappingSnapshots(LoadMappingSnapshotsTarget),
+    SaveMappingSnapshot(SaveMappingSnapshotTarget),
     CycleThroughGroupMappings(CycleThroughGroupMappingsTarget),
+    SelectLayer(SelectLayerTarget),
+    SetBankOffset(SetBankOffsetTarget),
+    AdjustMappingMode(AdjustMappingModeTarget),
+    Multi(MultiTarget),
+    Panic(PanicTarget),
+    InvokeInstance(InvokeInstanceTarget),
     Virtual(VirtualTarget),
 }
 
@@ -116,6 +147,47 @@ pub struct TransportActionTarget {
     pub action: TransportAction,
 }
 
+#[derive(PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct PunchActionTarget {
+    #[serde(flatten)]
+    pub commons: TargetCommons,
+    pub action: PunchAction,
+}
+
+#[derive(PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct UndoRedoTarget {
+    #[serde(flatten)]
+    pub commons: TargetCommons,
+    pub action: UndoRedoAction,
+}
+
+#[derive(PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct TimeSelectionTarget {
+    #[serde(flatten)]
+    pub commons: TargetCommons,
+    pub action: TimeSelectionAction,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub range: Option<TimeRangeKind>,
+}
+
+#[derive(PartialEq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct MonitoringVolumeTarget {
+    #[serde(flatten)]
+    pub commons: TargetCommons,
+}
+
+#[derive(PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct MetronomeTarget {
+    #[serde(flatten)]
+    pub commons: TargetCommons,
+    pub parameter: MetronomeParameter,
+}
+
 #[derive(PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct AnyOnTarget {
@@ -156,6 +228,31 @@ pub struct SeekTarget {
     pub feedback_resolution: Option<FeedbackResolution>,
 }
 
+#[derive(PartialEq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SetExtStateTarget {
+    #[serde(flatten)]
+    pub commons: TargetCommons,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub section: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<ExtStateValueFormat>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub feedback_resolution: Option<FeedbackResolution>,
+}
+
+#[derive(PartialEq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct RunScriptTarget {
+    #[serde(flatten)]
+    pub commons: TargetCommons,
+    /// Path to the script file (Lua, EEL or Python).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub script: Option<String>,
+}
+
 #[derive(PartialEq, Default, Serialize, Deserialize, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct PlayRateTarget {
@@ -182,6 +279,16 @@ pub struct GoToBookmarkTarget {
     pub set_loop_points: Option<bool>,
 }
 
+#[derive(PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct NavigateBookmarksTarget {
+    #[serde(flatten)]
+    pub commons: TargetCommons,
+    pub bookmark_type: BookmarkType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mode: Option<BookmarkNavigationMode>,
+}
+
 #[derive(PartialEq, Default, Serialize, Deserialize, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct TrackArmStateTarget {
@@ -266,6 +373,18 @@ pub struct TrackAutomationModeTarget {
     pub mode: AutomationMode,
 }
 
+#[derive(PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct TrackInputMonitoringTarget {
+    #[serde(flatten)]
+    pub commons: TargetCommons,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub track: Option<TrackDescriptor>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exclusivity: Option<TrackExclusivity>,
+    pub mode: InputMonitoringMode,
+}
+
 #[derive(PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct TrackAutomationTouchStateTarget {
@@ -287,6 +406,17 @@ pub struct TrackPanTarget {
     pub track: Option<TrackDescriptor>,
 }
 
+#[derive(PartialEq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct TrackDualPanTarget {
+    #[serde(flatten)]
+    pub commons: TargetCommons,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub track: Option<TrackDescriptor>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub component: Option<DualPanComponent>,
+}
+
 #[derive(PartialEq, Default, Serialize, Deserialize, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct TrackWidthTarget {
@@ -314,6 +444,67 @@ pub struct TrackToolTarget {
     pub track: Option<TrackDescriptor>,
 }
 
+#[derive(PartialEq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct InsertTrackTarget {
+    #[serde(flatten)]
+    pub commons: TargetCommons,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub track: Option<TrackDescriptor>,
+}
+
+#[derive(PartialEq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct DuplicateTrackTarget {
+    #[serde(flatten)]
+    pub commons: TargetCommons,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub track: Option<TrackDescriptor>,
+}
+
+#[derive(PartialEq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct DeleteTrackTarget {
+    #[serde(flatten)]
+    pub commons: TargetCommons,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub track: Option<TrackDescriptor>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub require_confirmation: Option<bool>,
+}
+
+#[derive(PartialEq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct TrackCueTarget {
+    #[serde(flatten)]
+    pub commons: TargetCommons,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub track: Option<TrackDescriptor>,
+    /// Index of the first hardware output channel of the stereo cue output.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cue_output: Option<u32>,
+}
+
+#[derive(PartialEq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SpillFolderTarget {
+    #[serde(flatten)]
+    pub commons: TargetCommons,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub track: Option<TrackDescriptor>,
+}
+
+#[derive(PartialEq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct TrackRecordInputTarget {
+    #[serde(flatten)]
+    pub commons: TargetCommons,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub track: Option<TrackDescriptor>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input_kind: Option<RecordInputKind>,
+}
+
 #[derive(PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct TrackVisibilityTarget {
@@ -401,6 +592,17 @@ pub struct FxParameterValueTarget {
     pub poll_for_feedback: Option<bool>,
 }
 
+#[derive(PartialEq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct FxParameterBankTarget {
+    #[serde(flatten)]
+    pub commons: TargetCommons,
+    /// Number of parameters by which FX parameter targets (addressing their parameter by index)
+    /// are shifted when switching to the next bank.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bank_size: Option<u32>,
+}
+
 #[derive(PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct RouteAutomationModeTarget {
@@ -471,6 +673,9 @@ pub struct ClipTransportActionTarget {
     pub next_bar: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub buffered: Option<bool>,
+    /// If not set, the instance-level default is used.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop_quantization: Option<ClipStopQuantization>,
 }
 
 #[derive(PartialEq, Serialize, Deserialize, JsonSchema)]
@@ -491,15 +696,125 @@ pub struct ClipVolumeTarget {
     pub clip: ClipDescriptor,
 }
 
+#[derive(PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ClipColumnStopTarget {
+    #[serde(flatten)]
+    pub commons: TargetCommons,
+    /// The track on which the clips of the column play.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub track: Option<TrackDescriptor>,
+    /// ID of a clip matrix shared among all instances. If not set, the instance's own clip matrix
+    /// is used.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub matrix: Option<String>,
+}
+
+#[derive(PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ClipSceneLaunchTarget {
+    #[serde(flatten)]
+    pub commons: TargetCommons,
+    /// Row of the clip matrix which makes up the scene (zero-based).
+    pub row: u32,
+    /// ID of a clip matrix shared among all instances. If not set, the instance's own clip matrix
+    /// is used.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub matrix: Option<String>,
+    /// Only applies to slots which haven't been played before.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_bar: Option<bool>,
+    /// Only applies to slots which haven't been played before.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub buffered: Option<bool>,
+}
+
+#[derive(PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ClipCueTarget {
+    #[serde(flatten)]
+    pub commons: TargetCommons,
+    pub clip: ClipDescriptor,
+    /// Index of the first hardware output channel of the stereo cue output.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cue_output: Option<u32>,
+}
+
 #[derive(PartialEq, Default, Serialize, Deserialize, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct SendMidiTarget {
     #[serde(flatten)]
     pub commons: TargetCommons,
+    /// Raw MIDI pattern. Several messages are separated by semicolons and can be interleaved with
+    /// delays such as `50ms`.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub message: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub destination: Option<MidiDestination>,
+    /// Interprets the value as root note and sends all notes of this chord.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chord: Option<ChordShape>,
+    /// Sends the notes one after the other instead of all at once.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub arpeggiator: Option<Arpeggiator>,
+}
+
+#[derive(PartialEq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SendMidiMacroTarget {
+    #[serde(flatten)]
+    pub commons: TargetCommons,
+    /// Name of a MIDI macro defined in the active controller preset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub macro_name: Option<String>,
+}
+
+#[derive(PartialEq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SendProgramChangeTarget {
+    #[serde(flatten)]
+    pub commons: TargetCommons,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub destination: Option<MidiDeviceDestination>,
+    /// 0-based MIDI channel.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channel: Option<u8>,
+    /// The patches to choose from, in order.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub patches: Option<Vec<ProgramChangePatch>>,
+}
+
+#[derive(PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ProgramChangePatch {
+    pub name: String,
+    /// Bank select MSB (CC 0).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bank_msb: Option<u8>,
+    /// Bank select LSB (CC 32).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bank_lsb: Option<u8>,
+    /// 0-based program number.
+    pub program: u8,
+}
+
+#[derive(PartialEq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SendMidiClockTarget {
+    #[serde(flatten)]
+    pub commons: TargetCommons,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub destination: Option<MidiDeviceDestination>,
+    /// If set, MIDI time code is sent in addition to MIDI clock.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mtc_frame_rate: Option<MtcFrameRate>,
+}
+
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub enum MtcFrameRate {
+    Fps24,
+    Fps25,
+    Fps30,
 }
 
 #[derive(PartialEq, Default, Serialize, Deserialize, JsonSchema)]
@@ -537,6 +852,17 @@ pub struct EnableMappingsTarget {
     pub exclusivity: Option<MappingExclusivity>,
 }
 
+#[derive(PartialEq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct EnableGroupTarget {
+    #[serde(flatten)]
+    pub commons: TargetCommons,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mode: Option<GroupEnableMode>,
+}
+
 #[derive(PartialEq, Default, Serialize, Deserialize, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct LoadMappingSnapshotsTarget {
@@ -546,6 +872,25 @@ pub struct LoadMappingSnapshotsTarget {
     pub tags: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub active_mappings_only: Option<bool>,
+    /// ID of the snapshot to load. If not set, the initial snapshot is loaded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snapshot: Option<String>,
+    /// Fade time in milliseconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fade_duration: Option<u32>,
+}
+
+#[derive(PartialEq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SaveMappingSnapshotTarget {
+    #[serde(flatten)]
+    pub commons: TargetCommons,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active_mappings_only: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snapshot: Option<String>,
 }
 
 #[derive(PartialEq, Default, Serialize, Deserialize, JsonSchema)]
@@ -559,6 +904,100 @@ pub struct CycleThroughGroupMappingsTarget {
     pub group: Option<String>,
 }
 
+#[derive(PartialEq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SelectLayerTarget {
+    #[serde(flatten)]
+    pub commons: TargetCommons,
+    /// Layer 0 is the base layer.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub layer: Option<u32>,
+}
+
+#[derive(PartialEq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SetBankOffsetTarget {
+    #[serde(flatten)]
+    pub commons: TargetCommons,
+    /// Number which is added to the index of each indexed virtual control element.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<u32>,
+}
+
+#[derive(PartialEq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AdjustMappingModeTarget {
+    #[serde(flatten)]
+    pub commons: TargetCommons,
+    /// Key of the mapping (in the same compartment) whose mode should be adjusted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mapping: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub setting: Option<MappingModeSetting>,
+}
+
+#[derive(PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct MultiTarget {
+    #[serde(flatten)]
+    pub commons: TargetCommons,
+    /// Child targets, hit in this order.
+    pub targets: Vec<MultiTargetChild>,
+}
+
+#[derive(PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct MultiTargetChild {
+    pub target: Target,
+    /// Factor (0.0 to 1.0) applied to absolute control values before they are passed to this
+    /// child target.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scale: Option<f64>,
+}
+
+#[derive(PartialEq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct PanicTarget {
+    #[serde(flatten)]
+    pub commons: TargetCommons,
+}
+
+#[derive(PartialEq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct InvokeInstanceTarget {
+    #[serde(flatten)]
+    pub commons: TargetCommons,
+    /// Instances with at least one of these tags receive the message. If not given, all other
+    /// instances in the same project receive it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+    /// ID of the receiving session. Takes precedence over tags.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session: Option<String>,
+    /// Name of the message.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub enum MappingModeSetting {
+    SourceMin,
+    SourceMax,
+    TargetMin,
+    TargetMax,
+    StepSizeMin,
+    StepSizeMax,
+    JumpMin,
+    JumpMax,
+    DeadZone,
+}
+
+impl Default for MappingModeSetting {
+    fn default() -> Self {
+        Self::TargetMax
+    }
+}
+
 #[derive(Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct VirtualTarget {
@@ -584,6 +1023,13 @@ pub enum AutomationMode {
     LatchPreview,
 }
 
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub enum InputMonitoringMode {
+    Normal,
+    NotWhenPlaying,
+    AllModes,
+}
+
 #[derive(Copy, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub enum TransportAction {
     PlayStop,
@@ -594,6 +1040,48 @@ pub enum TransportAction {
     Repeat,
 }
 
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub enum PunchAction {
+    SetPunchIn,
+    SetPunchOut,
+    AutoPunch,
+    RecordFromCursor,
+}
+
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub enum UndoRedoAction {
+    Undo,
+    Redo,
+}
+
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub enum TimeSelectionAction {
+    SetStart,
+    SetEnd,
+    Clear,
+    Repeat,
+}
+
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub enum TimeRangeKind {
+    TimeSelection,
+    LoopPoints,
+}
+
+impl Default for TimeRangeKind {
+    fn default() -> Self {
+        Self::TimeSelection
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub enum MetronomeParameter {
+    Enable,
+    Volume,
+    CountIn,
+    CountInLength,
+}
+
 #[derive(Copy, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub enum AnyOnParameter {
     TrackSolo,
@@ -689,6 +1177,42 @@ impl Default for FeedbackResolution {
     }
 }
 
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub enum RecordInputKind {
+    Audio,
+    Midi,
+}
+
+impl Default for RecordInputKind {
+    fn default() -> Self {
+        Self::Audio
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub enum DualPanComponent {
+    Left,
+    Right,
+}
+
+impl Default for DualPanComponent {
+    fn default() -> Self {
+        Self::Left
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub enum ExtStateValueFormat {
+    Normalized,
+    Percent,
+}
+
+impl Default for ExtStateValueFormat {
+    fn default() -> Self {
+        Self::Normalized
+    }
+}
+
 #[derive(Copy, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[allow(clippy::enum_variant_names)]
 pub enum TrackExclusivity {
@@ -715,6 +1239,18 @@ pub enum GroupMappingExclusivity {
     Exclusive,
 }
 
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub enum GroupEnableMode {
+    Momentary,
+    Toggle,
+}
+
+impl Default for GroupEnableMode {
+    fn default() -> Self {
+        Self::Momentary
+    }
+}
+
 #[derive(Copy, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub enum TouchedParameter {
     Volume,
@@ -733,6 +1269,8 @@ pub enum SoloBehavior {
     InPlace,
     IgnoreRouting,
     ReaperPreference,
+    ExclusiveRestoring,
+    Defeat,
 }
 
 impl Default for SoloBehavior {
@@ -755,6 +1293,25 @@ pub enum BookmarkRef {
     ByIndex { index: u32 },
 }
 
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub enum BookmarkType {
+    Marker,
+    Region,
+}
+
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub enum BookmarkNavigationMode {
+    Relative,
+    Next,
+    Previous,
+}
+
+impl Default for BookmarkNavigationMode {
+    fn default() -> Self {
+        BookmarkNavigationMode::Relative
+    }
+}
+
 #[derive(PartialEq, Default, Serialize, Deserialize, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct FxDescriptorCommons {
@@ -937,7 +1494,17 @@ impl Default for TrackRouteKind {
 #[derive(PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "address")]
 pub enum ClipDescriptor {
-    Slot { index: u32 },
+    Slot {
+        /// Column of the slot (zero-based).
+        index: u32,
+        /// Row of the slot (zero-based). If not set, the first row is used.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        row: Option<u32>,
+        /// ID of a clip matrix shared among all instances. If not set, the instance's own clip
+        /// matrix is used.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        matrix: Option<String>,
+    },
 }
 
 #[derive(PartialEq, Serialize, Deserialize, JsonSchema)]
@@ -955,6 +1522,51 @@ impl Default for ClipOutput {
     }
 }
 
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub enum ClipStopQuantization {
+    Immediately,
+    EndOfBar,
+    EndOfClip,
+}
+
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub enum ChordShape {
+    Major,
+    Minor,
+    Diminished,
+    Augmented,
+    Sus2,
+    Sus4,
+    Major7,
+    Minor7,
+    Dominant7,
+    Power,
+    Octave,
+}
+
+#[derive(PartialEq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Arpeggiator {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub direction: Option<ArpeggiatorDirection>,
+    /// Number of steps per beat (e.g. 4 means sixteenth notes in 4/4).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub steps_per_beat: Option<u32>,
+}
+
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub enum ArpeggiatorDirection {
+    Up,
+    Down,
+    UpDown,
+}
+
+impl Default for ArpeggiatorDirection {
+    fn default() -> Self {
+        Self::Up
+    }
+}
+
 #[derive(PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "kind")]
 pub enum MidiDestination {
@@ -968,6 +1580,19 @@ impl Default for MidiDestination {
     }
 }
 
+#[derive(PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "kind")]
+pub enum MidiDeviceDestination {
+    FeedbackOutput,
+    Device { id: u8 },
+}
+
+impl Default for MidiDeviceDestination {
+    fn default() -> Self {
+        Self::FeedbackOutput
+    }
+}
+
 #[derive(PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "kind")]
 pub enum OscDestination {
@@ -402,6 +402,7 @@ fn convert_capability_to_mappings(
                 feedback_behavior: None,
                 pattern: Some(format!("D0 [{:04b} dcba]", index)),
                 character: Some(SourceCharacter::Range),
+                output_device_id: None,
             });
             let mapping = Mapping {
                 control_enabled: Some(false),
@@ -414,6 +415,7 @@ fn convert_capability_to_mappings(
         Capability::FbMcuTimeDisplay => {
             let source = Source::MackieSevenSegmentDisplay(MackieSevenSegmentDisplaySource {
                 scope: Some(MackieSevenSegmentDisplayScope::Tc),
+                output_device_id: None,
             });
             let mapping = Mapping {
                 control_enabled: Some(false),
@@ -547,6 +549,7 @@ fn convert_max_short_msg_to_source(input: MsgConvInput) -> CsiResult<MsgConvOutp
                         feedback_behavior: None,
                         channel: Some(channel.get()),
                         key_number: Some(key_number.get()),
+                        output_device_id: None,
                     }),
                     reverse_if_button_like: velocity == U7::MIN,
                 }
@@ -565,6 +568,7 @@ fn convert_max_short_msg_to_source(input: MsgConvInput) -> CsiResult<MsgConvOutp
                         feedback_behavior: None,
                         channel: Some(channel.get()),
                         key_number: Some(key_number.get()),
+                        output_device_id: None,
                     }),
                     reverse_if_button_like: true,
                 }
@@ -584,6 +588,7 @@ fn convert_max_short_msg_to_source(input: MsgConvInput) -> CsiResult<MsgConvOutp
                             feedback_behavior: None,
                             channel: Some(channel.get()),
                             key_number: Some(key_number.get()),
+                            output_device_id: None,
                         },
                     ),
                     reverse_if_button_like: pressure_amount == U7::MIN,
@@ -605,6 +610,7 @@ fn convert_max_short_msg_to_source(input: MsgConvInput) -> CsiResult<MsgConvOutp
                         controller_number: Some(controller_number.get()),
                         character: Some(input.character),
                         fourteen_bit: Some(input.fourteen_bit),
+                        output_device_id: None,
                     }),
                     reverse_if_button_like: control_value == U7::MIN,
                 }
@@ -621,6 +627,7 @@ fn convert_max_short_msg_to_source(input: MsgConvInput) -> CsiResult<MsgConvOutp
                     source: Source::MidiProgramChangeNumber(MidiProgramChangeNumberSource {
                         feedback_behavior: None,
                         channel: Some(channel.get()),
+                        output_device_id: None,
                     }),
                     reverse_if_button_like: program_number == U7::MIN,
                 }
@@ -637,6 +644,7 @@ fn convert_max_short_msg_to_source(input: MsgConvInput) -> CsiResult<MsgConvOutp
                     source: Source::MidiChannelPressureAmount(MidiChannelPressureAmountSource {
                         feedback_behavior: None,
                         channel: Some(channel.get()),
+                        output_device_id: None,
                     }),
                     reverse_if_button_like: pressure_amount == U7::MIN,
                 }
@@ -653,6 +661,7 @@ fn convert_max_short_msg_to_source(input: MsgConvInput) -> CsiResult<MsgConvOutp
                     source: Source::MidiPitchBendChangeValue(MidiPitchBendChangeValueSource {
                         feedback_behavior: None,
                         channel: Some(channel.get()),
+                        output_device_id: None,
                     }),
                     reverse_if_button_like: pitch_bend_value == U14::MIN,
                 }
@@ -671,6 +680,7 @@ fn convert_short_msg_to_raw_midi_source(input: MsgConvInput) -> MsgConvOutput {
             feedback_behavior: None,
             pattern: Some(convert_to_raw_midi_pattern(input.msg)),
             character: Some(input.character),
+            output_device_id: None,
         }),
         reverse_if_button_like: false,
     }
@@ -695,6 +705,7 @@ fn create_mackie_lcd_mapping(
     let source = Source::MackieLcd(MackieLcdSource {
         channel: Some(index),
         line: Some(line),
+        output_device_id: None,
     });
     Mapping {
         control_enabled: Some(false),
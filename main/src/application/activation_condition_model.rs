@@ -1,6 +1,6 @@
 use crate::application::{ActivationType, BankConditionModel, ModifierConditionModel};
 use crate::base::Prop;
-use crate::domain::{ActivationCondition, EelCondition};
+use crate::domain::{ActivationCondition, EelCondition, ExpressionCondition, ReaperStateCondition};
 use rxrust::prelude::*;
 
 #[derive(Clone, Debug, Default)]
@@ -10,6 +10,8 @@ pub struct ActivationConditionModel {
     pub modifier_condition_2: Prop<ModifierConditionModel>,
     pub bank_condition: Prop<BankConditionModel>,
     pub eel_condition: Prop<String>,
+    pub expression_condition: Prop<String>,
+    pub reaper_state_condition: Prop<ReaperStateCondition>,
 }
 
 impl ActivationConditionModel {
@@ -22,7 +24,9 @@ impl ActivationConditionModel {
             .merge(self.modifier_condition_1.changed())
             .merge(self.modifier_condition_2.changed())
             .merge(self.eel_condition.changed())
+            .merge(self.expression_condition.changed())
             .merge(self.bank_condition.changed())
+            .merge(self.reaper_state_condition.changed())
     }
 
     pub fn create_activation_condition(&self) -> ActivationCondition {
@@ -44,6 +48,11 @@ impl ActivationConditionModel {
                 Ok(c) => ActivationCondition::Eel(Box::new(c)),
                 Err(_) => ActivationCondition::Always,
             },
+            Expression => match ExpressionCondition::compile(self.expression_condition.get_ref()) {
+                Ok(c) => ActivationCondition::Expression(Box::new(c)),
+                Err(_) => ActivationCondition::Always,
+            },
+            ReaperState => ActivationCondition::ReaperState(self.reaper_state_condition.get()),
         }
     }
 
@@ -30,6 +30,12 @@ pub enum ActivationType {
     #[serde(rename = "eel")]
     #[display(fmt = "When EEL result > 0")]
     Eel,
+    #[serde(rename = "reaperState")]
+    #[display(fmt = "When REAPER state")]
+    ReaperState,
+    #[serde(rename = "expression")]
+    #[display(fmt = "When expression > 0")]
+    Expression,
 }
 
 impl Default for ActivationType {
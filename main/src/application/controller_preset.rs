@@ -1,13 +1,23 @@
-use crate::application::{CompartmentModel, Preset};
-use std::collections::HashMap;
+use crate::application::{CompartmentModel, Preset, RawMidiMessage};
+use crate::domain::MidiMacros;
+use helgoboss_learn::RawMidiEvent;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
 
+/// Custom data key under which the ReaLearn Companion app saves the controller layout.
+const COMPANION_CUSTOM_DATA_KEY: &str = "companion";
+/// Properties of a custom data value which holds binary data.
+const BINARY_CONTENT_TYPE_KEY: &str = "contentType";
+const BINARY_BASE64_KEY: &str = "base64";
+
 #[derive(Clone, Debug)]
 pub struct ControllerPreset {
     id: String,
     name: String,
     data: CompartmentModel,
     custom_data: HashMap<String, serde_json::Value>,
+    midi_macros: HashMap<String, Vec<RawMidiMessage>>,
+    state_requests: Vec<RawMidiMessage>,
 }
 
 impl ControllerPreset {
@@ -16,12 +26,16 @@ impl ControllerPreset {
         name: String,
         data: CompartmentModel,
         custom_data: HashMap<String, serde_json::Value>,
+        midi_macros: HashMap<String, Vec<RawMidiMessage>>,
+        state_requests: Vec<RawMidiMessage>,
     ) -> ControllerPreset {
         ControllerPreset {
             id,
             name,
             data,
             custom_data,
+            midi_macros,
+            state_requests,
         }
     }
 
@@ -33,6 +47,110 @@ impl ControllerPreset {
         self.custom_data.insert(key, value);
     }
 
+    /// Saves binary data (e.g. a device layout blob or a display bitmap) as base64-encoded custom
+    /// data.
+    ///
+    /// Fails if the key is reserved for the controller layout of the Companion app, which must stay
+    /// JSON.
+    pub fn update_binary_custom_data(
+        &mut self,
+        key: String,
+        data: BinaryCustomData,
+    ) -> Result<(), &'static str> {
+        if key == COMPANION_CUSTOM_DATA_KEY {
+            return Err("custom data key is reserved for the Companion app");
+        }
+        let value = serde_json::json!({
+            BINARY_CONTENT_TYPE_KEY: data.content_type,
+            BINARY_BASE64_KEY: base64::encode(&data.bytes),
+        });
+        self.update_custom_data(key, value);
+        Ok(())
+    }
+
+    /// Returns `Ok(None)` if there's no custom data with the given key.
+    pub fn binary_custom_data(&self, key: &str) -> Result<Option<BinaryCustomData>, &'static str> {
+        let value = match self.custom_data.get(key) {
+            None => return Ok(None),
+            Some(v) => v,
+        };
+        let encoded = value
+            .get(BINARY_BASE64_KEY)
+            .and_then(|v| v.as_str())
+            .ok_or("custom data is not binary")?;
+        let data = BinaryCustomData {
+            content_type: value
+                .get(BINARY_CONTENT_TYPE_KEY)
+                .and_then(|v| v.as_str())
+                .unwrap_or(DEFAULT_BINARY_CONTENT_TYPE)
+                .to_string(),
+            bytes: base64::decode(encoded).map_err(|_| "binary custom data corrupt")?,
+        };
+        Ok(Some(data))
+    }
+
+    /// Returns all references from the controller layout in the custom data to mappings which
+    /// don't exist in this preset anymore.
+    pub fn orphaned_custom_data_refs(&self) -> Vec<OrphanedCustomDataRef> {
+        let mapping_ids = self.mapping_ids();
+        self.custom_data
+            .get(COMPANION_CUSTOM_DATA_KEY)
+            .and_then(companion_controls)
+            .map(|controls| find_orphaned_refs(controls, &mapping_ids))
+            .unwrap_or_default()
+    }
+
+    /// Removes all orphaned references from the controller layout in the custom data.
+    ///
+    /// Controls which only referred to mappings that don't exist anymore are removed as well.
+    /// Returns the removed references.
+    pub fn remove_orphaned_custom_data_refs(&mut self) -> Vec<OrphanedCustomDataRef> {
+        let mapping_ids = self.mapping_ids();
+        self.custom_data
+            .get_mut(COMPANION_CUSTOM_DATA_KEY)
+            .and_then(companion_controls_mut)
+            .map(|controls| remove_orphaned_refs(controls, &mapping_ids))
+            .unwrap_or_default()
+    }
+
+    fn mapping_ids(&self) -> HashSet<&str> {
+        self.data
+            .mappings
+            .iter()
+            .map(|m| m.key().as_ref())
+            .collect()
+    }
+
+    pub fn midi_macros(&self) -> &HashMap<String, Vec<RawMidiMessage>> {
+        &self.midi_macros
+    }
+
+    /// Macros containing invalid messages are left out.
+    pub fn create_midi_macros(&self) -> MidiMacros {
+        let macros = self
+            .midi_macros
+            .iter()
+            .filter_map(|(name, messages)| {
+                let events: Result<Vec<_>, _> =
+                    messages.iter().map(|m| m.create_raw_midi_event()).collect();
+                Some((name.clone(), events.ok()?))
+            })
+            .collect();
+        MidiMacros::new(macros)
+    }
+
+    pub fn state_requests(&self) -> &[RawMidiMessage] {
+        &self.state_requests
+    }
+
+    /// Invalid messages are left out.
+    pub fn create_state_requests(&self) -> Vec<RawMidiEvent> {
+        self.state_requests
+            .iter()
+            .filter_map(|m| m.create_raw_midi_event().ok())
+            .collect()
+    }
+
     pub fn update_realearn_data(&mut self, data: CompartmentModel) {
         self.data = data;
     }
@@ -57,3 +175,148 @@ impl fmt::Display for ControllerPreset {
         f.write_str(self.name())
     }
 }
+
+pub const DEFAULT_BINARY_CONTENT_TYPE: &str = "application/octet-stream";
+
+/// Custom data which is not JSON.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BinaryCustomData {
+    /// MIME type.
+    pub content_type: String,
+    pub bytes: Vec<u8>,
+}
+
+/// A control element in the controller layout which refers to a mapping that doesn't exist.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OrphanedCustomDataRef {
+    pub control_id: String,
+    pub mapping_id: String,
+}
+
+impl fmt::Display for OrphanedCustomDataRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Control {} refers to non-existing mapping {}",
+            self.control_id, self.mapping_id
+        )
+    }
+}
+
+fn companion_controls(companion_data: &serde_json::Value) -> Option<&Vec<serde_json::Value>> {
+    companion_data.get("controls")?.as_array()
+}
+
+fn companion_controls_mut(
+    companion_data: &mut serde_json::Value,
+) -> Option<&mut Vec<serde_json::Value>> {
+    companion_data.get_mut("controls")?.as_array_mut()
+}
+
+fn control_id(control: &serde_json::Value) -> String {
+    control
+        .get("id")
+        .and_then(|id| id.as_str())
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn find_orphaned_refs(
+    controls: &[serde_json::Value],
+    mapping_ids: &HashSet<&str>,
+) -> Vec<OrphanedCustomDataRef> {
+    controls
+        .iter()
+        .flat_map(|control| {
+            let mapping_refs = control
+                .get("mappings")
+                .and_then(|m| m.as_array())
+                .map(|m| m.as_slice())
+                .unwrap_or_default();
+            mapping_refs.iter().filter_map(move |r| {
+                let mapping_id = r.as_str()?;
+                if mapping_ids.contains(mapping_id) {
+                    return None;
+                }
+                Some(OrphanedCustomDataRef {
+                    control_id: control_id(control),
+                    mapping_id: mapping_id.to_string(),
+                })
+            })
+        })
+        .collect()
+}
+
+fn remove_orphaned_refs(
+    controls: &mut Vec<serde_json::Value>,
+    mapping_ids: &HashSet<&str>,
+) -> Vec<OrphanedCustomDataRef> {
+    let removed = find_orphaned_refs(controls, mapping_ids);
+    if removed.is_empty() {
+        return removed;
+    }
+    let mut i = 0;
+    while i < controls.len() {
+        let is_orphaned = match controls[i]
+            .get_mut("mappings")
+            .and_then(|m| m.as_array_mut())
+        {
+            Some(mapping_refs) if !mapping_refs.is_empty() => {
+                mapping_refs
+                    .retain(|r| r.as_str().map(|id| mapping_ids.contains(id)) != Some(false));
+                // All mappings of this control are gone
+                mapping_refs.is_empty()
+            }
+            _ => false,
+        };
+        if is_orphaned {
+            controls.remove(i);
+        } else {
+            i += 1;
+        }
+    }
+    removed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn remove_orphaned_refs_from_controls() {
+        // Given
+        let mut controls = vec![
+            json!({"id": "c1", "mappings": ["m1"]}),
+            json!({"id": "c2", "mappings": ["m2", "m3"]}),
+            json!({"id": "c3", "mappings": ["m4"]}),
+            json!({"id": "c4", "mappings": []}),
+        ];
+        let mapping_ids: HashSet<_> = vec!["m1", "m2"].into_iter().collect();
+        // When
+        let found = find_orphaned_refs(&controls, &mapping_ids);
+        let removed = remove_orphaned_refs(&mut controls, &mapping_ids);
+        // Then
+        let expected = vec![
+            OrphanedCustomDataRef {
+                control_id: "c2".to_string(),
+                mapping_id: "m3".to_string(),
+            },
+            OrphanedCustomDataRef {
+                control_id: "c3".to_string(),
+                mapping_id: "m4".to_string(),
+            },
+        ];
+        assert_eq!(found, expected);
+        assert_eq!(removed, expected);
+        assert_eq!(
+            controls,
+            vec![
+                json!({"id": "c1", "mappings": ["m1"]}),
+                json!({"id": "c2", "mappings": ["m2"]}),
+                json!({"id": "c4", "mappings": []}),
+            ]
+        );
+        assert!(find_orphaned_refs(&controls, &mapping_ids).is_empty());
+    }
+}
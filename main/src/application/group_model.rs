@@ -2,6 +2,7 @@ use crate::application::{ActivationConditionModel, GroupData};
 use crate::base::{prop, Prop};
 use crate::domain::{GroupId, GroupKey, MappingCompartment, Tag};
 use core::fmt;
+use reaper_medium::MidiOutputDeviceId;
 use rxrust::prelude::*;
 use std::cell::RefCell;
 use std::rc::{Rc, Weak};
@@ -16,6 +17,8 @@ pub struct GroupModel {
     pub tags: Prop<Vec<Tag>>,
     pub control_is_enabled: Prop<bool>,
     pub feedback_is_enabled: Prop<bool>,
+    /// `None` means the MIDI feedback output of the instance.
+    pub midi_output_device: Prop<Option<MidiOutputDeviceId>>,
     pub activation_condition_model: ActivationConditionModel,
 }
 
@@ -67,6 +70,7 @@ impl GroupModel {
             tags: Default::default(),
             control_is_enabled: prop(true),
             feedback_is_enabled: prop(true),
+            midi_output_device: prop(None),
             activation_condition_model: ActivationConditionModel::default(),
         }
     }
@@ -109,6 +113,7 @@ impl GroupModel {
                 .activation_condition_model
                 .create_activation_condition(),
             tags: self.tags.get_ref().clone(),
+            midi_output_device: self.midi_output_device.get(),
         }
     }
 
@@ -128,6 +133,7 @@ impl GroupModel {
             .changed()
             .merge(self.feedback_is_enabled.changed())
             .merge(self.tags.changed())
+            .merge(self.midi_output_device.changed())
             .merge(
                 self.activation_condition_model
                     .changed_processing_relevant(),
@@ -0,0 +1,267 @@
+use crate::application::LifecycleMidiMessageModel;
+use crate::domain::{
+    InstanceHookAction, InstanceHooks, LifecycleMidiMessage, MappingCompartment, MappingKey,
+    MidiMacros, QualifiedMappingId, PLUGIN_PARAMETER_COUNT,
+};
+use helgoboss_learn::UnitValue;
+use rosc::{OscMessage, OscType};
+use serde::{Deserialize, Serialize};
+
+/// User-configurable instance lifecycle hooks, edited as YAML.
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct InstanceHooksModel {
+    pub on_session_load: Vec<InstanceHookActionModel>,
+    pub on_preset_change: Vec<InstanceHookActionModel>,
+    pub on_feedback_output_change: Vec<InstanceHookActionModel>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InstanceHookActionModel {
+    SendMidi(LifecycleMidiMessageModel),
+    SendOsc(OscMessageModel),
+    SetParameter(SetParameterModel),
+    TriggerMapping(TriggerMappingModel),
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OscMessageModel {
+    pub address: String,
+    #[serde(default)]
+    pub args: Vec<OscArgModel>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum OscArgModel {
+    Bool(bool),
+    Int(i32),
+    Float(f32),
+    String(String),
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SetParameterModel {
+    /// Zero-based index of the ReaLearn parameter.
+    pub index: u32,
+    /// Parameter value (0.0 - 1.0).
+    pub value: f64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TriggerMappingModel {
+    #[serde(default)]
+    pub compartment: HookCompartment,
+    /// Key of the mapping to be triggered.
+    pub key: MappingKey,
+    /// Control value (0.0 - 1.0) sent to the mapping.
+    #[serde(default = "default_trigger_value")]
+    pub value: f64,
+}
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HookCompartment {
+    Controller,
+    Main,
+}
+
+impl Default for HookCompartment {
+    fn default() -> Self {
+        Self::Main
+    }
+}
+
+impl From<HookCompartment> for MappingCompartment {
+    fn from(c: HookCompartment) -> Self {
+        match c {
+            HookCompartment::Controller => MappingCompartment::ControllerMappings,
+            HookCompartment::Main => MappingCompartment::MainMappings,
+        }
+    }
+}
+
+fn default_trigger_value() -> f64 {
+    1.0
+}
+
+impl InstanceHooksModel {
+    /// Like in mapping lifecycle hooks, MIDI macros which are not defined in the active
+    /// controller preset expand to nothing. The same is true for mappings which don't exist.
+    pub fn create_instance_hooks(
+        &self,
+        midi_macros: &MidiMacros,
+        find_mapping: impl Fn(MappingCompartment, &MappingKey) -> Option<QualifiedMappingId>,
+    ) -> Result<InstanceHooks, &'static str> {
+        let convert_actions = |models: &[InstanceHookActionModel]| {
+            let mut actions = vec![];
+            for m in models {
+                if let Some(a) = m.create_instance_hook_action(midi_macros, &find_mapping)? {
+                    actions.push(a);
+                }
+            }
+            Ok::<_, &'static str>(actions)
+        };
+        let hooks = InstanceHooks {
+            on_session_load: convert_actions(&self.on_session_load)?,
+            on_preset_change: convert_actions(&self.on_preset_change)?,
+            on_feedback_output_change: convert_actions(&self.on_feedback_output_change)?,
+        };
+        Ok(hooks)
+    }
+}
+
+impl InstanceHookActionModel {
+    fn create_instance_hook_action(
+        &self,
+        midi_macros: &MidiMacros,
+        find_mapping: impl Fn(MappingCompartment, &MappingKey) -> Option<QualifiedMappingId>,
+    ) -> Result<Option<InstanceHookAction>, &'static str> {
+        use InstanceHookActionModel::*;
+        let action = match self {
+            SendMidi(msg) => {
+                let events: Vec<_> = msg
+                    .create_lifecycle_midi_messages(midi_macros)?
+                    .into_iter()
+                    .filter_map(|m| match m {
+                        LifecycleMidiMessage::Raw(e) => Some(*e),
+                        LifecycleMidiMessage::Short(_) => None,
+                    })
+                    .collect();
+                if events.is_empty() {
+                    return Ok(None);
+                }
+                InstanceHookAction::SendMidi(events)
+            }
+            SendOsc(msg) => InstanceHookAction::SendOsc(OscMessage {
+                addr: msg.address.clone(),
+                args: msg.args.iter().map(|a| a.to_osc_type()).collect(),
+            }),
+            SetParameter(p) => {
+                if p.index >= PLUGIN_PARAMETER_COUNT {
+                    return Err("parameter index out of range");
+                }
+                if !UnitValue::is_valid(p.value) {
+                    return Err("parameter value must be between 0.0 and 1.0");
+                }
+                InstanceHookAction::SetParameter {
+                    index: p.index,
+                    value: p.value as f32,
+                }
+            }
+            TriggerMapping(t) => {
+                if !UnitValue::is_valid(t.value) {
+                    return Err("trigger value must be between 0.0 and 1.0");
+                }
+                let id = match find_mapping(t.compartment.into(), &t.key) {
+                    None => return Ok(None),
+                    Some(id) => id,
+                };
+                InstanceHookAction::TriggerMapping {
+                    id,
+                    value: UnitValue::new(t.value),
+                }
+            }
+        };
+        Ok(Some(action))
+    }
+}
+
+impl OscArgModel {
+    fn to_osc_type(&self) -> OscType {
+        use OscArgModel::*;
+        match self {
+            Bool(v) => OscType::Bool(*v),
+            Int(v) => OscType::Int(*v),
+            Float(v) => OscType::Float(*v),
+            String(v) => OscType::String(v.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::MappingId;
+
+    #[test]
+    fn parse_and_resolve() {
+        // Given
+        let yaml = r#"
+on_session_load:
+  - send_midi:
+      raw: F0 00 20 6B 7F 42 02 00 10 77 01 F7
+  - send_osc:
+      address: /init
+      args: [1, 0.5, "hello"]
+on_preset_change:
+  - set_parameter:
+      index: 3
+      value: 0.25
+  - trigger_mapping:
+      key: existing
+  - trigger_mapping:
+      compartment: controller
+      key: missing
+"#;
+        let model: InstanceHooksModel = serde_yaml::from_str(yaml).unwrap();
+        let existing_id =
+            QualifiedMappingId::new(MappingCompartment::MainMappings, MappingId::random());
+        // When
+        let hooks = model
+            .create_instance_hooks(&MidiMacros::default(), |compartment, key| {
+                if compartment == MappingCompartment::MainMappings && key.as_ref() == "existing" {
+                    Some(existing_id)
+                } else {
+                    None
+                }
+            })
+            .unwrap();
+        // Then
+        assert_eq!(hooks.on_session_load.len(), 2);
+        assert!(
+            matches!(&hooks.on_session_load[0], InstanceHookAction::SendMidi(events) if events.len() == 1)
+        );
+        match &hooks.on_session_load[1] {
+            InstanceHookAction::SendOsc(msg) => {
+                assert_eq!(msg.addr, "/init");
+                assert_eq!(
+                    msg.args,
+                    vec![
+                        OscType::Int(1),
+                        OscType::Float(0.5),
+                        OscType::String("hello".to_owned())
+                    ]
+                );
+            }
+            _ => panic!("expected OSC action"),
+        }
+        assert_eq!(hooks.on_preset_change.len(), 2);
+        assert!(matches!(
+            hooks.on_preset_change[0],
+            InstanceHookAction::SetParameter { index: 3, value } if value == 0.25
+        ));
+        assert!(matches!(
+            hooks.on_preset_change[1],
+            InstanceHookAction::TriggerMapping { id, value } if id == existing_id && value == UnitValue::MAX
+        ));
+        assert!(hooks.on_feedback_output_change.is_empty());
+    }
+
+    #[test]
+    fn reject_invalid_parameter_value() {
+        // Given
+        let yaml = r#"
+on_session_load:
+  - set_parameter:
+      index: 0
+      value: 2.0
+"#;
+        let model: InstanceHooksModel = serde_yaml::from_str(yaml).unwrap();
+        // When
+        let result = model.create_instance_hooks(&MidiMacros::default(), |_, _| None);
+        // Then
+        assert!(result.is_err());
+    }
+}
@@ -1,18 +1,348 @@
-use crate::domain::{LifecycleMidiData, LifecycleMidiMessage, MappingExtension};
+use crate::domain::{
+    BeatBlinkSettings, BlinkDivision, EncoderAccelerationProfile, EncoderAccelerationSettings,
+    Hysteresis, LayerId, LifecycleMidiData, LifecycleMidiMessage, MappingExtension, MidiMacros,
+    MusicalScale, NoiseFilterSettings, OscArgFilter, RampCurve, RampSettings, ResponseCurve,
+    ScaleQuantizer, ScalingTakeoverSettings, ValueSequenceStepping, VelocityThreshold,
+    DEFAULT_ENCODER_ACCELERATION_CURVE,
+};
 
 use crate::application::parse_hex_string;
 use crate::domain::ui_util::DisplayRawMidi;
-use helgoboss_learn::RawMidiEvent;
+use helgoboss_learn::{RawMidiEvent, UnitValue};
 use serde::{Deserialize, Serialize};
 use serde_with::SerializeDisplay;
 use std::convert::TryFrom;
 use std::fmt::{Display, Formatter};
+use std::time::Duration;
 
 #[derive(Clone, Debug, Serialize, Deserialize, Default)]
 #[serde(default)]
 pub struct MappingExtensionModel {
     pub on_activate: LifecycleModel,
     pub on_deactivate: LifecycleModel,
+    #[serde(skip_serializing_if = "FeedbackExtensionModel::is_empty")]
+    pub feedback: FeedbackExtensionModel,
+    /// Overrides the maximum number of control values processed per second.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_control_rate: Option<u32>,
+    /// Overrides the layer in which the mapping can be controlled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub layer: Option<LayerId>,
+    #[serde(skip_serializing_if = "SourceExtensionModel::is_empty")]
+    pub source: SourceExtensionModel,
+    #[serde(skip_serializing_if = "GlueExtensionModel::is_empty")]
+    pub glue: GlueExtensionModel,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct FeedbackExtensionModel {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blink: Option<BlinkModel>,
+    /// Minimum difference (0.0 - 1.0) to the previously sent numeric feedback value.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deadband: Option<f64>,
+}
+
+impl FeedbackExtensionModel {
+    fn is_empty(&self) -> bool {
+        self.blink.is_none() && self.deadband.is_none()
+    }
+
+    /// Returns `None` if not set and `Some(None)` if the deadband is explicitly switched off.
+    pub fn create_feedback_deadband(&self) -> Option<Option<UnitValue>> {
+        let deadband = UnitValue::new_clamped(self.deadband?);
+        Some(Some(deadband).filter(|d| !d.is_zero()))
+    }
+}
+
+/// Source settings which don't have a control in the mapping panel.
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct SourceExtensionModel {
+    /// Overrides the argument filters of an OSC source. An empty list removes all filters.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub argument_filters: Option<Vec<OscArgFilter>>,
+}
+
+impl SourceExtensionModel {
+    fn is_empty(&self) -> bool {
+        self.argument_filters.is_none()
+    }
+}
+
+/// Glue settings which can be entered in the advanced settings.
+///
+/// Each of them overrides the corresponding glue setting of the mapping.
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct GlueExtensionModel {
+    /// Minimum time in milliseconds between two target hits.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_target_hit_interval: Option<u64>,
+    /// Incoming absolute values closer than this (0.0 - 1.0) to the last processed one are ignored.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dead_zone: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hysteresis: Option<HysteresisModel>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ramp: Option<RampModel>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encoder_acceleration: Option<EncoderAccelerationModel>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_value_sequence_stepping: Option<ValueSequenceStepping>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_curve: Option<ResponseCurve>,
+    /// Distance (0.0 - 1.0) within which the target value follows the control value directly when
+    /// taking over in "Catch up" mode.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub takeover_catch_range: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub velocity_threshold: Option<VelocityThresholdModel>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scale_quantization: Option<ScaleQuantizationModel>,
+}
+
+impl GlueExtensionModel {
+    fn is_empty(&self) -> bool {
+        self.min_target_hit_interval.is_none()
+            && self.dead_zone.is_none()
+            && self.hysteresis.is_none()
+            && self.ramp.is_none()
+            && self.encoder_acceleration.is_none()
+            && self.target_value_sequence_stepping.is_none()
+            && self.response_curve.is_none()
+            && self.takeover_catch_range.is_none()
+            && self.velocity_threshold.is_none()
+            && self.scale_quantization.is_none()
+    }
+
+    /// Returns `None` if not set and `Some(None)` if no scale is given.
+    pub fn create_scale_quantizer(&self) -> Option<Option<ScaleQuantizer>> {
+        self.scale_quantization
+            .as_ref()
+            .map(|q| q.create_scale_quantizer())
+    }
+
+    /// Returns `None` if not set and `Some(None)` if the threshold is 0.0.
+    pub fn create_velocity_threshold(&self) -> Option<Option<VelocityThreshold>> {
+        self.velocity_threshold
+            .as_ref()
+            .map(|t| t.create_velocity_threshold())
+    }
+
+    /// Returns `None` if not set and `Some(None)` if ramping is explicitly switched off.
+    pub fn create_ramp_settings(&self) -> Option<Option<RampSettings>> {
+        self.ramp.as_ref().map(|r| r.create_ramp_settings())
+    }
+
+    /// Returns `None` if not set and `Some(None)` if the profile is `off`.
+    pub fn create_encoder_acceleration_settings(
+        &self,
+    ) -> Option<Option<EncoderAccelerationSettings>> {
+        self.encoder_acceleration
+            .as_ref()
+            .map(|a| a.create_encoder_acceleration_settings())
+    }
+
+    /// Returns `None` if not set and `Some(None)` if stepping is `off`.
+    pub fn create_target_value_sequence_stepping(&self) -> Option<Option<ValueSequenceStepping>> {
+        let stepping = self.target_value_sequence_stepping?;
+        Some(Some(stepping).filter(|s| *s != ValueSequenceStepping::Off))
+    }
+
+    /// Returns `None` if not set and `Some(None)` if the curve has no points.
+    pub fn create_response_curve(&self) -> Option<Option<ResponseCurve>> {
+        let curve = self.response_curve.as_ref()?;
+        if curve.is_empty() {
+            return Some(None);
+        }
+        Some(Some(curve.normalized()))
+    }
+
+    /// Returns `None` if not set and `Some(None)` if scaling is explicitly switched off.
+    ///
+    /// Doesn't look at the takeover mode, that's up to the caller.
+    pub fn create_scaling_takeover_settings(&self) -> Option<Option<ScalingTakeoverSettings>> {
+        let catch_range = UnitValue::new_clamped(self.takeover_catch_range?);
+        if catch_range.is_zero() {
+            return Some(None);
+        }
+        Some(Some(ScalingTakeoverSettings { catch_range }))
+    }
+
+    /// Dead zone and hysteresis override the ones in the given glue settings independently of
+    /// each other.
+    pub fn create_noise_filter_settings(
+        &self,
+        glue_settings: Option<NoiseFilterSettings>,
+    ) -> Option<NoiseFilterSettings> {
+        let dead_zone = match self.dead_zone {
+            None => glue_settings.map(|s| s.dead_zone).unwrap_or(UnitValue::MIN),
+            Some(d) => UnitValue::new_clamped(d),
+        };
+        let hysteresis = match &self.hysteresis {
+            None => glue_settings.and_then(|s| s.hysteresis),
+            Some(h) => Some(h.create_hysteresis()),
+        };
+        if dead_zone.is_zero() && hysteresis.is_none() {
+            return None;
+        }
+        let settings = NoiseFilterSettings {
+            dead_zone,
+            hysteresis,
+        };
+        Some(settings)
+    }
+
+    /// Returns `None` if not set and `Some(None)` if throttling is explicitly switched off.
+    pub fn create_min_target_hit_interval(&self) -> Option<Option<Duration>> {
+        let millis = self.min_target_hit_interval?;
+        Some(Some(Duration::from_millis(millis)).filter(|i| *i > Duration::default()))
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RampModel {
+    /// Time in milliseconds it takes to sweep from the minimum to the maximum. 0 switches it off.
+    pub duration: u64,
+    #[serde(default)]
+    pub curve: RampCurve,
+    #[serde(default)]
+    pub return_on_release: bool,
+}
+
+impl RampModel {
+    pub fn create_ramp_settings(&self) -> Option<RampSettings> {
+        if self.duration == 0 {
+            return None;
+        }
+        let settings = RampSettings {
+            duration: Duration::from_millis(self.duration),
+            curve: self.curve,
+            return_on_release: self.return_on_release,
+        };
+        Some(settings)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct VelocityThresholdModel {
+    /// Velocity (0.0 - 1.0) from which on a press counts as hard. 0.0 switches it off.
+    pub threshold: f64,
+    /// Control value (0.0 - 1.0) for soft presses. If not set, soft presses are ignored.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub below_value: Option<f64>,
+    /// Control value (0.0 - 1.0) for hard presses. If not set, hard presses are ignored.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub above_value: Option<f64>,
+}
+
+impl VelocityThresholdModel {
+    pub fn create_velocity_threshold(&self) -> Option<VelocityThreshold> {
+        let threshold = UnitValue::new_clamped(self.threshold);
+        if threshold.is_zero() {
+            return None;
+        }
+        let settings = VelocityThreshold {
+            threshold,
+            below_value: self.below_value.map(UnitValue::new_clamped),
+            above_value: self.above_value.map(UnitValue::new_clamped),
+        };
+        Some(settings)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScaleQuantizationModel {
+    /// If not set, notes are not quantized.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scale: Option<MusicalScale>,
+    /// Pitch class of the root note (0 = C, ..., 11 = B).
+    #[serde(default)]
+    pub root: u8,
+}
+
+impl ScaleQuantizationModel {
+    pub fn create_scale_quantizer(&self) -> Option<ScaleQuantizer> {
+        let quantizer = ScaleQuantizer {
+            scale: self.scale?,
+            root: self.root,
+        };
+        Some(quantizer)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EncoderAccelerationModel {
+    pub profile: EncoderAccelerationProfile,
+    /// Exponent of the exponential profile.
+    #[serde(default = "default_encoder_acceleration_curve")]
+    pub curve: f64,
+}
+
+fn default_encoder_acceleration_curve() -> f64 {
+    DEFAULT_ENCODER_ACCELERATION_CURVE
+}
+
+impl EncoderAccelerationModel {
+    pub fn create_encoder_acceleration_settings(&self) -> Option<EncoderAccelerationSettings> {
+        if self.profile == EncoderAccelerationProfile::Off {
+            return None;
+        }
+        let settings = EncoderAccelerationSettings {
+            profile: self.profile,
+            curve: self.curve,
+        };
+        Some(settings)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HysteresisModel {
+    /// Value (0.0 - 1.0) at which the switch turns off.
+    pub lower_threshold: f64,
+    /// Value (0.0 - 1.0) at which the switch turns on.
+    pub upper_threshold: f64,
+}
+
+impl HysteresisModel {
+    pub fn create_hysteresis(&self) -> Hysteresis {
+        let lower = UnitValue::new_clamped(self.lower_threshold);
+        let upper = UnitValue::new_clamped(self.upper_threshold);
+        if lower <= upper {
+            Hysteresis {
+                lower_threshold: lower,
+                upper_threshold: upper,
+            }
+        } else {
+            Hysteresis {
+                lower_threshold: upper,
+                upper_threshold: lower,
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BlinkModel {
+    /// Target value (0.0 - 1.0) for which the feedback should blink.
+    pub target_value: f64,
+    #[serde(default)]
+    pub division: BlinkDivision,
+}
+
+impl BlinkModel {
+    pub fn create_beat_blink_settings(&self) -> Result<BeatBlinkSettings, &'static str> {
+        if !UnitValue::is_valid(self.target_value) {
+            return Err("blink target value must be between 0.0 and 1.0");
+        }
+        let settings = BeatBlinkSettings {
+            target_value: UnitValue::new(self.target_value),
+            division: self.division,
+        };
+        Ok(settings)
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, Default)]
@@ -25,9 +355,11 @@ pub struct LifecycleModel {
 #[serde(rename_all = "snake_case")]
 pub enum LifecycleMidiMessageModel {
     Raw(RawMidiMessage),
+    /// Name of a MIDI macro defined in the active controller preset.
+    Macro(String),
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum RawMidiMessage {
     HexString(RawHexStringMidiMessage),
@@ -42,9 +374,13 @@ impl RawMidiMessage {
             ByteArray(msg) => &msg.0,
         }
     }
+
+    pub fn create_raw_midi_event(&self) -> Result<RawMidiEvent, &'static str> {
+        RawMidiEvent::try_from_slice(0, self.bytes())
+    }
 }
 
-#[derive(Clone, Debug, SerializeDisplay, Deserialize)]
+#[derive(Clone, Debug, PartialEq, SerializeDisplay, Deserialize)]
 #[serde(try_from = "String")]
 pub struct RawHexStringMidiMessage(pub Vec<u8>);
 
@@ -54,7 +390,7 @@ impl Display for RawHexStringMidiMessage {
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct RawByteArrayMidiMessage(pub Vec<u8>);
 
 impl TryFrom<String> for RawHexStringMidiMessage {
@@ -67,32 +403,54 @@ impl TryFrom<String> for RawHexStringMidiMessage {
 }
 
 impl LifecycleMidiMessageModel {
-    pub fn create_lifecycle_midi_message(&self) -> Result<LifecycleMidiMessage, &'static str> {
+    /// Macros which are not defined in the active controller preset expand to nothing. That way
+    /// the remaining lifecycle messages keep working if no or another controller preset is active.
+    pub fn create_lifecycle_midi_messages(
+        &self,
+        midi_macros: &MidiMacros,
+    ) -> Result<Vec<LifecycleMidiMessage>, &'static str> {
         use LifecycleMidiMessageModel::*;
-        let message = match self {
+        let messages = match self {
             Raw(msg) => {
-                let event = RawMidiEvent::try_from_slice(0, msg.bytes())?;
-                LifecycleMidiMessage::Raw(Box::new(event))
+                let event = msg.create_raw_midi_event()?;
+                vec![LifecycleMidiMessage::Raw(Box::new(event))]
             }
+            Macro(name) => midi_macros
+                .get(name)
+                .unwrap_or_default()
+                .iter()
+                .map(|e| LifecycleMidiMessage::Raw(Box::new(*e)))
+                .collect(),
         };
-        Ok(message)
+        Ok(messages)
     }
 }
 
 impl MappingExtensionModel {
-    pub fn create_mapping_extension(&self) -> Result<MappingExtension, &'static str> {
-        fn convert_messages(
-            model: &[LifecycleMidiMessageModel],
-        ) -> Result<Vec<LifecycleMidiMessage>, &'static str> {
-            model
-                .iter()
-                .map(|m| m.create_lifecycle_midi_message())
-                .collect()
-        }
-        let ext = MappingExtension::new(LifecycleMidiData {
-            activation_midi_messages: convert_messages(&self.on_activate.send_midi_feedback)?,
-            deactivation_midi_messages: convert_messages(&self.on_deactivate.send_midi_feedback)?,
-        });
+    pub fn create_mapping_extension(
+        &self,
+        midi_macros: &MidiMacros,
+    ) -> Result<MappingExtension, &'static str> {
+        let convert_messages = |model: &[LifecycleMidiMessageModel]| {
+            let mut messages = vec![];
+            for m in model {
+                messages.extend(m.create_lifecycle_midi_messages(midi_macros)?);
+            }
+            Ok::<_, &'static str>(messages)
+        };
+        let ext = MappingExtension::new(
+            LifecycleMidiData {
+                activation_midi_messages: convert_messages(&self.on_activate.send_midi_feedback)?,
+                deactivation_midi_messages: convert_messages(
+                    &self.on_deactivate.send_midi_feedback,
+                )?,
+            },
+            self.feedback
+                .blink
+                .as_ref()
+                .map(|b| b.create_beat_blink_settings())
+                .transpose()?,
+        );
         Ok(ext)
     }
 }
@@ -1,24 +1,27 @@
 use crate::application::{
     convert_factor_to_unit_value, ActivationConditionModel, MappingExtensionModel, ModeModel,
-    SourceModel, TargetCategory, TargetModel, TargetModelFormatVeryShort, TargetModelWithContext,
+    SourceCategory, SourceModel, TargetCategory, TargetModel, TargetModelFormatVeryShort,
+    TargetModelWithContext,
 };
-use crate::base::{prop, Prop};
+use crate::base::{prop, Prop, RealearnError};
 use crate::domain::{
     ActivationCondition, CompoundMappingSource, CompoundMappingTarget, ExtendedProcessorContext,
-    ExtendedSourceCharacter, FeedbackSendBehavior, GroupId, MainMapping, MappingCompartment,
-    MappingId, MappingKey, Mode, PersistentMappingProcessingState, ProcessorMappingOptions,
-    QualifiedMappingId, RealearnTarget, ReaperTarget, Tag, TargetCharacter,
-    UnresolvedCompoundMappingTarget, VirtualFx, VirtualTrack,
+    ExtendedSourceCharacter, FeedbackSendBehavior, GroupId, LayerId, MainMapping,
+    MappingCompartment, MappingId, MappingKey, MidiMacros, Mode, PersistentMappingProcessingState,
+    ProcessorMappingOptions, QualifiedMappingId, RealearnTarget, ReaperTarget, Tag,
+    TargetCharacter, UnresolvedCompoundMappingTarget, VirtualFx, VirtualTrack,
 };
 use helgoboss_learn::{
     AbsoluteMode, ControlType, DetailedSourceCharacter, Interval, ModeApplicabilityCheckInput,
-    ModeParameter, SoftSymmetricUnitValue, SourceCharacter, Target, UnitValue,
+    ModeParameter, SoftSymmetricUnitValue, SourceCharacter, TakeoverMode, Target, UnitValue,
 };
+use reaper_medium::MidiOutputDeviceId;
 use rxrust::prelude::*;
 
 use std::cell::RefCell;
 use std::error::Error;
 use std::rc::Rc;
+use std::time::Duration;
 
 /// A model for creating mappings (a combination of source, mode and target).
 #[derive(Clone, Debug)]
@@ -35,6 +38,10 @@ pub struct MappingModel {
     pub feedback_send_behavior: Prop<FeedbackSendBehavior>,
     pub activation_condition_model: ActivationConditionModel,
     pub visible_in_projection: Prop<bool>,
+    /// Maximum number of control values per second. Faster ones get coalesced.
+    pub max_control_rate: Prop<Option<u32>>,
+    /// Layer in which this mapping can be controlled. `None` means all layers.
+    pub layer: Prop<Option<LayerId>>,
     pub source_model: SourceModel,
     pub mode_model: ModeModel,
     pub target_model: TargetModel,
@@ -90,6 +97,8 @@ impl MappingModel {
             feedback_send_behavior: prop(Default::default()),
             activation_condition_model: Default::default(),
             visible_in_projection: prop(true),
+            max_control_rate: prop(None),
+            layer: prop(None),
             source_model: Default::default(),
             mode_model: Default::default(),
             target_model: TargetModel {
@@ -300,6 +309,8 @@ impl MappingModel {
             .merge(self.feedback_is_enabled.changed())
             .merge(self.feedback_send_behavior.changed())
             .merge(self.visible_in_projection.changed())
+            .merge(self.max_control_rate.changed())
+            .merge(self.layer.changed())
             .merge(
                 self.activation_condition_model
                     .changed_processing_relevant(),
@@ -391,7 +402,11 @@ impl MappingModel {
 
     /// Creates an intermediate mapping for splintering into very dedicated mapping types that are
     /// then going to be distributed to real-time and main processor.
-    pub fn create_main_mapping(&self, group_data: GroupData) -> MainMapping {
+    pub fn create_main_mapping(
+        &self,
+        group_data: GroupData,
+        midi_macros: &MidiMacros,
+    ) -> MainMapping {
         let id = self.id;
         let source = self.create_source();
         let mode = self.create_mode();
@@ -406,7 +421,87 @@ impl MappingModel {
             control_is_enabled: group_data.control_is_enabled && self.control_is_enabled.get(),
             feedback_is_enabled: group_data.feedback_is_enabled && self.feedback_is_enabled.get(),
             feedback_send_behavior: self.feedback_send_behavior.get(),
+            velocity_threshold: self
+                .extension_model
+                .glue
+                .create_velocity_threshold()
+                .unwrap_or_else(|| self.mode_model.create_velocity_threshold()),
+            noise_filter: self
+                .extension_model
+                .glue
+                .create_noise_filter_settings(self.mode_model.create_noise_filter_settings()),
+            scale_quantizer: self
+                .extension_model
+                .glue
+                .create_scale_quantizer()
+                .unwrap_or_else(|| self.mode_model.create_scale_quantizer()),
+            osc_delta: self.source_model.create_osc_delta_settings(),
+            sysex_checksum: self.source_model.create_sysex_checksum(),
+            midi_input_device: self.source_model.create_midi_input_device(),
+            midi_output_device: self
+                .source_model
+                .create_midi_output_device(group_data.midi_output_device),
+            min_control_interval: self
+                .extension_model
+                .max_control_rate
+                .or_else(|| self.max_control_rate.get())
+                .filter(|rate| *rate > 0)
+                .map(|rate| Duration::from_secs_f64(1.0 / rate as f64)),
+            min_target_hit_interval: self
+                .extension_model
+                .glue
+                .create_min_target_hit_interval()
+                .unwrap_or_else(|| self.mode_model.create_min_target_hit_interval()),
+            ramp: self
+                .extension_model
+                .glue
+                .create_ramp_settings()
+                .unwrap_or_else(|| self.mode_model.create_ramp_settings()),
+            // The advanced setting takes precedence over the glue property.
+            feedback_deadband: self
+                .extension_model
+                .feedback
+                .create_feedback_deadband()
+                .unwrap_or_else(|| self.mode_model.create_feedback_deadband()),
+            encoder_acceleration: self
+                .extension_model
+                .glue
+                .create_encoder_acceleration_settings()
+                .unwrap_or_else(|| self.mode_model.create_encoder_acceleration_settings()),
+            target_value_sequence_stepping: self
+                .extension_model
+                .glue
+                .create_target_value_sequence_stepping()
+                .unwrap_or_else(|| self.mode_model.create_target_value_sequence_stepping())
+                .filter(|_| !self.mode_model.target_value_sequence.get_ref().is_empty()),
+            scaling_takeover: self
+                .extension_model
+                .glue
+                .create_scaling_takeover_settings()
+                .unwrap_or_else(|| self.mode_model.create_scaling_takeover_settings())
+                .filter(|_| self.mode_model.takeover_mode.get() == TakeoverMode::CatchUp),
+            response_curve: self
+                .extension_model
+                .glue
+                .create_response_curve()
+                .unwrap_or_else(|| self.mode_model.create_response_curve()),
+            // Applied by the main processor, which knows about the currently soloed mapping.
+            control_is_muted_by_solo: false,
+            layer: self.extension_model.layer.or_else(|| self.layer.get()),
+            // Applied by the main processor, which knows about the currently active layer.
+            control_is_muted_by_layer: false,
+            // Applied by the main processor, which knows about unplugged devices.
+            control_is_suspended_by_missing_device: false,
+            feedback_is_suspended_by_missing_device: false,
         };
+        // Argument filters only make sense for OSC sources, no matter where they come from.
+        let osc_arg_filters = self
+            .extension_model
+            .source
+            .argument_filters
+            .clone()
+            .filter(|_| self.source_model.category.get() == SourceCategory::Osc)
+            .unwrap_or_else(|| self.source_model.create_osc_arg_filters());
         let mut merged_tags = group_data.tags;
         merged_tags.extend_from_slice(self.tags.get_ref());
         MainMapping::new(
@@ -424,8 +519,9 @@ impl MappingModel {
             activation_condition,
             options,
             self.extension_model
-                .create_mapping_extension()
+                .create_mapping_extension(midi_macros)
                 .unwrap_or_default(),
+            osc_arg_filters,
         )
     }
 }
@@ -435,6 +531,8 @@ pub struct GroupData {
     pub feedback_is_enabled: bool,
     pub activation_condition: ActivationCondition,
     pub tags: Vec<Tag>,
+    /// MIDI output device for mappings whose source doesn't override it itself.
+    pub midi_output_device: Option<MidiOutputDeviceId>,
 }
 
 impl Default for GroupData {
@@ -444,6 +542,7 @@ impl Default for GroupData {
             feedback_is_enabled: true,
             activation_condition: ActivationCondition::Always,
             tags: vec![],
+            midi_output_device: None,
         }
     }
 }
@@ -454,7 +553,7 @@ pub struct MappingModelWithContext<'a> {
 }
 
 impl<'a> MappingModelWithContext<'a> {
-    pub fn mode_makes_sense(&self) -> Result<bool, &'static str> {
+    pub fn mode_makes_sense(&self) -> Result<bool, RealearnError> {
         use ExtendedSourceCharacter::*;
         use SourceCharacter::*;
         let mode_type = self.mapping.mode_model.r#type.get();
@@ -502,7 +601,7 @@ impl<'a> MappingModelWithContext<'a> {
             })
     }
 
-    pub fn preferred_mode_type(&self) -> Result<AbsoluteMode, &'static str> {
+    pub fn preferred_mode_type(&self) -> Result<AbsoluteMode, RealearnError> {
         use ExtendedSourceCharacter::*;
         use SourceCharacter::*;
         let result = match self.mapping.source_model.character() {
@@ -37,6 +37,9 @@ pub use preset_link::*;
 mod mapping_extension_model;
 pub use mapping_extension_model::*;
 
+mod instance_hooks_model;
+pub use instance_hooks_model::*;
+
 mod midi_util;
 pub use midi_util::*;
 
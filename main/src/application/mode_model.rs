@@ -1,5 +1,10 @@
 use crate::base::{prop, Prop};
-use crate::domain::{EelTransformation, Mode, OutputVariable};
+use crate::domain::{
+    EelTransformation, EncoderAccelerationProfile, EncoderAccelerationSettings, Hysteresis,
+    MappingModeSetting, Mode, MusicalScale, NoiseFilterSettings, OutputVariable, RampCurve,
+    RampSettings, ResponseCurve, ScaleQuantizer, ScalingTakeoverSettings, ValueSequenceStepping,
+    VelocityThreshold, DEFAULT_ENCODER_ACCELERATION_CURVE,
+};
 
 use helgoboss_learn::{
     check_mode_applicability, full_discrete_interval, full_unit_interval, AbsoluteMode,
@@ -57,6 +62,39 @@ pub struct ModeModel {
     pub textual_feedback_expression: Prop<String>,
     pub feedback_color: Prop<Option<VirtualColor>>,
     pub feedback_background_color: Prop<Option<VirtualColor>>,
+    /// If set, presses are classified as soft or hard by comparing the velocity with this value.
+    pub velocity_threshold: Prop<Option<UnitValue>>,
+    /// Control value for soft presses. `None` means soft presses are ignored.
+    pub velocity_below_threshold_value: Prop<Option<UnitValue>>,
+    /// Control value for hard presses. `None` means hard presses are ignored.
+    pub velocity_above_threshold_value: Prop<Option<UnitValue>>,
+    /// If set, notes generated by the "Send MIDI" target are snapped to this scale.
+    pub quantization_scale: Prop<Option<MusicalScale>>,
+    /// Pitch class of the scale's root note (0 = C, ..., 11 = B).
+    pub quantization_root: Prop<u8>,
+    /// Minimum time between two target hits. Zero means target hits are not throttled.
+    pub min_target_hit_interval: Prop<Duration>,
+    /// Absolute control values closer than this to the last processed one are ignored.
+    pub dead_zone: Prop<UnitValue>,
+    /// If set, the source acts as a switch which turns on at the max and off at the min.
+    pub hysteresis_thresholds: Prop<Option<Interval<UnitValue>>>,
+    /// Time for sweeping from min to max while a button is held. Zero means no ramping.
+    pub ramp_duration: Prop<Duration>,
+    pub ramp_curve: Prop<RampCurve>,
+    /// Whether releasing the button sweeps back down.
+    pub ramp_returns_on_release: Prop<bool>,
+    /// Source feedback closer than this to the previously sent value is suppressed.
+    pub feedback_deadband: Prop<UnitValue>,
+    pub encoder_acceleration_profile: Prop<EncoderAccelerationProfile>,
+    /// Exponent of the exponential acceleration profile.
+    pub encoder_acceleration_curve: Prop<f64>,
+    /// Whether button presses step through the target value sequence.
+    pub target_value_sequence_stepping: Prop<ValueSequenceStepping>,
+    /// Point-based curve applied to absolute control values. Empty means no shaping.
+    pub response_curve: Prop<ResponseCurve>,
+    /// With takeover mode "Catch up", control values at most this far away from the target value
+    /// are applied directly. Zero means the default catch-up behavior.
+    pub takeover_catch_range: Prop<UnitValue>,
 }
 
 impl Default for ModeModel {
@@ -89,11 +127,56 @@ impl Default for ModeModel {
             textual_feedback_expression: prop(Default::default()),
             feedback_color: prop(Default::default()),
             feedback_background_color: prop(Default::default()),
+            velocity_threshold: prop(None),
+            velocity_below_threshold_value: prop(None),
+            velocity_above_threshold_value: prop(None),
+            quantization_scale: prop(None),
+            quantization_root: prop(0),
+            min_target_hit_interval: prop(Duration::from_millis(0)),
+            dead_zone: prop(UnitValue::MIN),
+            hysteresis_thresholds: prop(None),
+            ramp_duration: prop(Duration::from_millis(0)),
+            ramp_curve: prop(Default::default()),
+            ramp_returns_on_release: prop(false),
+            feedback_deadband: prop(UnitValue::MIN),
+            encoder_acceleration_profile: prop(Default::default()),
+            encoder_acceleration_curve: prop(DEFAULT_ENCODER_ACCELERATION_CURVE),
+            target_value_sequence_stepping: prop(Default::default()),
+            response_curve: prop(Default::default()),
+            takeover_catch_range: prop(UnitValue::MIN),
         }
     }
 }
 
 impl ModeModel {
+    /// Changes one of the settings which can be adjusted at runtime via target.
+    pub fn adjust_setting(&mut self, setting: MappingModeSetting, value: UnitValue) {
+        use MappingModeSetting::*;
+        match setting {
+            SourceMin => self
+                .source_value_interval
+                .set_with(|prev| prev.with_min(value)),
+            SourceMax => self
+                .source_value_interval
+                .set_with(|prev| prev.with_max(value)),
+            TargetMin => self
+                .target_value_interval
+                .set_with(|prev| prev.with_min(value)),
+            TargetMax => self
+                .target_value_interval
+                .set_with(|prev| prev.with_max(value)),
+            StepSizeMin => self
+                .step_interval
+                .set_with(|prev| prev.with_min(value.to_symmetric())),
+            StepSizeMax => self
+                .step_interval
+                .set_with(|prev| prev.with_max(value.to_symmetric())),
+            JumpMin => self.jump_interval.set_with(|prev| prev.with_min(value)),
+            JumpMax => self.jump_interval.set_with(|prev| prev.with_max(value)),
+            DeadZone => self.dead_zone.set(value),
+        }
+    }
+
     pub fn default_step_size_interval() -> Interval<SoftSymmetricUnitValue> {
         // 0.01 has been chosen as default minimum step size because it corresponds to 1%.
         //
@@ -143,6 +226,33 @@ impl ModeModel {
         self.press_duration_interval
             .set(def.press_duration_interval.get());
         self.turbo_rate.set(def.turbo_rate.get());
+        self.velocity_threshold.set(def.velocity_threshold.get());
+        self.velocity_below_threshold_value
+            .set(def.velocity_below_threshold_value.get());
+        self.velocity_above_threshold_value
+            .set(def.velocity_above_threshold_value.get());
+        self.quantization_scale.set(def.quantization_scale.get());
+        self.quantization_root.set(def.quantization_root.get());
+        self.min_target_hit_interval
+            .set(def.min_target_hit_interval.get());
+        self.dead_zone.set(def.dead_zone.get());
+        self.hysteresis_thresholds
+            .set(def.hysteresis_thresholds.get());
+        self.ramp_duration.set(def.ramp_duration.get());
+        self.ramp_curve.set(def.ramp_curve.get());
+        self.ramp_returns_on_release
+            .set(def.ramp_returns_on_release.get());
+        self.feedback_deadband.set(def.feedback_deadband.get());
+        self.encoder_acceleration_profile
+            .set(def.encoder_acceleration_profile.get());
+        self.encoder_acceleration_curve
+            .set(def.encoder_acceleration_curve.get());
+        self.target_value_sequence_stepping
+            .set(def.target_value_sequence_stepping.get());
+        self.response_curve
+            .set(def.response_curve.get_ref().clone());
+        self.takeover_catch_range
+            .set(def.takeover_catch_range.get());
     }
 
     /// Fires whenever one of the properties of this model has changed
@@ -172,6 +282,113 @@ impl ModeModel {
             .merge(self.group_interaction.changed())
             .merge(self.target_value_sequence.changed())
             .merge(self.feedback_type.changed())
+            .merge(self.velocity_threshold.changed())
+            .merge(self.velocity_below_threshold_value.changed())
+            .merge(self.velocity_above_threshold_value.changed())
+            .merge(self.quantization_scale.changed())
+            .merge(self.quantization_root.changed())
+            .merge(self.min_target_hit_interval.changed())
+            .merge(self.dead_zone.changed())
+            .merge(self.hysteresis_thresholds.changed())
+            .merge(self.ramp_duration.changed())
+            .merge(self.ramp_curve.changed())
+            .merge(self.ramp_returns_on_release.changed())
+            .merge(self.feedback_deadband.changed())
+            .merge(self.encoder_acceleration_profile.changed())
+            .merge(self.encoder_acceleration_curve.changed())
+            .merge(self.target_value_sequence_stepping.changed())
+            .merge(self.response_curve.changed())
+            .merge(self.takeover_catch_range.changed())
+    }
+
+    pub fn create_velocity_threshold(&self) -> Option<VelocityThreshold> {
+        let threshold = VelocityThreshold {
+            threshold: self.velocity_threshold.get()?,
+            below_value: self.velocity_below_threshold_value.get(),
+            above_value: self.velocity_above_threshold_value.get(),
+        };
+        Some(threshold)
+    }
+
+    pub fn create_scale_quantizer(&self) -> Option<ScaleQuantizer> {
+        let quantizer = ScaleQuantizer {
+            scale: self.quantization_scale.get()?,
+            root: self.quantization_root.get(),
+        };
+        Some(quantizer)
+    }
+
+    pub fn create_noise_filter_settings(&self) -> Option<NoiseFilterSettings> {
+        let hysteresis = self.hysteresis_thresholds.get().map(|i| Hysteresis {
+            lower_threshold: i.min_val(),
+            upper_threshold: i.max_val(),
+        });
+        if self.dead_zone.get().is_zero() && hysteresis.is_none() {
+            return None;
+        }
+        let settings = NoiseFilterSettings {
+            dead_zone: self.dead_zone.get(),
+            hysteresis,
+        };
+        Some(settings)
+    }
+
+    pub fn create_min_target_hit_interval(&self) -> Option<Duration> {
+        Some(self.min_target_hit_interval.get()).filter(|i| *i > Duration::from_millis(0))
+    }
+
+    pub fn create_ramp_settings(&self) -> Option<RampSettings> {
+        let duration = self.ramp_duration.get();
+        if duration == Duration::from_millis(0) {
+            return None;
+        }
+        let settings = RampSettings {
+            duration,
+            curve: self.ramp_curve.get(),
+            return_on_release: self.ramp_returns_on_release.get(),
+        };
+        Some(settings)
+    }
+
+    pub fn create_feedback_deadband(&self) -> Option<UnitValue> {
+        Some(self.feedback_deadband.get()).filter(|d| !d.is_zero())
+    }
+
+    pub fn create_encoder_acceleration_settings(&self) -> Option<EncoderAccelerationSettings> {
+        let profile = self.encoder_acceleration_profile.get();
+        if profile == EncoderAccelerationProfile::Off {
+            return None;
+        }
+        let settings = EncoderAccelerationSettings {
+            profile,
+            curve: self.encoder_acceleration_curve.get(),
+        };
+        Some(settings)
+    }
+
+    pub fn create_target_value_sequence_stepping(&self) -> Option<ValueSequenceStepping> {
+        let stepping = self.target_value_sequence_stepping.get();
+        if stepping == ValueSequenceStepping::Off || self.target_value_sequence.get_ref().is_empty()
+        {
+            return None;
+        }
+        Some(stepping)
+    }
+
+    pub fn create_response_curve(&self) -> Option<ResponseCurve> {
+        let curve = self.response_curve.get_ref();
+        if curve.is_empty() {
+            return None;
+        }
+        Some(curve.normalized())
+    }
+
+    pub fn create_scaling_takeover_settings(&self) -> Option<ScalingTakeoverSettings> {
+        let catch_range = self.takeover_catch_range.get();
+        if self.takeover_mode.get() != TakeoverMode::CatchUp || catch_range.is_zero() {
+            return None;
+        }
+        Some(ScalingTakeoverSettings { catch_range })
     }
 
     pub fn mode_parameter_is_relevant(
@@ -1,19 +1,23 @@
 use crate::application::{
-    share_group, share_mapping, CompartmentModel, ControllerPreset, FxId, GroupModel, MainPreset,
-    MainPresetAutoLoadMode, MappingModel, Preset, PresetLinkManager, PresetManager, SharedGroup,
-    SharedMapping, SourceModel, TargetCategory, TargetModel, VirtualControlElementType,
+    share_group, share_mapping, CompartmentModel, ControllerPreset, FxId, GroupModel,
+    InstanceHooksModel, MainPreset, MainPresetAutoLoadMode, MappingModel, Preset,
+    PresetLinkManager, PresetManager, SharedGroup, SharedMapping, SourceCategory, SourceModel,
+    TargetCategory, TargetModel, VirtualControlElementType,
 };
 use crate::base::default_util::is_default;
-use crate::base::{prop, when, AsyncNotifier, Global, Prop};
+use crate::base::{notification, prop, when, AsyncNotifier, Global, Prop};
 use crate::domain::{
-    BackboneState, CompoundMappingSource, ControlContext, ControlInput, DomainEvent,
-    DomainEventHandler, ExtendedProcessorContext, FeedbackAudioHookTask, FeedbackOutput, GroupId,
-    GroupKey, IncomingCompoundSourceValue, InputDescriptor, InstanceContainer, InstanceId,
-    InstanceState, MainMapping, MappingCompartment, MappingId, MappingKey, MappingMatchedEvent,
-    MessageCaptureEvent, MidiControlInput, MidiDestination, NormalMainTask, NormalRealTimeTask,
-    OscDeviceId, OscFeedbackTask, ParameterArray, ProcessorContext, ProjectionFeedbackValue,
-    QualifiedMappingId, RealTimeSender, RealearnTarget, ReaperTarget, SharedInstanceState,
-    SourceFeedbackValue, Tag, TargetValueChangedEvent, VirtualControlElementId, VirtualSource,
+    BackboneState, CompoundMappingSource, ControlContext, ControlInput, ControlLatencyStatistics,
+    DomainEvent, DomainEventHandler, ExtendedProcessorContext, FeedbackAudioHookTask,
+    FeedbackLoopDetectedEvent, FeedbackOutput, GroupId, GroupKey, IdleAnimationSettings,
+    IdleAnimationType, IncomingCompoundSourceValue, InputDescriptor, InstanceContainer,
+    InstanceHookEvent, InstanceHooks, InstanceId, InstanceMessage, InstancePriority, InstanceState,
+    MainMapping, MappingCompartment, MappingId, MappingKey, MappingMatchedEvent,
+    MessageCaptureEvent, MidiControlInput, MidiDestination, MidiMacros, NormalMainTask,
+    NormalRealTimeTask, OscDeviceId, OscFeedbackTask, ParameterArray, ParameterLink,
+    ParameterLinking, ProcessorContext, ProjectionFeedbackValue, QualifiedMappingId,
+    RealTimeSender, RealearnTarget, ReaperTarget, SharedInstanceState, SourceFeedbackValue, Tag,
+    TargetValueChangedEvent, VirtualControlElement, VirtualControlElementId, VirtualSource,
     VirtualSourceValue, COMPARTMENT_PARAMETER_COUNT, ZEROED_PLUGIN_PARAMETERS,
 };
 use derivative::Derivative;
@@ -24,7 +28,7 @@ use reaper_high::Reaper;
 use rx_util::Notifier;
 use rxrust::prelude::ops::box_it::LocalBoxOp;
 use rxrust::prelude::*;
-use slog::{debug, trace};
+use slog::{debug, trace, warn};
 use std::cell::{Ref, RefCell};
 use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
@@ -34,10 +38,11 @@ use helgoboss_learn::AbsoluteValue;
 use itertools::Itertools;
 use reaper_medium::RecordingInput;
 use std::rc::{Rc, Weak};
+use std::time::Duration;
 
 pub trait SessionUi {
     fn show_mapping(&self, compartment: MappingCompartment, mapping_id: MappingId);
-    fn target_value_changed(&self, event: TargetValueChangedEvent);
+    fn target_value_changed(&self, session: &Session, event: TargetValueChangedEvent);
     fn parameters_changed(&self, session: &Session);
     fn send_projection_feedback(&self, session: &Session, value: ProjectionFeedbackValue);
     fn mapping_matched(&self, event: MappingMatchedEvent);
@@ -66,14 +71,26 @@ pub struct Session {
     pub osc_input_device_id: Prop<Option<OscDeviceId>>,
     pub osc_output_device_id: Prop<Option<OscDeviceId>>,
     pub main_preset_auto_load_mode: Prop<MainPresetAutoLoadMode>,
-    pub lives_on_upper_floor: Prop<bool>,
+    /// If multiple instances use the same control input or feedback output, only the ones with
+    /// the highest priority may use it.
+    pub instance_priority: Prop<InstancePriority>,
+    pub idle_animation_type: Prop<IdleAnimationType>,
+    pub idle_animation_timeout_minutes: Prop<u32>,
+    pub suppress_feedback_loops: Prop<bool>,
     pub tags: Prop<Vec<Tag>>,
+    /// Instance lifecycle hooks as edited by the user (YAML).
+    instance_hooks: Prop<Option<serde_yaml::Mapping>>,
+    instance_hooks_model: InstanceHooksModel,
     pub compartment_is_dirty: EnumMap<MappingCompartment, Prop<bool>>,
     // Is set when in the state of learning multiple mappings ("batch learn")
     learn_many_state: Prop<Option<LearnManyState>>,
+    // Is set while a remote client drives a guided mapping flow
+    guided_mapping_state: Prop<Option<GuidedMappingState>>,
     // We want that learn works independently of the UI, so they are session properties.
     mapping_which_learns_source: Prop<Option<QualifiedMappingId>>,
     mapping_which_learns_target: Prop<Option<QualifiedMappingId>>,
+    // Not persisted, it's just a temporary debugging aid.
+    soloed_mapping: Prop<Option<QualifiedMappingId>>,
     active_controller_preset_id: Option<String>,
     active_main_preset_id: Option<String>,
     context: ProcessorContext,
@@ -88,6 +105,7 @@ pub struct Session {
     parameter_settings_changed_subject: LocalSubject<'static, MappingCompartment, ()>,
     mapping_changed_subject: LocalSubject<'static, MappingCompartment, ()>,
     group_changed_subject: LocalSubject<'static, MappingCompartment, ()>,
+    activity_subject: LocalSubject<'static, SessionActivity, ()>,
     incoming_msg_captured_subject: LocalSubject<'static, MessageCaptureEvent, ()>,
     mapping_subscriptions: EnumMap<MappingCompartment, Vec<SubscriptionGuard<LocalSubscription>>>,
     group_subscriptions: EnumMap<MappingCompartment, Vec<SubscriptionGuard<LocalSubscription>>>,
@@ -110,7 +128,8 @@ pub struct Session {
 #[derive(Clone, PartialEq, Debug)]
 pub struct LearnManyState {
     pub compartment: MappingCompartment,
-    pub current_mapping_id: MappingId,
+    /// `None` while waiting for a target touch which creates the next mapping.
+    pub current_mapping_id: Option<MappingId>,
     pub sub_state: LearnManySubState,
 }
 
@@ -121,6 +140,8 @@ pub enum LearnManySubState {
         control_element_type: VirtualControlElementType,
     },
     LearningTarget,
+    /// When learning many targets, each touched target creates a new mapping.
+    WaitingForTarget,
 }
 
 impl LearnManyState {
@@ -131,7 +152,7 @@ impl LearnManyState {
     ) -> LearnManyState {
         LearnManyState {
             compartment,
-            current_mapping_id,
+            current_mapping_id: Some(current_mapping_id),
             sub_state: LearnManySubState::LearningSource {
                 control_element_type,
             },
@@ -144,21 +165,76 @@ impl LearnManyState {
     ) -> LearnManyState {
         LearnManyState {
             compartment,
-            current_mapping_id,
+            current_mapping_id: Some(current_mapping_id),
             sub_state: LearnManySubState::LearningTarget,
         }
     }
+
+    pub fn waiting_for_target(compartment: MappingCompartment) -> LearnManyState {
+        LearnManyState {
+            compartment,
+            current_mapping_id: None,
+            sub_state: LearnManySubState::WaitingForTarget,
+        }
+    }
+}
+
+/// State of a guided mapping flow which a remote client (e.g. the Companion app) drives step by
+/// step: Claim a control element, identify it, capture a target and commit the mapping.
+#[derive(Clone, PartialEq, Debug)]
+pub struct GuidedMappingState {
+    pub control_element: VirtualControlElement,
+    /// Target captured via "last touched". `None` as long as no target has been captured.
+    pub target: Option<ReaperTarget>,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum GuidedMappingStep {
+    CapturingTarget,
+    ReadyToCommit,
+}
+
+impl GuidedMappingState {
+    pub fn step(&self) -> GuidedMappingStep {
+        if self.target.is_some() {
+            GuidedMappingStep::ReadyToCommit
+        } else {
+            GuidedMappingStep::CapturingTarget
+        }
+    }
+}
+
+/// Noteworthy things which happened in a session, e.g. interesting for external systems.
+#[derive(Clone, PartialEq, Debug)]
+pub enum SessionActivity {
+    PresetLoaded {
+        compartment: MappingCompartment,
+        /// `None` means the <None> preset.
+        preset_id: Option<String>,
+    },
+    MappingEnabledChanged {
+        compartment: MappingCompartment,
+        mapping_id: MappingId,
+        mapping_name: String,
+        is_enabled: bool,
+    },
+    /// Recording has been started via a transport target.
+    RecordingStarted,
 }
 
 pub mod session_defaults {
     use crate::application::MainPresetAutoLoadMode;
+    use crate::domain::{IdleAnimationType, InstancePriority};
 
     pub const LET_MATCHED_EVENTS_THROUGH: bool = false;
     pub const LET_UNMATCHED_EVENTS_THROUGH: bool = true;
     pub const AUTO_CORRECT_SETTINGS: bool = true;
-    pub const LIVES_ON_UPPER_FLOOR: bool = false;
+    pub const INSTANCE_PRIORITY: InstancePriority = 0;
     pub const SEND_FEEDBACK_ONLY_IF_ARMED: bool = true;
     pub const MAIN_PRESET_AUTO_LOAD_MODE: MainPresetAutoLoadMode = MainPresetAutoLoadMode::Off;
+    pub const IDLE_ANIMATION_TYPE: IdleAnimationType = IdleAnimationType::Off;
+    pub const IDLE_ANIMATION_TIMEOUT_MINUTES: u32 = 5;
+    pub const SUPPRESS_FEEDBACK_LOOPS: bool = true;
 }
 
 impl Session {
@@ -195,12 +271,19 @@ impl Session {
             osc_input_device_id: prop(None),
             osc_output_device_id: prop(None),
             main_preset_auto_load_mode: prop(session_defaults::MAIN_PRESET_AUTO_LOAD_MODE),
-            lives_on_upper_floor: prop(false),
+            instance_priority: prop(session_defaults::INSTANCE_PRIORITY),
+            idle_animation_type: prop(session_defaults::IDLE_ANIMATION_TYPE),
+            idle_animation_timeout_minutes: prop(session_defaults::IDLE_ANIMATION_TIMEOUT_MINUTES),
+            suppress_feedback_loops: prop(session_defaults::SUPPRESS_FEEDBACK_LOOPS),
             tags: Default::default(),
+            instance_hooks: prop(None),
+            instance_hooks_model: Default::default(),
             compartment_is_dirty: Default::default(),
             learn_many_state: prop(None),
+            guided_mapping_state: prop(None),
             mapping_which_learns_source: prop(None),
             mapping_which_learns_target: prop(None),
+            soloed_mapping: prop(None),
             active_controller_preset_id: None,
             active_main_preset_id: None,
             context,
@@ -218,6 +301,7 @@ impl Session {
             parameter_settings_changed_subject: Default::default(),
             mapping_changed_subject: Default::default(),
             group_changed_subject: Default::default(),
+            activity_subject: Default::default(),
             incoming_msg_captured_subject: Default::default(),
             mapping_subscriptions: Default::default(),
             group_subscriptions: Default::default(),
@@ -271,6 +355,8 @@ impl Session {
                     }
                 }
                 MidiControlInput::Device(dev_id) => dev_id == *device_id,
+                // Network MIDI never reaches REAPER's MIDI inputs.
+                MidiControlInput::RtpMidi(_) => false,
             },
             InputDescriptor::Osc { device_id } => {
                 self.osc_input_device_id.get_ref().as_ref() == Some(device_id)
@@ -417,7 +503,7 @@ impl Session {
         // It's important to sync feedback device first, otherwise the initial feedback messages
         // won't arrive!
         self.sync_settings();
-        self.sync_upper_floor_membership();
+        self.sync_instance_priority();
         self.sync_control_is_globally_enabled();
         self.sync_feedback_is_globally_enabled();
         // Now sync mappings - which includes initial feedback.
@@ -434,6 +520,81 @@ impl Session {
             .unwrap();
     }
 
+    pub fn instance_hooks(&self) -> Option<&serde_yaml::Mapping> {
+        self.instance_hooks.get_ref().as_ref()
+    }
+
+    pub fn set_instance_hooks(
+        &mut self,
+        value: Option<serde_yaml::Mapping>,
+        with_notification: bool,
+    ) -> Result<(), String> {
+        self.instance_hooks
+            .set_with_optional_notification(value, with_notification);
+        // Immediately update hooks model
+        let model: InstanceHooksModel = if let Some(yaml_mapping) = self.instance_hooks.get_ref() {
+            serde_yaml::from_value(serde_yaml::Value::Mapping(yaml_mapping.clone()))
+                .map_err(|e| e.to_string())?
+        } else {
+            Default::default()
+        };
+        self.create_instance_hooks(&model)?;
+        self.instance_hooks_model = model;
+        Ok(())
+    }
+
+    /// Executes the actions attached to the given instance lifecycle event.
+    pub fn fire_instance_hook(&self, event: InstanceHookEvent) {
+        let sender = self.normal_main_task_sender.clone();
+        // Deferred so that the actions are executed after pending parameter resets (e.g. when
+        // activating a preset). Otherwise "set_parameter" actions would be overwritten.
+        let _ = Global::task_support().do_later_in_main_thread_from_main_thread_asap(move || {
+            sender
+                .try_send(NormalMainTask::FireInstanceHook(event))
+                .unwrap();
+        });
+    }
+
+    fn create_instance_hooks(&self, model: &InstanceHooksModel) -> Result<InstanceHooks, String> {
+        let hooks = model.create_instance_hooks(&self.midi_macros(), |compartment, key| {
+            self.mappings(compartment)
+                .map(|m| m.borrow())
+                .find(|m| m.key() == key)
+                .map(|m| m.qualified_id())
+        })?;
+        Ok(hooks)
+    }
+
+    /// Resolves the instance hooks (mapping keys and MIDI macros) and sends them to the main
+    /// processor.
+    fn sync_instance_hooks(&self) {
+        let hooks = match self.create_instance_hooks(&self.instance_hooks_model) {
+            Ok(h) => h,
+            Err(e) => {
+                warn!(self.logger, "Invalid instance hooks: {}", e);
+                Default::default()
+            }
+        };
+        self.normal_main_task_sender
+            .try_send(NormalMainTask::UpdateInstanceHooks(Box::new(hooks)))
+            .unwrap();
+    }
+
+    /// Sends the links of compartment parameters to parameters of other instances to the main
+    /// processor.
+    fn sync_parameter_linking(&self) {
+        let links = MappingCompartment::enum_iter().flat_map(move |compartment| {
+            self.parameter_settings[compartment]
+                .iter()
+                .enumerate()
+                .filter_map(move |(i, s)| Some((compartment, i as u32, s.link.clone()?)))
+        });
+        let linking = ParameterLinking::new(self.id.get_ref().clone(), links);
+        self.normal_main_task_sender
+            .try_send(NormalMainTask::UpdateParameterLinking(Box::new(linking)))
+            .unwrap();
+    }
+
     /// Instructs the main processor to hit the target directly.
     ///
     /// This doesn't invoke group interaction because it's meant to totally skip the mode.
@@ -443,6 +604,21 @@ impl Session {
             .unwrap();
     }
 
+    /// Instructs the main processor to clear all feedback, reset the MIDI feedback output device,
+    /// stop all clips and send fresh feedback.
+    pub fn panic(&self) {
+        self.normal_main_task_sender
+            .try_send(NormalMainTask::Panic)
+            .unwrap();
+    }
+
+    /// Lets the main processor control mappings with matching "Instance message" sources.
+    pub fn receive_instance_message(&self, msg: InstanceMessage) {
+        self.normal_main_task_sender
+            .try_send(NormalMainTask::ProcessInstanceMessage(msg))
+            .unwrap();
+    }
+
     /// Connects the dots.
     // TODO-low Too large. Split this into several methods.
     pub fn activate(&mut self, weak_session: WeakSession) {
@@ -477,12 +653,21 @@ impl Session {
                 session.sync_all_mappings_full(compartment);
                 session.mark_compartment_dirty(compartment);
             });
-        // Whenever something in the parameter settings changed, mark compartment dirty.
+        // Whenever something in the parameter settings changed, mark compartment dirty and let
+        // the host display the new parameter names.
         when(self.parameter_settings_changed())
             .with(weak_session.clone())
             .do_async(|shared_session, compartment| {
                 let mut session = shared_session.borrow_mut();
+                session.sync_parameter_linking();
                 session.mark_compartment_dirty(compartment);
+                session.context().notify_parameter_display_changed();
+            });
+        // Parameter links of other instances refer to this instance by session ID.
+        when(self.id.changed())
+            .with(weak_session.clone())
+            .do_async(|shared_session, _| {
+                shared_session.borrow().sync_parameter_linking();
             });
         // Whenever anything in a mapping list changes and other things which affect all
         // processors (including the real-time processor which takes care of sources only), resync
@@ -534,11 +719,11 @@ impl Session {
             .do_sync(move |s, (compartment, _)| {
                 s.borrow_mut().mark_compartment_dirty(compartment);
             });
-        // Keep adding/removing instance to/from upper floor.
-        when(self.lives_on_upper_floor.changed())
+        // Keep the main processor informed about the instance priority.
+        when(self.instance_priority.changed())
             .with(weak_session.clone())
-            .do_sync(move |s, _| {
-                s.borrow().sync_upper_floor_membership();
+            .do_async(move |s, _| {
+                s.borrow().sync_instance_priority();
             });
         // Keep syncing some general settings to real-time processor.
         when(self.settings_changed())
@@ -546,6 +731,26 @@ impl Session {
             .do_async(move |s, _| {
                 s.borrow().sync_settings();
             });
+        // Fire the corresponding hook when the user chooses another feedback output. Registered
+        // after syncing the settings, so the main processor already knows about the new output.
+        when(
+            self.midi_feedback_output
+                .changed()
+                .merge(self.osc_output_device_id.changed()),
+        )
+        .with(weak_session.clone())
+        .do_async(move |s, _| {
+            s.borrow()
+                .fire_instance_hook(InstanceHookEvent::FeedbackOutputChange);
+        });
+        // Keep the main processor's instance hooks up-to-date.
+        when(self.instance_hooks.changed())
+            .with(weak_session.clone())
+            .do_async(move |s, _| {
+                let s = s.borrow();
+                s.sync_instance_hooks();
+                s.mark_dirty();
+            });
         // When FX is reordered, invalidate FX indexes. This is primarily for the GUI.
         // Existing GUID-tracked `Fx` instances will detect wrong index automatically.
         when(
@@ -642,6 +847,10 @@ impl Session {
             .merge(self.main_preset_auto_load_mode.changed())
             .merge(self.input_logging_enabled.changed())
             .merge(self.output_logging_enabled.changed())
+            .merge(self.idle_animation_type.changed())
+            .merge(self.idle_animation_timeout_minutes.changed())
+            .merge(self.suppress_feedback_loops.changed())
+            .merge(self.instance_priority.changed())
     }
 
     pub fn captured_incoming_message(&mut self, event: MessageCaptureEvent) {
@@ -775,6 +984,7 @@ impl Session {
                 let shared_mapping_clone_1 = shared_mapping.clone();
                 let shared_mapping_clone_2 = shared_mapping.clone();
                 let shared_mapping_clone_3 = shared_mapping.clone();
+                let shared_mapping_clone_4 = shared_mapping.clone();
                 let all_subscriptions = LocalSubscription::default();
                 // Keep syncing persistent mapping processing state (shouldn't do too much because
                 // can be triggered by processing).
@@ -805,6 +1015,23 @@ impl Session {
                         });
                     all_subscriptions.add(subscription);
                 }
+                // Keep reporting enabled/disabled mappings
+                {
+                    let subscription = when(mapping.is_enabled.changed())
+                        .with(weak_session.clone())
+                        .do_sync(move |session, _| {
+                            let mapping = shared_mapping_clone_4.borrow();
+                            session.borrow_mut().notify_activity(
+                                SessionActivity::MappingEnabledChanged {
+                                    compartment,
+                                    mapping_id: mapping.id(),
+                                    mapping_name: mapping.effective_name(),
+                                    is_enabled: mapping.is_enabled.get(),
+                                },
+                            );
+                        });
+                    all_subscriptions.add(subscription);
+                }
                 // Keep marking project as dirty
                 {
                     let subscription = when(
@@ -926,6 +1153,7 @@ impl Session {
     pub fn control_context(&self) -> ControlContext {
         ControlContext {
             feedback_audio_hook_task_sender: self.global_feedback_audio_hook_task_sender,
+            normal_real_time_task_sender: &self.normal_real_time_task_sender,
             osc_feedback_task_sender: self.global_osc_feedback_task_sender,
             feedback_output: self.feedback_output(),
             instance_container: self.instance_container,
@@ -933,6 +1161,7 @@ impl Session {
             instance_id: self.instance_id(),
             output_logging_enabled: self.output_logging_enabled.get(),
             processor_context: &self.context,
+            instance_message_hop_count: None,
         }
     }
 
@@ -1059,6 +1288,7 @@ impl Session {
         self.groups[compartment].retain(|g| g.borrow().id() != id);
         if delete_mappings {
             self.mappings[compartment].retain(|m| m.borrow().group_id.get() != id);
+            self.unsolo_if_soloed_mapping_is_gone();
         } else {
             for m in self.mappings(compartment) {
                 let mut m = m.borrow_mut();
@@ -1131,6 +1361,7 @@ impl Session {
     ) {
         let mut mapping_key_set = self.mapping_key_set(compartment);
         self.mappings[compartment].retain(|m| m.borrow().group_id.get() != group_id);
+        self.unsolo_if_soloed_mapping_is_gone();
         for mut m in mappings {
             if !mapping_key_set.insert(m.key().clone()) {
                 m.reset_key();
@@ -1282,6 +1513,76 @@ impl Session {
         }
     }
 
+    /// Lets each touched target create a new main mapping in the given group and learns its
+    /// source right after that. Can be stopped with [`Self::stop_learning_many_mappings`].
+    pub fn start_learning_many_targets(&mut self, session: &SharedSession, group_id: GroupId) {
+        // Prepare
+        self.disable_control();
+        self.stop_learning_source();
+        self.stop_learning_target();
+        self.wait_for_one_of_many_targets(session, group_id);
+    }
+
+    fn wait_for_one_of_many_targets(&mut self, session: &SharedSession, group_id: GroupId) {
+        let compartment = MappingCompartment::MainMappings;
+        self.learn_many_state
+            .set(Some(LearnManyState::waiting_for_target(compartment)));
+        let project = self.context.project_or_current_project();
+        when(
+            ReaperTarget::touched()
+                // Prevent learning targets from in other project tabs
+                .filter(move |target| target.project().map_or(true, |p| p == project))
+                // We have this explicit stop criteria because we listen to global REAPER
+                // events.
+                .take_until(self.party_is_over())
+                .take_until(self.learn_many_state.changed_to(None))
+                .take(1),
+        )
+        .with(Rc::downgrade(session))
+        .do_async(move |session, target| {
+            session
+                .borrow_mut()
+                .add_one_of_many_targets(&session, group_id, target.as_ref());
+        });
+    }
+
+    fn add_one_of_many_targets(
+        &mut self,
+        session: &SharedSession,
+        group_id: GroupId,
+        target: &ReaperTarget,
+    ) {
+        let compartment = MappingCompartment::MainMappings;
+        let mapping =
+            self.add_default_mapping(compartment, group_id, VirtualControlElementType::Multi);
+        let qualified_mapping_id = {
+            let mut m = mapping.borrow_mut();
+            m.target_model
+                .apply_from_target(target, self.extended_context(), compartment);
+            m.qualified_id()
+        };
+        self.learn_many_state
+            .set(Some(LearnManyState::learning_source(
+                compartment,
+                qualified_mapping_id.id,
+                VirtualControlElementType::Multi,
+            )));
+        self.start_learning_source(Rc::downgrade(session), mapping, false, vec![], true);
+        // As soon as the source has been learned, wait for the next target touch.
+        when(
+            self.mapping_which_learns_source
+                .changed_to(None)
+                .take_until(self.learn_many_state.changed_to(None))
+                .take(1),
+        )
+        .with(Rc::downgrade(session))
+        .do_async(move |session, _| {
+            session
+                .borrow_mut()
+                .wait_for_one_of_many_targets(&session, group_id);
+        });
+    }
+
     pub fn stop_learning_many_mappings(&mut self) {
         self.learn_many_state.set(None);
         let source_learning_mapping_id = self.mapping_which_learns_source.get();
@@ -1294,6 +1595,106 @@ impl Session {
         }
     }
 
+    pub fn guided_mapping_state(&self) -> Option<&GuidedMappingState> {
+        self.guided_mapping_state.get_ref().as_ref()
+    }
+
+    /// Starts a guided mapping flow for the given control element, replacing any previous one.
+    ///
+    /// Fails if a main mapping already uses the control element as source.
+    pub fn claim_control_element_for_guided_mapping(
+        &mut self,
+        control_element: VirtualControlElement,
+    ) -> Result<(), &'static str> {
+        let is_already_mapped = self.mappings(MappingCompartment::MainMappings).any(|m| {
+            let source = &m.borrow().source_model;
+            source.category.get() == SourceCategory::Virtual
+                && source.create_control_element() == control_element
+        });
+        if is_already_mapped {
+            return Err("control element is already mapped");
+        }
+        self.guided_mapping_state.set(Some(GuidedMappingState {
+            control_element,
+            target: None,
+        }));
+        Ok(())
+    }
+
+    /// Lets the LED of the claimed control element blink so the user knows which one is meant.
+    pub fn identify_guided_mapping_control_element(&self) -> Result<(), &'static str> {
+        let state = self
+            .guided_mapping_state()
+            .ok_or("no guided mapping in progress")?;
+        self.normal_main_task_sender
+            .try_send(NormalMainTask::IdentifyControlElement(
+                state.control_element,
+            ))
+            .unwrap();
+        Ok(())
+    }
+
+    /// Takes the last touched target as target of the guided mapping.
+    pub fn capture_guided_mapping_target(&mut self) -> Result<(), &'static str> {
+        let mut state = self
+            .guided_mapping_state
+            .get_ref()
+            .clone()
+            .ok_or("no guided mapping in progress")?;
+        let target = BackboneState::get()
+            .last_touched_target()
+            .ok_or("no target has been touched yet")?;
+        if let Some(p) = target.project() {
+            if p != self.context.project_or_current_project() {
+                return Err("last touched target belongs to another project");
+            }
+        }
+        state.target = Some(target);
+        self.guided_mapping_state.set(Some(state));
+        Ok(())
+    }
+
+    /// Creates a main mapping from the claimed control element and the captured target and ends
+    /// the guided mapping flow.
+    pub fn commit_guided_mapping(&mut self) -> Result<SharedMapping, &'static str> {
+        let state = self
+            .guided_mapping_state
+            .get_ref()
+            .clone()
+            .ok_or("no guided mapping in progress")?;
+        let target = state.target.ok_or("no target captured yet")?;
+        let compartment = MappingCompartment::MainMappings;
+        let mut mapping = MappingModel::new(compartment, GroupId::default(), MappingKey::random());
+        mapping
+            .name
+            .set_without_notification(self.generate_name_for_new_mapping(compartment));
+        let (control_element_type, control_element_id) = match state.control_element {
+            VirtualControlElement::Multi(id) => (VirtualControlElementType::Multi, id),
+            VirtualControlElement::Button(id) => (VirtualControlElementType::Button, id),
+        };
+        mapping
+            .source_model
+            .category
+            .set_without_notification(SourceCategory::Virtual);
+        mapping
+            .source_model
+            .control_element_type
+            .set_without_notification(control_element_type);
+        mapping
+            .source_model
+            .control_element_id
+            .set_without_notification(control_element_id);
+        mapping
+            .target_model
+            .apply_from_target(&target, self.extended_context(), compartment);
+        self.guided_mapping_state.set(None);
+        Ok(self.add_mapping(compartment, mapping))
+    }
+
+    pub fn cancel_guided_mapping(&mut self) {
+        self.guided_mapping_state.set(None);
+    }
+
     pub fn learn_many_state_changed(
         &self,
     ) -> impl LocalObservable<'static, Item = (), Err = ()> + 'static {
@@ -1379,6 +1780,50 @@ impl Session {
         self.id.set(self.instance_id.to_string());
     }
 
+    pub fn mapping_is_soloed(&self, id: QualifiedMappingId) -> bool {
+        self.soloed_mapping.get() == Some(id)
+    }
+
+    /// Returns `true` if another mapping in the given compartment is soloed.
+    pub fn mapping_is_muted_by_solo(&self, id: QualifiedMappingId) -> bool {
+        match self.soloed_mapping.get() {
+            Some(soloed_id) => soloed_id.compartment == id.compartment && soloed_id != id,
+            None => false,
+        }
+    }
+
+    pub fn soloed_mapping_changed(
+        &self,
+    ) -> impl LocalObservable<'static, Item = (), Err = ()> + 'static {
+        self.soloed_mapping.changed()
+    }
+
+    /// Solos the given mapping or - if it's soloed already - unsolos it.
+    pub fn toggle_mapping_solo(&mut self, id: QualifiedMappingId) {
+        let new_value = if self.mapping_is_soloed(id) {
+            None
+        } else {
+            Some(id)
+        };
+        self.set_soloed_mapping(new_value);
+    }
+
+    fn set_soloed_mapping(&mut self, soloed_mapping: Option<QualifiedMappingId>) {
+        self.soloed_mapping.set(soloed_mapping);
+        self.normal_main_task_sender
+            .try_send(NormalMainTask::UpdateSoloedMapping(soloed_mapping))
+            .unwrap();
+    }
+
+    /// Makes sure the other mappings don't stay muted after removing the soloed one.
+    fn unsolo_if_soloed_mapping_is_gone(&mut self) {
+        if let Some(id) = self.soloed_mapping.get() {
+            if self.find_mapping_and_index_by_qualified_id(id).is_none() {
+                self.set_soloed_mapping(None);
+            }
+        }
+    }
+
     pub fn mapping_which_learns_source_changed(
         &self,
     ) -> impl LocalObservable<'static, Item = (), Err = ()> + 'static {
@@ -1579,6 +2024,7 @@ impl Session {
 
     pub fn remove_mapping(&mut self, id: QualifiedMappingId) {
         self.mappings[id.compartment].retain(|m| m.borrow().id() != id.id);
+        self.unsolo_if_soloed_mapping_is_gone();
         self.notify_mapping_list_changed(id.compartment, None);
     }
 
@@ -1690,9 +2136,15 @@ impl Session {
             // <None> preset
             None
         };
-        self.active_controller_preset_id = id;
+        self.active_controller_preset_id = id.clone();
         self.replace_compartment(compartment, model, weak_session);
         self.compartment_is_dirty[compartment].set(false);
+        self.fire_instance_hook(InstanceHookEvent::PresetChange);
+        self.notify_activity(SessionActivity::PresetLoaded {
+            compartment,
+            preset_id: id,
+        });
+        self.request_controller_state();
         Ok(())
     }
 
@@ -1712,9 +2164,14 @@ impl Session {
             // <None> preset
             None
         };
-        self.active_main_preset_id = id;
+        self.active_main_preset_id = id.clone();
         self.replace_compartment(compartment, model, weak_session);
         self.compartment_is_dirty[compartment].set(false);
+        self.fire_instance_hook(InstanceHookEvent::PresetChange);
+        self.notify_activity(SessionActivity::PresetLoaded {
+            compartment,
+            preset_id: id,
+        });
         Ok(())
     }
 
@@ -1848,6 +2305,13 @@ impl Session {
         self.mapping_changed_subject.clone()
     }
 
+    /// Fires when something noteworthy happened in this session, see [`SessionActivity`].
+    pub fn activity(
+        &self,
+    ) -> impl LocalObservable<'static, Item = SessionActivity, Err = ()> + 'static {
+        self.activity_subject.clone()
+    }
+
     /// Fires when a parameter setting has been changed.
     pub fn parameter_settings_changed(
         &self,
@@ -1861,6 +2325,7 @@ impl Session {
         mappings: impl IntoIterator<Item = MappingModel>,
     ) {
         self.mappings[compartment] = mappings.into_iter().map(share_mapping).collect();
+        self.unsolo_if_soloed_mapping_is_gone();
     }
 
     pub fn set_groups_without_notification(
@@ -1889,6 +2354,88 @@ impl Session {
             .unwrap();
     }
 
+    pub fn controller_has_state_requests(&self) -> bool {
+        self.active_controller()
+            .map(|c| !c.state_requests().is_empty())
+            .unwrap_or(false)
+    }
+
+    /// Asks the controller to report the current positions of its control elements.
+    ///
+    /// The state request messages are defined in the active controller preset. Responses are used
+    /// to align mappings with takeover mode "Pick up" with the physical positions.
+    pub fn request_controller_state(&self) {
+        let events = match self.active_controller() {
+            None => return,
+            Some(c) => c.create_state_requests(),
+        };
+        if events.is_empty() {
+            return;
+        }
+        let sender = self.normal_main_task_sender.clone();
+        // Deferred so that the requests are sent after the mappings have been synced to the
+        // processors. Otherwise the responses would hit outdated mappings.
+        let _ = Global::task_support().do_later_in_main_thread_from_main_thread_asap(move || {
+            sender
+                .try_send(NormalMainTask::RequestControllerState(events))
+                .unwrap();
+        });
+    }
+
+    pub fn control_latency_measurement_is_running(&self) -> bool {
+        self.instance_state
+            .borrow()
+            .control_latency_measurement()
+            .is_running()
+    }
+
+    /// Starts a new control latency measurement or stops the running one and logs its results.
+    pub fn toggle_control_latency_measurement(&self) {
+        let mut instance_state = self.instance_state.borrow_mut();
+        let measurement = instance_state.control_latency_measurement_mut();
+        if measurement.is_running() {
+            measurement.stop();
+            let msg = match measurement.statistics() {
+                None => "\n# Control latency\n\nNo control input has hit a target.\n".to_string(),
+                Some(s) => {
+                    let s = s.in_millis();
+                    format!(
+                        "\n\
+                        # Control latency\n\
+                        \n\
+                        - Samples: {}\n\
+                        - Min: {:.2} ms\n\
+                        - Median: {:.2} ms\n\
+                        - Mean: {:.2} ms\n\
+                        - 95th percentile: {:.2} ms\n\
+                        - 99th percentile: {:.2} ms\n\
+                        - Max: {:.2} ms\n\
+                        - Jitter (standard deviation): {:.2} ms\n\
+                        ",
+                        s.sample_count,
+                        s.min_ms,
+                        s.median_ms,
+                        s.mean_ms,
+                        s.p95_ms,
+                        s.p99_ms,
+                        s.max_ms,
+                        s.jitter_ms,
+                    )
+                }
+            };
+            Reaper::get().show_console_msg(msg);
+        } else {
+            measurement.start();
+        }
+    }
+
+    pub fn control_latency_statistics(&self) -> Option<ControlLatencyStatistics> {
+        self.instance_state
+            .borrow()
+            .control_latency_measurement()
+            .statistics()
+    }
+
     pub fn log_debug_info(&self) {
         self.log_debug_info_internal();
         self.normal_main_task_sender
@@ -1912,6 +2459,10 @@ impl Session {
         self.instance_state.borrow().mapping_is_on(id)
     }
 
+    pub fn mapping_is_suspended(&self, id: QualifiedMappingId) -> bool {
+        self.instance_state.borrow().mapping_is_suspended(id)
+    }
+
     fn log_debug_info_internal(&self) {
         // Summary
         let msg = format!(
@@ -2039,13 +2590,16 @@ impl Session {
         AsyncNotifier::notify(&mut self.mapping_changed_subject, &compartment);
     }
 
-    fn sync_upper_floor_membership(&self) {
-        let backbone_state = BackboneState::get();
-        if self.lives_on_upper_floor.get() {
-            backbone_state.add_to_upper_floor(self.instance_id);
-        } else {
-            backbone_state.remove_from_upper_floor(&self.instance_id);
-        }
+    fn notify_activity(&mut self, activity: SessionActivity) {
+        AsyncNotifier::notify(&mut self.activity_subject, &activity);
+    }
+
+    fn sync_instance_priority(&self) {
+        self.normal_main_task_sender
+            .try_send(NormalMainTask::UpdateInstancePriority(
+                self.instance_priority.get(),
+            ))
+            .unwrap();
     }
 
     pub fn control_input(&self) -> ControlInput {
@@ -2068,12 +2622,42 @@ impl Session {
         &self.instance_state
     }
 
+    fn feedback_loop_detected(&self, event: FeedbackLoopDetectedEvent) {
+        let mapping_name =
+            match self.find_mapping_and_index_by_id(event.compartment, event.mapping_id) {
+                Some((_, m)) => m.borrow().effective_name(),
+                None => return,
+            };
+        warn!(
+            self.logger,
+            "Feedback loop detected in mapping {} ({})", mapping_name, event.compartment
+        );
+        let consequence = if event.suppressed {
+            "Ignoring its control input for a few seconds."
+        } else {
+            "Consider setting its feedback to \"Prevent echo feedback\" or turning off the echo in the controller."
+        };
+        notification::warn(format!(
+            "Mapping \"{}\" seems to receive its own feedback as control input over and over again (feedback loop). {}",
+            mapping_name, consequence
+        ));
+    }
+
+    pub fn idle_animation_settings(&self) -> IdleAnimationSettings {
+        IdleAnimationSettings {
+            animation_type: self.idle_animation_type.get(),
+            timeout: Duration::from_secs(self.idle_animation_timeout_minutes.get() as u64 * 60),
+        }
+    }
+
     fn sync_settings(&self) {
         let task = NormalMainTask::UpdateSettings {
             control_input: self.control_input(),
             feedback_output: self.feedback_output(),
             input_logging_enabled: self.input_logging_enabled.get(),
             output_logging_enabled: self.output_logging_enabled.get(),
+            idle_animation_settings: self.idle_animation_settings(),
+            feedback_loop_suppression_enabled: self.suppress_feedback_loops.get(),
         };
         self.normal_main_task_sender.try_send(task).unwrap();
         let task = NormalRealTimeTask::UpdateSettings {
@@ -2101,7 +2685,7 @@ impl Session {
             .find_group_of_mapping(m)
             .map(|g| g.borrow().create_data())
             .unwrap_or_default();
-        let main_mapping = m.create_main_mapping(group_data);
+        let main_mapping = m.create_main_mapping(group_data, &self.midi_macros());
         self.normal_main_task_sender
             .try_send(NormalMainTask::UpdateSingleMapping(
                 compartment,
@@ -2167,17 +2751,29 @@ impl Session {
 
     /// Does a full mapping sync.
     fn sync_all_mappings_full(&self, compartment: MappingCompartment) {
-        let main_mappings = self.create_main_mappings(compartment);
+        let midi_macros = self.midi_macros();
+        let main_mappings = self.create_main_mappings(compartment, &midi_macros);
+        self.instance_state
+            .borrow_mut()
+            .set_midi_macros(midi_macros);
         self.normal_main_task_sender
             .try_send(NormalMainTask::UpdateAllMappings(
                 compartment,
                 main_mappings,
             ))
             .unwrap();
+        // Mapping keys and MIDI macros referred to by the hooks might have changed.
+        self.sync_instance_hooks();
+        // Parameter settings might have been replaced together with the mappings.
+        self.sync_parameter_linking();
     }
 
     /// Creates mappings from mapping models so they can be distributed to different processors.
-    fn create_main_mappings(&self, compartment: MappingCompartment) -> Vec<MainMapping> {
+    fn create_main_mappings(
+        &self,
+        compartment: MappingCompartment,
+        midi_macros: &MidiMacros,
+    ) -> Vec<MainMapping> {
         let group_map: HashMap<GroupId, Ref<GroupModel>> = self
             .groups_including_default_group(compartment)
             .map(|group| {
@@ -2198,11 +2794,19 @@ impl Session {
                     .get(mapping.group_id.get_ref())
                     .map(|g| g.create_data())
                     .unwrap_or_default();
-                mapping.create_main_mapping(group_data)
+                mapping.create_main_mapping(group_data, midi_macros)
             })
             .collect()
     }
 
+    /// The MIDI macros are defined in the active controller preset but can be used by mappings
+    /// of both compartments.
+    fn midi_macros(&self) -> MidiMacros {
+        self.active_controller()
+            .map(|c| c.create_midi_macros())
+            .unwrap_or_default()
+    }
+
     fn generate_name_for_new_mapping(&self, compartment: MappingCompartment) -> String {
         format!("{}", self.mappings[compartment].len() + 1)
     }
@@ -2246,11 +2850,14 @@ pub struct ParameterSetting {
     pub key: Option<String>,
     #[serde(default, skip_serializing_if = "is_default")]
     pub name: String,
+    /// Lets this parameter follow a parameter of another instance.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub link: Option<ParameterLink>,
 }
 
 impl ParameterSetting {
     pub fn is_default(&self) -> bool {
-        self.name.is_empty()
+        self.name.is_empty() && self.link.is_none()
     }
 
     pub fn key_matches(&self, key: &str) -> bool {
@@ -2297,7 +2904,7 @@ impl DomainEventHandler for WeakSession {
                 // tempo notification, https://github.com/helgoboss/realearn/issues/199). If the
                 // target value slider is not updated then ... so what.
                 if let Ok(s) = session.try_borrow() {
-                    s.ui.target_value_changed(e);
+                    s.ui.target_value_changed(&s, e);
                 }
             }
             UpdatedParameter { index, value } => {
@@ -2332,6 +2939,27 @@ impl DomainEventHandler for WeakSession {
                     }
                 }
             }
+            MappingModeChangeRequested(event) => {
+                if let Ok(s) = session.try_borrow() {
+                    if let Some((_, m)) =
+                        s.find_mapping_and_index_by_id(event.compartment, event.mapping_id)
+                    {
+                        m.borrow_mut()
+                            .mode_model
+                            .adjust_setting(event.setting, event.value);
+                    }
+                }
+            }
+            FeedbackLoopDetected(event) => {
+                if let Ok(s) = session.try_borrow() {
+                    s.feedback_loop_detected(event);
+                }
+            }
+            RecordingStarted => {
+                session
+                    .borrow_mut()
+                    .notify_activity(SessionActivity::RecordingStarted);
+            }
         }
     }
 }
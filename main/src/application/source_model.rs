@@ -1,8 +1,9 @@
 use crate::base::{prop, Prop};
 use crate::domain::{
-    CompoundMappingSource, EelMidiSourceScript, ExtendedSourceCharacter, MappingCompartment,
-    MidiSource, ReaperSource, VirtualControlElement, VirtualControlElementId, VirtualSource,
-    VirtualTarget,
+    extract_sysex_checksum, CompoundMappingSource, EelMidiSourceScript, ExtendedSourceCharacter,
+    GameControllerElement, GameControllerSource, InstanceMessageSource, MappingCompartment,
+    MidiSource, MmcCommand, MmcSource, OscArgFilter, OscDeltaMode, OscDeltaSettings, ReaperSource,
+    SysExChecksum, VirtualControlElement, VirtualControlElementId, VirtualSource, VirtualTarget,
 };
 use derive_more::Display;
 use enum_iterator::IntoEnumIterator;
@@ -13,6 +14,7 @@ use helgoboss_learn::{
 };
 use helgoboss_midi::{Channel, U14, U7};
 use num_enum::{IntoPrimitive, TryFromPrimitive};
+use reaper_medium::{MidiInputDeviceId, MidiOutputDeviceId};
 use rxrust::prelude::*;
 use serde::{Deserialize, Serialize};
 use serde_repr::*;
@@ -39,13 +41,28 @@ pub struct SourceModel {
     pub display_type: Prop<DisplayType>,
     pub display_id: Prop<Option<u8>>,
     pub line: Prop<Option<u8>>,
+    /// `None` means the MIDI control input of the instance.
+    pub midi_input_device: Prop<Option<MidiInputDeviceId>>,
+    /// `None` means the MIDI feedback output of the instance.
+    pub midi_output_device: Prop<Option<MidiOutputDeviceId>>,
     // OSC
     pub osc_address_pattern: Prop<String>,
     pub osc_arg_index: Prop<Option<u32>>,
     pub osc_arg_type_tag: Prop<OscTypeTag>,
     pub osc_arg_is_relative: Prop<bool>,
+    pub osc_delta_mode: Prop<OscDeltaMode>,
+    pub osc_feedback_is_relative: Prop<bool>,
+    /// Additional arguments which must have certain values in order to let a message through.
+    pub osc_arg_filters: Prop<Vec<OscArgFilter>>,
     // REAPER
     pub reaper_source_type: Prop<ReaperSourceType>,
+    /// `None` means any controller.
+    pub game_controller_index: Prop<Option<u32>>,
+    pub game_controller_element: Prop<GameControllerElement>,
+    pub instance_message_name: Prop<String>,
+    pub mmc_command: Prop<MmcCommand>,
+    /// `None` means any device.
+    pub mmc_device_id: Prop<Option<u8>>,
     // Virtual
     pub control_element_type: Prop<VirtualControlElementType>,
     pub control_element_id: Prop<VirtualControlElementId>,
@@ -70,11 +87,21 @@ impl Default for SourceModel {
             display_type: prop(Default::default()),
             display_id: prop(Default::default()),
             line: prop(None),
+            midi_input_device: prop(None),
+            midi_output_device: prop(None),
             osc_address_pattern: prop("".to_owned()),
             osc_arg_index: prop(Some(0)),
             osc_arg_type_tag: prop(Default::default()),
             osc_arg_is_relative: prop(false),
+            osc_delta_mode: prop(Default::default()),
+            osc_feedback_is_relative: prop(false),
+            osc_arg_filters: prop(vec![]),
             reaper_source_type: prop(Default::default()),
+            game_controller_index: prop(None),
+            game_controller_element: prop(Default::default()),
+            instance_message_name: prop("".to_owned()),
+            mmc_command: prop(Default::default()),
+            mmc_device_id: prop(None),
         }
     }
 }
@@ -97,12 +124,23 @@ impl SourceModel {
             .merge(self.display_type.changed())
             .merge(self.display_id.changed())
             .merge(self.line.changed())
+            .merge(self.midi_input_device.changed())
+            .merge(self.midi_output_device.changed())
             .merge(self.control_element_type.changed())
             .merge(self.control_element_id.changed())
             .merge(self.osc_address_pattern.changed())
             .merge(self.osc_arg_index.changed())
             .merge(self.osc_arg_type_tag.changed())
             .merge(self.osc_arg_is_relative.changed())
+            .merge(self.osc_delta_mode.changed())
+            .merge(self.osc_feedback_is_relative.changed())
+            .merge(self.osc_arg_filters.changed())
+            .merge(self.reaper_source_type.changed())
+            .merge(self.game_controller_index.changed())
+            .merge(self.game_controller_element.changed())
+            .merge(self.instance_message_name.changed())
+            .merge(self.mmc_command.changed())
+            .merge(self.mmc_device_id.changed())
     }
 
     pub fn supports_control(&self) -> bool {
@@ -205,6 +243,20 @@ impl SourceModel {
                 self.category.set(SourceCategory::Reaper);
                 self.reaper_source_type
                     .set(ReaperSourceType::from_source(s));
+                match s {
+                    ReaperSource::GameController(s) => {
+                        self.game_controller_index.set(s.controller_index);
+                        self.game_controller_element.set(s.element);
+                    }
+                    ReaperSource::InstanceMessage(s) => {
+                        self.instance_message_name.set(s.name.clone());
+                    }
+                    ReaperSource::Mmc(s) => {
+                        self.mmc_command.set(s.command);
+                        self.mmc_device_id.set(s.device_id);
+                    }
+                    _ => {}
+                }
             }
             Never => {
                 self.category.set(SourceCategory::Never);
@@ -306,7 +358,10 @@ impl SourceModel {
                         message: self.midi_clock_transport_message.get(),
                     },
                     Raw => MidiSource::Raw {
-                        pattern: self.raw_midi_pattern.get_ref().parse().unwrap_or_default(),
+                        pattern: extract_sysex_checksum(self.raw_midi_pattern.get_ref())
+                            .ok()
+                            .and_then(|(pattern, _)| pattern.parse().ok())
+                            .unwrap_or_default(),
                         custom_character: self.custom_character.get(),
                     },
                     Script => MidiSource::Script {
@@ -334,6 +389,17 @@ impl SourceModel {
                 let reaper_source = match self.reaper_source_type.get() {
                     MidiDeviceChanges => ReaperSource::MidiDeviceChanges,
                     RealearnInstanceStart => ReaperSource::RealearnInstanceStart,
+                    GameController => ReaperSource::GameController(GameControllerSource {
+                        controller_index: self.game_controller_index.get(),
+                        element: self.game_controller_element.get(),
+                    }),
+                    InstanceMessage => ReaperSource::InstanceMessage(InstanceMessageSource {
+                        name: self.instance_message_name.get_ref().clone(),
+                    }),
+                    Mmc => ReaperSource::Mmc(MmcSource {
+                        command: self.mmc_command.get(),
+                        device_id: self.mmc_device_id.get(),
+                    }),
                 };
                 CompoundMappingSource::Reaper(reaper_source)
             }
@@ -374,6 +440,59 @@ impl SourceModel {
             .unwrap_or_default()
     }
 
+    /// Returns `None` if this is not a relative OSC source.
+    pub fn create_osc_delta_settings(&self) -> Option<OscDeltaSettings> {
+        if self.category.get() != SourceCategory::Osc || !self.osc_arg_is_relative.get() {
+            return None;
+        }
+        let settings = OscDeltaSettings {
+            mode: self.osc_delta_mode.get(),
+            relative_feedback: self.osc_feedback_is_relative.get(),
+        };
+        Some(settings)
+    }
+
+    /// Returns an empty list if this is not an OSC source.
+    pub fn create_osc_arg_filters(&self) -> Vec<OscArgFilter> {
+        if self.category.get() != SourceCategory::Osc {
+            return vec![];
+        }
+        self.osc_arg_filters.get_ref().clone()
+    }
+
+    /// Returns `None` if this is not a MIDI source or if it listens to the instance's control input.
+    pub fn create_midi_input_device(&self) -> Option<MidiInputDeviceId> {
+        if self.category.get() != SourceCategory::Midi {
+            return None;
+        }
+        self.midi_input_device.get()
+    }
+
+    /// Returns `None` if this is not a MIDI source or if it sends feedback to the instance's
+    /// feedback output.
+    ///
+    /// The given group device is used if the source itself doesn't override the output device.
+    pub fn create_midi_output_device(
+        &self,
+        group_device: Option<MidiOutputDeviceId>,
+    ) -> Option<MidiOutputDeviceId> {
+        if self.category.get() != SourceCategory::Midi {
+            return None;
+        }
+        self.midi_output_device.get().or(group_device)
+    }
+
+    /// Returns `None` if this is not a raw MIDI source with a checksum in its pattern.
+    pub fn create_sysex_checksum(&self) -> Option<SysExChecksum> {
+        if self.category.get() != SourceCategory::Midi
+            || self.midi_source_type.get() != MidiSourceType::Raw
+        {
+            return None;
+        }
+        let (_, checksum) = extract_sysex_checksum(self.raw_midi_pattern.get_ref()).ok()?;
+        checksum
+    }
+
     fn osc_arg_descriptor(&self) -> Option<OscArgDescriptor> {
         let arg_index = self.osc_arg_index.get()?;
         Some(OscArgDescriptor::new(
@@ -517,9 +636,23 @@ impl Display for SourceModel {
                 self.create_control_element().to_string().into(),
             ],
             Osc => vec!["OSC".into(), self.osc_address_pattern.get_ref().into()],
-            Reaper => {
-                vec![self.reaper_source_type.get().to_string().into()]
-            }
+            Reaper => match self.reaper_source_type.get() {
+                ReaperSourceType::GameController => vec![
+                    self.game_controller_element.get().to_string().into(),
+                    match self.game_controller_index.get() {
+                        None => "Any controller".into(),
+                        Some(i) => format!("Controller {}", i).into(),
+                    },
+                ],
+                ReaperSourceType::InstanceMessage => vec![
+                    "Instance message".into(),
+                    self.instance_message_name.get_ref().into(),
+                ],
+                ReaperSourceType::Mmc => {
+                    vec!["MMC".into(), self.mmc_command.get().to_string().into()]
+                }
+                t => vec![t.to_string().into()],
+            },
             Never => vec!["None".into()],
         };
         let non_empty_lines: Vec<_> = lines.into_iter().filter(|l| !l.is_empty()).collect();
@@ -789,6 +922,15 @@ pub enum ReaperSourceType {
     #[serde(rename = "realearn-instance-start")]
     #[display(fmt = "ReaLearn instance start")]
     RealearnInstanceStart,
+    #[serde(rename = "game-controller")]
+    #[display(fmt = "Game controller")]
+    GameController,
+    #[serde(rename = "instance-message")]
+    #[display(fmt = "Instance message")]
+    InstanceMessage,
+    #[serde(rename = "mmc")]
+    #[display(fmt = "MIDI Machine Control")]
+    Mmc,
 }
 
 impl Default for ReaperSourceType {
@@ -803,6 +945,9 @@ impl ReaperSourceType {
         match source {
             MidiDeviceChanges => Self::MidiDeviceChanges,
             RealearnInstanceStart => Self::RealearnInstanceStart,
+            GameController(_) => Self::GameController,
+            InstanceMessage(_) => Self::InstanceMessage,
+            Mmc(_) => Self::Mmc,
         }
     }
 }
@@ -1,8 +1,8 @@
 use crate::base::default_util::is_default;
-use crate::base::{prop, Prop};
+use crate::base::{prop, ErrorContext, Prop, RealearnError};
 use derive_more::Display;
 use enum_iterator::IntoEnumIterator;
-use helgoboss_learn::{ControlType, OscArgDescriptor, OscTypeTag, Target};
+use helgoboss_learn::{ControlType, OscArgDescriptor, OscTypeTag, Target, UnitValue};
 use num_enum::{IntoPrimitive, TryFromPrimitive};
 use reaper_high::{
     Action, BookmarkType, Fx, FxParameter, Guid, Project, Track, TrackRoute, TrackRoutePartner,
@@ -14,44 +14,60 @@ use serde::{Deserialize, Serialize};
 use crate::application::VirtualControlElementType;
 use crate::domain::{
     find_bookmark, get_fx_param, get_fxs, get_non_present_virtual_route_label,
-    get_non_present_virtual_track_label, get_track_route, ActionInvocationType, AnyOnParameter,
-    CompoundMappingTarget, Exclusivity, ExpressionEvaluator, ExtendedProcessorContext,
-    FeedbackResolution, FxDescriptor, FxDisplayType, FxParameterDescriptor, GroupId,
-    MappingCompartment, OscDeviceId, ProcessorContext, RealearnTarget, ReaperTarget,
-    ReaperTargetType, SeekOptions, SendMidiDestination, SlotPlayOptions, SoloBehavior, Tag,
-    TagScope, TouchedParameterType, TrackDescriptor, TrackExclusivity, TrackRouteDescriptor,
-    TrackRouteSelector, TrackRouteType, TransportAction, UnresolvedActionTarget,
-    UnresolvedAllTrackFxEnableTarget, UnresolvedAnyOnTarget,
-    UnresolvedAutomationModeOverrideTarget, UnresolvedAutomationTouchStateTarget,
-    UnresolvedClipSeekTarget, UnresolvedClipTransportTarget, UnresolvedClipVolumeTarget,
-    UnresolvedCompoundMappingTarget, UnresolvedEnableInstancesTarget,
-    UnresolvedEnableMappingsTarget, UnresolvedFxEnableTarget, UnresolvedFxNavigateTarget,
-    UnresolvedFxOpenTarget, UnresolvedFxParameterTarget, UnresolvedFxPresetTarget,
-    UnresolvedGoToBookmarkTarget, UnresolvedLastTouchedTarget, UnresolvedLoadFxSnapshotTarget,
-    UnresolvedLoadMappingSnapshotTarget, UnresolvedMidiSendTarget,
-    UnresolvedNavigateWithinGroupTarget, UnresolvedOscSendTarget, UnresolvedPlayrateTarget,
+    get_non_present_virtual_track_label, get_track_route, resolve_clip_slot_index,
+    ActionInvocationType, AnyOnParameter, ArpeggiatorSettings, BookmarkNavigationMode, ChordShape,
+    ClipMatrixId, ClipSlotAddress, CompoundMappingTarget, DualPanComponent, Exclusivity,
+    ExpressionEvaluator, ExtStateValueFormat, ExtendedProcessorContext, FeedbackResolution,
+    FxDescriptor, FxDisplayType, FxParameterDescriptor, GroupEnableMode, GroupId,
+    InstanceMessageRecipients, LayerId, MappingCompartment, MappingModeSetting, MetronomeParameter,
+    MtcFrameRate, OscDeviceId, ProcessorContext, ProgramChangePatch, PunchAction, RealearnTarget,
+    ReaperTarget, ReaperTargetType, RecordInputKind, SeekOptions, SendMidiDestination,
+    SharedInstanceState, SlotPlayOptions, SlotStopQuantization, SoloBehavior, Tag, TagScope,
+    TimeRangeKind, TimeSelectionAction, TouchedParameterType, TrackDescriptor, TrackExclusivity,
+    TrackRouteDescriptor, TrackRouteSelector, TrackRouteType, TransportAction, UndoRedoAction,
+    UnresolvedActionTarget, UnresolvedAdjustMappingModeTarget, UnresolvedAllTrackFxEnableTarget,
+    UnresolvedAnyOnTarget, UnresolvedAutomationModeOverrideTarget,
+    UnresolvedAutomationTouchStateTarget, UnresolvedClipColumnStopTarget, UnresolvedClipCueTarget,
+    UnresolvedClipSceneLaunchTarget, UnresolvedClipSeekTarget, UnresolvedClipTransportTarget,
+    UnresolvedClipVolumeTarget, UnresolvedCompoundMappingTarget, UnresolvedEnableGroupTarget,
+    UnresolvedEnableInstancesTarget, UnresolvedEnableMappingsTarget, UnresolvedFxEnableTarget,
+    UnresolvedFxNavigateTarget, UnresolvedFxOpenTarget, UnresolvedFxParameterBankTarget,
+    UnresolvedFxParameterTarget, UnresolvedFxPresetTarget, UnresolvedGoToBookmarkTarget,
+    UnresolvedInvokeInstanceTarget, UnresolvedLastTouchedTarget, UnresolvedLoadFxSnapshotTarget,
+    UnresolvedLoadMappingSnapshotTarget, UnresolvedMetronomeTarget, UnresolvedMidiClockSendTarget,
+    UnresolvedMidiMacroSendTarget, UnresolvedMidiSendTarget, UnresolvedMonitoringVolumeTarget,
+    UnresolvedMultiTarget, UnresolvedMultiTargetChild, UnresolvedNavigateBookmarksTarget,
+    UnresolvedNavigateWithinGroupTarget, UnresolvedOscSendTarget, UnresolvedPanicTarget,
+    UnresolvedPlayrateTarget, UnresolvedProgramChangeSendTarget, UnresolvedPunchTarget,
     UnresolvedReaperTarget, UnresolvedRouteAutomationModeTarget, UnresolvedRouteMonoTarget,
     UnresolvedRouteMuteTarget, UnresolvedRoutePanTarget, UnresolvedRoutePhaseTarget,
-    UnresolvedRouteVolumeTarget, UnresolvedSeekTarget, UnresolvedSelectedTrackTarget,
-    UnresolvedTempoTarget, UnresolvedTrackArmTarget, UnresolvedTrackAutomationModeTarget,
-    UnresolvedTrackMuteTarget, UnresolvedTrackPanTarget, UnresolvedTrackPeakTarget,
-    UnresolvedTrackPhaseTarget, UnresolvedTrackSelectionTarget, UnresolvedTrackShowTarget,
+    UnresolvedRouteVolumeTarget, UnresolvedRunScriptTarget, UnresolvedSaveMappingSnapshotTarget,
+    UnresolvedSeekTarget, UnresolvedSelectLayerTarget, UnresolvedSelectedTrackTarget,
+    UnresolvedSetBankOffsetTarget, UnresolvedSetExtStateTarget, UnresolvedTempoTarget,
+    UnresolvedTimeSelectionTarget, UnresolvedTrackArmTarget, UnresolvedTrackAutomationModeTarget,
+    UnresolvedTrackCueTarget, UnresolvedTrackDeleteTarget, UnresolvedTrackDualPanTarget,
+    UnresolvedTrackDuplicateTarget, UnresolvedTrackFolderSpillTarget,
+    UnresolvedTrackInputMonitoringTarget, UnresolvedTrackInsertTarget, UnresolvedTrackMuteTarget,
+    UnresolvedTrackPanTarget, UnresolvedTrackPeakTarget, UnresolvedTrackPhaseTarget,
+    UnresolvedTrackRecordInputTarget, UnresolvedTrackSelectionTarget, UnresolvedTrackShowTarget,
     UnresolvedTrackSoloTarget, UnresolvedTrackToolTarget, UnresolvedTrackVolumeTarget,
-    UnresolvedTrackWidthTarget, UnresolvedTransportTarget, VirtualChainFx, VirtualControlElement,
-    VirtualControlElementId, VirtualFx, VirtualFxParameter, VirtualTarget, VirtualTrack,
-    VirtualTrackRoute,
+    UnresolvedTrackWidthTarget, UnresolvedTransportTarget, UnresolvedUndoRedoTarget,
+    VirtualChainFx, VirtualControlElement, VirtualControlElementId, VirtualFx, VirtualFxParameter,
+    VirtualTarget, VirtualTrack, VirtualTrackRoute, DEFAULT_FX_PARAMETER_BANK_SIZE,
 };
 use serde_repr::*;
 use std::borrow::Cow;
 use std::error::Error;
 
 use reaper_medium::{
-    AutomationMode, BookmarkId, GlobalAutomationModeOverride, TrackArea, TrackLocation,
-    TrackSendDirection,
+    AutomationMode, BookmarkId, GlobalAutomationModeOverride, InputMonitoringMode,
+    MidiOutputDeviceId, TrackArea, TrackLocation, TrackSendDirection,
 };
 use std::fmt;
 use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
+use std::time::Duration;
 use wildmatch::WildMatch;
 
 /// A model for creating targets
@@ -100,10 +116,23 @@ pub struct TargetModel {
     pub route_expression: Prop<String>,
     // # For track solo targets
     pub solo_behavior: Prop<SoloBehavior>,
+    // # For track dual pan target
+    pub dual_pan_component: Prop<DualPanComponent>,
     // # For toggleable track targets
     pub track_exclusivity: Prop<TrackExclusivity>,
     // # For transport target
     pub transport_action: Prop<TransportAction>,
+    // # For punch target
+    pub punch_action: Prop<PunchAction>,
+    // # For undo/redo target
+    pub undo_redo_action: Prop<UndoRedoAction>,
+    // # For time selection target
+    pub time_selection_action: Prop<TimeSelectionAction>,
+    pub time_range_kind: Prop<TimeRangeKind>,
+    // # For "Navigate bookmarks" target
+    pub bookmark_navigation_mode: Prop<BookmarkNavigationMode>,
+    // # For metronome target
+    pub metronome_parameter: Prop<MetronomeParameter>,
     // # For any-on target
     pub any_on_parameter: Prop<AnyOnParameter>,
     // # For "Load FX snapshot" target
@@ -125,8 +154,12 @@ pub struct TargetModel {
     pub feedback_resolution: Prop<FeedbackResolution>,
     // # For track show target
     pub track_area: Prop<RealearnTrackArea>,
+    // # For track delete target
+    pub require_confirmation: Prop<bool>,
     // # For track and route automation mode target
     pub automation_mode: Prop<RealearnAutomationMode>,
+    // # For track input monitoring target
+    pub input_monitoring_mode: Prop<RealearnInputMonitoringMode>,
     // # For automation mode override target
     pub automation_mode_override_type: Prop<AutomationModeOverrideType>,
     // # For FX Open and FX Navigate target
@@ -137,21 +170,67 @@ pub struct TargetModel {
     // # For Send MIDI target
     pub raw_midi_pattern: Prop<String>,
     pub send_midi_destination: Prop<SendMidiDestination>,
+    pub midi_chord: Prop<Option<ChordShape>>,
+    pub midi_arpeggiator: Prop<Option<ArpeggiatorSettings>>,
+    // # For Send MIDI macro target
+    pub midi_macro_name: Prop<String>,
+    // # For Send program change target
+    /// `None` means feedback output.
+    pub midi_output_device_id: Prop<Option<MidiOutputDeviceId>>,
+    /// 0-based.
+    pub midi_channel: Prop<u8>,
+    pub program_change_patches: Prop<Vec<ProgramChangePatch>>,
+    // # For Send MIDI clock target (also uses `midi_output_device_id`)
+    /// `None` means no MIDI time code.
+    pub midi_clock_mtc_frame_rate: Prop<Option<MtcFrameRate>>,
     // # For Send OSC target
     pub osc_address_pattern: Prop<String>,
     pub osc_arg_index: Prop<Option<u32>>,
     pub osc_arg_type_tag: Prop<OscTypeTag>,
     pub osc_dev_id: Prop<Option<OscDeviceId>>,
+    // # For "Set ext state" target (also uses `feedback_resolution`)
+    pub ext_state_section: Prop<String>,
+    pub ext_state_key: Prop<String>,
+    pub ext_state_format: Prop<ExtStateValueFormat>,
+    // # For "Run script" target
+    pub script_path: Prop<String>,
     // # For clip targets
-    pub slot_index: Prop<usize>,
+    pub clip_matrix_id: Prop<Option<ClipMatrixId>>,
+    /// Zero-based.
+    pub slot_row: Prop<usize>,
+    /// Zero-based.
+    pub slot_column: Prop<usize>,
     pub next_bar: Prop<bool>,
     pub buffered: Prop<bool>,
+    /// `None` means the instance-level default is used.
+    pub stop_quantization: Prop<Option<SlotStopQuantization>>,
+    // # For cue targets
+    pub cue_output: Prop<u32>,
+    // # For track record input target
+    pub record_input_kind: Prop<RecordInputKind>,
     // # For targets that might have to be polled in order to get automatic feedback in all cases.
     pub poll_for_feedback: Prop<bool>,
     pub tags: Prop<Vec<Tag>>,
     pub exclusivity: Prop<Exclusivity>,
     pub group_id: Prop<GroupId>,
+    pub group_enable_mode: Prop<GroupEnableMode>,
+    pub layer: Prop<LayerId>,
+    pub bank_offset: Prop<u32>,
+    // # For "FX parameter bank" target
+    pub fx_parameter_bank_size: Prop<u32>,
     pub active_mappings_only: Prop<bool>,
+    // # For mapping snapshot targets
+    pub mapping_snapshot_id: Prop<String>,
+    pub mapping_snapshot_fade_time: Prop<Duration>,
+    // # For "Adjust mapping mode" target
+    pub mapping_key: Prop<String>,
+    pub mapping_mode_setting: Prop<MappingModeSetting>,
+    // # For "Multi" target
+    pub multi_target_children: Prop<Vec<MultiTargetChildModel>>,
+    // # For "Invoke instance" target (also uses `tags`)
+    pub instance_message_name: Prop<String>,
+    /// If not empty, the message goes to this session only (tags are ignored).
+    pub instance_session_id: Prop<String>,
 }
 
 impl Default for TargetModel {
@@ -189,8 +268,15 @@ impl Default for TargetModel {
             route_name: prop(Default::default()),
             route_expression: prop(Default::default()),
             solo_behavior: prop(Default::default()),
+            dual_pan_component: prop(Default::default()),
             track_exclusivity: prop(Default::default()),
             transport_action: prop(TransportAction::default()),
+            punch_action: prop(PunchAction::default()),
+            undo_redo_action: prop(UndoRedoAction::default()),
+            time_selection_action: prop(TimeSelectionAction::default()),
+            time_range_kind: prop(TimeRangeKind::default()),
+            bookmark_navigation_mode: prop(BookmarkNavigationMode::default()),
+            metronome_parameter: prop(MetronomeParameter::default()),
             any_on_parameter: prop(AnyOnParameter::default()),
             fx_snapshot: prop(None),
             touched_parameter_type: prop(Default::default()),
@@ -205,25 +291,54 @@ impl Default for TargetModel {
             seek_play: prop(true),
             feedback_resolution: prop(Default::default()),
             track_area: prop(Default::default()),
+            require_confirmation: prop(true),
             automation_mode: prop(Default::default()),
+            input_monitoring_mode: prop(Default::default()),
             automation_mode_override_type: prop(Default::default()),
             fx_display_type: prop(Default::default()),
             scroll_arrange_view: prop(false),
             scroll_mixer: prop(false),
             raw_midi_pattern: prop(Default::default()),
             send_midi_destination: prop(Default::default()),
+            midi_chord: prop(None),
+            midi_arpeggiator: prop(None),
+            midi_macro_name: prop("".to_owned()),
+            midi_output_device_id: prop(None),
+            midi_channel: prop(0),
+            program_change_patches: prop(vec![]),
+            midi_clock_mtc_frame_rate: prop(None),
             osc_address_pattern: prop("".to_owned()),
             osc_arg_index: prop(Some(0)),
             osc_arg_type_tag: prop(Default::default()),
             osc_dev_id: prop(None),
-            slot_index: prop(0),
+            ext_state_section: prop("".to_owned()),
+            ext_state_key: prop("".to_owned()),
+            ext_state_format: prop(Default::default()),
+            script_path: prop("".to_owned()),
+            clip_matrix_id: prop(None),
+            slot_row: prop(0),
+            slot_column: prop(0),
             next_bar: prop(false),
             buffered: prop(false),
+            stop_quantization: prop(None),
+            cue_output: prop(0),
+            record_input_kind: prop(Default::default()),
             poll_for_feedback: prop(true),
             tags: prop(Default::default()),
             exclusivity: prop(Default::default()),
             group_id: prop(Default::default()),
+            group_enable_mode: prop(Default::default()),
+            layer: prop(Default::default()),
+            bank_offset: prop(0),
+            fx_parameter_bank_size: prop(DEFAULT_FX_PARAMETER_BANK_SIZE),
             active_mappings_only: prop(false),
+            mapping_snapshot_id: prop("".to_owned()),
+            mapping_snapshot_fade_time: prop(Duration::from_millis(0)),
+            mapping_key: prop("".to_owned()),
+            mapping_mode_setting: prop(Default::default()),
+            multi_target_children: prop(vec![]),
+            instance_message_name: prop("".to_owned()),
+            instance_session_id: prop("".to_owned()),
         }
     }
 }
@@ -683,17 +798,41 @@ impl TargetModel {
             Transport(t) => {
                 self.transport_action.set(t.action);
             }
+            Punch(t) => {
+                self.punch_action.set(t.action);
+            }
+            UndoRedo(t) => {
+                self.undo_redo_action.set(t.action);
+            }
+            TimeSelection(t) => {
+                self.time_selection_action.set(t.action);
+                self.time_range_kind.set(t.range_kind);
+            }
+            Metronome(t) => {
+                self.metronome_parameter.set(t.parameter);
+            }
             TrackSolo(t) => {
                 self.solo_behavior.set(t.behavior);
             }
+            TrackDualPan(t) => {
+                self.dual_pan_component.set(t.component);
+            }
             GoToBookmark(t) => {
                 self.bookmark_ref.set(t.index);
                 self.bookmark_type.set(t.bookmark_type);
             }
+            NavigateBookmarks(t) => {
+                self.bookmark_type.set(t.bookmark_type);
+                self.bookmark_navigation_mode.set(t.mode);
+            }
             TrackAutomationMode(t) => {
                 self.automation_mode
                     .set(RealearnAutomationMode::from_reaper(t.mode));
             }
+            TrackInputMonitoring(t) => {
+                self.input_monitoring_mode
+                    .set(RealearnInputMonitoringMode::from_reaper(t.mode));
+            }
             TrackRouteAutomationMode(t) => {
                 self.automation_mode
                     .set(RealearnAutomationMode::from_reaper(t.mode));
@@ -751,8 +890,15 @@ impl TargetModel {
             .merge(self.route_name.changed())
             .merge(self.route_expression.changed())
             .merge(self.solo_behavior.changed())
+            .merge(self.dual_pan_component.changed())
             .merge(self.track_exclusivity.changed())
             .merge(self.transport_action.changed())
+            .merge(self.punch_action.changed())
+            .merge(self.undo_redo_action.changed())
+            .merge(self.time_selection_action.changed())
+            .merge(self.time_range_kind.changed())
+            .merge(self.bookmark_navigation_mode.changed())
+            .merge(self.metronome_parameter.changed())
             .merge(self.any_on_parameter.changed())
             .merge(self.control_element_type.changed())
             .merge(self.control_element_id.changed())
@@ -769,25 +915,63 @@ impl TargetModel {
             .merge(self.seek_play.changed())
             .merge(self.feedback_resolution.changed())
             .merge(self.track_area.changed())
+            .merge(self.require_confirmation.changed())
             .merge(self.automation_mode.changed())
+            .merge(self.input_monitoring_mode.changed())
             .merge(self.automation_mode_override_type.changed())
             .merge(self.fx_display_type.changed())
             .merge(self.scroll_arrange_view.changed())
             .merge(self.scroll_mixer.changed())
             .merge(self.raw_midi_pattern.changed())
             .merge(self.send_midi_destination.changed())
+            .merge(self.midi_chord.changed())
+            .merge(self.midi_arpeggiator.changed())
+            .merge(self.midi_macro_name.changed())
+            .merge(self.midi_output_device_id.changed())
+            .merge(self.midi_channel.changed())
+            .merge(self.program_change_patches.changed())
+            .merge(self.midi_clock_mtc_frame_rate.changed())
             .merge(self.osc_address_pattern.changed())
             .merge(self.osc_arg_index.changed())
             .merge(self.osc_arg_type_tag.changed())
             .merge(self.osc_dev_id.changed())
-            .merge(self.slot_index.changed())
+            .merge(self.ext_state_section.changed())
+            .merge(self.ext_state_key.changed())
+            .merge(self.ext_state_format.changed())
+            .merge(self.script_path.changed())
+            .merge(self.clip_matrix_id.changed())
+            .merge(self.slot_row.changed())
+            .merge(self.slot_column.changed())
             .merge(self.next_bar.changed())
             .merge(self.buffered.changed())
+            .merge(self.stop_quantization.changed())
+            .merge(self.cue_output.changed())
+            .merge(self.record_input_kind.changed())
             .merge(self.poll_for_feedback.changed())
             .merge(self.tags.changed())
             .merge(self.exclusivity.changed())
             .merge(self.group_id.changed())
+            .merge(self.group_enable_mode.changed())
+            .merge(self.layer.changed())
+            .merge(self.bank_offset.changed())
+            .merge(self.fx_parameter_bank_size.changed())
             .merge(self.active_mappings_only.changed())
+            .merge(self.mapping_snapshot_id.changed())
+            .merge(self.mapping_snapshot_fade_time.changed())
+            .merge(self.mapping_key.changed())
+            .merge(self.mapping_mode_setting.changed())
+            .merge(self.multi_target_children.changed())
+            .merge(self.instance_message_name.changed())
+            .merge(self.instance_session_id.changed())
+    }
+
+    fn mapping_snapshot_label(&self) -> &str {
+        let id = self.mapping_snapshot_id.get_ref();
+        if id.is_empty() {
+            "Initial"
+        } else {
+            id
+        }
     }
 
     pub fn virtual_track(&self) -> Option<VirtualTrack> {
@@ -979,9 +1163,21 @@ impl TargetModel {
         Ok(desc)
     }
 
+    /// # Errors
+    ///
+    /// Returns an [`ErrorCode::InvalidInput`](crate::base::ErrorCode::InvalidInput) error if the
+    /// model doesn't contain enough information to describe a target.
     pub fn create_target(
         &self,
         compartment: MappingCompartment,
+    ) -> Result<UnresolvedCompoundMappingTarget, RealearnError> {
+        self.create_target_internal(compartment)
+            .map_err(|e| RealearnError::invalid_input(e).context("creating target"))
+    }
+
+    fn create_target_internal(
+        &self,
+        compartment: MappingCompartment,
     ) -> Result<UnresolvedCompoundMappingTarget, &'static str> {
         use TargetCategory::*;
         match self.category.get() {
@@ -1022,6 +1218,12 @@ impl TargetModel {
                     TrackPan => UnresolvedReaperTarget::TrackPan(UnresolvedTrackPanTarget {
                         track_descriptor: self.track_descriptor()?,
                     }),
+                    TrackDualPan => {
+                        UnresolvedReaperTarget::TrackDualPan(UnresolvedTrackDualPanTarget {
+                            track_descriptor: self.track_descriptor()?,
+                            component: self.dual_pan_component.get(),
+                        })
+                    }
                     TrackWidth => UnresolvedReaperTarget::TrackWidth(UnresolvedTrackWidthTarget {
                         track_descriptor: self.track_descriptor()?,
                     }),
@@ -1055,6 +1257,44 @@ impl TargetModel {
                         },
                         poll_for_feedback: self.poll_for_feedback.get(),
                     }),
+                    TrackInsert => {
+                        UnresolvedReaperTarget::TrackInsert(UnresolvedTrackInsertTarget {
+                            track_descriptor: self.track_descriptor()?,
+                        })
+                    }
+                    TrackDuplicate => {
+                        UnresolvedReaperTarget::TrackDuplicate(UnresolvedTrackDuplicateTarget {
+                            track_descriptor: self.track_descriptor()?,
+                        })
+                    }
+                    TrackDelete => {
+                        UnresolvedReaperTarget::TrackDelete(UnresolvedTrackDeleteTarget {
+                            track_descriptor: self.track_descriptor()?,
+                            require_confirmation: self.require_confirmation.get(),
+                        })
+                    }
+                    TrackFolderSpill => {
+                        UnresolvedReaperTarget::TrackFolderSpill(UnresolvedTrackFolderSpillTarget {
+                            track_descriptor: self.track_descriptor()?,
+                        })
+                    }
+                    TrackInputMonitoring => UnresolvedReaperTarget::TrackInputMonitoring(
+                        UnresolvedTrackInputMonitoringTarget {
+                            track_descriptor: self.track_descriptor()?,
+                            exclusivity: self.track_exclusivity.get(),
+                            mode: self.input_monitoring_mode.get().to_reaper(),
+                        },
+                    ),
+                    TrackRecordInput => {
+                        UnresolvedReaperTarget::TrackRecordInput(UnresolvedTrackRecordInputTarget {
+                            track_descriptor: self.track_descriptor()?,
+                            kind: self.record_input_kind.get(),
+                        })
+                    }
+                    TrackCue => UnresolvedReaperTarget::TrackCue(UnresolvedTrackCueTarget {
+                        track_descriptor: self.track_descriptor()?,
+                        cue_output: self.cue_output.get(),
+                    }),
                     TrackAutomationMode => UnresolvedReaperTarget::TrackAutomationMode(
                         UnresolvedTrackAutomationModeTarget {
                             track_descriptor: self.track_descriptor()?,
@@ -1145,6 +1385,24 @@ impl TargetModel {
                     Transport => UnresolvedReaperTarget::Transport(UnresolvedTransportTarget {
                         action: self.transport_action.get(),
                     }),
+                    Punch => UnresolvedReaperTarget::Punch(UnresolvedPunchTarget {
+                        action: self.punch_action.get(),
+                    }),
+                    UndoRedo => UnresolvedReaperTarget::UndoRedo(UnresolvedUndoRedoTarget {
+                        action: self.undo_redo_action.get(),
+                    }),
+                    TimeSelection => {
+                        UnresolvedReaperTarget::TimeSelection(UnresolvedTimeSelectionTarget {
+                            action: self.time_selection_action.get(),
+                            range_kind: self.time_range_kind.get(),
+                        })
+                    }
+                    Metronome => UnresolvedReaperTarget::Metronome(UnresolvedMetronomeTarget {
+                        parameter: self.metronome_parameter.get(),
+                    }),
+                    MonitoringVolume => {
+                        UnresolvedReaperTarget::MonitoringVolume(UnresolvedMonitoringVolumeTarget)
+                    }
                     LoadFxSnapshot => {
                         UnresolvedReaperTarget::LoadFxPreset(UnresolvedLoadFxSnapshotTarget {
                             fx_descriptor: self.fx_descriptor()?,
@@ -1174,13 +1432,50 @@ impl TargetModel {
                             set_loop_points: self.use_loop_points.get(),
                         })
                     }
+                    NavigateBookmarks => UnresolvedReaperTarget::NavigateBookmarks(
+                        UnresolvedNavigateBookmarksTarget {
+                            bookmark_type: self.bookmark_type.get(),
+                            mode: self.bookmark_navigation_mode.get(),
+                        },
+                    ),
                     Seek => UnresolvedReaperTarget::Seek(UnresolvedSeekTarget {
                         options: self.seek_options(),
                     }),
+                    SetExtState => {
+                        UnresolvedReaperTarget::SetExtState(UnresolvedSetExtStateTarget {
+                            section: self.ext_state_section.get_ref().trim().to_owned(),
+                            key: self.ext_state_key.get_ref().trim().to_owned(),
+                            format: self.ext_state_format.get(),
+                            feedback_resolution: self.feedback_resolution.get(),
+                        })
+                    }
+                    RunScript => UnresolvedReaperTarget::RunScript(UnresolvedRunScriptTarget {
+                        script_path: PathBuf::from(self.script_path.get_ref().trim()),
+                    }),
                     SendMidi => UnresolvedReaperTarget::SendMidi(UnresolvedMidiSendTarget {
-                        pattern: self.raw_midi_pattern.get_ref().parse().unwrap_or_default(),
+                        sequence: self.raw_midi_pattern.get_ref().parse().unwrap_or_default(),
                         destination: self.send_midi_destination.get(),
+                        chord: self.midi_chord.get(),
+                        arpeggiator: self.midi_arpeggiator.get(),
                     }),
+                    SendMidiMacro => {
+                        UnresolvedReaperTarget::SendMidiMacro(UnresolvedMidiMacroSendTarget {
+                            macro_name: self.midi_macro_name.get_ref().clone(),
+                        })
+                    }
+                    SendProgramChange => UnresolvedReaperTarget::SendProgramChange(
+                        UnresolvedProgramChangeSendTarget {
+                            output: self.midi_output_device_id.get(),
+                            channel: self.midi_channel.get(),
+                            patches: self.program_change_patches.get_ref().clone(),
+                        },
+                    ),
+                    SendMidiClock => {
+                        UnresolvedReaperTarget::SendMidiClock(UnresolvedMidiClockSendTarget {
+                            output: self.midi_output_device_id.get(),
+                            mtc_frame_rate: self.midi_clock_mtc_frame_rate.get(),
+                        })
+                    }
                     SendOsc => UnresolvedReaperTarget::SendOsc(UnresolvedOscSendTarget {
                         address_pattern: self.osc_address_pattern.get_ref().clone(),
                         arg_descriptor: self.osc_arg_descriptor(),
@@ -1190,17 +1485,38 @@ impl TargetModel {
                         UnresolvedReaperTarget::ClipTransport(UnresolvedClipTransportTarget {
                             // TODO-medium Make it possible to pass direct HW output channel instead
                             track_descriptor: Some(self.track_descriptor()?),
-                            slot_index: self.slot_index.get(),
+                            matrix_id: self.clip_matrix_id.get_ref().clone(),
+                            slot: self.slot_address(),
                             action: self.transport_action.get(),
                             play_options: self.slot_play_options(),
                         })
                     }
                     ClipSeek => UnresolvedReaperTarget::ClipSeek(UnresolvedClipSeekTarget {
-                        slot_index: self.slot_index.get(),
+                        matrix_id: self.clip_matrix_id.get_ref().clone(),
+                        slot: self.slot_address(),
                         feedback_resolution: self.feedback_resolution.get(),
                     }),
                     ClipVolume => UnresolvedReaperTarget::ClipVolume(UnresolvedClipVolumeTarget {
-                        slot_index: self.slot_index.get(),
+                        matrix_id: self.clip_matrix_id.get_ref().clone(),
+                        slot: self.slot_address(),
+                    }),
+                    ClipColumnStop => {
+                        UnresolvedReaperTarget::ClipColumnStop(UnresolvedClipColumnStopTarget {
+                            track_descriptor: self.track_descriptor()?,
+                            matrix_id: self.clip_matrix_id.get_ref().clone(),
+                        })
+                    }
+                    ClipSceneLaunch => {
+                        UnresolvedReaperTarget::ClipSceneLaunch(UnresolvedClipSceneLaunchTarget {
+                            matrix_id: self.clip_matrix_id.get_ref().clone(),
+                            row: self.slot_row.get(),
+                            play_options: self.slot_play_options(),
+                        })
+                    }
+                    ClipCue => UnresolvedReaperTarget::ClipCue(UnresolvedClipCueTarget {
+                        matrix_id: self.clip_matrix_id.get_ref().clone(),
+                        slot: self.slot_address(),
+                        cue_output: self.cue_output.get(),
                     }),
                     LoadMappingSnapshot => UnresolvedReaperTarget::LoadMappingSnapshot(
                         UnresolvedLoadMappingSnapshotTarget {
@@ -1208,8 +1524,28 @@ impl TargetModel {
                                 tags: self.tags.get_ref().iter().cloned().collect(),
                             },
                             active_mappings_only: self.active_mappings_only.get(),
+                            snapshot_id: if self.mapping_snapshot_id.get_ref().is_empty() {
+                                None
+                            } else {
+                                Some(self.mapping_snapshot_id.get_ref().clone())
+                            },
+                            fade_time: self.mapping_snapshot_fade_time.get(),
                         },
                     ),
+                    SaveMappingSnapshot => {
+                        if self.mapping_snapshot_id.get_ref().is_empty() {
+                            return Err("snapshot ID not set");
+                        }
+                        UnresolvedReaperTarget::SaveMappingSnapshot(
+                            UnresolvedSaveMappingSnapshotTarget {
+                                scope: TagScope {
+                                    tags: self.tags.get_ref().iter().cloned().collect(),
+                                },
+                                active_mappings_only: self.active_mappings_only.get(),
+                                snapshot_id: self.mapping_snapshot_id.get_ref().clone(),
+                            },
+                        )
+                    }
                     EnableMappings => {
                         UnresolvedReaperTarget::EnableMappings(UnresolvedEnableMappingsTarget {
                             compartment,
@@ -1219,6 +1555,13 @@ impl TargetModel {
                             exclusivity: self.exclusivity.get(),
                         })
                     }
+                    EnableGroup => {
+                        UnresolvedReaperTarget::EnableGroup(UnresolvedEnableGroupTarget {
+                            compartment,
+                            group_id: self.group_id.get(),
+                            mode: self.group_enable_mode.get(),
+                        })
+                    }
                     EnableInstances => {
                         UnresolvedReaperTarget::EnableInstances(UnresolvedEnableInstancesTarget {
                             scope: TagScope {
@@ -1234,9 +1577,54 @@ impl TargetModel {
                             exclusivity: self.exclusivity.get().into(),
                         },
                     ),
+                    SelectLayer => {
+                        UnresolvedReaperTarget::SelectLayer(UnresolvedSelectLayerTarget {
+                            compartment,
+                            layer: self.layer.get(),
+                        })
+                    }
+                    SetBankOffset => {
+                        UnresolvedReaperTarget::SetBankOffset(UnresolvedSetBankOffsetTarget {
+                            offset: self.bank_offset.get(),
+                        })
+                    }
+                    FxParameterBank => {
+                        UnresolvedReaperTarget::FxParameterBank(UnresolvedFxParameterBankTarget {
+                            bank_size: self.fx_parameter_bank_size.get(),
+                        })
+                    }
                     AnyOn => UnresolvedReaperTarget::AnyOn(UnresolvedAnyOnTarget {
                         parameter: self.any_on_parameter.get(),
                     }),
+                    AdjustMappingMode => UnresolvedReaperTarget::AdjustMappingMode(
+                        UnresolvedAdjustMappingModeTarget {
+                            mapping_key: self.mapping_key.get_ref().clone(),
+                            setting: self.mapping_mode_setting.get(),
+                        },
+                    ),
+                    Multi => UnresolvedReaperTarget::Multi(UnresolvedMultiTarget {
+                        children: self
+                            .multi_target_children
+                            .get_ref()
+                            .iter()
+                            .map(|c| c.create_unresolved_child(compartment))
+                            .collect::<Result<_, _>>()?,
+                    }),
+                    Panic => UnresolvedReaperTarget::Panic(UnresolvedPanicTarget),
+                    InvokeInstance => {
+                        let session_id = self.instance_session_id.get_ref();
+                        let recipients = if session_id.is_empty() {
+                            InstanceMessageRecipients::Tagged(TagScope {
+                                tags: self.tags.get_ref().iter().cloned().collect(),
+                            })
+                        } else {
+                            InstanceMessageRecipients::Session(session_id.clone())
+                        };
+                        UnresolvedReaperTarget::InvokeInstance(UnresolvedInvokeInstanceTarget {
+                            recipients,
+                            message_name: self.instance_message_name.get_ref().clone(),
+                        })
+                    }
                 };
                 Ok(UnresolvedCompoundMappingTarget::Reaper(target))
             }
@@ -1247,10 +1635,30 @@ impl TargetModel {
         }
     }
 
+    pub fn slot_address(&self) -> ClipSlotAddress {
+        ClipSlotAddress {
+            row: self.slot_row.get(),
+            column: self.slot_column.get(),
+        }
+    }
+
+    /// Returns the flat index of the addressed slot within the currently configured matrix.
+    pub fn resolve_slot_index(
+        &self,
+        instance_state: &SharedInstanceState,
+    ) -> Result<usize, &'static str> {
+        resolve_clip_slot_index(
+            instance_state,
+            self.clip_matrix_id.get_ref().as_ref(),
+            self.slot_address(),
+        )
+    }
+
     pub fn slot_play_options(&self) -> SlotPlayOptions {
         SlotPlayOptions {
             next_bar: self.next_bar.get(),
             buffered: self.buffered.get(),
+            stop_quantization: self.stop_quantization.get(),
         }
     }
 
@@ -1385,13 +1793,19 @@ impl<'a> Display for TargetModelFormatVeryShort<'a> {
                 use ReaperTargetType::*;
                 let tt = self.0.r#type.get();
                 match tt {
-                    ClipTransport | ClipSeek | ClipVolume => {
-                        write!(
-                            f,
-                            "{}: Slot {}",
-                            tt.short_name(),
-                            self.0.slot_index.get() + 1
-                        )
+                    ClipTransport | ClipSeek | ClipVolume | ClipCue => {
+                        write!(f, "{}: Slot {}", tt.short_name(), self.0.slot_address())?;
+                        if let Some(id) = self.0.clip_matrix_id.get_ref() {
+                            write!(f, " ({})", id)?;
+                        }
+                        Ok(())
+                    }
+                    ClipSceneLaunch => {
+                        write!(f, "{}: Row {}", tt.short_name(), self.0.slot_row.get() + 1)?;
+                        if let Some(id) = self.0.clip_matrix_id.get_ref() {
+                            write!(f, " ({})", id)?;
+                        }
+                        Ok(())
                     }
                     Action => match self.0.action().ok() {
                         None => write!(f, "Action {}", self.0.command_id_label()),
@@ -1409,6 +1823,18 @@ impl<'a> Display for TargetModelFormatVeryShort<'a> {
                     Transport => {
                         write!(f, "{}", self.0.transport_action.get())
                     }
+                    Punch => {
+                        write!(f, "{}", self.0.punch_action.get())
+                    }
+                    UndoRedo => {
+                        write!(f, "{}", self.0.undo_redo_action.get())
+                    }
+                    TimeSelection => {
+                        write!(f, "{}", self.0.time_selection_action.get())
+                    }
+                    Metronome => {
+                        write!(f, "{}", self.0.metronome_parameter.get())
+                    }
                     AnyOn => {
                         write!(f, "{}", self.0.any_on_parameter.get())
                     }
@@ -1429,15 +1855,102 @@ impl<'a> Display for TargetModelFormatVeryShort<'a> {
                             self.0.bookmark_ref.get()
                         )
                     }
+                    NavigateBookmarks => {
+                        let type_label = match self.0.bookmark_type.get() {
+                            BookmarkType::Marker => "markers",
+                            BookmarkType::Region => "regions",
+                        };
+                        write!(
+                            f,
+                            "{} {}",
+                            self.0.bookmark_navigation_mode.get(),
+                            type_label
+                        )
+                    }
                     TrackAutomationMode => {
                         write!(f, "{}: {}", tt.short_name(), self.0.automation_mode.get())
                     }
+                    TrackInputMonitoring => write!(
+                        f,
+                        "{}: {}",
+                        tt.short_name(),
+                        self.0.input_monitoring_mode.get()
+                    ),
+                    TrackDualPan => write!(
+                        f,
+                        "{}: {}",
+                        tt.short_name(),
+                        self.0.dual_pan_component.get()
+                    ),
                     AutomationTouchState => write!(
                         f,
                         "{}: {}",
                         tt.short_name(),
                         self.0.touched_parameter_type.get()
                     ),
+                    SendMidiMacro => {
+                        write!(
+                            f,
+                            "{}: {}",
+                            tt.short_name(),
+                            self.0.midi_macro_name.get_ref()
+                        )
+                    }
+                    SendProgramChange => {
+                        write!(
+                            f,
+                            "{}: Ch {}",
+                            tt.short_name(),
+                            self.0.midi_channel.get() + 1
+                        )
+                    }
+                    LoadMappingSnapshot | SaveMappingSnapshot => {
+                        write!(
+                            f,
+                            "{}: {}",
+                            tt.short_name(),
+                            self.0.mapping_snapshot_label()
+                        )
+                    }
+                    AdjustMappingMode => {
+                        write!(
+                            f,
+                            "{}: {}",
+                            tt.short_name(),
+                            self.0.mapping_mode_setting.get()
+                        )
+                    }
+                    SelectLayer => write!(f, "{}: {}", tt.short_name(), self.0.layer.get()),
+                    Multi => write!(
+                        f,
+                        "{}: {} targets",
+                        tt.short_name(),
+                        self.0.multi_target_children.get_ref().len()
+                    ),
+                    SetBankOffset => {
+                        write!(f, "{}: {}", tt.short_name(), self.0.bank_offset.get())
+                    }
+                    InvokeInstance => write!(
+                        f,
+                        "{}: {}",
+                        tt.short_name(),
+                        self.0.instance_message_name.get_ref()
+                    ),
+                    SetExtState => {
+                        write!(f, "{}: {}", tt.short_name(), self.0.ext_state_key.get_ref())
+                    }
+                    RunScript => write!(
+                        f,
+                        "{}: {}",
+                        tt.short_name(),
+                        script_file_name(self.0.script_path.get_ref())
+                    ),
+                    FxParameterBank => write!(
+                        f,
+                        "{}: {} per bank",
+                        tt.short_name(),
+                        self.0.fx_parameter_bank_size.get()
+                    ),
                     _ => f.write_str(tt.short_name()),
                 }
             }
@@ -1608,7 +2121,7 @@ impl<'a> Display for TargetModelFormatMultiLine<'a> {
                 use ReaperTargetType::*;
                 let tt = self.target.r#type.get();
                 match tt {
-                    ClipTransport | ClipSeek | ClipVolume => {
+                    ClipTransport | ClipSeek | ClipVolume | ClipCue | ClipSceneLaunch => {
                         write!(f, "{}", tt)
                     }
                     Action => write!(
@@ -1628,6 +2141,7 @@ impl<'a> Display for TargetModelFormatMultiLine<'a> {
                     ),
                     TrackTool | TrackVolume | TrackPeak | TrackPan | TrackWidth | TrackArm
                     | TrackSelection | TrackMute | TrackPhase | TrackSolo | TrackShow
+                    | TrackInsert | TrackDuplicate | TrackDelete | TrackFolderSpill | TrackCue
                     | FxNavigate | AllTrackFxEnable => {
                         write!(f, "{}\nTrack {}", tt, self.track_label())
                     }
@@ -1640,6 +2154,33 @@ impl<'a> Display for TargetModelFormatMultiLine<'a> {
                             self.target.automation_mode.get()
                         )
                     }
+                    TrackRecordInput => {
+                        write!(
+                            f,
+                            "{}\nTrack {}\n{}",
+                            tt,
+                            self.track_label(),
+                            self.target.record_input_kind.get()
+                        )
+                    }
+                    TrackInputMonitoring => {
+                        write!(
+                            f,
+                            "{}\nTrack {}\n{}",
+                            tt,
+                            self.track_label(),
+                            self.target.input_monitoring_mode.get()
+                        )
+                    }
+                    TrackDualPan => {
+                        write!(
+                            f,
+                            "{}\nTrack {}\n{}",
+                            tt,
+                            self.track_label(),
+                            self.target.dual_pan_component.get()
+                        )
+                    }
                     TrackSendVolume
                     | TrackSendPan
                     | TrackSendMute
@@ -1661,6 +2202,16 @@ impl<'a> Display for TargetModelFormatMultiLine<'a> {
                         self.fx_label(),
                     ),
                     Transport => write!(f, "{}\n{}", tt, self.target.transport_action.get()),
+                    Punch => write!(f, "{}\n{}", tt, self.target.punch_action.get()),
+                    UndoRedo => write!(f, "{}\n{}", tt, self.target.undo_redo_action.get()),
+                    TimeSelection => write!(
+                        f,
+                        "{}\n{}\n{}",
+                        tt,
+                        self.target.time_range_kind.get(),
+                        self.target.time_selection_action.get()
+                    ),
+                    Metronome => write!(f, "{}\n{}", tt, self.target.metronome_parameter.get()),
                     AnyOn => write!(f, "{}\n{}", tt, self.target.any_on_parameter.get()),
                     AutomationModeOverride => {
                         write!(
@@ -1691,6 +2242,74 @@ impl<'a> Display for TargetModelFormatMultiLine<'a> {
                     GoToBookmark => {
                         write!(f, "{}\n{}", tt, self.bookmark_label())
                     }
+                    NavigateBookmarks => {
+                        let type_label = match self.target.bookmark_type.get() {
+                            BookmarkType::Marker => "Markers",
+                            BookmarkType::Region => "Regions",
+                        };
+                        write!(
+                            f,
+                            "{}\n{}\n{}",
+                            tt,
+                            type_label,
+                            self.target.bookmark_navigation_mode.get()
+                        )
+                    }
+                    SendMidiMacro => {
+                        write!(f, "{}\n{}", tt, self.target.midi_macro_name.get_ref())
+                    }
+                    SendProgramChange => {
+                        write!(
+                            f,
+                            "{}\nChannel {}\n{} patches",
+                            tt,
+                            self.target.midi_channel.get() + 1,
+                            self.target.program_change_patches.get_ref().len()
+                        )
+                    }
+                    SendMidiClock => match self.target.midi_clock_mtc_frame_rate.get() {
+                        None => write!(f, "{}", tt),
+                        Some(rate) => write!(f, "{}\nMTC {}", tt, rate),
+                    },
+                    LoadMappingSnapshot | SaveMappingSnapshot => {
+                        write!(f, "{}\n{}", tt, self.target.mapping_snapshot_label())
+                    }
+                    AdjustMappingMode => write!(
+                        f,
+                        "{}\n{}\n{}",
+                        tt,
+                        self.target.mapping_key.get_ref(),
+                        self.target.mapping_mode_setting.get()
+                    ),
+                    SelectLayer => write!(f, "{}\n{}", tt, self.target.layer.get()),
+                    Multi => {
+                        let child_labels: Vec<_> = self
+                            .target
+                            .multi_target_children
+                            .get_ref()
+                            .iter()
+                            .map(|c| TargetModelFormatVeryShort(&c.target).to_string())
+                            .collect();
+                        write!(f, "{}\n{}", tt, child_labels.join(", "))
+                    }
+                    SetBankOffset => write!(f, "{}\n{}", tt, self.target.bank_offset.get()),
+                    InvokeInstance => {
+                        write!(f, "{}\n{}", tt, self.target.instance_message_name.get_ref())
+                    }
+                    SetExtState => write!(
+                        f,
+                        "{}\n{}\n{}",
+                        tt,
+                        self.target.ext_state_section.get_ref(),
+                        self.target.ext_state_key.get_ref()
+                    ),
+                    RunScript => write!(f, "{}\n{}", tt, self.target.script_path.get_ref()),
+                    FxParameterBank => write!(
+                        f,
+                        "{}\n{} per bank",
+                        tt,
+                        self.target.fx_parameter_bank_size.get()
+                    ),
                     _ => write!(f, "{}", tt),
                 }
             }
@@ -1761,14 +2380,20 @@ impl<'a> TargetModelWithContext<'a> {
     ///
     /// Returns an error if not enough information is provided by the model or if something (e.g.
     /// track/FX/parameter) is not available.
-    pub fn resolve(&self) -> Result<Vec<CompoundMappingTarget>, &'static str> {
+    pub fn resolve(&self) -> Result<Vec<CompoundMappingTarget>, RealearnError> {
         let unresolved = self.target.create_target(self.compartment)?;
-        unresolved.resolve(self.context, self.compartment)
+        unresolved
+            .resolve(self.context, self.compartment)
+            .map_err(|e| RealearnError::not_found(e).context("resolving target"))
     }
 
-    pub fn resolve_first(&self) -> Result<CompoundMappingTarget, &'static str> {
+    pub fn resolve_first(&self) -> Result<CompoundMappingTarget, RealearnError> {
         let targets = self.resolve()?;
-        targets.into_iter().next().ok_or("resolved to empty list")
+        targets
+            .into_iter()
+            .next()
+            .ok_or_else(|| RealearnError::not_found("resolved to empty list"))
+            .context("resolving target")
     }
 
     pub fn is_known_to_be_roundable(&self) -> bool {
@@ -1810,6 +2435,14 @@ impl<'a> TargetModelWithContext<'a> {
     }
 }
 
+/// Returns just the file name of the given script path (falls back to the complete path).
+fn script_file_name(path: &str) -> Cow<str> {
+    Path::new(path)
+        .file_name()
+        .map(|n| n.to_string_lossy())
+        .unwrap_or_else(|| path.into())
+}
+
 pub fn get_bookmark_label(index_within_type: u32, id: BookmarkId, name: &str) -> String {
     format!("{}. {} (ID {})", index_within_type + 1, name, id)
 }
@@ -2288,6 +2921,49 @@ impl Display for FxSnapshot {
     }
 }
 
+/// A child target of a "Multi" target.
+///
+/// The child target model is shared and never modified in place. Changing a child means replacing
+/// the complete list of children, so comparing the identity of the child models is enough for
+/// detecting changes.
+#[derive(Clone, Debug)]
+pub struct MultiTargetChildModel {
+    pub target: Rc<TargetModel>,
+    /// Factor applied to absolute control values before they are passed to the child target.
+    pub scale: UnitValue,
+}
+
+impl MultiTargetChildModel {
+    pub fn new(target: TargetModel, scale: UnitValue) -> Self {
+        Self {
+            target: Rc::new(target),
+            scale,
+        }
+    }
+
+    fn create_unresolved_child(
+        &self,
+        compartment: MappingCompartment,
+    ) -> Result<UnresolvedMultiTargetChild, &'static str> {
+        let target = match self.target.create_target_internal(compartment)? {
+            UnresolvedCompoundMappingTarget::Reaper(t) => t,
+            UnresolvedCompoundMappingTarget::Virtual(_) => {
+                return Err("child targets of multi target must be real targets");
+            }
+        };
+        Ok(UnresolvedMultiTargetChild {
+            target,
+            scale: self.scale,
+        })
+    }
+}
+
+impl PartialEq for MultiTargetChildModel {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.target, &other.target) && self.scale == other.scale
+    }
+}
+
 #[derive(Default)]
 pub struct TrackPropValues {
     pub r#type: VirtualTrackType,
@@ -2454,6 +3130,56 @@ impl RealearnAutomationMode {
     }
 }
 
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    Serialize_repr,
+    Deserialize_repr,
+    IntoEnumIterator,
+    TryFromPrimitive,
+    IntoPrimitive,
+    Display,
+)]
+#[repr(usize)]
+pub enum RealearnInputMonitoringMode {
+    #[display(fmt = "Normal")]
+    Normal = 0,
+    #[display(fmt = "Tape style (off when playing)")]
+    NotWhenPlaying = 1,
+    #[display(fmt = "All modes (off/normal/tape style)")]
+    AllModes = 2,
+}
+
+impl Default for RealearnInputMonitoringMode {
+    fn default() -> Self {
+        Self::Normal
+    }
+}
+
+impl RealearnInputMonitoringMode {
+    /// Returns `None` if the target should switch between all modes.
+    fn to_reaper(self) -> Option<InputMonitoringMode> {
+        use RealearnInputMonitoringMode::*;
+        match self {
+            Normal => Some(InputMonitoringMode::Normal),
+            NotWhenPlaying => Some(InputMonitoringMode::NotWhenPlaying),
+            AllModes => None,
+        }
+    }
+
+    fn from_reaper(value: Option<InputMonitoringMode>) -> Self {
+        use InputMonitoringMode::*;
+        match value {
+            None => Self::AllModes,
+            Some(NotWhenPlaying) => Self::NotWhenPlaying,
+            Some(_) => Self::Normal,
+        }
+    }
+}
+
 #[derive(
     Clone,
     Copy,
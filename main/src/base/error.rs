@@ -0,0 +1,177 @@
+use serde::Serialize;
+use std::borrow::Cow;
+use std::fmt;
+
+/// Machine-readable category of a [`RealearnError`].
+///
+/// Meant to be stable, so remote tools can react to it and UIs can translate it.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ErrorCode {
+    /// The given data is incomplete or contains invalid values.
+    InvalidInput,
+    /// A referenced object (session, mapping, preset, track, ...) doesn't exist.
+    NotFound,
+    /// The requested operation is not supported.
+    NotSupported,
+    /// The client didn't present valid credentials.
+    Unauthorized,
+    /// Something went wrong internally.
+    Internal,
+    /// Error which hasn't been categorized yet (converted from a plain message).
+    Unspecified,
+}
+
+impl ErrorCode {
+    pub fn as_str(self) -> &'static str {
+        use ErrorCode::*;
+        match self {
+            InvalidInput => "invalid-input",
+            NotFound => "not-found",
+            NotSupported => "not-supported",
+            Unauthorized => "unauthorized",
+            Internal => "internal",
+            Unspecified => "unspecified",
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Structured error of application and domain APIs.
+///
+/// Plain `&'static str` errors convert into this one with code [`ErrorCode::Unspecified`], so
+/// APIs can be migrated one by one.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct RealearnError {
+    code: ErrorCode,
+    message: Cow<'static, str>,
+    /// Describes what was going on when the error occurred, outermost first.
+    context: Vec<Cow<'static, str>>,
+}
+
+impl RealearnError {
+    pub fn new(code: ErrorCode, message: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            context: vec![],
+        }
+    }
+
+    pub fn invalid_input(message: impl Into<Cow<'static, str>>) -> Self {
+        Self::new(ErrorCode::InvalidInput, message)
+    }
+
+    pub fn not_found(message: impl Into<Cow<'static, str>>) -> Self {
+        Self::new(ErrorCode::NotFound, message)
+    }
+
+    pub fn not_supported(message: impl Into<Cow<'static, str>>) -> Self {
+        Self::new(ErrorCode::NotSupported, message)
+    }
+
+    pub fn unauthorized(message: impl Into<Cow<'static, str>>) -> Self {
+        Self::new(ErrorCode::Unauthorized, message)
+    }
+
+    pub fn internal(message: impl Into<Cow<'static, str>>) -> Self {
+        Self::new(ErrorCode::Internal, message)
+    }
+
+    /// Adds a description of what was going on when this error occurred.
+    pub fn context(mut self, context: impl Into<Cow<'static, str>>) -> Self {
+        self.context.insert(0, context.into());
+        self
+    }
+
+    /// Replaces the code, e.g. in order to categorize a plain message error.
+    pub fn with_code(mut self, code: ErrorCode) -> Self {
+        self.code = code;
+        self
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        self.code
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn context_entries(&self) -> &[Cow<'static, str>] {
+        &self.context
+    }
+}
+
+impl fmt::Display for RealearnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for c in &self.context {
+            write!(f, "{}: ", c)?;
+        }
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for RealearnError {}
+
+impl From<&'static str> for RealearnError {
+    fn from(message: &'static str) -> Self {
+        Self::new(ErrorCode::Unspecified, message)
+    }
+}
+
+impl From<String> for RealearnError {
+    fn from(message: String) -> Self {
+        Self::new(ErrorCode::Unspecified, message)
+    }
+}
+
+/// Makes it possible to add context to any error that converts into a [`RealearnError`].
+pub trait ErrorContext<T> {
+    fn context(self, context: impl Into<Cow<'static, str>>) -> Result<T, RealearnError>;
+}
+
+impl<T, E: Into<RealearnError>> ErrorContext<T> for Result<T, E> {
+    fn context(self, context: impl Into<Cow<'static, str>>) -> Result<T, RealearnError> {
+        self.map_err(|e| e.into().context(context))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_with_context() {
+        // Given
+        let result: Result<(), &'static str> = Err("track not set");
+        // When
+        let error = result
+            .context("creating target")
+            .context("syncing mapping 5")
+            .unwrap_err();
+        // Then
+        assert_eq!(error.code(), ErrorCode::Unspecified);
+        assert_eq!(error.message(), "track not set");
+        assert_eq!(
+            error.to_string(),
+            "syncing mapping 5: creating target: track not set"
+        );
+    }
+
+    #[test]
+    fn categorize() {
+        // Given
+        let error = RealearnError::from("session not found");
+        // When
+        let error = error.with_code(ErrorCode::NotFound);
+        // Then
+        assert_eq!(error.code(), ErrorCode::NotFound);
+        assert_eq!(error.code().to_string(), "not-found");
+    }
+}
@@ -0,0 +1,174 @@
+use serde::Deserialize;
+use serde_json::Value;
+
+/// One operation of a JSON Patch document (RFC 6902).
+///
+/// Only `add`, `remove` and `replace` are supported.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum JsonPatchOperation {
+    Add { path: String, value: Value },
+    Remove { path: String },
+    Replace { path: String, value: Value },
+}
+
+/// Applies the given operations in order.
+///
+/// Either all operations are applied or none. In deviation from RFC 6902, `replace` also
+/// creates a missing object member, which is what older clients of the controller PATCH
+/// endpoint rely on.
+pub fn apply_json_patch(doc: &mut Value, operations: &[JsonPatchOperation]) -> Result<(), String> {
+    let mut patched = doc.clone();
+    for (i, op) in operations.iter().enumerate() {
+        apply_operation(&mut patched, op).map_err(|e| format!("operation {}: {}", i, e))?;
+    }
+    *doc = patched;
+    Ok(())
+}
+
+fn apply_operation(doc: &mut Value, op: &JsonPatchOperation) -> Result<(), String> {
+    use JsonPatchOperation::*;
+    match op {
+        Add { path, value } => {
+            let (parent, key) = resolve_parent(doc, path)?;
+            match parent {
+                Value::Object(map) => {
+                    map.insert(key, value.clone());
+                }
+                Value::Array(array) => {
+                    let index = if key == "-" {
+                        array.len()
+                    } else {
+                        parse_index(&key, array.len() + 1)?
+                    };
+                    array.insert(index, value.clone());
+                }
+                _ => return Err(format!("can't add to non-container at \"{}\"", path)),
+            }
+        }
+        Remove { path } => {
+            let (parent, key) = resolve_parent(doc, path)?;
+            match parent {
+                Value::Object(map) => {
+                    map.remove(&key)
+                        .ok_or_else(|| format!("nothing to remove at \"{}\"", path))?;
+                }
+                Value::Array(array) => {
+                    let index = parse_index(&key, array.len())?;
+                    array.remove(index);
+                }
+                _ => return Err(format!("can't remove from non-container at \"{}\"", path)),
+            }
+        }
+        Replace { path, value } => {
+            if path.is_empty() {
+                *doc = value.clone();
+                return Ok(());
+            }
+            let (parent, key) = resolve_parent(doc, path)?;
+            match parent {
+                Value::Object(map) => {
+                    map.insert(key, value.clone());
+                }
+                Value::Array(array) => {
+                    let index = parse_index(&key, array.len())?;
+                    array[index] = value.clone();
+                }
+                _ => return Err(format!("can't replace in non-container at \"{}\"", path)),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns the container which holds the value at the given JSON pointer and the last,
+/// unescaped reference token.
+fn resolve_parent<'a>(doc: &'a mut Value, path: &str) -> Result<(&'a mut Value, String), String> {
+    if !path.starts_with('/') {
+        return Err(format!("path \"{}\" must start with a slash", path));
+    }
+    let mut tokens: Vec<String> = path[1..].split('/').map(unescape_token).collect();
+    let last = tokens
+        .pop()
+        .expect("split always yields at least one token");
+    let mut current = doc;
+    for token in tokens {
+        current = match current {
+            Value::Object(map) => map.get_mut(&token),
+            Value::Array(array) => {
+                let index = parse_index(&token, array.len())?;
+                array.get_mut(index)
+            }
+            _ => None,
+        }
+        .ok_or_else(|| format!("path \"{}\" doesn't exist", path))?;
+    }
+    Ok((current, last))
+}
+
+fn unescape_token(token: &str) -> String {
+    token.replace("~1", "/").replace("~0", "~")
+}
+
+/// `len` is the exclusive upper bound.
+fn parse_index(token: &str, len: usize) -> Result<usize, String> {
+    let index: usize = token
+        .parse()
+        .map_err(|_| format!("\"{}\" is not an array index", token))?;
+    if index >= len {
+        return Err(format!("array index {} out of bounds", index));
+    }
+    Ok(index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn add_remove_replace() {
+        // Given
+        let mut doc = json!({
+            "customData": {},
+            "mappings": [{"id": "a"}, {"id": "b"}]
+        });
+        let ops: Vec<JsonPatchOperation> = serde_json::from_value(json!([
+            {"op": "add", "path": "/mappings/-", "value": {"id": "c"}},
+            {"op": "remove", "path": "/mappings/0"},
+            {"op": "replace", "path": "/mappings/0/id", "value": "x"},
+            {"op": "replace", "path": "/customData/companion", "value": {"gridSize": 10}},
+            {"op": "add", "path": "/customData/a~1b", "value": 1}
+        ]))
+        .unwrap();
+        // When
+        apply_json_patch(&mut doc, &ops).unwrap();
+        // Then
+        assert_eq!(
+            doc,
+            json!({
+                "customData": {"companion": {"gridSize": 10}, "a/b": 1},
+                "mappings": [{"id": "x"}, {"id": "c"}]
+            })
+        );
+    }
+
+    #[test]
+    fn all_or_nothing() {
+        // Given
+        let mut doc = json!({"mappings": [{"id": "a"}]});
+        let ops = vec![
+            JsonPatchOperation::Remove {
+                path: "/mappings/0".to_string(),
+            },
+            JsonPatchOperation::Remove {
+                path: "/mappings/0".to_string(),
+            },
+        ];
+        // When
+        let result = apply_json_patch(&mut doc, &ops);
+        // Then
+        assert!(result.is_err());
+        assert_eq!(doc, json!({"mappings": [{"id": "a"}]}));
+    }
+}
@@ -19,6 +19,12 @@ pub use property::*;
 mod moving_average_calculator;
 pub use moving_average_calculator::*;
 
+mod error;
+pub use error::*;
+
+mod json_patch;
+pub use json_patch::*;
+
 pub mod notification;
 
 pub mod eel;
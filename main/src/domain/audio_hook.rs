@@ -1,21 +1,30 @@
 use crate::domain::{
-    classify_midi_message, Event, Garbage, GarbageBin, IncomingMidiMessage, InstanceId,
-    MidiControlInput, MidiMessageClassification, MidiScanResult, MidiScanner, RealTimeProcessor,
+    classify_midi_message, short_message_to_raw_midi_event, Event, FeedbackMaxRateMap,
+    FeedbackMergeKey, FeedbackPriority, FeedbackRateLimiter, FeedbackScheduler, Garbage,
+    GarbageBin, IncomingMidiMessage, InstanceId, MidiClockGenerator, MidiClockSettings,
+    MidiControlInput, MidiMessageClassification, MidiOutputScheduler, MidiScanResult, MidiScanner,
+    RealTimeProcessor, RtpMidiDeviceId, RtpMidiEvent, SysexChunkingSettingsMap, TransportBlock,
 };
 use assert_no_alloc::*;
 use helgoboss_learn::{MidiSourceValue, RawMidiEvent};
 use helgoboss_midi::{DataEntryByteOrder, RawShortMessage};
-use reaper_high::{MidiInputDevice, MidiOutputDevice, Reaper};
+use reaper_high::{MidiInputDevice, MidiOutputDevice, Project, Reaper};
 use reaper_medium::{
-    MidiEvent, MidiInputDeviceId, MidiOutputDeviceId, OnAudioBuffer, OnAudioBufferArgs,
-    SendMidiTime,
+    MidiEvent, MidiFrameOffset, MidiInputDeviceId, MidiOutputDeviceId, OnAudioBuffer,
+    OnAudioBufferArgs, SendMidiTime,
 };
+use slog::warn;
 use smallvec::SmallVec;
 use std::sync::{Arc, Mutex, MutexGuard};
 use std::time::{Duration, Instant};
 
 const AUDIO_HOOK_TASK_BULK_SIZE: usize = 1;
 const FEEDBACK_TASK_BULK_SIZE: usize = 1000;
+const RTP_MIDI_EVENT_BULK_SIZE: usize = 100;
+/// If more feedback tasks than this arrive within one cycle, lower-priority feedback is degraded.
+const FEEDBACK_PRESSURE_THRESHOLD: usize = 100;
+/// Maximum number of MIDI output devices which can receive generated MIDI clock at once.
+const MAX_MIDI_CLOCK_OUTPUT_COUNT: usize = 8;
 
 /// This needs to be thread-safe because if "Allow live FX multiprocessing" is active in the REAPER
 /// preferences, the VST processing is executed in another thread than the audio hook!
@@ -37,6 +46,8 @@ pub enum NormalAudioHookTask {
     RemoveRealTimeProcessor(InstanceId),
     StartCapturingMidi(MidiCaptureSender),
     StopCapturingMidi,
+    SetSysexChunkingSettings(SysexChunkingSettingsMap),
+    SetFeedbackMaxRates(FeedbackMaxRateMap<MidiOutputDeviceId>),
 }
 
 /// A global feedback task (which is potentially sent very frequently).
@@ -47,16 +58,54 @@ pub enum FeedbackAudioHookTask {
         MidiSourceValue<'static, RawShortMessage>,
     ),
     SendMidi(MidiOutputDeviceId, Vec<RawMidiEvent>),
+    RtpMidiDeviceFeedback(RtpMidiDeviceId, MidiSourceValue<'static, RawShortMessage>),
+    /// Starts (`Some`) or stops (`None`) generating MIDI clock on the given device.
+    ///
+    /// Handled immediately instead of going through the feedback scheduler.
+    SetMidiClockOutput(MidiOutputDeviceId, Option<MidiClockSettings>),
 }
 
+impl FeedbackAudioHookTask {
+    fn priority(&self) -> (FeedbackPriority, Option<FeedbackMergeKey>) {
+        use FeedbackAudioHookTask::*;
+        match self {
+            MidiDeviceFeedback(dev_id, value) => {
+                let merge_key = match value {
+                    MidiSourceValue::Plain(msg) => {
+                        Some(FeedbackMergeKey::of_short_message(*dev_id, *msg))
+                    }
+                    _ => None,
+                };
+                (FeedbackPriority::of_midi_source_value(value), merge_key)
+            }
+            // Not feedback but output of the "MIDI: Send message" target. Never degrade it.
+            SendMidi(..) => (FeedbackPriority::Critical, None),
+            RtpMidiDeviceFeedback(_, value) => {
+                (FeedbackPriority::of_midi_source_value(value), None)
+            }
+            SetMidiClockOutput(..) => (FeedbackPriority::Critical, None),
+        }
+    }
+}
+
+type MidiFeedbackRateLimiter =
+    FeedbackRateLimiter<MidiOutputDeviceId, FeedbackMergeKey, FeedbackAudioHookTask>;
+
 #[derive(Debug)]
 pub struct RealearnAudioHook {
     state: AudioHookState,
     real_time_processors: SmallVec<[(InstanceId, SharedRealTimeProcessor); 256]>,
     normal_task_receiver: crossbeam_channel::Receiver<NormalAudioHookTask>,
     feedback_task_receiver: crossbeam_channel::Receiver<FeedbackAudioHookTask>,
+    rtp_midi_incoming_receiver: crossbeam_channel::Receiver<RtpMidiEvent>,
+    rtp_midi_outgoing_sender: crossbeam_channel::Sender<RtpMidiEvent>,
     time_of_last_run: Option<Instant>,
     garbage_bin: GarbageBin,
+    midi_output_scheduler: MidiOutputScheduler,
+    feedback_scheduler: FeedbackScheduler<FeedbackAudioHookTask>,
+    feedback_rate_limiter: MidiFeedbackRateLimiter,
+    midi_clock_generators:
+        [Option<(MidiOutputDeviceId, MidiClockGenerator)>; MAX_MIDI_CLOCK_OUTPUT_COUNT],
 }
 
 #[derive(Debug)]
@@ -74,6 +123,8 @@ impl RealearnAudioHook {
     pub fn new(
         normal_task_receiver: crossbeam_channel::Receiver<NormalAudioHookTask>,
         feedback_task_receiver: crossbeam_channel::Receiver<FeedbackAudioHookTask>,
+        rtp_midi_incoming_receiver: crossbeam_channel::Receiver<RtpMidiEvent>,
+        rtp_midi_outgoing_sender: crossbeam_channel::Sender<RtpMidiEvent>,
         garbage_bin: GarbageBin,
     ) -> RealearnAudioHook {
         Self {
@@ -81,60 +132,161 @@ impl RealearnAudioHook {
             real_time_processors: Default::default(),
             normal_task_receiver,
             feedback_task_receiver,
+            rtp_midi_incoming_receiver,
+            rtp_midi_outgoing_sender,
             time_of_last_run: None,
             garbage_bin,
+            midi_output_scheduler: Default::default(),
+            feedback_scheduler: FeedbackScheduler::with_capacity(FEEDBACK_TASK_BULK_SIZE),
+            feedback_rate_limiter: Default::default(),
+            midi_clock_generators: Default::default(),
         }
     }
 
-    fn process_feedback_tasks(&mut self) {
+    fn process_feedback_tasks(&mut self, current_time: Instant) {
+        // Send sys-ex chunks which have been waiting for their turn first.
+        self.midi_output_scheduler
+            .send_due(current_time, |dev_id, event| {
+                MidiOutputDevice::new(dev_id).with_midi_output(|mo| {
+                    if let Some(mo) = mo {
+                        mo.send_msg(event, SendMidiTime::Instantly);
+                    }
+                });
+            });
+        // Then feedback which had to wait because of a device's maximum feedback rate.
+        {
+            let midi_output_scheduler = &mut self.midi_output_scheduler;
+            let rtp_midi_outgoing_sender = &self.rtp_midi_outgoing_sender;
+            let garbage_bin = &self.garbage_bin;
+            self.feedback_rate_limiter
+                .send_due(current_time, |_, task| {
+                    process_feedback_task(
+                        task,
+                        midi_output_scheduler,
+                        rtp_midi_outgoing_sender,
+                        garbage_bin,
+                        current_time,
+                    )
+                });
+        }
         // Process global direct device feedback (since v2.8.0-pre6) - in order to
         // have deterministic feedback ordering, which is important for multi-instance
-        // orchestration.
+        // orchestration. The feedback scheduler keeps that order within each priority class.
         for task in self
             .feedback_task_receiver
             .try_iter()
             .take(FEEDBACK_TASK_BULK_SIZE)
         {
-            use FeedbackAudioHookTask::*;
-            match task {
-                MidiDeviceFeedback(dev_id, value) => {
-                    if let Some(events) = value.to_raw() {
-                        MidiOutputDevice::new(dev_id).with_midi_output(|mo| {
-                            if let Some(mo) = mo {
-                                for event in events {
-                                    mo.send_msg(&*event, SendMidiTime::Instantly);
-                                }
-                            }
-                        });
-                    } else {
-                        let shorts = value.to_short_messages(DataEntryByteOrder::MsbFirst);
-                        if shorts[0].is_none() {
-                            return;
-                        }
-                        MidiOutputDevice::new(dev_id).with_midi_output(|mo| {
-                            if let Some(mo) = mo {
-                                for short in shorts.iter().flatten() {
-                                    mo.send(*short, SendMidiTime::Instantly);
-                                }
-                            }
-                        });
-                    }
-                    if let Some(garbage) = value.into_garbage() {
-                        self.garbage_bin.dispose(Garbage::RawMidiEvents(garbage));
-                    }
+            if let FeedbackAudioHookTask::SetMidiClockOutput(dev_id, settings) = task {
+                self.set_midi_clock_output(dev_id, settings);
+                continue;
+            }
+            let (priority, merge_key) = task.priority();
+            if let Err(task) = self.feedback_scheduler.push(priority, merge_key, task) {
+                send_feedback_task(
+                    task,
+                    &mut self.feedback_rate_limiter,
+                    &mut self.midi_output_scheduler,
+                    &self.rtp_midi_outgoing_sender,
+                    &self.garbage_bin,
+                    current_time,
+                );
+            }
+        }
+        let under_pressure = self.feedback_scheduler.len() >= FEEDBACK_PRESSURE_THRESHOLD
+            || !self.feedback_task_receiver.is_empty();
+        let feedback_rate_limiter = &mut self.feedback_rate_limiter;
+        let midi_output_scheduler = &mut self.midi_output_scheduler;
+        let rtp_midi_outgoing_sender = &self.rtp_midi_outgoing_sender;
+        let garbage_bin = &self.garbage_bin;
+        self.feedback_scheduler.flush(
+            under_pressure,
+            |task| {
+                send_feedback_task(
+                    task,
+                    feedback_rate_limiter,
+                    midi_output_scheduler,
+                    rtp_midi_outgoing_sender,
+                    garbage_bin,
+                    current_time,
+                )
+            },
+            |task| dispose_feedback_task(task, garbage_bin),
+        );
+    }
+
+    fn set_midi_clock_output(
+        &mut self,
+        dev_id: MidiOutputDeviceId,
+        settings: Option<MidiClockSettings>,
+    ) {
+        let index = self
+            .midi_clock_generators
+            .iter()
+            .position(|g| matches!(g, Some((id, _)) if *id == dev_id));
+        match (index, settings) {
+            (Some(i), Some(settings)) => {
+                let unchanged = matches!(
+                    &self.midi_clock_generators[i],
+                    Some((_, g)) if g.settings() == settings
+                );
+                if !unchanged {
+                    self.midi_clock_generators[i] =
+                        Some((dev_id, MidiClockGenerator::new(settings)));
                 }
-                SendMidi(dev_id, raw_midi_events) => {
+            }
+            (Some(i), None) => {
+                if let Some((_, mut generator)) = self.midi_clock_generators[i].take() {
+                    // Don't leave the receiver running.
                     MidiOutputDevice::new(dev_id).with_midi_output(|mo| {
                         if let Some(mo) = mo {
-                            for event in &raw_midi_events {
-                                mo.send_msg(&*event, SendMidiTime::Instantly);
-                            }
+                            generator.stop(|bytes| {
+                                if let Ok(event) = RawMidiEvent::try_from_slice(0, bytes) {
+                                    mo.send_msg(&event, SendMidiTime::Instantly);
+                                }
+                            });
                         }
                     });
-                    self.garbage_bin
-                        .dispose(Garbage::RawMidiEvents(raw_midi_events));
                 }
             }
+            (None, Some(settings)) => {
+                // If all slots are taken, the request is ignored.
+                if let Some(slot) = self.midi_clock_generators.iter_mut().find(|g| g.is_none()) {
+                    *slot = Some((dev_id, MidiClockGenerator::new(settings)));
+                }
+            }
+            (None, None) => {}
+        }
+    }
+
+    fn generate_midi_clock(&mut self, args: &OnAudioBufferArgs) {
+        if self.midi_clock_generators.iter().all(|g| g.is_none()) {
+            return;
+        }
+        let project = Reaper::get().current_project();
+        let start_secs = project.play_position_next_audio_block().get();
+        let end_secs = start_secs + args.len as f64 / args.srate.get();
+        let block = TransportBlock {
+            is_playing: project.play_state().is_playing,
+            start_beats: time_to_quarter_notes(project, start_secs),
+            end_beats: time_to_quarter_notes(project, end_secs),
+            start_secs,
+            end_secs,
+            frame_count: args.len as _,
+        };
+        for (dev_id, generator) in self.midi_clock_generators.iter_mut().flatten() {
+            MidiOutputDevice::new(*dev_id).with_midi_output(|mo| {
+                if let Some(mo) = mo {
+                    generator.process(&block, |offset, bytes| {
+                        if let Ok(event) = RawMidiEvent::try_from_slice(offset, bytes) {
+                            mo.send_msg(
+                                &event,
+                                SendMidiTime::AtFrameOffset(MidiFrameOffset::new(offset)),
+                            );
+                        }
+                    });
+                }
+            });
         }
     }
 
@@ -162,6 +314,17 @@ impl RealearnAudioHook {
                         }
                     });
                 }
+                // Network MIDI messages don't have a hardware device ID, so they are learned
+                // without one.
+                for e in self
+                    .rtp_midi_incoming_receiver
+                    .try_iter()
+                    .take(RTP_MIDI_EVENT_BULK_SIZE)
+                {
+                    if let Some(res) = scan_midi_bytes(e.event().bytes(), midi_scanner) {
+                        let _ = sender.try_send(res);
+                    }
+                }
                 if let Some(res) = midi_scanner.poll() {
                     // Source detected via polling. Return to normal mode.
                     let _ = sender.try_send(res);
@@ -200,6 +363,11 @@ impl RealearnAudioHook {
                     midi_dev_id_is_used[dev_id.get() as usize] = true;
                     midi_devs_used_at_all = true;
                 }
+                // Mappings can listen to other devices than the instance's control input.
+                for dev_id in guard.midi_input_device_overrides() {
+                    midi_dev_id_is_used[dev_id.get() as usize] = true;
+                    midi_devs_used_at_all = true;
+                }
             }
         }
         // 1b. Forward MIDI events from MIDI devices to ReaLearn instances and filter
@@ -207,6 +375,30 @@ impl RealearnAudioHook {
         if midi_devs_used_at_all {
             self.distribute_midi_events_to_processors(args, &midi_dev_id_is_used);
         }
+        // 1c. Forward MIDI events received via network. They arrive between audio blocks, so
+        //     there's no meaningful sample offset and nothing to filter.
+        self.distribute_rtp_midi_events_to_processors();
+    }
+
+    fn distribute_rtp_midi_events_to_processors(&mut self) {
+        for e in self
+            .rtp_midi_incoming_receiver
+            .try_iter()
+            .take(RTP_MIDI_EVENT_BULK_SIZE)
+        {
+            let msg = match IncomingMidiMessage::from_bytes(e.event().bytes()) {
+                Err(_) => continue,
+                Ok(m) => m,
+            };
+            let our_event = Event::without_offset(msg);
+            let input = MidiControlInput::RtpMidi(e.dev_id());
+            for (_, p) in self.real_time_processors.iter() {
+                let mut guard = p.lock_recover();
+                if guard.control_is_globally_enabled() && guard.midi_control_input() == input {
+                    guard.process_incoming_midi_from_audio_hook(our_event, input);
+                }
+            }
+        }
     }
 
     fn distribute_midi_events_to_processors(
@@ -234,8 +426,11 @@ impl RealearnAudioHook {
                         for (_, p) in self.real_time_processors.iter() {
                             let mut guard = p.lock_recover();
                             if guard.control_is_globally_enabled()
-                                && guard.midi_control_input() == MidiControlInput::Device(dev_id)
-                                && guard.process_incoming_midi_from_audio_hook(our_event)
+                                && guard.listens_to_midi_input_device(dev_id)
+                                && guard.process_incoming_midi_from_audio_hook(
+                                    our_event,
+                                    MidiControlInput::Device(dev_id),
+                                )
                             {
                                 filter_out_event = true;
                             }
@@ -281,6 +476,16 @@ impl RealearnAudioHook {
                         self.garbage_bin.dispose(Garbage::MidiCaptureSender(sender));
                     }
                 }
+                SetSysexChunkingSettings(settings) => {
+                    let old_settings = self.midi_output_scheduler.replace_settings(settings);
+                    self.garbage_bin
+                        .dispose(Garbage::SysexChunkingSettings(old_settings));
+                }
+                SetFeedbackMaxRates(max_rates) => {
+                    let old_max_rates = self.feedback_rate_limiter.replace_max_rates(max_rates);
+                    self.garbage_bin
+                        .dispose(Garbage::FeedbackMaxRates(old_max_rates));
+                }
             }
         }
     }
@@ -299,13 +504,196 @@ impl OnAudioBuffer for RealearnAudioHook {
             } else {
                 false
             };
-            self.process_feedback_tasks();
+            self.process_feedback_tasks(current_time);
+            self.generate_midi_clock(&args);
             self.call_real_time_processors(&args, might_be_rebirth);
             self.process_add_remove_tasks();
         });
     }
 }
 
+/// Sends the feedback right now or - if the device has a maximum feedback rate - maybe later.
+fn send_feedback_task(
+    task: FeedbackAudioHookTask,
+    feedback_rate_limiter: &mut MidiFeedbackRateLimiter,
+    midi_output_scheduler: &mut MidiOutputScheduler,
+    rtp_midi_outgoing_sender: &crossbeam_channel::Sender<RtpMidiEvent>,
+    garbage_bin: &GarbageBin,
+    current_time: Instant,
+) {
+    let limited_dev_id = match &task {
+        FeedbackAudioHookTask::MidiDeviceFeedback(dev_id, _)
+            if feedback_rate_limiter.is_limited(*dev_id) =>
+        {
+            Some(*dev_id)
+        }
+        _ => None,
+    };
+    let dev_id = match limited_dev_id {
+        None => {
+            process_feedback_task(
+                task,
+                midi_output_scheduler,
+                rtp_midi_outgoing_sender,
+                garbage_bin,
+                current_time,
+            );
+            return;
+        }
+        Some(id) => id,
+    };
+    // The merge key identifies the control element, so it's also the address of the feedback.
+    let (_, address) = task.priority();
+    feedback_rate_limiter.send(
+        dev_id,
+        address,
+        task,
+        current_time,
+        |task| {
+            process_feedback_task(
+                task,
+                midi_output_scheduler,
+                rtp_midi_outgoing_sender,
+                garbage_bin,
+                current_time,
+            )
+        },
+        |task| dispose_feedback_task(task, garbage_bin),
+    );
+}
+
+fn warn_about_failed_midi_output(dev_id: MidiOutputDeviceId, msg: &str) {
+    permit_alloc(|| {
+        warn!(
+            Reaper::get().logger(),
+            "Couldn't send MIDI to output device {}: {}",
+            dev_id.get(),
+            msg
+        );
+    });
+}
+
+/// MIDI clock counts in quarter notes, no matter the time signature. Full beats wouldn't do
+/// because their length depends on the denominator (e.g. eighth notes in 6/8).
+fn time_to_quarter_notes(project: Project, secs: f64) -> f64 {
+    unsafe {
+        Reaper::get()
+            .medium_reaper()
+            .low()
+            .TimeMap2_timeToQN(project.raw().as_ptr(), secs)
+    }
+}
+
+fn process_feedback_task(
+    task: FeedbackAudioHookTask,
+    midi_output_scheduler: &mut MidiOutputScheduler,
+    rtp_midi_outgoing_sender: &crossbeam_channel::Sender<RtpMidiEvent>,
+    garbage_bin: &GarbageBin,
+    current_time: Instant,
+) {
+    use FeedbackAudioHookTask::*;
+    match task {
+        MidiDeviceFeedback(dev_id, value) => {
+            if let Some(events) = value.to_raw() {
+                MidiOutputDevice::new(dev_id).with_midi_output(|mo| {
+                    if let Some(mo) = mo {
+                        for event in events {
+                            midi_output_scheduler
+                                .send(dev_id, &*event, current_time, |e| {
+                                    mo.send_msg(e, SendMidiTime::Instantly);
+                                })
+                                .unwrap_or_else(|e| warn_about_failed_midi_output(dev_id, e));
+                        }
+                    }
+                });
+            } else {
+                let shorts = value.to_short_messages(DataEntryByteOrder::MsbFirst);
+                if shorts[0].is_none() {
+                    return;
+                }
+                MidiOutputDevice::new(dev_id).with_midi_output(|mo| {
+                    if let Some(mo) = mo {
+                        for short in shorts.iter().flatten() {
+                            if !midi_output_scheduler.has_pending(dev_id) {
+                                mo.send(*short, SendMidiTime::Instantly);
+                                continue;
+                            }
+                            // Don't let it slip into a chunked sys-ex message.
+                            if let Some(event) = short_message_to_raw_midi_event(*short) {
+                                midi_output_scheduler
+                                    .send(dev_id, &event, current_time, |e| {
+                                        mo.send_msg(e, SendMidiTime::Instantly);
+                                    })
+                                    .unwrap_or_else(|e| warn_about_failed_midi_output(dev_id, e));
+                            }
+                        }
+                    }
+                });
+            }
+            if let Some(garbage) = value.into_garbage() {
+                garbage_bin.dispose(Garbage::RawMidiEvents(garbage));
+            }
+        }
+        SendMidi(dev_id, raw_midi_events) => {
+            MidiOutputDevice::new(dev_id).with_midi_output(|mo| {
+                if let Some(mo) = mo {
+                    for event in &raw_midi_events {
+                        midi_output_scheduler
+                            .send(dev_id, event, current_time, |e| {
+                                mo.send_msg(e, SendMidiTime::Instantly);
+                            })
+                            .unwrap_or_else(|e| warn_about_failed_midi_output(dev_id, e));
+                    }
+                }
+            });
+            garbage_bin.dispose(Garbage::RawMidiEvents(raw_midi_events));
+        }
+        RtpMidiDeviceFeedback(dev_id, value) => {
+            // The channel is bounded, so sending doesn't allocate. If the RTP-MIDI thread is not
+            // running (no sessions configured), the messages are simply dropped.
+            if let Some(events) = value.to_raw() {
+                for event in events {
+                    if let Ok(event) = RawMidiEvent::try_from_slice(0, event.bytes()) {
+                        let _ = rtp_midi_outgoing_sender.try_send(RtpMidiEvent::new(dev_id, event));
+                    }
+                }
+            } else {
+                let shorts = value.to_short_messages(DataEntryByteOrder::MsbFirst);
+                for short in shorts.iter().flatten() {
+                    if let Some(event) = short_message_to_raw_midi_event(*short) {
+                        let _ = rtp_midi_outgoing_sender.try_send(RtpMidiEvent::new(dev_id, event));
+                    }
+                }
+            }
+            if let Some(garbage) = value.into_garbage() {
+                garbage_bin.dispose(Garbage::RawMidiEvents(garbage));
+            }
+        }
+        // Never scheduled, see `RealearnAudioHook::process_feedback_tasks`.
+        SetMidiClockOutput(..) => {}
+    }
+}
+
+fn dispose_feedback_task(task: FeedbackAudioHookTask, garbage_bin: &GarbageBin) {
+    use FeedbackAudioHookTask::*;
+    match task {
+        MidiDeviceFeedback(_, value) => {
+            if let Some(garbage) = value.into_garbage() {
+                garbage_bin.dispose(Garbage::RawMidiEvents(garbage));
+            }
+        }
+        SendMidi(_, raw_midi_events) => {
+            garbage_bin.dispose(Garbage::RawMidiEvents(raw_midi_events));
+        }
+        RtpMidiDeviceFeedback(_, value) => {
+            if let Some(garbage) = value.into_garbage() {
+                garbage_bin.dispose(Garbage::RawMidiEvents(garbage));
+            }
+        }
+        SetMidiClockOutput(..) => {}
+    }
+}
+
 fn scan_midi(
     dev_id: MidiInputDeviceId,
     evt: &MidiEvent,
@@ -326,6 +714,18 @@ fn scan_midi(
     }
 }
 
+fn scan_midi_bytes(bytes: &[u8], midi_scanner: &mut MidiScanner) -> Option<MidiScanResult> {
+    let msg = IncomingMidiMessage::from_bytes(bytes).ok()?;
+    if classify_midi_message(msg) != MidiMessageClassification::Normal {
+        return None;
+    }
+    use IncomingMidiMessage::*;
+    match msg {
+        Short(short_msg) => midi_scanner.feed_short(short_msg, None),
+        SysEx(bytes) => permit_alloc(|| MidiScanResult::try_from_bytes(bytes, None).ok()),
+    }
+}
+
 pub trait RealTimeProcessorLocker {
     fn lock_recover(&self) -> MutexGuard<RealTimeProcessor>;
 }
@@ -1,7 +1,12 @@
 use crate::domain::{
-    ControlInput, DeviceControlInput, DeviceFeedbackOutput, FeedbackOutput, InstanceId,
-    RealearnTargetContext, ReaperTarget,
+    clip_changed_event, ClipChangedEvent, ClipMatrix, ClipMatrixContent, ClipMatrixId,
+    ClipMatrixSize, ClipSlot, ControlInput, DeviceControlInput, DeviceFeedbackOutput,
+    FeedbackOutput, InstanceId, InstanceStateChanged, MidiClockSettings, RealearnTargetContext,
+    ReaperTarget,
 };
+use crossbeam_channel::TrySendError;
+use reaper_high::Project;
+use reaper_medium::{MidiOutputDeviceId, PlayState};
 use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
 use std::hash::Hash;
@@ -17,7 +22,20 @@ pub struct BackboneState {
     control_input_usages: RefCell<HashMap<DeviceControlInput, HashSet<InstanceId>>>,
     /// Value: Instance ID of the ReaLearn instance that owns the feedback output.
     feedback_output_usages: RefCell<HashMap<DeviceFeedbackOutput, HashSet<InstanceId>>>,
-    upper_floor_instances: RefCell<HashSet<InstanceId>>,
+    /// Priorities of all instances which currently use a control input or feedback output.
+    ///
+    /// If multiple instances use the same device, only the ones with the highest priority own it.
+    instance_priorities: RefCell<HashMap<InstanceId, InstancePriority>>,
+    /// Clip matrices which can be controlled by multiple instances at once.
+    ///
+    /// Created on demand as soon as a clip target refers to a not-yet-existing matrix ID.
+    shared_clip_matrices: RefCell<HashMap<ClipMatrixId, ClipMatrix>>,
+    /// Instance feedback event senders of all instances, used for broadcasting changes of
+    /// shared clip matrices. Senders of instances that are gone are removed lazily.
+    instance_feedback_event_senders: RefCell<Vec<crossbeam_channel::Sender<InstanceStateChanged>>>,
+    /// MIDI output devices which currently receive MIDI clock generated by the audio hook, along
+    /// with the instance which switched it on.
+    midi_clock_outputs: RefCell<HashMap<MidiOutputDeviceId, (InstanceId, MidiClockSettings)>>,
 }
 
 impl BackboneState {
@@ -27,7 +45,10 @@ impl BackboneState {
             last_touched_target: Default::default(),
             control_input_usages: Default::default(),
             feedback_output_usages: Default::default(),
-            upper_floor_instances: Default::default(),
+            instance_priorities: Default::default(),
+            shared_clip_matrices: Default::default(),
+            instance_feedback_event_senders: Default::default(),
+            midi_clock_outputs: Default::default(),
         }
     }
 
@@ -39,18 +60,6 @@ impl BackboneState {
         self.last_touched_target.borrow().clone()
     }
 
-    pub fn lives_on_upper_floor(&self, instance_id: &InstanceId) -> bool {
-        self.upper_floor_instances.borrow().contains(instance_id)
-    }
-
-    pub fn add_to_upper_floor(&self, instance_id: InstanceId) {
-        self.upper_floor_instances.borrow_mut().insert(instance_id);
-    }
-
-    pub fn remove_from_upper_floor(&self, instance_id: &InstanceId) {
-        self.upper_floor_instances.borrow_mut().remove(instance_id);
-    }
-
     pub fn control_is_allowed(
         &self,
         instance_id: &InstanceId,
@@ -77,21 +86,236 @@ impl BackboneState {
 
     /// Also drops all previous usage  of that instance.
     ///
-    /// Returns true if this actually caused a change in *feedback output* usage.
+    /// The control inputs include the input devices which single mappings listen to, the feedback
+    /// outputs the output devices which single mappings send their feedback to.
+    ///
+    /// Returns the other instances whose ownership of their feedback output changed because of
+    /// this, e.g. because this instance started using the same output with a higher priority.
     pub fn update_io_usage(
         &self,
         instance_id: &InstanceId,
-        control_input: Option<DeviceControlInput>,
-        feedback_output: Option<DeviceFeedbackOutput>,
-    ) -> bool {
+        priority: InstancePriority,
+        control_inputs: &HashSet<DeviceControlInput>,
+        feedback_outputs: &HashSet<DeviceFeedbackOutput>,
+    ) -> Vec<InstanceId> {
+        let previous_feedback_output_owners = self.feedback_output_owners();
         {
             let mut usages = self.control_input_usages.borrow_mut();
-            update_io_usage(&mut usages, instance_id, control_input);
+            update_io_usage(&mut usages, instance_id, control_inputs.iter().copied());
         }
         {
             let mut usages = self.feedback_output_usages.borrow_mut();
-            update_io_usage(&mut usages, instance_id, feedback_output)
+            update_io_usage(&mut usages, instance_id, feedback_outputs.iter().copied());
+        }
+        {
+            let mut priorities = self.instance_priorities.borrow_mut();
+            if control_inputs.is_empty() && feedback_outputs.is_empty() {
+                priorities.remove(instance_id);
+            } else {
+                priorities.insert(*instance_id, priority);
+            }
+        }
+        previous_feedback_output_owners
+            .symmetric_difference(&self.feedback_output_owners())
+            .filter(|id| *id != instance_id)
+            .copied()
+            .collect()
+    }
+
+    /// Returns all used control inputs along with the instances using them.
+    pub fn control_input_ownerships(&self) -> Vec<DeviceOwnership<DeviceControlInput>> {
+        self.device_ownerships(&self.control_input_usages)
+    }
+
+    /// Returns all used feedback outputs along with the instances using them.
+    pub fn feedback_output_ownerships(&self) -> Vec<DeviceOwnership<DeviceFeedbackOutput>> {
+        self.device_ownerships(&self.feedback_output_usages)
+    }
+
+    pub fn midi_clock_output(&self, dev_id: MidiOutputDeviceId) -> Option<MidiClockSettings> {
+        self.midi_clock_outputs
+            .borrow()
+            .get(&dev_id)
+            .map(|(_, settings)| *settings)
+    }
+
+    /// Returns the instance which has switched on MIDI clock on the given device most recently.
+    pub fn midi_clock_output_owner(&self, dev_id: MidiOutputDeviceId) -> Option<InstanceId> {
+        self.midi_clock_outputs
+            .borrow()
+            .get(&dev_id)
+            .map(|(instance_id, _)| *instance_id)
+    }
+
+    /// Only keeps track of the state. Starting or stopping the actual generation is up to the
+    /// caller.
+    pub fn set_midi_clock_output(
+        &self,
+        dev_id: MidiOutputDeviceId,
+        instance_id: InstanceId,
+        settings: Option<MidiClockSettings>,
+    ) {
+        let mut outputs = self.midi_clock_outputs.borrow_mut();
+        if let Some(s) = settings {
+            outputs.insert(dev_id, (instance_id, s));
+        } else {
+            outputs.remove(&dev_id);
+        }
+    }
+
+    pub fn register_instance_feedback_event_sender(
+        &self,
+        sender: crossbeam_channel::Sender<InstanceStateChanged>,
+    ) {
+        self.instance_feedback_event_senders
+            .borrow_mut()
+            .push(sender);
+    }
+
+    pub fn with_shared_clip_matrix<R>(
+        &self,
+        id: &ClipMatrixId,
+        f: impl FnOnce(&ClipMatrix) -> R,
+    ) -> R {
+        let mut matrices = self.shared_clip_matrices.borrow_mut();
+        f(matrices.entry(id.clone()).or_default())
+    }
+
+    /// Don't use this for operations that cause clip change events, they would not be broadcast.
+    pub fn modify_shared_clip_matrix<R>(
+        &self,
+        id: &ClipMatrixId,
+        f: impl FnOnce(&mut ClipMatrix) -> R,
+    ) -> R {
+        let mut matrices = self.shared_clip_matrices.borrow_mut();
+        f(matrices.entry(id.clone()).or_default())
+    }
+
+    pub fn modify_shared_clip_slot(
+        &self,
+        id: &ClipMatrixId,
+        slot_index: usize,
+        op: impl FnOnce(&mut ClipSlot) -> Result<ClipChangedEvent, &'static str>,
+    ) -> Result<(), &'static str> {
+        let event = self.modify_shared_clip_matrix(id, |m| op(m.get_slot_mut(slot_index)?))?;
+        self.broadcast_clip_changed_event(id, slot_index, event);
+        Ok(())
+    }
+
+    pub fn modify_shared_clip_matrix_slots(
+        &self,
+        id: &ClipMatrixId,
+        op: impl FnOnce(&mut ClipMatrix) -> Result<Vec<(usize, ClipChangedEvent)>, &'static str>,
+    ) -> Result<(), &'static str> {
+        let events = self.modify_shared_clip_matrix(id, op)?;
+        for (slot_index, event) in events {
+            self.broadcast_clip_changed_event(id, slot_index, event);
         }
+        Ok(())
+    }
+
+    pub fn process_shared_clip_transport_change(
+        &self,
+        id: &ClipMatrixId,
+        new_play_state: PlayState,
+    ) {
+        let events =
+            self.modify_shared_clip_matrix(id, |m| m.process_transport_change(new_play_state));
+        for (slot_index, event) in events {
+            self.broadcast_clip_changed_event(id, slot_index, event);
+        }
+    }
+
+    /// Should be called once per main loop cycle (not once per instance).
+    pub fn poll_shared_clip_matrices(&self) {
+        let events: Vec<_> = {
+            let mut matrices = self.shared_clip_matrices.borrow_mut();
+            matrices
+                .iter_mut()
+                .flat_map(|(id, m)| {
+                    let mut events: Vec<_> = (0..m.slot_count())
+                        .filter_map(|i| Some((id.clone(), i, m.poll_slot(i)?)))
+                        .collect();
+                    events.extend(
+                        m.process_follow_actions()
+                            .into_iter()
+                            .map(|(i, e)| (id.clone(), i, e)),
+                    );
+                    events
+                })
+                .collect()
+        };
+        for (id, slot_index, event) in events {
+            self.broadcast_clip_changed_event(&id, slot_index, event);
+        }
+    }
+
+    /// Returns the contents of all shared clip matrices which are filled or have a custom size.
+    pub fn shared_clip_matrix_contents(&self) -> HashMap<ClipMatrixId, ClipMatrixContent> {
+        self.shared_clip_matrices
+            .borrow()
+            .iter()
+            .filter(|(_, m)| !m.is_empty() || m.size() != ClipMatrixSize::default())
+            .map(|(id, m)| (id.clone(), m.content()))
+            .collect()
+    }
+
+    pub fn shared_clip_matrix_sizes(&self) -> Vec<(ClipMatrixId, ClipMatrixSize)> {
+        let mut sizes: Vec<_> = self
+            .shared_clip_matrices
+            .borrow()
+            .iter()
+            .map(|(id, m)| (id.clone(), m.size()))
+            .collect();
+        sizes.sort_by(|(a, _), (b, _)| a.get().cmp(b.get()));
+        sizes
+    }
+
+    /// Slot indexes change, so clip targets of all instances need to be resolved again.
+    pub fn resize_shared_clip_matrix(
+        &self,
+        id: &ClipMatrixId,
+        size: ClipMatrixSize,
+    ) -> Result<(), &'static str> {
+        self.modify_shared_clip_matrix(id, |m| m.resize(size))
+    }
+
+    /// Loads the given content into the shared clip matrix unless another instance has already
+    /// filled it.
+    pub fn load_shared_clip_matrix_if_empty(
+        &self,
+        id: &ClipMatrixId,
+        content: ClipMatrixContent,
+        project: Option<Project>,
+    ) -> Result<(), &'static str> {
+        let events = self.modify_shared_clip_matrix(id, |m| {
+            if m.is_empty() {
+                m.load(content, project)
+            } else {
+                Ok(vec![])
+            }
+        })?;
+        for (slot_index, event) in events {
+            self.broadcast_clip_changed_event(id, slot_index, event);
+        }
+        Ok(())
+    }
+
+    fn broadcast_clip_changed_event(
+        &self,
+        id: &ClipMatrixId,
+        slot_index: usize,
+        event: ClipChangedEvent,
+    ) {
+        self.instance_feedback_event_senders
+            .borrow_mut()
+            .retain(|sender| {
+                let instance_event = clip_changed_event(Some(id), slot_index, event.clone());
+                !matches!(
+                    sender.try_send(instance_event),
+                    Err(TrySendError::Disconnected(_))
+                )
+            });
     }
 
     pub(super) fn set_last_touched_target(&self, target: ReaperTarget) {
@@ -104,50 +328,106 @@ impl BackboneState {
         device: D,
         usages: &RefCell<HashMap<D, HashSet<InstanceId>>>,
     ) -> bool {
-        let upper_floor_instances = self.upper_floor_instances.borrow();
-        if upper_floor_instances.is_empty() || upper_floor_instances.contains(instance_id) {
-            // There's no instance living on a higher floor.
-            true
+        let usages = usages.borrow();
+        if let Some(instances) = usages.get(&device) {
+            let own_priority = self.instance_priority(instance_id);
+            // Allow usage only if no other instance using this device has a higher priority.
+            instances
+                .iter()
+                .all(|id| self.instance_priority(id) <= own_priority)
         } else {
-            // There's at least one instance living on a higher floor and it's not ours.
-            let usages = usages.borrow();
-            if let Some(instances) = usages.get(&device) {
-                if instances.len() <= 1 {
-                    // It's just us using this device (or nobody, but shouldn't happen).
-                    true
-                } else {
-                    // Other instances use this device as well.
-                    // Allow usage only if none of these instances are on the upper floor.
-                    !instances
-                        .iter()
-                        .any(|id| upper_floor_instances.contains(id))
-                }
-            } else {
-                // No instance using this device (shouldn't happen because at least we use it).
-                true
-            }
+            // No instance using this device (shouldn't happen because at least we use it).
+            true
         }
     }
+
+    fn feedback_output_owners(&self) -> HashSet<InstanceId> {
+        self.feedback_output_ownerships()
+            .into_iter()
+            .flat_map(|o| o.users)
+            .filter(|u| u.is_owner)
+            .map(|u| u.instance_id)
+            .collect()
+    }
+
+    fn instance_priority(&self, instance_id: &InstanceId) -> InstancePriority {
+        self.instance_priorities
+            .borrow()
+            .get(instance_id)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    fn device_ownerships<D: Copy + Eq + Hash>(
+        &self,
+        usages: &RefCell<HashMap<D, HashSet<InstanceId>>>,
+    ) -> Vec<DeviceOwnership<D>> {
+        usages
+            .borrow()
+            .iter()
+            .filter(|(_, instances)| !instances.is_empty())
+            .map(|(dev, instances)| {
+                let max_priority = instances
+                    .iter()
+                    .map(|id| self.instance_priority(id))
+                    .max()
+                    .unwrap_or_default();
+                let mut users: Vec<_> = instances
+                    .iter()
+                    .map(|id| {
+                        let priority = self.instance_priority(id);
+                        DeviceUser {
+                            instance_id: *id,
+                            priority,
+                            is_owner: priority == max_priority,
+                        }
+                    })
+                    .collect();
+                users.sort_by(|a, b| {
+                    b.priority
+                        .cmp(&a.priority)
+                        .then(a.instance_id.cmp(&b.instance_id))
+                });
+                DeviceOwnership {
+                    device: *dev,
+                    users,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Decides which instance owns a device if multiple instances use it. Higher wins.
+pub type InstancePriority = i32;
+
+/// A device which is used by at least one instance.
+#[derive(Clone, Debug)]
+pub struct DeviceOwnership<D> {
+    pub device: D,
+    /// Sorted by priority, highest first.
+    pub users: Vec<DeviceUser>,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct DeviceUser {
+    pub instance_id: InstanceId,
+    pub priority: InstancePriority,
+    /// All users with the highest priority own the device together.
+    pub is_owner: bool,
 }
 
-/// Returns `true` if there was an actual change.
 fn update_io_usage<D: Eq + Hash + Copy>(
     usages: &mut HashMap<D, HashSet<InstanceId>>,
     instance_id: &InstanceId,
-    device: Option<D>,
-) -> bool {
-    let mut previously_used_device: Option<D> = None;
-    for (dev, ids) in usages.iter_mut() {
-        let was_removed = ids.remove(instance_id);
-        if was_removed {
-            previously_used_device = Some(*dev);
-        }
+    devices: impl IntoIterator<Item = D>,
+) {
+    for ids in usages.values_mut() {
+        ids.remove(instance_id);
     }
-    if let Some(dev) = device {
+    for dev in devices {
         usages
             .entry(dev)
             .or_default()
             .insert(instance_id.to_owned());
     }
-    device != previously_used_device
 }
@@ -0,0 +1,121 @@
+use helgoboss_learn::{UnitValue, BASE_EPSILON};
+use serde::{Deserialize, Serialize};
+
+/// Resolution of the beat change events, in ticks per beat.
+///
+/// Fine enough to let the shortest blink division switch on and off in time.
+pub const BEAT_TICKS_PER_BEAT: u32 = 4;
+
+/// Makes the feedback of a mapping blink in sync with the project tempo while the target has a
+/// certain value (e.g. 75% = clip scheduled to play).
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct BeatBlinkSettings {
+    pub target_value: UnitValue,
+    pub division: BlinkDivision,
+}
+
+impl BeatBlinkSettings {
+    pub fn applies_to(&self, target_value: UnitValue) -> bool {
+        (target_value.get() - self.target_value.get()).abs() <= BASE_EPSILON
+    }
+}
+
+/// Length of one blink period (on + off).
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlinkDivision {
+    HalfBeat,
+    Beat,
+    TwoBeats,
+}
+
+impl Default for BlinkDivision {
+    fn default() -> Self {
+        Self::Beat
+    }
+}
+
+impl BlinkDivision {
+    pub fn length_in_beats(self) -> f64 {
+        use BlinkDivision::*;
+        match self {
+            HalfBeat => 0.5,
+            Beat => 1.0,
+            TwoBeats => 2.0,
+        }
+    }
+
+    /// The feedback is on in the first half of each period (so it lights up exactly on the beat)
+    /// and off in the second half.
+    pub fn is_off_phase(self, full_beats: f64) -> bool {
+        let half_periods = (full_beats * 2.0 / self.length_in_beats()).floor() as i64;
+        half_periods.rem_euclid(2) == 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn beat_phases() {
+        // Given
+        let division = BlinkDivision::Beat;
+        // When
+        // Then
+        assert!(!division.is_off_phase(0.0));
+        assert!(!division.is_off_phase(0.25));
+        assert!(division.is_off_phase(0.5));
+        assert!(division.is_off_phase(0.75));
+        assert!(!division.is_off_phase(1.0));
+        assert!(!division.is_off_phase(7.25));
+    }
+
+    #[test]
+    fn half_beat_phases() {
+        // Given
+        let division = BlinkDivision::HalfBeat;
+        // When
+        // Then
+        assert!(!division.is_off_phase(0.0));
+        assert!(division.is_off_phase(0.25));
+        assert!(!division.is_off_phase(0.5));
+        assert!(division.is_off_phase(0.75));
+    }
+
+    #[test]
+    fn two_beats_phases() {
+        // Given
+        let division = BlinkDivision::TwoBeats;
+        // When
+        // Then
+        assert!(!division.is_off_phase(0.75));
+        assert!(division.is_off_phase(1.0));
+        assert!(division.is_off_phase(1.75));
+        assert!(!division.is_off_phase(2.0));
+    }
+
+    #[test]
+    fn negative_beats() {
+        // Given
+        let division = BlinkDivision::Beat;
+        // When
+        // Then
+        assert!(division.is_off_phase(-0.25));
+        assert!(!division.is_off_phase(-0.75));
+    }
+
+    #[test]
+    fn applies_to() {
+        // Given
+        let settings = BeatBlinkSettings {
+            target_value: UnitValue::new(0.75),
+            division: BlinkDivision::Beat,
+        };
+        // When
+        // Then
+        assert!(settings.applies_to(UnitValue::new(0.75)));
+        assert!(!settings.applies_to(UnitValue::new(1.0)));
+        assert!(!settings.applies_to(UnitValue::MIN));
+    }
+}
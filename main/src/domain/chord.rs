@@ -0,0 +1,443 @@
+use crate::domain::ScaleQuantizer;
+use derive_more::Display;
+use enum_iterator::IntoEnumIterator;
+use helgoboss_learn::AbsoluteValue;
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// Number of notes of the largest chord shape.
+const MAX_CHORD_NOTE_COUNT: usize = 4;
+
+/// Notes which the "Send MIDI" target generates in addition to the root note.
+#[derive(
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    Debug,
+    Serialize,
+    Deserialize,
+    IntoEnumIterator,
+    TryFromPrimitive,
+    IntoPrimitive,
+    Display,
+)]
+#[repr(usize)]
+pub enum ChordShape {
+    #[serde(rename = "major")]
+    #[display(fmt = "Major")]
+    Major,
+    #[serde(rename = "minor")]
+    #[display(fmt = "Minor")]
+    Minor,
+    #[serde(rename = "diminished")]
+    #[display(fmt = "Diminished")]
+    Diminished,
+    #[serde(rename = "augmented")]
+    #[display(fmt = "Augmented")]
+    Augmented,
+    #[serde(rename = "sus2")]
+    #[display(fmt = "Sus2")]
+    Sus2,
+    #[serde(rename = "sus4")]
+    #[display(fmt = "Sus4")]
+    Sus4,
+    #[serde(rename = "major7")]
+    #[display(fmt = "Major 7")]
+    Major7,
+    #[serde(rename = "minor7")]
+    #[display(fmt = "Minor 7")]
+    Minor7,
+    #[serde(rename = "dominant7")]
+    #[display(fmt = "Dominant 7")]
+    Dominant7,
+    #[serde(rename = "power")]
+    #[display(fmt = "Power (root + fifth)")]
+    Power,
+    #[serde(rename = "octave")]
+    #[display(fmt = "Octave")]
+    Octave,
+}
+
+impl Default for ChordShape {
+    fn default() -> Self {
+        Self::Major
+    }
+}
+
+impl ChordShape {
+    /// Semitone offsets from the root note, including the root note itself.
+    fn intervals(&self) -> &'static [u32] {
+        use ChordShape::*;
+        match self {
+            Major => &[0, 4, 7],
+            Minor => &[0, 3, 7],
+            Diminished => &[0, 3, 6],
+            Augmented => &[0, 4, 8],
+            Sus2 => &[0, 2, 7],
+            Sus4 => &[0, 5, 7],
+            Major7 => &[0, 4, 7, 11],
+            Minor7 => &[0, 3, 7, 10],
+            Dominant7 => &[0, 4, 7, 10],
+            Power => &[0, 7],
+            Octave => &[0, 12],
+        }
+    }
+
+    /// Returns the notes of this chord built on the given root note, in ascending order.
+    ///
+    /// Notes above `max_note` are left out.
+    pub fn notes(&self, root: u32, max_note: u32) -> impl Iterator<Item = u32> {
+        self.intervals()
+            .iter()
+            .map(move |i| root + i)
+            .filter(move |n| *n <= max_note)
+    }
+}
+
+/// Interprets a control value as root note for chord mode and arpeggiator.
+///
+/// Returns `None` if the sounding notes should be switched off. This is decided before snapping
+/// the value to the scale: A zero value (e.g. from a note-on with velocity zero) must release the
+/// notes even if the nearest note in the scale is not zero.
+pub fn root_note(
+    value: AbsoluteValue,
+    quantizer: Option<ScaleQuantizer>,
+    max_note: u32,
+) -> Option<u32> {
+    if value.to_unit_value().is_zero() {
+        return None;
+    }
+    let value = match quantizer {
+        None => value,
+        Some(q) => q.quantize(value, max_note),
+    };
+    let note = match value {
+        AbsoluteValue::Continuous(v) => (v.get() * max_note as f64).round() as u32,
+        AbsoluteValue::Discrete(f) => f.actual(),
+    };
+    Some(note)
+}
+
+/// Notes of the chord which is currently sounding.
+///
+/// Keeps the notes themselves instead of the root, so they are switched off correctly even if the
+/// chord shape has changed in the meantime. Doesn't allocate.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub struct HeldChord {
+    notes: [u32; MAX_CHORD_NOTE_COUNT],
+    count: usize,
+}
+
+impl HeldChord {
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Remembers the given notes as sounding (in addition to the ones sounding already).
+    pub fn hold(&mut self, notes: impl Iterator<Item = u32>) {
+        for note in notes {
+            if self.count == MAX_CHORD_NOTE_COUNT {
+                break;
+            }
+            self.notes[self.count] = note;
+            self.count += 1;
+        }
+    }
+
+    /// Forgets all sounding notes and returns them, so they can be switched off.
+    pub fn release(&mut self) -> impl Iterator<Item = u32> {
+        let released = std::mem::take(self);
+        (0..released.count).map(move |i| released.notes[i])
+    }
+
+    /// Takes over the notes which are still sounding from a previous incarnation of the target,
+    /// e.g. after the mappings have been resynced. Otherwise they would never be switched off.
+    pub fn take_over(&mut self, previous: &mut HeldChord) {
+        let notes = previous.release();
+        self.hold(notes);
+    }
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    Debug,
+    Serialize,
+    Deserialize,
+    IntoEnumIterator,
+    TryFromPrimitive,
+    IntoPrimitive,
+    Display,
+)]
+#[repr(usize)]
+pub enum ArpeggiatorDirection {
+    #[serde(rename = "up")]
+    #[display(fmt = "Up")]
+    Up,
+    #[serde(rename = "down")]
+    #[display(fmt = "Down")]
+    Down,
+    #[serde(rename = "up-down")]
+    #[display(fmt = "Up/down")]
+    UpDown,
+}
+
+impl Default for ArpeggiatorDirection {
+    fn default() -> Self {
+        Self::Up
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArpeggiatorSettings {
+    pub direction: ArpeggiatorDirection,
+    /// Number of arpeggiator steps per beat (e.g. 4 means sixteenth notes in 4/4).
+    pub steps_per_beat: u32,
+}
+
+impl Default for ArpeggiatorSettings {
+    fn default() -> Self {
+        Self {
+            direction: Default::default(),
+            steps_per_beat: 4,
+        }
+    }
+}
+
+/// A simple tempo-synced arpeggiator.
+///
+/// Doesn't send anything itself. It's polled regularly and tells the consumer which note to
+/// switch off and which one to switch on.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Arpeggiator {
+    settings: ArpeggiatorSettings,
+    /// Notes in ascending order.
+    notes: Vec<u32>,
+    started_at: Option<Instant>,
+    last_step_index: Option<u64>,
+    sounding_note: Option<u32>,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct ArpeggiatorStep {
+    pub note_off: Option<u32>,
+    pub note_on: u32,
+}
+
+impl Arpeggiator {
+    pub fn new(settings: ArpeggiatorSettings) -> Self {
+        Self {
+            settings,
+            notes: vec![],
+            started_at: None,
+            last_step_index: None,
+            sounding_note: None,
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.started_at.is_some()
+    }
+
+    /// Starts arpeggiating the given notes, beginning with the next poll.
+    ///
+    /// Returns the note which is still sounding from a previous run and should be switched off.
+    pub fn start(&mut self, notes: impl Iterator<Item = u32>, now: Instant) -> Option<u32> {
+        self.notes = notes.collect();
+        self.notes.sort_unstable();
+        self.started_at = Some(now);
+        self.last_step_index = None;
+        self.sounding_note.take()
+    }
+
+    /// Returns the note which is still sounding and should be switched off.
+    pub fn stop(&mut self) -> Option<u32> {
+        self.started_at = None;
+        self.last_step_index = None;
+        self.sounding_note.take()
+    }
+
+    /// Returns the next step if it's due.
+    pub fn poll(&mut self, now: Instant, bpm: f64) -> Option<ArpeggiatorStep> {
+        let started_at = self.started_at?;
+        if self.notes.is_empty() || bpm <= 0.0 || self.settings.steps_per_beat == 0 {
+            return None;
+        }
+        let step_duration =
+            Duration::from_secs_f64(60.0 / bpm / self.settings.steps_per_beat as f64);
+        let step_index =
+            (now.duration_since(started_at).as_secs_f64() / step_duration.as_secs_f64()) as u64;
+        if self.last_step_index == Some(step_index) {
+            return None;
+        }
+        self.last_step_index = Some(step_index);
+        let note_on = self.notes[self.note_index(step_index)];
+        let step = ArpeggiatorStep {
+            note_off: self.sounding_note.replace(note_on),
+            note_on,
+        };
+        Some(step)
+    }
+
+    fn note_index(&self, step_index: u64) -> usize {
+        let count = self.notes.len() as u64;
+        use ArpeggiatorDirection::*;
+        let index = match self.settings.direction {
+            Up => step_index % count,
+            Down => count - 1 - step_index % count,
+            UpDown => {
+                if count == 1 {
+                    0
+                } else {
+                    // Don't repeat the highest and lowest note when turning around.
+                    let cycle_length = 2 * (count - 1);
+                    let pos = step_index % cycle_length;
+                    if pos < count {
+                        pos
+                    } else {
+                        cycle_length - pos
+                    }
+                }
+            }
+        };
+        index as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::MusicalScale;
+    use helgoboss_learn::{Fraction, UnitValue};
+
+    #[test]
+    fn chord_notes() {
+        // Then
+        assert_eq!(
+            ChordShape::Minor7.notes(60, 127).collect::<Vec<_>>(),
+            vec![60, 63, 67, 70]
+        );
+        assert_eq!(
+            ChordShape::Major.notes(122, 127).collect::<Vec<_>>(),
+            vec![122, 126]
+        );
+    }
+
+    #[test]
+    fn release_on_zero_even_if_scale_excludes_it() {
+        // Given
+        let quantizer = Some(ScaleQuantizer {
+            scale: MusicalScale::Major,
+            // D major doesn't contain C. Both neighbours are in the scale, so ties are resolved
+            // downwards (note 0 becomes C# because there's no lower note).
+            root: 2,
+        });
+        let zero = AbsoluteValue::Discrete(Fraction::new(0, 127));
+        let c = AbsoluteValue::Discrete(Fraction::new(60, 127));
+        // Then
+        assert_eq!(root_note(zero, quantizer, 127), None);
+        assert_eq!(
+            root_note(AbsoluteValue::Continuous(UnitValue::MIN), quantizer, 127),
+            None
+        );
+        assert_eq!(root_note(c, quantizer, 127), Some(59));
+        assert_eq!(root_note(c, None, 127), Some(60));
+    }
+
+    #[test]
+    fn release_held_chord() {
+        // Given
+        let mut held = HeldChord::default();
+        held.hold(ChordShape::Minor.notes(60, 127));
+        // When
+        let released: Vec<_> = held.release().collect();
+        // Then
+        assert_eq!(released, vec![60, 63, 67]);
+        assert!(held.is_empty());
+        assert_eq!(held.release().count(), 0);
+    }
+
+    #[test]
+    fn take_over_held_chord_after_resync() {
+        // Given
+        let mut previous = HeldChord::default();
+        previous.hold(ChordShape::Major7.notes(48, 127));
+        // When
+        let mut current = HeldChord::default();
+        current.take_over(&mut previous);
+        // Then
+        assert!(previous.is_empty());
+        assert_eq!(current.release().collect::<Vec<_>>(), vec![48, 52, 55, 59]);
+    }
+
+    #[test]
+    fn arpeggiate_up() {
+        // Given
+        let start = Instant::now();
+        let mut arp = Arpeggiator::new(ArpeggiatorSettings {
+            direction: ArpeggiatorDirection::Up,
+            steps_per_beat: 2,
+        });
+        arp.start(ChordShape::Major.notes(60, 127), start);
+        // Then
+        // At 120 bpm and 2 steps per beat, one step takes 250ms.
+        assert_eq!(
+            arp_poll(&mut arp, start, 0),
+            Some(ArpeggiatorStep {
+                note_off: None,
+                note_on: 60
+            })
+        );
+        assert_eq!(arp_poll(&mut arp, start, 100), None);
+        assert_eq!(
+            arp_poll(&mut arp, start, 250),
+            Some(ArpeggiatorStep {
+                note_off: Some(60),
+                note_on: 64
+            })
+        );
+        assert_eq!(arp_poll(&mut arp, start, 500).map(|s| s.note_on), Some(67));
+        assert_eq!(arp_poll(&mut arp, start, 750).map(|s| s.note_on), Some(60));
+    }
+
+    #[test]
+    fn arpeggiate_up_down() {
+        // Given
+        let start = Instant::now();
+        let mut arp = Arpeggiator::new(ArpeggiatorSettings {
+            direction: ArpeggiatorDirection::UpDown,
+            steps_per_beat: 2,
+        });
+        arp.start(ChordShape::Major.notes(60, 127), start);
+        // When
+        let notes: Vec<_> = (0..6)
+            .filter_map(|i| arp_poll(&mut arp, start, i * 250).map(|s| s.note_on))
+            .collect();
+        // Then
+        assert_eq!(notes, vec![60, 64, 67, 64, 60, 64]);
+    }
+
+    #[test]
+    fn stop_releases_sounding_note() {
+        // Given
+        let start = Instant::now();
+        let mut arp = Arpeggiator::new(Default::default());
+        arp.start(ChordShape::Power.notes(48, 127), start);
+        arp_poll(&mut arp, start, 0);
+        // When
+        let released = arp.stop();
+        // Then
+        assert_eq!(released, Some(48));
+        assert!(!arp.is_running());
+        assert_eq!(arp_poll(&mut arp, start, 1000), None);
+    }
+
+    fn arp_poll(arp: &mut Arpeggiator, start: Instant, millis: u64) -> Option<ArpeggiatorStep> {
+        arp.poll(start + Duration::from_millis(millis), 120.0)
+    }
+}
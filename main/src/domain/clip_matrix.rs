@@ -0,0 +1,636 @@
+use crate::domain::{
+    BackboneState, ClipChangedEvent, ClipPlayState, ClipSlot, DueFollowAction,
+    InstanceStateChanged, QualifiedSlotDescriptor, SharedInstanceState, SlotContent, SlotFades,
+    SlotFollowAction, SlotFollowActionType, SlotPlayOptions, SlotStopQuantization,
+};
+use derive_more::Display;
+use rand::seq::SliceRandom;
+use reaper_high::{Item, Project, Track};
+use reaper_medium::PlayState;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+pub const MAX_CLIP_MATRIX_ROWS: usize = 16;
+pub const MAX_CLIP_MATRIX_COLUMNS: usize = 16;
+
+/// Number of rows and columns of a clip matrix.
+///
+/// The default is one row with 8 columns, which corresponds to the 8 slots that ReaLearn had
+/// before the matrix became configurable.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClipMatrixSize {
+    pub rows: usize,
+    pub columns: usize,
+}
+
+impl Default for ClipMatrixSize {
+    fn default() -> Self {
+        Self {
+            rows: 1,
+            columns: 8,
+        }
+    }
+}
+
+impl ClipMatrixSize {
+    pub fn new(rows: usize, columns: usize) -> Result<Self, &'static str> {
+        if !(1..=MAX_CLIP_MATRIX_ROWS).contains(&rows) {
+            return Err("invalid number of clip matrix rows");
+        }
+        if !(1..=MAX_CLIP_MATRIX_COLUMNS).contains(&columns) {
+            return Err("invalid number of clip matrix columns");
+        }
+        Ok(Self { rows, columns })
+    }
+
+    pub fn slot_count(&self) -> usize {
+        self.rows * self.columns
+    }
+
+    /// Slots are numbered row by row.
+    pub fn slot_index(&self, address: ClipSlotAddress) -> Option<usize> {
+        if address.row >= self.rows || address.column >= self.columns {
+            return None;
+        }
+        Some(address.row * self.columns + address.column)
+    }
+
+    pub fn slot_address(&self, slot_index: usize) -> Option<ClipSlotAddress> {
+        if slot_index >= self.slot_count() {
+            return None;
+        }
+        let address = ClipSlotAddress {
+            row: slot_index / self.columns,
+            column: slot_index % self.columns,
+        };
+        Some(address)
+    }
+
+    pub fn slot_addresses(&self) -> impl Iterator<Item = ClipSlotAddress> + '_ {
+        (0..self.slot_count()).filter_map(move |i| self.slot_address(i))
+    }
+}
+
+impl fmt::Display for ClipMatrixSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}x{}", self.rows, self.columns)
+    }
+}
+
+impl FromStr for ClipMatrixSize {
+    type Err = &'static str;
+
+    /// Parses sizes such as `8x8` (rows x columns).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (rows, columns) = s
+            .split_once(|c| c == 'x' || c == 'X')
+            .ok_or("clip matrix size must have the form ROWSxCOLUMNS")?;
+        let rows = rows.trim().parse().map_err(|_| "invalid number of rows")?;
+        let columns = columns
+            .trim()
+            .parse()
+            .map_err(|_| "invalid number of columns")?;
+        Self::new(rows, columns)
+    }
+}
+
+/// Position of a slot within a clip matrix (zero-based).
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct ClipSlotAddress {
+    pub row: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for ClipSlotAddress {
+    /// Displays the one-based row and column, e.g. `2/3`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.row + 1, self.column + 1)
+    }
+}
+
+/// Size and filled slots of a clip matrix as saved in the session.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct ClipMatrixContent {
+    pub size: ClipMatrixSize,
+    pub slots: Vec<QualifiedSlotDescriptor>,
+}
+
+/// Identifies a clip matrix which is shared among all ReaLearn instances.
+///
+/// Clip targets without a matrix ID address the instance's own clip matrix.
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Display, Serialize, Deserialize)]
+pub struct ClipMatrixId(String);
+
+impl ClipMatrixId {
+    /// Returns `None` if the given string is empty.
+    pub fn from_str_opt(value: &str) -> Option<Self> {
+        let trimmed = value.trim();
+        if trimmed.is_empty() {
+            None
+        } else {
+            Some(Self(trimmed.to_owned()))
+        }
+    }
+
+    pub fn get(&self) -> &str {
+        &self.0
+    }
+}
+
+/// A grid of clip slots with a configurable number of rows and columns.
+///
+/// Slots are addressed by index, see [`ClipMatrixSize::slot_index`]. Doesn't send any events
+/// itself. Operations return the change events and it's the job of the owner to forward them to
+/// the interested instances.
+#[derive(Debug)]
+pub struct ClipMatrix {
+    size: ClipMatrixSize,
+    clip_slots: Vec<ClipSlot>,
+}
+
+impl Default for ClipMatrix {
+    fn default() -> Self {
+        let size = ClipMatrixSize::default();
+        Self {
+            size,
+            clip_slots: create_slots(size),
+        }
+    }
+}
+
+impl ClipMatrix {
+    pub fn size(&self) -> ClipMatrixSize {
+        self.size
+    }
+
+    pub fn slot_count(&self) -> usize {
+        self.clip_slots.len()
+    }
+
+    pub fn slot_index(&self, address: ClipSlotAddress) -> Result<usize, &'static str> {
+        self.size
+            .slot_index(address)
+            .ok_or("slot doesn't exist in clip matrix")
+    }
+
+    /// Changes the number of rows and columns.
+    ///
+    /// Each slot keeps its row and column (and therefore its content and play state). Fails
+    /// if a filled slot would be outside of the new size.
+    pub fn resize(&mut self, size: ClipMatrixSize) -> Result<(), &'static str> {
+        let size = ClipMatrixSize::new(size.rows, size.columns)?;
+        let old_size = self.size;
+        let new_indexes: Vec<_> = (0..self.clip_slots.len())
+            .map(|i| size.slot_index(old_size.slot_address(i)?))
+            .collect();
+        let would_lose_slot = self
+            .clip_slots
+            .iter()
+            .zip(new_indexes.iter())
+            .any(|(slot, new_index)| slot.is_filled() && new_index.is_none());
+        if would_lose_slot {
+            return Err("clip matrix would lose filled slots");
+        }
+        let mut new_slots = create_slots(size);
+        for (slot, new_index) in self.clip_slots.iter_mut().zip(new_indexes) {
+            if let Some(i) = new_index {
+                new_slots[i] = std::mem::take(slot);
+            }
+        }
+        self.size = size;
+        self.clip_slots = new_slots;
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        !self.clip_slots.iter().any(|s| s.is_filled())
+    }
+
+    pub fn process_transport_change(
+        &mut self,
+        new_play_state: PlayState,
+    ) -> Vec<(usize, ClipChangedEvent)> {
+        self.clip_slots
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(i, slot)| {
+                let event = slot.process_transport_change(new_play_state).ok()??;
+                Some((i, event))
+            })
+            .collect()
+    }
+
+    /// Detects clips that are finished playing and invokes a stop feedback event if not looped.
+    pub fn poll_slot(&mut self, slot_index: usize) -> Option<ClipChangedEvent> {
+        self.clip_slots
+            .get_mut(slot_index)
+            .expect("no such slot")
+            .poll()
+    }
+
+    /// Executes the follow actions of all slots whose clips have been played often enough.
+    ///
+    /// Should be called after polling all slots.
+    pub fn process_follow_actions(&mut self) -> Vec<(usize, ClipChangedEvent)> {
+        let due_actions: Vec<_> = self
+            .clip_slots
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(i, s)| Some((i, s.take_due_follow_action()?)))
+            .collect();
+        due_actions
+            .into_iter()
+            .filter_map(|(i, action)| self.execute_follow_action(i, action).ok())
+            .flatten()
+            .collect()
+    }
+
+    fn execute_follow_action(
+        &mut self,
+        slot_index: usize,
+        action: DueFollowAction,
+    ) -> Result<Vec<(usize, ClipChangedEvent)>, &'static str> {
+        use SlotFollowActionType::*;
+        let next_slot_index = match action.r#type {
+            Next => self.neighbor_filled_slot_index(slot_index, true),
+            Previous => self.neighbor_filled_slot_index(slot_index, false),
+            Random => self.random_filled_slot_index(slot_index),
+            Stop => {
+                let event = self
+                    .get_slot_mut(slot_index)?
+                    .stop(SlotStopQuantization::Immediately)?;
+                return Ok(vec![(slot_index, event)]);
+            }
+        }
+        .ok_or("no filled slot to follow")?;
+        let mut events = vec![];
+        if action.track.is_none() {
+            // Playing the next slot only stops slots on the same track.
+            let event = self
+                .get_slot_mut(slot_index)?
+                .stop(SlotStopQuantization::Immediately)?;
+            events.push((slot_index, event));
+        }
+        events.extend(self.play_slot(next_slot_index, action.track, action.options)?);
+        Ok(events)
+    }
+
+    /// Wraps around. Returns the given slot itself if it's the only filled one.
+    fn neighbor_filled_slot_index(&self, slot_index: usize, forward: bool) -> Option<usize> {
+        let count = self.clip_slots.len();
+        (1..=count)
+            .map(|distance| {
+                if forward {
+                    (slot_index + distance) % count
+                } else {
+                    (slot_index + count - distance) % count
+                }
+            })
+            .find(|i| self.clip_slots[*i].is_filled())
+    }
+
+    /// Prefers other slots. Returns the given slot itself if it's the only filled one.
+    fn random_filled_slot_index(&self, slot_index: usize) -> Option<usize> {
+        let others: Vec<_> = (0..self.clip_slots.len())
+            .filter(|i| *i != slot_index && self.clip_slots[*i].is_filled())
+            .collect();
+        others
+            .choose(&mut rand::thread_rng())
+            .copied()
+            .or_else(|| Some(slot_index).filter(|i| self.clip_slots[*i].is_filled()))
+    }
+
+    /// Starts playing the given slot on the given track.
+    ///
+    /// The track determines the column. Other slots playing in the same column are stopped (at the
+    /// same time the new slot starts), so there's at most one playing clip per column.
+    pub fn play_slot(
+        &mut self,
+        slot_index: usize,
+        track: Option<Track>,
+        options: SlotPlayOptions,
+    ) -> Result<Vec<(usize, ClipChangedEvent)>, &'static str> {
+        // Not using the stop quantization here because the other slot should stop exactly when
+        // this one starts.
+        let quantization = if options.next_bar {
+            SlotStopQuantization::EndOfClip
+        } else {
+            SlotStopQuantization::Immediately
+        };
+        let mut events = match &track {
+            None => vec![],
+            Some(t) => self.stop_column_except(t, Some(slot_index), quantization),
+        };
+        let event = self.get_slot_mut(slot_index)?.play(track, options)?;
+        events.push((slot_index, event));
+        Ok(events)
+    }
+
+    /// Stops all slots playing in the column of the given track.
+    pub fn stop_column(
+        &mut self,
+        track: &Track,
+        quantization: SlotStopQuantization,
+    ) -> Vec<(usize, ClipChangedEvent)> {
+        self.stop_column_except(track, None, quantization)
+    }
+
+    /// Stops all playing slots, no matter in which column.
+    pub fn stop_all(
+        &mut self,
+        quantization: SlotStopQuantization,
+    ) -> Vec<(usize, ClipChangedEvent)> {
+        self.clip_slots
+            .iter_mut()
+            .enumerate()
+            .filter(|(_, s)| s.playing_track().is_some())
+            .filter_map(|(i, s)| Some((i, s.stop(quantization).ok()?)))
+            .collect()
+    }
+
+    /// Starts playing all filled slots in the given row (a scene) at once.
+    ///
+    /// Each slot is played with the track and options it was played with most recently. Slots
+    /// which haven't been played yet are played with the given fallback options.
+    pub fn play_row(
+        &mut self,
+        row: usize,
+        fallback_options: SlotPlayOptions,
+    ) -> Result<Vec<(usize, ClipChangedEvent)>, &'static str> {
+        let slot_indexes: Vec<_> = self.row_slot_indexes(row)?.collect();
+        let mut events = vec![];
+        for i in slot_indexes {
+            let slot = &self.clip_slots[i];
+            if !slot.is_filled() {
+                continue;
+            }
+            let (track, options) = slot
+                .last_play_settings()
+                .unwrap_or((None, fallback_options));
+            if let Ok(slot_events) = self.play_slot(i, track, options) {
+                events.extend(slot_events);
+            }
+        }
+        Ok(events)
+    }
+
+    /// Returns `true` if at least one slot in the given row is playing.
+    pub fn row_is_playing(&self, row: usize) -> bool {
+        match self.row_slot_indexes(row) {
+            Ok(mut indexes) => {
+                indexes.any(|i| self.clip_slots[i].play_state() == ClipPlayState::Playing)
+            }
+            Err(_) => false,
+        }
+    }
+
+    fn row_slot_indexes(&self, row: usize) -> Result<impl Iterator<Item = usize>, &'static str> {
+        if row >= self.size.rows {
+            return Err("row doesn't exist in clip matrix");
+        }
+        let columns = self.size.columns;
+        Ok((0..columns).map(move |column| row * columns + column))
+    }
+
+    pub fn column_is_playing(&self, track: &Track) -> bool {
+        self.clip_slots
+            .iter()
+            .any(|s| s.playing_track() == Some(track))
+    }
+
+    fn stop_column_except(
+        &mut self,
+        track: &Track,
+        except_slot_index: Option<usize>,
+        quantization: SlotStopQuantization,
+    ) -> Vec<(usize, ClipChangedEvent)> {
+        self.clip_slots
+            .iter_mut()
+            .enumerate()
+            .filter(|(i, s)| Some(*i) != except_slot_index && s.playing_track() == Some(track))
+            .filter_map(|(i, s)| Some((i, s.stop(quantization).ok()?)))
+            .collect()
+    }
+
+    pub fn content(&self) -> ClipMatrixContent {
+        ClipMatrixContent {
+            size: self.size,
+            slots: self.filled_slot_descriptors(),
+        }
+    }
+
+    fn filled_slot_descriptors(&self) -> Vec<QualifiedSlotDescriptor> {
+        self.clip_slots
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| s.is_filled())
+            .map(|(i, s)| QualifiedSlotDescriptor {
+                index: i,
+                descriptor: s.descriptor().clone(),
+            })
+            .collect()
+    }
+
+    pub fn load(
+        &mut self,
+        content: ClipMatrixContent,
+        project: Option<Project>,
+    ) -> Result<Vec<(usize, ClipChangedEvent)>, &'static str> {
+        for slot in &mut self.clip_slots {
+            let _ = slot.reset();
+        }
+        self.resize(content.size)?;
+        let mut all_events = vec![];
+        for desc in content.slots {
+            let events = self
+                .get_slot_mut(desc.index)?
+                .load(desc.descriptor, project)?;
+            all_events.extend(events.into_iter().map(|e| (desc.index, e)));
+        }
+        Ok(all_events)
+    }
+
+    pub fn fill_slot_by_user(
+        &mut self,
+        slot_index: usize,
+        content: SlotContent,
+        project: Option<Project>,
+    ) -> Result<(), &'static str> {
+        self.get_slot_mut(slot_index)?
+            .fill_by_user(content, project)
+    }
+
+    pub fn fill_slot_with_item_source(
+        &mut self,
+        slot_index: usize,
+        item: Item,
+    ) -> Result<(), Box<dyn Error>> {
+        self.get_slot_mut(slot_index)?
+            .fill_with_source_from_item(item)
+    }
+
+    pub fn set_slot_tempo(&mut self, slot_index: usize, bpm: f64) -> Result<(), &'static str> {
+        self.get_slot_mut(slot_index)?.set_tempo(bpm)
+    }
+
+    pub fn set_slot_fades(
+        &mut self,
+        slot_index: usize,
+        fades: SlotFades,
+    ) -> Result<(), &'static str> {
+        self.get_slot_mut(slot_index)?.set_fades(fades);
+        Ok(())
+    }
+
+    pub fn set_slot_follow_action(
+        &mut self,
+        slot_index: usize,
+        follow_action: Option<SlotFollowAction>,
+    ) -> Result<(), &'static str> {
+        self.get_slot_mut(slot_index)?
+            .set_follow_action(follow_action);
+        Ok(())
+    }
+
+    /// Embeds the contents of all filled slots in the session data. Returns the number of
+    /// migrated slots.
+    pub fn embed_contents(&mut self, project: Option<Project>) -> Result<usize, &'static str> {
+        let mut count = 0;
+        for slot in &mut self.clip_slots {
+            if slot.embed_content(project)? {
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    /// Writes the embedded contents of all filled slots to external files. Returns the number of
+    /// migrated slots.
+    pub fn externalize_contents(&mut self, project: Project) -> Result<usize, &'static str> {
+        let mut count = 0;
+        for slot in &mut self.clip_slots {
+            if slot.externalize_content(project)? {
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    pub fn get_slot(&self, slot_index: usize) -> Result<&ClipSlot, &'static str> {
+        self.clip_slots.get(slot_index).ok_or("no such slot")
+    }
+
+    pub fn get_slot_mut(&mut self, slot_index: usize) -> Result<&mut ClipSlot, &'static str> {
+        self.clip_slots.get_mut(slot_index).ok_or("no such slot")
+    }
+}
+
+fn create_slots(size: ClipMatrixSize) -> Vec<ClipSlot> {
+    (0..size.slot_count())
+        .map(|_| ClipSlot::default())
+        .collect()
+}
+
+/// Returns the index of the slot at the given address in either the instance's own clip matrix or
+/// the shared one with the given ID.
+pub fn resolve_clip_slot_index(
+    instance_state: &SharedInstanceState,
+    matrix_id: Option<&ClipMatrixId>,
+    address: ClipSlotAddress,
+) -> Result<usize, &'static str> {
+    with_clip_matrix(instance_state, matrix_id, |m| m.slot_index(address))
+}
+
+/// Gives read access to either the instance's own clip matrix or the shared one with the given ID.
+pub fn with_clip_matrix<R>(
+    instance_state: &SharedInstanceState,
+    matrix_id: Option<&ClipMatrixId>,
+    f: impl FnOnce(&ClipMatrix) -> R,
+) -> R {
+    match matrix_id {
+        None => f(instance_state.borrow().clip_matrix()),
+        Some(id) => BackboneState::get().with_shared_clip_matrix(id, f),
+    }
+}
+
+/// Modifies a slot of either the instance's own clip matrix or the shared one with the given ID
+/// and notifies all interested instances about the change.
+pub fn modify_clip_slot(
+    instance_state: &SharedInstanceState,
+    matrix_id: Option<&ClipMatrixId>,
+    slot_index: usize,
+    op: impl FnOnce(&mut ClipSlot) -> Result<ClipChangedEvent, &'static str>,
+) -> Result<(), &'static str> {
+    match matrix_id {
+        None => instance_state.borrow_mut().modify_clip_slot(slot_index, op),
+        Some(id) => BackboneState::get().modify_shared_clip_slot(id, slot_index, op),
+    }
+}
+
+/// Applies an operation which might affect multiple slots to either the instance's own clip matrix
+/// or the shared one with the given ID and notifies all interested instances about the changes.
+pub fn modify_clip_matrix(
+    instance_state: &SharedInstanceState,
+    matrix_id: Option<&ClipMatrixId>,
+    op: impl FnOnce(&mut ClipMatrix) -> Result<Vec<(usize, ClipChangedEvent)>, &'static str>,
+) -> Result<(), &'static str> {
+    match matrix_id {
+        None => instance_state.borrow_mut().modify_clip_matrix(op),
+        Some(id) => BackboneState::get().modify_shared_clip_matrix_slots(id, op),
+    }
+}
+
+/// Lets the instance's own clip matrix or the shared one with the given ID react to a transport
+/// change.
+pub fn process_clip_transport_change(
+    instance_state: &SharedInstanceState,
+    matrix_id: Option<&ClipMatrixId>,
+    new_play_state: PlayState,
+) {
+    match matrix_id {
+        None => instance_state
+            .borrow_mut()
+            .process_transport_change(new_play_state),
+        Some(id) => BackboneState::get().process_shared_clip_transport_change(id, new_play_state),
+    }
+}
+
+pub(super) fn clip_changed_event(
+    matrix_id: Option<&ClipMatrixId>,
+    slot_index: usize,
+    event: ClipChangedEvent,
+) -> InstanceStateChanged {
+    InstanceStateChanged::Clip {
+        matrix_id: matrix_id.cloned(),
+        slot_index,
+        event,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slot_addressing() {
+        // Given
+        let size: ClipMatrixSize = "8x4".parse().unwrap();
+        // Then
+        assert_eq!(size.slot_count(), 32);
+        assert_eq!(
+            size.slot_index(ClipSlotAddress { row: 2, column: 3 }),
+            Some(11)
+        );
+        assert_eq!(size.slot_index(ClipSlotAddress { row: 0, column: 4 }), None);
+        assert_eq!(
+            size.slot_address(11),
+            Some(ClipSlotAddress { row: 2, column: 3 })
+        );
+        assert_eq!(size.slot_address(32), None);
+        assert!("0x8".parse::<ClipMatrixSize>().is_err());
+        assert!("8".parse::<ClipMatrixSize>().is_err());
+    }
+}
@@ -1,5 +1,7 @@
 use crate::base::default_util::is_default;
-use crate::domain::ClipChangedEvent;
+use crate::domain::{ClipChangedEvent, ClipTempo};
+use derive_more::Display;
+use enum_iterator::IntoEnumIterator;
 use enumflags2::BitFlags;
 use helgoboss_learn::{UnitValue, BASE_EPSILON};
 use helgoboss_midi::{controller_numbers, Channel, RawShortMessage, ShortMessageFactory, U7};
@@ -15,10 +17,15 @@ use reaper_medium::{
     SaveStateArgs, SetAvailableArgs, SetFileNameArgs, SetSourceArgs,
 };
 use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
 use std::convert::TryInto;
 use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::hash::{Hash, Hasher};
 use std::path::{Path, PathBuf};
 use std::ptr::{null_mut, NonNull};
+use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -32,6 +39,13 @@ pub struct SlotDescriptor {
     pub repeat: bool,
     #[serde(rename = "content", default, skip_serializing_if = "is_default")]
     pub content: Option<SlotContent>,
+    /// Native tempo of the contained clip. Detected on import, can be overridden by the user.
+    #[serde(rename = "tempo", default, skip_serializing_if = "is_default")]
+    pub tempo: Option<ClipTempo>,
+    #[serde(rename = "fades", default, skip_serializing_if = "is_default")]
+    pub fades: SlotFades,
+    #[serde(rename = "followAction", default, skip_serializing_if = "is_default")]
+    pub follow_action: Option<SlotFollowAction>,
 }
 
 impl Default for SlotDescriptor {
@@ -40,10 +54,141 @@ impl Default for SlotDescriptor {
             volume: ReaperVolumeValue::ZERO_DB,
             repeat: false,
             content: None,
+            tempo: None,
+            fades: Default::default(),
+            follow_action: None,
         }
     }
 }
 
+/// Micro-fades which are applied to audio clips on start, retrigger and immediate stop in order
+/// to prevent clicks when the clip is cut mid-waveform.
+///
+/// A retrigger results in a fade-out of the old playback followed by a fade-in of the new one.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub struct SlotFades {
+    #[serde(rename = "fadeInMillis")]
+    pub fade_in_millis: u32,
+    #[serde(rename = "fadeOutMillis")]
+    pub fade_out_millis: u32,
+}
+
+impl SlotFades {
+    /// Fade-outs are awaited synchronously before suspending playback, so they must be short.
+    pub const MAX_MILLIS: u32 = 50;
+
+    pub fn new(fade_in_millis: u32, fade_out_millis: u32) -> Self {
+        Self {
+            fade_in_millis: fade_in_millis.min(Self::MAX_MILLIS),
+            fade_out_millis: fade_out_millis.min(Self::MAX_MILLIS),
+        }
+    }
+}
+
+impl Default for SlotFades {
+    fn default() -> Self {
+        Self {
+            fade_in_millis: 3,
+            fade_out_millis: 3,
+        }
+    }
+}
+
+/// Lets a slot automatically trigger another slot (or stop) after its clip has been played a
+/// number of times.
+///
+/// A one-shot has been played when it reaches its end. A looped clip has been played whenever it
+/// completes a loop.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub struct SlotFollowAction {
+    #[serde(rename = "type")]
+    pub r#type: SlotFollowActionType,
+    #[serde(rename = "afterPlayCount")]
+    pub after_play_count: u32,
+}
+
+impl SlotFollowAction {
+    fn effective_play_count(&self) -> u32 {
+        self.after_play_count.max(1)
+    }
+}
+
+impl fmt::Display for SlotFollowAction {
+    /// Displays e.g. `next 4`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.r#type, self.effective_play_count())
+    }
+}
+
+impl FromStr for SlotFollowAction {
+    type Err = &'static str;
+
+    /// Parses e.g. `next 4` or just `next` (after one play).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split_whitespace();
+        let r#type = parts.next().ok_or("follow action type missing")?.parse()?;
+        let after_play_count = match parts.next() {
+            None => 1,
+            Some(c) => c.parse().map_err(|_| "invalid play count")?,
+        };
+        if after_play_count == 0 || parts.next().is_some() {
+            return Err("follow action must have the form TYPE [PLAY_COUNT]");
+        }
+        let action = Self {
+            r#type,
+            after_play_count,
+        };
+        Ok(action)
+    }
+}
+
+/// Slots are ordered row by row. "Next" and "previous" wrap around and skip empty slots.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Serialize, Deserialize, IntoEnumIterator)]
+#[serde(rename_all = "camelCase")]
+pub enum SlotFollowActionType {
+    Next,
+    Previous,
+    Random,
+    Stop,
+}
+
+impl SlotFollowActionType {
+    pub fn id(self) -> &'static str {
+        use SlotFollowActionType::*;
+        match self {
+            Next => "next",
+            Previous => "previous",
+            Random => "random",
+            Stop => "stop",
+        }
+    }
+}
+
+impl fmt::Display for SlotFollowActionType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.id())
+    }
+}
+
+impl FromStr for SlotFollowActionType {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::into_enum_iter()
+            .find(|t| t.id().eq_ignore_ascii_case(s))
+            .ok_or("follow action type must be next, previous, random or stop")
+    }
+}
+
+/// A follow action which should be executed now, together with the way the finished clip was
+/// played.
+#[derive(Clone, Debug)]
+pub struct DueFollowAction {
+    pub r#type: SlotFollowActionType,
+    pub track: Option<Track>,
+    pub options: SlotPlayOptions,
+}
+
 impl SlotDescriptor {
     pub fn is_filled(&self) -> bool {
         self.content.is_some()
@@ -57,6 +202,14 @@ pub enum SlotContent {
         #[serde(rename = "file")]
         file: PathBuf,
     },
+    /// Content stored directly in the session data (base64-encoded file content) so that the
+    /// project doesn't depend on external media files.
+    Embedded {
+        #[serde(rename = "fileExtension")]
+        file_extension: String,
+        #[serde(rename = "data")]
+        data: String,
+    },
 }
 
 impl SlotContent {
@@ -64,24 +217,75 @@ impl SlotContent {
         use SlotContent::*;
         match self {
             File { file } => Some(file),
+            Embedded { .. } => None,
         }
     }
 
-    pub fn create_source(&self, project: Option<Project>) -> Result<OwnedSource, &'static str> {
+    pub fn is_embedded(&self) -> bool {
+        matches!(self, SlotContent::Embedded { .. })
+    }
+
+    /// Reads the given media file and creates embedded content from it.
+    pub fn embed_file(file: &Path) -> Result<Self, &'static str> {
+        let bytes = fs::read(file).map_err(|_| "couldn't read clip file")?;
+        let file_extension = file
+            .extension()
+            .ok_or("clip file has no extension")?
+            .to_string_lossy()
+            .to_string();
+        let content = SlotContent::Embedded {
+            file_extension,
+            data: base64::encode(bytes),
+        };
+        Ok(content)
+    }
+
+    /// Returns the absolute path of the file which contains the content.
+    ///
+    /// Embedded content is written to a cache file first because REAPER can only create PCM
+    /// sources from files.
+    fn resolve_file(&self, project: Option<Project>) -> Result<PathBuf, &'static str> {
         match self {
             SlotContent::File { file } => {
-                let absolute_file = if file.is_relative() {
+                if file.is_relative() {
                     project
                         .ok_or("slot source given as relative file but without project")?
                         .make_path_absolute(file)
-                        .ok_or("couldn't make clip source path absolute")?
+                        .ok_or("couldn't make clip source path absolute")
                 } else {
-                    file.clone()
-                };
-                OwnedSource::from_file(&absolute_file, MidiImportBehavior::UsePreference)
+                    Ok(file.clone())
+                }
+            }
+            SlotContent::Embedded {
+                file_extension,
+                data,
+            } => {
+                let mut hasher = DefaultHasher::new();
+                data.hash(&mut hasher);
+                let file_name = format!("{:016x}.{}", hasher.finish(), file_extension);
+                let cache_file = embedded_clip_cache_dir_path().join(file_name);
+                if !cache_file.exists() {
+                    let bytes =
+                        base64::decode(data).map_err(|_| "embedded clip content corrupt")?;
+                    fs::create_dir_all(embedded_clip_cache_dir_path())
+                        .map_err(|_| "couldn't create clip cache directory")?;
+                    fs::write(&cache_file, bytes).map_err(|_| "couldn't write clip cache file")?;
+                }
+                Ok(cache_file)
             }
         }
     }
+
+    pub fn create_source(&self, project: Option<Project>) -> Result<OwnedSource, &'static str> {
+        let absolute_file = self.resolve_file(project)?;
+        OwnedSource::from_file(&absolute_file, MidiImportBehavior::UsePreference)
+    }
+}
+
+fn embedded_clip_cache_dir_path() -> PathBuf {
+    Reaper::get()
+        .resource_path()
+        .join("Data/helgoboss/realearn/clip-cache")
 }
 
 #[derive(Debug)]
@@ -89,6 +293,14 @@ pub struct ClipSlot {
     descriptor: SlotDescriptor,
     register: SharedRegister,
     state: State,
+    follow_action_progress: FollowActionProgress,
+}
+
+#[derive(Debug, Default)]
+struct FollowActionProgress {
+    play_count: u32,
+    last_position: f64,
+    due: Option<DueFollowAction>,
 }
 
 impl Default for ClipSlot {
@@ -99,6 +311,7 @@ impl Default for ClipSlot {
             descriptor,
             register,
             state: State::Empty,
+            follow_action_progress: Default::default(),
         }
     }
 }
@@ -153,6 +366,10 @@ impl ClipSlot {
             return Ok(());
         };
         self.fill_with_source(source)?;
+        // Slots saved before tempo detection was introduced don't have any tempo info yet.
+        if self.descriptor.tempo.is_none() {
+            self.descriptor.tempo = self.detect_tempo(reference_bpm(project));
+        }
         Ok(())
     }
 
@@ -200,6 +417,89 @@ impl ClipSlot {
         self.fill_with_source(source)?;
         // Here it's important to not set the descriptor (change things) unless load was successful.
         self.descriptor.content = Some(content);
+        self.descriptor.tempo = self.detect_tempo(reference_bpm(project));
+        Ok(())
+    }
+
+    /// Uses the beat length of MIDI sources. For all other sources, the tempo is estimated
+    /// assuming that the clip spans whole bars at roughly the reference tempo.
+    fn detect_tempo(&self, reference_bpm: f64) -> Option<ClipTempo> {
+        let guard = self.register.lock().ok()?;
+        let source = guard.src()?.as_ref();
+        let length = source.get_length().ok()?.get();
+        let tempo = match source.get_length_beats() {
+            Some(beats) => ClipTempo::from_beat_count(length, beats.get()),
+            None => ClipTempo::estimate(length, reference_bpm),
+        };
+        tempo.ok()
+    }
+
+    /// Moves the content of this slot into the session data. Returns `false` if the content was
+    /// embedded already or the slot is empty.
+    ///
+    /// The loaded source stays untouched because the audio/MIDI data is the same.
+    pub fn embed_content(&mut self, project: Option<Project>) -> Result<bool, &'static str> {
+        let content = match &self.descriptor.content {
+            Some(c @ SlotContent::File { .. }) => c,
+            _ => return Ok(false),
+        };
+        let file = content.resolve_file(project)?;
+        self.descriptor.content = Some(SlotContent::embed_file(&file)?);
+        Ok(true)
+    }
+
+    /// Writes embedded content of this slot to a file in the recording directory of the given
+    /// project and references it from then on (relative to the project directory if possible).
+    /// Returns `false` if the content is stored in an external file already or the slot is empty.
+    pub fn externalize_content(&mut self, project: Project) -> Result<bool, &'static str> {
+        let (file_extension, data) = match &self.descriptor.content {
+            Some(SlotContent::Embedded {
+                file_extension,
+                data,
+            }) => (file_extension, data),
+            _ => return Ok(false),
+        };
+        let bytes = base64::decode(data).map_err(|_| "embedded clip content corrupt")?;
+        let recording_path = project.recording_path();
+        fs::create_dir_all(&recording_path)
+            .map_err(|_| "couldn't create project recording directory")?;
+        let file_name = format!("clip-{}.{}", nanoid::nanoid!(8), file_extension);
+        let file = recording_path.join(file_name);
+        fs::write(&file, bytes).map_err(|_| "couldn't write clip file")?;
+        let content = SlotContent::File {
+            file: project
+                .make_path_relative_if_in_project_directory(&file)
+                .unwrap_or(file),
+        };
+        self.descriptor.content = Some(content);
+        Ok(true)
+    }
+
+    pub fn fades(&self) -> SlotFades {
+        self.descriptor.fades
+    }
+
+    /// Takes effect immediately, even if the clip is playing.
+    pub fn set_fades(&mut self, fades: SlotFades) {
+        self.descriptor.fades = fades;
+        set_fades_with_guard(&lock(&self.register), fades);
+    }
+
+    pub fn tempo(&self) -> Option<ClipTempo> {
+        self.descriptor.tempo
+    }
+
+    /// Lets the user override the detected native tempo of the contained clip.
+    pub fn set_tempo(&mut self, bpm: f64) -> Result<(), &'static str> {
+        let length = {
+            let guard = lock(&self.register);
+            let source = guard.src().ok_or("no source loaded")?;
+            source
+                .as_ref()
+                .get_length()
+                .map_err(|_| "source has no length")?
+        };
+        self.descriptor.tempo = Some(ClipTempo::from_bpm(length.get(), bpm)?);
         Ok(())
     }
 
@@ -211,6 +511,7 @@ impl ClipSlot {
             r#type: source.get_type(|t| t.to_string()),
             file_name: source.get_file_name(|p| Some(p?.to_owned())),
             length: source.get_length().ok(),
+            tempo: self.descriptor.tempo,
         };
         // TODO-medium This is probably necessary to make sure the mutex is not unlocked before the
         //  PCM source operations are done. How can we solve this in a better way API-wise? On the
@@ -219,13 +520,70 @@ impl ClipSlot {
         Some(info)
     }
 
-    /// Should be called regularly to detect stops.
+    pub fn follow_action(&self) -> Option<SlotFollowAction> {
+        self.descriptor.follow_action
+    }
+
+    pub fn set_follow_action(&mut self, follow_action: Option<SlotFollowAction>) {
+        self.descriptor.follow_action = follow_action;
+        self.follow_action_progress = Default::default();
+    }
+
+    /// Returns the follow action if it's due (only once).
+    pub fn take_due_follow_action(&mut self) -> Option<DueFollowAction> {
+        self.follow_action_progress.due.take()
+    }
+
+    /// Should be called regularly to detect stops and completed loops.
     pub fn poll(&mut self) -> Option<ClipChangedEvent> {
+        // A clip which has been scheduled for stop by the user shouldn't trigger a follow action.
+        let was_playing = self.play_state() == ClipPlayState::Playing;
         let (result, change_events) = self.start_transition().poll(&self.register);
         self.finish_transition(result).ok()?;
+        if was_playing {
+            if let Some(e) = &change_events {
+                self.count_plays(e);
+            }
+        }
         change_events
     }
 
+    fn count_plays(&mut self, event: &ClipChangedEvent) {
+        let progress = &mut self.follow_action_progress;
+        let play_finished = match event {
+            ClipChangedEvent::PlayState(ClipPlayState::Stopped) => true,
+            ClipChangedEvent::ClipPosition(pos) => {
+                // The position jumps back whenever a loop has been completed.
+                let wrapped = pos.get() < progress.last_position;
+                progress.last_position = pos.get();
+                wrapped
+            }
+            _ => false,
+        };
+        if !play_finished {
+            return;
+        }
+        progress.play_count += 1;
+        let follow_action = match self.descriptor.follow_action {
+            Some(a) if progress.play_count >= a.effective_play_count() => a,
+            _ => return,
+        };
+        progress.play_count = 0;
+        let args = match &self.state {
+            State::Playing(s) => &s.args,
+            State::Suspended(SuspendedState {
+                last_play_args: Some(args),
+                ..
+            }) => args,
+            _ => return,
+        };
+        progress.due = Some(DueFollowAction {
+            r#type: follow_action.r#type,
+            track: args.track.clone(),
+            options: args.options,
+        });
+    }
+
     pub fn is_filled(&self) -> bool {
         self.descriptor.is_filled()
     }
@@ -248,7 +606,9 @@ impl ClipSlot {
             Playing(s) => match s.scheduled_for {
                 None => ClipPlayState::Playing,
                 Some(ScheduledFor::Play) => ClipPlayState::ScheduledForPlay,
-                Some(ScheduledFor::Stop) => ClipPlayState::ScheduledForStop,
+                Some(ScheduledFor::Stop) | Some(ScheduledFor::StopAfterMeasure(_)) => {
+                    ClipPlayState::ScheduledForStop
+                }
             },
             Transitioning => unreachable!(),
         }
@@ -270,18 +630,45 @@ impl ClipSlot {
         track: Option<Track>,
         options: SlotPlayOptions,
     ) -> Result<ClipChangedEvent, &'static str> {
+        self.follow_action_progress = Default::default();
         let result = self.start_transition().play(
             &self.register,
             ClipPlayArgs {
                 options,
                 track,
                 repeat: self.descriptor.repeat,
+                fades: self.descriptor.fades,
             },
         );
         self.finish_transition(result)?;
         Ok(self.play_state_changed_event())
     }
 
+    /// Returns the track on which this slot is currently playing (or scheduled to play).
+    ///
+    /// This determines the column of the slot. Slots that play without a track don't belong to any
+    /// column.
+    pub fn playing_track(&self) -> Option<&Track> {
+        match &self.state {
+            State::Playing(s) => s.args.track.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Returns the track and options with which this slot is playing or has been played most
+    /// recently.
+    pub fn last_play_settings(&self) -> Option<(Option<Track>, SlotPlayOptions)> {
+        let args = match &self.state {
+            State::Playing(s) => &s.args,
+            State::Suspended(SuspendedState {
+                last_play_args: Some(args),
+                ..
+            }) => args,
+            _ => return None,
+        };
+        Some((args.track.clone(), args.options))
+    }
+
     /// Stops playback if necessary, destroys the contained source and resets the playback position
     /// to zero.
     pub fn clear(&mut self) -> Result<(), &'static str> {
@@ -304,8 +691,12 @@ impl ClipSlot {
         Ok(Some(self.play_state_changed_event()))
     }
 
-    pub fn stop(&mut self, immediately: bool) -> Result<ClipChangedEvent, &'static str> {
-        let result = self.start_transition().stop(&self.register, immediately);
+    pub fn stop(
+        &mut self,
+        quantization: SlotStopQuantization,
+    ) -> Result<ClipChangedEvent, &'static str> {
+        self.follow_action_progress = Default::default();
+        let result = self.start_transition().stop(&self.register, quantization);
         self.finish_transition(result)?;
         Ok(self.play_state_changed_event())
     }
@@ -366,6 +757,8 @@ impl ClipSlot {
             .map_err(|_| "source has no length")?;
         let real_pos = PositionInSeconds::new(position.get() * length.get());
         guard.set_cur_pos(real_pos);
+        // Seeking backwards is not a completed loop.
+        self.follow_action_progress.last_position = position.get();
         Ok(ClipChangedEvent::ClipPosition(position))
     }
 
@@ -444,7 +837,7 @@ impl State {
                 } else if new_play_state.is_paused {
                     s.pause(reg, true)
                 } else {
-                    s.stop(reg, true, true)
+                    s.stop(reg, SlotStopQuantization::Immediately, true)
                 }
             }
             s => Ok(s),
@@ -461,12 +854,16 @@ impl State {
         }
     }
 
-    pub fn stop(self, reg: &SharedRegister, immediately: bool) -> TransitionResult {
+    pub fn stop(
+        self,
+        reg: &SharedRegister,
+        quantization: SlotStopQuantization,
+    ) -> TransitionResult {
         use State::*;
         match self {
             Empty => Ok(Empty),
             Suspended(s) => s.stop(reg),
-            Playing(s) => s.stop(reg, immediately, false),
+            Playing(s) => s.stop(reg, quantization, false),
             Transitioning => unreachable!(),
         }
     }
@@ -499,10 +896,16 @@ impl State {
     }
 
     pub fn fill_with_source(self, source: OwnedSource, reg: &SharedRegister) -> TransitionResult {
+        let inner = source.into_raw();
+        let is_midi = unsafe { inner.get_type_unchecked() }.to_str() == "MIDI";
         let source = DecoratedPcmSource {
-            inner: source.into_raw(),
+            inner,
+            is_midi,
             state: DecoratedPcmSourceState::Normal,
             send_all_notes_off: false,
+            fades: Default::default(),
+            fade_in_frame: None,
+            fade_out_frame: 0,
         };
         let source = create_custom_owned_pcm_source(source);
         let source = FlexibleOwnedPcmSource::Custom(source);
@@ -536,6 +939,7 @@ struct ClipPlayArgs {
     options: SlotPlayOptions,
     track: Option<Track>,
     repeat: bool,
+    fades: SlotFades,
 }
 
 impl SuspendedState {
@@ -545,6 +949,9 @@ impl SuspendedState {
             guard.set_preview_track(args.track.as_ref().map(|t| t.raw()));
             // The looped field might have been reset on non-immediate stop. Set it again.
             guard.set_looped(args.repeat);
+            // Make sure we start with a fade-in.
+            set_fades_with_guard(&guard, args.fades);
+            reset_with_guard(&guard);
         }
         let buffering_behavior = if args.options.is_effectively_buffered() {
             BitFlags::from_flag(BufferingBehavior::BufferSource)
@@ -617,6 +1024,9 @@ enum ScheduledFor {
     Play,
     /// Still playing but will stop soon. Final stop detection done by polling.
     Stop,
+    /// Still playing but will stop as soon as the project leaves the measure with the given index.
+    /// Final stop detection done by polling.
+    StopAfterMeasure(i32),
 }
 
 impl PlayingState {
@@ -657,6 +1067,13 @@ impl PlayingState {
                         ..self
                     })
                 }
+                Some(ScheduledFor::StopAfterMeasure(_)) => {
+                    // Backpedal (undo schedule for stop)! Looped field was not touched.
+                    State::Playing(PlayingState {
+                        scheduled_for: None,
+                        ..self
+                    })
+                }
             };
             Ok(next_state)
         }
@@ -675,19 +1092,32 @@ impl PlayingState {
     pub fn stop(
         self,
         reg: &SharedRegister,
-        immediately: bool,
+        quantization: SlotStopQuantization,
         caused_by_transport_change: bool,
     ) -> TransitionResult {
-        if immediately {
+        use SlotStopQuantization::*;
+        if quantization == Immediately {
             let suspended = self.stop_immediately(reg, caused_by_transport_change);
             Ok(State::Suspended(suspended))
         } else {
             match self.scheduled_for {
                 None => {
-                    // Schedule stop.
-                    lock(reg).set_looped(false);
+                    let scheduled_for = if quantization == EndOfBar {
+                        let project = self.project();
+                        if !project.is_playing() {
+                            // Without a running timeline, there's no bar to wait for.
+                            let suspended = self.stop_immediately(reg, caused_by_transport_change);
+                            return Ok(State::Suspended(suspended));
+                        }
+                        let pos = project.play_position_latency_compensated();
+                        ScheduledFor::StopAfterMeasure(project.beat_info_at(pos).measure_index)
+                    } else {
+                        // Let it play until the end of the clip.
+                        lock(reg).set_looped(false);
+                        ScheduledFor::Stop
+                    };
                     let playing = PlayingState {
-                        scheduled_for: Some(ScheduledFor::Stop),
+                        scheduled_for: Some(scheduled_for),
                         ..self
                     };
                     Ok(State::Playing(playing))
@@ -700,7 +1130,7 @@ impl PlayingState {
                     let suspended = self.suspend(reg, false, caused_by_transport_change);
                     Ok(State::Suspended(suspended))
                 }
-                Some(ScheduledFor::Stop) => {
+                Some(ScheduledFor::Stop) | Some(ScheduledFor::StopAfterMeasure(_)) => {
                     let suspended = self.stop_immediately(reg, caused_by_transport_change);
                     Ok(State::Suspended(suspended))
                 }
@@ -708,6 +1138,15 @@ impl PlayingState {
         }
     }
 
+    /// The project whose timeline is relevant for quantization.
+    fn project(&self) -> Project {
+        self.args
+            .track
+            .as_ref()
+            .map(|t| t.project())
+            .unwrap_or_else(|| Reaper::get().current_project())
+    }
+
     fn stop_immediately(
         self,
         reg: &SharedRegister,
@@ -745,16 +1184,33 @@ impl PlayingState {
                 .unwrap_or_default();
             (guard.cur_pos(), length, guard.is_looped())
         };
+        let reached_end =
+            current_pos.get() > length.get() || (length.get() - current_pos.get()) < BASE_EPSILON;
         let (next_state, event) = match self.scheduled_for {
             None | Some(ScheduledFor::Stop) if !is_looped => {
-                if current_pos.get() > length.get()
-                    || (length.get() - current_pos.get()) < BASE_EPSILON
-                {
+                if reached_end {
                     // Stop detected. Make it official! If we let the preview running, nothing
                     // will happen because it's not looped but the preview will still be
                     // active (e.g. respond to position changes) - which can't be good.
                     (
-                        self.stop(reg, true, false),
+                        self.stop(reg, SlotStopQuantization::Immediately, false),
+                        Some(ClipChangedEvent::PlayState(ClipPlayState::Stopped)),
+                    )
+                } else {
+                    (Ok(State::Playing(self)), None)
+                }
+            }
+            Some(ScheduledFor::StopAfterMeasure(measure_index)) => {
+                let project = self.project();
+                let pos = project.play_position_latency_compensated();
+                if (!is_looped && reached_end)
+                    || !project.is_playing()
+                    || project.beat_info_at(pos).measure_index > measure_index
+                {
+                    // Bar boundary reached (or one-shot finished or timeline stopped in the
+                    // meantime).
+                    (
+                        self.stop(reg, SlotStopQuantization::Immediately, false),
                         Some(ClipChangedEvent::PlayState(ClipPlayState::Stopped)),
                     )
                 } else {
@@ -812,6 +1268,7 @@ pub struct ClipInfo {
     pub r#type: String,
     pub file_name: Option<PathBuf>,
     pub length: Option<DurationInSeconds>,
+    pub tempo: Option<ClipTempo>,
 }
 
 #[derive(Copy, Clone, PartialEq, Debug, Default)]
@@ -819,6 +1276,8 @@ pub struct SlotPlayOptions {
     /// Syncs with timeline.
     pub next_bar: bool,
     pub buffered: bool,
+    /// `None` means the instance-level default is used.
+    pub stop_quantization: Option<SlotStopQuantization>,
 }
 
 impl SlotPlayOptions {
@@ -826,6 +1285,60 @@ impl SlotPlayOptions {
         // Observation: buffered must be on if next bar is enabled.
         self.buffered || self.next_bar
     }
+
+    /// Determines when a stop request takes effect.
+    ///
+    /// If neither these options nor the instance define a stop quantization, it's derived from
+    /// "Next bar" (as in older versions): Let the clip play until its end if synced with the
+    /// timeline, otherwise stop immediately.
+    pub fn effective_stop_quantization(
+        &self,
+        instance_default: Option<SlotStopQuantization>,
+    ) -> SlotStopQuantization {
+        self.stop_quantization
+            .or(instance_default)
+            .unwrap_or(if self.next_bar {
+                SlotStopQuantization::EndOfClip
+            } else {
+                SlotStopQuantization::Immediately
+            })
+    }
+}
+
+/// Determines when a stop request takes effect.
+#[derive(
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    Debug,
+    Serialize,
+    Deserialize,
+    IntoEnumIterator,
+    TryFromPrimitive,
+    IntoPrimitive,
+    Display,
+)]
+#[repr(usize)]
+#[serde(rename_all = "camelCase")]
+pub enum SlotStopQuantization {
+    #[display(fmt = "Immediately")]
+    Immediately,
+    /// Stops when the project timeline reaches the start of the next bar. Stops immediately if
+    /// the timeline is not running.
+    #[display(fmt = "End of bar")]
+    EndOfBar,
+    /// Lets the clip play until its end (even if it's looped).
+    #[display(fmt = "End of clip")]
+    EndOfClip,
+}
+
+fn reference_bpm(project: Option<Project>) -> f64 {
+    project
+        .unwrap_or_else(|| Reaper::get().current_project())
+        .tempo()
+        .bpm()
+        .get()
 }
 
 fn lock(reg: &SharedRegister) -> ReaperMutexGuard<OwnedPreviewRegister> {
@@ -846,19 +1359,28 @@ fn calculate_proportional_position(
 const EXT_REQUEST_ALL_NOTES_OFF: i32 = 2359767;
 const EXT_QUERY_STATE: i32 = 2359769;
 const EXT_RESET: i32 = 2359770;
+const EXT_SET_FADES: i32 = 2359771;
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug, TryFromPrimitive, IntoPrimitive)]
 #[repr(i32)]
 enum DecoratedPcmSourceState {
     Normal = 10,
+    /// For audio clips this means that a fade-out has been requested.
     AllNotesOffRequested = 11,
+    /// For audio clips this means that the fade-out is finished.
     AllNotesOffSent = 12,
 }
 
 struct DecoratedPcmSource {
     inner: OwnedPcmSource,
+    is_midi: bool,
     state: DecoratedPcmSourceState,
     send_all_notes_off: bool,
+    fades: SlotFades,
+    /// Current position within the fade-in (if fading in).
+    fade_in_frame: Option<u64>,
+    /// Current position within the fade-out (only relevant if fade-out requested).
+    fade_out_frame: u64,
 }
 
 impl CustomPcmSource for DecoratedPcmSource {
@@ -929,12 +1451,35 @@ impl CustomPcmSource for DecoratedPcmSource {
         }
         use DecoratedPcmSourceState::*;
         match self.state {
-            Normal => unsafe {
-                self.inner.get_samples(args.block);
-            },
+            Normal => {
+                unsafe {
+                    self.inner.get_samples(args.block);
+                }
+                if let Some(frame) = self.fade_in_frame {
+                    let total = fade_length_in_frames(&args, self.fades.fade_in_millis);
+                    let next_frame = apply_fade(&args, frame, total, FadeDirection::In);
+                    self.fade_in_frame = if next_frame < total {
+                        Some(next_frame)
+                    } else {
+                        None
+                    };
+                }
+            }
             AllNotesOffRequested => {
-                send_all_notes_off(&args);
-                self.state = AllNotesOffSent;
+                if self.is_midi {
+                    send_all_notes_off(&args);
+                    self.state = AllNotesOffSent;
+                } else {
+                    unsafe {
+                        self.inner.get_samples(args.block);
+                    }
+                    let total = fade_length_in_frames(&args, self.fades.fade_out_millis);
+                    self.fade_out_frame =
+                        apply_fade(&args, self.fade_out_frame, total, FadeDirection::Out);
+                    if self.fade_out_frame >= total {
+                        self.state = AllNotesOffSent;
+                    }
+                }
             }
             AllNotesOffSent => {}
         }
@@ -975,12 +1520,25 @@ impl CustomPcmSource for DecoratedPcmSource {
     unsafe fn extended(&mut self, args: ExtendedArgs) -> i32 {
         match args.call {
             EXT_REQUEST_ALL_NOTES_OFF => {
-                self.state = DecoratedPcmSourceState::AllNotesOffRequested;
+                self.state = if !self.is_midi && self.fades.fade_out_millis == 0 {
+                    // Nothing to wait for.
+                    DecoratedPcmSourceState::AllNotesOffSent
+                } else {
+                    DecoratedPcmSourceState::AllNotesOffRequested
+                };
+                self.fade_out_frame = 0;
                 1
             }
             EXT_QUERY_STATE => self.state.into(),
             EXT_RESET => {
                 self.state = DecoratedPcmSourceState::Normal;
+                if !self.is_midi && self.fades.fade_in_millis > 0 {
+                    self.fade_in_frame = Some(0);
+                }
+                1
+            }
+            EXT_SET_FADES => {
+                self.fades = *(args.parm_1 as *const SlotFades);
                 1
             }
             _ => self
@@ -990,11 +1548,11 @@ impl CustomPcmSource for DecoratedPcmSource {
     }
 }
 
-/// Waits until "all-notes-off" is sent for MIDI clips, e.g. as preparation for a suspension
-/// request.
+/// Waits until "all-notes-off" is sent for MIDI clips (or the fade-out is finished for audio
+/// clips), e.g. as preparation for a suspension request.
 fn wait_until_all_notes_off_sent(reg: &SharedRegister, reset_position: bool) {
-    // Try 10 times
-    for _ in 0..10 {
+    // Try 20 times (enough for the maximum fade-out length)
+    for _ in 0..20 {
         if attempt_to_send_all_notes_off(reg, reset_position) {
             // Preparation finished.
             return;
@@ -1008,6 +1566,10 @@ fn wait_until_all_notes_off_sent(reg: &SharedRegister, reset_position: bool) {
     if reset_position {
         guard.set_cur_pos(PositionInSeconds::new(0.0));
     }
+    reset_with_guard(&guard);
+}
+
+fn reset_with_guard(guard: &ReaperMutexGuard<OwnedPreviewRegister>) {
     let src = match guard.src() {
         None => return,
         Some(s) => s.as_ref(),
@@ -1017,6 +1579,21 @@ fn wait_until_all_notes_off_sent(reg: &SharedRegister, reset_position: bool) {
     }
 }
 
+fn set_fades_with_guard(guard: &ReaperMutexGuard<OwnedPreviewRegister>, fades: SlotFades) {
+    let src = match guard.src() {
+        None => return,
+        Some(s) => s.as_ref(),
+    };
+    unsafe {
+        src.extended(
+            EXT_SET_FADES,
+            &fades as *const SlotFades as *mut _,
+            null_mut(),
+            null_mut(),
+        );
+    }
+}
+
 /// Returns `true` as soon as "All notes off" sent.
 fn attempt_to_send_all_notes_off(reg: &SharedRegister, reset_position: bool) -> bool {
     let mut guard = lock(reg);
@@ -1036,14 +1613,15 @@ fn attempt_to_send_all_notes_off_with_guard(
         Some(s) => s,
     };
     let src = src.as_ref();
-    if src.get_type(|t| t.to_str() != "MIDI") {
-        return true;
-    }
-    // Don't just stop MIDI! Send all-notes-off first to prevent hanging notes.
-    let state = unsafe { src.extended(EXT_QUERY_STATE, null_mut(), null_mut(), null_mut()) };
-    let state: DecoratedPcmSourceState = state.try_into().expect("invalid state");
+    // Don't just stop MIDI! Send all-notes-off first to prevent hanging notes. Don't just stop
+    // audio either! Fade out first to prevent clicks.
+    let query_state = || {
+        let state = unsafe { src.extended(EXT_QUERY_STATE, null_mut(), null_mut(), null_mut()) };
+        let state: DecoratedPcmSourceState = state.try_into().expect("invalid state");
+        state
+    };
     use DecoratedPcmSourceState::*;
-    match state {
+    match query_state() {
         Normal => unsafe {
             src.extended(
                 EXT_REQUEST_ALL_NOTES_OFF,
@@ -1051,7 +1629,13 @@ fn attempt_to_send_all_notes_off_with_guard(
                 null_mut(),
                 null_mut(),
             );
-            false
+            // Audio clips without fade-out don't need to wait.
+            if query_state() == AllNotesOffSent {
+                src.extended(EXT_RESET, null_mut(), null_mut(), null_mut());
+                true
+            } else {
+                false
+            }
         },
         AllNotesOffRequested => {
             // Wait
@@ -1064,6 +1648,51 @@ fn attempt_to_send_all_notes_off_with_guard(
     }
 }
 
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+enum FadeDirection {
+    In,
+    Out,
+}
+
+fn fade_length_in_frames(args: &GetSamplesArgs, millis: u32) -> u64 {
+    let sample_rate = unsafe { args.block.as_ptr().as_ref() }.samplerate;
+    (sample_rate * millis as f64 / 1000.0) as u64
+}
+
+/// Applies a linear gain ramp to the rendered audio samples, starting at the given frame within
+/// the fade. Returns the frame within the fade at which the next block should continue.
+fn apply_fade(
+    args: &GetSamplesArgs,
+    start_frame: u64,
+    total_frames: u64,
+    dir: FadeDirection,
+) -> u64 {
+    let block = unsafe { args.block.as_ptr().as_ref() };
+    let frame_count = block.samples_out.max(0) as usize;
+    let channel_count = block.nch.max(0) as usize;
+    if block.samples.is_null() || frame_count == 0 || channel_count == 0 {
+        return start_frame;
+    }
+    let samples =
+        unsafe { std::slice::from_raw_parts_mut(block.samples, frame_count * channel_count) };
+    for (i, frame) in samples.chunks_mut(channel_count).enumerate() {
+        let pos = start_frame + i as u64;
+        let progress = if total_frames == 0 {
+            1.0
+        } else {
+            (pos as f64 / total_frames as f64).min(1.0)
+        };
+        let gain = match dir {
+            FadeDirection::In => progress,
+            FadeDirection::Out => 1.0 - progress,
+        };
+        for sample in frame {
+            *sample *= gain;
+        }
+    }
+    start_frame + frame_count as u64
+}
+
 fn send_all_notes_off(args: &GetSamplesArgs) {
     for ch in 0..16 {
         let msg = RawShortMessage::control_change(
@@ -0,0 +1,126 @@
+use serde::{Deserialize, Serialize};
+
+/// Assumed number of beats per bar when estimating the tempo of an audio clip.
+const ESTIMATION_BEATS_PER_BAR: f64 = 4.0;
+
+/// Native tempo of a clip.
+///
+/// Stored with the slot, so the detected (or user-given) tempo survives reloading the project
+/// and doesn't need to be estimated again.
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ClipTempo {
+    #[serde(rename = "bpm")]
+    bpm: f64,
+    #[serde(rename = "beatCount")]
+    beat_count: f64,
+}
+
+impl ClipTempo {
+    /// Creates the tempo info for a clip of the given length which is known to have the given
+    /// tempo (e.g. specified by the user).
+    pub fn from_bpm(length_in_secs: f64, bpm: f64) -> Result<Self, &'static str> {
+        if length_in_secs <= 0.0 {
+            return Err("clip has no length");
+        }
+        if bpm <= 0.0 {
+            return Err("tempo must be positive");
+        }
+        let tempo = Self {
+            bpm,
+            beat_count: length_in_secs * bpm / 60.0,
+        };
+        Ok(tempo)
+    }
+
+    /// Creates the tempo info for a clip of the given length which is known to span the given
+    /// number of beats (e.g. a MIDI clip).
+    pub fn from_beat_count(length_in_secs: f64, beat_count: f64) -> Result<Self, &'static str> {
+        if length_in_secs <= 0.0 {
+            return Err("clip has no length");
+        }
+        if beat_count <= 0.0 {
+            return Err("beat count must be positive");
+        }
+        let tempo = Self {
+            bpm: beat_count * 60.0 / length_in_secs,
+            beat_count,
+        };
+        Ok(tempo)
+    }
+
+    /// Guesses the tempo of a clip without any tempo information (e.g. an audio file) by assuming
+    /// that it spans a whole number of bars at roughly the given reference tempo.
+    pub fn estimate(length_in_secs: f64, reference_bpm: f64) -> Result<Self, &'static str> {
+        if reference_bpm <= 0.0 {
+            return Err("reference tempo must be positive");
+        }
+        let beat_count_at_reference = length_in_secs * reference_bpm / 60.0;
+        let bar_count = (beat_count_at_reference / ESTIMATION_BEATS_PER_BAR)
+            .round()
+            .max(1.0);
+        Self::from_beat_count(length_in_secs, bar_count * ESTIMATION_BEATS_PER_BAR)
+    }
+
+    pub fn bpm(&self) -> f64 {
+        self.bpm
+    }
+
+    pub fn beat_count(&self) -> f64 {
+        self.beat_count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_abs_diff_eq;
+
+    #[test]
+    fn from_bpm() {
+        // Given
+        let tempo = ClipTempo::from_bpm(4.0, 120.0).unwrap();
+        // When
+        // Then
+        assert_abs_diff_eq!(tempo.beat_count(), 8.0);
+        assert_abs_diff_eq!(tempo.bpm(), 120.0);
+    }
+
+    #[test]
+    fn from_beat_count() {
+        // Given
+        let tempo = ClipTempo::from_beat_count(3.0, 8.0).unwrap();
+        // When
+        // Then
+        assert_abs_diff_eq!(tempo.bpm(), 160.0);
+    }
+
+    #[test]
+    fn invalid_input() {
+        assert!(ClipTempo::from_bpm(0.0, 120.0).is_err());
+        assert!(ClipTempo::from_bpm(4.0, 0.0).is_err());
+        assert!(ClipTempo::from_beat_count(4.0, 0.0).is_err());
+        assert!(ClipTempo::estimate(4.0, 0.0).is_err());
+    }
+
+    #[test]
+    fn estimate_whole_bars() {
+        // Given
+        // 2 bars at 125 bpm, project at 120 bpm
+        let length = 8.0 * 60.0 / 125.0;
+        // When
+        let tempo = ClipTempo::estimate(length, 120.0).unwrap();
+        // Then
+        assert_abs_diff_eq!(tempo.beat_count(), 8.0);
+        assert_abs_diff_eq!(tempo.bpm(), 125.0, epsilon = 0.0001);
+    }
+
+    #[test]
+    fn estimate_very_short_clip() {
+        // Given
+        let length = 0.1;
+        // When
+        let tempo = ClipTempo::estimate(length, 120.0).unwrap();
+        // Then
+        assert_abs_diff_eq!(tempo.beat_count(), 4.0);
+    }
+}
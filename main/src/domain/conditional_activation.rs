@@ -1,5 +1,11 @@
 use crate::base::eel;
-use crate::domain::{ParameterSlice, COMPARTMENT_PARAMETER_COUNT};
+use crate::domain::{ExpressionEvaluator, ParameterSlice, COMPARTMENT_PARAMETER_COUNT};
+use derive_more::Display;
+use enum_iterator::IntoEnumIterator;
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+use reaper_high::{ChangeEvent, Project, Reaper};
+use reaper_medium::MasterTrackBehavior;
+use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 
 #[derive(Debug)]
@@ -12,20 +18,31 @@ pub enum ActivationCondition {
     },
     // Boxed in order to keep the enum variants at a similar size (clippy gave that hint)
     Eel(Box<EelCondition>),
+    Expression(Box<ExpressionCondition>),
+    ReaperState(ReaperStateCondition),
 }
 
 impl ActivationCondition {
     /// Returns if this activation condition can be affected by parameter changes in general.
     pub fn can_be_affected_by_parameters(&self) -> bool {
-        !matches!(self, ActivationCondition::Always)
+        !matches!(
+            self,
+            ActivationCondition::Always | ActivationCondition::ReaperState(_)
+        )
+    }
+
+    /// Returns if this activation condition can be affected by REAPER state changes in general.
+    pub fn can_be_affected_by_reaper_state(&self) -> bool {
+        matches!(self, ActivationCondition::ReaperState(_))
     }
 
     /// Returns if this activation condition is fulfilled in presence of the given set of
-    /// parameters.
-    pub fn is_fulfilled(&self, params: &ParameterSlice) -> bool {
+    /// parameters and the current state of the given project.
+    pub fn is_fulfilled(&self, params: &ParameterSlice, project: Project) -> bool {
         use ActivationCondition::*;
         match self {
             Always => true,
+            ReaperState(condition) => condition.is_fulfilled(project),
             Modifiers(conditions) => modifier_conditions_are_fulfilled(conditions, params),
             Program {
                 param_index,
@@ -35,6 +52,7 @@ impl ActivationCondition {
                 condition.notify_params_changed(params);
                 condition.is_fulfilled()
             }
+            Expression(condition) => condition.is_fulfilled(params),
         }
     }
 
@@ -84,12 +102,94 @@ impl ActivationCondition {
                 }
                 condition.is_fulfilled()
             }
-            Always => return None,
+            Expression(condition) => {
+                if !condition.is_affected_by_param_change(index) {
+                    return None;
+                }
+                condition.is_fulfilled(params)
+            }
+            Always | ReaperState(_) => return None,
         };
         Some(is_fulfilled)
     }
 }
 
+/// Condition which depends on the current state of REAPER instead of ReaLearn parameters.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    IntoEnumIterator,
+    TryFromPrimitive,
+    IntoPrimitive,
+    Display,
+)]
+#[repr(usize)]
+pub enum ReaperStateCondition {
+    #[serde(rename = "playing")]
+    #[display(fmt = "Playing")]
+    Playing,
+    #[serde(rename = "recording")]
+    #[display(fmt = "Recording")]
+    Recording,
+    #[serde(rename = "stopped")]
+    #[display(fmt = "Stopped")]
+    Stopped,
+    #[serde(rename = "any-track-armed")]
+    #[display(fmt = "Any track armed")]
+    AnyTrackArmed,
+    #[serde(rename = "any-track-selected")]
+    #[display(fmt = "Any track selected")]
+    AnyTrackSelected,
+    #[serde(rename = "any-fx-focused")]
+    #[display(fmt = "Any FX focused")]
+    AnyFxFocused,
+}
+
+impl Default for ReaperStateCondition {
+    fn default() -> Self {
+        Self::Playing
+    }
+}
+
+impl ReaperStateCondition {
+    pub fn is_fulfilled(self, project: Project) -> bool {
+        use ReaperStateCondition::*;
+        match self {
+            Playing => project.play_state().is_playing,
+            Recording => project.play_state().is_recording,
+            Stopped => {
+                let play_state = project.play_state();
+                !play_state.is_playing && !play_state.is_paused
+            }
+            AnyTrackArmed => project.tracks().any(|t| t.is_armed(false)),
+            AnyTrackSelected => {
+                project.selected_track_count(MasterTrackBehavior::IncludeMasterTrack) > 0
+            }
+            AnyFxFocused => Reaper::get().focused_fx().is_some(),
+        }
+    }
+
+    /// Returns if the given event might change the result of any REAPER state condition.
+    pub fn is_potential_change_event(evt: &ChangeEvent) -> bool {
+        use ChangeEvent::*;
+        matches!(
+            evt,
+            PlayStateChanged(_)
+                | TrackArmChanged(_)
+                | TrackSelectedChanged(_)
+                | TrackAdded(_)
+                | TrackRemoved(_)
+                | FxFocused(_)
+                | ProjectSwitched(_)
+        )
+    }
+}
+
 fn modifier_conditions_are_fulfilled(
     conditions: &[ModifierCondition],
     params: &ParameterSlice,
@@ -215,6 +315,46 @@ impl EelCondition {
     }
 }
 
+/// Condition which evaluates a mathematical expression (using the same syntax as dynamic targets).
+///
+/// Other than the EEL condition, this one doesn't need a VM and the expression can use the result
+/// of comparisons and logical operators directly, e.g. `p1 > 0 && p2 < 0.5`.
+#[derive(Debug)]
+pub struct ExpressionCondition {
+    evaluator: ExpressionEvaluator,
+    used_param_indexes: HashSet<u32>,
+    /// If the expression uses the parameter array syntax (`p(i)`), we can't know statically which
+    /// parameters are used.
+    uses_param_array: bool,
+}
+
+impl ExpressionCondition {
+    pub fn compile(expression: &str) -> Result<ExpressionCondition, String> {
+        if expression.trim().is_empty() {
+            return Err("expression empty".to_string());
+        }
+        let evaluator = ExpressionEvaluator::compile(expression).map_err(|e| e.to_string())?;
+        let condition = ExpressionCondition {
+            evaluator,
+            used_param_indexes: extract_used_param_indexes(expression),
+            uses_param_array: regex!(r#"\bp\s*\("#).is_match(expression),
+        };
+        Ok(condition)
+    }
+
+    pub fn is_affected_by_param_change(&self, param_index: u32) -> bool {
+        self.uses_param_array || self.used_param_indexes.contains(&param_index)
+    }
+
+    /// Errors during evaluation (e.g. usage of unknown variables) make the condition unfulfilled.
+    pub fn is_fulfilled(&self, params: &ParameterSlice) -> bool {
+        match self.evaluator.evaluate(params) {
+            Ok(result) => result > 0.0,
+            Err(_) => false,
+        }
+    }
+}
+
 fn extract_used_param_indexes(eel_script: &str) -> HashSet<u32> {
     let param_regex = regex!(r#"\bp([0-9]+)\b"#);
     param_regex
@@ -225,3 +365,60 @@ fn extract_used_param_indexes(eel_script: &str) -> HashSet<u32> {
         .map(|i: u32| i - 1)
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expression_condition_with_logical_operators() {
+        // Given
+        let c = ExpressionCondition::compile("p1 > 0.5 && p3 == 0").unwrap();
+        let mut params = [0.0f32; COMPARTMENT_PARAMETER_COUNT as usize];
+        // When
+        params[0] = 0.7;
+        // Then
+        assert!(c.is_fulfilled(&params));
+        // When
+        params[2] = 1.0;
+        // Then
+        assert!(!c.is_fulfilled(&params));
+    }
+
+    #[test]
+    fn expression_condition_affected_params() {
+        // Given
+        let c = ExpressionCondition::compile("p1 + p10 > 1").unwrap();
+        // Then
+        assert!(c.is_affected_by_param_change(0));
+        assert!(c.is_affected_by_param_change(9));
+        assert!(!c.is_affected_by_param_change(1));
+    }
+
+    #[test]
+    fn expression_condition_with_param_array() {
+        // Given
+        let c = ExpressionCondition::compile("p(4) > 0").unwrap();
+        let mut params = [0.0f32; COMPARTMENT_PARAMETER_COUNT as usize];
+        params[4] = 1.0;
+        // Then
+        assert!(c.is_affected_by_param_change(50));
+        assert!(c.is_fulfilled(&params));
+    }
+
+    #[test]
+    fn expression_condition_invalid() {
+        // Then
+        assert!(ExpressionCondition::compile("").is_err());
+        assert!(ExpressionCondition::compile("p1 >").is_err());
+    }
+
+    #[test]
+    fn expression_condition_unknown_variable() {
+        // Given
+        let c = ExpressionCondition::compile("x > 0").unwrap();
+        let params = [0.0f32; COMPARTMENT_PARAMETER_COUNT as usize];
+        // Then
+        assert!(!c.is_fulfilled(&params));
+    }
+}
@@ -0,0 +1,92 @@
+use crate::domain::VirtualControlElement;
+use helgoboss_learn::UnitValue;
+use std::time::{Duration, Instant};
+
+/// How long a control element blinks when it's identified.
+const IDENTIFICATION_DURATION: Duration = Duration::from_secs(2);
+
+/// Duration of one on or off phase of the blinking.
+const BLINK_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Lets the LED of one control element blink so the user can see which element is meant.
+///
+/// Like the idle animation, this doesn't send anything itself. The main processor polls it and
+/// turns the values into feedback.
+#[derive(Copy, Clone, Debug)]
+pub struct ControlElementIdentification {
+    control_element: VirtualControlElement,
+    started_at: Instant,
+    last_is_on: Option<bool>,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum IdentificationPollResult {
+    /// The element should display the given value.
+    Show(UnitValue),
+    /// Identification is over, the normal feedback should be restored.
+    Finished,
+}
+
+impl ControlElementIdentification {
+    pub fn new(control_element: VirtualControlElement, now: Instant) -> Self {
+        Self {
+            control_element,
+            started_at: now,
+            last_is_on: None,
+        }
+    }
+
+    pub fn control_element(&self) -> VirtualControlElement {
+        self.control_element
+    }
+
+    /// Returns `None` if nothing has changed since the last poll.
+    pub fn poll(&mut self, now: Instant) -> Option<IdentificationPollResult> {
+        let elapsed = now.saturating_duration_since(self.started_at);
+        if elapsed >= IDENTIFICATION_DURATION {
+            return Some(IdentificationPollResult::Finished);
+        }
+        let is_on = (elapsed.as_millis() / BLINK_INTERVAL.as_millis()) % 2 == 0;
+        if self.last_is_on == Some(is_on) {
+            return None;
+        }
+        self.last_is_on = Some(is_on);
+        let value = if is_on {
+            UnitValue::MAX
+        } else {
+            UnitValue::MIN
+        };
+        Some(IdentificationPollResult::Show(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::VirtualControlElementId;
+
+    #[test]
+    fn blinks_and_finishes() {
+        // Given
+        let start = Instant::now();
+        let element = VirtualControlElement::Button(VirtualControlElementId::Indexed(0));
+        let mut identification = ControlElementIdentification::new(element, start);
+        // Then
+        assert_eq!(
+            identification.poll(start),
+            Some(IdentificationPollResult::Show(UnitValue::MAX))
+        );
+        assert_eq!(
+            identification.poll(start + Duration::from_millis(100)),
+            None
+        );
+        assert_eq!(
+            identification.poll(start + BLINK_INTERVAL),
+            Some(IdentificationPollResult::Show(UnitValue::MIN))
+        );
+        assert_eq!(
+            identification.poll(start + IDENTIFICATION_DURATION),
+            Some(IdentificationPollResult::Finished)
+        );
+    }
+}
@@ -0,0 +1,221 @@
+use crate::domain::{InstanceId, MappingCompartment, QualifiedMappingId};
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::time::Duration;
+
+/// Name of the exposed Prometheus metric.
+const METRIC_NAME: &str = "realearn_mapping_control_latency_seconds";
+
+/// Upper bounds (inclusive, in seconds) of the histogram buckets.
+///
+/// The main processor runs roughly every 33ms, so most latencies should end up below that.
+const BUCKET_BOUNDS: [f64; 10] = [
+    0.001, 0.0025, 0.005, 0.01, 0.02, 0.035, 0.05, 0.1, 0.25, 0.5,
+];
+
+/// The stages of controlling a mapping in the main processor.
+///
+/// Each stage is measured from the arrival of the control input in the real-time processor up to
+/// the end of the stage. Stage one includes invoking the target.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum ControlStage {
+    One,
+    Two,
+    Three,
+}
+
+impl ControlStage {
+    fn label(self) -> &'static str {
+        use ControlStage::*;
+        match self {
+            One => "one",
+            Two => "two",
+            Three => "three",
+        }
+    }
+}
+
+/// Histogram of control latencies in Prometheus style (cumulative buckets, sum and count).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LatencyHistogram {
+    /// Number of samples per bucket (not cumulative).
+    bucket_counts: [u64; BUCKET_BOUNDS.len()],
+    count: u64,
+    sum: Duration,
+}
+
+impl LatencyHistogram {
+    pub fn record(&mut self, latency: Duration) {
+        let secs = latency.as_secs_f64();
+        if let Some(i) = BUCKET_BOUNDS.iter().position(|bound| secs <= *bound) {
+            self.bucket_counts[i] += 1;
+        }
+        self.count += 1;
+        self.sum += latency;
+    }
+
+    /// Returns the cumulative count of each bucket, the last one being "+Inf".
+    fn cumulative_counts(&self) -> impl Iterator<Item = (String, u64)> + '_ {
+        let finite =
+            BUCKET_BOUNDS
+                .iter()
+                .zip(self.bucket_counts.iter())
+                .scan(0, |acc, (bound, count)| {
+                    *acc += count;
+                    Some((bound.to_string(), *acc))
+                });
+        finite.chain(std::iter::once(("+Inf".to_owned(), self.count)))
+    }
+}
+
+/// Per-mapping control latencies of one instance.
+#[derive(Debug, Default)]
+pub struct ControlLatencies {
+    histograms: HashMap<(QualifiedMappingId, ControlStage), LatencyHistogram>,
+}
+
+impl ControlLatencies {
+    pub fn record(&mut self, id: QualifiedMappingId, stage: ControlStage, latency: Duration) {
+        self.histograms
+            .entry((id, stage))
+            .or_default()
+            .record(latency);
+    }
+
+    /// Forgets the latencies of mappings which don't exist anymore.
+    pub fn retain_mappings(&mut self, mut keep: impl FnMut(QualifiedMappingId) -> bool) {
+        self.histograms.retain(|(id, _), _| keep(*id));
+    }
+}
+
+/// Writes the control latencies of the given instances in the Prometheus text format.
+pub fn write_control_latency_metrics<'a>(
+    text: &mut String,
+    instances: impl Iterator<Item = (&'a InstanceId, &'a ControlLatencies)>,
+) {
+    let _ = writeln!(
+        text,
+        "# HELP {} Time from control input arrival in the real-time processor to the end of the given control stage in the main processor.",
+        METRIC_NAME
+    );
+    let _ = writeln!(text, "# TYPE {} histogram", METRIC_NAME);
+    for (instance_id, latencies) in instances {
+        let mut entries: Vec<_> = latencies.histograms.iter().collect();
+        // Stable output makes it easier to compare subsequent scrapes.
+        entries.sort_by_key(|((id, stage), _)| (usize::from(id.compartment), id.id, *stage));
+        for ((id, stage), histogram) in entries {
+            let labels = format!(
+                "instance=\"{}\",compartment=\"{}\",mapping=\"{}\",stage=\"{}\"",
+                instance_id,
+                compartment_label(id.compartment),
+                id.id,
+                stage.label()
+            );
+            for (bound, count) in histogram.cumulative_counts() {
+                let _ = writeln!(
+                    text,
+                    "{}_bucket{{{},le=\"{}\"}} {}",
+                    METRIC_NAME, labels, bound, count
+                );
+            }
+            let _ = writeln!(
+                text,
+                "{}_sum{{{}}} {}",
+                METRIC_NAME,
+                labels,
+                histogram.sum.as_secs_f64()
+            );
+            let _ = writeln!(
+                text,
+                "{}_count{{{}}} {}",
+                METRIC_NAME, labels, histogram.count
+            );
+        }
+    }
+}
+
+fn compartment_label(compartment: MappingCompartment) -> &'static str {
+    match compartment {
+        MappingCompartment::ControllerMappings => "controller",
+        MappingCompartment::MainMappings => "main",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::MappingId;
+
+    #[test]
+    fn cumulative_buckets() {
+        // Given
+        let mut histogram = LatencyHistogram::default();
+        // When
+        histogram.record(Duration::from_micros(500));
+        histogram.record(Duration::from_millis(1));
+        histogram.record(Duration::from_millis(30));
+        histogram.record(Duration::from_secs(2));
+        // Then
+        let counts: Vec<_> = histogram.cumulative_counts().collect();
+        assert_eq!(counts.len(), BUCKET_BOUNDS.len() + 1);
+        assert_eq!(counts[0], ("0.001".to_owned(), 2));
+        assert_eq!(counts[4], ("0.02".to_owned(), 2));
+        assert_eq!(counts[5], ("0.035".to_owned(), 3));
+        assert_eq!(counts[9], ("0.5".to_owned(), 3));
+        assert_eq!(counts[10], ("+Inf".to_owned(), 4));
+        assert_eq!(histogram.count, 4);
+        assert_eq!(histogram.sum, Duration::from_micros(2_031_500));
+    }
+
+    #[test]
+    fn per_mapping_and_stage() {
+        // Given
+        let mut latencies = ControlLatencies::default();
+        let mapping_1 =
+            QualifiedMappingId::new(MappingCompartment::MainMappings, MappingId::random());
+        let mapping_2 =
+            QualifiedMappingId::new(MappingCompartment::ControllerMappings, MappingId::random());
+        // When
+        latencies.record(mapping_1, ControlStage::One, Duration::from_millis(3));
+        latencies.record(mapping_1, ControlStage::Three, Duration::from_millis(4));
+        latencies.record(mapping_2, ControlStage::One, Duration::from_millis(5));
+        latencies.retain_mappings(|id| id != mapping_2);
+        // Then
+        let count = |id, stage| latencies.histograms.get(&(id, stage)).map(|h| h.count);
+        assert_eq!(count(mapping_1, ControlStage::One), Some(1));
+        assert_eq!(count(mapping_1, ControlStage::Two), None);
+        assert_eq!(count(mapping_1, ControlStage::Three), Some(1));
+        assert_eq!(count(mapping_2, ControlStage::One), None);
+    }
+
+    #[test]
+    fn prometheus_text() {
+        // Given
+        let mut latencies = ControlLatencies::default();
+        let mapping =
+            QualifiedMappingId::new(MappingCompartment::MainMappings, MappingId::random());
+        latencies.record(mapping, ControlStage::Two, Duration::from_millis(2));
+        let instance_id = InstanceId::random();
+        // When
+        let mut text = String::new();
+        write_control_latency_metrics(&mut text, std::iter::once((&instance_id, &latencies)));
+        // Then
+        let labels = format!(
+            "instance=\"{}\",compartment=\"main\",mapping=\"{}\",stage=\"two\"",
+            instance_id, mapping.id
+        );
+        assert!(text.contains("# TYPE realearn_mapping_control_latency_seconds histogram"));
+        assert!(text.contains(&format!(
+            "realearn_mapping_control_latency_seconds_bucket{{{},le=\"0.001\"}} 0",
+            labels
+        )));
+        assert!(text.contains(&format!(
+            "realearn_mapping_control_latency_seconds_bucket{{{},le=\"0.0025\"}} 1",
+            labels
+        )));
+        assert!(text.contains(&format!(
+            "realearn_mapping_control_latency_seconds_count{{{}}} 1",
+            labels
+        )));
+    }
+}
@@ -0,0 +1,207 @@
+use serde::Serialize;
+use std::time::Duration;
+
+/// Upper limit for the number of collected samples so that a forgotten measurement doesn't eat up
+/// memory.
+const MAX_SAMPLE_COUNT: usize = 100_000;
+
+/// Collects the latencies between arrival of control input in the real-time processor and the
+/// corresponding target hit in the main processor while the user measures them.
+///
+/// Other than the control latency metrics, this doesn't distinguish between mappings but is
+/// meant to give a quick impression of the overall responsiveness of one instance.
+#[derive(Debug, Default)]
+pub struct ControlLatencyMeasurement {
+    is_running: bool,
+    samples: Vec<Duration>,
+}
+
+/// Distribution of the latencies collected by a measurement.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ControlLatencyStatistics {
+    pub sample_count: usize,
+    pub min: Duration,
+    pub max: Duration,
+    pub mean: Duration,
+    pub median: Duration,
+    pub p95: Duration,
+    pub p99: Duration,
+    /// Standard deviation.
+    pub jitter: Duration,
+}
+
+/// Same as [`ControlLatencyStatistics`] but with all latencies in milliseconds, which is what
+/// users get to see.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ControlLatencyStatisticsInMillis {
+    pub sample_count: usize,
+    pub min_ms: f64,
+    pub max_ms: f64,
+    pub mean_ms: f64,
+    pub median_ms: f64,
+    pub p95_ms: f64,
+    pub p99_ms: f64,
+    /// Standard deviation.
+    pub jitter_ms: f64,
+}
+
+impl ControlLatencyStatistics {
+    pub fn in_millis(&self) -> ControlLatencyStatisticsInMillis {
+        ControlLatencyStatisticsInMillis {
+            sample_count: self.sample_count,
+            min_ms: as_millis(self.min),
+            max_ms: as_millis(self.max),
+            mean_ms: as_millis(self.mean),
+            median_ms: as_millis(self.median),
+            p95_ms: as_millis(self.p95),
+            p99_ms: as_millis(self.p99),
+            jitter_ms: as_millis(self.jitter),
+        }
+    }
+}
+
+impl ControlLatencyMeasurement {
+    /// Starts a new measurement, discarding the samples of the previous one.
+    pub fn start(&mut self) {
+        self.samples.clear();
+        self.is_running = true;
+    }
+
+    /// Stops the measurement but keeps the samples.
+    pub fn stop(&mut self) {
+        self.is_running = false;
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.is_running
+    }
+
+    pub fn record(&mut self, latency: Duration) {
+        if !self.is_running || self.samples.len() >= MAX_SAMPLE_COUNT {
+            return;
+        }
+        self.samples.push(latency);
+    }
+
+    /// Returns `None` if no samples have been collected.
+    pub fn statistics(&self) -> Option<ControlLatencyStatistics> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        let mut sorted = self.samples.clone();
+        sorted.sort_unstable();
+        let count = sorted.len();
+        let secs: Vec<f64> = sorted.iter().map(|d| d.as_secs_f64()).collect();
+        let mean = secs.iter().sum::<f64>() / count as f64;
+        let variance = secs.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / count as f64;
+        let stats = ControlLatencyStatistics {
+            sample_count: count,
+            min: sorted[0],
+            max: sorted[count - 1],
+            mean: Duration::from_secs_f64(mean),
+            median: percentile(&sorted, 0.5),
+            p95: percentile(&sorted, 0.95),
+            p99: percentile(&sorted, 0.99),
+            jitter: Duration::from_secs_f64(variance.sqrt()),
+        };
+        Some(stats)
+    }
+}
+
+fn as_millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// Nearest-rank percentile of the given sorted samples.
+fn percentile(sorted: &[Duration], p: f64) -> Duration {
+    let rank = (p * sorted.len() as f64).ceil() as usize;
+    sorted[rank.max(1) - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_records_while_running() {
+        // Given
+        let mut m = ControlLatencyMeasurement::default();
+        // When
+        m.record(Duration::from_millis(5));
+        // Then
+        assert_eq!(m.statistics(), None);
+        // When
+        m.start();
+        m.record(Duration::from_millis(5));
+        m.stop();
+        m.record(Duration::from_millis(7));
+        // Then
+        assert_eq!(m.statistics().unwrap().sample_count, 1);
+    }
+
+    #[test]
+    fn restart_discards_previous_samples() {
+        // Given
+        let mut m = ControlLatencyMeasurement::default();
+        m.start();
+        m.record(Duration::from_millis(5));
+        // When
+        m.start();
+        // Then
+        assert!(m.is_running());
+        assert_eq!(m.statistics(), None);
+    }
+
+    #[test]
+    fn distribution() {
+        // Given
+        let mut m = ControlLatencyMeasurement::default();
+        m.start();
+        // When
+        for ms in (1..=100).rev() {
+            m.record(Duration::from_millis(ms));
+        }
+        // Then
+        let stats = m.statistics().unwrap();
+        assert_eq!(stats.sample_count, 100);
+        assert_eq!(stats.min, Duration::from_millis(1));
+        assert_eq!(stats.max, Duration::from_millis(100));
+        assert_eq!(stats.median, Duration::from_millis(50));
+        assert_eq!(stats.p95, Duration::from_millis(95));
+        assert_eq!(stats.p99, Duration::from_millis(99));
+        assert!((stats.mean.as_secs_f64() - 0.0505).abs() < 1e-9);
+        // Standard deviation of 1..=100 is ~28.866
+        assert_eq!(stats.jitter.as_micros() / 100, 288);
+    }
+
+    #[test]
+    fn constant_latency_has_no_jitter() {
+        // Given
+        let mut m = ControlLatencyMeasurement::default();
+        m.start();
+        // When
+        m.record(Duration::from_millis(10));
+        m.record(Duration::from_millis(10));
+        // Then
+        let stats = m.statistics().unwrap();
+        assert_eq!(stats.jitter, Duration::from_millis(0));
+        assert_eq!(stats.p99, Duration::from_millis(10));
+    }
+
+    #[test]
+    fn in_millis() {
+        // Given
+        let mut m = ControlLatencyMeasurement::default();
+        m.start();
+        m.record(Duration::from_micros(1500));
+        let stats = m.statistics().unwrap();
+        // When
+        let millis = stats.in_millis();
+        // Then
+        assert_eq!(millis.sample_count, 1);
+        assert!((millis.min_ms - 1.5).abs() < 1e-9);
+        assert!((millis.max_ms - 1.5).abs() < 1e-9);
+        assert_eq!(millis.jitter_ms, 0.0);
+    }
+}
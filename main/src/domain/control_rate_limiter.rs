@@ -0,0 +1,213 @@
+use crate::domain::{ControlOptions, QualifiedMappingId};
+use helgoboss_learn::{ControlValue, DiscreteIncrement};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// A control value on its way to a mapping.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct RateLimitedControl {
+    pub value: ControlValue,
+    pub options: ControlOptions,
+    /// When the (first coalesced) control input was received.
+    pub received_at: Instant,
+}
+
+/// Makes sure that mappings with a maximum control rate are not controlled more often than
+/// allowed.
+///
+/// Control values which arrive too early are coalesced: The latest absolute value wins, relative
+/// increments are summed up. The coalesced value is released as soon as the interval has elapsed.
+#[derive(Debug, Default)]
+pub struct ControlRateLimiter {
+    entries: HashMap<QualifiedMappingId, RateLimitEntry>,
+}
+
+#[derive(Debug)]
+struct RateLimitEntry {
+    min_interval: Duration,
+    last_released_at: Instant,
+    pending: Option<RateLimitedControl>,
+}
+
+impl ControlRateLimiter {
+    /// Returns the given control if it may be processed right now. Otherwise keeps it for later.
+    pub fn process(
+        &mut self,
+        id: QualifiedMappingId,
+        control: RateLimitedControl,
+        min_interval: Duration,
+        now: Instant,
+    ) -> Option<RateLimitedControl> {
+        let entry = match self.entries.get_mut(&id) {
+            None => {
+                let entry = RateLimitEntry {
+                    min_interval,
+                    last_released_at: now,
+                    pending: None,
+                };
+                self.entries.insert(id, entry);
+                return Some(control);
+            }
+            Some(e) => e,
+        };
+        entry.min_interval = min_interval;
+        if entry.pending.is_none() && entry.interval_elapsed(now) {
+            entry.last_released_at = now;
+            return Some(control);
+        }
+        entry.pending = coalesce(entry.pending.take(), control);
+        None
+    }
+
+    /// Returns all kept controls whose interval has elapsed in the meantime.
+    pub fn release_due(&mut self, now: Instant) -> Vec<(QualifiedMappingId, RateLimitedControl)> {
+        let mut due = vec![];
+        self.entries.retain(|id, entry| {
+            if !entry.interval_elapsed(now) {
+                return true;
+            }
+            match entry.pending.take() {
+                None => {
+                    // Idle, no need to remember anything anymore.
+                    false
+                }
+                Some(control) => {
+                    entry.last_released_at = now;
+                    due.push((*id, control));
+                    true
+                }
+            }
+        });
+        due
+    }
+
+    /// Forgets all kept controls, e.g. when control has been disabled.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+impl RateLimitEntry {
+    fn interval_elapsed(&self, now: Instant) -> bool {
+        now.saturating_duration_since(self.last_released_at) >= self.min_interval
+    }
+}
+
+fn coalesce(
+    pending: Option<RateLimitedControl>,
+    control: RateLimitedControl,
+) -> Option<RateLimitedControl> {
+    let pending = match pending {
+        None => return Some(control),
+        Some(p) => p,
+    };
+    let coalesced = RateLimitedControl {
+        value: coalesce_control_values(pending.value, control.value)?,
+        options: control.options,
+        received_at: pending.received_at,
+    };
+    Some(coalesced)
+}
+
+/// Merges two consecutive control values into one.
+///
+/// The latest absolute value wins, relative increments are summed up. Returns `None` if the
+/// increments cancel each other out.
+pub fn coalesce_control_values(previous: ControlValue, next: ControlValue) -> Option<ControlValue> {
+    let value = match (previous, next) {
+        (ControlValue::Relative(a), ControlValue::Relative(b)) => {
+            let sum = a.get().saturating_add(b.get());
+            if sum == 0 {
+                // Increments cancel each other out.
+                return None;
+            }
+            ControlValue::Relative(DiscreteIncrement::new(sum))
+        }
+        (_, v) => v,
+    };
+    Some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{MappingCompartment, MappingId};
+    use helgoboss_learn::UnitValue;
+
+    const INTERVAL: Duration = Duration::from_millis(100);
+
+    fn control(value: ControlValue, received_at: Instant) -> RateLimitedControl {
+        RateLimitedControl {
+            value,
+            options: Default::default(),
+            received_at,
+        }
+    }
+
+    fn relative(increment: i32) -> ControlValue {
+        ControlValue::Relative(DiscreteIncrement::new(increment))
+    }
+
+    fn absolute(value: f64) -> ControlValue {
+        ControlValue::AbsoluteContinuous(UnitValue::new(value))
+    }
+
+    #[test]
+    fn sum_relative_increments() {
+        // Given
+        let mut limiter = ControlRateLimiter::default();
+        let id = QualifiedMappingId::new(MappingCompartment::MainMappings, MappingId::random());
+        let start = Instant::now();
+        // When
+        let first = limiter.process(id, control(relative(1), start), INTERVAL, start);
+        let t1 = start + Duration::from_millis(10);
+        let second = limiter.process(id, control(relative(2), t1), INTERVAL, t1);
+        let t2 = start + Duration::from_millis(20);
+        let third = limiter.process(id, control(relative(3), t2), INTERVAL, t2);
+        let too_early = limiter.release_due(start + Duration::from_millis(50));
+        let due = limiter.release_due(start + INTERVAL);
+        // Then
+        assert_eq!(first.unwrap().value, relative(1));
+        assert!(second.is_none());
+        assert!(third.is_none());
+        assert!(too_early.is_empty());
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].0, id);
+        assert_eq!(due[0].1.value, relative(5));
+        assert_eq!(due[0].1.received_at, t1);
+    }
+
+    #[test]
+    fn keep_latest_absolute_value() {
+        // Given
+        let mut limiter = ControlRateLimiter::default();
+        let id = QualifiedMappingId::new(MappingCompartment::MainMappings, MappingId::random());
+        let start = Instant::now();
+        // When
+        limiter.process(id, control(absolute(0.1), start), INTERVAL, start);
+        let t1 = start + Duration::from_millis(10);
+        limiter.process(id, control(absolute(0.2), t1), INTERVAL, t1);
+        let t2 = start + Duration::from_millis(20);
+        limiter.process(id, control(absolute(0.3), t2), INTERVAL, t2);
+        let due = limiter.release_due(start + INTERVAL);
+        // Then
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].1.value, absolute(0.3));
+    }
+
+    #[test]
+    fn pass_through_after_idle_interval() {
+        // Given
+        let mut limiter = ControlRateLimiter::default();
+        let id = QualifiedMappingId::new(MappingCompartment::MainMappings, MappingId::random());
+        let start = Instant::now();
+        limiter.process(id, control(absolute(0.1), start), INTERVAL, start);
+        // When
+        let released = limiter.release_due(start + INTERVAL);
+        let later = start + INTERVAL * 2;
+        let result = limiter.process(id, control(absolute(0.2), later), INTERVAL, later);
+        // Then
+        assert!(released.is_empty());
+        assert_eq!(result.unwrap().value, absolute(0.2));
+    }
+}
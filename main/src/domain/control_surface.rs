@@ -2,10 +2,11 @@ use crate::base::Global;
 use crate::domain::{
     ActivationChange, BackboneState, CompoundMappingSource, DeviceChangeDetector,
     DeviceControlInput, DeviceFeedbackOutput, DomainEventHandler, EelTransformation,
-    FeedbackOutput, FeedbackRealTimeTask, InstanceId, LifecycleMidiData, MainProcessor,
-    MidiCaptureSender, NormalRealTimeTask, OscDeviceId, OscInputDevice, OscScanResult,
-    RealTimeCompoundMappingTarget, RealTimeMapping, ReaperMessage, ReaperTarget,
-    SharedRealTimeProcessor, SourceFeedbackValue, TouchedParameterType,
+    FeedbackMaxRateMap, FeedbackOutput, FeedbackRealTimeTask, GameControllerInput, InstanceId,
+    InstancePriority, LifecycleMidiData, MainProcessor, MidiCaptureSender, NormalRealTimeTask,
+    OscDeviceId, OscInputDevice, OscScanResult, RealTimeCompoundMappingTarget, RealTimeMapping,
+    ReaperMessage, ReaperTarget, SharedRealTimeProcessor, SourceFeedbackValue,
+    SysexChunkingSettingsMap, TouchedParameterType, BEAT_TICKS_PER_BEAT,
 };
 use crossbeam_channel::Receiver;
 use helgoboss_learn::{ModeGarbage, RawMidiEvent};
@@ -17,13 +18,17 @@ use reaper_rx::ControlSurfaceRxMiddleware;
 use rosc::{OscMessage, OscPacket};
 
 use reaper_medium::{
-    CommandId, ExtSupportsExtendedTouchArgs, GetTouchStateArgs, MediaTrack, PositionInSeconds,
-    ReaProject, ReaperNormalizedFxParamValue,
+    CommandId, ExtSupportsExtendedTouchArgs, GetTouchStateArgs, MediaTrack, MidiOutputDeviceId,
+    PositionInSeconds, ReaProject, ReaperNormalizedFxParamValue,
 };
 use rxrust::prelude::*;
-use slog::debug;
+use slog::{debug, warn};
 use smallvec::SmallVec;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::time::Instant;
+
+#[cfg(feature = "realearn-meter")]
+use crate::domain::write_control_latency_metrics;
 
 type OscCaptureSender = async_channel::Sender<OscScanResult>;
 
@@ -49,16 +54,19 @@ pub struct RealearnControlSurfaceMiddleware<EH: DomainEventHandler> {
     main_task_middleware: MainTaskMiddleware,
     future_middleware: FutureMiddleware,
     counter: u64,
-    full_beats: HashMap<ReaProject, u32>,
+    beat_ticks: HashMap<ReaProject, i64>,
     metrics_enabled: bool,
     state: State,
     osc_input_devices: Vec<OscInputDevice>,
     garbage_receiver: crossbeam_channel::Receiver<Garbage>,
     device_change_detector: DeviceChangeDetector,
+    /// `None` if game controller support couldn't be initialized on this system.
+    game_controller_input: Option<GameControllerInput>,
 }
 
 pub enum Garbage {
     RawMidiEvents(Vec<RawMidiEvent>),
+    DelayedMidiEvents(Vec<(Instant, RawMidiEvent)>),
     RealTimeProcessor(SharedRealTimeProcessor),
     LifecycleMidiData(LifecycleMidiData),
     ResolvedTarget(Option<RealTimeCompoundMappingTarget>),
@@ -70,6 +78,8 @@ pub enum Garbage {
     NormalRealTimeTask(NormalRealTimeTask),
     FeedbackRealTimeTask(FeedbackRealTimeTask),
     MidiCaptureSender(MidiCaptureSender),
+    SysexChunkingSettings(SysexChunkingSettingsMap),
+    FeedbackMaxRates(FeedbackMaxRateMap<MidiOutputDeviceId>),
 }
 
 #[derive(Debug)]
@@ -115,6 +125,9 @@ pub enum InstanceOrchestrationEvent {
     /// Whenever something about instance's device usage changes (either input or output or both
     /// potentially change).
     IoUpdated(IoUpdatedEvent),
+    /// Sent by a ReaLearn instance whenever one of its parameters changes, so that linked
+    /// parameters of other instances can follow.
+    ParameterChanged(ParameterChangedEvent),
 }
 
 /// Communicates changes in which input and output device a ReaLearn instance uses or used.
@@ -122,10 +135,25 @@ pub enum InstanceOrchestrationEvent {
 pub struct IoUpdatedEvent {
     pub instance_id: InstanceId,
     pub control_input: Option<DeviceControlInput>,
+    /// Devices which single main mappings listen to instead of the control input.
+    pub control_input_overrides: HashSet<DeviceControlInput>,
     pub control_input_used: bool,
     pub feedback_output: Option<DeviceFeedbackOutput>,
+    /// Devices which single main mappings send their feedback to instead of the feedback output.
+    pub feedback_output_overrides: HashSet<DeviceFeedbackOutput>,
     pub feedback_output_used: bool,
     pub feedback_output_usage_might_have_changed: bool,
+    /// Decides which instance owns the devices if other instances use them as well.
+    pub instance_priority: InstancePriority,
+}
+
+#[derive(Debug)]
+pub struct ParameterChangedEvent {
+    pub instance_id: InstanceId,
+    pub session_id: String,
+    /// Absolute parameter index.
+    pub index: u32,
+    pub value: f32,
 }
 
 #[derive(Debug)]
@@ -139,6 +167,10 @@ pub struct SourceReleasedEvent {
 pub struct BeatChangedEvent {
     pub project: Project,
     pub new_value: PositionInSeconds,
+    pub full_beats: f64,
+    /// Beat change events are emitted for each beat tick (see [`BEAT_TICKS_PER_BEAT`]). This is
+    /// `true` if a new full beat has started. Only blinking feedback is interested in the others.
+    pub is_full_beat_change: bool,
 }
 
 #[derive(Debug)]
@@ -181,8 +213,14 @@ impl<EH: DomainEventHandler> RealearnControlSurfaceMiddleware<EH> {
         let logger = parent_logger.new(slog::o!("struct" => "RealearnControlSurfaceMiddleware"));
         let mut device_change_detector = DeviceChangeDetector::new();
         // Prevent change messages to be sent on load by polling one time and ignoring result.
-        device_change_detector.poll_for_midi_input_device_changes();
-        device_change_detector.poll_for_midi_output_device_changes();
+        device_change_detector.poll_for_device_changes();
+        let game_controller_input = match GameControllerInput::new() {
+            Ok(input) => Some(input),
+            Err(e) => {
+                warn!(logger, "Game controller input not available: {}", e);
+                None
+            }
+        };
         Self {
             logger: logger.clone(),
             change_detection_middleware: ChangeDetectionMiddleware::new(),
@@ -205,12 +243,13 @@ impl<EH: DomainEventHandler> RealearnControlSurfaceMiddleware<EH> {
                 Global::get().local_executor(),
             ),
             counter: 0,
-            full_beats: Default::default(),
+            beat_ticks: Default::default(),
             metrics_enabled,
             state: State::Normal,
             osc_input_devices: vec![],
             garbage_receiver,
             device_change_detector,
+            game_controller_input,
         }
     }
 
@@ -250,7 +289,9 @@ impl<EH: DomainEventHandler> RealearnControlSurfaceMiddleware<EH> {
         self.process_instance_orchestration_events();
         self.emit_beats_as_feedback_events();
         self.emit_device_changes_as_reaper_source_messages();
+        self.emit_game_controller_events_as_reaper_source_messages();
         self.process_incoming_osc_messages();
+        BackboneState::get().poll_shared_clip_matrices();
         self.run_main_processors();
         #[cfg(feature = "realearn-meter")]
         if self.metrics_enabled {
@@ -304,12 +345,21 @@ impl<EH: DomainEventHandler> RealearnControlSurfaceMiddleware<EH> {
             match t {
                 ProvidePrometheusMetrics(sender) => {
                     #[cfg(feature = "realearn-meter")]
-                    let text = serde_prometheus::to_string(
-                        self.meter_middleware.metrics(),
-                        Some("realearn"),
-                        HashMap::new(),
-                    )
-                    .unwrap();
+                    let text = {
+                        let mut text = serde_prometheus::to_string(
+                            self.meter_middleware.metrics(),
+                            Some("realearn"),
+                            HashMap::new(),
+                        )
+                        .unwrap();
+                        write_control_latency_metrics(
+                            &mut text,
+                            self.main_processors
+                                .iter()
+                                .map(|p| (p.instance_id(), p.control_latencies())),
+                        );
+                        text
+                    };
                     #[cfg(not(feature = "realearn-meter"))]
                     let text = String::new();
                     let _ = sender.send(text);
@@ -397,43 +447,53 @@ impl<EH: DomainEventHandler> RealearnControlSurfaceMiddleware<EH> {
                         }
                     }
                 }
+                ParameterChanged(e) => {
+                    for p in self
+                        .main_processors
+                        .iter()
+                        .filter(|p| p.instance_id() != &e.instance_id)
+                    {
+                        p.follow_linked_parameter(&e);
+                    }
+                }
                 IoUpdated(e) => {
                     let backbone_state = BackboneState::get();
-                    let feedback_dev_usage_changed = backbone_state.update_io_usage(
+                    let control_inputs: HashSet<_> = if e.control_input_used {
+                        e.control_input
+                            .into_iter()
+                            .chain(e.control_input_overrides.iter().copied())
+                            .collect()
+                    } else {
+                        HashSet::new()
+                    };
+                    let feedback_outputs: HashSet<_> = if e.feedback_output_used {
+                        e.feedback_output
+                            .into_iter()
+                            .chain(e.feedback_output_overrides.iter().copied())
+                            .collect()
+                    } else {
+                        HashSet::new()
+                    };
+                    let affected_instances = backbone_state.update_io_usage(
                         &e.instance_id,
-                        if e.control_input_used {
-                            e.control_input
-                        } else {
-                            None
-                        },
-                        if e.feedback_output_used {
-                            e.feedback_output
-                        } else {
-                            None
-                        },
+                        e.instance_priority,
+                        &control_inputs,
+                        &feedback_outputs,
                     );
-                    if feedback_dev_usage_changed
-                        && backbone_state.lives_on_upper_floor(&e.instance_id)
-                    {
+                    if !affected_instances.is_empty() {
                         debug!(
                             self.logger,
-                            "Upper-floor instance {} {} feedback output",
+                            "Instance {} (priority {}) changed feedback output ownership of {:?}",
                             e.instance_id,
-                            if e.feedback_output_used {
-                                "claimed"
-                            } else {
-                                "released"
-                            }
+                            e.instance_priority,
+                            affected_instances
                         );
-                        if let Some(feedback_output) = e.feedback_output {
-                            // Give lower-floor instances the chance to cancel or reactivate.
-                            self.main_processors
-                                .iter()
-                                .filter(|p| p.instance_id() != &e.instance_id)
-                                .for_each(|p| {
-                                    p.handle_change_of_some_upper_floor_instance(feedback_output)
-                                });
-                        }
+                        // Give instances which lost or gained ownership the chance to cancel or
+                        // reactivate.
+                        self.main_processors
+                            .iter()
+                            .filter(|p| affected_instances.contains(p.instance_id()))
+                            .for_each(|p| p.handle_change_of_feedback_output_ownership());
                     }
                 }
             }
@@ -447,10 +507,14 @@ impl<EH: DomainEventHandler> RealearnControlSurfaceMiddleware<EH> {
             } else {
                 project.edit_cursor_position()
             };
-            if self.record_possible_beat_change(project, reference_pos) {
+            if let Some((full_beats, is_full_beat_change)) =
+                self.record_possible_beat_change(project, reference_pos)
+            {
                 let event = AdditionalFeedbackEvent::BeatChanged(BeatChangedEvent {
                     project,
                     new_value: reference_pos,
+                    full_beats,
+                    is_full_beat_change,
                 });
                 for p in &mut self.main_processors {
                     p.process_additional_feedback_event(&event);
@@ -462,21 +526,17 @@ impl<EH: DomainEventHandler> RealearnControlSurfaceMiddleware<EH> {
     fn emit_device_changes_as_reaper_source_messages(&mut self) {
         // Check roughly every 2 seconds
         if self.counter % (30 * 2) == 0 {
-            let midi_in_diff = self
-                .device_change_detector
-                .poll_for_midi_input_device_changes();
-            let midi_out_diff = self
-                .device_change_detector
-                .poll_for_midi_output_device_changes();
+            let changes = self.device_change_detector.poll_for_device_changes();
             let mut msgs = Vec::with_capacity(2);
-            if !midi_in_diff.added_devices.is_empty() || !midi_out_diff.added_devices.is_empty() {
+            if changes.devices_added() {
+                changes.reopen_added_devices();
                 msgs.push(ReaperMessage::MidiDevicesConnected);
             }
-            if !midi_in_diff.removed_devices.is_empty() || !midi_out_diff.removed_devices.is_empty()
-            {
+            if changes.devices_removed() {
                 msgs.push(ReaperMessage::MidiDevicesDisconnected);
             }
             for p in &mut self.main_processors {
+                p.process_device_changes(&changes);
                 for msg in &msgs {
                     p.process_reaper_message(msg);
                 }
@@ -484,6 +544,19 @@ impl<EH: DomainEventHandler> RealearnControlSurfaceMiddleware<EH> {
         }
     }
 
+    fn emit_game_controller_events_as_reaper_source_messages(&mut self) {
+        let input = match self.game_controller_input.as_mut() {
+            None => return,
+            Some(i) => i,
+        };
+        for evt in input.poll() {
+            let msg = ReaperMessage::GameController(*evt);
+            for p in &mut self.main_processors {
+                p.process_reaper_message(&msg);
+            }
+        }
+    }
+
     fn log_debug_info(&self) {
         // Summary
         let msg = format!(
@@ -491,8 +564,13 @@ impl<EH: DomainEventHandler> RealearnControlSurfaceMiddleware<EH> {
             # Backbone control surface\n\
             \n\
             - Garbage count: {} \n\
+            - Game controllers: {:?} \n\
             ",
             self.garbage_receiver.len(),
+            self.game_controller_input
+                .as_ref()
+                .map(|i| i.controllers())
+                .unwrap_or_default(),
         );
         Reaper::get().show_console_msg(msg);
     }
@@ -565,17 +643,25 @@ impl<EH: DomainEventHandler> RealearnControlSurfaceMiddleware<EH> {
         })
     }
 
+    /// Returns the new beat position and whether a new full beat has started if the position
+    /// moved to another beat tick.
     fn record_possible_beat_change(
         &mut self,
         project: Project,
         reference_pos: PositionInSeconds,
-    ) -> bool {
+    ) -> Option<(f64, bool)> {
         let beat_info = project.beat_info_at(reference_pos);
-        let new_full_beats = beat_info.full_beats.get() as _;
-        let full_beats = self.full_beats.entry(project.raw()).or_default();
-        let beat_changed = new_full_beats != *full_beats;
-        *full_beats = new_full_beats;
-        beat_changed
+        let full_beats = beat_info.full_beats.get();
+        let ticks_per_beat = BEAT_TICKS_PER_BEAT as i64;
+        let new_tick = (full_beats * ticks_per_beat as f64).floor() as i64;
+        let tick = self.beat_ticks.entry(project.raw()).or_default();
+        if new_tick == *tick {
+            return None;
+        }
+        let is_full_beat_change =
+            new_tick.div_euclid(ticks_per_beat) != tick.div_euclid(ticks_per_beat);
+        *tick = new_tick;
+        Some((full_beats, is_full_beat_change))
     }
 }
 
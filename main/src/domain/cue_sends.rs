@@ -0,0 +1,215 @@
+use crate::domain::MappingId;
+use reaper_high::{Reaper, SendPartnerType, Track, TrackRoute, Volume};
+use reaper_medium::{SendTarget, TrackSendAttributeKey, TrackSendCategory};
+
+/// Temporary hardware output sends which route tracks to a dedicated cue output (e.g.
+/// headphones), so they can be pre-listened independently from the main mix.
+///
+/// The sends are created on demand by the cue targets and removed as soon as the mapping which
+/// created them is switched off or the instance goes away.
+#[derive(Debug, Default)]
+pub struct CueSends {
+    sends: Vec<CueSend>,
+}
+
+#[derive(Debug)]
+struct CueSend {
+    owner: MappingId,
+    track: Track,
+    /// Index of the first hardware output channel of the (stereo) cue output.
+    cue_output: u32,
+    /// Index among the hardware output sends of the track at the time the send was created.
+    ///
+    /// The user might add or remove hardware output sends afterwards, so this is just a hint.
+    send_index: u32,
+}
+
+impl CueSend {
+    fn matches(&self, track: &Track, cue_output: u32) -> bool {
+        &self.track == track && self.cue_output == cue_output
+    }
+
+    /// Returns the current index of this send among the hardware output sends of the track or
+    /// `None` if it doesn't exist anymore.
+    fn current_send_index(&self) -> Option<u32> {
+        if !self.track.is_available() {
+            return None;
+        }
+        if goes_to_cue_output(&self.track, self.send_index, self.cue_output) {
+            return Some(self.send_index);
+        }
+        find_hardware_output_send(&self.track, self.cue_output)
+    }
+
+    fn route(&self) -> Option<TrackRoute> {
+        let index = self.current_send_index()?;
+        self.track
+            .typed_send_by_index(SendPartnerType::HardwareOutput, index)
+    }
+}
+
+impl CueSends {
+    /// Returns the volume of the cue send if the given track is currently cued on the given
+    /// output.
+    pub fn volume(&self, track: &Track, cue_output: u32) -> Option<Volume> {
+        let send = self.sends.iter().find(|s| s.matches(track, cue_output))?;
+        send.route()?.volume().ok()
+    }
+
+    /// Cues the given track with the given volume or stops cueing it if the volume is `None`.
+    ///
+    /// A mapping cues at most one track at a time. If it cued another track before (e.g. because
+    /// a clip moved to another column), the old cue send is removed.
+    pub fn cue(
+        &mut self,
+        owner: MappingId,
+        track: &Track,
+        cue_output: u32,
+        volume: Option<Volume>,
+    ) -> Result<(), &'static str> {
+        self.remove_where(|s| s.owner == owner && !s.matches(track, cue_output));
+        let volume = match volume {
+            None => {
+                self.remove_where(|s| s.matches(track, cue_output));
+                return Ok(());
+            }
+            Some(v) => v,
+        };
+        let existing_index = self.sends.iter().position(|s| s.matches(track, cue_output));
+        let index = match existing_index {
+            Some(i) => {
+                self.sends[i].owner = owner;
+                i
+            }
+            None => {
+                let send = CueSend {
+                    owner,
+                    track: track.clone(),
+                    cue_output,
+                    send_index: create_hardware_output_send(track, cue_output)?,
+                };
+                self.sends.push(send);
+                self.sends.len() - 1
+            }
+        };
+        let route = self.sends[index].route().ok_or("cue send disappeared")?;
+        route
+            .set_volume(volume)
+            .map_err(|_| "couldn't set cue volume")?;
+        Ok(())
+    }
+
+    /// Returns the tracks which are currently cued, each one together with its cue output.
+    ///
+    /// Used for remembering the cue sends when saving, because they end up in the project as
+    /// normal hardware output sends.
+    pub fn cued_tracks(&self) -> impl Iterator<Item = (&Track, u32)> + '_ {
+        self.sends.iter().map(|s| (&s.track, s.cue_output))
+    }
+
+    /// Removes cue sends which were left over in the project from a previous session (e.g. because
+    /// the project was saved while cueing).
+    ///
+    /// Cue sends which are currently managed by this instance are left alone.
+    pub fn remove_stale(&self, stale: impl IntoIterator<Item = (Track, u32)>) {
+        for (track, cue_output) in stale {
+            if !track.is_available() || self.sends.iter().any(|s| s.matches(&track, cue_output)) {
+                continue;
+            }
+            if let Some(index) = find_hardware_output_send(&track, cue_output) {
+                remove_hardware_output_send_at(&track, index);
+            }
+        }
+    }
+
+    /// Removes the cue sends of all mappings which don't satisfy the given predicate.
+    pub fn retain_owners(&mut self, mut keep: impl FnMut(MappingId) -> bool) {
+        self.remove_where(|s| !keep(s.owner));
+    }
+
+    fn remove_where(&mut self, mut predicate: impl FnMut(&CueSend) -> bool) {
+        while let Some(i) = self.sends.iter().position(|s| predicate(s)) {
+            let removed = self.sends.remove(i);
+            // If the send is gone already (e.g. removed by the user), we must not remove anything,
+            // otherwise we might hit one of the user's own sends.
+            let removed_index = match removed.current_send_index() {
+                None => continue,
+                Some(i) => i,
+            };
+            remove_hardware_output_send_at(&removed.track, removed_index);
+            // The hardware output sends behind the removed one move up by one.
+            for s in self.sends.iter_mut() {
+                if s.track == removed.track && s.send_index > removed_index {
+                    s.send_index -= 1;
+                }
+            }
+        }
+    }
+}
+
+impl Drop for CueSends {
+    fn drop(&mut self) {
+        self.remove_where(|_| true);
+    }
+}
+
+fn create_hardware_output_send(track: &Track, cue_output: u32) -> Result<u32, &'static str> {
+    if !track.is_available() {
+        return Err("track not available");
+    }
+    let reaper = Reaper::get().medium_reaper();
+    unsafe {
+        let send_index = reaper
+            .create_track_send(track.raw(), SendTarget::HardwareOutput)
+            .map_err(|_| "couldn't create cue send")?;
+        reaper
+            .set_track_send_info_value(
+                track.raw(),
+                TrackSendCategory::HardwareOutput,
+                send_index,
+                TrackSendAttributeKey::DstChan,
+                cue_output as f64,
+            )
+            .map_err(|_| "couldn't route cue send to cue output")?;
+        Ok(send_index)
+    }
+}
+
+/// Returns the index of the last hardware output send of the given track which goes to the given
+/// cue output.
+///
+/// The last one because that's where newly created sends end up.
+fn find_hardware_output_send(track: &Track, cue_output: u32) -> Option<u32> {
+    let count = track.typed_sends(SendPartnerType::HardwareOutput).count() as u32;
+    (0..count)
+        .rev()
+        .find(|i| goes_to_cue_output(track, *i, cue_output))
+}
+
+fn goes_to_cue_output(track: &Track, send_index: u32, cue_output: u32) -> bool {
+    if track
+        .typed_send_by_index(SendPartnerType::HardwareOutput, send_index)
+        .is_none()
+    {
+        return false;
+    }
+    let dst_chan = unsafe {
+        Reaper::get().medium_reaper().get_track_send_info_value(
+            track.raw(),
+            TrackSendCategory::HardwareOutput,
+            send_index,
+            TrackSendAttributeKey::DstChan,
+        )
+    };
+    dst_chan as u32 == cue_output
+}
+
+fn remove_hardware_output_send_at(track: &Track, send_index: u32) {
+    unsafe {
+        let _ = Reaper::get().medium_reaper().remove_track_send(
+            track.raw(),
+            TrackSendCategory::HardwareOutput,
+            send_index,
+        );
+    }
+}
@@ -1,4 +1,5 @@
-use reaper_high::Reaper;
+use crate::domain::QualifiedMappingId;
+use reaper_high::{MidiInputDevice, MidiOutputDevice, Reaper};
 use reaper_medium::{MidiInputDeviceId, MidiOutputDeviceId};
 use std::collections::HashSet;
 use std::hash::Hash;
@@ -14,6 +15,13 @@ impl DeviceChangeDetector {
         Default::default()
     }
 
+    pub fn poll_for_device_changes(&mut self) -> DeviceChanges {
+        DeviceChanges {
+            midi_in: self.poll_for_midi_input_device_changes(),
+            midi_out: self.poll_for_midi_output_device_changes(),
+        }
+    }
+
     pub fn poll_for_midi_input_device_changes(&mut self) -> DeviceDiff<MidiInputDeviceId> {
         let connected_devs: HashSet<_> = Reaper::get()
             .midi_input_devices()
@@ -37,11 +45,129 @@ impl DeviceChangeDetector {
     }
 }
 
+#[derive(Debug, Default)]
+pub struct DeviceChanges {
+    pub midi_in: DeviceDiff<MidiInputDeviceId>,
+    pub midi_out: DeviceDiff<MidiOutputDeviceId>,
+}
+
+impl DeviceChanges {
+    pub fn devices_added(&self) -> bool {
+        !self.midi_in.added_devices.is_empty() || !self.midi_out.added_devices.is_empty()
+    }
+
+    pub fn devices_removed(&self) -> bool {
+        !self.midi_in.removed_devices.is_empty() || !self.midi_out.removed_devices.is_empty()
+    }
+
+    /// Makes REAPER open devices which have been reconnected.
+    ///
+    /// REAPER doesn't do that automatically, so without this, a controller which has been
+    /// unplugged during playback would stay silent until the user resets the MIDI devices. Only the
+    /// reconnected devices are reopened. Resetting all devices would interrupt the MIDI streams of
+    /// all other devices.
+    pub fn reopen_added_devices(&self) {
+        let low = Reaper::get().medium_reaper().low();
+        for id in &self.midi_in.added_devices {
+            if !MidiInputDevice::new(*id).is_open() {
+                low.midi_init(id.get() as i32, -1);
+            }
+        }
+        for id in &self.midi_out.added_devices {
+            if !MidiOutputDevice::new(*id).is_open() {
+                low.midi_init(-1, id.get() as i32);
+            }
+        }
+    }
+}
+
+/// Connection status of the MIDI devices used by one ReaLearn instance.
+///
+/// Used devices are the control input and feedback output of the instance and the devices which
+/// single mappings listen or send to instead.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct IoConnectionStatus {
+    /// Used MIDI input devices which are currently unplugged.
+    pub missing_midi_input_devices: HashSet<MidiInputDeviceId>,
+    /// Used MIDI output devices which are currently unplugged.
+    pub missing_midi_output_devices: HashSet<MidiOutputDeviceId>,
+    /// Mappings which are suspended because they use one of the missing devices.
+    ///
+    /// Maintained by the main processor.
+    pub suspended_mappings: HashSet<QualifiedMappingId>,
+}
+
+impl IoConnectionStatus {
+    pub fn is_fully_connected(&self) -> bool {
+        self.missing_midi_input_devices.is_empty() && self.missing_midi_output_devices.is_empty()
+    }
+
+    pub fn midi_input_device_is_missing(&self, id: MidiInputDeviceId) -> bool {
+        self.missing_midi_input_devices.contains(&id)
+    }
+
+    pub fn midi_output_device_is_missing(&self, id: MidiOutputDeviceId) -> bool {
+        self.missing_midi_output_devices.contains(&id)
+    }
+
+    pub fn mapping_is_suspended(&self, id: QualifiedMappingId) -> bool {
+        self.suspended_mappings.contains(&id)
+    }
+
+    /// Applies the given device changes to this status.
+    ///
+    /// Devices which are not used anymore are forgotten. Returns `true` if at least one of the
+    /// missing devices has been reconnected. In this case the consumer should resync and resend
+    /// all feedback.
+    pub fn update(
+        &mut self,
+        changes: &DeviceChanges,
+        used_inputs: &HashSet<MidiInputDeviceId>,
+        used_outputs: &HashSet<MidiOutputDeviceId>,
+    ) -> bool {
+        let reconnected_inputs = update_missing_devices(
+            &mut self.missing_midi_input_devices,
+            &changes.midi_in,
+            used_inputs,
+        );
+        let reconnected_outputs = update_missing_devices(
+            &mut self.missing_midi_output_devices,
+            &changes.midi_out,
+            used_outputs,
+        );
+        reconnected_inputs || reconnected_outputs
+    }
+}
+
+fn update_missing_devices<T: Eq + Hash + Copy>(
+    missing_devices: &mut HashSet<T>,
+    diff: &DeviceDiff<T>,
+    used_devices: &HashSet<T>,
+) -> bool {
+    missing_devices.retain(|id| used_devices.contains(id));
+    let mut reconnected = false;
+    for id in &diff.added_devices {
+        reconnected |= missing_devices.remove(id);
+    }
+    missing_devices.extend(diff.removed_devices.intersection(used_devices));
+    reconnected
+}
+
+#[derive(Debug)]
 pub struct DeviceDiff<T> {
     pub added_devices: HashSet<T>,
     pub removed_devices: HashSet<T>,
 }
 
+impl<T> Default for DeviceDiff<T> {
+    fn default() -> Self {
+        Self {
+            added_devices: Default::default(),
+            removed_devices: Default::default(),
+        }
+    }
+}
+
 impl<T: Eq + Hash + Copy> DeviceDiff<T> {
     fn new(old_devs: &HashSet<T>, new_devs: &HashSet<T>) -> Self {
         Self {
@@ -50,3 +176,113 @@ impl<T: Eq + Hash + Copy> DeviceDiff<T> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disconnect_and_reconnect_feedback_output() {
+        // Given
+        let mut status = IoConnectionStatus::default();
+        let out = MidiOutputDeviceId::new(3);
+        let used_outputs = [out].iter().copied().collect();
+        let removed = DeviceChanges {
+            midi_out: DeviceDiff {
+                added_devices: Default::default(),
+                removed_devices: [out].iter().copied().collect(),
+            },
+            ..Default::default()
+        };
+        let added = DeviceChanges {
+            midi_out: DeviceDiff {
+                added_devices: [out].iter().copied().collect(),
+                removed_devices: Default::default(),
+            },
+            ..Default::default()
+        };
+        // When
+        let reconnected_after_removal = status.update(&removed, &HashSet::new(), &used_outputs);
+        let status_after_removal = status.clone();
+        let reconnected_after_adding = status.update(&added, &HashSet::new(), &used_outputs);
+        // Then
+        assert!(!reconnected_after_removal);
+        assert!(status_after_removal.midi_output_device_is_missing(out));
+        assert!(reconnected_after_adding);
+        assert!(status.is_fully_connected());
+    }
+
+    #[test]
+    fn ignore_foreign_devices() {
+        // Given
+        let mut status = IoConnectionStatus::default();
+        let changes = DeviceChanges {
+            midi_in: DeviceDiff {
+                added_devices: Default::default(),
+                removed_devices: [MidiInputDeviceId::new(1)].iter().copied().collect(),
+            },
+            ..Default::default()
+        };
+        let used_inputs = [MidiInputDeviceId::new(2)].iter().copied().collect();
+        // When
+        let reconnected = status.update(&changes, &used_inputs, &HashSet::new());
+        // Then
+        assert!(!reconnected);
+        assert!(status.is_fully_connected());
+    }
+
+    #[test]
+    fn track_each_missing_device() {
+        // Given
+        let mut status = IoConnectionStatus::default();
+        let instance_input = MidiInputDeviceId::new(1);
+        let override_input = MidiInputDeviceId::new(2);
+        let used_inputs = [instance_input, override_input].iter().copied().collect();
+        let removed = DeviceChanges {
+            midi_in: DeviceDiff {
+                added_devices: Default::default(),
+                removed_devices: [instance_input, override_input].iter().copied().collect(),
+            },
+            ..Default::default()
+        };
+        let added = DeviceChanges {
+            midi_in: DeviceDiff {
+                added_devices: [override_input].iter().copied().collect(),
+                removed_devices: Default::default(),
+            },
+            ..Default::default()
+        };
+        // When
+        status.update(&removed, &used_inputs, &HashSet::new());
+        let reconnected = status.update(&added, &used_inputs, &HashSet::new());
+        // Then
+        assert!(reconnected);
+        assert!(status.midi_input_device_is_missing(instance_input));
+        assert!(!status.midi_input_device_is_missing(override_input));
+        assert!(!status.is_fully_connected());
+    }
+
+    #[test]
+    fn forget_devices_which_are_not_used_anymore() {
+        // Given
+        let mut status = IoConnectionStatus::default();
+        let input = MidiInputDeviceId::new(1);
+        let removed = DeviceChanges {
+            midi_in: DeviceDiff {
+                added_devices: Default::default(),
+                removed_devices: [input].iter().copied().collect(),
+            },
+            ..Default::default()
+        };
+        status.update(
+            &removed,
+            &[input].iter().copied().collect(),
+            &HashSet::new(),
+        );
+        // When
+        let reconnected = status.update(&Default::default(), &HashSet::new(), &HashSet::new());
+        // Then
+        assert!(!reconnected);
+        assert!(status.is_fully_connected());
+    }
+}
@@ -0,0 +1,166 @@
+use derive_more::Display;
+use enum_iterator::IntoEnumIterator;
+use helgoboss_learn::{ControlValue, DiscreteIncrement};
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// Increments arriving at least this fast are multiplied by the maximum factor.
+const FAST_INTERVAL: Duration = Duration::from_millis(5);
+/// Increments arriving this slow or slower are not accelerated at all.
+const SLOW_INTERVAL: Duration = Duration::from_millis(80);
+/// Factor applied when turning the encoder as fast as possible.
+const MAX_FACTOR: f64 = 10.0;
+
+pub const DEFAULT_ENCODER_ACCELERATION_CURVE: f64 = 2.0;
+
+/// Settings for multiplying relative increments depending on how fast an encoder is turned.
+///
+/// Makes it possible to do fine adjustments by turning slowly and coarse ones by turning fast.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct EncoderAccelerationSettings {
+    pub profile: EncoderAccelerationProfile,
+    /// Exponent of the exponential profile. Higher values keep moderately fast turns finer.
+    pub curve: f64,
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    Debug,
+    Serialize,
+    Deserialize,
+    IntoEnumIterator,
+    TryFromPrimitive,
+    IntoPrimitive,
+    Display,
+)]
+#[repr(usize)]
+pub enum EncoderAccelerationProfile {
+    #[serde(rename = "off")]
+    #[display(fmt = "Off")]
+    Off,
+    /// The factor grows proportionally with the turning speed.
+    #[serde(rename = "linear")]
+    #[display(fmt = "Linear")]
+    Linear,
+    /// The factor stays small for slow and moderate turns and grows steeply for fast ones.
+    #[serde(rename = "exponential")]
+    #[display(fmt = "Exponential")]
+    Exponential,
+}
+
+impl Default for EncoderAccelerationProfile {
+    fn default() -> Self {
+        EncoderAccelerationProfile::Off
+    }
+}
+
+impl EncoderAccelerationSettings {
+    /// Returns the factor for the given normalized speed (0.0 = slow, 1.0 = fast).
+    fn factor(&self, speed: f64) -> f64 {
+        use EncoderAccelerationProfile::*;
+        let shaped_speed = match self.profile {
+            Off => return 1.0,
+            Linear => speed,
+            Exponential => speed.powf(self.curve.max(f64::EPSILON)),
+        };
+        1.0 + (MAX_FACTOR - 1.0) * shaped_speed
+    }
+}
+
+/// Keeps track of the timing of previous increments in order to derive the turning speed.
+#[derive(Clone, Debug, Default)]
+pub struct EncoderAccelerator {
+    /// When the last increment arrived and whether it was positive.
+    last_increment: Option<(Instant, bool)>,
+}
+
+impl EncoderAccelerator {
+    /// Returns the given control value with an accelerated increment.
+    ///
+    /// Absolute values are let through unchanged. A change of direction resets the acceleration.
+    pub fn process(
+        &mut self,
+        settings: &EncoderAccelerationSettings,
+        value: ControlValue,
+        now: Instant,
+    ) -> ControlValue {
+        let increment = match value {
+            ControlValue::Relative(i) => i,
+            _ => return value,
+        };
+        let is_positive = increment.is_positive();
+        let speed = match self.last_increment {
+            Some((last_time, last_was_positive)) if last_was_positive == is_positive => {
+                normalized_speed(now.saturating_duration_since(last_time))
+            }
+            _ => 0.0,
+        };
+        self.last_increment = Some((now, is_positive));
+        let factor = settings.factor(speed);
+        let accelerated = (increment.get() as f64 * factor).round() as i32;
+        ControlValue::Relative(DiscreteIncrement::new(accelerated))
+    }
+}
+
+fn normalized_speed(interval: Duration) -> f64 {
+    if interval >= SLOW_INTERVAL {
+        return 0.0;
+    }
+    if interval <= FAST_INTERVAL {
+        return 1.0;
+    }
+    let range = (SLOW_INTERVAL - FAST_INTERVAL).as_secs_f64();
+    (SLOW_INTERVAL - interval).as_secs_f64() / range
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accelerate_fast_turns_only() {
+        // Given
+        let settings = EncoderAccelerationSettings {
+            profile: EncoderAccelerationProfile::Linear,
+            curve: 2.0,
+        };
+        let mut accelerator = EncoderAccelerator::default();
+        let start = Instant::now();
+        // When
+        let first = accelerator.process(&settings, rel(1), start);
+        let slow = accelerator.process(&settings, rel(1), start + Duration::from_millis(200));
+        let fast = accelerator.process(&settings, rel(1), start + Duration::from_millis(202));
+        let reversed = accelerator.process(&settings, rel(-1), start + Duration::from_millis(204));
+        // Then
+        assert_eq!(first, rel(1));
+        assert_eq!(slow, rel(1));
+        assert_eq!(fast, rel(10));
+        assert_eq!(reversed, rel(-1));
+    }
+
+    #[test]
+    fn exponential_keeps_moderate_turns_finer() {
+        // Given
+        let linear = EncoderAccelerationSettings {
+            profile: EncoderAccelerationProfile::Linear,
+            curve: 2.0,
+        };
+        let exponential = EncoderAccelerationSettings {
+            profile: EncoderAccelerationProfile::Exponential,
+            curve: 2.0,
+        };
+        // When
+        let speed = normalized_speed(Duration::from_millis(42));
+        // Then
+        assert!(exponential.factor(speed) < linear.factor(speed));
+        assert_eq!(exponential.factor(1.0), MAX_FACTOR);
+    }
+
+    fn rel(increment: i32) -> ControlValue {
+        ControlValue::Relative(DiscreteIncrement::new(increment))
+    }
+}
@@ -1,8 +1,10 @@
 use crate::domain::{
-    CompoundMappingTarget, MappingCompartment, MappingId, MessageCaptureResult, ParameterArray,
-    ProjectionFeedbackValue, QualifiedMappingId,
+    CompoundMappingTarget, MappingCompartment, MappingId, MappingModeSetting, MessageCaptureResult,
+    ParameterArray, ProjectionFeedbackValue, QualifiedMappingId,
 };
-use helgoboss_learn::AbsoluteValue;
+use derive_more::Display;
+use helgoboss_learn::{AbsoluteValue, UnitValue};
+use serde::Serialize;
 use std::collections::HashSet;
 use std::fmt::Debug;
 
@@ -19,6 +21,10 @@ pub enum DomainEvent<'a> {
     MappingMatched(MappingMatchedEvent),
     FullResyncRequested,
     MappingEnabledChangeRequested(MappingEnabledChangeRequestedEvent),
+    MappingModeChangeRequested(MappingModeChangeRequestedEvent),
+    FeedbackLoopDetected(FeedbackLoopDetectedEvent),
+    /// Recording has been started by a transport target.
+    RecordingStarted,
 }
 
 #[derive(Clone, Debug)]
@@ -41,6 +47,23 @@ pub struct MappingEnabledChangeRequestedEvent {
     pub is_enabled: bool,
 }
 
+#[derive(Copy, Clone, Debug)]
+pub struct MappingModeChangeRequestedEvent {
+    pub compartment: MappingCompartment,
+    pub mapping_id: MappingId,
+    pub setting: MappingModeSetting,
+    pub value: UnitValue,
+}
+
+/// Control input of a mapping turned out to be the echo of our own feedback, over and over again.
+#[derive(Copy, Clone, Debug)]
+pub struct FeedbackLoopDetectedEvent {
+    pub compartment: MappingCompartment,
+    pub mapping_id: MappingId,
+    /// Whether control input from the affected source is ignored for a while.
+    pub suppressed: bool,
+}
+
 #[derive(Copy, Clone, Debug)]
 pub struct MappingMatchedEvent {
     pub compartment: MappingCompartment,
@@ -62,6 +85,25 @@ pub struct TargetValueChangedEvent<'a> {
     pub mapping_id: MappingId,
     pub targets: &'a [CompoundMappingTarget],
     pub new_value: AbsoluteValue,
+    pub origin: TargetValueChangeOrigin,
+}
+
+/// What caused a target value to change.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Display, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TargetValueChangeOrigin {
+    /// The mapping itself controlled the target.
+    #[display(fmt = "ReaLearn control")]
+    Control,
+    /// Another mapping in the same group controlled the target.
+    #[display(fmt = "group interaction")]
+    GroupInteraction,
+    /// A mapping snapshot has been loaded.
+    #[display(fmt = "mapping snapshot")]
+    SnapshotLoad,
+    /// The target value has been changed from outside of ReaLearn (e.g. in REAPER itself).
+    #[display(fmt = "external change")]
+    External,
 }
 
 pub trait DomainEventHandler: Debug {
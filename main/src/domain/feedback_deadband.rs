@@ -0,0 +1,162 @@
+use helgoboss_learn::UnitValue;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct FeedbackDeadband {
+    /// Minimum difference to the previously sent value.
+    pub epsilon: UnitValue,
+    /// Numeric value which is about to be sent.
+    pub value: UnitValue,
+}
+
+impl FeedbackDeadband {
+    /// Returns `true` if the value is too close to the previously sent one.
+    ///
+    /// The extremes are always let through, otherwise a motorized fader might never reach its end
+    /// positions. Exact duplicates are taken care of by the usual duplicate blocking.
+    pub fn suppresses(&self, previous_value: UnitValue) -> bool {
+        let is_extreme = self.value.is_zero() || self.value == UnitValue::MAX;
+        !is_extreme && (self.value.get() - previous_value.get()).abs() < self.epsilon.get()
+    }
+}
+
+/// Remembers the numeric values previously sent to each feedback address by mappings with
+/// feedback deadband.
+#[derive(Debug)]
+pub struct FeedbackDeadbandMemory<A> {
+    last_values: HashMap<A, UnitValue>,
+}
+
+impl<A> Default for FeedbackDeadbandMemory<A> {
+    fn default() -> Self {
+        Self {
+            last_values: Default::default(),
+        }
+    }
+}
+
+impl<A: Eq + Hash> FeedbackDeadbandMemory<A> {
+    /// Returns `true` if the value is within the deadband of the value previously sent to the same
+    /// address and therefore should be blocked.
+    ///
+    /// If it's not blocked, it's remembered as the value sent last. Feedback after control is never
+    /// blocked. Feedback without deadband makes the address forget its value, so the next value
+    /// with deadband is always sent.
+    pub fn blocks(
+        &mut self,
+        address: A,
+        deadband: Option<FeedbackDeadband>,
+        is_feedback_after_control: bool,
+    ) -> bool {
+        match deadband {
+            Some(d) => {
+                let is_within_deadband = self
+                    .last_values
+                    .get(&address)
+                    .map(|prev| d.suppresses(*prev))
+                    .unwrap_or(false);
+                if !is_feedback_after_control && is_within_deadband {
+                    return true;
+                }
+                self.last_values.insert(address, d.value);
+                false
+            }
+            None => {
+                self.last_values.remove(&address);
+                false
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.last_values.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn suppress_values_closer_than_epsilon() {
+        // Given
+        let deadband = |value| FeedbackDeadband {
+            epsilon: UnitValue::new(0.25),
+            value: UnitValue::new(value),
+        };
+        // Then
+        assert!(deadband(0.5).suppresses(UnitValue::new(0.3)));
+        assert!(!deadband(0.5).suppresses(UnitValue::new(0.25)));
+        assert!(!deadband(0.5).suppresses(UnitValue::new(0.2)));
+        assert!(deadband(0.5).suppresses(UnitValue::new(0.7)));
+        assert!(!deadband(0.5).suppresses(UnitValue::new(0.75)));
+    }
+
+    #[test]
+    fn always_let_extremes_through() {
+        // Given
+        let deadband = |value| FeedbackDeadband {
+            epsilon: UnitValue::new(0.1),
+            value: UnitValue::new(value),
+        };
+        // Then
+        assert!(!deadband(0.0).suppresses(UnitValue::new(0.05)));
+        assert!(!deadband(1.0).suppresses(UnitValue::new(0.95)));
+    }
+
+    #[test]
+    fn send_first_value() {
+        // Given
+        let mut memory = FeedbackDeadbandMemory::default();
+        // When
+        let blocked = memory.blocks(1, Some(deadband(0.5)), false);
+        // Then
+        assert!(!blocked);
+        assert!(memory.blocks(1, Some(deadband(0.55)), false));
+        assert!(!memory.blocks(1, Some(deadband(0.65)), false));
+    }
+
+    #[test]
+    fn track_addresses_separately() {
+        // Given
+        let mut memory = FeedbackDeadbandMemory::default();
+        memory.blocks(1, Some(deadband(0.5)), false);
+        // When
+        let other_address_blocked = memory.blocks(2, Some(deadband(0.55)), false);
+        // Then
+        assert!(!other_address_blocked);
+        assert!(memory.blocks(1, Some(deadband(0.55)), false));
+    }
+
+    #[test]
+    fn forget_value_when_deadband_is_gone() {
+        // Given
+        let mut memory = FeedbackDeadbandMemory::default();
+        memory.blocks(1, Some(deadband(0.5)), false);
+        // When
+        let blocked_without_deadband = memory.blocks(1, None, false);
+        // Then
+        assert!(!blocked_without_deadband);
+        assert!(!memory.blocks(1, Some(deadband(0.55)), false));
+    }
+
+    #[test]
+    fn let_feedback_after_control_through() {
+        // Given
+        let mut memory = FeedbackDeadbandMemory::default();
+        memory.blocks(1, Some(deadband(0.5)), false);
+        // When
+        let blocked = memory.blocks(1, Some(deadband(0.55)), true);
+        // Then
+        assert!(!blocked);
+        assert!(memory.blocks(1, Some(deadband(0.6)), false));
+    }
+
+    fn deadband(value: f64) -> FeedbackDeadband {
+        FeedbackDeadband {
+            epsilon: UnitValue::new(0.1),
+            value: UnitValue::new(value),
+        }
+    }
+}
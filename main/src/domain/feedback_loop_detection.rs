@@ -0,0 +1,271 @@
+use crate::domain::CompoundMappingSourceAddress;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Control input which arrives at most this long after feedback has been sent to the same address
+/// (and which corresponds to that feedback) is considered as an echo of that feedback.
+const ECHO_WINDOW: Duration = Duration::from_millis(50);
+
+/// Number of echoes within [`LOOP_WINDOW`] from which on we consider it a feedback loop.
+///
+/// A single echo is harmless (many controllers echo feedback and ReaLearn blocks duplicate
+/// feedback anyway). It only gets problematic if the echo keeps changing the target value.
+const LOOP_ECHO_COUNT: u32 = 10;
+
+const LOOP_WINDOW: Duration = Duration::from_secs(1);
+
+/// How long control input from the affected address is ignored after a loop has been detected.
+const SUPPRESSION_DURATION: Duration = Duration::from_secs(3);
+
+/// Detects control/feedback loops, e.g. a controller which echoes feedback back to ReaLearn which
+/// then controls the target again, which produces new feedback and so on.
+///
+/// `F` is the recorded feedback. It's needed to tell an echo apart from the user simply moving
+/// a control element while receiving feedback.
+#[derive(Debug)]
+pub struct FeedbackLoopDetector<F> {
+    suppression_enabled: bool,
+    last_feedback_time: Option<Instant>,
+    last_feedback_by_address: HashMap<CompoundMappingSourceAddress, SentFeedback<F>>,
+    echo_series_by_address: HashMap<CompoundMappingSourceAddress, EchoSeries>,
+    suppressed_until_by_address: HashMap<CompoundMappingSourceAddress, Instant>,
+}
+
+#[derive(Debug)]
+struct SentFeedback<F> {
+    time: Instant,
+    feedback: F,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct EchoSeries {
+    started_at: Instant,
+    count: u32,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum FeedbackLoopCheckResult {
+    /// Process the control input as usual.
+    Pass,
+    /// A loop has just been detected with this control input.
+    LoopDetected {
+        /// Whether this and subsequent control input from the same address is suppressed.
+        suppressed: bool,
+    },
+    /// Control input from this address is currently suppressed because of a previously detected
+    /// loop.
+    Suppressed,
+}
+
+impl<F> FeedbackLoopDetector<F> {
+    pub fn new(suppression_enabled: bool) -> Self {
+        Self {
+            suppression_enabled,
+            last_feedback_time: None,
+            last_feedback_by_address: Default::default(),
+            echo_series_by_address: Default::default(),
+            suppressed_until_by_address: Default::default(),
+        }
+    }
+
+    pub fn set_suppression_enabled(&mut self, enabled: bool) {
+        self.suppression_enabled = enabled;
+        if !enabled {
+            self.suppressed_until_by_address.clear();
+        }
+    }
+
+    pub fn feedback_sent(
+        &mut self,
+        address: CompoundMappingSourceAddress,
+        feedback: F,
+        now: Instant,
+    ) {
+        self.last_feedback_time = Some(now);
+        self.last_feedback_by_address.insert(
+            address,
+            SentFeedback {
+                time: now,
+                feedback,
+            },
+        );
+    }
+
+    /// Returns `false` if it's clear without looking at the address that the given control input
+    /// can't be part of a loop.
+    ///
+    /// Extracting the source address is not free, so it's good to check this first.
+    pub fn is_relevant(&self, now: Instant) -> bool {
+        !self.suppressed_until_by_address.is_empty()
+            || matches!(self.last_feedback_time, Some(t) if is_within_echo_window(t, now))
+    }
+
+    /// `is_echo_of` should return `true` if the control input corresponds to the given feedback.
+    pub fn check_control(
+        &mut self,
+        address: &CompoundMappingSourceAddress,
+        now: Instant,
+        is_echo_of: impl FnOnce(&F) -> bool,
+    ) -> FeedbackLoopCheckResult {
+        if let Some(until) = self.suppressed_until_by_address.get(address).copied() {
+            if now < until {
+                return FeedbackLoopCheckResult::Suppressed;
+            }
+            self.suppressed_until_by_address.remove(address);
+        }
+        let is_echo = match self.last_feedback_by_address.get(address) {
+            Some(f) => is_within_echo_window(f.time, now) && is_echo_of(&f.feedback),
+            None => false,
+        };
+        if !is_echo {
+            return FeedbackLoopCheckResult::Pass;
+        }
+        let new_series = EchoSeries {
+            started_at: now,
+            count: 0,
+        };
+        let series = self
+            .echo_series_by_address
+            .entry(address.clone())
+            .or_insert(new_series);
+        if now.saturating_duration_since(series.started_at) > LOOP_WINDOW {
+            *series = new_series;
+        }
+        series.count += 1;
+        if series.count < LOOP_ECHO_COUNT {
+            return FeedbackLoopCheckResult::Pass;
+        }
+        self.echo_series_by_address.remove(address);
+        if self.suppression_enabled {
+            self.suppressed_until_by_address
+                .insert(address.clone(), now + SUPPRESSION_DURATION);
+        }
+        FeedbackLoopCheckResult::LoopDetected {
+            suppressed: self.suppression_enabled,
+        }
+    }
+}
+
+fn is_within_echo_window(feedback_time: Instant, now: Instant) -> bool {
+    now.saturating_duration_since(feedback_time) <= ECHO_WINDOW
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{VirtualControlElement, VirtualControlElementId};
+
+    #[test]
+    fn ignore_single_echo() {
+        // Given
+        let mut detector = FeedbackLoopDetector::new(true);
+        let now = Instant::now();
+        // When
+        detector.feedback_sent(address(0), 64, now);
+        // Then
+        assert!(detector.is_relevant(now + ms(10)));
+        assert_eq!(
+            detector.check_control(&address(0), now + ms(10), |f| *f == 64),
+            FeedbackLoopCheckResult::Pass
+        );
+        assert!(!detector.is_relevant(now + ms(100)));
+    }
+
+    #[test]
+    fn detect_and_suppress_loop() {
+        // Given
+        let mut detector = FeedbackLoopDetector::new(true);
+        let now = Instant::now();
+        // When
+        let results = run_cycles(&mut detector, now, 20, true);
+        // Then
+        assert!(results[..results.len() - 1]
+            .iter()
+            .all(|r| *r == FeedbackLoopCheckResult::Pass));
+        assert_eq!(
+            results.last(),
+            Some(&FeedbackLoopCheckResult::LoopDetected { suppressed: true })
+        );
+        let later = now + Duration::from_secs(1);
+        assert_eq!(
+            detector.check_control(&address(0), later, |_| false),
+            FeedbackLoopCheckResult::Suppressed
+        );
+        assert_eq!(
+            detector.check_control(&address(1), later, |_| true),
+            FeedbackLoopCheckResult::Pass
+        );
+        assert_eq!(
+            detector.check_control(&address(0), later + SUPPRESSION_DURATION, |_| false),
+            FeedbackLoopCheckResult::Pass
+        );
+    }
+
+    #[test]
+    fn detect_without_suppression() {
+        // Given
+        let mut detector = FeedbackLoopDetector::new(false);
+        let now = Instant::now();
+        // When
+        let results = run_cycles(&mut detector, now, 20, true);
+        // Then
+        assert_eq!(
+            results.last(),
+            Some(&FeedbackLoopCheckResult::LoopDetected { suppressed: false })
+        );
+        assert_eq!(
+            detector.check_control(&address(0), now + Duration::from_secs(1), |_| false),
+            FeedbackLoopCheckResult::Pass
+        );
+    }
+
+    #[test]
+    fn slow_echoes_are_no_loop() {
+        // Given
+        let mut detector = FeedbackLoopDetector::new(true);
+        let now = Instant::now();
+        // When
+        let results = run_cycles(&mut detector, now, 500, true);
+        // Then
+        assert!(results.iter().all(|r| *r == FeedbackLoopCheckResult::Pass));
+    }
+
+    #[test]
+    fn moving_control_element_is_no_loop() {
+        // Given
+        let mut detector = FeedbackLoopDetector::new(true);
+        let now = Instant::now();
+        // When
+        let results = run_cycles(&mut detector, now, 20, false);
+        // Then
+        assert!(results.iter().all(|r| *r == FeedbackLoopCheckResult::Pass));
+    }
+
+    /// Simulates feedback which is followed by control input for the same control element shortly
+    /// after. If `echo` is `false`, the control input has a different value than the feedback.
+    fn run_cycles(
+        detector: &mut FeedbackLoopDetector<u32>,
+        start: Instant,
+        cycle_millis: u64,
+        echo: bool,
+    ) -> Vec<FeedbackLoopCheckResult> {
+        (0..LOOP_ECHO_COUNT)
+            .map(|i| {
+                let t = start + ms(i as u64 * cycle_millis);
+                detector.feedback_sent(address(0), i, t);
+                let control_value = if echo { i } else { i + 1 };
+                detector.check_control(&address(0), t + ms(5), |f| *f == control_value)
+            })
+            .collect()
+    }
+
+    fn address(index: u32) -> CompoundMappingSourceAddress {
+        CompoundMappingSourceAddress::Virtual(VirtualControlElement::Multi(
+            VirtualControlElementId::Indexed(index),
+        ))
+    }
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+}
@@ -0,0 +1,257 @@
+use enum_iterator::IntoEnumIterator;
+use helgoboss_learn::MidiSourceValue;
+use helgoboss_midi::{RawShortMessage, ShortMessage, ShortMessageType};
+use reaper_medium::MidiOutputDeviceId;
+use std::collections::HashSet;
+
+/// Priority class of outgoing feedback.
+///
+/// When the feedback pipeline is under pressure, lower classes are degraded first.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, IntoEnumIterator)]
+pub enum FeedbackPriority {
+    /// Button LEDs and other state which must always be accurate.
+    Critical,
+    /// Text displays (usually sent as sys-ex).
+    Display,
+    /// Meters, which change very often and of which only the latest value matters.
+    Meter,
+}
+
+impl FeedbackPriority {
+    pub fn of_midi_source_value(value: &MidiSourceValue<RawShortMessage>) -> Self {
+        match value {
+            MidiSourceValue::Raw { .. } => Self::Display,
+            MidiSourceValue::Plain(msg) => match msg.r#type() {
+                // Mackie Control and many other protocols use these for meters.
+                ShortMessageType::ChannelPressure | ShortMessageType::PolyphonicKeyPressure => {
+                    Self::Meter
+                }
+                _ => Self::Critical,
+            },
+            _ => Self::Critical,
+        }
+    }
+}
+
+/// Identifies the control element of a meter update, so that older updates for the same element
+/// can be merged into the latest one.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct FeedbackMergeKey {
+    dev_id: MidiOutputDeviceId,
+    status_byte: u8,
+    element: u8,
+}
+
+impl FeedbackMergeKey {
+    pub fn of_short_message(dev_id: MidiOutputDeviceId, msg: RawShortMessage) -> Self {
+        let (status_byte, data_byte_1, _) = msg.to_bytes();
+        let element = if msg.r#type() == ShortMessageType::ChannelPressure {
+            // Mackie Control packs the meter index into the upper nibble of the only data byte.
+            data_byte_1.get() >> 4
+        } else {
+            data_byte_1.get()
+        };
+        Self {
+            dev_id,
+            status_byte,
+            element,
+        }
+    }
+}
+
+/// Collects the feedback of one audio hook cycle and decides in which order it's sent.
+///
+/// Without pressure, everything is sent in the original order. Under pressure, critical feedback
+/// is sent first, then displays, then meters - of which only the latest update per control
+/// element survives. Order within one class is always kept. Doesn't allocate after creation.
+#[derive(Debug)]
+pub struct FeedbackScheduler<T> {
+    entries: Vec<ScheduledFeedback<T>>,
+    /// Merge keys of the latest meter updates, collected while walking backwards.
+    ///
+    /// Only used during flushing. Memory is reserved up-front for as many keys as there can be
+    /// entries.
+    later_merge_keys: HashSet<FeedbackMergeKey>,
+}
+
+#[derive(Debug)]
+struct ScheduledFeedback<T> {
+    priority: FeedbackPriority,
+    merge_key: Option<FeedbackMergeKey>,
+    /// Set during flushing if there's a later meter update for the same control element.
+    is_outdated: bool,
+    payload: Option<T>,
+}
+
+impl<T> FeedbackScheduler<T> {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: Vec::with_capacity(capacity),
+            later_merge_keys: HashSet::with_capacity(capacity),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the payload if there's no room left.
+    pub fn push(
+        &mut self,
+        priority: FeedbackPriority,
+        merge_key: Option<FeedbackMergeKey>,
+        payload: T,
+    ) -> Result<(), T> {
+        if self.entries.len() == self.entries.capacity() {
+            return Err(payload);
+        }
+        self.entries.push(ScheduledFeedback {
+            priority,
+            merge_key,
+            is_outdated: false,
+            payload: Some(payload),
+        });
+        Ok(())
+    }
+
+    /// Hands out all collected feedback, either to `send` or - if merged - to `discard`.
+    pub fn flush(
+        &mut self,
+        under_pressure: bool,
+        mut send: impl FnMut(T),
+        mut discard: impl FnMut(T),
+    ) {
+        if under_pressure {
+            self.mark_outdated_meter_updates();
+            for priority in FeedbackPriority::into_enum_iter() {
+                for entry in &mut self.entries {
+                    if entry.priority != priority {
+                        continue;
+                    }
+                    if let Some(payload) = entry.payload.take() {
+                        if entry.is_outdated {
+                            discard(payload);
+                        } else {
+                            send(payload);
+                        }
+                    }
+                }
+            }
+        } else {
+            for entry in &mut self.entries {
+                if let Some(payload) = entry.payload.take() {
+                    send(payload);
+                }
+            }
+        }
+        self.entries.clear();
+    }
+
+    /// Walks backwards, so the first occurrence of a merge key is the latest update.
+    fn mark_outdated_meter_updates(&mut self) {
+        for entry in self.entries.iter_mut().rev() {
+            if entry.priority != FeedbackPriority::Meter {
+                continue;
+            }
+            if let Some(key) = entry.merge_key {
+                entry.is_outdated = !self.later_merge_keys.insert(key);
+            }
+        }
+        self.later_merge_keys.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use helgoboss_midi::test_util::{channel, channel_pressure, key_number, note_on, u7};
+    use helgoboss_midi::ShortMessageFactory;
+
+    #[test]
+    fn classify() {
+        // Then
+        assert_eq!(
+            FeedbackPriority::of_midi_source_value(&MidiSourceValue::Plain(note_on(0, 60, 127))),
+            FeedbackPriority::Critical
+        );
+        assert_eq!(
+            FeedbackPriority::of_midi_source_value(&MidiSourceValue::Plain(channel_pressure(
+                0, 0x35
+            ))),
+            FeedbackPriority::Meter
+        );
+    }
+
+    #[test]
+    fn keep_order_without_pressure() {
+        // Given
+        let mut scheduler = FeedbackScheduler::with_capacity(10);
+        scheduler.push(FeedbackPriority::Meter, None, 1).unwrap();
+        scheduler.push(FeedbackPriority::Critical, None, 2).unwrap();
+        scheduler.push(FeedbackPriority::Display, None, 3).unwrap();
+        let mut sent = vec![];
+        // When
+        scheduler.flush(
+            false,
+            |p| sent.push(p),
+            |_| panic!("nothing should be discarded"),
+        );
+        // Then
+        assert_eq!(sent, vec![1, 2, 3]);
+        assert!(scheduler.is_empty());
+    }
+
+    #[test]
+    fn degrade_meters_under_pressure() {
+        // Given
+        let dev_id = MidiOutputDeviceId::new(0);
+        let meter_1 = FeedbackMergeKey::of_short_message(dev_id, channel_pressure(0, 0x05));
+        let meter_1_later = FeedbackMergeKey::of_short_message(dev_id, channel_pressure(0, 0x0A));
+        let meter_2 = FeedbackMergeKey::of_short_message(dev_id, channel_pressure(0, 0x15));
+        let mut scheduler = FeedbackScheduler::with_capacity(10);
+        scheduler
+            .push(FeedbackPriority::Meter, Some(meter_1), 1)
+            .unwrap();
+        scheduler
+            .push(FeedbackPriority::Meter, Some(meter_2), 2)
+            .unwrap();
+        scheduler.push(FeedbackPriority::Critical, None, 3).unwrap();
+        scheduler.push(FeedbackPriority::Display, None, 4).unwrap();
+        scheduler
+            .push(FeedbackPriority::Meter, Some(meter_1_later), 5)
+            .unwrap();
+        scheduler.push(FeedbackPriority::Critical, None, 6).unwrap();
+        let mut sent = vec![];
+        let mut discarded = vec![];
+        // When
+        scheduler.flush(true, |p| sent.push(p), |p| discarded.push(p));
+        // Then
+        assert_eq!(sent, vec![3, 6, 4, 2, 5]);
+        assert_eq!(discarded, vec![1]);
+    }
+
+    #[test]
+    fn merge_key() {
+        // Given
+        let dev_id = MidiOutputDeviceId::new(0);
+        // Then
+        assert_eq!(
+            FeedbackMergeKey::of_short_message(
+                dev_id,
+                RawShortMessage::polyphonic_key_pressure(channel(0), key_number(60), u7(1))
+            ),
+            FeedbackMergeKey::of_short_message(
+                dev_id,
+                RawShortMessage::polyphonic_key_pressure(channel(0), key_number(60), u7(100))
+            )
+        );
+        assert_ne!(
+            FeedbackMergeKey::of_short_message(dev_id, note_on(0, 60, 127)),
+            FeedbackMergeKey::of_short_message(dev_id, note_on(0, 61, 127))
+        );
+    }
+}
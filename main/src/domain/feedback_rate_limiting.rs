@@ -0,0 +1,269 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::hash::Hash;
+use std::time::{Duration, Instant};
+
+/// Maximum number of feedback messages which can wait for being sent at the same time.
+///
+/// Memory for them is reserved up-front because the limiter is used in the audio hook.
+const PENDING_CAPACITY: usize = 1000;
+/// Maximum number of devices for which a rate limit can be in effect at the same time.
+const DEVICE_CAPACITY: usize = 64;
+
+/// Maximum number of feedback messages per second, keyed by device.
+///
+/// Some older hardware (e.g. Behringer BCF2000) chokes when receiving feedback too fast.
+pub type FeedbackMaxRateMap<D> = HashMap<D, u32>;
+
+/// Spaces out feedback sent to devices which have a maximum message rate.
+///
+/// Messages which can't be sent immediately are kept and sent in a later cycle. If a newer message
+/// for the same address arrives while an older one is still waiting, the older one is replaced,
+/// so the device ends up with the latest value without getting all the intermediate ones. The
+/// order of messages for one device is kept. Doesn't allocate after creation.
+#[derive(Debug)]
+pub struct FeedbackRateLimiter<D, A, T> {
+    max_rates: FeedbackMaxRateMap<D>,
+    /// Earliest time at which the next message may be sent, per device.
+    next_slots: Vec<(D, Instant)>,
+    pending: Vec<PendingFeedback<D, A, T>>,
+}
+
+#[derive(Debug)]
+struct PendingFeedback<D, A, T> {
+    dev_id: D,
+    address: Option<A>,
+    payload: Option<T>,
+}
+
+impl<D, A, T> Default for FeedbackRateLimiter<D, A, T> {
+    fn default() -> Self {
+        Self {
+            max_rates: Default::default(),
+            next_slots: Vec::with_capacity(DEVICE_CAPACITY),
+            pending: Vec::with_capacity(PENDING_CAPACITY),
+        }
+    }
+}
+
+impl<D: Copy + Eq + Hash, A: PartialEq, T> FeedbackRateLimiter<D, A, T> {
+    /// Returns the previous maximum rates (so they can be disposed outside of the audio thread).
+    pub fn replace_max_rates(&mut self, max_rates: FeedbackMaxRateMap<D>) -> FeedbackMaxRateMap<D> {
+        self.next_slots.clear();
+        std::mem::replace(&mut self.max_rates, max_rates)
+    }
+
+    pub fn is_limited(&self, dev_id: D) -> bool {
+        self.interval(dev_id).is_some()
+    }
+
+    pub fn has_pending(&self, dev_id: D) -> bool {
+        self.pending.iter().any(|p| p.dev_id == dev_id)
+    }
+
+    /// Sends the given message right now via `send_now` or keeps it for later (if the device has
+    /// received a message too recently).
+    ///
+    /// Messages without address are never replaced. Replaced messages are handed to `discard`.
+    pub fn send(
+        &mut self,
+        dev_id: D,
+        address: Option<A>,
+        payload: T,
+        now: Instant,
+        mut send_now: impl FnMut(T),
+        mut discard: impl FnMut(T),
+    ) {
+        let interval = match self.interval(dev_id) {
+            None => {
+                send_now(payload);
+                return;
+            }
+            Some(i) => i,
+        };
+        if address.is_some() {
+            if let Some(existing) = self
+                .pending
+                .iter_mut()
+                .find(|p| p.dev_id == dev_id && p.address == address)
+            {
+                if let Some(outdated) = existing.payload.replace(payload) {
+                    discard(outdated);
+                }
+                return;
+            }
+        }
+        if !self.has_pending(dev_id) && self.take_slot(dev_id, interval, now) {
+            send_now(payload);
+            return;
+        }
+        if self.pending.len() == self.pending.capacity() {
+            // Better send it too early than not at all (and we must not allocate).
+            send_now(payload);
+            return;
+        }
+        self.pending.push(PendingFeedback {
+            dev_id,
+            address,
+            payload: Some(payload),
+        });
+    }
+
+    /// Sends all kept messages whose time has come.
+    pub fn send_due(&mut self, now: Instant, mut send_now: impl FnMut(D, T)) {
+        for i in 0..self.pending.len() {
+            let dev_id = self.pending[i].dev_id;
+            let is_due = match self.interval(dev_id) {
+                // The limit has been removed in the meantime.
+                None => true,
+                Some(interval) => match self.next_slots.iter_mut().find(|(d, _)| *d == dev_id) {
+                    Some((_, slot)) if *slot <= now => {
+                        *slot = next_tick(*slot, interval, now);
+                        true
+                    }
+                    Some(_) => false,
+                    None => true,
+                },
+            };
+            if is_due {
+                if let Some(payload) = self.pending[i].payload.take() {
+                    send_now(dev_id, payload);
+                }
+            }
+        }
+        self.pending.retain(|p| p.payload.is_some());
+    }
+
+    fn interval(&self, dev_id: D) -> Option<Duration> {
+        let max_rate = *self.max_rates.get(&dev_id)?;
+        if max_rate == 0 {
+            return None;
+        }
+        let interval = Duration::from_nanos(1_000_000_000 / max_rate as u64);
+        // More than one message per nanosecond is as good as no limit.
+        Some(interval).filter(|i| *i > Duration::default())
+    }
+
+    /// Returns `true` if the device may receive a message right now.
+    fn take_slot(&mut self, dev_id: D, interval: Duration, now: Instant) -> bool {
+        match self.next_slots.iter_mut().find(|(d, _)| *d == dev_id) {
+            Some((_, slot)) => {
+                if *slot > now {
+                    return false;
+                }
+                *slot = now + interval;
+            }
+            None => {
+                if self.next_slots.len() < self.next_slots.capacity() {
+                    self.next_slots.push((dev_id, now + interval));
+                }
+            }
+        }
+        true
+    }
+}
+
+/// Returns the first tick after `now` on the grid which starts at `slot`.
+///
+/// Staying on the grid (instead of starting from now) keeps the rate accurate if we are called
+/// less often than the interval. Ticks which have been missed completely are skipped, otherwise
+/// messages would go out in a burst after a hiccup.
+fn next_tick(slot: Instant, interval: Duration, now: Instant) -> Instant {
+    let missed_tick_count = now.saturating_duration_since(slot).as_nanos() / interval.as_nanos();
+    match u32::try_from(missed_tick_count + 1) {
+        Ok(tick_count) => slot + interval * tick_count,
+        Err(_) => now + interval,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn space_out_messages() {
+        // Given
+        let mut limiter = limiter(100);
+        let now = Instant::now();
+        let mut sent = vec![];
+        // When
+        for (address, value) in &[(1, 1), (2, 2), (3, 3)] {
+            limiter.send(
+                0,
+                Some(*address),
+                *value,
+                now,
+                |v| sent.push(v),
+                |_| panic!("nothing should be discarded"),
+            );
+        }
+        // Then
+        assert_eq!(sent, vec![1]);
+        limiter.send_due(now + ms(5), |_, v| sent.push(v));
+        assert_eq!(sent, vec![1]);
+        limiter.send_due(now + ms(10), |_, v| sent.push(v));
+        assert_eq!(sent, vec![1, 2]);
+        limiter.send_due(now + ms(25), |_, v| sent.push(v));
+        assert_eq!(sent, vec![1, 2, 3]);
+        assert!(!limiter.has_pending(0));
+    }
+
+    #[test]
+    fn replace_superseded_messages() {
+        // Given
+        let mut limiter = limiter(100);
+        let now = Instant::now();
+        let mut sent = vec![];
+        let mut discarded = vec![];
+        // When
+        for (dev_id, address, value) in &[(0, 1, 1), (0, 1, 2), (0, 2, 3), (0, 1, 4), (1, 1, 5)] {
+            limiter.send(
+                *dev_id,
+                Some(*address),
+                *value,
+                now,
+                |v| sent.push(v),
+                |v| discarded.push(v),
+            );
+        }
+        limiter.send_due(now + ms(20), |_, v| sent.push(v));
+        // Then
+        assert_eq!(sent, vec![1, 5, 4, 3]);
+        assert_eq!(discarded, vec![2]);
+    }
+
+    #[test]
+    fn skip_missed_ticks() {
+        // Given
+        let mut limiter = limiter(100);
+        let now = Instant::now();
+        let mut sent = vec![];
+        for (address, value) in &[(1, 1), (2, 2), (3, 3)] {
+            limiter.send(
+                0,
+                Some(*address),
+                *value,
+                now,
+                |v| sent.push(v),
+                |_| panic!("nothing should be discarded"),
+            );
+        }
+        // When
+        limiter.send_due(now + ms(55), |_, v| sent.push(v));
+        limiter.send_due(now + ms(56), |_, v| sent.push(v));
+        // Then
+        assert_eq!(sent, vec![1, 2]);
+        limiter.send_due(now + ms(60), |_, v| sent.push(v));
+        assert_eq!(sent, vec![1, 2, 3]);
+    }
+
+    fn limiter(max_rate: u32) -> FeedbackRateLimiter<u32, u32, u32> {
+        let mut limiter = FeedbackRateLimiter::default();
+        limiter.replace_max_rates(std::iter::once((0, max_rate)).collect());
+        limiter
+    }
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+}
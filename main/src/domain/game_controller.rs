@@ -0,0 +1,238 @@
+use derivative::Derivative;
+use derive_more::Display;
+use enum_iterator::IntoEnumIterator;
+use gilrs::{Axis, Button, EventType, Gilrs};
+use helgoboss_learn::UnitValue;
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+use serde::{Deserialize, Serialize};
+use std::hash::{Hash, Hasher};
+
+/// Axis or button of a game controller (gamepad, joystick, ...).
+#[derive(
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    Hash,
+    Debug,
+    Serialize,
+    Deserialize,
+    IntoEnumIterator,
+    TryFromPrimitive,
+    IntoPrimitive,
+    Display,
+)]
+#[repr(usize)]
+pub enum GameControllerElement {
+    #[display(fmt = "Left stick X")]
+    LeftStickX,
+    #[display(fmt = "Left stick Y")]
+    LeftStickY,
+    #[display(fmt = "Left Z")]
+    LeftZ,
+    #[display(fmt = "Right stick X")]
+    RightStickX,
+    #[display(fmt = "Right stick Y")]
+    RightStickY,
+    #[display(fmt = "Right Z")]
+    RightZ,
+    #[display(fmt = "D-pad X")]
+    DPadX,
+    #[display(fmt = "D-pad Y")]
+    DPadY,
+    #[display(fmt = "South button")]
+    South,
+    #[display(fmt = "East button")]
+    East,
+    #[display(fmt = "North button")]
+    North,
+    #[display(fmt = "West button")]
+    West,
+    #[display(fmt = "Left trigger")]
+    LeftTrigger,
+    #[display(fmt = "Left trigger 2")]
+    LeftTrigger2,
+    #[display(fmt = "Right trigger")]
+    RightTrigger,
+    #[display(fmt = "Right trigger 2")]
+    RightTrigger2,
+    #[display(fmt = "Select")]
+    Select,
+    #[display(fmt = "Start")]
+    Start,
+    #[display(fmt = "Mode")]
+    Mode,
+    #[display(fmt = "Left thumb")]
+    LeftThumb,
+    #[display(fmt = "Right thumb")]
+    RightThumb,
+    #[display(fmt = "D-pad up")]
+    DPadUp,
+    #[display(fmt = "D-pad down")]
+    DPadDown,
+    #[display(fmt = "D-pad left")]
+    DPadLeft,
+    #[display(fmt = "D-pad right")]
+    DPadRight,
+}
+
+impl Default for GameControllerElement {
+    fn default() -> Self {
+        GameControllerElement::South
+    }
+}
+
+impl GameControllerElement {
+    pub fn is_axis(&self) -> bool {
+        use GameControllerElement::*;
+        matches!(
+            self,
+            LeftStickX | LeftStickY | LeftZ | RightStickX | RightStickY | RightZ | DPadX | DPadY
+        )
+    }
+
+    fn from_axis(axis: Axis) -> Option<Self> {
+        use GameControllerElement::*;
+        let element = match axis {
+            Axis::LeftStickX => LeftStickX,
+            Axis::LeftStickY => LeftStickY,
+            Axis::LeftZ => LeftZ,
+            Axis::RightStickX => RightStickX,
+            Axis::RightStickY => RightStickY,
+            Axis::RightZ => RightZ,
+            Axis::DPadX => DPadX,
+            Axis::DPadY => DPadY,
+            Axis::Unknown => return None,
+        };
+        Some(element)
+    }
+
+    fn from_button(button: Button) -> Option<Self> {
+        use GameControllerElement::*;
+        let element = match button {
+            Button::South => South,
+            Button::East => East,
+            Button::North => North,
+            Button::West => West,
+            Button::LeftTrigger => LeftTrigger,
+            Button::LeftTrigger2 => LeftTrigger2,
+            Button::RightTrigger => RightTrigger,
+            Button::RightTrigger2 => RightTrigger2,
+            Button::Select => Select,
+            Button::Start => Start,
+            Button::Mode => Mode,
+            Button::LeftThumb => LeftThumb,
+            Button::RightThumb => RightThumb,
+            Button::DPadUp => DPadUp,
+            Button::DPadDown => DPadDown,
+            Button::DPadLeft => DPadLeft,
+            Button::DPadRight => DPadRight,
+            Button::C | Button::Z | Button::Unknown => return None,
+        };
+        Some(element)
+    }
+}
+
+/// A value change of one axis or button.
+#[derive(Copy, Clone, PartialEq, Debug, Display)]
+#[display(fmt = "Game controller {}: {} = {}", controller_index, element, value)]
+pub struct GameControllerEvent {
+    /// Index assigned by the operating system layer in the order in which controllers got
+    /// connected.
+    pub controller_index: u32,
+    pub element: GameControllerElement,
+    pub value: UnitValue,
+}
+
+// Unit values are never NaN, so comparing and hashing the bits is fine.
+impl Eq for GameControllerEvent {}
+
+impl Hash for GameControllerEvent {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.controller_index.hash(state);
+        self.element.hash(state);
+        self.value.get().to_bits().hash(state);
+    }
+}
+
+/// Gives access to connected game controllers.
+///
+/// Must be polled regularly from the main thread.
+#[derive(Derivative)]
+#[derivative(Debug)]
+pub struct GameControllerInput {
+    #[derivative(Debug = "ignore")]
+    gilrs: Gilrs,
+    /// Reused on each poll in order to not allocate in each main loop cycle.
+    events: Vec<GameControllerEvent>,
+}
+
+impl GameControllerInput {
+    pub fn new() -> Result<Self, String> {
+        let gilrs = Gilrs::new().map_err(|e| e.to_string())?;
+        let input = Self {
+            gilrs,
+            events: Vec::with_capacity(32),
+        };
+        Ok(input)
+    }
+
+    /// Returns index and name of each connected controller.
+    pub fn controllers(&self) -> Vec<(u32, String)> {
+        self.gilrs
+            .gamepads()
+            .map(|(id, gamepad)| (controller_index(id), gamepad.name().to_string()))
+            .collect()
+    }
+
+    /// Returns all axis and button changes since the last poll.
+    pub fn poll(&mut self) -> &[GameControllerEvent] {
+        self.events.clear();
+        while let Some(evt) = self.gilrs.next_event() {
+            let (element, value) = match evt.event {
+                EventType::AxisChanged(axis, value, _) => {
+                    match GameControllerElement::from_axis(axis) {
+                        Some(e) => (e, axis_value_to_unit_value(value)),
+                        None => continue,
+                    }
+                }
+                EventType::ButtonChanged(button, value, _) => {
+                    match GameControllerElement::from_button(button) {
+                        Some(e) => (e, UnitValue::new_clamped(value as f64)),
+                        None => continue,
+                    }
+                }
+                _ => continue,
+            };
+            let event = GameControllerEvent {
+                controller_index: controller_index(evt.id),
+                element,
+                value,
+            };
+            self.events.push(event);
+        }
+        &self.events
+    }
+}
+
+fn controller_index(id: gilrs::GamepadId) -> u32 {
+    usize::from(id) as u32
+}
+
+/// Converts an axis value (-1.0 to 1.0) to a unit value (center = 50%).
+fn axis_value_to_unit_value(value: f32) -> UnitValue {
+    UnitValue::new_clamped((value as f64 + 1.0) / 2.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn axis_values() {
+        assert_eq!(axis_value_to_unit_value(-1.0), UnitValue::MIN);
+        assert_eq!(axis_value_to_unit_value(0.0), UnitValue::new(0.5));
+        assert_eq!(axis_value_to_unit_value(1.0), UnitValue::MAX);
+        assert_eq!(axis_value_to_unit_value(1.5), UnitValue::MAX);
+    }
+}
@@ -0,0 +1,263 @@
+use derive_more::Display;
+use enum_iterator::IntoEnumIterator;
+use helgoboss_learn::UnitValue;
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// Duration of one animation frame.
+const FRAME_DURATION: Duration = Duration::from_millis(100);
+
+/// Number of frames for one complete fade cycle (dark -> bright -> dark).
+const FADE_CYCLE_FRAME_COUNT: u32 = 30;
+
+/// Pattern played on the feedback-capable control elements of the controller preset after there
+/// has been no control input for a while.
+#[derive(
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    Debug,
+    Serialize,
+    Deserialize,
+    IntoEnumIterator,
+    TryFromPrimitive,
+    IntoPrimitive,
+    Display,
+)]
+#[repr(usize)]
+pub enum IdleAnimationType {
+    #[serde(rename = "off")]
+    #[display(fmt = "Off")]
+    Off,
+    /// Lights up one control element after the other.
+    #[serde(rename = "chase")]
+    #[display(fmt = "Chase")]
+    Chase,
+    /// Fades all control elements in and out.
+    #[serde(rename = "fade")]
+    #[display(fmt = "Fade")]
+    Fade,
+}
+
+impl Default for IdleAnimationType {
+    fn default() -> Self {
+        Self::Off
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct IdleAnimationSettings {
+    pub animation_type: IdleAnimationType,
+    /// Time without control input after which the animation starts.
+    pub timeout: Duration,
+}
+
+impl Default for IdleAnimationSettings {
+    fn default() -> Self {
+        Self {
+            animation_type: IdleAnimationType::Off,
+            timeout: Duration::from_secs(5 * 60),
+        }
+    }
+}
+
+/// Keeps track of control activity and decides when to render which animation frame.
+///
+/// Doesn't send anything itself. The main processor asks it for due frames and turns them into
+/// feedback, which goes through the usual duplicate-blocking feedback pipeline.
+#[derive(Debug)]
+pub struct IdleAnimation {
+    settings: IdleAnimationSettings,
+    last_activity: Instant,
+    started_at: Option<Instant>,
+    last_frame_index: Option<u32>,
+}
+
+impl IdleAnimation {
+    pub fn new(settings: IdleAnimationSettings, now: Instant) -> Self {
+        Self {
+            settings,
+            last_activity: now,
+            started_at: None,
+            last_frame_index: None,
+        }
+    }
+
+    pub fn settings(&self) -> IdleAnimationSettings {
+        self.settings
+    }
+
+    /// Returns `true` if the animation was running and has been stopped as a consequence.
+    pub fn update_settings(&mut self, settings: IdleAnimationSettings, now: Instant) -> bool {
+        self.settings = settings;
+        self.notify_activity(now)
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.started_at.is_some()
+    }
+
+    /// Should be called whenever there's control input.
+    ///
+    /// Returns `true` if the animation was running and has been stopped as a consequence. In this
+    /// case the consumer should restore the normal feedback.
+    pub fn notify_activity(&mut self, now: Instant) -> bool {
+        self.last_activity = now;
+        self.last_frame_index = None;
+        self.started_at.take().is_some()
+    }
+
+    /// Returns the frame to be rendered if a new one is due.
+    pub fn poll(&mut self, now: Instant) -> Option<IdleAnimationFrame> {
+        if self.settings.animation_type == IdleAnimationType::Off {
+            return None;
+        }
+        let started_at = match self.started_at {
+            Some(t) => t,
+            None => {
+                if now.duration_since(self.last_activity) < self.settings.timeout {
+                    return None;
+                }
+                self.started_at = Some(now);
+                now
+            }
+        };
+        let frame_index =
+            (now.duration_since(started_at).as_millis() / FRAME_DURATION.as_millis()) as u32;
+        if self.last_frame_index == Some(frame_index) {
+            return None;
+        }
+        self.last_frame_index = Some(frame_index);
+        let frame = IdleAnimationFrame {
+            animation_type: self.settings.animation_type,
+            index: frame_index,
+        };
+        Some(frame)
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct IdleAnimationFrame {
+    animation_type: IdleAnimationType,
+    index: u32,
+}
+
+impl IdleAnimationFrame {
+    /// Returns the value which the control element at the given position should display in this
+    /// frame.
+    pub fn element_value(&self, element_index: usize, element_count: usize) -> UnitValue {
+        use IdleAnimationType::*;
+        match self.animation_type {
+            Off => UnitValue::MIN,
+            Chase => {
+                if element_count > 0 && element_index == self.index as usize % element_count {
+                    UnitValue::MAX
+                } else {
+                    UnitValue::MIN
+                }
+            }
+            Fade => {
+                let half_cycle = FADE_CYCLE_FRAME_COUNT / 2;
+                let pos = self.index % FADE_CYCLE_FRAME_COUNT;
+                let rising = if pos <= half_cycle {
+                    pos
+                } else {
+                    FADE_CYCLE_FRAME_COUNT - pos
+                };
+                UnitValue::new(rising as f64 / half_cycle as f64)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn waits_for_timeout() {
+        // Given
+        let start = Instant::now();
+        let mut animation = IdleAnimation::new(chase_after(10), start);
+        // Then
+        assert_eq!(animation.poll(start + Duration::from_secs(9)), None);
+        assert!(!animation.is_running());
+        assert!(animation.poll(start + Duration::from_secs(10)).is_some());
+        assert!(animation.is_running());
+    }
+
+    #[test]
+    fn emits_each_frame_only_once() {
+        // Given
+        let start = Instant::now();
+        let mut animation = IdleAnimation::new(chase_after(0), start);
+        // Then
+        assert!(animation.poll(start).is_some());
+        assert_eq!(animation.poll(start + Duration::from_millis(50)), None);
+        assert!(animation.poll(start + FRAME_DURATION).is_some());
+    }
+
+    #[test]
+    fn stops_on_activity() {
+        // Given
+        let start = Instant::now();
+        let mut animation = IdleAnimation::new(chase_after(10), start);
+        let running_time = start + Duration::from_secs(10);
+        animation.poll(running_time);
+        // When
+        let was_stopped = animation.notify_activity(running_time);
+        // Then
+        assert!(was_stopped);
+        assert!(!animation.is_running());
+        assert_eq!(animation.poll(running_time + FRAME_DURATION), None);
+        assert!(!animation.notify_activity(running_time));
+    }
+
+    #[test]
+    fn off_never_starts() {
+        // Given
+        let start = Instant::now();
+        let mut animation = IdleAnimation::new(Default::default(), start);
+        // Then
+        assert_eq!(animation.poll(start + Duration::from_secs(60 * 60)), None);
+    }
+
+    #[test]
+    fn chase() {
+        // Given
+        let frame = |index| IdleAnimationFrame {
+            animation_type: IdleAnimationType::Chase,
+            index,
+        };
+        // Then
+        assert_eq!(frame(0).element_value(0, 3), UnitValue::MAX);
+        assert_eq!(frame(0).element_value(1, 3), UnitValue::MIN);
+        assert_eq!(frame(1).element_value(1, 3), UnitValue::MAX);
+        assert_eq!(frame(3).element_value(0, 3), UnitValue::MAX);
+        assert_eq!(frame(3).element_value(0, 0), UnitValue::MIN);
+    }
+
+    #[test]
+    fn fade() {
+        // Given
+        let frame = |index| IdleAnimationFrame {
+            animation_type: IdleAnimationType::Fade,
+            index,
+        };
+        // Then
+        assert_eq!(frame(0).element_value(0, 3), UnitValue::MIN);
+        assert_eq!(frame(15).element_value(2, 3), UnitValue::MAX);
+        assert_eq!(frame(30).element_value(1, 3), UnitValue::MIN);
+        assert!(frame(5).element_value(0, 3) < frame(10).element_value(0, 3));
+        assert!(frame(20).element_value(0, 3) > frame(25).element_value(0, 3));
+    }
+
+    fn chase_after(secs: u64) -> IdleAnimationSettings {
+        IdleAnimationSettings {
+            animation_type: IdleAnimationType::Chase,
+            timeout: Duration::from_secs(secs),
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use crate::domain::QualifiedMappingId;
+use helgoboss_learn::{RawMidiEvent, UnitValue};
+use rosc::OscMessage;
+
+/// Instance-level events to which the user can attach actions.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum InstanceHookEvent {
+    /// Fired after the session has been loaded (e.g. when opening the project).
+    SessionLoad,
+    /// Fired after a controller or main preset has been activated.
+    PresetChange,
+    /// Fired after the user has chosen another feedback output.
+    FeedbackOutputChange,
+}
+
+/// Declarative setup steps which are executed by the main processor when certain instance
+/// events occur.
+#[derive(Clone, Debug, Default)]
+pub struct InstanceHooks {
+    pub on_session_load: Vec<InstanceHookAction>,
+    pub on_preset_change: Vec<InstanceHookAction>,
+    pub on_feedback_output_change: Vec<InstanceHookAction>,
+}
+
+impl InstanceHooks {
+    pub fn actions(&self, event: InstanceHookEvent) -> &[InstanceHookAction] {
+        use InstanceHookEvent::*;
+        match event {
+            SessionLoad => &self.on_session_load,
+            PresetChange => &self.on_preset_change,
+            FeedbackOutputChange => &self.on_feedback_output_change,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum InstanceHookAction {
+    /// Sends the given raw MIDI messages to the MIDI feedback output device.
+    SendMidi(Vec<RawMidiEvent>),
+    /// Sends the given OSC message to the OSC feedback output device.
+    SendOsc(OscMessage),
+    /// Sets the ReaLearn parameter at the given index.
+    SetParameter { index: u32, value: f32 },
+    /// Controls the given mapping as if the corresponding source had sent the given value.
+    TriggerMapping {
+        id: QualifiedMappingId,
+        value: UnitValue,
+    },
+}
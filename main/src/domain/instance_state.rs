@@ -1,12 +1,15 @@
 use crate::base::{AsyncNotifier, Prop};
 use crate::domain::{
-    ClipPlayState, ClipSlot, GroupId, MappingCompartment, MappingId, QualifiedMappingId,
-    SlotContent, SlotDescriptor, SlotPlayOptions, Tag,
+    clip_changed_event, ClipMatrix, ClipMatrixContent, ClipMatrixId, ClipMatrixSize, ClipPlayState,
+    ClipSlot, ControlLatencyMeasurement, CueSends, GroupId, IoConnectionStatus, LayerId,
+    MappingCompartment, MappingId, MappingSnapshotContainer, MidiClockOutputs, MidiMacros,
+    ProgramChangePatch, QualifiedMappingId, SentProgramChanges, SlotContent, SlotDescriptor,
+    SlotFades, SlotFollowAction, SlotStopQuantization, Tag,
 };
 use enum_map::EnumMap;
 use helgoboss_learn::UnitValue;
-use reaper_high::{Item, Project, Track};
-use reaper_medium::{PlayState, ReaperVolumeValue};
+use reaper_high::{Guid, Item, Project};
+use reaper_medium::{MidiOutputDeviceId, PlayState, ReaperVolumeValue};
 use rx_util::Notifier;
 use rxrust::prelude::*;
 use serde::{Deserialize, Serialize};
@@ -15,15 +18,16 @@ use std::collections::{HashMap, HashSet};
 use std::error::Error;
 use std::rc::Rc;
 
-pub const CLIP_SLOT_COUNT: usize = 8;
-
 pub type SharedInstanceState = Rc<RefCell<InstanceState>>;
 
 /// State connected to the instance which also needs to be accessible from layers *above* the
 /// processing layer (otherwise it could reside in the main processor).
 #[derive(Debug)]
 pub struct InstanceState {
-    clip_slots: [ClipSlot; CLIP_SLOT_COUNT],
+    /// The instance's own clip matrix.
+    ///
+    /// Clip targets with a matrix ID use a shared clip matrix in the backbone state instead.
+    clip_matrix: ClipMatrix,
     instance_feedback_event_sender: crossbeam_channel::Sender<InstanceStateChanged>,
     slot_contents_changed_subject: LocalSubject<'static, (), ()>,
     /// Which mappings are in which group.
@@ -55,11 +59,96 @@ pub struct InstanceState {
     /// - Set by target "ReaLearn: Enable/disable mappings".
     /// - Non-redundant state!
     active_mapping_tags: EnumMap<MappingCompartment, HashSet<Tag>>,
+    /// All groups whose mappings have been switched off via group.
+    ///
+    /// - Set by target "ReaLearn: Enable/disable group".
+    /// - Non-redundant state!
+    disabled_groups: EnumMap<MappingCompartment, HashSet<GroupId>>,
+    /// The currently active layer in each compartment.
+    ///
+    /// - Set by target "ReaLearn: Select layer".
+    /// - Mappings assigned to other layers can't be controlled.
+    /// - Non-redundant state!
+    active_layer: EnumMap<MappingCompartment, LayerId>,
+    /// Bank offset which is added to the index of each indexed virtual control element.
+    ///
+    /// - Set by target "ReaLearn: Set bank offset".
+    /// - Allows controller mappings for a few physical control elements to address many more
+    ///   virtual ones.
+    /// - Non-redundant state!
+    virtual_control_element_offset: u32,
+    /// Offset which is added to the index of each FX parameter target addressing its parameter
+    /// by index.
+    ///
+    /// - Set by target "FX: Navigate between parameter banks".
+    /// - Allows a few knobs to page through all parameters of an FX.
+    /// - Non-redundant state!
+    fx_parameter_bank_offset: u32,
+    /// Folder track whose children are addressed by tracks selected by position.
+    ///
+    /// - Set by target "Track: Spill folder".
+    /// - Allows a few faders to control the children of a folder one after the other.
+    /// - Non-redundant state!
+    spilled_folder_track: Option<Guid>,
     /// All instance tags whose instances have been switched on via tag.
     ///
     /// - Set by target "ReaLearn: Enable/disable instances".
     /// - Non-redundant state!
     active_instance_tags: HashSet<Tag>,
+    /// Which MIDI devices used by this instance are currently unplugged and which mappings are
+    /// suspended because of that.
+    ///
+    /// - Set by main processor when devices are unplugged or plugged in again and when mappings
+    ///   are synced.
+    /// - Completely derived from device changes and mappings, so it's redundant state.
+    /// - It's needed by layers above (for marking suspended mappings in the UI and reporting
+    ///   them to API clients).
+    io_connection_status: Prop<IoConnectionStatus>,
+    /// Sends for pre-listening tracks and clips on the cue output.
+    ///
+    /// - Set by the cue targets.
+    /// - Cleaned up automatically whenever the owning mapping is not on anymore.
+    cue_sends: CueSends,
+    /// MIDI output devices on which the mappings of this instance have switched on MIDI clock.
+    ///
+    /// - Set by target "MIDI: Send clock".
+    /// - Cleaned up automatically whenever the owning mapping is not on anymore.
+    midi_clock_outputs: MidiClockOutputs,
+    /// Named raw MIDI sequences of the active controller preset.
+    ///
+    /// - Set by the session whenever mappings are synced.
+    /// - Used by target "MIDI: Send macro".
+    /// - Completely derived from the controller preset, so it's redundant state.
+    midi_macros: MidiMacros,
+    /// The patch which has been sent most recently to each external MIDI device and channel.
+    ///
+    /// - Set by target "MIDI: Send program change".
+    /// - Non-redundant state! But not persisted.
+    sent_program_changes: SentProgramChanges,
+    /// Named mapping snapshots and currently fading snapshot recalls.
+    ///
+    /// - Set by targets "ReaLearn: Save mapping snapshot" and "ReaLearn: Load mapping snapshot".
+    /// - Fades are advanced by the main processor.
+    /// - Non-redundant state! But not persisted.
+    mapping_snapshots: MappingSnapshotContainer,
+    /// Control latencies collected while the user measures them.
+    ///
+    /// - Started and stopped by the session.
+    /// - Samples are recorded by the main processor.
+    /// - Non-redundant state! But not persisted.
+    control_latency_measurement: ControlLatencyMeasurement,
+    /// Clip slots which have not been loaded because the clip engine is disabled in safe mode.
+    ///
+    /// - Set when loading the session.
+    /// - Saved again as they are, so nothing gets lost.
+    /// - Non-redundant state!
+    deferred_clip_slots: Option<DeferredClipSlots>,
+    /// Default stop quantization for clip targets which don't define their own.
+    ///
+    /// - Set by the user.
+    /// - `None` means it's derived from the "Next bar" option of the target.
+    /// - Non-redundant state!
+    clip_stop_quantization: Option<SlotStopQuantization>,
 }
 
 #[derive(Debug)]
@@ -72,7 +161,7 @@ impl InstanceState {
         instance_feedback_event_sender: crossbeam_channel::Sender<InstanceStateChanged>,
     ) -> Self {
         Self {
-            clip_slots: Default::default(),
+            clip_matrix: Default::default(),
             instance_feedback_event_sender,
             slot_contents_changed_subject: Default::default(),
             mappings_by_group: Default::default(),
@@ -80,7 +169,21 @@ impl InstanceState {
             mapping_infos: Default::default(),
             on_mappings: Default::default(),
             active_mapping_tags: Default::default(),
+            disabled_groups: Default::default(),
+            active_layer: Default::default(),
+            virtual_control_element_offset: 0,
+            fx_parameter_bank_offset: 0,
+            spilled_folder_track: None,
             active_instance_tags: Default::default(),
+            io_connection_status: Default::default(),
+            cue_sends: Default::default(),
+            midi_clock_outputs: Default::default(),
+            midi_macros: Default::default(),
+            sent_program_changes: Default::default(),
+            mapping_snapshots: Default::default(),
+            control_latency_measurement: Default::default(),
+            deferred_clip_slots: None,
+            clip_stop_quantization: None,
         }
     }
 
@@ -138,6 +241,90 @@ impl InstanceState {
             .unwrap();
     }
 
+    pub fn group_is_enabled(&self, compartment: MappingCompartment, group_id: GroupId) -> bool {
+        !self.disabled_groups[compartment].contains(&group_id)
+    }
+
+    pub fn set_group_enabled(
+        &mut self,
+        compartment: MappingCompartment,
+        group_id: GroupId,
+        enabled: bool,
+    ) {
+        if enabled {
+            self.disabled_groups[compartment].remove(&group_id);
+        } else {
+            self.disabled_groups[compartment].insert(group_id);
+        }
+        let instance_event = InstanceStateChanged::GroupEnabled {
+            compartment,
+            group_id,
+        };
+        self.instance_feedback_event_sender
+            .try_send(instance_event)
+            .unwrap();
+    }
+
+    pub fn active_layer(&self, compartment: MappingCompartment) -> LayerId {
+        self.active_layer[compartment]
+    }
+
+    pub fn set_active_layer(&mut self, compartment: MappingCompartment, layer: LayerId) {
+        self.active_layer[compartment] = layer;
+        let instance_event = InstanceStateChanged::ActiveLayer { compartment, layer };
+        self.instance_feedback_event_sender
+            .try_send(instance_event)
+            .unwrap();
+    }
+
+    pub fn virtual_control_element_offset(&self) -> u32 {
+        self.virtual_control_element_offset
+    }
+
+    pub fn set_virtual_control_element_offset(&mut self, offset: u32) {
+        self.virtual_control_element_offset = offset;
+        let instance_event = InstanceStateChanged::VirtualControlElementOffset { offset };
+        self.instance_feedback_event_sender
+            .try_send(instance_event)
+            .unwrap();
+    }
+
+    pub fn fx_parameter_bank_offset(&self) -> u32 {
+        self.fx_parameter_bank_offset
+    }
+
+    pub fn set_fx_parameter_bank_offset(&mut self, offset: u32) {
+        self.fx_parameter_bank_offset = offset;
+        let instance_event = InstanceStateChanged::FxParameterBankOffset { offset };
+        self.instance_feedback_event_sender
+            .try_send(instance_event)
+            .unwrap();
+    }
+
+    pub fn spilled_folder_track(&self) -> Option<Guid> {
+        self.spilled_folder_track
+    }
+
+    pub fn set_spilled_folder_track(&mut self, id: Option<Guid>) {
+        self.spilled_folder_track = id;
+        let instance_event = InstanceStateChanged::SpilledFolderTrack { id };
+        self.instance_feedback_event_sender
+            .try_send(instance_event)
+            .unwrap();
+    }
+
+    pub fn disabled_groups(&self, compartment: MappingCompartment) -> &HashSet<GroupId> {
+        &self.disabled_groups[compartment]
+    }
+
+    pub fn set_disabled_groups(
+        &mut self,
+        compartment: MappingCompartment,
+        group_ids: HashSet<GroupId>,
+    ) {
+        self.disabled_groups[compartment] = group_ids;
+    }
+
     pub fn only_these_instance_tags_are_active(&self, tags: &HashSet<Tag>) -> bool {
         tags == &self.active_instance_tags
     }
@@ -184,8 +371,27 @@ impl InstanceState {
         self.on_mappings.changed()
     }
 
+    pub fn io_connection_status(&self) -> &IoConnectionStatus {
+        self.io_connection_status.get_ref()
+    }
+
+    pub fn mapping_is_suspended(&self, id: QualifiedMappingId) -> bool {
+        self.io_connection_status.get_ref().mapping_is_suspended(id)
+    }
+
+    pub fn io_connection_status_changed(
+        &self,
+    ) -> impl LocalObservable<'static, Item = (), Err = ()> + 'static {
+        self.io_connection_status.changed()
+    }
+
+    pub fn set_io_connection_status(&mut self, status: IoConnectionStatus) {
+        self.io_connection_status.set(status);
+    }
+
     pub fn set_on_mappings(&mut self, on_mappings: HashSet<QualifiedMappingId>) {
         self.on_mappings.set(on_mappings);
+        self.clean_up_after_off_mappings();
     }
 
     pub fn set_mapping_on(&mut self, id: QualifiedMappingId, is_on: bool) {
@@ -196,6 +402,69 @@ impl InstanceState {
                 m.remove(&id);
             }
         });
+        self.clean_up_after_off_mappings();
+    }
+
+    pub fn mapping_snapshots(&self) -> &MappingSnapshotContainer {
+        &self.mapping_snapshots
+    }
+
+    pub fn mapping_snapshots_mut(&mut self) -> &mut MappingSnapshotContainer {
+        &mut self.mapping_snapshots
+    }
+
+    pub fn control_latency_measurement(&self) -> &ControlLatencyMeasurement {
+        &self.control_latency_measurement
+    }
+
+    pub fn control_latency_measurement_mut(&mut self) -> &mut ControlLatencyMeasurement {
+        &mut self.control_latency_measurement
+    }
+
+    pub fn cue_sends(&self) -> &CueSends {
+        &self.cue_sends
+    }
+
+    pub fn cue_sends_mut(&mut self) -> &mut CueSends {
+        &mut self.cue_sends
+    }
+
+    pub fn midi_clock_outputs_mut(&mut self) -> &mut MidiClockOutputs {
+        &mut self.midi_clock_outputs
+    }
+
+    pub fn midi_macros(&self) -> &MidiMacros {
+        &self.midi_macros
+    }
+
+    pub fn set_midi_macros(&mut self, midi_macros: MidiMacros) {
+        self.midi_macros = midi_macros;
+    }
+
+    pub fn sent_program_change(
+        &self,
+        dev_id: MidiOutputDeviceId,
+        channel: u8,
+    ) -> Option<&ProgramChangePatch> {
+        self.sent_program_changes.get(dev_id, channel)
+    }
+
+    pub fn set_sent_program_change(
+        &mut self,
+        dev_id: MidiOutputDeviceId,
+        channel: u8,
+        patch: ProgramChangePatch,
+    ) {
+        self.sent_program_changes.insert(dev_id, channel, patch);
+        self.send_feedback_event(InstanceStateChanged::SentProgramChange { dev_id, channel });
+    }
+
+    /// Removes cue sends and stops MIDI clocks whose mapping is not on anymore.
+    fn clean_up_after_off_mappings(&mut self) {
+        let on_mappings = self.on_mappings.get_ref();
+        let is_on = |owner: MappingId| on_mappings.iter().any(|id| id.id == owner);
+        self.cue_sends.retain_owners(is_on);
+        self.midi_clock_outputs.retain_owners(is_on);
     }
 
     pub fn active_mapping_by_group(
@@ -277,14 +546,21 @@ impl InstanceState {
             .filter(move |id| self.mapping_is_on(QualifiedMappingId::new(compartment, *id)))
     }
 
+    pub fn clip_matrix(&self) -> &ClipMatrix {
+        &self.clip_matrix
+    }
+
+    pub fn clip_stop_quantization(&self) -> Option<SlotStopQuantization> {
+        self.clip_stop_quantization
+    }
+
+    pub fn set_clip_stop_quantization(&mut self, quantization: Option<SlotStopQuantization>) {
+        self.clip_stop_quantization = quantization;
+    }
+
     pub fn process_transport_change(&mut self, new_play_state: PlayState) {
-        for (slot_index, slot) in self.clip_slots.iter_mut().enumerate() {
-            if let Ok(Some(event)) = slot.process_transport_change(new_play_state) {
-                let instance_event = InstanceStateChanged::Clip { slot_index, event };
-                self.instance_feedback_event_sender
-                    .try_send(instance_event)
-                    .unwrap();
-            }
+        for (slot_index, event) in self.clip_matrix.process_transport_change(new_play_state) {
+            self.send_clip_changed_event(slot_index, event);
         }
     }
 
@@ -296,53 +572,50 @@ impl InstanceState {
 
     /// Detects clips that are finished playing and invokes a stop feedback event if not looped.
     pub fn poll_slot(&mut self, slot_index: usize) -> Option<ClipChangedEvent> {
-        self.clip_slots
-            .get_mut(slot_index)
-            .expect("no such slot")
-            .poll()
+        self.clip_matrix.poll_slot(slot_index)
+    }
+
+    /// Should be called after polling all slots.
+    pub fn process_clip_follow_actions(&mut self) {
+        for (slot_index, event) in self.clip_matrix.process_follow_actions() {
+            self.send_clip_changed_event(slot_index, event);
+        }
     }
 
-    pub fn filled_slot_descriptors(&self) -> Vec<QualifiedSlotDescriptor> {
-        self.clip_slots
-            .iter()
-            .enumerate()
-            .filter(|(_, s)| s.is_filled())
-            .map(|(i, s)| QualifiedSlotDescriptor {
-                index: i,
-                descriptor: s.descriptor().clone(),
-            })
-            .collect()
+    pub fn clip_matrix_content(&self) -> ClipMatrixContent {
+        self.clip_matrix.content()
     }
 
-    pub fn load_slots(
+    pub fn load_clip_matrix(
         &mut self,
-        descriptors: Vec<QualifiedSlotDescriptor>,
+        content: ClipMatrixContent,
         project: Option<Project>,
     ) -> Result<(), &'static str> {
-        for slot in &mut self.clip_slots {
-            let _ = slot.reset();
-        }
-        for desc in descriptors {
-            let events = {
-                let slot = self.get_slot_mut(desc.index)?;
-                slot.load(desc.descriptor, project)?
-            };
-            for e in events {
-                self.send_clip_changed_event(desc.index, e);
-            }
+        let events = self.clip_matrix.load(content, project)?;
+        for (slot_index, e) in events {
+            self.send_clip_changed_event(slot_index, e);
         }
         self.notify_slot_contents_changed();
         Ok(())
     }
 
+    /// Changes the number of rows and columns of this instance's own clip matrix.
+    ///
+    /// Slot indexes change, so clip targets need to be resolved again.
+    pub fn resize_clip_matrix(&mut self, size: ClipMatrixSize) -> Result<(), &'static str> {
+        self.clip_matrix.resize(size)?;
+        self.notify_slot_contents_changed();
+        Ok(())
+    }
+
     pub fn fill_slot_by_user(
         &mut self,
         slot_index: usize,
         content: SlotContent,
         project: Option<Project>,
     ) -> Result<(), &'static str> {
-        self.get_slot_mut(slot_index)?
-            .fill_by_user(content, project)?;
+        self.clip_matrix
+            .fill_slot_by_user(slot_index, content, project)?;
         self.notify_slot_contents_changed();
         Ok(())
     }
@@ -352,78 +625,94 @@ impl InstanceState {
         slot_index: usize,
         item: Item,
     ) -> Result<(), Box<dyn Error>> {
-        self.get_slot_mut(slot_index)?
-            .fill_with_source_from_item(item)?;
+        self.clip_matrix
+            .fill_slot_with_item_source(slot_index, item)?;
         self.notify_slot_contents_changed();
         Ok(())
     }
 
-    pub fn play(
+    pub fn set_slot_tempo(&mut self, slot_index: usize, bpm: f64) -> Result<(), &'static str> {
+        self.clip_matrix.set_slot_tempo(slot_index, bpm)
+    }
+
+    pub fn set_slot_fades(
         &mut self,
         slot_index: usize,
-        track: Option<Track>,
-        options: SlotPlayOptions,
+        fades: SlotFades,
     ) -> Result<(), &'static str> {
-        let event = self.get_slot_mut(slot_index)?.play(track, options)?;
-        self.send_clip_changed_event(slot_index, event);
-        Ok(())
+        self.clip_matrix.set_slot_fades(slot_index, fades)
     }
 
-    /// If repeat is not enabled and `immediately` is false, this has essentially no effect.
-    pub fn stop(&mut self, slot_index: usize, immediately: bool) -> Result<(), &'static str> {
-        let event = self.get_slot_mut(slot_index)?.stop(immediately)?;
-        self.send_clip_changed_event(slot_index, event);
-        Ok(())
+    pub fn set_slot_follow_action(
+        &mut self,
+        slot_index: usize,
+        follow_action: Option<SlotFollowAction>,
+    ) -> Result<(), &'static str> {
+        self.clip_matrix
+            .set_slot_follow_action(slot_index, follow_action)
     }
 
-    pub fn pause(&mut self, slot_index: usize) -> Result<(), &'static str> {
-        let event = self.get_slot_mut(slot_index)?.pause()?;
-        self.send_clip_changed_event(slot_index, event);
-        Ok(())
+    pub fn embed_clip_contents(&mut self, project: Option<Project>) -> Result<usize, &'static str> {
+        let count = self.clip_matrix.embed_contents(project)?;
+        self.notify_slot_contents_changed();
+        Ok(count)
     }
 
-    pub fn toggle_repeat(&mut self, slot_index: usize) -> Result<(), &'static str> {
-        let event = self.get_slot_mut(slot_index)?.toggle_repeat();
-        self.send_clip_changed_event(slot_index, event);
-        Ok(())
+    pub fn externalize_clip_contents(&mut self, project: Project) -> Result<usize, &'static str> {
+        let count = self.clip_matrix.externalize_contents(project)?;
+        self.notify_slot_contents_changed();
+        Ok(count)
     }
 
-    pub fn seek_slot(
+    /// Applies the given operation to a slot of this instance's own clip matrix and sends the
+    /// resulting change event.
+    pub fn modify_clip_slot(
         &mut self,
         slot_index: usize,
-        position: UnitValue,
+        op: impl FnOnce(&mut ClipSlot) -> Result<ClipChangedEvent, &'static str>,
     ) -> Result<(), &'static str> {
-        let event = self.get_slot_mut(slot_index)?.set_position(position)?;
+        let event = op(self.clip_matrix.get_slot_mut(slot_index)?)?;
         self.send_clip_changed_event(slot_index, event);
         Ok(())
     }
 
-    pub fn set_volume(
+    /// Applies the given operation to this instance's own clip matrix and sends the resulting change
+    /// events.
+    pub fn modify_clip_matrix(
         &mut self,
-        slot_index: usize,
-        volume: ReaperVolumeValue,
+        op: impl FnOnce(&mut ClipMatrix) -> Result<Vec<(usize, ClipChangedEvent)>, &'static str>,
     ) -> Result<(), &'static str> {
-        let event = self.get_slot_mut(slot_index)?.set_volume(volume);
-        self.send_clip_changed_event(slot_index, event);
+        let events = op(&mut self.clip_matrix)?;
+        for (slot_index, event) in events {
+            self.send_clip_changed_event(slot_index, event);
+        }
         Ok(())
     }
 
     pub fn get_slot(&self, slot_index: usize) -> Result<&ClipSlot, &'static str> {
-        self.clip_slots.get(slot_index).ok_or("no such slot")
-    }
-
-    fn get_slot_mut(&mut self, slot_index: usize) -> Result<&mut ClipSlot, &'static str> {
-        self.clip_slots.get_mut(slot_index).ok_or("no such slot")
+        self.clip_matrix.get_slot(slot_index)
     }
 
     fn send_clip_changed_event(&self, slot_index: usize, event: ClipChangedEvent) {
-        self.send_feedback_event(InstanceStateChanged::Clip { slot_index, event });
+        self.send_feedback_event(clip_changed_event(None, slot_index, event));
     }
 
     fn send_feedback_event(&self, event: InstanceStateChanged) {
         self.instance_feedback_event_sender.try_send(event).unwrap();
     }
 
+    pub fn deferred_clip_slots(&self) -> Option<&DeferredClipSlots> {
+        self.deferred_clip_slots.as_ref()
+    }
+
+    pub fn defer_clip_slots(&mut self, slots: DeferredClipSlots) {
+        self.deferred_clip_slots = Some(slots);
+    }
+
+    pub fn take_deferred_clip_slots(&mut self) -> Option<DeferredClipSlots> {
+        self.deferred_clip_slots.take()
+    }
+
     fn notify_slot_contents_changed(&mut self) {
         AsyncNotifier::notify(&mut self.slot_contents_changed_subject, &());
     }
@@ -437,9 +726,19 @@ pub struct QualifiedSlotDescriptor {
     pub descriptor: SlotDescriptor,
 }
 
+/// Clip slots of the instance's own clip matrix and of the shared clip matrices as saved in the
+/// session.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct DeferredClipSlots {
+    pub own: ClipMatrixContent,
+    pub shared: HashMap<ClipMatrixId, ClipMatrixContent>,
+}
+
 #[derive(Debug)]
 pub enum InstanceStateChanged {
     Clip {
+        /// `None` if the event is about the instance's own clip matrix.
+        matrix_id: Option<ClipMatrixId>,
         slot_index: usize,
         event: ClipChangedEvent,
     },
@@ -451,10 +750,31 @@ pub enum InstanceStateChanged {
     ActiveMappingTags {
         compartment: MappingCompartment,
     },
+    GroupEnabled {
+        compartment: MappingCompartment,
+        group_id: GroupId,
+    },
+    ActiveLayer {
+        compartment: MappingCompartment,
+        layer: LayerId,
+    },
+    VirtualControlElementOffset {
+        offset: u32,
+    },
+    FxParameterBankOffset {
+        offset: u32,
+    },
+    SpilledFolderTrack {
+        id: Option<Guid>,
+    },
     ActiveInstanceTags,
+    SentProgramChange {
+        dev_id: MidiOutputDeviceId,
+        channel: u8,
+    },
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum ClipChangedEvent {
     PlayState(ClipPlayState),
     ClipVolume(ReaperVolumeValue),
@@ -1,4 +1,4 @@
-use crate::domain::{MidiControlInput, MidiDestination, OscDeviceId};
+use crate::domain::{MidiControlInput, MidiDestination, OscDeviceId, RtpMidiDeviceId};
 use reaper_medium::{MidiInputDeviceId, MidiOutputDeviceId};
 
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
@@ -12,6 +12,7 @@ impl ControlInput {
         use ControlInput::*;
         match self {
             Midi(MidiControlInput::Device(id)) => Some(DeviceControlInput::Midi(id)),
+            Midi(MidiControlInput::RtpMidi(id)) => Some(DeviceControlInput::RtpMidi(id)),
             Osc(id) => Some(DeviceControlInput::Osc(id)),
             _ => None,
         }
@@ -36,6 +37,7 @@ impl Default for ControlInput {
 pub enum DeviceControlInput {
     Midi(MidiInputDeviceId),
     Osc(OscDeviceId),
+    RtpMidi(RtpMidiDeviceId),
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
@@ -49,6 +51,7 @@ impl FeedbackOutput {
         use FeedbackOutput::*;
         match self {
             Midi(MidiDestination::Device(id)) => Some(DeviceFeedbackOutput::Midi(id)),
+            Midi(MidiDestination::RtpMidi(id)) => Some(DeviceFeedbackOutput::RtpMidi(id)),
             Osc(id) => Some(DeviceFeedbackOutput::Osc(id)),
             _ => None,
         }
@@ -59,4 +62,5 @@ impl FeedbackOutput {
 pub enum DeviceFeedbackOutput {
     Midi(MidiOutputDeviceId),
     Osc(OscDeviceId),
+    RtpMidi(RtpMidiDeviceId),
 }
@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Identifies a layer of mappings within a compartment.
+///
+/// Exactly one layer per compartment is active at a time. Mappings which are assigned to a layer
+/// can only be controlled while that layer is active. Layer 0 is the base layer, which is active
+/// initially.
+#[derive(
+    Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default, Serialize, Deserialize,
+)]
+#[serde(transparent)]
+pub struct LayerId(u32);
+
+impl LayerId {
+    pub fn new(index: u32) -> Self {
+        Self(index)
+    }
+
+    pub fn get(self) -> u32 {
+        self.0
+    }
+
+    pub fn is_base(self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl fmt::Display for LayerId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_base() {
+            f.write_str("Base layer")
+        } else {
+            write!(f, "Layer {}", self.0)
+        }
+    }
+}
+
+impl FromStr for LayerId {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let index = s
+            .trim()
+            .parse()
+            .map_err(|_| "layer must be a non-negative number")?;
+        Ok(Self(index))
+    }
+}
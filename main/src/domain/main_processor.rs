@@ -1,25 +1,33 @@
 use crate::domain::{
-    aggregate_target_values, ActivationChange, AdditionalFeedbackEvent, BackboneState,
-    ClipChangedEvent, CompoundChangeEvent, CompoundFeedbackValue, CompoundMappingSource,
-    CompoundMappingSourceAddress, CompoundMappingTarget, ControlContext, ControlInput, ControlMode,
-    DeviceFeedbackOutput, DomainEvent, DomainEventHandler, ExtendedProcessorContext,
-    FeedbackAudioHookTask, FeedbackDestinations, FeedbackOutput, FeedbackRealTimeTask,
-    FeedbackResolution, FeedbackSendBehavior, GroupId, HitInstructionContext, InstanceContainer,
-    InstanceOrchestrationEvent, InstanceStateChanged, IoUpdatedEvent, MainMapping,
-    MainSourceMessage, MappingActivationEffect, MappingCompartment, MappingControlResult,
-    MappingId, MappingInfo, MessageCaptureEvent, MessageCaptureResult, MidiDestination,
-    MidiScanResult, NormalRealTimeTask, OrderedMappingIdSet, OrderedMappingMap, OscDeviceId,
-    OscFeedbackTask, OscScanResult, ProcessorContext, QualifiedMappingId, QualifiedSource,
-    RealFeedbackValue, RealTimeSender, RealearnMonitoringFxParameterValueChangedEvent,
-    ReaperMessage, ReaperTarget, SharedInstanceState, SmallAsciiString, SourceFeedbackValue,
-    SourceReleasedEvent, SpecificCompoundFeedbackValue, TargetValueChangedEvent,
-    UpdatedSingleMappingOnStateEvent, VirtualSourceValue, CLIP_SLOT_COUNT,
+    aggregate_target_values, load_mapping_snapshot_value, ActivationChange,
+    AdditionalFeedbackEvent, BackboneState, ClipChangedEvent, CompoundChangeEvent,
+    CompoundFeedbackValue, CompoundMappingSource, CompoundMappingSourceAddress,
+    CompoundMappingTarget, ControlContext, ControlElementIdentification, ControlInput, ControlMode,
+    ControlRateLimiter, DeviceChanges, DeviceControlInput, DeviceFeedbackOutput, DomainEvent,
+    DomainEventHandler, ExtendedProcessorContext, FeedbackAudioHookTask, FeedbackDeadband,
+    FeedbackDeadbandMemory, FeedbackDestinations, FeedbackLoopCheckResult,
+    FeedbackLoopDetectedEvent, FeedbackLoopDetector, FeedbackOutput, FeedbackRealTimeTask,
+    FeedbackResolution, FeedbackSendBehavior, GroupId, HitInstructionContext,
+    IdentificationPollResult, IdleAnimation, IdleAnimationSettings, IncomingCompoundSourceValue,
+    InstanceContainer, InstanceHookAction, InstanceHookEvent, InstanceHooks, InstanceMessage,
+    InstanceOrchestrationEvent, InstancePriority, InstanceStateChanged, IoConnectionStatus,
+    IoUpdatedEvent, LayerId, MainMapping, MainSourceMessage, MappingActivationEffect,
+    MappingCompartment, MappingControlResult, MappingId, MappingInfo, MessageCaptureEvent,
+    MessageCaptureResult, MidiControlInput, MidiDestination, MidiScanResult, MmcMessage,
+    NormalRealTimeTask, OrderedMappingIdSet, OrderedMappingMap, OscDeviceId, OscFeedbackTask,
+    OscScanResult, ParameterChangedEvent, ParameterLinking, ProcessorContext, QualifiedMappingId,
+    QualifiedSource, RateLimitedControl, RealFeedbackValue, RealTimeSender,
+    RealearnMonitoringFxParameterValueChangedEvent, ReaperMessage, ReaperStateCondition,
+    ReaperTarget, SharedInstanceState, SlotStopQuantization, SmallAsciiString, SourceFeedbackValue,
+    SourceReleasedEvent, SpecificCompoundFeedbackValue, TargetDependencyIndex,
+    TargetValueChangeOrigin, TargetValueChangedEvent, UpdatedSingleMappingOnStateEvent,
+    VirtualControlElement, VirtualFeedbackValue, VirtualSourceValue,
 };
 use derive_more::Display;
 use enum_map::EnumMap;
 use helgoboss_learn::{
-    AbsoluteValue, ControlValue, GroupInteraction, MidiSourceValue, MinIsMaxBehavior,
-    ModeControlOptions, RawMidiEvent, Target, BASE_EPSILON,
+    AbsoluteValue, ControlValue, FeedbackValue, GroupInteraction, MidiSourceValue,
+    MinIsMaxBehavior, ModeControlOptions, NumericFeedbackValue, RawMidiEvent, Target, BASE_EPSILON,
 };
 use std::borrow::Cow;
 use std::cell::RefCell;
@@ -27,12 +35,15 @@ use std::cell::RefCell;
 use crate::domain::ui_util::{
     format_incoming_midi_message, format_midi_source_value, format_osc_message, format_osc_packet,
     format_raw_midi, log_control_input, log_feedback_output, log_learn_input, log_lifecycle_output,
-    log_target_output,
+    log_output, log_target_output, OutputReason,
 };
 use ascii::{AsciiString, ToAsciiChar};
-use helgoboss_midi::{ControlChange14BitMessage, ParameterNumberMessage, RawShortMessage};
+use helgoboss_midi::{
+    Channel, ControlChange14BitMessage, ControllerNumber, ParameterNumberMessage, RawShortMessage,
+    ShortMessage, ShortMessageFactory, U7,
+};
 use reaper_high::{ChangeEvent, Reaper};
-use reaper_medium::ReaperNormalizedFxParamValue;
+use reaper_medium::{Guid, MidiInputDeviceId, MidiOutputDeviceId, ReaperNormalizedFxParamValue};
 use rosc::{OscMessage, OscPacket, OscType};
 use slog::{debug, trace};
 use smallvec::SmallVec;
@@ -40,6 +51,10 @@ use std::collections::hash_map::Entry;
 use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::hash::{Hash, Hasher};
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "realearn-meter")]
+use crate::domain::{ControlLatencies, ControlStage};
 
 // This can be come pretty big when multiple track volumes are adjusted at once.
 const FEEDBACK_TASK_QUEUE_SIZE: usize = 20_000;
@@ -47,6 +62,9 @@ const NORMAL_TASK_BULK_SIZE: usize = 32;
 const FEEDBACK_TASK_BULK_SIZE: usize = 64;
 const CONTROL_TASK_BULK_SIZE: usize = 32;
 const PARAMETER_TASK_BULK_SIZE: usize = 32;
+/// How long after sending the controller state requests incoming control values are treated as
+/// state responses.
+const CONTROLLER_STATE_SYNC_WINDOW: Duration = Duration::from_millis(500);
 
 pub const PLUGIN_PARAMETER_COUNT: u32 = 200;
 pub const COMPARTMENT_PARAMETER_COUNT: u32 = 100;
@@ -60,6 +78,18 @@ pub struct MainProcessor<EH: DomainEventHandler> {
     collections: Collections,
     /// Contains IDs of those mappings who need to be polled as frequently as possible.
     poll_control_mappings: EnumMap<MappingCompartment, OrderedMappingIdSet>,
+    /// Mappings with a minimum target hit interval (which might hold back target values).
+    throttled_target_mappings: EnumMap<MappingCompartment, OrderedMappingIdSet>,
+    instance_hooks: InstanceHooks,
+    parameter_linking: ParameterLinking,
+    /// Coalesces control values of mappings which have a maximum control rate.
+    control_rate_limiter: ControlRateLimiter,
+    /// While set and not yet reached, control values of pickup mappings are only used to prime
+    /// the mode (they are most likely responses to the controller state requests).
+    controller_state_sync_deadline: Option<Instant>,
+    /// Latencies of control input coming from the real-time processor, exposed as metrics.
+    #[cfg(feature = "realearn-meter")]
+    control_latencies: ControlLatencies,
 }
 
 #[derive(Debug)]
@@ -76,6 +106,7 @@ struct Basics<EH: DomainEventHandler> {
     control_is_globally_enabled: bool,
     control_input: ControlInput,
     feedback_output: Option<FeedbackOutput>,
+    instance_priority: InstancePriority,
     instance_state: SharedInstanceState,
     input_logging_enabled: bool,
     output_logging_enabled: bool,
@@ -87,6 +118,57 @@ struct Basics<EH: DomainEventHandler> {
     // context. Rightfully so, because it's potentially reentrant!
     last_feedback_checksum_by_address:
         RefCell<HashMap<CompoundMappingSourceAddress, FeedbackChecksum>>,
+    // RefCell for the same reason as `last_feedback_checksum_by_address`.
+    feedback_deadband_memory: RefCell<FeedbackDeadbandMemory<CompoundMappingSourceAddress>>,
+    idle_animation: IdleAnimation,
+    control_element_identification: Option<ControlElementIdentification>,
+    // RefCell for the same reason as `last_feedback_checksum_by_address`.
+    feedback_loop_detector: RefCell<FeedbackLoopDetector<FeedbackEcho>>,
+    io_connection_status: IoConnectionStatus,
+    /// While a mapping is soloed, control processing of all other mappings in its compartment is
+    /// muted.
+    soloed_mapping: Option<QualifiedMappingId>,
+    /// Bank offset added to indexed virtual control elements on their way from controller
+    /// mappings to main mappings (and subtracted on the way back).
+    virtual_control_element_offset: u32,
+    /// Hop count of the instance message which is currently being processed, if any.
+    instance_message_hop_count: Option<u32>,
+}
+
+/// Sent feedback as it would arrive as control input if the controller sent it right back.
+#[derive(Debug)]
+enum FeedbackEcho {
+    Midi(MidiSourceValue<'static, RawShortMessage>),
+    Osc(OscMessage),
+    Virtual(VirtualSourceValue),
+}
+
+impl FeedbackEcho {
+    fn from_source_feedback_value(v: &SourceFeedbackValue) -> Option<Self> {
+        use SourceFeedbackValue::*;
+        match v {
+            // Sys-ex feedback (usually displays) doesn't come back as control input.
+            Midi(MidiSourceValue::Raw { .. }) => None,
+            Midi(v) => Some(Self::Midi(v.clone())),
+            Osc(v) => Some(Self::Osc(v.clone())),
+        }
+    }
+
+    fn from_virtual_feedback_value(v: &VirtualFeedbackValue) -> Option<Self> {
+        let value = v.feedback_value().to_numeric()?.value.to_unit_value();
+        let source_value =
+            VirtualSourceValue::new(v.control_element(), ControlValue::AbsoluteContinuous(value));
+        Some(Self::Virtual(source_value))
+    }
+
+    fn as_incoming_source_value(&self) -> IncomingCompoundSourceValue {
+        use FeedbackEcho::*;
+        match self {
+            Midi(v) => IncomingCompoundSourceValue::Midi(v),
+            Osc(v) => IncomingCompoundSourceValue::Osc(v),
+            Virtual(v) => IncomingCompoundSourceValue::Virtual(v),
+        }
+    }
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
@@ -202,10 +284,20 @@ struct Collections {
     ///  could be optimized. However, this is what makes the seek target work currently when
     ///  changing cursor position while stopped.
     milli_dependent_feedback_mappings: EnumMap<MappingCompartment, OrderedMappingIdSet>,
+    /// Contains IDs of those mappings which should be refreshed when a particular track or FX
+    /// changes.
+    target_dependency_index: EnumMap<MappingCompartment, TargetDependencyIndex>,
     parameters: ParameterArray,
     previous_target_values: EnumMap<MappingCompartment, HashMap<MappingId, AbsoluteValue>>,
 }
 
+/// Target refreshes requested by tasks, done only once after processing all tasks of one cycle.
+#[derive(Default)]
+struct PendingTargetRefresh {
+    all: bool,
+    objects: HashSet<Guid>,
+}
+
 #[derive(Debug)]
 struct Channels {
     self_feedback_sender: crossbeam_channel::Sender<FeedbackMainTask>,
@@ -264,6 +356,7 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
                 control_is_globally_enabled: true,
                 control_input: Default::default(),
                 feedback_output: Default::default(),
+                instance_priority: Default::default(),
                 instance_state,
                 instance_container,
                 input_logging_enabled: false,
@@ -286,6 +379,14 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
                     integration_test_feedback_sender: None,
                 },
                 last_feedback_checksum_by_address: Default::default(),
+                feedback_deadband_memory: Default::default(),
+                idle_animation: IdleAnimation::new(Default::default(), Instant::now()),
+                control_element_identification: None,
+                feedback_loop_detector: RefCell::new(FeedbackLoopDetector::new(true)),
+                io_connection_status: Default::default(),
+                soloed_mapping: None,
+                virtual_control_element_offset: 0,
+                instance_message_hop_count: None,
             },
             collections: Collections {
                 mappings: Default::default(),
@@ -293,10 +394,18 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
                 target_touch_dependent_mappings: Default::default(),
                 beat_dependent_feedback_mappings: Default::default(),
                 milli_dependent_feedback_mappings: Default::default(),
+                target_dependency_index: Default::default(),
                 parameters: ZEROED_PLUGIN_PARAMETERS,
                 previous_target_values: Default::default(),
             },
             poll_control_mappings: Default::default(),
+            throttled_target_mappings: Default::default(),
+            instance_hooks: Default::default(),
+            parameter_linking: Default::default(),
+            control_rate_limiter: Default::default(),
+            controller_state_sync_deadline: None,
+            #[cfg(feature = "realearn-meter")]
+            control_latencies: Default::default(),
         }
     }
 
@@ -304,23 +413,29 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
         &self.basics.instance_id
     }
 
+    #[cfg(feature = "realearn-meter")]
+    pub fn control_latencies(&self) -> &ControlLatencies {
+        &self.control_latencies
+    }
+
     /// This is the chance to take over a source from another instance (send our feedback).
     ///
     /// This is a very important principle when using multiple instances. It allows feedback to
     /// not be accidentally cleared while still guaranteeing that feedback for non-used control
     /// elements are cleared eventually - independently from the order of instance processing.
     pub fn maybe_takeover_source(&self, released_event: &SourceReleasedEvent) -> bool {
-        if Some(released_event.feedback_output) != self.basics.feedback_output {
-            // Difference feedback device. No source takeover of course.
-            return false;
-        }
+        let feedback_output = Some(released_event.feedback_output);
         if let Some(mapping_with_source) = self.all_mappings().find(|m| {
-            m.feedback_is_effectively_on()
+            // Different feedback device means no source takeover of course.
+            self.basics
+                .effective_feedback_output(m.options().midi_output_device)
+                == feedback_output
+                && m.feedback_is_effectively_on()
                 && m.source()
                     .has_same_feedback_address_as_value(&released_event.feedback_value)
         }) {
             if let Some(followed_mapping) = self.follow_maybe_virtual_mapping(mapping_with_source) {
-                if self.basics.instance_feedback_is_effectively_enabled() {
+                if self.basics.feedback_is_effectively_enabled(feedback_output) {
                     debug!(
                         self.basics.logger,
                         "Taking over source {:?}...",
@@ -363,6 +478,7 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
             FeedbackReason::FinallySwitchOffSource,
             feedback_value,
             false,
+            None,
         );
     }
 
@@ -389,10 +505,25 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
         // It's possible that control is disabled because another instance cancels us. In that case
         // the RealTimeProcessor won't know about it and keeps sending MIDI. Stop it here!
         if !self.control_is_effectively_enabled() {
+            self.control_rate_limiter.clear();
             return;
         }
         self.process_control_tasks(control_tasks.into_iter());
+        self.process_rate_limited_controls();
         self.poll_control();
+        self.release_pending_target_hits();
+    }
+
+    fn process_rate_limited_controls(&mut self) {
+        for (id, control) in self.control_rate_limiter.release_due(Instant::now()) {
+            let _ = self.control(
+                id.compartment,
+                id.id,
+                control.value,
+                control.options,
+                control.received_at,
+            );
+        }
     }
 
     fn process_control_tasks(&mut self, control_tasks: impl Iterator<Item = ControlMainTask>) {
@@ -404,8 +535,42 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
                     mapping_id,
                     value,
                     options,
+                    received_at,
                 } => {
-                    let _ = self.control(compartment, mapping_id, value, options);
+                    if self.controller_state_sync_is_running()
+                        && self.prime_mapping_with_controller_state(compartment, mapping_id, value)
+                    {
+                        continue;
+                    }
+                    let min_interval = self.collections.mappings[compartment]
+                        .get(&mapping_id)
+                        .and_then(|m| m.options().min_control_interval);
+                    let control = RateLimitedControl {
+                        value,
+                        options,
+                        received_at,
+                    };
+                    let control = if let Some(min_interval) = min_interval {
+                        let id = QualifiedMappingId::new(compartment, mapping_id);
+                        match self.control_rate_limiter.process(
+                            id,
+                            control,
+                            min_interval,
+                            Instant::now(),
+                        ) {
+                            None => continue,
+                            Some(c) => c,
+                        }
+                    } else {
+                        control
+                    };
+                    let _ = self.control(
+                        compartment,
+                        mapping_id,
+                        control.value,
+                        control.options,
+                        control.received_at,
+                    );
                 }
                 LogControlInput {
                     value,
@@ -422,10 +587,61 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
                 LogTargetOutput { event } => {
                     log_target_output(self.instance_id(), format_raw_midi(event.bytes()));
                 }
+                ProcessMmc { msg } => {
+                    self.process_reaper_message(&ReaperMessage::Mmc(msg));
+                }
             }
         }
     }
 
+    fn controller_state_sync_is_running(&mut self) -> bool {
+        match self.controller_state_sync_deadline {
+            None => false,
+            Some(deadline) if Instant::now() < deadline => true,
+            Some(_) => {
+                self.controller_state_sync_deadline = None;
+                false
+            }
+        }
+    }
+
+    /// Returns `true` if the given mapping uses pickup and therefore consumed the value.
+    fn prime_mapping_with_controller_state(
+        &mut self,
+        compartment: MappingCompartment,
+        mapping_id: MappingId,
+        value: ControlValue,
+    ) -> bool {
+        let m = match self.collections.mappings[compartment].get_mut(&mapping_id) {
+            None => return false,
+            Some(m) => m,
+        };
+        if !m.control_is_effectively_on() || !m.uses_pickup() {
+            return false;
+        }
+        m.prime_mode(value, self.basics.control_context());
+        true
+    }
+
+    /// Sends the state requests to the feedback output and interprets the following control input
+    /// as the current state of the control elements.
+    fn request_controller_state(&mut self, events: Vec<RawMidiEvent>) {
+        let dev_id = match self.basics.feedback_output {
+            Some(FeedbackOutput::Midi(MidiDestination::Device(dev_id))) => dev_id,
+            _ => {
+                debug!(
+                    self.basics.logger,
+                    "Can't request controller state because feedback output is not a MIDI device"
+                );
+                return;
+            }
+        };
+        self.basics
+            .control_context()
+            .send_raw_midi(OutputReason::System, dev_id, events);
+        self.controller_state_sync_deadline = Some(Instant::now() + CONTROLLER_STATE_SYNC_WINDOW);
+    }
+
     fn poll_control(&mut self) {
         for compartment in MappingCompartment::enum_iter() {
             for id in self.poll_control_mappings[compartment].iter() {
@@ -488,6 +704,68 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
         }
     }
 
+    /// Applies target values which have been held back because of a minimum target hit interval.
+    fn release_pending_target_hits(&mut self) {
+        for compartment in MappingCompartment::enum_iter() {
+            for id in self.throttled_target_mappings[compartment].iter() {
+                let (control_result, group_interaction) =
+                    if let Some(m) = self.collections.mappings[compartment].get_mut(id) {
+                        if !m.has_pending_target_hit() || !m.control_is_effectively_on() {
+                            continue;
+                        }
+                        let control_context = self.basics.control_context();
+                        let mut control_result = match m.release_pending_target_hit(
+                            control_context,
+                            &self.basics.logger,
+                            ExtendedProcessorContext::new(
+                                &self.basics.context,
+                                &self.collections.parameters,
+                                control_context,
+                            ),
+                        ) {
+                            None => continue,
+                            Some(r) => r,
+                        };
+                        control_mapping_stage_two(
+                            &self.basics,
+                            &mut control_result,
+                            m,
+                            ManualFeedbackProcessing::On {
+                                mappings_with_virtual_targets: &self
+                                    .collections
+                                    .mappings_with_virtual_targets,
+                            },
+                        );
+                        (control_result, m.group_interaction())
+                    } else {
+                        continue;
+                    };
+                // Control-value based group interaction has been done when the value arrived
+                // already, so only target-value based one is left.
+                let needs_group_interaction = control_result.successful
+                    && matches!(
+                        group_interaction,
+                        GroupInteraction::SameTargetValue | GroupInteraction::InverseTargetValue
+                    );
+                control_mapping_stage_three(
+                    &self.basics,
+                    &mut self.collections,
+                    compartment,
+                    control_result,
+                    if needs_group_interaction {
+                        GroupInteractionProcessing::On(GroupInteractionInput {
+                            mapping_id: *id,
+                            control_value: ControlValue::AbsoluteContinuous(Default::default()),
+                            group_interaction,
+                        })
+                    } else {
+                        GroupInteractionProcessing::Off
+                    },
+                );
+            }
+        }
+    }
+
     /// Processes incoming control messages from the real-time processor.
     fn control(
         &mut self,
@@ -495,7 +773,11 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
         mapping_id: MappingId,
         control_value: ControlValue,
         options: ControlOptions,
+        received_at: Instant,
     ) -> Result<(), &'static str> {
+        self.notify_control_activity();
+        #[cfg(feature = "realearn-meter")]
+        let qualified_id = QualifiedMappingId::new(compartment, mapping_id);
         // Resolving mappings with virtual targets is not necessary anymore. It has
         // been done in the real-time processor already.
         let (control_result, group_interaction) = {
@@ -512,16 +794,36 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
             if !m.control_is_effectively_on() {
                 return Ok(());
             }
-            let control_result = control_mapping_stage_one_and_two(
+            // Stages are invoked one by one (instead of via `control_mapping_stage_one_and_two`)
+            // in order to measure the latency after each of them.
+            let mut control_result = control_mapping_stage_one(
                 &self.basics,
                 &self.collections.parameters,
                 m,
                 control_value,
                 options,
+            );
+            if control_result.successful {
+                self.basics
+                    .instance_state
+                    .borrow_mut()
+                    .control_latency_measurement_mut()
+                    .record(received_at.elapsed());
+            }
+            #[cfg(feature = "realearn-meter")]
+            self.control_latencies
+                .record(qualified_id, ControlStage::One, received_at.elapsed());
+            control_mapping_stage_two(
+                &self.basics,
+                &mut control_result,
+                m,
                 ManualFeedbackProcessing::On {
                     mappings_with_virtual_targets: &self.collections.mappings_with_virtual_targets,
                 },
             );
+            #[cfg(feature = "realearn-meter")]
+            self.control_latencies
+                .record(qualified_id, ControlStage::Two, received_at.elapsed());
             (control_result, m.group_interaction())
         };
         control_mapping_stage_three(
@@ -535,6 +837,9 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
                 group_interaction,
             }),
         );
+        #[cfg(feature = "realearn-meter")]
+        self.control_latencies
+            .record(qualified_id, ControlStage::Three, received_at.elapsed());
         Ok(())
     }
 
@@ -546,7 +851,135 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
         self.process_feedback_tasks();
         self.poll_slots();
         self.process_instance_feedback_events();
-        self.poll_for_feedback()
+        self.poll_for_feedback();
+        self.process_mapping_snapshot_fades();
+        self.process_idle_animation();
+        self.process_control_element_identification();
+    }
+
+    /// Moves the target values of mappings whose snapshot is being recalled with a fade.
+    fn process_mapping_snapshot_fades(&mut self) {
+        let values = {
+            let mut instance_state = self.basics.instance_state.borrow_mut();
+            let snapshots = instance_state.mapping_snapshots_mut();
+            if !snapshots.is_fading() {
+                return;
+            }
+            snapshots.advance_fades(Instant::now())
+        };
+        for (id, value) in values {
+            let control_context = self.basics.control_context();
+            let m = match self.collections.mappings[id.compartment].get_mut(&id.id) {
+                None => continue,
+                Some(m) => m,
+            };
+            let control_result = load_mapping_snapshot_value(
+                m,
+                value,
+                control_context,
+                &self.basics.logger,
+                ExtendedProcessorContext::new(
+                    &self.basics.context,
+                    &self.collections.parameters,
+                    control_context,
+                ),
+                &self.basics.event_handler,
+            );
+            control_mapping_stage_three(
+                &self.basics,
+                &mut self.collections,
+                id.compartment,
+                control_result,
+                GroupInteractionProcessing::Off,
+            );
+        }
+    }
+
+    /// Stops a running idle animation and restores the normal feedback.
+    fn notify_control_activity(&mut self) {
+        if self.basics.idle_animation.notify_activity(Instant::now()) {
+            debug!(self.basics.logger, "Stopping idle animation...");
+            self.send_all_feedback();
+        }
+    }
+
+    /// Renders the next idle animation frame (if one is due) on all feedback-enabled control
+    /// elements of the controller compartment.
+    fn process_idle_animation(&mut self) {
+        let frame = match self.basics.idle_animation.poll(Instant::now()) {
+            None => return,
+            Some(f) => f,
+        };
+        let elements: Vec<_> = self
+            .collections
+            .mappings_with_virtual_targets
+            .values()
+            .filter(|m| m.feedback_is_enabled())
+            .collect();
+        let element_count = elements.len();
+        let feedback_values: Vec<_> = elements
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, m)| {
+                let value = frame.element_value(i, element_count);
+                let feedback_value = FeedbackValue::Numeric(NumericFeedbackValue::new(
+                    Default::default(),
+                    AbsoluteValue::Continuous(value),
+                ));
+                m.feedback_given_target_value(
+                    Cow::Owned(feedback_value),
+                    FeedbackDestinations {
+                        with_projection_feedback: false,
+                        with_source_feedback: true,
+                    },
+                )
+                .map(CompoundFeedbackValue::normal)
+            })
+            .collect();
+        self.send_feedback(FeedbackReason::IdleAnimation, feedback_values);
+    }
+
+    /// Lets the control element which is being identified blink and restores the normal feedback
+    /// when done.
+    fn process_control_element_identification(&mut self) {
+        let identification = match self.basics.control_element_identification.as_mut() {
+            None => return,
+            Some(i) => i,
+        };
+        let control_element = identification.control_element();
+        let value = match identification.poll(Instant::now()) {
+            None => return,
+            Some(IdentificationPollResult::Show(v)) => v,
+            Some(IdentificationPollResult::Finished) => {
+                self.basics.control_element_identification = None;
+                self.send_all_feedback();
+                return;
+            }
+        };
+        let feedback_values: Vec<_> = self
+            .collections
+            .mappings_with_virtual_targets
+            .values()
+            .filter(|m| {
+                m.feedback_is_enabled()
+                    && m.virtual_target_control_element() == Some(control_element)
+            })
+            .filter_map(|m| {
+                let feedback_value = FeedbackValue::Numeric(NumericFeedbackValue::new(
+                    Default::default(),
+                    AbsoluteValue::Continuous(value),
+                ));
+                m.feedback_given_target_value(
+                    Cow::Owned(feedback_value),
+                    FeedbackDestinations {
+                        with_projection_feedback: false,
+                        with_source_feedback: true,
+                    },
+                )
+                .map(CompoundFeedbackValue::normal)
+            })
+            .collect();
+        self.send_feedback(FeedbackReason::Identification, feedback_values);
     }
 
     /// This goes through all mappings that returned "high" feedback resolution - which they do if
@@ -640,6 +1073,9 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
     }
 
     fn process_instance_feedback_events(&mut self) {
+        let mut layer_changes = vec![];
+        let mut offset_change = None;
+        let mut needs_target_refresh = false;
         for event in self
             .basics
             .channels
@@ -647,6 +1083,19 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
             .try_iter()
             .take(FEEDBACK_TASK_BULK_SIZE)
         {
+            if let InstanceStateChanged::ActiveLayer { compartment, layer } = &event {
+                layer_changes.push((*compartment, *layer));
+            }
+            if let InstanceStateChanged::VirtualControlElementOffset { offset } = &event {
+                offset_change = Some(*offset);
+            }
+            if matches!(
+                &event,
+                InstanceStateChanged::FxParameterBankOffset { .. }
+                    | InstanceStateChanged::SpilledFolderTrack { .. }
+            ) {
+                needs_target_refresh = true;
+            }
             self.process_feedback_related_reaper_event(|mapping, target| {
                 mapping.process_change_event(
                     target,
@@ -655,17 +1104,77 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
                 )
             });
         }
+        for (compartment, layer) in layer_changes {
+            self.update_active_layer(compartment, layer);
+        }
+        if let Some(offset) = offset_change {
+            self.update_virtual_control_element_offset(offset);
+        }
+        if needs_target_refresh {
+            // Targets which address their FX parameter or track by index now resolve to other
+            // parameters or tracks.
+            self.basics
+                .channels
+                .self_normal_sender
+                .try_send(NormalMainTask::RefreshAllTargets)
+                .unwrap();
+        }
+    }
+
+    fn update_virtual_control_element_offset(&mut self, offset: u32) {
+        if offset == self.basics.virtual_control_element_offset {
+            return;
+        }
+        debug!(
+            self.basics.logger,
+            "Updating virtual control element offset to {}", offset
+        );
+        self.basics.virtual_control_element_offset = offset;
+        // Sync to real-time processor
+        self.basics
+            .channels
+            .normal_real_time_task_sender
+            .send(NormalRealTimeTask::UpdateVirtualControlElementOffset(
+                offset,
+            ))
+            .unwrap();
+        // The controller now displays different main mappings
+        self.send_all_feedback();
+    }
+
+    fn update_active_layer(&mut self, compartment: MappingCompartment, layer: LayerId) {
+        debug!(
+            self.basics.logger,
+            "Updating active layer in {} to {:?}", compartment, layer
+        );
+        // Sync to real-time processor
+        self.basics
+            .channels
+            .normal_real_time_task_sender
+            .send(NormalRealTimeTask::UpdateActiveLayer(compartment, layer))
+            .unwrap();
+        // Update
+        for m in self.collections.mappings[compartment].values_mut() {
+            m.update_active_layer(layer);
+        }
+        if compartment == MappingCompartment::ControllerMappings {
+            for m in self.collections.mappings_with_virtual_targets.values_mut() {
+                m.update_active_layer(layer);
+            }
+        }
     }
 
     fn poll_slots(&mut self) {
-        // TODO-medium This is polled on each main loop cycle. As soon as we have more than 8 slots,
-        //  We should introduce a set that contains the currently filled or playing slot numbers
+        // TODO-medium This is polled on each main loop cycle. With big clip matrices, we should
+        //  introduce a set that contains the currently filled or playing slot numbers and
         //  iterate over them only instead of all slots.
         let mut instance_state = self.basics.instance_state.borrow_mut();
-        for i in 0..CLIP_SLOT_COUNT {
+        let slot_count = instance_state.clip_matrix().slot_count();
+        for i in 0..slot_count {
             for event in instance_state.poll_slot(i).into_iter() {
                 let is_position_change = matches!(&event, ClipChangedEvent::ClipPosition(_));
                 let instance_event = InstanceStateChanged::Clip {
+                    matrix_id: None,
                     slot_index: i,
                     event,
                 };
@@ -705,6 +1214,7 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
                 }
             }
         }
+        instance_state.process_clip_follow_actions();
     }
 
     fn process_feedback_tasks(&mut self) {
@@ -736,7 +1246,7 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
                     // is always on. Switching off is not necessary since the last
                     // touched target can never be "unset".
                     let control_context = self.basics.control_context();
-                    m.refresh_target(
+                    let (target_has_changed, _) = m.refresh_target(
                         ExtendedProcessorContext::new(
                             &self.basics.context,
                             &self.collections.parameters,
@@ -744,6 +1254,9 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
                         ),
                         control_context,
                     );
+                    if target_has_changed {
+                        self.collections.target_dependency_index[compartment].invalidate();
+                    }
                     if m.has_reaper_target() && m.has_resolved_successfully() {
                         if m.feedback_is_effectively_on() {
                             // TODO-medium Is this executed too frequently and maybe
@@ -771,57 +1284,82 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
             .try_iter()
             .take(PARAMETER_TASK_BULK_SIZE)
             .collect();
-        for task in parameter_tasks {
+        // Single parameter changes are processed in one go. Otherwise automating many parameters
+        // at once would make us scan all mappings for each parameter.
+        let mut parameter_changes: SmallVec<[(u32, f32); PARAMETER_TASK_BULK_SIZE]> =
+            SmallVec::new();
+        for task in coalesce_parameter_tasks(parameter_tasks) {
             use ParameterMainTask::*;
             match task {
                 UpdateAllParameters(parameters) => {
                     self.update_all_parameters(parameters);
                 }
-                UpdateParameter { index, value } => self.update_single_parameter(index, value),
+                UpdateParameter { index, value } => parameter_changes.push((index, value)),
             }
         }
+        if !parameter_changes.is_empty() {
+            self.update_parameters(&parameter_changes);
+        }
     }
 
+    /// Updates the given parameters (index and new value) and processes the consequences for all
+    /// mappings at once.
     // https://github.com/rust-lang/rust-clippy/issues/6066
     #[allow(clippy::needless_collect)]
-    fn update_single_parameter(&mut self, index: u32, value: f32) {
-        debug!(
-            self.basics.logger,
-            "Updating parameter {} to {}...", index, value
-        );
-        // Work around REAPER's inability to notify about parameter changes in
-        // monitoring FX by simulating the notification ourselves.
-        // Then parameter learning and feedback works at least for
-        // ReaLearn monitoring FX instances, which is especially
-        // useful for conditional activation.
-        if self.basics.context.is_on_monitoring_fx_chain() {
-            let parameter = self
-                .basics
-                .context
-                .containing_fx()
-                .parameter_by_index(index);
+    fn update_parameters(&mut self, changes: &[(u32, f32)]) {
+        debug!(self.basics.logger, "Updating parameters {:?}...", changes);
+        // Contains index and previous value of each parameter
+        let mut previous_values: SmallVec<[(u32, f32); PARAMETER_TASK_BULK_SIZE]> = SmallVec::new();
+        for &(index, value) in changes {
+            // Work around REAPER's inability to notify about parameter changes in
+            // monitoring FX by simulating the notification ourselves.
+            // Then parameter learning and feedback works at least for
+            // ReaLearn monitoring FX instances, which is especially
+            // useful for conditional activation.
+            if self.basics.context.is_on_monitoring_fx_chain() {
+                let parameter = self
+                    .basics
+                    .context
+                    .containing_fx()
+                    .parameter_by_index(index);
+                self.basics
+                    .channels
+                    .additional_feedback_event_sender
+                    .try_send(
+                        AdditionalFeedbackEvent::RealearnMonitoringFxParameterValueChanged(
+                            RealearnMonitoringFxParameterValueChangedEvent {
+                                parameter,
+                                new_value: ReaperNormalizedFxParamValue::new(value as _),
+                            },
+                        ),
+                    )
+                    .unwrap();
+            }
+            // Update own value (important to do first)
+            let previous_value = self.collections.parameters[index as usize];
+            self.collections.parameters[index as usize] = value;
             self.basics
-                .channels
-                .additional_feedback_event_sender
-                .try_send(
-                    AdditionalFeedbackEvent::RealearnMonitoringFxParameterValueChanged(
-                        RealearnMonitoringFxParameterValueChangedEvent {
-                            parameter,
-                            new_value: ReaperNormalizedFxParamValue::new(value as _),
-                        },
-                    ),
-                )
-                .unwrap();
+                .event_handler
+                .handle_event(DomainEvent::UpdatedParameter { index, value });
+            if value != previous_value {
+                self.notify_parameter_changed(index, value);
+            }
+            previous_values.push((index, previous_value));
         }
-        // Update own value (important to do first)
-        let previous_value = self.collections.parameters[index as usize];
-        self.collections.parameters[index as usize] = value;
-        self.basics
-            .event_handler
-            .handle_event(DomainEvent::UpdatedParameter { index, value });
         // Mapping activation is supported for both compartments and target activation
         // might change also in non-virtual controller mappings due to dynamic targets.
-        if let Some(compartment) = MappingCompartment::by_absolute_param_index(index) {
+        for compartment in MappingCompartment::enum_iter() {
+            let compartment_changes: SmallVec<[(u32, f32); PARAMETER_TASK_BULK_SIZE]> =
+                previous_values
+                    .iter()
+                    .copied()
+                    .filter(|(index, _)| {
+                        MappingCompartment::by_absolute_param_index(*index) == Some(compartment)
+                    })
+                    .collect();
+            if compartment_changes.is_empty() {
+                continue;
+            }
             let mut changed_mappings = HashSet::new();
             let mut unused_sources = self.currently_feedback_enabled_sources(compartment, true);
             // In order to avoid a mutable borrow of mappings and an immutable borrow of
@@ -831,7 +1369,7 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
             let activation_effects: Vec<MappingActivationEffect> = self
                 .all_mappings_in_compartment(compartment)
                 .filter_map(|m| {
-                    m.check_activation_effect(&self.collections.parameters, index, previous_value)
+                    m.check_activation_effect(&self.collections.parameters, &compartment_changes)
                 })
                 .collect();
             // 2. Mapping activation: Write
@@ -864,6 +1402,9 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
                     );
                     let (target_has_changed, activation_change) =
                         m.refresh_target(context, control_context);
+                    if target_has_changed {
+                        self.collections.target_dependency_index[compartment].invalidate();
+                    }
                     if target_has_changed || activation_change.is_some() {
                         changed_mappings.insert(m.id());
                     }
@@ -884,16 +1425,27 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
                 target_activation_changes,
                 unused_sources,
                 changed_mappings.into_iter(),
-            )
+            );
         }
     }
 
     fn update_all_parameters(&mut self, parameters: Box<ParameterArray>) {
         debug!(self.basics.logger, "Updating all parameters...");
+        let previous_parameters = self.collections.parameters;
         self.collections.parameters = *parameters;
+        for (i, (value, previous_value)) in parameters
+            .iter()
+            .zip(previous_parameters.iter())
+            .enumerate()
+        {
+            if value != previous_value {
+                self.notify_parameter_changed(i as u32, *value);
+            }
+        }
         self.basics
             .event_handler
             .handle_event(DomainEvent::UpdatedAllParameters(parameters));
+        let project = self.basics.context.project_or_current_project();
         for compartment in MappingCompartment::enum_iter() {
             let mut mapping_activation_changes: Vec<ActivationChange> = vec![];
             let mut target_activation_changes: Vec<ActivationChange> = vec![];
@@ -905,7 +1457,8 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
                 compartment,
             ) {
                 if m.activation_can_be_affected_by_parameters() {
-                    if let Some(update) = m.update_activation(&self.collections.parameters) {
+                    if let Some(update) = m.update_activation(&self.collections.parameters, project)
+                    {
                         mapping_activation_changes.push(update);
                     }
                 }
@@ -918,6 +1471,9 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
                     );
                     let (has_changed, activation_change) =
                         m.refresh_target(context, control_context);
+                    if has_changed {
+                        self.collections.target_dependency_index[compartment].invalidate();
+                    }
                     if has_changed || activation_change.is_some() {
                         changed_mappings.push(m.id())
                     }
@@ -954,6 +1510,7 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
             .take(NORMAL_TASK_BULK_SIZE)
             .collect();
         let normal_task_count = normal_tasks.len();
+        let mut pending_target_refresh = PendingTargetRefresh::default();
         for task in normal_tasks {
             use NormalMainTask::*;
             match task {
@@ -962,12 +1519,16 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
                     feedback_output,
                     input_logging_enabled,
                     output_logging_enabled,
+                    idle_animation_settings,
+                    feedback_loop_suppression_enabled,
                 } => {
                     self.update_settings(
                         control_input,
                         feedback_output,
                         input_logging_enabled,
                         output_logging_enabled,
+                        idle_animation_settings,
+                        feedback_loop_suppression_enabled,
                     );
                 }
                 UpdateAllMappings(compartment, mappings) => {
@@ -976,12 +1537,31 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
                 NotifyRealearnInstanceStarted => {
                     self.process_reaper_message(&ReaperMessage::RealearnInstanceStarted);
                 }
+                UpdateInstanceHooks(hooks) => {
+                    self.instance_hooks = *hooks;
+                }
+                UpdateParameterLinking(linking) => {
+                    self.parameter_linking = *linking;
+                }
+                FireInstanceHook(event) => {
+                    self.fire_instance_hook(event);
+                }
+                RequestControllerState(events) => {
+                    self.request_controller_state(events);
+                }
                 HitTarget { id, value } => {
                     self.hit_target(id, value);
                 }
                 // This is sent on events such as track list change, FX focus etc.
                 RefreshAllTargets => {
-                    self.refresh_all_targets();
+                    pending_target_refresh.all = true;
+                }
+                // This is sent on events such as track selection or FX chain changes.
+                RefreshTargetsDependingOn(objects) => {
+                    pending_target_refresh.objects.extend(objects);
+                }
+                UpdateReaperStateActivations => {
+                    self.update_reaper_state_activations();
                 }
                 UpdateSingleMapping(compartment, mapping) => {
                     self.update_single_mapping(compartment, mapping);
@@ -992,6 +1572,15 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
                 SendAllFeedback => {
                     self.send_all_feedback();
                 }
+                Panic => {
+                    self.panic();
+                }
+                ProcessInstanceMessage(msg) => {
+                    // Messages sent as a consequence of this one must know how far it traveled.
+                    self.basics.instance_message_hop_count = Some(msg.hop_count);
+                    self.process_reaper_message(&ReaperMessage::InstanceMessage(msg));
+                    self.basics.instance_message_hop_count = None;
+                }
                 LogDebugInfo => {
                     self.log_debug_info(normal_task_count);
                 }
@@ -1019,6 +1608,10 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
                     debug!(self.basics.logger, "Return to control mode");
                     self.basics.control_mode = ControlMode::Controlling;
                 }
+                UpdateInstancePriority(priority) => {
+                    self.basics.instance_priority = priority;
+                    self.send_io_update(self.basic_io_changed_event()).unwrap();
+                }
                 UpdateControlIsGloballyEnabled(is_enabled) => {
                     self.basics.control_is_globally_enabled = is_enabled;
                     let event = IoUpdatedEvent {
@@ -1026,11 +1619,48 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
                     };
                     self.send_io_update(event).unwrap();
                 }
+                UpdateSoloedMapping(soloed_mapping) => {
+                    self.update_soloed_mapping(soloed_mapping);
+                }
+                IdentifyControlElement(control_element) => {
+                    self.basics.control_element_identification = Some(
+                        ControlElementIdentification::new(control_element, Instant::now()),
+                    );
+                }
                 UseIntegrationTestFeedbackSender(sender) => {
                     self.basics.channels.integration_test_feedback_sender = Some(sender);
                 }
             }
         }
+        if pending_target_refresh.all {
+            self.refresh_targets(None);
+        } else if !pending_target_refresh.objects.is_empty() {
+            self.refresh_targets(Some(&pending_target_refresh.objects));
+        }
+    }
+
+    fn update_soloed_mapping(&mut self, soloed_mapping: Option<QualifiedMappingId>) {
+        debug!(
+            self.basics.logger,
+            "Updating soloed mapping to {:?}", soloed_mapping
+        );
+        self.basics.soloed_mapping = soloed_mapping;
+        // Sync to real-time processor
+        self.basics
+            .channels
+            .normal_real_time_task_sender
+            .send(NormalRealTimeTask::UpdateSoloedMapping(soloed_mapping))
+            .unwrap();
+        // Update
+        for compartment in MappingCompartment::enum_iter() {
+            for m in self.collections.mappings[compartment].values_mut() {
+                m.update_soloed_mapping(soloed_mapping);
+            }
+        }
+        for m in self.collections.mappings_with_virtual_targets.values_mut() {
+            m.update_soloed_mapping(soloed_mapping);
+        }
+        self.update_on_mappings();
     }
 
     fn update_feedback_is_globally_enabled(&mut self, is_enabled: bool) {
@@ -1053,28 +1683,49 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
         self.send_io_update(event).unwrap();
     }
 
-    fn refresh_all_targets(&mut self) {
-        debug!(self.basics.logger, "Refreshing all targets...");
+    /// Refreshes the targets of all mappings if `objects` is `None`. Otherwise only refreshes
+    /// the targets of mappings which depend on one of the given tracks or FXs.
+    fn refresh_targets(&mut self, objects: Option<&HashSet<Guid>>) {
+        debug!(
+            self.basics.logger,
+            "Refreshing targets depending on {:?}...", objects
+        );
         for compartment in MappingCompartment::enum_iter() {
             let mut activation_updates: Vec<ActivationChange> = vec![];
             let mut changed_mappings = vec![];
             let mut unused_sources = self.currently_feedback_enabled_sources(compartment, false);
+            let affected_mappings = objects.map(|objects| {
+                let index = &mut self.collections.target_dependency_index[compartment];
+                if !index.is_up_to_date() {
+                    let dependencies = self.collections.mappings[compartment]
+                        .values()
+                        .map(|m| (m.id(), m.target_dependencies()));
+                    index.rebuild(dependencies);
+                }
+                index.affected_mappings(objects)
+            });
             // Mappings with virtual targets don't have to be refreshed because virtual
             // targets are always active and never change depending on circumstances.
             for m in self.collections.mappings[compartment].values_mut() {
-                let control_context = self.basics.control_context();
-                let context = ExtendedProcessorContext::new(
-                    &self.basics.context,
-                    &self.collections.parameters,
-                    control_context,
-                );
-                let (target_changed, activation_update) =
-                    m.refresh_target(context, control_context);
-                if target_changed || activation_update.is_some() {
-                    changed_mappings.push(m.id());
-                }
-                if let Some(u) = activation_update {
-                    activation_updates.push(u);
+                let is_affected = affected_mappings
+                    .as_ref()
+                    .map(|ids| ids.contains(&m.id()))
+                    .unwrap_or(true);
+                if is_affected {
+                    let control_context = self.basics.control_context();
+                    let context = ExtendedProcessorContext::new(
+                        &self.basics.context,
+                        &self.collections.parameters,
+                        control_context,
+                    );
+                    let (target_changed, activation_update) =
+                        m.refresh_target(context, control_context);
+                    if target_changed || activation_update.is_some() {
+                        changed_mappings.push(m.id());
+                    }
+                    if let Some(u) = activation_update {
+                        activation_updates.push(u);
+                    }
                 }
                 if m.feedback_is_effectively_on() {
                     // Mark source as used
@@ -1083,6 +1734,9 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
                     }
                 }
             }
+            if !changed_mappings.is_empty() {
+                self.collections.target_dependency_index[compartment].invalidate();
+            }
             if !activation_updates.is_empty() {
                 // In some cases like closing projects, it's possible that this will
                 // fail because the real-time processor is
@@ -1102,19 +1756,76 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
         self.update_on_mappings();
     }
 
+    /// Re-evaluates activation conditions which depend on REAPER state (e.g. transport).
+    fn update_reaper_state_activations(&mut self) {
+        let project = self.basics.context.project_or_current_project();
+        for compartment in MappingCompartment::enum_iter() {
+            let mut mapping_activation_changes: Vec<ActivationChange> = vec![];
+            let mut changed_mappings = vec![];
+            let mut unused_sources = self.currently_feedback_enabled_sources(compartment, true);
+            for m in all_mappings_in_compartment_mut(
+                &mut self.collections.mappings,
+                &mut self.collections.mappings_with_virtual_targets,
+                compartment,
+            ) {
+                if !m.activation_can_be_affected_by_reaper_state() {
+                    continue;
+                }
+                if let Some(update) = m.update_activation(&self.collections.parameters, project) {
+                    changed_mappings.push(m.id());
+                    mapping_activation_changes.push(update);
+                }
+            }
+            if changed_mappings.is_empty() {
+                continue;
+            }
+            for m in self.all_mappings_in_compartment(compartment) {
+                if m.feedback_is_effectively_on() {
+                    // Mark source as used
+                    if let Some(addr) = m.source().extract_feedback_address() {
+                        unused_sources.remove(&addr);
+                    }
+                }
+            }
+            self.process_mapping_updates_due_to_parameter_changes(
+                compartment,
+                mapping_activation_changes,
+                vec![],
+                unused_sources,
+                changed_mappings.into_iter(),
+            );
+        }
+    }
+
     fn update_settings(
         &mut self,
         control_input: ControlInput,
         feedback_output: Option<FeedbackOutput>,
         input_logging_enabled: bool,
         output_logging_enabled: bool,
+        idle_animation_settings: IdleAnimationSettings,
+        feedback_loop_suppression_enabled: bool,
     ) {
         self.basics.clear_last_feedback();
         self.basics.input_logging_enabled = input_logging_enabled;
         self.basics.output_logging_enabled = output_logging_enabled;
+        self.basics
+            .feedback_loop_detector
+            .borrow_mut()
+            .set_suppression_enabled(feedback_loop_suppression_enabled);
+        if self
+            .basics
+            .idle_animation
+            .update_settings(idle_animation_settings, Instant::now())
+        {
+            self.send_all_feedback();
+        }
         let released_event = self.io_released_event();
         self.basics.control_input = control_input;
         self.basics.feedback_output = feedback_output;
+        // The status only covers devices which have been unplugged while being used.
+        self.basics.io_connection_status = Default::default();
+        self.update_missing_devices();
         let changed_event = self.feedback_output_usage_might_have_changed_event();
         self.send_io_update(released_event).unwrap();
         self.send_io_update(changed_event).unwrap();
@@ -1136,14 +1847,30 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
         let mut mapping_infos: HashMap<QualifiedMappingId, MappingInfo> = HashMap::new();
         let mut unused_sources = self.currently_feedback_enabled_sources(compartment, true);
         self.collections.target_touch_dependent_mappings[compartment].clear();
+        self.collections.target_dependency_index[compartment].invalidate();
         self.collections.beat_dependent_feedback_mappings[compartment].clear();
         self.collections.milli_dependent_feedback_mappings[compartment].clear();
         self.collections.previous_target_values[compartment].clear();
         self.poll_control_mappings[compartment].clear();
+        self.throttled_target_mappings[compartment].clear();
+        let active_layer = self
+            .basics
+            .instance_state
+            .borrow()
+            .active_layer(compartment);
+        let midi_control_input = self.basics.midi_control_input_device();
+        let midi_feedback_output = self.basics.midi_feedback_output_device();
         // Refresh and splinter real-time mappings
         let real_time_mappings = mappings
             .iter_mut()
             .map(|m| {
+                m.update_soloed_mapping(self.basics.soloed_mapping);
+                m.update_active_layer(active_layer);
+                m.update_missing_devices(
+                    &self.basics.io_connection_status,
+                    midi_control_input,
+                    midi_feedback_output,
+                );
                 mappings_by_group
                     .entry(m.group_id())
                     .or_default()
@@ -1168,7 +1895,7 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
                     self.collections.target_touch_dependent_mappings[compartment].insert(m.id());
                 }
                 let feedback_resolution = m.feedback_resolution();
-                if feedback_resolution == Some(FeedbackResolution::Beat) {
+                if m.wants_beat_change_events() {
                     self.collections.beat_dependent_feedback_mappings[compartment].insert(m.id());
                 }
                 if feedback_resolution == Some(FeedbackResolution::High) {
@@ -1177,6 +1904,9 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
                 if m.wants_to_be_polled_for_control() {
                     self.poll_control_mappings[compartment].insert(m.id());
                 }
+                if m.throttles_target_hits() {
+                    self.throttled_target_mappings[compartment].insert(m.id());
+                }
                 m.splinter_real_time_mapping()
             })
             .collect();
@@ -1196,6 +1926,13 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
         } else {
             self.collections.mappings[compartment] = mapping_tuples.collect();
         }
+        #[cfg(feature = "realearn-meter")]
+        {
+            let mappings = &self.collections.mappings;
+            self.control_latencies.retain_mappings(|id| {
+                id.compartment != compartment || mappings[compartment].contains_key(&id.id)
+            });
+        }
         // Sync to real-time processor
         self.basics
             .channels
@@ -1207,12 +1944,13 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
             .unwrap();
         // Important to send IO event first ...
         self.notify_feedback_dev_usage_might_have_changed(compartment);
-        // ... and then mapping update. Otherwise, if this is an upper-floor instance
+        // ... and then mapping update. Otherwise, if this is a higher-priority instance
         // clearing all mappings, other instances won't see yet that they are actually
         // allowed to take over sources! Which might delay the reactivation of
-        // lower-floor instances.
+        // lower-priority instances.
         self.handle_feedback_after_having_updated_all_mappings(compartment, unused_sources);
         self.update_on_mappings();
+        self.update_suspended_mappings();
     }
 
     fn process_normal_tasks_from_real_time_processor(&mut self) {
@@ -1258,16 +1996,30 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
     }
 
     fn basic_io_changed_event(&self) -> IoUpdatedEvent {
-        let active = self.collections.mappings[MappingCompartment::MainMappings]
+        let main_mappings = &self.collections.mappings[MappingCompartment::MainMappings];
+        let active = main_mappings.values().any(|m| m.is_effectively_on());
+        let control_input_overrides = main_mappings
+            .values()
+            .filter(|m| m.is_effectively_on())
+            .filter_map(|m| m.options().midi_input_device)
+            .map(DeviceControlInput::Midi)
+            .collect();
+        let feedback_output_overrides = main_mappings
             .values()
-            .any(|m| m.is_effectively_on());
+            .filter(|m| m.is_effectively_on())
+            .filter_map(|m| m.options().midi_output_device)
+            .map(DeviceFeedbackOutput::Midi)
+            .collect();
         IoUpdatedEvent {
             instance_id: self.basics.instance_id,
             control_input: self.basics.control_input.device_input(),
+            control_input_overrides,
             control_input_used: self.basics.control_is_globally_enabled && active,
             feedback_output: self.basics.feedback_output.and_then(|o| o.device_output()),
+            feedback_output_overrides,
             feedback_output_used: self.basics.feedback_is_globally_enabled && active,
             feedback_output_usage_might_have_changed: false,
+            instance_priority: self.basics.instance_priority,
         }
     }
 
@@ -1279,7 +2031,9 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
 
     fn io_released_event(&self) -> IoUpdatedEvent {
         IoUpdatedEvent {
+            control_input_overrides: Default::default(),
             control_input_used: false,
+            feedback_output_overrides: Default::default(),
             feedback_output_used: false,
             ..self.feedback_output_usage_might_have_changed_event()
         }
@@ -1306,6 +2060,47 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
         }
     }
 
+    /// Gives linked parameters of other instances the chance to follow.
+    fn notify_parameter_changed(&self, index: u32, value: f32) {
+        let event = ParameterChangedEvent {
+            instance_id: self.basics.instance_id,
+            session_id: self.parameter_linking.own_session_id().to_owned(),
+            index,
+            value,
+        };
+        // If the queue is full, linked parameters will just not follow this time.
+        let _ = self
+            .basics
+            .channels
+            .instance_orchestration_event_sender
+            .try_send(InstanceOrchestrationEvent::ParameterChanged(event));
+    }
+
+    /// Sets the parameters of this instance which are linked to the changed parameter of another
+    /// instance.
+    pub fn follow_linked_parameter(&self, event: &ParameterChangedEvent) {
+        for (index, value) in
+            self.parameter_linking
+                .followers(&event.session_id, event.index, event.value)
+        {
+            let result = self
+                .basics
+                .context
+                .containing_fx()
+                .parameter_by_index(index)
+                .set_reaper_normalized_value(ReaperNormalizedFxParamValue::new(value as _));
+            if result.is_err() {
+                debug!(
+                    self.basics.logger,
+                    "Couldn't let parameter {} follow parameter {} of session {}",
+                    index,
+                    event.index,
+                    event.session_id
+                );
+            }
+        }
+    }
+
     fn send_io_update(
         &self,
         event: IoUpdatedEvent,
@@ -1382,6 +2177,19 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
     }
 
     pub fn process_control_surface_change_event(&self, event: &ChangeEvent) {
+        if ReaperStateCondition::is_potential_change_event(event)
+            && self
+                .all_mappings()
+                .any(|m| m.activation_can_be_affected_by_reaper_state())
+        {
+            // Mapping activation depending on REAPER state. Same reentrancy consideration as
+            // below, so we re-evaluate in the next main loop cycle.
+            self.basics
+                .channels
+                .self_normal_sender
+                .try_send(NormalMainTask::UpdateReaperStateActivations)
+                .unwrap();
+        }
         if ReaperTarget::is_potential_change_event(event) {
             // Handle dynamic target changes and target activation depending on REAPER state.
             //
@@ -1400,10 +2208,18 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
             // We don't have mutable access to self here (for good reentrancy reasons) so we
             // do the refresh in the next main loop cycle. This is what we always did, also when
             // this was still based on Rx!
+            //
+            // If the event concerns a particular track or FX (e.g. track selection), only those
+            // mappings need to be refreshed whose targets depend on it. Otherwise we would
+            // refresh hundreds of mappings whenever another track is selected.
+            let task = match ReaperTarget::objects_affected_by_change_event(event) {
+                None => NormalMainTask::RefreshAllTargets,
+                Some(objects) => NormalMainTask::RefreshTargetsDependingOn(objects),
+            };
             self.basics
                 .channels
                 .self_normal_sender
-                .try_send(NormalMainTask::RefreshAllTargets)
+                .try_send(task)
                 .unwrap();
         }
         self.process_feedback_related_reaper_event(|mapping, target| {
@@ -1461,6 +2277,82 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
         self.basics.control_input == ControlInput::Osc(*device_id)
     }
 
+    /// Suspends or resumes mappings when the MIDI devices they use are unplugged or plugged in
+    /// again.
+    pub fn process_device_changes(&mut self, changes: &DeviceChanges) {
+        let (used_inputs, used_outputs) = self.used_midi_devices();
+        let mut status = self.basics.io_connection_status.clone();
+        let reconnected = status.update(changes, &used_inputs, &used_outputs);
+        if status == self.basics.io_connection_status {
+            return;
+        }
+        debug!(
+            self.basics.logger,
+            "Device connection status changed: {:?}", status
+        );
+        self.basics.io_connection_status = status;
+        self.update_missing_devices();
+        // The real-time processor needs to know about the suspended mappings, too. After
+        // reconnection, the full resync also makes sure that it doesn't hold stale state from
+        // the time the device was missing.
+        self.basics
+            .event_handler
+            .handle_event(DomainEvent::FullResyncRequested);
+        if reconnected {
+            self.send_all_feedback();
+        }
+    }
+
+    /// Returns the MIDI devices used by the instance itself and by single mappings.
+    fn used_midi_devices(&self) -> (HashSet<MidiInputDeviceId>, HashSet<MidiOutputDeviceId>) {
+        let mut inputs: HashSet<_> = self
+            .basics
+            .midi_control_input_device()
+            .into_iter()
+            .collect();
+        let mut outputs: HashSet<_> = self
+            .basics
+            .midi_feedback_output_device()
+            .into_iter()
+            .collect();
+        for m in self.all_mappings() {
+            inputs.extend(m.options().midi_input_device);
+            outputs.extend(m.options().midi_output_device);
+        }
+        (inputs, outputs)
+    }
+
+    /// Applies the current device connection status to all mappings.
+    fn update_missing_devices(&mut self) {
+        let midi_control_input = self.basics.midi_control_input_device();
+        let midi_feedback_output = self.basics.midi_feedback_output_device();
+        let status = &self.basics.io_connection_status;
+        for compartment in MappingCompartment::enum_iter() {
+            for m in self.collections.mappings[compartment].values_mut() {
+                m.update_missing_devices(status, midi_control_input, midi_feedback_output);
+            }
+        }
+        for m in self.collections.mappings_with_virtual_targets.values_mut() {
+            m.update_missing_devices(status, midi_control_input, midi_feedback_output);
+        }
+        self.update_suspended_mappings();
+    }
+
+    /// Makes the device connection status including suspended mappings available to the layers
+    /// above.
+    fn update_suspended_mappings(&mut self) {
+        let suspended_mappings: HashSet<_> = self
+            .all_mappings()
+            .filter(|m| m.is_suspended_by_missing_device())
+            .map(|m| m.qualified_id())
+            .collect();
+        self.basics.io_connection_status.suspended_mappings = suspended_mappings;
+        let mut instance_state = self.basics.instance_state.borrow_mut();
+        if instance_state.io_connection_status() != &self.basics.io_connection_status {
+            instance_state.set_io_connection_status(self.basics.io_connection_status.clone());
+        }
+    }
+
     pub fn process_reaper_message(&mut self, msg: &ReaperMessage) {
         if self.basics.control_mode != ControlMode::Controlling {
             return;
@@ -1518,6 +2410,7 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
         match self.basics.control_mode {
             ControlMode::Controlling => {
                 if self.control_is_effectively_enabled() {
+                    self.notify_control_activity();
                     let msg = MainSourceMessage::Osc(msg);
                     let results = self
                         .basics
@@ -1575,6 +2468,19 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
                 } else {
                     continue;
                 };
+                if m.take_osc_captures_change() {
+                    // The target might depend on the captured OSC address segments.
+                    let control_context = self.basics.control_context();
+                    let context = ExtendedProcessorContext::new(
+                        &self.basics.context,
+                        &self.collections.parameters,
+                        control_context,
+                    );
+                    let (target_has_changed, _) = m.refresh_target(context, control_context);
+                    if target_has_changed {
+                        self.collections.target_dependency_index[compartment].invalidate();
+                    }
+                }
                 let options = ControlOptions {
                     enforce_target_refresh,
                     ..Default::default()
@@ -1726,6 +2632,63 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
         self.send_feedback(FeedbackReason::Normal, self.feedback_all());
     }
 
+    /// The ultimate recovery button, e.g. when a controller gets stuck lights or notes.
+    fn panic(&self) {
+        debug!(self.basics.logger, "Panic!");
+        self.clear_all_feedback_allowing_source_takeover();
+        self.send_midi_panic();
+        let result = self
+            .basics
+            .instance_state
+            .borrow_mut()
+            .modify_clip_matrix(|matrix| Ok(matrix.stop_all(SlotStopQuantization::Immediately)));
+        if let Err(e) = result {
+            debug!(self.basics.logger, "Couldn't stop clips: {}", e);
+        }
+        self.send_all_feedback();
+    }
+
+    /// Sends "All notes off" and "Reset all controllers" to the feedback output and to the output
+    /// devices of mappings which send their feedback somewhere else.
+    fn send_midi_panic(&self) {
+        let mut dev_ids: HashSet<_> = self
+            .all_mappings()
+            .filter_map(|m| m.options().midi_output_device)
+            .collect();
+        match self.basics.feedback_output {
+            Some(FeedbackOutput::Midi(MidiDestination::Device(dev_id))) => {
+                dev_ids.insert(dev_id);
+            }
+            Some(FeedbackOutput::Midi(MidiDestination::FxOutput)) => {
+                // Only the real-time processor can write to the FX output.
+                for msg in create_midi_panic_messages() {
+                    self.basics
+                        .channels
+                        .feedback_real_time_task_sender
+                        .send(FeedbackRealTimeTask::FxOutputFeedback(
+                            MidiSourceValue::Plain(msg),
+                        ))
+                        .unwrap();
+                }
+            }
+            _ => {}
+        }
+        for dev_id in dev_ids {
+            if self
+                .basics
+                .io_connection_status
+                .midi_output_device_is_missing(dev_id)
+            {
+                continue;
+            }
+            self.basics.control_context().send_raw_midi(
+                OutputReason::System,
+                dev_id,
+                create_midi_panic_events(),
+            );
+        }
+    }
+
     fn feedback_all(&self) -> Vec<CompoundFeedbackValue> {
         // Virtual targets don't cause feedback themselves
         self.all_mappings_without_virtual_targets()
@@ -1781,6 +2744,8 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
 
     fn follow_maybe_virtual_mapping<'a>(&'a self, m: &'a MainMapping) -> Option<&'a MainMapping> {
         if let Some(control_element) = m.virtual_target_control_element() {
+            let control_element =
+                control_element.with_offset(self.basics.virtual_control_element_offset);
             self.collections.mappings[MappingCompartment::MainMappings]
                 .values()
                 .find(|m| {
@@ -1792,28 +2757,20 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
         }
     }
 
-    pub fn handle_change_of_some_upper_floor_instance(
-        &self,
-        feedback_output: DeviceFeedbackOutput,
-    ) {
+    /// Called when this instance gained or lost ownership of its feedback output because another
+    /// instance claimed or released it (or changed its priority).
+    pub fn handle_change_of_feedback_output_ownership(&self) {
         self.update_on_mappings();
-        if self
-            .basics
-            .feedback_output
-            .and_then(FeedbackOutput::device_output)
-            == Some(feedback_output)
-        {
-            if self.basics.instance_feedback_is_effectively_enabled() {
-                debug!(self.basics.logger, "Reactivating instance...");
-                // For this to really work reliably (eventual feedback consistency), it was
-                // necessary to let the direct MIDI device feedback process in the global
-                // *audio hook*, not in the real-time processor. Because there's only one audio
-                // hook can guarantee a deterministic feedback send order.
-                self.send_all_feedback();
-            } else {
-                debug!(self.basics.logger, "Cancelling instance...");
-                self.send_feedback(FeedbackReason::SuspendInstance, self.feedback_all_zero());
-            }
+        if self.basics.instance_feedback_is_effectively_enabled() {
+            debug!(self.basics.logger, "Reactivating instance...");
+            // For this to really work reliably (eventual feedback consistency), it was
+            // necessary to let the direct MIDI device feedback process in the global
+            // *audio hook*, not in the real-time processor. Because there's only one audio
+            // hook can guarantee a deterministic feedback send order.
+            self.send_all_feedback();
+        } else {
+            debug!(self.basics.logger, "Cancelling instance...");
+            self.send_feedback(FeedbackReason::SuspendInstance, self.feedback_all_zero());
         }
     }
 
@@ -1992,6 +2949,18 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
         );
         self.basics.clear_last_feedback();
         // Refresh
+        mapping.update_soloed_mapping(self.basics.soloed_mapping);
+        mapping.update_active_layer(
+            self.basics
+                .instance_state
+                .borrow()
+                .active_layer(compartment),
+        );
+        mapping.update_missing_devices(
+            &self.basics.io_connection_status,
+            self.basics.midi_control_input_device(),
+            self.basics.midi_feedback_output_device(),
+        );
         let control_context = self.basics.control_context();
         mapping.init_target_and_activation(
             ExtendedProcessorContext::new(
@@ -2025,6 +2994,7 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
         self.update_map_entries(compartment, *mapping);
         self.send_diff_feedback(diff_feedback);
         self.update_single_mapping_on_state(id);
+        self.update_suspended_mappings();
     }
 
     fn update_persistent_mapping_processing_state(
@@ -2140,6 +3110,7 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
     }
 
     fn update_map_entries(&mut self, compartment: MappingCompartment, mapping: MainMapping) {
+        self.collections.target_dependency_index[compartment].invalidate();
         if mapping.needs_refresh_when_target_touched() {
             self.collections.target_touch_dependent_mappings[compartment].insert(mapping.id());
         } else {
@@ -2147,7 +3118,7 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
                 .shift_remove(&mapping.id());
         }
         let influence = mapping.feedback_resolution();
-        if influence == Some(FeedbackResolution::Beat) {
+        if mapping.wants_beat_change_events() {
             self.collections.beat_dependent_feedback_mappings[compartment].insert(mapping.id());
         } else {
             self.collections.beat_dependent_feedback_mappings[compartment]
@@ -2165,6 +3136,11 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
         } else {
             self.poll_control_mappings[compartment].shift_remove(&mapping.id());
         }
+        if mapping.throttles_target_hits() {
+            self.throttled_target_mappings[compartment].insert(mapping.id());
+        } else {
+            self.throttled_target_mappings[compartment].shift_remove(&mapping.id());
+        }
         let relevant_map = if mapping.has_virtual_target() {
             self.collections.mappings[compartment].shift_remove(&mapping.id());
             &mut self.collections.mappings_with_virtual_targets
@@ -2177,6 +3153,76 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
         relevant_map.insert(mapping.id(), mapping);
     }
 
+    fn fire_instance_hook(&mut self, event: InstanceHookEvent) {
+        debug!(self.basics.logger, "Firing instance hook {:?}...", event);
+        // Cloning is cheap enough, hooks are fired rarely.
+        let actions = self.instance_hooks.actions(event).to_vec();
+        for action in actions {
+            if let Err(e) = self.execute_instance_hook_action(action) {
+                debug!(
+                    self.basics.logger,
+                    "Couldn't execute action of instance hook {:?}: {}", event, e
+                );
+            }
+        }
+    }
+
+    fn execute_instance_hook_action(
+        &mut self,
+        action: InstanceHookAction,
+    ) -> Result<(), &'static str> {
+        use InstanceHookAction::*;
+        match action {
+            SendMidi(events) => {
+                let dev_id = match self.basics.feedback_output {
+                    Some(FeedbackOutput::Midi(MidiDestination::Device(dev_id))) => dev_id,
+                    _ => return Err("feedback output is not a MIDI device"),
+                };
+                self.basics.control_context().send_raw_midi(
+                    OutputReason::Lifecycle,
+                    dev_id,
+                    events,
+                );
+            }
+            SendOsc(msg) => {
+                let dev_id = match self.basics.feedback_output {
+                    Some(FeedbackOutput::Osc(dev_id)) => dev_id,
+                    _ => return Err("feedback output is not an OSC device"),
+                };
+                if self.basics.output_logging_enabled {
+                    log_output(
+                        &self.basics.instance_id,
+                        OutputReason::Lifecycle,
+                        format_osc_message(&msg),
+                    );
+                }
+                self.basics
+                    .channels
+                    .osc_feedback_task_sender
+                    .try_send(OscFeedbackTask::new(dev_id, msg))
+                    .unwrap();
+            }
+            SetParameter { index, value } => {
+                self.basics
+                    .context
+                    .containing_fx()
+                    .parameter_by_index(index)
+                    .set_reaper_normalized_value(ReaperNormalizedFxParamValue::new(value as _))
+                    .map_err(|_| "couldn't set parameter value")?;
+            }
+            TriggerMapping { id, value } => {
+                self.control(
+                    id.compartment,
+                    id.id,
+                    ControlValue::AbsoluteContinuous(value),
+                    ControlOptions::default(),
+                    Instant::now(),
+                )?;
+            }
+        }
+        Ok(())
+    }
+
     fn hit_target(&mut self, id: QualifiedMappingId, value: AbsoluteValue) {
         let control_result = if let Some(m) =
             self.collections.mappings[id.compartment].get_mut(&id.id)
@@ -2247,6 +3293,15 @@ pub enum NormalMainTask {
     },
     /// Invokes the "ReaLearn instance started" source.
     NotifyRealearnInstanceStarted,
+    /// Replaces the instance lifecycle hooks.
+    UpdateInstanceHooks(Box<InstanceHooks>),
+    /// Replaces the links of compartment parameters to parameters of other instances.
+    UpdateParameterLinking(Box<ParameterLinking>),
+    /// Executes the actions attached to the given instance lifecycle event.
+    FireInstanceHook(InstanceHookEvent),
+    /// Sends the given state request messages to the feedback output and uses the responses for
+    /// aligning pickup mappings with the current positions of the control elements.
+    RequestControllerState(Vec<RawMidiEvent>),
     /// Instructs the main processor to hit the target directly.
     ///
     /// This doesn't invoke group interaction because it's meant to totally skip the mode.
@@ -2255,15 +3310,33 @@ pub enum NormalMainTask {
         value: AbsoluteValue,
     },
     RefreshAllTargets,
+    /// Refreshes only the targets of mappings which depend on one of the given tracks or FXs
+    /// (identified by GUID).
+    RefreshTargetsDependingOn(Vec<Guid>),
+    UpdateReaperStateActivations,
     UpdateSettings {
         control_input: ControlInput,
         feedback_output: Option<FeedbackOutput>,
         input_logging_enabled: bool,
         output_logging_enabled: bool,
+        idle_animation_settings: IdleAnimationSettings,
+        feedback_loop_suppression_enabled: bool,
     },
+    /// Decides which instance owns a control input or feedback output used by multiple instances.
+    UpdateInstancePriority(InstancePriority),
     UpdateControlIsGloballyEnabled(bool),
     UpdateFeedbackIsGloballyEnabled(bool),
+    /// Mutes control processing of all other mappings in the compartment of the given mapping
+    /// (or unmutes everything if `None`).
+    UpdateSoloedMapping(Option<QualifiedMappingId>),
+    /// Lets the LED of the given control element blink for a moment.
+    IdentifyControlElement(VirtualControlElement),
     SendAllFeedback,
+    /// Clears all feedback, resets the MIDI feedback output device, stops all clips and sends
+    /// fresh feedback.
+    Panic,
+    /// Message sent from another instance via an "Invoke instance" target.
+    ProcessInstanceMessage(InstanceMessage),
     LogDebugInfo,
     LogMapping(MappingCompartment, MappingId),
     StartLearnSource {
@@ -2301,6 +3374,64 @@ pub enum ParameterMainTask {
     UpdateAllParameters(Box<ParameterArray>),
 }
 
+/// Reduces the given parameter tasks to the ones which make a difference in the end.
+///
+/// Host automation can change the same parameter many times within one main loop cycle. Each
+/// update potentially re-evaluates the activation of all mappings, so we only process the latest
+/// value of each parameter. Everything before a complete parameter update is obsolete anyway.
+fn coalesce_parameter_tasks(
+    tasks: SmallVec<[ParameterMainTask; PARAMETER_TASK_BULK_SIZE]>,
+) -> SmallVec<[ParameterMainTask; PARAMETER_TASK_BULK_SIZE]> {
+    let mut coalesced: SmallVec<[ParameterMainTask; PARAMETER_TASK_BULK_SIZE]> = SmallVec::new();
+    for task in tasks {
+        use ParameterMainTask::*;
+        match task {
+            UpdateAllParameters(_) => {
+                coalesced.clear();
+                coalesced.push(task);
+            }
+            UpdateParameter { index, value } => {
+                let existing = coalesced.iter_mut().find_map(|t| match t {
+                    UpdateParameter { index: i, value: v } if *i == index => Some(v),
+                    _ => None,
+                });
+                if let Some(existing_value) = existing {
+                    *existing_value = value;
+                } else {
+                    coalesced.push(task);
+                }
+            }
+        }
+    }
+    coalesced
+}
+
+/// Creates "All notes off" and "Reset all controllers" messages for all MIDI channels.
+fn create_midi_panic_messages() -> impl Iterator<Item = RawShortMessage> {
+    const RESET_ALL_CONTROLLERS: u8 = 121;
+    const ALL_NOTES_OFF: u8 = 123;
+    (0..16u8).flat_map(|channel| {
+        [ALL_NOTES_OFF, RESET_ALL_CONTROLLERS]
+            .iter()
+            .map(move |cc| {
+                RawShortMessage::control_change(
+                    Channel::new(channel),
+                    ControllerNumber::new(*cc),
+                    U7::MIN,
+                )
+            })
+    })
+}
+
+fn create_midi_panic_events() -> Vec<RawMidiEvent> {
+    create_midi_panic_messages()
+        .map(|msg| {
+            let (status, data_1, data_2) = msg.to_bytes();
+            RawMidiEvent::try_from_slice(0, &[status, data_1.get(), data_2.get()]).unwrap()
+        })
+        .collect()
+}
+
 /// A feedback-related task (which is potentially sent very frequently).
 #[derive(Debug)]
 pub enum FeedbackMainTask {
@@ -2316,6 +3447,8 @@ pub enum ControlMainTask {
         mapping_id: MappingId,
         value: ControlValue,
         options: ControlOptions,
+        /// When the real-time processor forwarded the control input (right after matching it).
+        received_at: Instant,
     },
     LogControlInput {
         value: MidiSourceValue<'static, RawShortMessage>,
@@ -2327,6 +3460,10 @@ pub enum ControlMainTask {
     LogTargetOutput {
         event: Box<RawMidiEvent>,
     },
+    /// MMC sources are REAPER sources, so matching happens in the main processor.
+    ProcessMmc {
+        msg: MmcMessage,
+    },
 }
 
 pub enum OwnedIncomingMidiMessage {
@@ -2373,9 +3510,9 @@ enum FeedbackReason {
     /// letting other instances take over (e.g. when removing instance completely). Only needs to
     /// be processed when feedback enabled.
     ClearAllPreventingSourceTakeover,
-    /// When a lower-floor ReaLearn instance is cancelled by an upper-floor one. Must be processed
-    /// even if feedback is effectively disabled (because sent at a time when the lower-floor
-    /// instance is covered by the higher-floor instance already).
+    /// When a ReaLearn instance is cancelled by one with a higher priority. Must be processed
+    /// even if feedback is effectively disabled (because sent at a time when the lower-priority
+    /// instance is covered by the higher-priority instance already).
     SuspendInstance,
     /// Normal feedback scenarios.
     Normal,
@@ -2383,6 +3520,10 @@ enum FeedbackReason {
     TakeOverSource,
     /// When no ReaLearn instance has taken over a source and now it's finally being switched off.
     FinallySwitchOffSource,
+    /// When rendering a frame of the idle animation.
+    IdleAnimation,
+    /// When letting a control element blink in order to identify it.
+    Identification,
 }
 
 impl FeedbackReason {
@@ -2406,13 +3547,68 @@ impl FeedbackReason {
 }
 
 impl<EH: DomainEventHandler> Basics<EH> {
+    /// Checks if the given control input is an echo of our own feedback which keeps coming back.
+    ///
+    /// Emits a diagnostic event when detecting a new loop.
+    fn control_is_blocked_by_feedback_loop(
+        &self,
+        m: &MainMapping,
+        control_value: ControlValue,
+    ) -> bool {
+        let now = Instant::now();
+        let mut detector = self.feedback_loop_detector.borrow_mut();
+        if !detector.is_relevant(now) {
+            return false;
+        }
+        let address = match m.source().extract_feedback_address() {
+            None => return false,
+            Some(a) => a,
+        };
+        let result = detector.check_control(&address, now, |echo| {
+            m.source().control(echo.as_incoming_source_value()) == Some(control_value)
+        });
+        match result {
+            FeedbackLoopCheckResult::Pass => false,
+            FeedbackLoopCheckResult::Suppressed => true,
+            FeedbackLoopCheckResult::LoopDetected { suppressed } => {
+                // Release borrow before handing control to the event handler (reentrancy).
+                drop(detector);
+                self.event_handler
+                    .handle_event(DomainEvent::FeedbackLoopDetected(
+                        FeedbackLoopDetectedEvent {
+                            compartment: m.compartment(),
+                            mapping_id: m.id(),
+                            suppressed,
+                        },
+                    ));
+                suppressed
+            }
+        }
+    }
+
+    pub fn midi_control_input_device(&self) -> Option<MidiInputDeviceId> {
+        match self.control_input {
+            ControlInput::Midi(MidiControlInput::Device(id)) => Some(id),
+            _ => None,
+        }
+    }
+
+    pub fn midi_feedback_output_device(&self) -> Option<MidiOutputDeviceId> {
+        match self.feedback_output {
+            Some(FeedbackOutput::Midi(MidiDestination::Device(id))) => Some(id),
+            _ => None,
+        }
+    }
+
     pub fn clear_last_feedback(&self) {
         self.last_feedback_checksum_by_address.borrow_mut().clear();
+        self.feedback_deadband_memory.borrow_mut().clear();
     }
 
     pub fn control_context(&self) -> ControlContext {
         ControlContext {
             feedback_audio_hook_task_sender: &self.channels.feedback_audio_hook_task_sender,
+            normal_real_time_task_sender: &self.channels.normal_real_time_task_sender,
             osc_feedback_task_sender: &self.channels.osc_feedback_task_sender,
             feedback_output: self.feedback_output,
             instance_container: self.instance_container,
@@ -2420,6 +3616,7 @@ impl<EH: DomainEventHandler> Basics<EH> {
             instance_id: &self.instance_id,
             output_logging_enabled: self.output_logging_enabled,
             processor_context: &self.context,
+            instance_message_hop_count: self.instance_message_hop_count,
         }
     }
 
@@ -2540,7 +3737,11 @@ impl<EH: DomainEventHandler> Basics<EH> {
         for other_mapping in other_mappings {
             let other_control_result = f(other_mapping, self, &collections.parameters);
             if let Some(new_value) = other_control_result.new_target_value {
-                self.notify_target_value_changed(other_mapping, new_value);
+                self.notify_target_value_changed(
+                    other_mapping,
+                    new_value,
+                    TargetValueChangeOrigin::GroupInteraction,
+                );
             }
             self.send_feedback(
                 &collections.mappings_with_virtual_targets,
@@ -2627,18 +3828,31 @@ impl<EH: DomainEventHandler> Basics<EH> {
                 FeedbackReason::Normal,
                 feedback_value,
             );
-            self.notify_target_value_changed(m, new_value);
+            // If ReaLearn itself has controlled the target just a moment ago, this is most likely
+            // just the echo of it.
+            let origin = if m.is_echo() {
+                TargetValueChangeOrigin::Control
+            } else {
+                TargetValueChangeOrigin::External
+            };
+            self.notify_target_value_changed(m, new_value, origin);
         }
     }
 
     /// Inform session, e.g. for UI updates
-    fn notify_target_value_changed(&self, m: &MainMapping, new_value: AbsoluteValue) {
+    fn notify_target_value_changed(
+        &self,
+        m: &MainMapping,
+        new_value: AbsoluteValue,
+        origin: TargetValueChangeOrigin,
+    ) {
         self.event_handler
             .handle_event(DomainEvent::TargetValueChanged(TargetValueChangedEvent {
                 compartment: m.compartment(),
                 mapping_id: m.id(),
                 targets: m.targets(),
                 new_value,
+                origin,
             }));
     }
 
@@ -2660,6 +3874,8 @@ impl<EH: DomainEventHandler> Basics<EH> {
             .filter(|m| m.control_is_effectively_on())
             .flat_map(|m| {
                 if let Some(virtual_source_value) = m.control_virtualizing(msg) {
+                    let virtual_source_value =
+                        virtual_source_value.with_offset(self.virtual_control_element_offset);
                     self.event_handler
                         .notify_mapping_matched(MappingCompartment::ControllerMappings, m.id());
                     self.process_main_mappings_with_virtual_sources(
@@ -2713,6 +3929,17 @@ impl<EH: DomainEventHandler> Basics<EH> {
                     destinations,
                     value,
                 } => {
+                    // Main mappings with virtual sources check for loops using the virtual
+                    // address.
+                    if destinations.with_source_feedback {
+                        if let Some(echo) = FeedbackEcho::from_virtual_feedback_value(&value) {
+                            self.feedback_loop_detector.borrow_mut().feedback_sent(
+                                CompoundMappingSourceAddress::Virtual(value.control_element()),
+                                echo,
+                                Instant::now(),
+                            );
+                        }
+                    }
                     // At this point we still include controller mappings for which feedback
                     // is explicitly not enabled (not supported by controller) in order to
                     // support at least projection feedback (#414)!
@@ -2720,7 +3947,10 @@ impl<EH: DomainEventHandler> Basics<EH> {
                     for m in mappings_with_virtual_targets.values() {
                         // Should always be true.
                         if let Some(t) = m.virtual_target() {
-                            if t.control_element() == value.control_element() {
+                            if t.control_element()
+                                .with_offset(self.virtual_control_element_offset)
+                                == value.control_element()
+                            {
                                 // Virtual source matched virtual target. The following method
                                 // will always produce real target values (because controller
                                 // mappings can't have virtual sources).
@@ -2765,16 +3995,31 @@ impl<EH: DomainEventHandler> Basics<EH> {
         feedback_reason: FeedbackReason,
         source_feedback_value: SourceFeedbackValue,
         is_feedback_after_control: bool,
+        deadband: Option<FeedbackDeadband>,
     ) {
-        // Block duplicates.
         // Extracting a feedback address is not super cheap for OSC and MIDI Raw because it has to
         // clone the address string. On the other hand, address strings are not large, so what.
         if let Some(address) = source_feedback_value.extract_address() {
+            // Block values within the deadband (e.g. to prevent motorized faders from chattering).
+            if self.feedback_deadband_memory.borrow_mut().blocks(
+                address.clone(),
+                deadband,
+                is_feedback_after_control,
+            ) {
+                trace!(
+                    self.logger,
+                    "Block feedback because within deadband (reason: {:?}): {:?}",
+                    feedback_reason,
+                    source_feedback_value
+                );
+                return;
+            }
+            // Block duplicates.
             let checksum = FeedbackChecksum::from_value(&source_feedback_value);
             let previous_checksum = self
                 .last_feedback_checksum_by_address
                 .borrow_mut()
-                .insert(address, checksum);
+                .insert(address.clone(), checksum);
             if !is_feedback_after_control && Some(checksum) == previous_checksum {
                 trace!(
                     self.logger,
@@ -2784,6 +4029,13 @@ impl<EH: DomainEventHandler> Basics<EH> {
                 );
                 return;
             }
+            if let Some(echo) = FeedbackEcho::from_source_feedback_value(&source_feedback_value) {
+                self.feedback_loop_detector.borrow_mut().feedback_sent(
+                    address,
+                    echo,
+                    Instant::now(),
+                );
+            }
         }
         trace!(
             self.logger,
@@ -2834,6 +4086,20 @@ impl<EH: DomainEventHandler> Basics<EH> {
                                 .send(FeedbackAudioHookTask::MidiDeviceFeedback(dev_id, v))
                                 .unwrap();
                         }
+                        MidiDestination::RtpMidi(dev_id) => {
+                            // Same reasoning as above. The audio hook passes the messages on to
+                            // the RTP-MIDI thread.
+                            if self.output_logging_enabled {
+                                log_feedback_output(
+                                    &self.instance_id,
+                                    format_midi_source_value(&v),
+                                );
+                            }
+                            self.channels
+                                .feedback_audio_hook_task_sender
+                                .send(FeedbackAudioHookTask::RtpMidiDeviceFeedback(dev_id, v))
+                                .unwrap();
+                        }
                     }
                 }
                 (SourceFeedbackValue::Osc(msg), FeedbackOutput::Osc(dev_id)) => {
@@ -2856,8 +4122,25 @@ impl<EH: DomainEventHandler> Basics<EH> {
         feedback_value: RealFeedbackValue,
         is_feedback_after_control: bool,
     ) {
-        if feedback_reason.is_always_allowed() || self.instance_feedback_is_effectively_enabled() {
-            if let Some(feedback_output) = self.feedback_output {
+        // While the idle animation is running, it owns the control elements. Normal feedback
+        // will be restored as soon as the animation stops.
+        let blocked_by_idle_animation =
+            feedback_reason == FeedbackReason::Normal && self.idle_animation.is_running();
+        let feedback_output = self.effective_feedback_output(feedback_value.midi_output_device);
+        // Feedback sent to an unplugged device would get lost anyway. Not remembering it in the
+        // duplicate-blocking checksums makes sure it's resent as soon as the device is back.
+        let blocked_by_disconnected_device = match feedback_output {
+            Some(FeedbackOutput::Midi(MidiDestination::Device(dev_id))) => self
+                .io_connection_status
+                .midi_output_device_is_missing(dev_id),
+            _ => false,
+        };
+        if !blocked_by_idle_animation
+            && !blocked_by_disconnected_device
+            && (feedback_reason.is_always_allowed()
+                || self.feedback_is_effectively_enabled(feedback_output))
+        {
+            if let Some(feedback_output) = feedback_output {
                 if let Some(source_feedback_value) = feedback_value.source {
                     // At this point we can be sure that this mapping can't have a
                     // virtual source.
@@ -2880,7 +4163,8 @@ impl<EH: DomainEventHandler> Basics<EH> {
                             feedback_output,
                             feedback_reason,
                             source_feedback_value,
-                            is_feedback_after_control,
+                            is_feedback_after_control || feedback_value.source_is_relative,
+                            feedback_value.deadband,
                         );
                     }
                 }
@@ -2892,8 +4176,24 @@ impl<EH: DomainEventHandler> Basics<EH> {
         }
     }
 
+    /// Returns the output to which feedback of a mapping with the given MIDI output device
+    /// override goes.
+    fn effective_feedback_output(
+        &self,
+        midi_output_device: Option<MidiOutputDeviceId>,
+    ) -> Option<FeedbackOutput> {
+        match midi_output_device {
+            None => self.feedback_output,
+            Some(dev_id) => Some(FeedbackOutput::Midi(MidiDestination::Device(dev_id))),
+        }
+    }
+
     pub fn instance_feedback_is_effectively_enabled(&self) -> bool {
-        if let Some(fo) = self.feedback_output {
+        self.feedback_is_effectively_enabled(self.feedback_output)
+    }
+
+    fn feedback_is_effectively_enabled(&self, feedback_output: Option<FeedbackOutput>) -> bool {
+        if let Some(fo) = feedback_output {
             self.feedback_is_globally_enabled
                 && BackboneState::get().feedback_is_allowed(&self.instance_id, fo)
         } else {
@@ -2932,7 +4232,11 @@ impl<EH: DomainEventHandler> Basics<EH> {
                         ManualFeedbackProcessing::Off,
                     );
                     if let Some(new_value) = control_result.new_target_value {
-                        self.notify_target_value_changed(m, new_value);
+                        self.notify_target_value_changed(
+                            m,
+                            new_value,
+                            TargetValueChangeOrigin::Control,
+                        );
                     }
                     enforce_target_refresh = true;
                     let extended_control_result = ExtendedMappingControlResult {
@@ -3035,7 +4339,8 @@ fn control_mapping_stage_one_and_two<EH: DomainEventHandler>(
 /// Takes care of:
 ///
 /// 1. Notifying that mapping matched
-/// 2. Controlling with given control value (probably produced by source) starting from mode.
+/// 2. Accelerating relative increments (if enabled)
+/// 3. Controlling with given control value (probably produced by source) starting from mode.
 #[must_use]
 fn control_mapping_stage_one<EH: DomainEventHandler>(
     basics: &Basics<EH>,
@@ -3044,9 +4349,13 @@ fn control_mapping_stage_one<EH: DomainEventHandler>(
     control_value: ControlValue,
     options: ControlOptions,
 ) -> MappingControlResult {
+    if basics.control_is_blocked_by_feedback_loop(m, control_value) {
+        return Default::default();
+    }
     basics
         .event_handler
         .notify_mapping_matched(m.compartment(), m.id());
+    let control_value = m.accelerate(control_value, Instant::now());
     m.control_from_mode(
         control_value,
         options,
@@ -3068,7 +4377,7 @@ fn control_mapping_stage_two<EH: DomainEventHandler>(
     feedback_handling: ManualFeedbackProcessing,
 ) {
     if let Some(new_value) = control_result.new_target_value {
-        basics.notify_target_value_changed(m, new_value);
+        basics.notify_target_value_changed(m, new_value, TargetValueChangeOrigin::Control);
     }
     if let ManualFeedbackProcessing::On {
         mappings_with_virtual_targets,
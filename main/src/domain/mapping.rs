@@ -1,13 +1,20 @@
 use crate::domain::{
-    get_prop_value, prop_feedback_resolution, prop_is_affected_by, ActivationChange,
-    ActivationCondition, CompoundChangeEvent, ControlContext, ControlOptions,
-    ExtendedProcessorContext, FeedbackResolution, GroupId, HitInstructionReturnValue,
-    MappingActivationEffect, MappingControlContext, MappingData, MappingInfo, MessageCaptureEvent,
-    MidiScanResult, MidiSource, Mode, OscDeviceId, OscScanResult, ParameterArray, ParameterSlice,
-    PersistentMappingProcessingState, RealTimeReaperTarget, RealearnTarget, ReaperMessage,
-    ReaperSource, ReaperTarget, ReaperTargetType, Tag, TargetCharacter, TrackExclusivity,
-    UnresolvedReaperTarget, VirtualControlElement, VirtualFeedbackValue, VirtualSource,
-    VirtualSourceAddress, VirtualSourceValue, VirtualTarget, COMPARTMENT_PARAMETER_COUNT,
+    denormalize_from_interval, get_prop_value, normalize_to_interval, osc_arg_filters_match,
+    prop_feedback_resolution, prop_is_affected_by, unpack_value_sequence, ActivationChange,
+    ActivationCondition, AdditionalFeedbackEvent, BeatBlinkSettings, BeatChangedEvent,
+    CompoundChangeEvent, ControlContext, ControlOptions, EncoderAccelerationSettings,
+    EncoderAccelerator, ExtendedProcessorContext, FeedbackDeadband, FeedbackResolution, GroupId,
+    HitInstructionReturnValue, IoConnectionStatus, LayerId, MappingActivationEffect,
+    MappingControlContext, MappingData, MappingInfo, MessageCaptureEvent, MidiScanResult,
+    MidiSource, Mode, NoiseFilter, NoiseFilterSettings, OscAddressCaptures, OscAddressPattern,
+    OscArgFilter, OscDeltaMode, OscDeltaSettings, OscDeviceId, OscScanResult, ParameterArray,
+    ParameterSlice, PersistentMappingProcessingState, Ramp, RampSettings, RealTimeReaperTarget,
+    RealearnTarget, ReaperMessage, ReaperSource, ReaperTarget, ReaperTargetType, ResponseCurve,
+    ScaleQuantizer, ScalingTakeover, ScalingTakeoverSettings, SysExChecksum, Tag, TargetCharacter,
+    TargetHitThrottle, TrackExclusivity, UnresolvedReaperTarget, ValueSequenceStepper,
+    ValueSequenceStepping, VelocityThreshold, VelocityThresholdFilter, VirtualControlElement,
+    VirtualFeedbackValue, VirtualSource, VirtualSourceAddress, VirtualSourceValue, VirtualTarget,
+    COMPARTMENT_PARAMETER_COUNT,
 };
 use derive_more::Display;
 use enum_iterator::IntoEnumIterator;
@@ -16,8 +23,8 @@ use helgoboss_learn::{
     format_percentage_without_unit, parse_percentage_without_unit, AbsoluteValue, ControlType,
     ControlValue, FeedbackValue, GroupInteraction, MidiSourceAddress, MidiSourceValue,
     ModeControlOptions, ModeControlResult, ModeFeedbackOptions, NumericFeedbackValue, NumericValue,
-    OscSource, OscSourceAddress, PropValue, RawMidiEvent, SourceCharacter, Target, UnitValue,
-    ValueFormatter, ValueParser,
+    OscSource, OscSourceAddress, PropValue, RawMidiEvent, SourceCharacter, TakeoverMode, Target,
+    UnitValue, ValueFormatter, ValueParser,
 };
 use helgoboss_midi::{Channel, RawShortMessage, ShortMessage};
 use num_enum::{IntoPrimitive, TryFromPrimitive};
@@ -27,8 +34,8 @@ use std::cell::Cell;
 use crate::domain::unresolved_reaper_target::UnresolvedReaperTargetDef;
 use indexmap::map::IndexMap;
 use indexmap::set::IndexSet;
-use reaper_high::{Fx, Project, Track, TrackRoute};
-use reaper_medium::MidiInputDeviceId;
+use reaper_high::{Fx, Project, Reaper, Track, TrackRoute};
+use reaper_medium::{Guid, MidiInputDeviceId, MidiOutputDeviceId};
 use rosc::OscMessage;
 use serde::{Deserialize, Serialize};
 use smallvec::alloc::fmt::Formatter;
@@ -40,7 +47,7 @@ use std::rc::Rc;
 use std::time::{Duration, Instant};
 use uuid::Uuid;
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Clone, Debug)]
 pub struct ProcessorMappingOptions {
     /// In the main processor mapping this might be overridden by the unresolved target's
     /// is_always_active() result. The real-time processor always gets the effective result of the
@@ -50,15 +57,63 @@ pub struct ProcessorMappingOptions {
     pub control_is_enabled: bool,
     pub feedback_is_enabled: bool,
     pub feedback_send_behavior: FeedbackSendBehavior,
+    pub velocity_threshold: Option<VelocityThreshold>,
+    /// Set if jitter of absolute sources should be suppressed.
+    pub noise_filter: Option<NoiseFilterSettings>,
+    pub scale_quantizer: Option<ScaleQuantizer>,
+    /// Set if this is a relative OSC source.
+    pub osc_delta: Option<OscDeltaSettings>,
+    /// Set if this is a raw MIDI source whose pattern contains a checksum.
+    pub sysex_checksum: Option<SysExChecksum>,
+    /// Set if this MIDI source should listen to another MIDI input device than the instance's
+    /// control input.
+    pub midi_input_device: Option<MidiInputDeviceId>,
+    /// Set if feedback of this MIDI source should go to another MIDI output device than the
+    /// instance's feedback output.
+    pub midi_output_device: Option<MidiOutputDeviceId>,
+    /// Set if control values arriving more frequently should be coalesced.
+    pub min_control_interval: Option<Duration>,
+    /// Set if the target shouldn't be hit more frequently (the latest value is applied later).
+    pub min_target_hit_interval: Option<Duration>,
+    /// Set if holding a button should sweep the target value instead of hitting it directly.
+    pub ramp: Option<RampSettings>,
+    /// Set if source feedback which is too close to the previously sent value should be
+    /// suppressed (e.g. to prevent motorized faders from chattering).
+    pub feedback_deadband: Option<UnitValue>,
+    /// Set if relative increments should be multiplied depending on the turning speed.
+    pub encoder_acceleration: Option<EncoderAccelerationSettings>,
+    /// Set if button presses should step through the target value sequence.
+    pub target_value_sequence_stepping: Option<ValueSequenceStepping>,
+    /// Set if the target value should be taken over by value scaling with a catch range.
+    pub scaling_takeover: Option<ScalingTakeoverSettings>,
+    /// Set if absolute control values should be shaped by a point-based curve. Feedback takes the
+    /// inverse way.
+    pub response_curve: Option<ResponseCurve>,
+    /// Set if another mapping in the same compartment is soloed.
+    pub control_is_muted_by_solo: bool,
+    /// Set if this mapping should only be controllable while the given layer is active.
+    pub layer: Option<LayerId>,
+    /// Set if this mapping belongs to a layer which is currently not active.
+    pub control_is_muted_by_layer: bool,
+    /// Set if the MIDI input device which this mapping listens to is currently unplugged.
+    pub control_is_suspended_by_missing_device: bool,
+    /// Set if the MIDI output device which this mapping sends feedback to is currently unplugged.
+    pub feedback_is_suspended_by_missing_device: bool,
 }
 
 impl ProcessorMappingOptions {
     pub fn control_is_effectively_enabled(&self) -> bool {
-        self.persistent_processing_state.is_enabled && self.control_is_enabled
+        self.persistent_processing_state.is_enabled
+            && self.control_is_enabled
+            && !self.control_is_muted_by_solo
+            && !self.control_is_muted_by_layer
+            && !self.control_is_suspended_by_missing_device
     }
 
     pub fn feedback_is_effectively_enabled(&self) -> bool {
-        self.persistent_processing_state.is_enabled && self.feedback_is_enabled
+        self.persistent_processing_state.is_enabled
+            && self.feedback_is_enabled
+            && !self.feedback_is_suspended_by_missing_device
     }
 }
 
@@ -156,12 +211,17 @@ pub struct LifecycleMidiData {
 pub struct MappingExtension {
     /// If it's None, it means it's splintered already.
     lifecycle_midi_data: Option<LifecycleMidiData>,
+    beat_blink: Option<BeatBlinkSettings>,
 }
 
 impl MappingExtension {
-    pub fn new(lifecycle_midi_data: LifecycleMidiData) -> Self {
+    pub fn new(
+        lifecycle_midi_data: LifecycleMidiData,
+        beat_blink: Option<BeatBlinkSettings>,
+    ) -> Self {
         Self {
             lifecycle_midi_data: Some(lifecycle_midi_data),
+            beat_blink,
         }
     }
 }
@@ -185,9 +245,26 @@ pub struct MainMapping {
     activation_condition_2: ActivationCondition,
     activation_state: ActivationState,
     extension: MappingExtension,
+    /// Only relevant for OSC sources.
+    osc_arg_filters: Vec<OscArgFilter>,
+    /// Set if this is an OSC source whose address contains wildcards or captures.
+    osc_address_pattern: Option<OscAddressPattern>,
+    /// Captured values of the last matching OSC address (used when resolving the target).
+    osc_captures: OscAddressCaptures,
+    /// Set when the last control message changed the captured values.
+    osc_captures_changed: bool,
     initial_target_value: Option<AbsoluteValue>,
     /// Called "y_last" in the control transformation formula.
     last_non_performance_target_value: Cell<Option<AbsoluteValue>>,
+    /// Last numeric value sent to a relative OSC source (needed to calculate the next delta).
+    last_osc_feedback_value: Cell<Option<UnitValue>>,
+    /// Whether blinking feedback is currently in its "off" phase.
+    blink_is_off: Cell<bool>,
+    target_hit_throttle: TargetHitThrottle,
+    ramp: Ramp,
+    encoder_accelerator: EncoderAccelerator,
+    value_sequence_stepper: ValueSequenceStepper,
+    scaling_takeover: ScalingTakeover,
 }
 
 #[derive(Default, Debug)]
@@ -219,7 +296,12 @@ impl MainMapping {
         activation_condition_2: ActivationCondition,
         options: ProcessorMappingOptions,
         extension: MappingExtension,
+        osc_arg_filters: Vec<OscArgFilter>,
     ) -> MainMapping {
+        let osc_address_pattern = match &source {
+            CompoundMappingSource::Osc(s) => OscAddressPattern::parse(s.address_pattern()),
+            _ => None,
+        };
         MainMapping {
             core: MappingCore {
                 compartment,
@@ -230,6 +312,8 @@ impl MainMapping {
                 group_interaction,
                 options,
                 time_of_last_control: None,
+                noise_filter: Default::default(),
+                velocity_threshold_filter: Default::default(),
             },
             key: {
                 let key_str: &str = key.as_ref();
@@ -243,8 +327,19 @@ impl MainMapping {
             activation_condition_2,
             activation_state: Default::default(),
             extension,
+            osc_arg_filters,
+            osc_address_pattern,
+            osc_captures: Default::default(),
+            osc_captures_changed: false,
             initial_target_value: None,
             last_non_performance_target_value: Cell::new(None),
+            last_osc_feedback_value: Cell::new(None),
+            blink_is_off: Cell::new(false),
+            target_hit_throttle: Default::default(),
+            ramp: Default::default(),
+            encoder_accelerator: Default::default(),
+            value_sequence_stepper: Default::default(),
+            scaling_takeover: Default::default(),
         }
     }
 
@@ -261,6 +356,21 @@ impl MainMapping {
         evt: CompoundChangeEvent,
         context: ControlContext,
     ) -> (bool, Option<AbsoluteValue>) {
+        let blink_phase_changed = match evt {
+            CompoundChangeEvent::Additional(AdditionalFeedbackEvent::BeatChanged(e)) => {
+                let changed = self.update_blink_phase(target, e);
+                if !e.is_full_beat_change {
+                    // Beat ticks in between full beats are only relevant for blinking.
+                    return if changed {
+                        (true, target.current_value(context))
+                    } else {
+                        (false, None)
+                    };
+                }
+                changed
+            }
+            _ => false,
+        };
         // Textual feedback relates to whatever properties are mentioned in the text expression.
         // But even numeric feedback can use properties - as part of the feedback style
         // (color etc.). That means we need to check for each of these mentioned properties if
@@ -287,7 +397,7 @@ impl MainMapping {
                     true,
                 )
             };
-        if is_affected {
+        if is_affected || blink_phase_changed {
             let new_value = new_value.or_else(|| target.current_value(context));
             if handle_performance_mapping {
                 self.update_last_non_performance_target_value_if_appropriate(new_value);
@@ -298,6 +408,37 @@ impl MainMapping {
         }
     }
 
+    /// Returns whether the blink phase changed.
+    fn update_blink_phase(&self, target: &ReaperTarget, event: &BeatChangedEvent) -> bool {
+        if self.extension.beat_blink.is_none() {
+            return false;
+        }
+        let project = target
+            .project()
+            .unwrap_or_else(|| Reaper::get().current_project());
+        if event.project != project {
+            return false;
+        }
+        let is_off = match self.extension.beat_blink {
+            Some(s) if event.project.is_playing() => s.division.is_off_phase(event.full_beats),
+            _ => false,
+        };
+        self.blink_is_off.replace(is_off) != is_off
+    }
+
+    fn is_blinked_off(&self, target_value: AbsoluteValue) -> bool {
+        match self.extension.beat_blink {
+            Some(s) => self.blink_is_off.get() && s.applies_to(target_value.to_unit_value()),
+            None => false,
+        }
+    }
+
+    /// Mappings with blinking feedback need to be informed about beat changes, too.
+    pub fn wants_beat_change_events(&self) -> bool {
+        self.extension.beat_blink.is_some()
+            || self.feedback_resolution() == Some(FeedbackResolution::Beat)
+    }
+
     pub fn update_last_non_performance_target_value_if_appropriate(
         &self,
         value: Option<AbsoluteValue>,
@@ -331,6 +472,32 @@ impl MainMapping {
         self.core.options.persistent_processing_state = state;
     }
 
+    pub fn update_soloed_mapping(&mut self, soloed_mapping: Option<QualifiedMappingId>) {
+        self.core.update_soloed_mapping(soloed_mapping);
+    }
+
+    pub fn update_active_layer(&mut self, active_layer: LayerId) {
+        self.core.update_active_layer(active_layer);
+    }
+
+    /// Suspends or resumes this mapping depending on whether its MIDI devices are connected.
+    ///
+    /// The given control input and feedback output are the ones of the instance.
+    pub fn update_missing_devices(
+        &mut self,
+        status: &IoConnectionStatus,
+        control_input: Option<MidiInputDeviceId>,
+        feedback_output: Option<MidiOutputDeviceId>,
+    ) {
+        self.core
+            .update_missing_devices(status, control_input, feedback_output);
+    }
+
+    pub fn is_suspended_by_missing_device(&self) -> bool {
+        self.core.options.control_is_suspended_by_missing_device
+            || self.core.options.feedback_is_suspended_by_missing_device
+    }
+
     pub fn tags(&self) -> &[Tag] {
         &self.tags
     }
@@ -344,6 +511,8 @@ impl MainMapping {
             compartment: self.core.compartment,
             mapping_key: self.key.clone(),
             source: self.source().clone(),
+            sysex_checksum: self.core.options.sysex_checksum,
+            midi_output_device: self.core.options.midi_output_device,
         }
     }
 
@@ -355,6 +524,10 @@ impl MainMapping {
         self.core.id
     }
 
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
     pub fn qualified_id(&self) -> QualifiedMappingId {
         QualifiedMappingId::new(self.core.compartment, self.core.id)
     }
@@ -372,7 +545,7 @@ impl MainMapping {
             core: MappingCore {
                 options: ProcessorMappingOptions {
                     target_is_active: self.target_is_effectively_active(),
-                    ..self.core.options
+                    ..self.core.options.clone()
                 },
                 ..self.core.clone()
             },
@@ -417,28 +590,34 @@ impl MainMapping {
         !self.targets.is_empty()
     }
 
-    /// Returns `Some` if this affects the mapping's activation state in any way.
+    /// Returns `Some` if the given parameter changes affect the mapping's activation state in any
+    /// way.
+    ///
+    /// Each change consists of the absolute parameter index and the previous value. The given
+    /// parameters must already contain the new values.
     pub fn check_activation_effect(
         &self,
         params: &ParameterArray,
-        absolute_param_index: u32,
-        previous_value: f32,
+        changes: &[(u32, f32)],
     ) -> Option<MappingActivationEffect> {
         let sliced_params = self.core.compartment.slice_params(params);
-        let rel_param_index = self
-            .core
-            .compartment
-            .relativize_absolute_index(absolute_param_index);
-        let effect_1 = self.activation_condition_1.is_fulfilled_single(
-            sliced_params,
-            rel_param_index,
-            previous_value,
-        );
-        let effect_2 = self.activation_condition_2.is_fulfilled_single(
-            sliced_params,
-            rel_param_index,
-            previous_value,
-        );
+        let mut effect_1 = None;
+        let mut effect_2 = None;
+        for (absolute_param_index, previous_value) in changes {
+            let rel_param_index = self
+                .core
+                .compartment
+                .relativize_absolute_index(*absolute_param_index);
+            // All effects are evaluated against the new parameter values, so the last one wins.
+            effect_1 = self
+                .activation_condition_1
+                .is_fulfilled_single(sliced_params, rel_param_index, *previous_value)
+                .or(effect_1);
+            effect_2 = self
+                .activation_condition_2
+                .is_fulfilled_single(sliced_params, rel_param_index, *previous_value)
+                .or(effect_2);
+        }
         MappingActivationEffect::new(self.id(), effect_1, effect_2)
     }
 
@@ -456,6 +635,15 @@ impl MainMapping {
             || self.activation_condition_2.can_be_affected_by_parameters()
     }
 
+    /// Returns if this activation condition is affected by REAPER state changes in general.
+    pub fn activation_can_be_affected_by_reaper_state(&self) -> bool {
+        self.activation_condition_1
+            .can_be_affected_by_reaper_state()
+            || self
+                .activation_condition_2
+                .can_be_affected_by_reaper_state()
+    }
+
     pub fn update_activation_from_effect(
         &mut self,
         activation_effect: MappingActivationEffect,
@@ -486,7 +674,10 @@ impl MainMapping {
         let (targets, is_active) = self.resolve_target(context, control_context);
         self.targets = targets;
         self.core.options.target_is_active = is_active;
-        self.update_activation(context.params());
+        self.update_activation(
+            context.params(),
+            context.context().project_or_current_project(),
+        );
         let target_value = self.current_aggregated_target_value(control_context);
         self.initial_target_value = target_value;
         self.last_non_performance_target_value = Cell::new(target_value);
@@ -497,6 +688,11 @@ impl MainMapping {
         context: ExtendedProcessorContext,
         control_context: ControlContext,
     ) -> (Vec<CompoundMappingTarget>, bool) {
+        let context = if self.osc_address_pattern.is_some() {
+            context.with_osc_captures(&self.osc_captures)
+        } else {
+            context
+        };
         match self.unresolved_target.as_ref() {
             None => (vec![], false),
             Some(ut) => match ut.resolve(context, self.core.compartment).ok() {
@@ -517,6 +713,38 @@ impl MainMapping {
         }
     }
 
+    /// Returns the GUIDs of the tracks and FXs which the resolved targets are bound to.
+    ///
+    /// Returns `None` if the target might have to be refreshed on change events concerning any
+    /// track or FX, e.g. because it uses the `<Selected>` track or hasn't been resolved yet.
+    pub fn target_dependencies(&self) -> Option<Vec<Guid>> {
+        let t = match self.unresolved_target.as_ref() {
+            Some(UnresolvedCompoundMappingTarget::Reaper(t))
+                if t.can_be_affected_by_change_events() =>
+            {
+                t
+            }
+            _ => return Some(vec![]),
+        };
+        if t.can_be_affected_by_foreign_change_events() || !self.has_resolved_successfully() {
+            return None;
+        }
+        let mut guids = vec![];
+        for target in &self.targets {
+            if let CompoundMappingTarget::Reaper(ReaperTarget::Multi(t)) = target {
+                guids.extend(t.dependencies());
+                continue;
+            }
+            if let Some(track) = target.track() {
+                guids.push(*track.guid());
+            }
+            if let Some(guid) = target.fx().and_then(|fx| fx.guid()) {
+                guids.push(guid);
+            }
+        }
+        Some(guids)
+    }
+
     pub fn needs_refresh_when_target_touched(&self) -> bool {
         matches!(
             self.unresolved_target,
@@ -551,6 +779,11 @@ impl MainMapping {
 
     pub fn wants_to_be_polled_for_control(&self) -> bool {
         self.core.mode.wants_to_be_polled()
+            || self.core.options.ramp.is_some()
+            || self.targets.iter().any(|t| match t {
+                CompoundMappingTarget::Reaper(t) => t.wants_to_be_polled_for_control(),
+                CompoundMappingTarget::Virtual(_) => false,
+            })
     }
 
     /// The boolean return value tells if the resolved target changed in some way, the activation
@@ -583,11 +816,25 @@ impl MainMapping {
         (target_changed, Some(update))
     }
 
-    pub fn update_activation(&mut self, params: &ParameterArray) -> Option<ActivationChange> {
+    /// Returns `true` (once) if the last control message changed the captured values of a
+    /// templated OSC address, which means that the target needs to be resolved again.
+    pub fn take_osc_captures_change(&mut self) -> bool {
+        std::mem::replace(&mut self.osc_captures_changed, false)
+    }
+
+    pub fn update_activation(
+        &mut self,
+        params: &ParameterArray,
+        project: Project,
+    ) -> Option<ActivationChange> {
         let sliced_params = self.core.compartment.slice_params(params);
         let was_active_before = self.is_active_in_terms_of_activation_state();
-        self.activation_state.is_active_1 = self.activation_condition_1.is_fulfilled(sliced_params);
-        self.activation_state.is_active_2 = self.activation_condition_2.is_fulfilled(sliced_params);
+        self.activation_state.is_active_1 = self
+            .activation_condition_1
+            .is_fulfilled(sliced_params, project);
+        self.activation_state.is_active_2 = self
+            .activation_condition_2
+            .is_fulfilled(sliced_params, project);
         let now_is_active = self.is_active_in_terms_of_activation_state();
         if now_is_active == was_active_before {
             return None;
@@ -619,9 +866,11 @@ impl MainMapping {
         target_is_effectively_active(&self.core.options, self.unresolved_target.as_ref())
     }
 
-    /// Returns `true` if mapping & target is active and control or feedback is enabled.
+    /// Returns `true` if mapping & target is active, the mapping is not muted because another one
+    /// is soloed and control or feedback is enabled.
     pub fn is_effectively_on(&self) -> bool {
         self.is_effectively_active()
+            && !self.core.options.control_is_muted_by_solo
             && (self.control_is_enabled() || self.core.options.feedback_is_effectively_enabled())
     }
 
@@ -661,6 +910,42 @@ impl MainMapping {
         logger: &slog::Logger,
         processor_context: ExtendedProcessorContext,
     ) -> MappingControlResult {
+        let ctx = MappingControlContext {
+            control_context: context,
+            mapping_data: self.data(),
+        };
+        for target in &mut self.targets {
+            if let CompoundMappingTarget::Reaper(t) = target {
+                if t.wants_to_be_polled_for_control() {
+                    t.poll_control(ctx);
+                }
+            }
+        }
+        if let Some(settings) = self.core.options.ramp {
+            let position = match self.ramp.poll(&settings, Instant::now()) {
+                None => return MappingControlResult::default(),
+                Some(p) => p,
+            };
+            // The ramp acts like a fader moved by the button, so the mode still applies.
+            return self.control_internal(
+                ControlOptions::default(),
+                context,
+                logger,
+                processor_context,
+                true,
+                |options, context, mode, target| {
+                    mode.control_with_options(
+                        ControlValue::AbsoluteContinuous(position),
+                        target,
+                        context,
+                        options.mode_control_options,
+                    )
+                },
+            );
+        }
+        if !self.core.mode.wants_to_be_polled() {
+            return MappingControlResult::default();
+        }
         self.control_internal(
             ControlOptions::default(),
             context,
@@ -671,10 +956,52 @@ impl MainMapping {
         )
     }
 
+    pub fn throttles_target_hits(&self) -> bool {
+        self.core.options.min_target_hit_interval.is_some()
+    }
+
+    /// Returns whether a target value held back because of the minimum target hit interval is
+    /// waiting to be applied.
+    pub fn has_pending_target_hit(&self) -> bool {
+        self.target_hit_throttle.has_pending()
+    }
+
+    /// Hits the target with the held back target value if the minimum target hit interval has
+    /// elapsed. Returns `None` if it's not yet time to do so.
+    #[must_use]
+    pub fn release_pending_target_hit(
+        &mut self,
+        context: ControlContext,
+        logger: &slog::Logger,
+        processor_context: ExtendedProcessorContext,
+    ) -> Option<MappingControlResult> {
+        let min_interval = self.core.options.min_target_hit_interval?;
+        let value = self
+            .target_hit_throttle
+            .take_due(min_interval, Instant::now())?;
+        let result = self.control_internal(
+            ControlOptions::default(),
+            context,
+            logger,
+            processor_context,
+            false,
+            |_, _, _, _| Some(ModeControlResult::hit_target(value)),
+        );
+        Some(result)
+    }
+
     pub fn group_interaction(&self) -> GroupInteraction {
         self.core.group_interaction
     }
 
+    /// Multiplies relative increments depending on how fast the encoder is turned (if enabled).
+    pub fn accelerate(&mut self, value: ControlValue, now: Instant) -> ControlValue {
+        match &self.core.options.encoder_acceleration {
+            None => value,
+            Some(settings) => self.encoder_accelerator.process(settings, value, now),
+        }
+    }
+
     /// Controls mode => target.
     ///
     /// Don't execute in real-time processor because this executes REAPER main-thread-only
@@ -688,6 +1015,87 @@ impl MainMapping {
         logger: &slog::Logger,
         processor_context: ExtendedProcessorContext,
     ) -> MappingControlResult {
+        let source_value = match self
+            .core
+            .process_velocity_threshold(source_value)
+            .and_then(|v| self.core.process_noise_filter(v))
+        {
+            None => return MappingControlResult::default(),
+            Some(v) => v,
+        };
+        let source_value = match &self.core.options.response_curve {
+            None => source_value,
+            Some(curve) => curve.process(source_value),
+        };
+        if let Some(settings) = &self.core.options.ramp {
+            if self
+                .ramp
+                .process_button(settings, source_value, Instant::now())
+            {
+                // The target is hit when polling.
+                return MappingControlResult::default();
+            }
+        }
+        if let Some(stepping) = self.core.options.target_value_sequence_stepping {
+            let sequence = unpack_value_sequence(&self.core.mode.settings().target_value_sequence);
+            let current_target_value = self
+                .current_aggregated_target_value(context)
+                .map(|v| v.to_unit_value());
+            if let Some(next_value) = self.value_sequence_stepper.process_button(
+                stepping,
+                &sequence,
+                current_target_value,
+                source_value,
+            ) {
+                let next_value = match next_value {
+                    // Button release
+                    None => return MappingControlResult::default(),
+                    Some(v) => v,
+                };
+                // Sequence values are target values already, so they bypass the rest of the mode.
+                return self.control_internal(
+                    options,
+                    context,
+                    logger,
+                    processor_context,
+                    false,
+                    |_, _, _, _| {
+                        Some(ModeControlResult::hit_target(
+                            ControlValue::AbsoluteContinuous(next_value),
+                        ))
+                    },
+                );
+            }
+        }
+        if let Some(settings) = &self.core.options.scaling_takeover {
+            if let Ok(control_value) = source_value.to_unit_value() {
+                let target_interval = &self.core.mode.settings().target_value_interval;
+                let current_target_value = self
+                    .current_aggregated_target_value(context)
+                    .map(|v| normalize_to_interval(v.to_unit_value(), target_interval));
+                let next_value = match self.scaling_takeover.process(
+                    settings,
+                    control_value,
+                    current_target_value,
+                ) {
+                    None => return MappingControlResult::default(),
+                    Some(v) => denormalize_from_interval(v, target_interval),
+                };
+                // The takeover replaces the rest of the mode.
+                return self.control_internal(
+                    options,
+                    context,
+                    logger,
+                    processor_context,
+                    false,
+                    |_, _, _, _| {
+                        Some(ModeControlResult::hit_target(
+                            ControlValue::AbsoluteContinuous(next_value),
+                        ))
+                    },
+                );
+            }
+        }
         self.control_internal(
             options,
             context,
@@ -705,6 +1113,32 @@ impl MainMapping {
         )
     }
 
+    pub fn uses_pickup(&self) -> bool {
+        self.core.mode.settings().takeover_mode == TakeoverMode::Pickup
+    }
+
+    /// Lets the mode process the given source value without touching the target.
+    ///
+    /// Used for making the mode aware of the current position of the control element, e.g. in
+    /// response to a controller state request. Pickup then starts aligned with the physical
+    /// position.
+    pub fn prime_mode(&mut self, source_value: ControlValue, context: ControlContext) {
+        let ctx = MappingControlContext {
+            control_context: context,
+            mapping_data: self.data(),
+        };
+        for target in &self.targets {
+            if let CompoundMappingTarget::Reaper(t) = target {
+                let _ = self.core.mode.control_with_options(
+                    source_value,
+                    t,
+                    ctx,
+                    ModeControlOptions::default(),
+                );
+            }
+        }
+    }
+
     /// Controls target directly without using mode.
     ///
     /// Don't execute in real-time processor because this executes REAPER main-thread-only
@@ -755,6 +1189,7 @@ impl MainMapping {
             mapping_id: self.core.id,
             group_id: self.core.group_id,
             last_non_performance_target_value: self.last_non_performance_target_value.get(),
+            scale_quantizer: self.core.options.scale_quantizer,
         }
     }
 
@@ -801,6 +1236,8 @@ impl MainMapping {
         let mut at_least_one_relevant_target_exists = false;
         let mut at_least_one_target_was_reached = false;
         let mut hit_instruction = None;
+        // With multiple targets, the throttle decides once for all of them.
+        let mut target_hits_are_throttled = None;
         use ModeControlResult::*;
         let mut fresh_targets = if options.enforce_target_refresh {
             let (targets, conditions_are_met) = self.resolve_target(processor_context, context);
@@ -837,6 +1274,27 @@ impl MainMapping {
                 }
                 Some(HitTarget { value }) => {
                     at_least_one_target_was_reached = true;
+                    let value = match self.core.options.min_target_hit_interval {
+                        None => value,
+                        Some(min_interval) => match target_hits_are_throttled {
+                            Some(true) => continue,
+                            Some(false) => value,
+                            None => match self.target_hit_throttle.process(
+                                value,
+                                min_interval,
+                                Instant::now(),
+                            ) {
+                                None => {
+                                    target_hits_are_throttled = Some(true);
+                                    continue;
+                                }
+                                Some(v) => {
+                                    target_hits_are_throttled = Some(false);
+                                    v
+                                }
+                            },
+                        },
+                    };
                     if !is_polling {
                         self.core.time_of_last_control = Some(Instant::now());
                     }
@@ -970,6 +1428,20 @@ impl MainMapping {
         //   form of feedback it sends, it just provides us with options and we can choose.
         // - This leaves us with asking the mode. That means the user needs to explicitly choose
         //   whether it wants numerical or textual feedback.
+        let source_feedback_is_okay = if self.core.options.feedback_send_behavior
+            == FeedbackSendBehavior::PreventEchoFeedback
+        {
+            !self.core.is_echo()
+        } else {
+            true
+        };
+        let destinations = FeedbackDestinations {
+            with_projection_feedback,
+            with_source_feedback: with_source_feedback && source_feedback_is_okay,
+        };
+        if self.is_blinked_off(combined_target_value) {
+            return self.feedback_given_mode_value(Cow::Owned(FeedbackValue::Off), destinations);
+        }
         let feedback_value = if self.core.mode.wants_textual_feedback() {
             let v = self
                 .core
@@ -983,20 +1455,7 @@ impl MainMapping {
                 .feedback_style(&|key| get_prop_value(key, self, control_context));
             FeedbackValue::Numeric(NumericFeedbackValue::new(style, combined_target_value))
         };
-        let source_feedback_is_okay = if self.core.options.feedback_send_behavior
-            == FeedbackSendBehavior::PreventEchoFeedback
-        {
-            !self.core.is_echo()
-        } else {
-            true
-        };
-        self.feedback_given_target_value(
-            Cow::Owned(feedback_value),
-            FeedbackDestinations {
-                with_projection_feedback,
-                with_source_feedback: with_source_feedback && source_feedback_is_okay,
-            },
-        )
+        self.feedback_given_target_value(Cow::Owned(feedback_value), destinations)
     }
 
     pub fn current_aggregated_target_value(
@@ -1038,6 +1497,10 @@ impl MainMapping {
                     options,
                     Default::default(),
                 )?;
+                let mode_value = match &self.core.options.response_curve {
+                    None => mode_value,
+                    Some(curve) => curve.process_feedback(mode_value),
+                };
                 Cow::Owned(Numeric(NumericFeedbackValue::new(v.style, mode_value)))
             }
             // Textual feedback is not processed (created by the mode in the first place).
@@ -1051,13 +1514,94 @@ impl MainMapping {
         mode_value: Cow<FeedbackValue>,
         destinations: FeedbackDestinations,
     ) -> Option<SpecificCompoundFeedbackValue> {
-        SpecificCompoundFeedbackValue::from_mode_value(
+        let numeric_value = mode_value.to_numeric().map(|v| v.value.to_unit_value());
+        let mut value = SpecificCompoundFeedbackValue::from_mode_value(
             self.core.compartment,
             self.key.clone(),
             &self.core.source,
             mode_value,
             destinations,
-        )
+        )?;
+        if let SpecificCompoundFeedbackValue::Real(v) = &mut value {
+            self.make_osc_feedback_relative_if_necessary(v, numeric_value);
+            self.resolve_osc_feedback_address_if_necessary(v);
+            if let (Some(checksum), Some(source_value)) =
+                (&self.core.options.sysex_checksum, &mut v.source)
+            {
+                checksum.apply_to_feedback_value(source_value);
+            }
+            if let (Some(epsilon), Some(value)) =
+                (self.core.options.feedback_deadband, numeric_value)
+            {
+                v.deadband = Some(FeedbackDeadband { epsilon, value });
+            }
+            v.midi_output_device = self.core.options.midi_output_device;
+            if v.projection.is_none() && v.source.is_none() {
+                return None;
+            }
+        }
+        Some(value)
+    }
+
+    /// Replaces the absolute argument of OSC feedback with the delta to the previously sent value
+    /// if the source is relative and relative feedback is enabled.
+    fn make_osc_feedback_relative_if_necessary(
+        &self,
+        value: &mut RealFeedbackValue,
+        numeric_value: Option<UnitValue>,
+    ) {
+        let settings = match self.core.options.osc_delta {
+            Some(s) if s.relative_feedback => s,
+            _ => return,
+        };
+        let msg = match &mut value.source {
+            Some(SourceFeedbackValue::Osc(msg)) => msg,
+            _ => return,
+        };
+        let arg_descriptor = match &self.core.source {
+            CompoundMappingSource::Osc(s) => s.arg_descriptor(),
+            _ => None,
+        };
+        let delta_arg = match (arg_descriptor, numeric_value) {
+            (Some(d), Some(current)) => {
+                let previous = self.last_osc_feedback_value.replace(Some(current));
+                // Without a previous value, we can't know the delta. Just remember the value.
+                previous.and_then(|p| {
+                    let arg = settings.feedback_arg(p, current, d.type_tag())?;
+                    Some((d.index() as usize, arg))
+                })
+            }
+            _ => None,
+        };
+        match delta_arg {
+            Some((index, arg)) if index < msg.args.len() => {
+                msg.args[index] = arg;
+                value.source_is_relative = true;
+            }
+            _ => {
+                value.source = None;
+            }
+        }
+    }
+
+    /// Sends feedback of templated OSC sources to the address which has been used last.
+    fn resolve_osc_feedback_address_if_necessary(&self, value: &mut RealFeedbackValue) {
+        let pattern = match &self.osc_address_pattern {
+            None => return,
+            Some(p) => p,
+        };
+        let msg = match &mut value.source {
+            Some(SourceFeedbackValue::Osc(msg)) => msg,
+            _ => return,
+        };
+        match pattern.format(&self.osc_captures) {
+            Some(addr) => {
+                msg.addr = addr;
+            }
+            None => {
+                value.source = None;
+            }
+        }
     }
 
     /// This returns a "lights off" feedback.
@@ -1104,7 +1648,38 @@ impl MainMapping {
 
     pub fn control(&mut self, msg: MainSourceMessage) -> Option<ControlValue> {
         match (msg, &self.core.source) {
-            (MainSourceMessage::Osc(m), CompoundMappingSource::Osc(s)) => s.control(m),
+            (MainSourceMessage::Osc(m), CompoundMappingSource::Osc(s)) => {
+                if !osc_arg_filters_match(&self.osc_arg_filters, m) {
+                    return None;
+                }
+                let control_value = match &self.osc_address_pattern {
+                    None => s.control(m)?,
+                    Some(p) => {
+                        let captures = p.matches(&m.addr)?;
+                        // The library compares addresses literally, so we pretend that the
+                        // message was sent to the pattern address.
+                        let normalized_msg = OscMessage {
+                            addr: s.address_pattern().to_owned(),
+                            args: m.args.clone(),
+                        };
+                        let control_value = s.control(&normalized_msg)?;
+                        if captures != self.osc_captures {
+                            self.osc_captures = captures;
+                            self.osc_captures_changed = true;
+                        }
+                        control_value
+                    }
+                };
+                match self.core.options.osc_delta {
+                    Some(d) if d.mode != OscDeltaMode::Sign => {
+                        // The library only knows about the sign, so we interpret the argument
+                        // ourselves.
+                        let arg_index = s.arg_descriptor()?.index();
+                        d.interpret(m.args.get(arg_index as usize)?)
+                    }
+                    _ => Some(control_value),
+                }
+            }
             (MainSourceMessage::Reaper(m), CompoundMappingSource::Reaper(s)) => s.control(m),
             _ => None,
         }
@@ -1218,6 +1793,37 @@ impl RealTimeMapping {
         self.core.options.persistent_processing_state = state;
     }
 
+    /// Should be called when this mapping replaces the given one with the same ID.
+    ///
+    /// Chord state lives in the "Send MIDI" target only, so it would be lost on resync and the
+    /// chord notes would never be switched off.
+    pub fn take_over_held_chord(&mut self, previous: &mut RealTimeMapping) {
+        use RealTimeCompoundMappingTarget::Reaper;
+        use RealTimeReaperTarget::*;
+        match (
+            self.resolved_target.as_mut(),
+            previous.resolved_target.as_mut(),
+        ) {
+            (Some(Reaper(SendMidi(t))), Some(Reaper(SendMidi(previous_t)))) => {
+                t.take_over_held_chord(previous_t);
+            }
+            (Some(Reaper(Multi(t))), Some(Reaper(Multi(previous_t)))) => {
+                for (c, previous_c) in t.children.iter_mut().zip(&mut previous_t.children) {
+                    c.target.take_over_held_chord(&mut previous_c.target);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub fn update_soloed_mapping(&mut self, soloed_mapping: Option<QualifiedMappingId>) {
+        self.core.update_soloed_mapping(soloed_mapping);
+    }
+
+    pub fn update_active_layer(&mut self, active_layer: LayerId) {
+        self.core.update_active_layer(active_layer);
+    }
+
     pub fn update_target_activation(&mut self, is_active: bool) {
         self.core.options.target_is_active = is_active;
     }
@@ -1282,9 +1888,41 @@ pub struct MappingCore {
     group_interaction: GroupInteraction,
     options: ProcessorMappingOptions,
     time_of_last_control: Option<Instant>,
+    noise_filter: NoiseFilter,
+    velocity_threshold_filter: VelocityThresholdFilter,
 }
 
 impl MappingCore {
+    fn update_soloed_mapping(&mut self, soloed_mapping: Option<QualifiedMappingId>) {
+        self.options.control_is_muted_by_solo = match soloed_mapping {
+            Some(id) => id.compartment == self.compartment && id.id != self.id,
+            None => false,
+        };
+    }
+
+    fn update_active_layer(&mut self, active_layer: LayerId) {
+        self.options.control_is_muted_by_layer = match self.options.layer {
+            Some(layer) => layer != active_layer,
+            None => false,
+        };
+    }
+
+    fn update_missing_devices(
+        &mut self,
+        status: &IoConnectionStatus,
+        control_input: Option<MidiInputDeviceId>,
+        feedback_output: Option<MidiOutputDeviceId>,
+    ) {
+        // Only MIDI sources talk to MIDI devices directly.
+        let is_midi = matches!(self.source, CompoundMappingSource::Midi(_));
+        let input = self.options.midi_input_device.or(control_input);
+        let output = self.options.midi_output_device.or(feedback_output);
+        self.options.control_is_suspended_by_missing_device =
+            is_midi && input.map_or(false, |id| status.midi_input_device_is_missing(id));
+        self.options.feedback_is_suspended_by_missing_device =
+            is_midi && output.map_or(false, |id| status.midi_output_device_is_missing(id));
+    }
+
     fn is_echo(&self) -> bool {
         if let Some(t) = self.time_of_last_control {
             t.elapsed() <= MAX_ECHO_FEEDBACK_DELAY
@@ -1298,6 +1936,22 @@ impl MappingCore {
             enforce_rotate: self.mode.settings().rotate,
         }
     }
+
+    /// Returns `None` if the control value should be ignored because of the velocity threshold.
+    pub fn process_velocity_threshold(&mut self, value: ControlValue) -> Option<ControlValue> {
+        match &self.options.velocity_threshold {
+            None => Some(value),
+            Some(t) => self.velocity_threshold_filter.process(t, value),
+        }
+    }
+
+    /// Returns `None` if the control value should be ignored because it's considered as jitter.
+    pub fn process_noise_filter(&mut self, value: ControlValue) -> Option<ControlValue> {
+        match &self.options.noise_filter {
+            None => Some(value),
+            Some(settings) => self.noise_filter.process(settings, value),
+        }
+    }
 }
 
 // PartialEq because we want to put it into a Prop.
@@ -1322,11 +1976,13 @@ pub struct QualifiedSource {
     pub compartment: MappingCompartment,
     pub mapping_key: Rc<str>,
     pub source: CompoundMappingSource,
+    pub sysex_checksum: Option<SysExChecksum>,
+    pub midi_output_device: Option<MidiOutputDeviceId>,
 }
 
 impl QualifiedSource {
     pub fn off_feedback(self) -> Option<CompoundFeedbackValue> {
-        SpecificCompoundFeedbackValue::from_mode_value(
+        let mut value = SpecificCompoundFeedbackValue::from_mode_value(
             self.compartment,
             self.mapping_key,
             &self.source,
@@ -1335,8 +1991,21 @@ impl QualifiedSource {
                 with_projection_feedback: true,
                 with_source_feedback: true,
             },
-        )
-        .map(CompoundFeedbackValue::normal)
+        )?;
+        if let (
+            Some(checksum),
+            SpecificCompoundFeedbackValue::Real(RealFeedbackValue {
+                source: Some(source_value),
+                ..
+            }),
+        ) = (&self.sysex_checksum, &mut value)
+        {
+            checksum.apply_to_feedback_value(source_value);
+        }
+        if let SpecificCompoundFeedbackValue::Real(v) = &mut value {
+            v.midi_output_device = self.midi_output_device;
+        }
+        Some(CompoundFeedbackValue::normal(value))
     }
 }
 
@@ -1565,10 +2234,7 @@ impl SpecificCompoundFeedbackValue {
             let projection = if destinations.with_projection_feedback
                 && compartment == MappingCompartment::ControllerMappings
             {
-                // TODO-medium Support textual projection feedback
-                mode_value.to_numeric().map(|v| {
-                    ProjectionFeedbackValue::new(compartment, mapping_key, v.value.to_unit_value())
-                })
+                ProjectionFeedbackValue::from_mode_value(compartment, mapping_key, &mode_value)
             } else {
                 None
             };
@@ -1595,6 +2261,16 @@ pub struct RealFeedbackValue {
     /// This is an option because there are situations when we don't want source feedback but
     /// projection feedback (e.g. if "MIDI feedback output" is set to None).
     pub source: Option<SourceFeedbackValue>,
+    /// Whether the source feedback is a delta, not an absolute value.
+    ///
+    /// Two equal deltas in a row must both be sent, so they are excluded from duplicate blocking.
+    pub source_is_relative: bool,
+    /// Set if the source feedback should be suppressed if it's too close to the previously sent
+    /// value.
+    pub deadband: Option<FeedbackDeadband>,
+    /// Set if the source feedback should be sent to this MIDI output device instead of the
+    /// instance's feedback output.
+    pub midi_output_device: Option<MidiOutputDeviceId>,
 }
 
 impl RealFeedbackValue {
@@ -1605,7 +2281,13 @@ impl RealFeedbackValue {
         if projection.is_none() && source.is_none() {
             return None;
         }
-        let val = Self { projection, source };
+        let val = Self {
+            projection,
+            source,
+            source_is_relative: false,
+            deadband: None,
+            midi_output_device: None,
+        };
         Some(val)
     }
 }
@@ -1614,7 +2296,12 @@ impl RealFeedbackValue {
 pub struct ProjectionFeedbackValue {
     pub compartment: MappingCompartment,
     pub mapping_key: Rc<str>,
-    pub value: UnitValue,
+    /// `None` if the mapping sends textual feedback.
+    pub value: Option<UnitValue>,
+    /// Only set if the mapping sends textual feedback.
+    pub text: Option<String>,
+    /// Number of possible values if the value is discrete (useful for drawing segmented arcs).
+    pub step_count: Option<u32>,
 }
 
 impl ProjectionFeedbackValue {
@@ -1622,8 +2309,41 @@ impl ProjectionFeedbackValue {
         Self {
             compartment,
             mapping_key,
-            value,
+            value: Some(value),
+            text: None,
+            step_count: None,
+        }
+    }
+
+    pub fn from_mode_value(
+        compartment: MappingCompartment,
+        mapping_key: Rc<str>,
+        mode_value: &FeedbackValue,
+    ) -> Option<Self> {
+        if let FeedbackValue::Textual(v) = mode_value {
+            let value = Self {
+                compartment,
+                mapping_key,
+                value: None,
+                text: Some(v.text.to_string()),
+                step_count: None,
+            };
+            return Some(value);
         }
+        let numeric_value = mode_value.to_numeric()?;
+        let step_count = match numeric_value.value {
+            AbsoluteValue::Continuous(_) => None,
+            AbsoluteValue::Discrete(f) => Some(f.max_val() + 1),
+        };
+        let value = Self {
+            step_count,
+            ..Self::new(
+                compartment,
+                mapping_key,
+                numeric_value.value.to_unit_value(),
+            )
+        };
+        Some(value)
     }
 }
 
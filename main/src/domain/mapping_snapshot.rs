@@ -0,0 +1,238 @@
+use crate::domain::{MappingCompartment, MappingId, QualifiedMappingId};
+use enum_map::EnumMap;
+use helgoboss_learn::{AbsoluteValue, UnitValue};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Target values of a set of mappings, captured by target "ReaLearn: Save mapping snapshot".
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MappingSnapshot {
+    values: HashMap<MappingId, AbsoluteValue>,
+}
+
+impl MappingSnapshot {
+    pub fn new(values: HashMap<MappingId, AbsoluteValue>) -> Self {
+        Self { values }
+    }
+
+    pub fn value(&self, mapping_id: MappingId) -> Option<AbsoluteValue> {
+        self.values.get(&mapping_id).copied()
+    }
+}
+
+/// Named mapping snapshots of one instance plus the snapshot recalls which are currently fading.
+#[derive(Debug, Default)]
+pub struct MappingSnapshotContainer {
+    snapshots: EnumMap<MappingCompartment, HashMap<String, MappingSnapshot>>,
+    fades: Vec<MappingSnapshotFade>,
+}
+
+#[derive(Debug)]
+struct MappingSnapshotFade {
+    compartment: MappingCompartment,
+    start: Instant,
+    duration: Duration,
+    entries: Vec<MappingSnapshotFadeEntry>,
+}
+
+/// Describes how the target value of one mapping should change while recalling a snapshot.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct MappingSnapshotFadeEntry {
+    pub mapping_id: MappingId,
+    pub from: AbsoluteValue,
+    pub to: AbsoluteValue,
+}
+
+impl MappingSnapshotContainer {
+    pub fn find_snapshot(
+        &self,
+        compartment: MappingCompartment,
+        snapshot_id: &str,
+    ) -> Option<&MappingSnapshot> {
+        self.snapshots[compartment].get(snapshot_id)
+    }
+
+    /// Replaces the snapshot with the given ID.
+    pub fn save_snapshot(
+        &mut self,
+        compartment: MappingCompartment,
+        snapshot_id: String,
+        snapshot: MappingSnapshot,
+    ) {
+        self.snapshots[compartment].insert(snapshot_id, snapshot);
+    }
+
+    pub fn is_fading(&self) -> bool {
+        !self.fades.is_empty()
+    }
+
+    /// Starts fading the target values of the given mappings.
+    ///
+    /// Mappings which are still part of a previously started fade are taken over by the new one.
+    pub fn start_fade(
+        &mut self,
+        compartment: MappingCompartment,
+        entries: Vec<MappingSnapshotFadeEntry>,
+        duration: Duration,
+        now: Instant,
+    ) {
+        for fade in self
+            .fades
+            .iter_mut()
+            .filter(|f| f.compartment == compartment)
+        {
+            fade.entries
+                .retain(|e| !entries.iter().any(|new| new.mapping_id == e.mapping_id));
+        }
+        self.fades.retain(|f| !f.entries.is_empty());
+        if entries.is_empty() {
+            return;
+        }
+        let fade = MappingSnapshotFade {
+            compartment,
+            start: now,
+            duration,
+            entries,
+        };
+        self.fades.push(fade);
+    }
+
+    /// Returns the target values which should be applied now.
+    ///
+    /// Continuous values are interpolated, discrete ones are applied when the fade is finished.
+    /// Finished fades are removed.
+    pub fn advance_fades(&mut self, now: Instant) -> Vec<(QualifiedMappingId, AbsoluteValue)> {
+        let mut values = vec![];
+        self.fades.retain(|fade| {
+            let progress = fade.progress(now);
+            let finished = progress >= 1.0;
+            for e in &fade.entries {
+                let value = if finished {
+                    e.to
+                } else {
+                    match (e.from, e.to) {
+                        (AbsoluteValue::Continuous(from), AbsoluteValue::Continuous(to)) => {
+                            let v = from.get() + (to.get() - from.get()) * progress;
+                            AbsoluteValue::Continuous(UnitValue::new_clamped(v))
+                        }
+                        _ => continue,
+                    }
+                };
+                values.push((
+                    QualifiedMappingId::new(fade.compartment, e.mapping_id),
+                    value,
+                ));
+            }
+            !finished
+        });
+        values
+    }
+}
+
+impl MappingSnapshotFade {
+    fn progress(&self, now: Instant) -> f64 {
+        if self.duration.as_secs_f64() == 0.0 {
+            return 1.0;
+        }
+        let elapsed = now.saturating_duration_since(self.start);
+        (elapsed.as_secs_f64() / self.duration.as_secs_f64()).min(1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use helgoboss_learn::Fraction;
+
+    fn continuous(v: f64) -> AbsoluteValue {
+        AbsoluteValue::Continuous(UnitValue::new(v))
+    }
+
+    #[test]
+    fn interpolate_continuous_values() {
+        // Given
+        let mut container = MappingSnapshotContainer::default();
+        let mapping_id = MappingId::random();
+        let start = Instant::now();
+        // When
+        container.start_fade(
+            MappingCompartment::MainMappings,
+            vec![MappingSnapshotFadeEntry {
+                mapping_id,
+                from: continuous(0.2),
+                to: continuous(0.6),
+            }],
+            Duration::from_secs(1),
+            start,
+        );
+        let halfway = container.advance_fades(start + Duration::from_millis(500));
+        let end = container.advance_fades(start + Duration::from_secs(2));
+        // Then
+        let id = QualifiedMappingId::new(MappingCompartment::MainMappings, mapping_id);
+        assert_eq!(halfway.len(), 1);
+        assert_eq!(halfway[0].0, id);
+        match halfway[0].1 {
+            AbsoluteValue::Continuous(v) => assert!((v.get() - 0.4).abs() < 0.0001),
+            _ => panic!("expected continuous value"),
+        }
+        assert_eq!(end, vec![(id, continuous(0.6))]);
+        assert!(!container.is_fading());
+    }
+
+    #[test]
+    fn apply_discrete_values_at_end() {
+        // Given
+        let mut container = MappingSnapshotContainer::default();
+        let mapping_id = MappingId::random();
+        let start = Instant::now();
+        let to = AbsoluteValue::Discrete(Fraction::new(3, 10));
+        // When
+        container.start_fade(
+            MappingCompartment::MainMappings,
+            vec![MappingSnapshotFadeEntry {
+                mapping_id,
+                from: AbsoluteValue::Discrete(Fraction::new(1, 10)),
+                to,
+            }],
+            Duration::from_secs(1),
+            start,
+        );
+        let halfway = container.advance_fades(start + Duration::from_millis(500));
+        let end = container.advance_fades(start + Duration::from_secs(1));
+        // Then
+        assert!(halfway.is_empty());
+        assert_eq!(end.len(), 1);
+        assert_eq!(end[0].1, to);
+    }
+
+    #[test]
+    fn new_fade_takes_over_mapping() {
+        // Given
+        let mut container = MappingSnapshotContainer::default();
+        let mapping_id = MappingId::random();
+        let start = Instant::now();
+        let entry = |to: f64| MappingSnapshotFadeEntry {
+            mapping_id,
+            from: continuous(0.0),
+            to: continuous(to),
+        };
+        container.start_fade(
+            MappingCompartment::MainMappings,
+            vec![entry(1.0)],
+            Duration::from_secs(10),
+            start,
+        );
+        // When
+        container.start_fade(
+            MappingCompartment::MainMappings,
+            vec![entry(0.5)],
+            Duration::from_secs(1),
+            start,
+        );
+        let values = container.advance_fades(start + Duration::from_secs(1));
+        // Then
+        assert_eq!(values.len(), 1);
+        assert_eq!(values[0].1, continuous(0.5));
+        assert!(!container.is_fading());
+    }
+}
@@ -0,0 +1,312 @@
+use derive_more::Display;
+use enum_iterator::IntoEnumIterator;
+use serde::{Deserialize, Serialize};
+
+const TIMING_CLOCK: u8 = 0xF8;
+const START: u8 = 0xFA;
+const CONTINUE: u8 = 0xFB;
+const STOP: u8 = 0xFC;
+const SONG_POSITION_POINTER: u8 = 0xF2;
+const MTC_QUARTER_FRAME: u8 = 0xF1;
+
+/// MIDI clock resolution as defined by the MIDI spec.
+const CLOCKS_PER_QUARTER_NOTE: i64 = 24;
+/// The song position pointer counts in 16th notes.
+const CLOCKS_PER_SIXTEENTH_NOTE: i64 = 6;
+/// The song position pointer has 14 bits.
+const MAX_SONG_POSITION: i64 = 0x3FFF;
+/// A position deviating more than this from the expected one is treated as a jump.
+const RELOCATION_TOLERANCE_IN_BEATS: f64 = 1.0 / CLOCKS_PER_QUARTER_NOTE as f64;
+
+#[derive(
+    Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize, IntoEnumIterator, Display,
+)]
+pub enum MtcFrameRate {
+    #[display(fmt = "24 fps")]
+    Fps24,
+    #[display(fmt = "25 fps")]
+    Fps25,
+    #[display(fmt = "30 fps")]
+    Fps30,
+}
+
+impl MtcFrameRate {
+    fn fps(self) -> i64 {
+        use MtcFrameRate::*;
+        match self {
+            Fps24 => 24,
+            Fps25 => 25,
+            Fps30 => 30,
+        }
+    }
+
+    /// Rate code as encoded in bits 5 and 6 of the MTC hours byte.
+    fn rate_code(self) -> u8 {
+        use MtcFrameRate::*;
+        match self {
+            Fps24 => 0,
+            Fps25 => 1,
+            Fps30 => 3,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub struct MidiClockSettings {
+    /// If set, MIDI time code is sent in addition to MIDI clock.
+    pub mtc_frame_rate: Option<MtcFrameRate>,
+}
+
+/// State of the transport during one audio block.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct TransportBlock {
+    pub is_playing: bool,
+    /// Position in quarter notes at the start of the block.
+    pub start_beats: f64,
+    /// Position in quarter notes at the end of the block.
+    pub end_beats: f64,
+    pub start_secs: f64,
+    pub end_secs: f64,
+    pub frame_count: u32,
+}
+
+/// Generates MIDI clock and MIDI time code which follows the transport.
+///
+/// Works with beat positions instead of the tempo, so tempo changes within the project are
+/// reflected automatically.
+#[derive(Clone, Debug)]
+pub struct MidiClockGenerator {
+    settings: MidiClockSettings,
+    was_playing: bool,
+    expected_start_beats: f64,
+    next_clock_index: i64,
+    next_quarter_frame_index: i64,
+}
+
+impl MidiClockGenerator {
+    pub fn new(settings: MidiClockSettings) -> Self {
+        Self {
+            settings,
+            was_playing: false,
+            expected_start_beats: 0.0,
+            next_clock_index: 0,
+            next_quarter_frame_index: 0,
+        }
+    }
+
+    pub fn settings(&self) -> MidiClockSettings {
+        self.settings
+    }
+
+    /// Generates the messages for the given block and passes each one together with its frame
+    /// offset within the block to `emit`.
+    ///
+    /// Doesn't allocate, so it can be called in the audio thread.
+    pub fn process(&mut self, block: &TransportBlock, mut emit: impl FnMut(u32, &[u8])) {
+        if !block.is_playing {
+            if self.was_playing {
+                emit(0, &[STOP]);
+                self.was_playing = false;
+            }
+            return;
+        }
+        if !self.was_playing {
+            self.start(block, &mut emit);
+        } else if (block.start_beats - self.expected_start_beats).abs()
+            > RELOCATION_TOLERANCE_IN_BEATS
+        {
+            // Jumped (e.g. seek or loop). Let the receiver relocate as well.
+            emit(0, &[STOP]);
+            self.start(block, &mut emit);
+        }
+        self.was_playing = true;
+        self.expected_start_beats = block.end_beats;
+        loop {
+            let beats = self.next_clock_index as f64 / CLOCKS_PER_QUARTER_NOTE as f64;
+            if beats >= block.end_beats {
+                break;
+            }
+            let offset = frame_offset(
+                beats - block.start_beats,
+                block.end_beats - block.start_beats,
+                block.frame_count,
+            );
+            emit(offset, &[TIMING_CLOCK]);
+            self.next_clock_index += 1;
+        }
+        if let Some(rate) = self.settings.mtc_frame_rate {
+            let quarter_frames_per_sec = (rate.fps() * 4) as f64;
+            loop {
+                let secs = self.next_quarter_frame_index as f64 / quarter_frames_per_sec;
+                if secs >= block.end_secs {
+                    break;
+                }
+                let offset = frame_offset(
+                    secs - block.start_secs,
+                    block.end_secs - block.start_secs,
+                    block.frame_count,
+                );
+                let data = quarter_frame_data(self.next_quarter_frame_index, rate);
+                emit(offset, &[MTC_QUARTER_FRAME, data]);
+                self.next_quarter_frame_index += 1;
+            }
+        }
+    }
+
+    /// Stops the receiver if it's currently running.
+    pub fn stop(&mut self, mut emit: impl FnMut(&[u8])) {
+        if self.was_playing {
+            emit(&[STOP]);
+            self.was_playing = false;
+        }
+    }
+
+    /// Sends the song position and start or continue.
+    fn start(&mut self, block: &TransportBlock, emit: &mut impl FnMut(u32, &[u8])) {
+        let song_position = ((block.start_beats.max(0.0) * CLOCKS_PER_QUARTER_NOTE as f64) as i64
+            / CLOCKS_PER_SIXTEENTH_NOTE)
+            .min(MAX_SONG_POSITION);
+        emit(
+            0,
+            &[
+                SONG_POSITION_POINTER,
+                (song_position & 0x7F) as u8,
+                (song_position >> 7) as u8,
+            ],
+        );
+        let msg = if song_position == 0 { START } else { CONTINUE };
+        emit(0, &[msg]);
+        // The receiver continues at the position pointed to with the next clock.
+        self.next_clock_index = song_position * CLOCKS_PER_SIXTEENTH_NOTE;
+        if let Some(rate) = self.settings.mtc_frame_rate {
+            let frame = (block.start_secs.max(0.0) * rate.fps() as f64).ceil() as i64;
+            // A full quarter frame sequence spans 2 frames.
+            let frame = frame + frame % 2;
+            let (h, m, s, f) = split_frame(frame, rate);
+            emit(
+                0,
+                &[
+                    0xF0,
+                    0x7F,
+                    0x7F,
+                    0x01,
+                    0x01,
+                    (rate.rate_code() << 5) | h,
+                    m,
+                    s,
+                    f,
+                    0xF7,
+                ],
+            );
+            self.next_quarter_frame_index = frame * 4;
+        }
+    }
+}
+
+fn frame_offset(delta: f64, span: f64, frame_count: u32) -> u32 {
+    if span <= 0.0 || frame_count == 0 {
+        return 0;
+    }
+    let offset = (delta / span * frame_count as f64).max(0.0) as u32;
+    offset.min(frame_count - 1)
+}
+
+fn quarter_frame_data(quarter_frame_index: i64, rate: MtcFrameRate) -> u8 {
+    let piece = quarter_frame_index % 8;
+    // Each sequence of 8 pieces transmits the time of the frame at which it started.
+    let (h, m, s, f) = split_frame((quarter_frame_index - piece) / 4, rate);
+    let nibble = match piece {
+        0 => f & 0x0F,
+        1 => f >> 4,
+        2 => s & 0x0F,
+        3 => s >> 4,
+        4 => m & 0x0F,
+        5 => m >> 4,
+        6 => h & 0x0F,
+        _ => (h >> 4) | (rate.rate_code() << 1),
+    };
+    ((piece as u8) << 4) | nibble
+}
+
+/// Splits the given frame number into hours, minutes, seconds and frames.
+fn split_frame(frame: i64, rate: MtcFrameRate) -> (u8, u8, u8, u8) {
+    let fps = rate.fps();
+    let f = frame % fps;
+    let total_secs = frame / fps;
+    let s = total_secs % 60;
+    let m = (total_secs / 60) % 60;
+    let h = (total_secs / 3600) % 24;
+    (h as u8, m as u8, s as u8, f as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(is_playing: bool, start_beats: f64, end_beats: f64) -> TransportBlock {
+        // 120 bpm
+        TransportBlock {
+            is_playing,
+            start_beats,
+            end_beats,
+            start_secs: start_beats / 2.0,
+            end_secs: end_beats / 2.0,
+            frame_count: 1000,
+        }
+    }
+
+    fn collect(generator: &mut MidiClockGenerator, block: TransportBlock) -> Vec<(u32, Vec<u8>)> {
+        let mut messages = vec![];
+        generator.process(&block, |offset, bytes| {
+            messages.push((offset, bytes.to_vec()))
+        });
+        messages
+    }
+
+    #[test]
+    fn start_and_stop() {
+        // Given
+        let mut generator = MidiClockGenerator::new(Default::default());
+        // When
+        let started = collect(&mut generator, block(true, 0.0, 1.0));
+        let stopped = collect(&mut generator, block(false, 1.0, 1.0));
+        // Then
+        assert_eq!(started[0], (0, vec![SONG_POSITION_POINTER, 0, 0]));
+        assert_eq!(started[1], (0, vec![START]));
+        let clocks: Vec<_> = started[2..].iter().map(|(offset, _)| *offset).collect();
+        assert_eq!(clocks.len(), 24);
+        assert_eq!(clocks[0], 0);
+        assert_eq!(clocks[12], 500);
+        assert_eq!(stopped, vec![(0, vec![STOP])]);
+    }
+
+    #[test]
+    fn relocate() {
+        // Given
+        let mut generator = MidiClockGenerator::new(Default::default());
+        collect(&mut generator, block(true, 0.0, 1.0));
+        // When
+        let continued = collect(&mut generator, block(true, 1.0, 2.0));
+        let relocated = collect(&mut generator, block(true, 8.0, 9.0));
+        // Then
+        assert_eq!(continued.len(), 24);
+        assert_eq!(relocated[0], (0, vec![STOP]));
+        assert_eq!(relocated[1], (0, vec![SONG_POSITION_POINTER, 32, 0]));
+        assert_eq!(relocated[2], (0, vec![CONTINUE]));
+        assert_eq!(relocated.len(), 3 + 24);
+    }
+
+    #[test]
+    fn mtc_quarter_frames() {
+        // Given
+        let rate = MtcFrameRate::Fps25;
+        // 01:02:03:04
+        let frame = ((3600 + 2 * 60 + 3) * 25 + 4) as i64;
+        // When
+        let pieces: Vec<_> = (0..8)
+            .map(|i| quarter_frame_data(frame * 4 + i, rate))
+            .collect();
+        // Then
+        assert_eq!(pieces, vec![0x04, 0x10, 0x23, 0x30, 0x42, 0x50, 0x61, 0x72]);
+    }
+}
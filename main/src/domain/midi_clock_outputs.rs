@@ -0,0 +1,84 @@
+use crate::domain::{
+    BackboneState, FeedbackAudioHookTask, InstanceId, MappingId, MidiClockSettings, RealTimeSender,
+};
+use reaper_medium::MidiOutputDeviceId;
+
+/// MIDI clock outputs which have been switched on by the mappings of one instance.
+///
+/// The clock is generated in the audio hook, which doesn't know anything about instances and
+/// mappings. Without this, the clock would keep running after the mapping which switched it on
+/// has been removed or the instance has been unloaded.
+#[derive(Debug, Default)]
+pub struct MidiClockOutputs {
+    outputs: Vec<MidiClockOutput>,
+}
+
+#[derive(Debug)]
+struct MidiClockOutput {
+    owner: MappingId,
+    instance_id: InstanceId,
+    dev_id: MidiOutputDeviceId,
+    /// Kept for stopping the clock when the owner goes away.
+    sender: RealTimeSender<FeedbackAudioHookTask>,
+}
+
+impl MidiClockOutput {
+    fn stop(&self) {
+        // Another instance might have switched the clock on again in the meantime. Then it's
+        // not ours anymore.
+        let backbone_state = BackboneState::get();
+        if backbone_state.midi_clock_output_owner(self.dev_id) != Some(self.instance_id) {
+            return;
+        }
+        // If this fails, the audio hook is gone anyway.
+        let _ = self
+            .sender
+            .send(FeedbackAudioHookTask::SetMidiClockOutput(self.dev_id, None));
+        backbone_state.set_midi_clock_output(self.dev_id, self.instance_id, None);
+    }
+}
+
+impl MidiClockOutputs {
+    /// Starts (`Some`) or stops (`None`) generating MIDI clock on the given device.
+    pub fn set(
+        &mut self,
+        owner: MappingId,
+        instance_id: InstanceId,
+        dev_id: MidiOutputDeviceId,
+        settings: Option<MidiClockSettings>,
+        sender: &RealTimeSender<FeedbackAudioHookTask>,
+    ) -> Result<(), &'static str> {
+        sender
+            .send(FeedbackAudioHookTask::SetMidiClockOutput(dev_id, settings))
+            .map_err(|_| "couldn't send MIDI clock task to audio hook")?;
+        BackboneState::get().set_midi_clock_output(dev_id, instance_id, settings);
+        self.outputs.retain(|o| o.dev_id != dev_id);
+        if settings.is_some() {
+            let output = MidiClockOutput {
+                owner,
+                instance_id,
+                dev_id,
+                sender: sender.clone(),
+            };
+            self.outputs.push(output);
+        }
+        Ok(())
+    }
+
+    /// Stops the clocks of all owners for which `keep` returns `false`.
+    pub fn retain_owners(&mut self, mut keep: impl FnMut(MappingId) -> bool) {
+        self.outputs.retain(|o| {
+            let is_kept = keep(o.owner);
+            if !is_kept {
+                o.stop();
+            }
+            is_kept
+        });
+    }
+}
+
+impl Drop for MidiClockOutputs {
+    fn drop(&mut self) {
+        self.retain_owners(|_| false);
+    }
+}
@@ -0,0 +1,22 @@
+use helgoboss_learn::RawMidiEvent;
+use std::collections::HashMap;
+
+/// Named sequences of raw MIDI messages defined by the active controller preset.
+///
+/// Typical examples are initialization sequences, mode switches or backlight settings of a
+/// controller. They can be sent by mapping lifecycle hooks and by the "MIDI: Send macro" target.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MidiMacros {
+    macros: HashMap<String, Vec<RawMidiEvent>>,
+}
+
+impl MidiMacros {
+    pub fn new(macros: HashMap<String, Vec<RawMidiEvent>>) -> Self {
+        Self { macros }
+    }
+
+    /// Returns the messages of the macro with the given name.
+    pub fn get(&self, name: &str) -> Option<&[RawMidiEvent]> {
+        self.macros.get(name).map(|m| m.as_slice())
+    }
+}
@@ -1,3 +1,255 @@
 use crate::domain::EelMidiSourceScript;
+use derive_more::Display;
+use enum_iterator::IntoEnumIterator;
+use helgoboss_learn::{ControlValue, DetailedSourceCharacter, SourceCharacter, UnitValue};
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+use serde::{Deserialize, Serialize};
+use std::fmt;
 
 pub type MidiSource = helgoboss_learn::MidiSource<EelMidiSourceScript>;
+
+/// Device ID which addresses all devices.
+const MMC_ALL_CALL_DEVICE_ID: u8 = 0x7f;
+/// Sub-ID #1 of MMC commands (sent from controller to machine).
+const MMC_COMMAND_SUB_ID: u8 = 0x06;
+const MMC_LOCATE_COMMAND: u8 = 0x44;
+/// Locate position normalization. The time code can't go beyond 24 hours.
+const SECONDS_PER_DAY: f64 = 24.0 * 60.0 * 60.0;
+
+/// Reacts to MIDI Machine Control (MMC) commands as sent by old hardware transports.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct MmcSource {
+    pub command: MmcCommand,
+    /// If `None`, commands addressed to any device are accepted.
+    pub device_id: Option<u8>,
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    Hash,
+    Debug,
+    Serialize,
+    Deserialize,
+    IntoEnumIterator,
+    TryFromPrimitive,
+    IntoPrimitive,
+    Display,
+)]
+#[repr(usize)]
+pub enum MmcCommand {
+    #[display(fmt = "Stop")]
+    Stop,
+    #[display(fmt = "Play")]
+    Play,
+    #[display(fmt = "Deferred play")]
+    DeferredPlay,
+    #[display(fmt = "Fast forward")]
+    FastForward,
+    #[display(fmt = "Rewind")]
+    Rewind,
+    #[display(fmt = "Record strobe")]
+    RecordStrobe,
+    #[display(fmt = "Record exit")]
+    RecordExit,
+    #[display(fmt = "Pause")]
+    Pause,
+    /// Jump to a time code position.
+    #[display(fmt = "Locate")]
+    Locate,
+}
+
+impl Default for MmcCommand {
+    fn default() -> Self {
+        MmcCommand::Play
+    }
+}
+
+impl MmcCommand {
+    fn from_byte(byte: u8) -> Option<Self> {
+        use MmcCommand::*;
+        let cmd = match byte {
+            0x01 => Stop,
+            0x02 => Play,
+            0x03 => DeferredPlay,
+            0x04 => FastForward,
+            0x05 => Rewind,
+            0x06 => RecordStrobe,
+            0x07 => RecordExit,
+            0x09 => Pause,
+            MMC_LOCATE_COMMAND => Locate,
+            _ => return None,
+        };
+        Some(cmd)
+    }
+}
+
+/// A parsed MMC sys-ex message.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct MmcMessage {
+    pub device_id: u8,
+    pub command: MmcCommand,
+    /// Only set for locate commands.
+    pub locate_position: Option<MmcTimeCode>,
+}
+
+/// Time code position of an MMC locate command.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct MmcTimeCode {
+    pub frames_per_second: u8,
+    pub hours: u8,
+    pub minutes: u8,
+    pub seconds: u8,
+    pub frames: u8,
+}
+
+impl MmcTimeCode {
+    pub fn to_seconds(&self) -> f64 {
+        (self.hours as u32 * 3600 + self.minutes as u32 * 60 + self.seconds as u32) as f64
+            + self.frames as f64 / self.frames_per_second as f64
+    }
+}
+
+impl MmcMessage {
+    /// Parses a complete sys-ex message (including start and end byte).
+    ///
+    /// Returns `None` if it's not an MMC command or if the command is not supported.
+    pub fn parse(bytes: &[u8]) -> Option<Self> {
+        match bytes {
+            [0xf0, 0x7f, device_id, MMC_COMMAND_SUB_ID, command, rest @ ..] => {
+                let command = MmcCommand::from_byte(*command)?;
+                let locate_position = if command == MmcCommand::Locate {
+                    Some(parse_locate_target(rest)?)
+                } else {
+                    if rest != [0xf7] {
+                        return None;
+                    }
+                    None
+                };
+                let msg = MmcMessage {
+                    device_id: *device_id,
+                    command,
+                    locate_position,
+                };
+                Some(msg)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for MmcMessage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "MMC {}", self.command)?;
+        if let Some(pos) = self.locate_position {
+            write!(
+                f,
+                " to {:02}:{:02}:{:02}:{:02}",
+                pos.hours, pos.minutes, pos.seconds, pos.frames
+            )?;
+        }
+        write!(f, " (device ID {})", self.device_id)
+    }
+}
+
+/// Parses the remainder of a "Locate" command of type "TARGET".
+fn parse_locate_target(bytes: &[u8]) -> Option<MmcTimeCode> {
+    match bytes {
+        [0x06, 0x01, hr, mn, sc, fr, _subframes, 0xf7] => {
+            // Bits 5 and 6 of the hour byte contain the frame rate.
+            let frames_per_second = match (hr >> 5) & 0b11 {
+                0 => 24,
+                1 => 25,
+                // 30 drop frame and 30 non-drop
+                _ => 30,
+            };
+            let time_code = MmcTimeCode {
+                frames_per_second,
+                hours: hr & 0b1_1111,
+                minutes: *mn,
+                seconds: *sc,
+                frames: fr & 0b1_1111,
+            };
+            Some(time_code)
+        }
+        _ => None,
+    }
+}
+
+impl MmcSource {
+    pub fn possible_detailed_characters(&self) -> Vec<DetailedSourceCharacter> {
+        match self.command {
+            MmcCommand::Locate => vec![DetailedSourceCharacter::RangeControl],
+            _ => vec![DetailedSourceCharacter::PressOnlyButton],
+        }
+    }
+
+    pub fn character(&self) -> SourceCharacter {
+        match self.command {
+            MmcCommand::Locate => SourceCharacter::RangeElement,
+            _ => SourceCharacter::MomentaryButton,
+        }
+    }
+
+    /// Transport commands fire 100%, locate commands send the position as fraction of 24 hours.
+    pub fn control(&self, msg: &MmcMessage) -> Option<ControlValue> {
+        if msg.command != self.command {
+            return None;
+        }
+        if let Some(id) = self.device_id {
+            if msg.device_id != id && msg.device_id != MMC_ALL_CALL_DEVICE_ID {
+                return None;
+            }
+        }
+        let value = match msg.locate_position {
+            None => UnitValue::MAX,
+            Some(pos) => UnitValue::new_clamped(pos.to_seconds() / SECONDS_PER_DAY),
+        };
+        Some(ControlValue::AbsoluteContinuous(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_transport_commands() {
+        // Given
+        let play = [0xf0, 0x7f, 0x7f, 0x06, 0x02, 0xf7];
+        let stop = [0xf0, 0x7f, 0x01, 0x06, 0x01, 0xf7];
+        let not_mmc = [0xf0, 0x7e, 0x7f, 0x06, 0x01, 0xf7];
+        // When
+        let play = MmcMessage::parse(&play).unwrap();
+        let stop = MmcMessage::parse(&stop).unwrap();
+        // Then
+        assert_eq!(play.command, MmcCommand::Play);
+        assert_eq!(play.device_id, 0x7f);
+        assert_eq!(stop.command, MmcCommand::Stop);
+        assert_eq!(stop.device_id, 0x01);
+        assert_eq!(MmcMessage::parse(&not_mmc), None);
+    }
+
+    #[test]
+    fn control_with_locate_position() {
+        // Given
+        let source = MmcSource {
+            command: MmcCommand::Locate,
+            device_id: Some(0x01),
+        };
+        // 25 fps, 01:00:00:00
+        let locate = [
+            0xf0, 0x7f, 0x01, 0x06, 0x44, 0x06, 0x01, 0x21, 0x00, 0x00, 0x00, 0x00, 0xf7,
+        ];
+        // When
+        let msg = MmcMessage::parse(&locate).unwrap();
+        // Then
+        assert_eq!(msg.locate_position.unwrap().to_seconds(), 3600.0);
+        assert_eq!(
+            source.control(&msg),
+            Some(ControlValue::AbsoluteContinuous(UnitValue::new(1.0 / 24.0)))
+        );
+    }
+}
@@ -1,6 +1,6 @@
 use helgoboss_learn::{MidiSourceValue, RawMidiEvent, SourceCharacter};
 use helgoboss_midi::{
-    Channel, ControlChange14BitMessageScanner, ControllerNumber,
+    Channel, ControlChange14BitMessageScanner, ControllerNumber, ParameterNumberMessage,
     PollingParameterNumberMessageScanner, RawShortMessage, ShortMessage, ShortMessageFactory,
     StructuredShortMessage, U7,
 };
@@ -10,6 +10,10 @@ use std::time::{Duration, Instant};
 
 const MAX_CC_MSG_COUNT: usize = 10;
 const MAX_CC_WAITING_TIME: Duration = Duration::from_millis(250);
+/// (N)RPN messages are collected for a while, too, in order to sense whether the device uses
+/// 7-bit or 14-bit data entry.
+const MAX_PN_MSG_COUNT: usize = 10;
+const MAX_PN_WAITING_TIME: Duration = Duration::from_millis(250);
 
 #[derive(Debug)]
 pub struct MidiScanner {
@@ -35,6 +39,7 @@ impl Default for MidiScanner {
 enum State {
     Initial,
     WaitingForMoreCcMsgs(ControlChangeState),
+    WaitingForMorePnMsgs(ParameterNumberState),
 }
 
 #[derive(Debug)]
@@ -72,6 +77,53 @@ impl ControlChangeState {
     }
 }
 
+#[derive(Debug)]
+struct ParameterNumberState {
+    start_time: Instant,
+    first_msg: ParameterNumberMessage,
+    /// Some devices send the data entry LSB only for some of the values, so one is enough.
+    first_14_bit_msg: Option<ParameterNumberMessage>,
+    msg_count: usize,
+    /// Only filled as long as all messages have 7-bit values.
+    values: [U7; MAX_PN_MSG_COUNT],
+}
+
+impl ParameterNumberState {
+    fn new(msg: ParameterNumberMessage) -> ParameterNumberState {
+        let mut state = ParameterNumberState {
+            start_time: Instant::now(),
+            first_msg: msg,
+            first_14_bit_msg: None,
+            msg_count: 0,
+            values: [U7::MIN; MAX_PN_MSG_COUNT],
+        };
+        state.add_msg(msg);
+        state
+    }
+
+    fn add_msg(&mut self, msg: ParameterNumberMessage) {
+        assert!(self.msg_count < MAX_PN_MSG_COUNT);
+        if msg.is_14_bit() {
+            if self.first_14_bit_msg.is_none() {
+                self.first_14_bit_msg = Some(msg);
+            }
+        } else {
+            self.values[self.msg_count] = U7::new(msg.value().get() as u8);
+        }
+        self.msg_count += 1;
+    }
+
+    fn time_to_guess(&self) -> bool {
+        self.msg_count >= MAX_PN_MSG_COUNT || Instant::now() - self.start_time > MAX_PN_WAITING_TIME
+    }
+
+    fn matches(&self, msg: &ParameterNumberMessage) -> bool {
+        msg.channel() == self.first_msg.channel()
+            && msg.number() == self.first_msg.number()
+            && msg.is_registered() == self.first_msg.is_registered()
+    }
+}
+
 #[derive(Clone, PartialEq, Debug)]
 pub struct MidiScanResult {
     pub value: MidiSourceValue<'static, RawShortMessage>,
@@ -150,8 +202,8 @@ impl MidiScanner {
             self.dev_id = dev_id;
         }
         match &mut self.state {
-            State::Initial => {
-                if let MidiSourceValue::Plain(msg) = source_value {
+            State::Initial => match source_value {
+                MidiSourceValue::Plain(msg) => {
                     if let StructuredShortMessage::ControlChange {
                         channel,
                         controller_number,
@@ -169,16 +221,19 @@ impl MidiScanner {
                             None,
                         ))
                     }
-                } else {
-                    Some(MidiScanResult::new(
-                        source_value.try_into_owned().ok()?,
-                        dev_id,
-                        None,
-                    ))
                 }
-            }
-            State::WaitingForMoreCcMsgs(cc_state) => {
-                if let MidiSourceValue::Plain(msg) = source_value {
+                MidiSourceValue::ParameterNumber(msg) => {
+                    self.state = State::WaitingForMorePnMsgs(ParameterNumberState::new(msg));
+                    None
+                }
+                _ => Some(MidiScanResult::new(
+                    source_value.try_into_owned().ok()?,
+                    dev_id,
+                    None,
+                )),
+            },
+            State::WaitingForMoreCcMsgs(cc_state) => match source_value {
+                MidiSourceValue::Plain(msg) => {
                     if let StructuredShortMessage::ControlChange {
                         channel,
                         controller_number,
@@ -190,15 +245,32 @@ impl MidiScanner {
                         }
                     }
                     self.guess_or_not()
-                } else {
-                    // Looks like in the meantime, the composite scanners ((N)RPN or
-                    // 14-bit CC) have figured out that the combination is a composite
-                    // message. This fixes https://github.com/helgoboss/realearn/issues/95.
+                }
+                MidiSourceValue::ParameterNumber(msg) => {
+                    // Looks like in the meantime, the (N)RPN scanner has figured out that the
+                    // CCs we have been waiting for are part of (N)RPN messages. This fixes
+                    // https://github.com/helgoboss/realearn/issues/95. We still need to sense
+                    // the resolution.
+                    self.state = State::WaitingForMorePnMsgs(ParameterNumberState::new(msg));
+                    None
+                }
+                _ => {
+                    // Looks like in the meantime, the 14-bit CC scanner has figured out that the
+                    // combination is a composite message.
                     let res =
                         MidiScanResult::new(source_value.try_into_owned().ok()?, dev_id, None);
                     self.reset();
                     Some(res)
                 }
+            },
+            State::WaitingForMorePnMsgs(pn_state) => {
+                // The single CCs making up the (N)RPN messages are ignored.
+                if let MidiSourceValue::ParameterNumber(msg) = source_value {
+                    if pn_state.matches(&msg) {
+                        pn_state.add_msg(msg);
+                    }
+                }
+                self.guess_or_not()
             }
         }
     }
@@ -223,17 +295,23 @@ impl MidiScanner {
     }
 
     fn guess_or_not(&mut self) -> Option<MidiScanResult> {
-        if let State::WaitingForMoreCcMsgs(cc_state) = &self.state {
-            if cc_state.time_to_guess() {
-                let guessed_result = guess(cc_state, self.dev_id);
-                self.reset();
-                Some(guessed_result)
-            } else {
-                None
+        let guessed_result = match &self.state {
+            State::Initial => return None,
+            State::WaitingForMoreCcMsgs(cc_state) => {
+                if !cc_state.time_to_guess() {
+                    return None;
+                }
+                guess(cc_state, self.dev_id)
             }
-        } else {
-            None
-        }
+            State::WaitingForMorePnMsgs(pn_state) => {
+                if !pn_state.time_to_guess() {
+                    return None;
+                }
+                guess_parameter_number(pn_state, self.dev_id)
+            }
+        };
+        self.reset();
+        Some(guessed_result)
     }
 }
 
@@ -252,6 +330,28 @@ fn guess(cc_state: &ControlChangeState, dev_id: Option<MidiInputDeviceId>) -> Mi
     }
 }
 
+/// Learns a 14-bit source if at least one message had a 14-bit value, otherwise a 7-bit source
+/// with guessed character.
+fn guess_parameter_number(
+    pn_state: &ParameterNumberState,
+    dev_id: Option<MidiInputDeviceId>,
+) -> MidiScanResult {
+    let (msg, character) = match pn_state.first_14_bit_msg {
+        Some(msg) => (msg, None),
+        None => (
+            pn_state.first_msg,
+            Some(guess_custom_character(
+                &pn_state.values[0..pn_state.msg_count],
+            )),
+        ),
+    };
+    MidiScanResult {
+        value: MidiSourceValue::ParameterNumber(msg),
+        dev_id,
+        character,
+    }
+}
+
 fn contains_direction_change(values: &[U7]) -> bool {
     #[derive(Copy, Clone, PartialEq)]
     enum Direction {
@@ -354,8 +454,8 @@ mod tests {
 
     mod scanning {
         use super::*;
-        use helgoboss_midi::test_util::{channel, control_change, nrpn_14_bit, u14};
-        use helgoboss_midi::{ParameterNumberMessage, ParameterNumberMessageScanner};
+        use helgoboss_midi::test_util::{channel, control_change, nrpn_14_bit, u14, u7};
+        use helgoboss_midi::ParameterNumberMessageScanner;
 
         #[test]
         fn scan_nrpn() {
@@ -388,6 +488,12 @@ mod tests {
             );
             let source_4_nrpn = source_scanner.feed(ParameterNumber(nrpn_4), None);
             let source_4_short = source_scanner.feed(Plain(msg_4), None);
+            let further_sources: Vec<_> = (1..MAX_PN_MSG_COUNT)
+                .map(|i| {
+                    let nrpn = nrpn_14_bit(1, 99, 259 + i as u16);
+                    source_scanner.feed(ParameterNumber(nrpn), None)
+                })
+                .collect();
             // Then
             // Even our source scanner is already waiting for more CC messages with the same number,
             // a suddenly arriving (N)RPN message should take precedence! Because our real-time
@@ -398,15 +504,77 @@ mod tests {
             assert_eq!(source_1, None);
             assert_eq!(source_2, None);
             assert_eq!(source_3, None);
+            assert_eq!(source_4_nrpn, None);
+            assert_eq!(source_4_short, None);
+            let (last, previous) = further_sources.split_last().unwrap();
+            assert!(previous.iter().all(|s| s.is_none()));
             assert_eq!(
-                source_4_nrpn.unwrap(),
+                last.clone().unwrap(),
                 MidiScanResult {
                     value: MidiSourceValue::ParameterNumber(nrpn_14_bit(1, 99, 259)),
                     dev_id: None,
                     character: None
                 }
             );
-            assert_eq!(source_4_short, None);
+        }
+
+        #[test]
+        fn sense_14_bit_data_entry() {
+            // Given
+            let mut source_scanner = MidiScanner::default();
+            // When
+            // Some devices send the data entry LSB only if it's different from the last one.
+            let results: Vec<_> = (0..MAX_PN_MSG_COUNT as u8)
+                .map(|i| {
+                    let nrpn = if i == 3 {
+                        ParameterNumberMessage::non_registered_14_bit(
+                            channel(0),
+                            u14(12),
+                            u14(1000),
+                        )
+                    } else {
+                        ParameterNumberMessage::non_registered_7_bit(channel(0), u14(12), u7(i))
+                    };
+                    source_scanner.feed(MidiSourceValue::ParameterNumber(nrpn), None)
+                })
+                .collect();
+            // Then
+            assert_eq!(
+                results.last().unwrap().as_ref().unwrap().value,
+                MidiSourceValue::ParameterNumber(ParameterNumberMessage::non_registered_14_bit(
+                    channel(0),
+                    u14(12),
+                    u14(1000)
+                ))
+            );
+        }
+
+        #[test]
+        fn sense_7_bit_data_entry() {
+            // Given
+            let mut source_scanner = MidiScanner::default();
+            // When
+            let results: Vec<_> = (0..MAX_PN_MSG_COUNT as u8)
+                .map(|i| {
+                    let nrpn = ParameterNumberMessage::non_registered_7_bit(
+                        channel(0),
+                        u14(12),
+                        u7(40 + i),
+                    );
+                    source_scanner.feed(MidiSourceValue::ParameterNumber(nrpn), None)
+                })
+                .collect();
+            // Then
+            assert_eq!(
+                results.last().unwrap().clone().unwrap(),
+                MidiScanResult {
+                    value: MidiSourceValue::ParameterNumber(
+                        ParameterNumberMessage::non_registered_7_bit(channel(0), u14(12), u7(40))
+                    ),
+                    dev_id: None,
+                    character: Some(SourceCharacter::RangeElement)
+                }
+            );
         }
     }
 
@@ -69,6 +69,9 @@ pub use instance_state::*;
 mod osc;
 pub use osc::*;
 
+mod rtp_midi;
+pub use rtp_midi::*;
+
 mod exclusivity;
 pub use exclusivity::*;
 
@@ -78,12 +81,21 @@ pub use io::*;
 mod clip_slot;
 pub use clip_slot::*;
 
+mod clip_matrix;
+pub use clip_matrix::*;
+
+mod clip_tempo;
+pub use clip_tempo::*;
+
 mod targets;
 pub use targets::*;
 
 mod group;
 pub use group::*;
 
+mod layer;
+pub use layer::*;
+
 mod midi_types;
 pub use midi_types::*;
 
@@ -93,6 +105,9 @@ pub use reaper_source::*;
 mod device_change_detector;
 pub use device_change_detector::*;
 
+mod game_controller;
+pub use game_controller::*;
+
 mod small_ascii_string;
 pub use small_ascii_string::*;
 
@@ -104,3 +119,103 @@ pub use organization::*;
 
 mod props;
 pub use props::*;
+
+mod idle_animation;
+pub use idle_animation::*;
+
+mod control_element_identification;
+pub use control_element_identification::*;
+
+mod velocity_threshold;
+pub use velocity_threshold::*;
+mod noise_filter;
+pub use noise_filter::*;
+mod ramp;
+pub use ramp::*;
+mod encoder_acceleration;
+pub use encoder_acceleration::*;
+mod value_sequence_stepper;
+pub use value_sequence_stepper::*;
+mod response_curve;
+pub use response_curve::*;
+mod scaling_takeover;
+pub use scaling_takeover::*;
+
+mod scale_quantizer;
+pub use scale_quantizer::*;
+
+mod chord;
+pub use chord::*;
+
+mod sysex_chunking;
+pub use sysex_chunking::*;
+
+mod raw_midi_sequence;
+pub use raw_midi_sequence::*;
+
+mod feedback_priority;
+pub use feedback_priority::*;
+
+mod feedback_rate_limiting;
+pub use feedback_rate_limiting::*;
+
+mod feedback_loop_detection;
+pub use feedback_loop_detection::*;
+
+mod feedback_deadband;
+pub use feedback_deadband::*;
+
+mod osc_delta;
+pub use osc_delta::*;
+
+mod osc_arg_filter;
+pub use osc_arg_filter::*;
+
+mod osc_address_pattern;
+pub use osc_address_pattern::*;
+
+mod beat_blink;
+pub use beat_blink::*;
+
+mod cue_sends;
+pub use cue_sends::*;
+
+mod target_dependency_index;
+pub use target_dependency_index::*;
+
+mod midi_macros;
+pub use midi_macros::*;
+
+mod program_change_patches;
+pub use program_change_patches::*;
+
+mod midi_clock_generator;
+pub use midi_clock_generator::*;
+
+mod midi_clock_outputs;
+pub use midi_clock_outputs::*;
+
+mod instance_hooks;
+pub use instance_hooks::*;
+
+mod sysex_checksum;
+pub use sysex_checksum::*;
+
+mod control_rate_limiter;
+pub use control_rate_limiter::*;
+mod target_hit_throttle;
+pub use target_hit_throttle::*;
+
+mod mapping_snapshot;
+pub use mapping_snapshot::*;
+
+mod parameter_link;
+pub use parameter_link::*;
+
+mod control_latency_measurement;
+pub use control_latency_measurement::*;
+
+#[cfg(feature = "realearn-meter")]
+mod control_latency;
+#[cfg(feature = "realearn-meter")]
+pub use control_latency::*;
@@ -0,0 +1,160 @@
+use helgoboss_learn::{ControlValue, UnitValue};
+
+/// Settings for taming jittery absolute sources such as cheap faders or expression pedals.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct NoiseFilterSettings {
+    /// Absolute values which differ less than this from the last processed value are ignored.
+    pub dead_zone: UnitValue,
+    /// If set, the source is turned into a switch with separate on and off thresholds.
+    pub hysteresis: Option<Hysteresis>,
+}
+
+/// Switches on when reaching the upper threshold and only switches off again when reaching the
+/// lower threshold.
+///
+/// That way, a noisy source resting near a single threshold doesn't keep toggling the target.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Hysteresis {
+    pub lower_threshold: UnitValue,
+    pub upper_threshold: UnitValue,
+}
+
+/// Keeps track of what has been let through so far.
+#[derive(Clone, Debug, Default)]
+pub struct NoiseFilter {
+    last_value: Option<UnitValue>,
+    is_on: Option<bool>,
+}
+
+impl NoiseFilter {
+    /// Returns `None` if the control value should not be processed any further.
+    ///
+    /// Relative values are let through unchanged.
+    pub fn process(
+        &mut self,
+        settings: &NoiseFilterSettings,
+        value: ControlValue,
+    ) -> Option<ControlValue> {
+        let v = match value.to_unit_value() {
+            Ok(v) => v,
+            Err(_) => return Some(value),
+        };
+        if let Some(h) = &settings.hysteresis {
+            let is_on = if v >= h.upper_threshold {
+                true
+            } else if v <= h.lower_threshold {
+                false
+            } else {
+                // In-between values never change the state.
+                return None;
+            };
+            if self.is_on == Some(is_on) {
+                return None;
+            }
+            self.is_on = Some(is_on);
+            let new_value = if is_on {
+                UnitValue::MAX
+            } else {
+                UnitValue::MIN
+            };
+            return Some(ControlValue::AbsoluteContinuous(new_value));
+        }
+        if let Some(last_value) = self.last_value {
+            let distance = (v.get() - last_value.get()).abs();
+            // The extremes must always be reachable, otherwise a dead zone would make it
+            // impossible to move the target to its minimum or maximum.
+            let is_extreme = v.is_zero() || v == UnitValue::MAX;
+            if distance < settings.dead_zone.get() && !(is_extreme && v != last_value) {
+                return None;
+            }
+        }
+        self.last_value = Some(v);
+        Some(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use helgoboss_learn::DiscreteIncrement;
+
+    #[test]
+    fn ignore_values_within_dead_zone() {
+        // Given
+        let settings = NoiseFilterSettings {
+            dead_zone: UnitValue::new(0.05),
+            hysteresis: None,
+        };
+        let mut filter = NoiseFilter::default();
+        // When
+        let first = filter.process(&settings, abs(0.5));
+        let jitter = filter.process(&settings, abs(0.52));
+        let movement = filter.process(&settings, abs(0.6));
+        let jitter_back = filter.process(&settings, abs(0.57));
+        // Then
+        assert_eq!(first, Some(abs(0.5)));
+        assert_eq!(jitter, None);
+        assert_eq!(movement, Some(abs(0.6)));
+        assert_eq!(jitter_back, None);
+    }
+
+    #[test]
+    fn always_reach_extremes() {
+        // Given
+        let settings = NoiseFilterSettings {
+            dead_zone: UnitValue::new(0.05),
+            hysteresis: None,
+        };
+        let mut filter = NoiseFilter::default();
+        // When
+        filter.process(&settings, abs(0.98));
+        let max = filter.process(&settings, abs(1.0));
+        let max_again = filter.process(&settings, abs(1.0));
+        // Then
+        assert_eq!(max, Some(abs(1.0)));
+        assert_eq!(max_again, None);
+    }
+
+    #[test]
+    fn switch_with_hysteresis() {
+        // Given
+        let settings = NoiseFilterSettings {
+            dead_zone: UnitValue::MIN,
+            hysteresis: Some(Hysteresis {
+                lower_threshold: UnitValue::new(0.4),
+                upper_threshold: UnitValue::new(0.6),
+            }),
+        };
+        let mut filter = NoiseFilter::default();
+        // When
+        let in_between = filter.process(&settings, abs(0.5));
+        let on = filter.process(&settings, abs(0.65));
+        let jitter_down = filter.process(&settings, abs(0.45));
+        let jitter_up = filter.process(&settings, abs(0.7));
+        let off = filter.process(&settings, abs(0.3));
+        // Then
+        assert_eq!(in_between, None);
+        assert_eq!(on, Some(abs(1.0)));
+        assert_eq!(jitter_down, None);
+        assert_eq!(jitter_up, None);
+        assert_eq!(off, Some(abs(0.0)));
+    }
+
+    #[test]
+    fn let_through_relative() {
+        // Given
+        let settings = NoiseFilterSettings {
+            dead_zone: UnitValue::new(0.5),
+            hysteresis: None,
+        };
+        let mut filter = NoiseFilter::default();
+        let relative = ControlValue::Relative(DiscreteIncrement::new(1));
+        // Then
+        assert_eq!(filter.process(&settings, relative), Some(relative));
+        assert_eq!(filter.process(&settings, relative), Some(relative));
+    }
+
+    fn abs(v: f64) -> ControlValue {
+        ControlValue::AbsoluteContinuous(UnitValue::new(v))
+    }
+}
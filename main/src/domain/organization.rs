@@ -50,6 +50,12 @@ impl TagScope {
     pub fn has_tags(&self) -> bool {
         !self.tags.is_empty()
     }
+
+    /// Returns whether something with the given tags is in scope. Everything is in scope if the
+    /// scope doesn't define any tags.
+    pub fn matches(&self, tags: &[Tag]) -> bool {
+        !self.has_tags() || has_any_of(&self.tags, tags)
+    }
 }
 
 fn has_any_of<'a, T: 'a + Eq + Hash>(
@@ -1,24 +1,36 @@
+use crate::domain::FeedbackRateLimiter;
 use crossbeam_channel::Receiver;
 use derive_more::Display;
 use rosc::{OscBundle, OscMessage, OscPacket};
 use serde::{Deserialize, Serialize};
 
-use slog::{trace, warn};
+use slog::{debug, trace, warn};
 
+use std::collections::VecDeque;
 use std::error::Error;
 use std::io;
-use std::net::{Ipv4Addr, SocketAddrV4, ToSocketAddrs, UdpSocket};
+use std::io::{Read, Write};
+use std::net::{
+    Ipv4Addr, SocketAddr, SocketAddrV4, TcpListener, TcpStream, ToSocketAddrs, UdpSocket,
+};
 
 use core::mem;
 use std::str::FromStr;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::thread::JoinHandle;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use uuid::Uuid;
 
 const MAX_INCOMING_PACKET_SIZE: usize = 10_000;
 const OSC_OUTGOING_BULK_SIZE: usize = 16;
+const TCP_CONNECT_TIMEOUT: Duration = Duration::from_millis(500);
+/// A device which doesn't accept data for this long is considered as gone.
+const TCP_WRITE_TIMEOUT: Duration = Duration::from_millis(100);
+/// Delay before trying to connect again after the first failed attempt. Doubled with each further
+/// failed attempt.
+const TCP_MIN_RECONNECT_DELAY: Duration = Duration::from_millis(500);
+const TCP_MAX_RECONNECT_DELAY: Duration = Duration::from_secs(10);
 
 pub struct OscFeedbackTask {
     dev_id: OscDeviceId,
@@ -71,9 +83,16 @@ impl OscFeedbackProcessor {
         } else {
             panic!("manager was not stopped");
         };
+        let max_rates = osc_output_devices
+            .iter()
+            .filter_map(|dev| Some((dev.id(), dev.feedback_max_rate()?)))
+            .collect();
+        let mut rate_limiter = FeedbackRateLimiter::default();
+        rate_limiter.replace_max_rates(max_rates);
         let mut handler = OscFeedbackHandler {
             task_receiver: state.task_receiver,
             osc_output_devices,
+            rate_limiter,
         };
         let request_stop = Arc::new(AtomicBool::new(false));
         let request_stop_clone = request_stop.clone();
@@ -112,11 +131,21 @@ impl OscFeedbackProcessor {
 struct OscFeedbackHandler {
     task_receiver: Receiver<OscFeedbackTask>,
     osc_output_devices: Vec<OscOutputDevice>,
+    /// The OSC address is used for replacing outdated feedback.
+    rate_limiter: FeedbackRateLimiter<OscDeviceId, String, OscMessage>,
 }
 
 impl OscFeedbackHandler {
     pub fn cycle(&mut self) {
         use itertools::Itertools;
+        let now = Instant::now();
+        let osc_output_devices = &mut self.osc_output_devices;
+        // Send feedback which had to wait because of a device's maximum feedback rate.
+        self.rate_limiter.send_due(now, |dev_id, msg| {
+            if let Some(dev) = osc_output_devices.iter_mut().find(|d| d.id() == dev_id) {
+                let _ = dev.send(std::iter::once(msg));
+            }
+        });
         let grouped_by_device = self
             .task_receiver
             .try_iter()
@@ -124,7 +153,25 @@ impl OscFeedbackHandler {
             .sorted_by_key(|task| task.dev_id)
             .group_by(|task| task.dev_id);
         for (dev_id, group) in grouped_by_device.into_iter() {
-            if let Some(dev) = self.osc_output_devices.iter().find(|d| d.id() == dev_id) {
+            let dev = match osc_output_devices.iter_mut().find(|d| d.id() == dev_id) {
+                None => continue,
+                Some(d) => d,
+            };
+            if self.rate_limiter.is_limited(dev_id) {
+                for task in group {
+                    let address = Some(task.msg.addr.clone());
+                    self.rate_limiter.send(
+                        dev_id,
+                        address,
+                        task.msg,
+                        now,
+                        |msg| {
+                            let _ = dev.send(std::iter::once(msg));
+                        },
+                        |_| {},
+                    );
+                }
+            } else {
                 let _ = dev.send(group.map(|task| task.msg));
             }
         }
@@ -136,25 +183,78 @@ impl OscFeedbackHandler {
     }
 }
 
+/// Network transport of an OSC device.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Serialize, Deserialize, Display)]
+pub enum OscTransport {
+    #[serde(rename = "udp")]
+    #[display(fmt = "UDP")]
+    Udp,
+    /// OSC 1.1 stream transport: Each packet is framed with SLIP (RFC 1055).
+    #[serde(rename = "tcp")]
+    #[display(fmt = "TCP (SLIP)")]
+    Tcp,
+}
+
+impl Default for OscTransport {
+    fn default() -> Self {
+        OscTransport::Udp
+    }
+}
+
 #[derive(Debug)]
 pub struct OscInputDevice {
     id: OscDeviceId,
-    socket: UdpSocket,
+    receiver: OscReceiver,
     logger: slog::Logger,
     osc_buffer: [u8; MAX_INCOMING_PACKET_SIZE],
 }
 
+#[derive(Debug)]
+enum OscReceiver {
+    Udp(UdpSocket),
+    Tcp(TcpOscReceiver),
+}
+
+/// Accepts TCP connections from OSC devices and collects the SLIP-framed packets they send.
+#[derive(Debug)]
+struct TcpOscReceiver {
+    listener: TcpListener,
+    connections: Vec<SlipConnection>,
+    pending_packets: VecDeque<Vec<u8>>,
+}
+
+#[derive(Debug)]
+struct SlipConnection {
+    stream: TcpStream,
+    decoder: SlipDecoder,
+}
+
 impl OscInputDevice {
     pub fn bind(
         id: OscDeviceId,
         addr: impl ToSocketAddrs,
         logger: slog::Logger,
+        transport: OscTransport,
     ) -> Result<OscInputDevice, Box<dyn Error>> {
-        let socket = UdpSocket::bind(addr)?;
-        socket.set_nonblocking(true)?;
+        let receiver = match transport {
+            OscTransport::Udp => {
+                let socket = UdpSocket::bind(addr)?;
+                socket.set_nonblocking(true)?;
+                OscReceiver::Udp(socket)
+            }
+            OscTransport::Tcp => {
+                let listener = TcpListener::bind(addr)?;
+                listener.set_nonblocking(true)?;
+                OscReceiver::Tcp(TcpOscReceiver {
+                    listener,
+                    connections: vec![],
+                    pending_packets: Default::default(),
+                })
+            }
+        };
         let dev = OscInputDevice {
             id,
-            socket,
+            receiver,
             logger,
             osc_buffer: [0; MAX_INCOMING_PACKET_SIZE],
         };
@@ -166,29 +266,27 @@ impl OscInputDevice {
     }
 
     pub fn poll(&mut self) -> Result<Option<OscPacket>, &'static str> {
-        match self.socket.recv(&mut self.osc_buffer) {
-            Ok(num_bytes) => match rosc::decoder::decode(&self.osc_buffer[..num_bytes]) {
-                Ok(packet) => {
-                    trace!(
-                        self.logger,
-                        "Received packet with {} bytes: {:#?}",
-                        num_bytes,
-                        &packet
-                    );
-                    Ok(Some(packet))
-                }
-                Err(err) => {
-                    warn!(self.logger, "Error trying to decode OSC packet: {:?}", err);
-                    Err("error trying to decode OSC messages")
+        let packet = match &mut self.receiver {
+            OscReceiver::Udp(socket) => match socket.recv(&mut self.osc_buffer) {
+                Ok(num_bytes) => decode_osc_packet(&self.osc_buffer[..num_bytes], &self.logger)?,
+                Err(ref err) if err.kind() != io::ErrorKind::WouldBlock => {
+                    warn!(self.logger, "Error trying to receive OSC packet: {}", err);
+                    return Err("error trying to receive OSC message");
                 }
+                // We don't need to handle "would block" because we are running in a loop anyway.
+                _ => return Ok(None),
             },
-            Err(ref err) if err.kind() != io::ErrorKind::WouldBlock => {
-                warn!(self.logger, "Error trying to receive OSC packet: {}", err);
-                Err("error trying to receive OSC message")
+            OscReceiver::Tcp(receiver) => {
+                if receiver.pending_packets.is_empty() {
+                    receiver.receive(&mut self.osc_buffer, &self.logger);
+                }
+                match receiver.pending_packets.pop_front() {
+                    None => return Ok(None),
+                    Some(bytes) => decode_osc_packet(&bytes, &self.logger)?,
+                }
             }
-            // We don't need to handle "would block" because we are running in a loop anyway.
-            _ => Ok(None),
-        }
+        };
+        Ok(Some(packet))
     }
 
     pub fn poll_multiple(&mut self, n: usize) -> impl Iterator<Item = OscPacket> + '_ {
@@ -196,12 +294,98 @@ impl OscInputDevice {
     }
 }
 
+impl TcpOscReceiver {
+    fn receive(&mut self, buffer: &mut [u8], logger: &slog::Logger) {
+        // Accept new connections
+        loop {
+            match self.listener.accept() {
+                Ok((stream, addr)) => {
+                    debug!(logger, "Accepted OSC connection from {}", addr);
+                    if stream.set_nonblocking(true).is_ok() {
+                        self.connections.push(SlipConnection {
+                            stream,
+                            decoder: Default::default(),
+                        });
+                    }
+                }
+                Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => break,
+                Err(err) => {
+                    warn!(logger, "Error trying to accept OSC connection: {}", err);
+                    break;
+                }
+            }
+        }
+        // Read from existing connections
+        let mut i = 0;
+        while i < self.connections.len() {
+            let c = &mut self.connections[i];
+            let keep = match c.stream.read(buffer) {
+                // Connection closed by peer
+                Ok(0) => false,
+                Ok(num_bytes) => {
+                    self.pending_packets
+                        .extend(c.decoder.feed(&buffer[..num_bytes]));
+                    true
+                }
+                Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => true,
+                Err(err) => {
+                    warn!(
+                        logger,
+                        "Error trying to receive OSC packet via TCP: {}", err
+                    );
+                    false
+                }
+            };
+            if keep {
+                i += 1;
+            } else {
+                self.connections.swap_remove(i);
+            }
+        }
+    }
+}
+
+fn decode_osc_packet(bytes: &[u8], logger: &slog::Logger) -> Result<OscPacket, &'static str> {
+    match rosc::decoder::decode(bytes) {
+        Ok(packet) => {
+            trace!(
+                logger,
+                "Received packet with {} bytes: {:#?}",
+                bytes.len(),
+                &packet
+            );
+            Ok(packet)
+        }
+        Err(err) => {
+            warn!(logger, "Error trying to decode OSC packet: {:?}", err);
+            Err("error trying to decode OSC messages")
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct OscOutputDevice {
     id: OscDeviceId,
-    socket: UdpSocket,
+    sender: OscSender,
     logger: slog::Logger,
     can_deal_with_bundles: bool,
+    feedback_max_rate: Option<u32>,
+}
+
+#[derive(Debug)]
+enum OscSender {
+    Udp(UdpSocket),
+    Tcp(TcpOscSender),
+}
+
+/// Connects lazily when there's something to send, so it's only ever blocked in the OSC feedback
+/// thread (never in the main thread) and devices which are not reachable yet are not dropped.
+#[derive(Debug)]
+struct TcpOscSender {
+    addr: SocketAddr,
+    stream: Option<TcpStream>,
+    next_connect_attempt: Option<Instant>,
+    reconnect_delay: Duration,
 }
 
 impl OscOutputDevice {
@@ -210,14 +394,34 @@ impl OscOutputDevice {
         addr: impl ToSocketAddrs,
         logger: slog::Logger,
         can_deal_with_bundles: bool,
+        transport: OscTransport,
+        feedback_max_rate: Option<u32>,
     ) -> Result<OscOutputDevice, Box<dyn Error>> {
-        let socket = UdpSocket::bind(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0))?;
-        socket.connect(addr)?;
+        let sender = match transport {
+            OscTransport::Udp => {
+                let socket = UdpSocket::bind(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0))?;
+                socket.connect(addr)?;
+                OscSender::Udp(socket)
+            }
+            OscTransport::Tcp => {
+                let addr = addr
+                    .to_socket_addrs()?
+                    .next()
+                    .ok_or("couldn't resolve OSC device address")?;
+                OscSender::Tcp(TcpOscSender {
+                    addr,
+                    stream: None,
+                    next_connect_attempt: None,
+                    reconnect_delay: TCP_MIN_RECONNECT_DELAY,
+                })
+            }
+        };
         let dev = OscOutputDevice {
             id,
-            socket,
+            sender,
             logger,
             can_deal_with_bundles,
+            feedback_max_rate,
         };
         Ok(dev)
     }
@@ -226,7 +430,12 @@ impl OscOutputDevice {
         self.id
     }
 
-    pub fn send(&self, messages: impl Iterator<Item = OscMessage>) -> Result<(), &'static str> {
+    /// Maximum number of feedback messages per second.
+    pub fn feedback_max_rate(&self) -> Option<u32> {
+        self.feedback_max_rate
+    }
+
+    pub fn send(&mut self, messages: impl Iterator<Item = OscMessage>) -> Result<(), &'static str> {
         if self.can_deal_with_bundles {
             // Haven't realized a performance difference between sending a bundle or single
             // messages. However, REAPER sends a bundle (maybe in order to use time tags).
@@ -238,7 +447,7 @@ impl OscOutputDevice {
     }
 
     fn send_as_bundle(
-        &self,
+        &mut self,
         messages: impl Iterator<Item = OscMessage>,
     ) -> Result<(), &'static str> {
         let bundle = OscBundle {
@@ -255,14 +464,13 @@ impl OscOutputDevice {
             bytes.len(),
             &packet
         );
-        self.socket
-            .send(&bytes)
+        self.send_bytes(&bytes)
             .map_err(|_| "error trying to send OSC bundle packet")?;
         Ok(())
     }
 
     fn send_as_messages(
-        &self,
+        &mut self,
         messages: impl Iterator<Item = OscMessage>,
     ) -> Result<(), &'static str> {
         for m in messages {
@@ -275,12 +483,128 @@ impl OscOutputDevice {
                 bytes.len(),
                 &packet
             );
-            self.socket
-                .send(&bytes)
+            self.send_bytes(&bytes)
                 .map_err(|_| "error trying to send OSC message packet")?;
         }
         Ok(())
     }
+
+    fn send_bytes(&mut self, bytes: &[u8]) -> io::Result<()> {
+        match &mut self.sender {
+            OscSender::Udp(socket) => {
+                socket.send(bytes)?;
+                Ok(())
+            }
+            OscSender::Tcp(sender) => sender.send(&slip_encode(bytes)),
+        }
+    }
+}
+
+impl TcpOscSender {
+    fn send(&mut self, frame: &[u8]) -> io::Result<()> {
+        if let Some(s) = &mut self.stream {
+            if s.write_all(frame).is_ok() {
+                return Ok(());
+            }
+            // The device might have been restarted. Try to reconnect right away.
+            self.stream = None;
+        }
+        let stream = self.connect()?;
+        let result = stream.write_all(frame);
+        if result.is_err() {
+            self.stream = None;
+        }
+        result
+    }
+
+    /// Doesn't try again before the reconnect delay has passed, which grows with each failed
+    /// attempt. Without that, an unreachable device would block the feedback thread all the time.
+    fn connect(&mut self) -> io::Result<&mut TcpStream> {
+        let now = Instant::now();
+        if let Some(t) = self.next_connect_attempt {
+            if now < t {
+                return Err(io::ErrorKind::NotConnected.into());
+            }
+        }
+        match self.connect_now() {
+            Ok(stream) => {
+                self.next_connect_attempt = None;
+                self.reconnect_delay = TCP_MIN_RECONNECT_DELAY;
+                Ok(self.stream.get_or_insert(stream))
+            }
+            Err(e) => {
+                self.next_connect_attempt = Some(now + self.reconnect_delay);
+                self.reconnect_delay = (self.reconnect_delay * 2).min(TCP_MAX_RECONNECT_DELAY);
+                Err(e)
+            }
+        }
+    }
+
+    fn connect_now(&self) -> io::Result<TcpStream> {
+        let stream = TcpStream::connect_timeout(&self.addr, TCP_CONNECT_TIMEOUT)?;
+        stream.set_nodelay(true)?;
+        stream.set_write_timeout(Some(TCP_WRITE_TIMEOUT))?;
+        Ok(stream)
+    }
+}
+
+const SLIP_END: u8 = 0xC0;
+const SLIP_ESC: u8 = 0xDB;
+const SLIP_ESC_END: u8 = 0xDC;
+const SLIP_ESC_ESC: u8 = 0xDD;
+
+/// Frames the given packet with SLIP as required by OSC 1.1 for stream transports.
+///
+/// Uses the "double END" variant, which also starts with an END byte to flush line noise.
+fn slip_encode(bytes: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(bytes.len() + 2);
+    frame.push(SLIP_END);
+    for b in bytes {
+        match *b {
+            SLIP_END => frame.extend_from_slice(&[SLIP_ESC, SLIP_ESC_END]),
+            SLIP_ESC => frame.extend_from_slice(&[SLIP_ESC, SLIP_ESC_ESC]),
+            b => frame.push(b),
+        }
+    }
+    frame.push(SLIP_END);
+    frame
+}
+
+/// Reassembles SLIP-framed packets from a byte stream which may split them arbitrarily.
+#[derive(Debug, Default)]
+struct SlipDecoder {
+    current: Vec<u8>,
+    escaped: bool,
+}
+
+impl SlipDecoder {
+    /// Feeds the next chunk of the stream and returns all packets completed by it.
+    fn feed(&mut self, bytes: &[u8]) -> Vec<Vec<u8>> {
+        let mut packets = vec![];
+        for b in bytes {
+            if self.escaped {
+                self.escaped = false;
+                match *b {
+                    SLIP_ESC_END => self.current.push(SLIP_END),
+                    SLIP_ESC_ESC => self.current.push(SLIP_ESC),
+                    // Protocol violation. Take the byte as it is.
+                    b => self.current.push(b),
+                }
+                continue;
+            }
+            match *b {
+                SLIP_END => {
+                    // Empty frames occur with "double END" framing.
+                    if !self.current.is_empty() {
+                        packets.push(mem::take(&mut self.current));
+                    }
+                }
+                SLIP_ESC => self.escaped = true,
+                b => self.current.push(b),
+            }
+        }
+        packets
+    }
 }
 
 /// An OSC device ID.
@@ -315,3 +639,45 @@ pub struct OscScanResult {
     pub message: OscMessage,
     pub dev_id: Option<OscDeviceId>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slip_roundtrip() {
+        // Given
+        let packet = vec![1, SLIP_END, 2, SLIP_ESC, 3];
+        // When
+        let frame = slip_encode(&packet);
+        let decoded = SlipDecoder::default().feed(&frame);
+        // Then
+        assert_eq!(
+            frame,
+            vec![
+                SLIP_END,
+                1,
+                SLIP_ESC,
+                SLIP_ESC_END,
+                2,
+                SLIP_ESC,
+                SLIP_ESC_ESC,
+                3,
+                SLIP_END
+            ]
+        );
+        assert_eq!(decoded, vec![packet]);
+    }
+
+    #[test]
+    fn slip_decode_across_chunks() {
+        // Given
+        let mut decoder = SlipDecoder::default();
+        // When
+        let first = decoder.feed(&[SLIP_END, 1, 2, SLIP_ESC]);
+        let second = decoder.feed(&[SLIP_ESC_END, SLIP_END, SLIP_END, 4, SLIP_END]);
+        // Then
+        assert!(first.is_empty());
+        assert_eq!(second, vec![vec![1, 2, SLIP_END], vec![4]]);
+    }
+}
@@ -0,0 +1,117 @@
+/// An OSC source address which matches a whole family of addresses.
+///
+/// Supports `*` (matches any single address segment) and `{name}` (matches any numeric segment
+/// and makes it available as variable `name` in dynamic target expressions), e.g.
+/// `/track/{index}/volume`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct OscAddressPattern {
+    segments: Vec<Segment>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+enum Segment {
+    Literal(String),
+    Wildcard,
+    Capture(String),
+}
+
+/// Values of the captured segments of the last matching address.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct OscAddressCaptures(Vec<(String, f64)>);
+
+impl OscAddressCaptures {
+    pub fn get(&self, name: &str) -> Option<f64> {
+        self.0.iter().find(|(n, _)| n == name).map(|(_, v)| *v)
+    }
+}
+
+impl OscAddressPattern {
+    /// Returns `None` if the given address doesn't contain any wildcards or captures.
+    pub fn parse(pattern: &str) -> Option<Self> {
+        let segments: Vec<_> = pattern
+            .split('/')
+            .map(|s| {
+                if s == "*" {
+                    Segment::Wildcard
+                } else if s.len() > 2 && s.starts_with('{') && s.ends_with('}') {
+                    Segment::Capture(s[1..s.len() - 1].to_string())
+                } else {
+                    Segment::Literal(s.to_string())
+                }
+            })
+            .collect();
+        if segments.iter().all(|s| matches!(s, Segment::Literal(_))) {
+            return None;
+        }
+        Some(Self { segments })
+    }
+
+    /// Returns the captured values if the given address matches this pattern.
+    pub fn matches(&self, address: &str) -> Option<OscAddressCaptures> {
+        let mut captures = vec![];
+        let mut address_segments = address.split('/');
+        for segment in &self.segments {
+            let actual = address_segments.next()?;
+            match segment {
+                Segment::Literal(expected) => {
+                    if actual != expected {
+                        return None;
+                    }
+                }
+                Segment::Wildcard => {}
+                Segment::Capture(name) => {
+                    let value: f64 = actual.parse().ok()?;
+                    captures.push((name.clone(), value));
+                }
+            }
+        }
+        if address_segments.next().is_some() {
+            return None;
+        }
+        Some(OscAddressCaptures(captures))
+    }
+
+    /// Builds a concrete address from the given captured values.
+    ///
+    /// Returns `None` if the pattern contains wildcards or if a captured value is missing.
+    pub fn format(&self, captures: &OscAddressCaptures) -> Option<String> {
+        let segments: Option<Vec<_>> = self
+            .segments
+            .iter()
+            .map(|s| match s {
+                Segment::Literal(l) => Some(l.clone()),
+                Segment::Wildcard => None,
+                Segment::Capture(name) => Some(captures.get(name)?.to_string()),
+            })
+            .collect();
+        Some(segments?.join("/"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wildcards_and_captures() {
+        // Given
+        let pattern = OscAddressPattern::parse("/track/{index}/*").unwrap();
+        // When
+        let captures = pattern.matches("/track/3/volume").unwrap();
+        // Then
+        assert_eq!(captures.get("index"), Some(3.0));
+        assert_eq!(captures.get("foo"), None);
+        assert!(pattern.matches("/track/3/pan").is_some());
+        assert!(pattern.matches("/track/x/volume").is_none());
+        assert!(pattern.matches("/track/3/volume/1").is_none());
+        assert!(pattern.matches("/fx/3/volume").is_none());
+        assert_eq!(pattern.format(&captures), None);
+        assert_eq!(
+            OscAddressPattern::parse("/track/{index}/volume")
+                .unwrap()
+                .format(&captures),
+            Some("/track/3/volume".to_string())
+        );
+        assert_eq!(OscAddressPattern::parse("/track/1/volume"), None);
+    }
+}
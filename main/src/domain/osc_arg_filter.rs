@@ -0,0 +1,73 @@
+use rosc::{OscMessage, OscType};
+use serde::{Deserialize, Serialize};
+
+/// Restricts an OSC source to messages whose argument at the given index has a certain value.
+///
+/// Useful for devices which pack several values into one message, e.g. `/note 60 0.8`.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct OscArgFilter {
+    pub index: u32,
+    /// Compared numerically if the argument is a number, textually otherwise.
+    pub value: String,
+}
+
+impl OscArgFilter {
+    pub fn matches(&self, msg: &OscMessage) -> bool {
+        let arg = match msg.args.get(self.index as usize) {
+            None => return false,
+            Some(a) => a,
+        };
+        use OscType::*;
+        let number = match arg {
+            Int(v) => *v as f64,
+            Long(v) => *v as f64,
+            Float(v) => *v as f64,
+            Double(v) => *v,
+            Bool(v) => return self.value.trim().parse::<bool>() == Ok(*v),
+            String(v) => return self.value == *v,
+            _ => return false,
+        };
+        match self.value.trim().parse::<f64>() {
+            // Float arguments are not exact, so we need some tolerance.
+            Ok(expected) => (number - expected).abs() < f32::EPSILON as f64,
+            Err(_) => false,
+        }
+    }
+}
+
+/// Returns `true` if the message passes all of the given filters.
+pub fn osc_arg_filters_match(filters: &[OscArgFilter], msg: &OscMessage) -> bool {
+    filters.iter().all(|f| f.matches(msg))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn match_multiple_args() {
+        // Given
+        let filters = vec![filter(0, "60"), filter(2, "on")];
+        let msg = |note: i32, state: &str| OscMessage {
+            addr: "/note".to_string(),
+            args: vec![
+                OscType::Int(note),
+                OscType::Float(0.8),
+                OscType::String(state.to_string()),
+            ],
+        };
+        // Then
+        assert!(osc_arg_filters_match(&filters, &msg(60, "on")));
+        assert!(!osc_arg_filters_match(&filters, &msg(61, "on")));
+        assert!(!osc_arg_filters_match(&filters, &msg(60, "off")));
+        assert!(filter(1, "0.8").matches(&msg(60, "on")));
+        assert!(!filter(3, "0").matches(&msg(60, "on")));
+    }
+
+    fn filter(index: u32, value: &str) -> OscArgFilter {
+        OscArgFilter {
+            index,
+            value: value.to_string(),
+        }
+    }
+}
@@ -0,0 +1,251 @@
+use derive_more::Display;
+use enum_iterator::IntoEnumIterator;
+use helgoboss_learn::{ControlValue, DiscreteIncrement, OscTypeTag, UnitValue};
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+use rosc::OscType;
+use serde::{Deserialize, Serialize};
+
+/// Center of integer arguments in [`OscDeltaMode::Centered`] (like MIDI relative mode 2).
+const INT_CENTER: i64 = 64;
+
+/// Number of increments which make up the complete unit interval when converting between deltas
+/// and unit values (like a 7-bit MIDI encoder).
+const STEP_COUNT: f64 = 127.0;
+
+/// Factor by which float arguments deviating from 0.5 are scaled in
+/// [`OscDeltaMode::Centered`].
+const FLOAT_CENTERED_FACTOR: f64 = 128.0;
+
+/// Defines how the argument of a relative OSC source (endless encoder) is interpreted.
+#[derive(
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    Debug,
+    Serialize,
+    Deserialize,
+    IntoEnumIterator,
+    TryFromPrimitive,
+    IntoPrimitive,
+    Display,
+)]
+#[repr(usize)]
+pub enum OscDeltaMode {
+    /// Positive values increment by one, negative values decrement by one.
+    #[serde(rename = "sign")]
+    #[display(fmt = "Sign (+/- 1)")]
+    Sign,
+    /// The value is the number of increments (negative values decrement).
+    #[serde(rename = "steps")]
+    #[display(fmt = "Steps (+/- n)")]
+    Steps,
+    /// Values above the center increment, values below decrement. The center is 64 for integers
+    /// and 0.5 for floats.
+    #[serde(rename = "centered")]
+    #[display(fmt = "Centered (64 / 0.5)")]
+    Centered,
+}
+
+impl Default for OscDeltaMode {
+    fn default() -> Self {
+        Self::Sign
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub struct OscDeltaSettings {
+    pub mode: OscDeltaMode,
+    /// Whether feedback is sent as delta instead of the absolute value.
+    pub relative_feedback: bool,
+}
+
+impl OscDeltaSettings {
+    /// Interprets the given OSC argument as relative control value.
+    ///
+    /// Returns `None` if the argument doesn't contain an increment (e.g. zero or the center).
+    pub fn interpret(&self, arg: &OscType) -> Option<ControlValue> {
+        use OscDeltaMode::*;
+        let increment = match (self.mode, arg) {
+            (Sign, _) | (_, OscType::Bool(_)) => {
+                let v = to_f64(arg)?;
+                if v > 0.0 {
+                    1
+                } else if v < 0.0 {
+                    -1
+                } else {
+                    0
+                }
+            }
+            (Steps, _) => to_f64(arg)?.round() as i64,
+            (Centered, OscType::Int(_)) | (Centered, OscType::Long(_)) => {
+                to_f64(arg)? as i64 - INT_CENTER
+            }
+            (Centered, _) => ((to_f64(arg)? - 0.5) * FLOAT_CENTERED_FACTOR).round() as i64,
+        };
+        let increment = increment.clamp(i32::MIN as i64, i32::MAX as i64) as i32;
+        if increment == 0 {
+            return None;
+        }
+        Some(ControlValue::Relative(DiscreteIncrement::new(increment)))
+    }
+
+    /// Converts the difference between two consecutive feedback values into an OSC argument.
+    ///
+    /// Returns `None` if the values are equal.
+    pub fn feedback_arg(
+        &self,
+        previous: UnitValue,
+        current: UnitValue,
+        type_tag: OscTypeTag,
+    ) -> Option<OscType> {
+        use OscDeltaMode::*;
+        let diff = current.get() - previous.get();
+        if diff == 0.0 {
+            return None;
+        }
+        let steps = (diff * STEP_COUNT).round() as i64;
+        // Even the tiniest change should move the encoder ring at least one step.
+        let steps = if steps == 0 {
+            diff.signum() as i64
+        } else {
+            steps
+        };
+        let arg = match self.mode {
+            Sign => NumericArg::Int(steps.signum()),
+            Steps => NumericArg::Int(steps),
+            Centered => match type_tag {
+                OscTypeTag::Int | OscTypeTag::Long => NumericArg::Int(INT_CENTER + steps),
+                _ => NumericArg::Float(0.5 + steps as f64 / FLOAT_CENTERED_FACTOR),
+            },
+        };
+        Some(arg.into_osc_type(type_tag))
+    }
+}
+
+enum NumericArg {
+    Int(i64),
+    Float(f64),
+}
+
+impl NumericArg {
+    fn into_osc_type(self, type_tag: OscTypeTag) -> OscType {
+        let (int, float) = match self {
+            NumericArg::Int(i) => (i, i as f64),
+            NumericArg::Float(f) => (f.round() as i64, f),
+        };
+        match type_tag {
+            OscTypeTag::Int => OscType::Int(int as i32),
+            OscTypeTag::Long => OscType::Long(int),
+            OscTypeTag::Double => OscType::Double(float),
+            _ => OscType::Float(float as f32),
+        }
+    }
+}
+
+fn to_f64(arg: &OscType) -> Option<f64> {
+    use OscType::*;
+    let v = match arg {
+        Int(v) => *v as f64,
+        Long(v) => *v as f64,
+        Float(v) => *v as f64,
+        Double(v) => *v,
+        Bool(v) => {
+            if *v {
+                1.0
+            } else {
+                -1.0
+            }
+        }
+        _ => return None,
+    };
+    Some(v)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sign() {
+        // Given
+        let settings = settings(OscDeltaMode::Sign);
+        // Then
+        assert_eq!(settings.interpret(&OscType::Int(5)), Some(rel(1)));
+        assert_eq!(settings.interpret(&OscType::Float(-0.3)), Some(rel(-1)));
+        assert_eq!(settings.interpret(&OscType::Int(0)), None);
+        assert_eq!(settings.interpret(&OscType::Bool(false)), Some(rel(-1)));
+        assert_eq!(settings.interpret(&OscType::String("x".into())), None);
+    }
+
+    #[test]
+    fn steps() {
+        // Given
+        let settings = settings(OscDeltaMode::Steps);
+        // Then
+        assert_eq!(settings.interpret(&OscType::Int(5)), Some(rel(5)));
+        assert_eq!(settings.interpret(&OscType::Long(-3)), Some(rel(-3)));
+        assert_eq!(settings.interpret(&OscType::Float(2.4)), Some(rel(2)));
+        assert_eq!(settings.interpret(&OscType::Float(0.2)), None);
+    }
+
+    #[test]
+    fn centered() {
+        // Given
+        let settings = settings(OscDeltaMode::Centered);
+        // Then
+        assert_eq!(settings.interpret(&OscType::Int(66)), Some(rel(2)));
+        assert_eq!(settings.interpret(&OscType::Int(61)), Some(rel(-3)));
+        assert_eq!(settings.interpret(&OscType::Int(64)), None);
+        assert_eq!(settings.interpret(&OscType::Float(0.5)), None);
+        assert_eq!(
+            settings.interpret(&OscType::Float(0.5 + 1.0 / 128.0)),
+            Some(rel(1))
+        );
+        assert_eq!(settings.interpret(&OscType::Double(0.25)), Some(rel(-32)));
+    }
+
+    #[test]
+    fn feedback() {
+        // Given
+        let previous = UnitValue::new(0.5);
+        let up = UnitValue::new(0.5 + 3.0 / 127.0);
+        let down = UnitValue::new(0.49);
+        // Then
+        assert_eq!(
+            settings(OscDeltaMode::Sign).feedback_arg(previous, up, OscTypeTag::Float),
+            Some(OscType::Float(1.0))
+        );
+        assert_eq!(
+            settings(OscDeltaMode::Steps).feedback_arg(previous, up, OscTypeTag::Int),
+            Some(OscType::Int(3))
+        );
+        assert_eq!(
+            settings(OscDeltaMode::Steps).feedback_arg(previous, down, OscTypeTag::Int),
+            Some(OscType::Int(-1))
+        );
+        assert_eq!(
+            settings(OscDeltaMode::Centered).feedback_arg(previous, up, OscTypeTag::Int),
+            Some(OscType::Int(67))
+        );
+        assert_eq!(
+            settings(OscDeltaMode::Centered).feedback_arg(previous, down, OscTypeTag::Double),
+            Some(OscType::Double(0.5 - 1.0 / 128.0))
+        );
+        assert_eq!(
+            settings(OscDeltaMode::Steps).feedback_arg(previous, previous, OscTypeTag::Int),
+            None
+        );
+    }
+
+    fn settings(mode: OscDeltaMode) -> OscDeltaSettings {
+        OscDeltaSettings {
+            mode,
+            relative_feedback: false,
+        }
+    }
+
+    fn rel(increment: i32) -> ControlValue {
+        ControlValue::Relative(DiscreteIncrement::new(increment))
+    }
+}
@@ -0,0 +1,122 @@
+use crate::base::default_util::is_default;
+use crate::domain::MappingCompartment;
+use serde::{Deserialize, Serialize};
+
+/// Lets a compartment parameter follow a parameter of another ReaLearn instance.
+///
+/// Whenever the leading parameter changes, the following parameter is set to
+/// `leader value * scale + offset` (clamped to the valid range).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParameterLink {
+    /// Session ID of the instance which owns the leading parameter.
+    pub session_id: String,
+    /// Index of the leading parameter within the same compartment (zero-based).
+    pub index: u32,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub offset: f32,
+    #[serde(default = "scale_one", skip_serializing_if = "is_scale_one")]
+    pub scale: f32,
+}
+
+impl ParameterLink {
+    pub fn follow(&self, leader_value: f32) -> f32 {
+        (leader_value * self.scale + self.offset).max(0.0).min(1.0)
+    }
+}
+
+fn scale_one() -> f32 {
+    1.0
+}
+
+fn is_scale_one(v: &f32) -> bool {
+    *v == 1.0
+}
+
+/// All parameter links of one instance, with absolute parameter indexes.
+#[derive(Clone, Debug, Default)]
+pub struct ParameterLinking {
+    own_session_id: String,
+    links: Vec<AbsoluteParameterLink>,
+}
+
+#[derive(Clone, Debug)]
+struct AbsoluteParameterLink {
+    follower_index: u32,
+    leader_index: u32,
+    link: ParameterLink,
+}
+
+impl ParameterLinking {
+    /// Takes the links together with the compartment and relative index of the following
+    /// parameter.
+    pub fn new(
+        own_session_id: String,
+        links: impl Iterator<Item = (MappingCompartment, u32, ParameterLink)>,
+    ) -> Self {
+        let links = links
+            .map(|(compartment, rel_index, link)| {
+                let offset = compartment.param_range().start;
+                AbsoluteParameterLink {
+                    follower_index: offset + rel_index,
+                    leader_index: offset + link.index,
+                    link,
+                }
+            })
+            .collect();
+        Self {
+            own_session_id,
+            links,
+        }
+    }
+
+    pub fn own_session_id(&self) -> &str {
+        &self.own_session_id
+    }
+
+    /// Returns the absolute indexes and new values of all parameters which follow the given
+    /// parameter of the given session.
+    pub fn followers<'a>(
+        &'a self,
+        leader_session_id: &'a str,
+        leader_index: u32,
+        leader_value: f32,
+    ) -> impl Iterator<Item = (u32, f32)> + 'a {
+        self.links
+            .iter()
+            .filter(move |l| {
+                l.leader_index == leader_index
+                    && l.link.session_id == leader_session_id
+                    && leader_session_id != self.own_session_id
+            })
+            .map(move |l| (l.follower_index, l.link.follow(leader_value)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn followers() {
+        // Given
+        let link = ParameterLink {
+            session_id: "leader".to_string(),
+            index: 2,
+            offset: 0.25,
+            scale: 0.5,
+        };
+        let linking = ParameterLinking::new(
+            "follower".to_string(),
+            vec![(MappingCompartment::ControllerMappings, 5, link)].into_iter(),
+        );
+        // When
+        let matching: Vec<_> = linking.followers("leader", 102, 1.0).collect();
+        let other_index: Vec<_> = linking.followers("leader", 2, 1.0).collect();
+        let other_session: Vec<_> = linking.followers("other", 102, 1.0).collect();
+        // Then
+        assert_eq!(matching, vec![(105, 0.75)]);
+        assert!(other_index.is_empty());
+        assert!(other_session.is_empty());
+    }
+}
@@ -1,4 +1,4 @@
-use crate::domain::{ControlContext, ParameterArray};
+use crate::domain::{ControlContext, OscAddressCaptures, ParameterArray};
 use derivative::Derivative;
 use reaper_high::{Fx, FxChain, FxChainContext, Project, Reaper, Track};
 use reaper_medium::TypeSpecificPluginContext;
@@ -11,6 +11,8 @@ pub struct ExtendedProcessorContext<'a> {
     context: &'a ProcessorContext,
     params: &'a ParameterArray,
     control_context: ControlContext<'a>,
+    /// Set if the target is resolved on behalf of a mapping with a templated OSC source address.
+    osc_captures: Option<&'a OscAddressCaptures>,
 }
 
 impl<'a> ExtendedProcessorContext<'a> {
@@ -23,6 +25,22 @@ impl<'a> ExtendedProcessorContext<'a> {
             context,
             params,
             control_context,
+            osc_captures: None,
+        }
+    }
+
+    pub fn with_osc_captures<'b>(
+        &self,
+        osc_captures: &'b OscAddressCaptures,
+    ) -> ExtendedProcessorContext<'b>
+    where
+        'a: 'b,
+    {
+        ExtendedProcessorContext {
+            context: self.context,
+            params: self.params,
+            control_context: self.control_context,
+            osc_captures: Some(osc_captures),
         }
     }
 
@@ -37,6 +55,11 @@ impl<'a> ExtendedProcessorContext<'a> {
     pub fn control_context(&self) -> ControlContext {
         self.control_context
     }
+
+    /// Returns the value of the given OSC address capture variable (for dynamic expressions).
+    pub fn osc_capture(&self, name: &str) -> Option<f64> {
+        self.osc_captures?.get(name)
+    }
 }
 
 #[derive(Clone, Derivative)]
@@ -90,6 +113,11 @@ impl ProcessorContext {
     pub fn notify_dirty(&self) {
         self.host.automate(-1, 0.0);
     }
+
+    /// Makes the host query parameter names and values again.
+    pub fn notify_parameter_display_changed(&self) {
+        self.host.update_display();
+    }
 }
 
 /// Calling this in the `new()` method is too early. The containing FX can't generally be found
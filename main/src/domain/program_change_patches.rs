@@ -0,0 +1,204 @@
+use helgoboss_learn::RawMidiEvent;
+use reaper_medium::MidiOutputDeviceId;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::fmt::Write;
+
+/// A patch of an external MIDI device, addressed by optional bank select and program change.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProgramChangePatch {
+    pub name: String,
+    /// Bank select MSB (CC 0).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bank_msb: Option<u8>,
+    /// Bank select LSB (CC 32).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bank_lsb: Option<u8>,
+    /// 0-based program number.
+    pub program: u8,
+}
+
+impl ProgramChangePatch {
+    /// Creates the bank select messages (if any) followed by the program change message.
+    pub fn create_midi_events(&self, channel: u8) -> Vec<RawMidiEvent> {
+        let channel = channel & 0x0F;
+        let cc_status = 0xB0 | channel;
+        let messages = [
+            self.bank_msb.map(|msb| [cc_status, 0, msb & 0x7F]),
+            self.bank_lsb.map(|lsb| [cc_status, 32, lsb & 0x7F]),
+        ];
+        let mut events: Vec<_> = messages
+            .iter()
+            .flatten()
+            .filter_map(|bytes| RawMidiEvent::try_from_slice(0, bytes).ok())
+            .collect();
+        if let Ok(pc) = RawMidiEvent::try_from_slice(0, &[0xC0 | channel, self.program & 0x7F]) {
+            events.push(pc);
+        }
+        events
+    }
+
+    /// Returns whether both patches are addressed by the same MIDI messages.
+    pub fn has_same_address(&self, other: &ProgramChangePatch) -> bool {
+        self.bank_msb == other.bank_msb
+            && self.bank_lsb == other.bank_lsb
+            && self.program == other.program
+    }
+}
+
+impl fmt::Display for ProgramChangePatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(msb) = self.bank_msb {
+            write!(f, "{}", msb)?;
+            if let Some(lsb) = self.bank_lsb {
+                write!(f, ".{}", lsb)?;
+            }
+            f.write_char('/')?;
+        }
+        write!(f, "{}", self.program)?;
+        if !self.name.is_empty() {
+            write!(f, " {}", self.name)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses a patch list in the format used by the mapping panel.
+///
+/// Entries are separated by semicolons. Each entry has the form `[MSB[.LSB]/]PROGRAM [NAME]`,
+/// e.g. `0.3/12 Warm Pad`.
+pub fn parse_program_change_patches(text: &str) -> Result<Vec<ProgramChangePatch>, &'static str> {
+    text.split(';')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(parse_program_change_patch)
+        .collect()
+}
+
+/// Formats a patch list in the format used by the mapping panel.
+pub fn format_program_change_patches(patches: &[ProgramChangePatch]) -> String {
+    let entries: Vec<_> = patches.iter().map(|p| p.to_string()).collect();
+    entries.join("; ")
+}
+
+fn parse_program_change_patch(entry: &str) -> Result<ProgramChangePatch, &'static str> {
+    let (address, name) = match entry.split_once(char::is_whitespace) {
+        None => (entry, ""),
+        Some((address, name)) => (address, name.trim()),
+    };
+    let (bank, program) = match address.split_once('/') {
+        None => (None, address),
+        Some((bank, program)) => (Some(bank), program),
+    };
+    let (bank_msb, bank_lsb) = match bank {
+        None => (None, None),
+        Some(bank) => match bank.split_once('.') {
+            None => (Some(parse_7_bit(bank)?), None),
+            Some((msb, lsb)) => (Some(parse_7_bit(msb)?), Some(parse_7_bit(lsb)?)),
+        },
+    };
+    let patch = ProgramChangePatch {
+        name: name.to_owned(),
+        bank_msb,
+        bank_lsb,
+        program: parse_7_bit(program)?,
+    };
+    Ok(patch)
+}
+
+fn parse_7_bit(text: &str) -> Result<u8, &'static str> {
+    let value: u8 = text.parse().map_err(|_| "invalid patch number")?;
+    if value > 127 {
+        return Err("patch number out of range");
+    }
+    Ok(value)
+}
+
+/// Remembers the patch which has been sent most recently to each device and channel.
+///
+/// External devices don't report their current patch, so this is the only way to provide
+/// feedback for program change targets.
+#[derive(Clone, Debug, Default)]
+pub struct SentProgramChanges {
+    patches: HashMap<(MidiOutputDeviceId, u8), ProgramChangePatch>,
+}
+
+impl SentProgramChanges {
+    pub fn get(&self, dev_id: MidiOutputDeviceId, channel: u8) -> Option<&ProgramChangePatch> {
+        self.patches.get(&(dev_id, channel))
+    }
+
+    pub fn insert(&mut self, dev_id: MidiOutputDeviceId, channel: u8, patch: ProgramChangePatch) {
+        self.patches.insert((dev_id, channel), patch);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_patch_list() {
+        // Given
+        let text = "12 Warm Pad; 0/5; 1.3/127 Bright Lead";
+        // When
+        let patches = parse_program_change_patches(text).unwrap();
+        // Then
+        assert_eq!(
+            patches,
+            vec![
+                patch("Warm Pad", None, None, 12),
+                patch("", Some(0), None, 5),
+                patch("Bright Lead", Some(1), Some(3), 127),
+            ]
+        );
+        assert_eq!(format_program_change_patches(&patches), text);
+    }
+
+    #[test]
+    fn reject_invalid_numbers() {
+        assert!(parse_program_change_patches("128 Too high").is_err());
+        assert!(parse_program_change_patches("a/1").is_err());
+    }
+
+    #[test]
+    fn create_bank_select_and_program_change() {
+        // Given
+        let patch = patch("Bright Lead", Some(1), Some(3), 12);
+        // When
+        let events = patch.create_midi_events(2);
+        // Then
+        let bytes: Vec<_> = events.iter().map(|e| e.bytes().to_vec()).collect();
+        assert_eq!(
+            bytes,
+            vec![vec![0xB2, 0, 1], vec![0xB2, 32, 3], vec![0xC2, 12]]
+        );
+    }
+
+    #[test]
+    fn create_program_change_only() {
+        // Given
+        let patch = patch("Warm Pad", None, None, 5);
+        // When
+        let events = patch.create_midi_events(0);
+        // Then
+        let bytes: Vec<_> = events.iter().map(|e| e.bytes().to_vec()).collect();
+        assert_eq!(bytes, vec![vec![0xC0, 5]]);
+    }
+
+    fn patch(
+        name: &str,
+        bank_msb: Option<u8>,
+        bank_lsb: Option<u8>,
+        program: u8,
+    ) -> ProgramChangePatch {
+        ProgramChangePatch {
+            name: name.to_owned(),
+            bank_msb,
+            bank_lsb,
+            program,
+        }
+    }
+}
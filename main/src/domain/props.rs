@@ -1,10 +1,14 @@
 use crate::domain::{
-    get_track_color, get_track_name, CompoundChangeEvent, CompoundMappingTarget, ControlContext,
-    FeedbackResolution, MainMapping, RealearnTarget, ReaperTarget, UnresolvedCompoundMappingTarget,
+    get_track_color, get_track_folder_depth, get_track_name, AdditionalFeedbackEvent,
+    CompoundChangeEvent, CompoundMappingTarget, ControlContext, FeedbackResolution, MainMapping,
+    RealearnTarget, ReaperTarget, UnresolvedCompoundMappingTarget,
 };
 use enum_dispatch::enum_dispatch;
-use helgoboss_learn::{PropValue, Target};
-use reaper_high::ChangeEvent;
+use helgoboss_learn::{NumericValue, PropValue, Target};
+use reaper_high::{ChangeEvent, Project, Reaper};
+use reaper_medium::PositionInSeconds;
+use std::ffi::CStr;
+use std::os::raw::c_char;
 use std::str::FromStr;
 use strum_macros::EnumString;
 
@@ -75,6 +79,7 @@ pub fn get_prop_value(
 enum Props {
     Mapping(MappingProps),
     Target(TargetProps),
+    Project(ProjectProps),
 }
 
 impl Props {
@@ -95,6 +100,7 @@ impl Props {
                 };
                 p.feedback_resolution(args)
             }
+            Props::Project(p) => Some(p.feedback_resolution()),
         }
     }
 
@@ -125,6 +131,14 @@ impl Props {
                     p.is_affected_by(args)
                 })
                 .unwrap_or(false),
+            Props::Project(p) => {
+                let args = PropIsAffectedByArgs {
+                    event,
+                    object: project_of(target),
+                    control_context,
+                };
+                p.is_affected_by(args)
+            }
         }
     }
 
@@ -150,6 +164,13 @@ impl Props {
                 };
                 p.get_value(args)
             }),
+            Props::Project(p) => {
+                let args = PropGetValueArgs {
+                    object: project_of(target),
+                    control_context,
+                };
+                p.get_value(args)
+            }
         }
     }
 }
@@ -161,9 +182,17 @@ impl FromStr for Props {
         s.parse::<MappingProps>()
             .map(Props::Mapping)
             .or_else(|_| s.parse::<TargetProps>().map(Props::Target))
+            .or_else(|_| s.parse::<ProjectProps>().map(Props::Project))
     }
 }
 
+/// The project of the target or - if the target is not project-specific - the current project.
+fn project_of(target: Option<&CompoundMappingTarget>) -> Project {
+    target
+        .and_then(|t| t.project())
+        .unwrap_or_else(|| Reaper::get().current_project())
+}
+
 #[enum_dispatch]
 #[derive(EnumString)]
 enum MappingProps {
@@ -192,6 +221,8 @@ enum TargetProps {
     TrackName(TargetTrackNameProp),
     #[strum(serialize = "target.track.color")]
     TrackColor(TargetTrackColorProp),
+    #[strum(serialize = "target.track.folder_depth")]
+    TrackFolderDepth(TargetTrackFolderDepthProp),
     #[strum(serialize = "target.fx.index")]
     FxIndex(TargetFxIndexProp),
     #[strum(serialize = "target.fx.name")]
@@ -202,6 +233,25 @@ enum TargetProps {
     RouteName(TargetRouteNameProp),
 }
 
+#[enum_dispatch]
+#[derive(EnumString)]
+enum ProjectProps {
+    #[strum(serialize = "project.position.hmsf")]
+    PositionHmsf(ProjectPositionHmsfProp),
+    #[strum(serialize = "project.position.hmsf.24")]
+    PositionHmsf24(ProjectPositionFixedRateHmsfProp<24>),
+    #[strum(serialize = "project.position.hmsf.25")]
+    PositionHmsf25(ProjectPositionFixedRateHmsfProp<25>),
+    #[strum(serialize = "project.position.hmsf.30")]
+    PositionHmsf30(ProjectPositionFixedRateHmsfProp<30>),
+    #[strum(serialize = "project.position.bbt")]
+    PositionBbt(ProjectPositionBbtProp),
+    #[strum(serialize = "project.position.bars_beats")]
+    PositionBarsBeats(ProjectPositionBarsBeatsProp),
+    #[strum(serialize = "project.position.samples")]
+    PositionSamples(ProjectPositionSamplesProp),
+}
+
 #[enum_dispatch(MappingProps)]
 trait MappingProp {
     /// `None` means that no polling is necessary for feedback because we are notified via events.
@@ -244,6 +294,23 @@ trait TargetProp {
     fn get_value(&self, args: PropGetValueArgs<MappingAndTarget>) -> Option<PropValue>;
 }
 
+#[enum_dispatch(ProjectProps)]
+trait ProjectProp {
+    /// Project props don't have change events, so they always need some kind of polling.
+    fn feedback_resolution(&self) -> FeedbackResolution {
+        FeedbackResolution::High
+    }
+
+    /// Returns whether the value of this property could be affected by the given change event.
+    fn is_affected_by(&self, args: PropIsAffectedByArgs<Project>) -> bool {
+        let _ = args;
+        false
+    }
+
+    /// Returns the current value of this property.
+    fn get_value(&self, args: PropGetValueArgs<Project>) -> Option<PropValue>;
+}
+
 #[allow(dead_code)]
 struct MappingAndTarget<'a> {
     pub mapping: &'a MainMapping,
@@ -453,6 +520,24 @@ impl TargetProp for TargetTrackColorProp {
     }
 }
 
+#[derive(Default)]
+struct TargetTrackFolderDepthProp;
+
+impl TargetProp for TargetTrackFolderDepthProp {
+    fn feedback_resolution(
+        &self,
+        _: PropFeedbackResolutionArgs<MappingAndUnresolvedTarget>,
+    ) -> Option<FeedbackResolution> {
+        // Indenting tracks doesn't cause any change event, so we fall back to polling.
+        Some(FeedbackResolution::High)
+    }
+
+    fn get_value(&self, args: PropGetValueArgs<MappingAndTarget>) -> Option<PropValue> {
+        let depth = get_track_folder_depth(args.object.target.track()?);
+        Some(PropValue::Numeric(NumericValue::Discrete(depth as i32)))
+    }
+}
+
 #[derive(Default)]
 struct TargetFxNameProp;
 
@@ -494,3 +579,146 @@ impl TargetProp for TargetRouteNameProp {
         ))
     }
 }
+
+/// Uses the frame rate from the project settings.
+#[derive(Default)]
+struct ProjectPositionHmsfProp;
+
+impl ProjectProp for ProjectPositionHmsfProp {
+    fn get_value(&self, args: PropGetValueArgs<Project>) -> Option<PropValue> {
+        let pos = reference_position(args.object);
+        Some(PropValue::Text(format_time_string(pos, TIME_MODE_HMSF)))
+    }
+}
+
+#[derive(Default)]
+struct ProjectPositionFixedRateHmsfProp<const FPS: u32>;
+
+impl<const FPS: u32> ProjectProp for ProjectPositionFixedRateHmsfProp<FPS> {
+    fn get_value(&self, args: PropGetValueArgs<Project>) -> Option<PropValue> {
+        let pos = reference_position(args.object);
+        Some(PropValue::Text(format_hmsf(pos.get(), FPS)))
+    }
+}
+
+#[derive(Default)]
+struct ProjectPositionBbtProp;
+
+impl ProjectProp for ProjectPositionBbtProp {
+    fn get_value(&self, args: PropGetValueArgs<Project>) -> Option<PropValue> {
+        let pos = reference_position(args.object);
+        let beat_info = args.object.beat_info_at(pos);
+        Some(PropValue::Text(format_bbt(
+            beat_info.measure_index,
+            beat_info.beats_since_measure.get(),
+        )))
+    }
+}
+
+#[derive(Default)]
+struct ProjectPositionBarsBeatsProp;
+
+impl ProjectProp for ProjectPositionBarsBeatsProp {
+    fn feedback_resolution(&self) -> FeedbackResolution {
+        FeedbackResolution::Beat
+    }
+
+    fn is_affected_by(&self, args: PropIsAffectedByArgs<Project>) -> bool {
+        matches!(
+            args.event,
+            CompoundChangeEvent::Additional(AdditionalFeedbackEvent::BeatChanged(e))
+                if e.is_full_beat_change && e.project == args.object
+        )
+    }
+
+    fn get_value(&self, args: PropGetValueArgs<Project>) -> Option<PropValue> {
+        let pos = reference_position(args.object);
+        let beat_info = args.object.beat_info_at(pos);
+        Some(PropValue::Text(format!(
+            "{}.{}",
+            beat_info.measure_index + 1,
+            beat_info.beats_since_measure.get().floor() as i32 + 1
+        )))
+    }
+}
+
+#[derive(Default)]
+struct ProjectPositionSamplesProp;
+
+impl ProjectProp for ProjectPositionSamplesProp {
+    fn get_value(&self, args: PropGetValueArgs<Project>) -> Option<PropValue> {
+        let pos = reference_position(args.object);
+        Some(PropValue::Text(format_time_string(pos, TIME_MODE_SAMPLES)))
+    }
+}
+
+/// Resolution of the ticks in bars.beats.ticks display.
+const TICKS_PER_BEAT: f64 = 960.0;
+/// Time mode overrides as understood by REAPER's `format_timestr_pos`.
+const TIME_MODE_SAMPLES: i32 = 4;
+const TIME_MODE_HMSF: i32 = 5;
+
+/// The play position while playing, otherwise the edit cursor position.
+fn reference_position(project: Project) -> PositionInSeconds {
+    if project.is_playing() {
+        project.play_position_latency_compensated()
+    } else {
+        project.edit_cursor_position()
+    }
+}
+
+fn format_time_string(pos: PositionInSeconds, mode: i32) -> String {
+    let mut buffer = [0 as c_char; 64];
+    unsafe {
+        Reaper::get().medium_reaper().low().format_timestr_pos(
+            pos.get(),
+            buffer.as_mut_ptr(),
+            buffer.len() as _,
+            mode,
+        );
+        CStr::from_ptr(buffer.as_ptr())
+            .to_string_lossy()
+            .into_owned()
+    }
+}
+
+fn format_hmsf(secs: f64, fps: u32) -> String {
+    let sign = if secs < 0.0 { "-" } else { "" };
+    let total_frames = (secs.abs() * fps as f64).floor() as u64;
+    let fps = fps as u64;
+    let f = total_frames % fps;
+    let total_secs = total_frames / fps;
+    format!(
+        "{}{}:{:02}:{:02}:{:02}",
+        sign,
+        total_secs / 3600,
+        (total_secs / 60) % 60,
+        total_secs % 60,
+        f
+    )
+}
+
+/// Bars and beats are one-based as in REAPER.
+fn format_bbt(measure_index: i32, beats_since_measure: f64) -> String {
+    let beats = beats_since_measure.max(0.0);
+    let ticks = ((beats - beats.floor()) * TICKS_PER_BEAT).floor() as u32;
+    format!(
+        "{}.{}.{:03}",
+        measure_index + 1,
+        beats.floor() as u32 + 1,
+        ticks
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn position_formats() {
+        assert_eq!(format_hmsf(3723.5, 24), "1:02:03:12");
+        assert_eq!(format_hmsf(-1.0, 25), "-0:00:01:00");
+        assert_eq!(format_bbt(4, 1.5), "5.2.480");
+        assert_eq!(format_bbt(0, 0.0), "1.1.000");
+    }
+}
@@ -0,0 +1,236 @@
+use derive_more::Display;
+use enum_iterator::IntoEnumIterator;
+use helgoboss_learn::{ControlValue, UnitValue};
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// Settings for sweeping the target value while a button is held ("ramp while held").
+///
+/// Meant for sustain-pedal style foot controllers which can only send on/off but should still
+/// be able to move continuous parameters.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct RampSettings {
+    /// Time it takes to sweep from the minimum to the maximum.
+    pub duration: Duration,
+    pub curve: RampCurve,
+    /// If `true`, releasing the button sweeps back down to the minimum.
+    pub return_on_release: bool,
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    Debug,
+    Serialize,
+    Deserialize,
+    IntoEnumIterator,
+    TryFromPrimitive,
+    IntoPrimitive,
+    Display,
+)]
+#[repr(usize)]
+pub enum RampCurve {
+    #[serde(rename = "linear")]
+    #[display(fmt = "Linear")]
+    Linear,
+    /// Starts slowly and gets faster towards the end.
+    #[serde(rename = "exponential")]
+    #[display(fmt = "Exponential")]
+    Exponential,
+    /// Starts fast and gets slower towards the end.
+    #[serde(rename = "logarithmic")]
+    #[display(fmt = "Logarithmic")]
+    Logarithmic,
+}
+
+impl Default for RampCurve {
+    fn default() -> Self {
+        RampCurve::Linear
+    }
+}
+
+impl RampCurve {
+    fn apply(self, position: f64) -> f64 {
+        use RampCurve::*;
+        match self {
+            Linear => position,
+            Exponential => position * position,
+            Logarithmic => 1.0 - (1.0 - position) * (1.0 - position),
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum RampDirection {
+    Up,
+    Down,
+}
+
+/// Keeps track of the virtual fader position which is moved while the button is held.
+///
+/// The button presses themselves never reach the mode. Instead, the main processor polls the
+/// ramp and feeds the resulting positions into the mode as if they came from a fader.
+#[derive(Clone, Debug, Default)]
+pub struct Ramp {
+    /// Linear position within the sweep, before applying the curve.
+    position: f64,
+    direction: Option<RampDirection>,
+    last_tick: Option<Instant>,
+}
+
+impl Ramp {
+    /// Starts or stops moving depending on whether the button has been pressed or released.
+    ///
+    /// Returns `false` if the value is not a button value (e.g. a relative one) and should
+    /// therefore be processed as usual.
+    pub fn process_button(
+        &mut self,
+        settings: &RampSettings,
+        value: ControlValue,
+        now: Instant,
+    ) -> bool {
+        let v = match value.to_unit_value() {
+            Ok(v) => v,
+            Err(_) => return false,
+        };
+        self.direction = if v.is_zero() {
+            if settings.return_on_release && self.position > 0.0 {
+                Some(RampDirection::Down)
+            } else {
+                None
+            }
+        } else {
+            // Without returning on release, the next press would have nothing left to do.
+            if !settings.return_on_release && self.position >= 1.0 {
+                self.position = 0.0;
+            }
+            Some(RampDirection::Up)
+        };
+        self.last_tick = Some(now);
+        true
+    }
+
+    /// Advances the position according to the time elapsed since the last call.
+    ///
+    /// Returns `None` if the ramp is not moving. The value at which the ramp arrives at one of
+    /// its ends is returned before it stops.
+    pub fn poll(&mut self, settings: &RampSettings, now: Instant) -> Option<UnitValue> {
+        let direction = self.direction?;
+        let elapsed = match self.last_tick {
+            None => Duration::ZERO,
+            Some(t) => now.saturating_duration_since(t),
+        };
+        self.last_tick = Some(now);
+        let delta = if settings.duration.is_zero() {
+            1.0
+        } else {
+            elapsed.as_secs_f64() / settings.duration.as_secs_f64()
+        };
+        self.position = match direction {
+            RampDirection::Up => (self.position + delta).min(1.0),
+            RampDirection::Down => (self.position - delta).max(0.0),
+        };
+        if self.position <= 0.0 || self.position >= 1.0 {
+            self.direction = None;
+        }
+        Some(UnitValue::new_clamped(settings.curve.apply(self.position)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ramp_up_while_held() {
+        // Given
+        let settings = settings(RampCurve::Linear, false);
+        let mut ramp = Ramp::default();
+        let start = Instant::now();
+        // When
+        ramp.process_button(&settings, abs(1.0), start);
+        let first = ramp.poll(&settings, start + Duration::from_millis(250));
+        let second = ramp.poll(&settings, start + Duration::from_millis(500));
+        ramp.process_button(&settings, abs(0.0), start + Duration::from_millis(500));
+        let after_release = ramp.poll(&settings, start + Duration::from_millis(750));
+        // Then
+        assert_eq!(first, Some(UnitValue::new(0.25)));
+        assert_eq!(second, Some(UnitValue::new(0.5)));
+        assert_eq!(after_release, None);
+    }
+
+    #[test]
+    fn stop_at_max() {
+        // Given
+        let settings = settings(RampCurve::Linear, false);
+        let mut ramp = Ramp::default();
+        let start = Instant::now();
+        // When
+        ramp.process_button(&settings, abs(1.0), start);
+        let max = ramp.poll(&settings, start + Duration::from_millis(1500));
+        let after_max = ramp.poll(&settings, start + Duration::from_millis(1600));
+        // Then
+        assert_eq!(max, Some(UnitValue::MAX));
+        assert_eq!(after_max, None);
+    }
+
+    #[test]
+    fn return_on_release() {
+        // Given
+        let settings = settings(RampCurve::Linear, true);
+        let mut ramp = Ramp::default();
+        let start = Instant::now();
+        // When
+        ramp.process_button(&settings, abs(1.0), start);
+        ramp.poll(&settings, start + Duration::from_millis(500));
+        ramp.process_button(&settings, abs(0.0), start + Duration::from_millis(500));
+        let returning = ramp.poll(&settings, start + Duration::from_millis(750));
+        let min = ramp.poll(&settings, start + Duration::from_millis(1500));
+        let after_min = ramp.poll(&settings, start + Duration::from_millis(1600));
+        // Then
+        assert_eq!(returning, Some(UnitValue::new(0.25)));
+        assert_eq!(min, Some(UnitValue::MIN));
+        assert_eq!(after_min, None);
+    }
+
+    #[test]
+    fn apply_curve() {
+        // Given
+        let settings = settings(RampCurve::Exponential, false);
+        let mut ramp = Ramp::default();
+        let start = Instant::now();
+        // When
+        ramp.process_button(&settings, abs(1.0), start);
+        let value = ramp.poll(&settings, start + Duration::from_millis(500));
+        // Then
+        assert_eq!(value, Some(UnitValue::new(0.25)));
+    }
+
+    #[test]
+    fn let_through_relative() {
+        // Given
+        let settings = settings(RampCurve::Linear, false);
+        let mut ramp = Ramp::default();
+        let relative = ControlValue::Relative(helgoboss_learn::DiscreteIncrement::new(1));
+        // When
+        let consumed = ramp.process_button(&settings, relative, Instant::now());
+        // Then
+        assert!(!consumed);
+        assert_eq!(ramp.poll(&settings, Instant::now()), None);
+    }
+
+    fn settings(curve: RampCurve, return_on_release: bool) -> RampSettings {
+        RampSettings {
+            duration: Duration::from_secs(1),
+            curve,
+            return_on_release,
+        }
+    }
+
+    fn abs(v: f64) -> ControlValue {
+        ControlValue::AbsoluteContinuous(UnitValue::new(v))
+    }
+}
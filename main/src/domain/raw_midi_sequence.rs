@@ -0,0 +1,373 @@
+use crate::domain::MidiDestination;
+use helgoboss_learn::{AbsoluteValue, RawMidiEvent, RawMidiPattern};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+/// Maximum number of delayed sequence messages which can wait for being sent at the same time.
+///
+/// Memory for them is reserved up-front because the scheduler is used in the real-time processor.
+const DELAYED_CAPACITY: usize = 500;
+
+/// Maximum number of delayed sequence messages for the FX output.
+///
+/// Lower than the total capacity because FX output messages can only be sent while the plug-in is
+/// processed. They shouldn't be able to take away all the room from messages to devices.
+const DELAYED_FX_OUTPUT_CAPACITY: usize = 100;
+
+/// Delayed messages which are due since longer than this and still couldn't be sent are dropped.
+///
+/// Happens with FX output messages if the plug-in is bypassed or its track is not processed.
+const MAX_DELAYED_OVERDUE: Duration = Duration::from_secs(1);
+
+/// Sequence of raw MIDI message patterns, optionally with delays between the messages.
+///
+/// Needed for devices which require init sequences or paged display updates. In textual form,
+/// entries are separated by semicolons. An entry of the form `<n>ms` is a delay, e.g.
+/// `B0 63 01; B0 62 02; 20ms; B0 06 [0gfe dcba]`. A delay must be followed by a message.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RawMidiSequence {
+    /// Never empty.
+    steps: Vec<RawMidiSequenceStep>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RawMidiSequenceStep {
+    /// Pause between the previous message and this one.
+    pub delay: Duration,
+    pub pattern: RawMidiPattern,
+}
+
+impl Default for RawMidiSequence {
+    fn default() -> Self {
+        Self::from_pattern(Default::default())
+    }
+}
+
+impl RawMidiSequence {
+    pub fn from_pattern(pattern: RawMidiPattern) -> Self {
+        Self {
+            steps: vec![RawMidiSequenceStep {
+                delay: Duration::default(),
+                pattern,
+            }],
+        }
+    }
+
+    pub fn steps(&self) -> &[RawMidiSequenceStep] {
+        &self.steps
+    }
+
+    /// Returns the pattern which decides about control type and step size.
+    ///
+    /// That's the first one with the highest resolution, so a sequence which just adds some
+    /// constant messages around a value-carrying message behaves like the value-carrying message.
+    pub fn main_pattern(&self) -> &RawMidiPattern {
+        let mut main = &self.steps[0].pattern;
+        for step in &self.steps[1..] {
+            if step.pattern.resolution() > main.resolution() {
+                main = &step.pattern;
+            }
+        }
+        main
+    }
+
+    /// Creates the MIDI events resulting from the given value and passes them to `emit`, together
+    /// with the time which should pass (counted from now) before sending each of them.
+    pub fn create_midi_events(
+        &self,
+        value: AbsoluteValue,
+        mut emit: impl FnMut(Duration, RawMidiEvent),
+    ) {
+        let mut offset = Duration::default();
+        for step in &self.steps {
+            offset += step.delay;
+            emit(offset, step.pattern.to_concrete_midi_event(value));
+        }
+    }
+}
+
+impl FromStr for RawMidiSequence {
+    type Err = &'static str;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut steps = vec![];
+        let mut delay = Duration::default();
+        let mut ends_with_delay = false;
+        for entry in text.split(';').map(str::trim).filter(|e| !e.is_empty()) {
+            if let Some(millis) = entry.strip_suffix("ms") {
+                let millis: u64 = millis.trim().parse().map_err(|_| "invalid delay")?;
+                delay += Duration::from_millis(millis);
+                ends_with_delay = true;
+                continue;
+            }
+            let pattern = entry.parse().map_err(|_| "invalid MIDI message pattern")?;
+            steps.push(RawMidiSequenceStep { delay, pattern });
+            delay = Duration::default();
+            ends_with_delay = false;
+        }
+        if steps.is_empty() {
+            return Err("no MIDI message given");
+        }
+        if ends_with_delay {
+            // Nothing would wait for it.
+            return Err("delay not followed by MIDI message");
+        }
+        Ok(Self { steps })
+    }
+}
+
+/// Keeps delayed messages of raw MIDI sequences until they are due.
+///
+/// Doesn't allocate after creation.
+#[derive(Debug)]
+pub struct DelayedMidiEventScheduler {
+    /// Sorted by due time. Messages with the same due time are kept in scheduling order.
+    pending: Vec<DelayedMidiEvent>,
+    fx_output_count: usize,
+}
+
+#[derive(Debug)]
+struct DelayedMidiEvent {
+    due: Instant,
+    destination: MidiDestination,
+    event: RawMidiEvent,
+}
+
+impl Default for DelayedMidiEventScheduler {
+    fn default() -> Self {
+        Self {
+            pending: Vec::with_capacity(DELAYED_CAPACITY),
+            fx_output_count: 0,
+        }
+    }
+}
+
+impl DelayedMidiEventScheduler {
+    /// Returns `false` if there are too many pending messages already, in which case the message
+    /// is dropped (we must not allocate).
+    pub fn schedule(
+        &mut self,
+        due: Instant,
+        destination: MidiDestination,
+        event: RawMidiEvent,
+    ) -> bool {
+        if self.pending.len() == self.pending.capacity() {
+            return false;
+        }
+        let is_fx_output = destination == MidiDestination::FxOutput;
+        if is_fx_output {
+            if self.fx_output_count == DELAYED_FX_OUTPUT_CAPACITY {
+                return false;
+            }
+            self.fx_output_count += 1;
+        }
+        let index = self
+            .pending
+            .iter()
+            .position(|e| e.due > due)
+            .unwrap_or_else(|| self.pending.len());
+        self.pending.insert(
+            index,
+            DelayedMidiEvent {
+                due,
+                destination,
+                event,
+            },
+        );
+        true
+    }
+
+    /// Passes all due messages to `send_now`, in the order in which they became due.
+    ///
+    /// If `send_now` returns `false` (e.g. because the destination can't be served by the
+    /// caller), the message stays pending - unless it's overdue for too long already.
+    pub fn send_due(
+        &mut self,
+        now: Instant,
+        mut send_now: impl FnMut(MidiDestination, &RawMidiEvent) -> bool,
+    ) {
+        let fx_output_count = &mut self.fx_output_count;
+        self.pending.retain(|e| {
+            if e.due > now {
+                return true;
+            }
+            let is_done = send_now(e.destination, &e.event)
+                || now.duration_since(e.due) > MAX_DELAYED_OVERDUE;
+            if is_done && e.destination == MidiDestination::FxOutput {
+                *fx_output_count -= 1;
+            }
+            !is_done
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use helgoboss_learn::UnitValue;
+    use reaper_medium::MidiOutputDeviceId;
+
+    #[test]
+    fn parse_single_message() {
+        // Given
+        let text = "B0 07 7F";
+        // When
+        let sequence: RawMidiSequence = text.parse().unwrap();
+        // Then
+        assert_eq!(sequence.steps().len(), 1);
+        assert_eq!(sequence.steps()[0].delay, ms(0));
+    }
+
+    #[test]
+    fn parse_sequence_with_delays() {
+        // Given
+        let text = "B0 63 01; B0 62 02; 20ms; 5ms; B0 06 7F";
+        // When
+        let sequence: RawMidiSequence = text.parse().unwrap();
+        // Then
+        let delays: Vec<_> = sequence.steps().iter().map(|s| s.delay).collect();
+        assert_eq!(delays, vec![ms(0), ms(0), ms(25)]);
+    }
+
+    #[test]
+    fn reject_invalid_sequences() {
+        assert!("".parse::<RawMidiSequence>().is_err());
+        assert!("20ms".parse::<RawMidiSequence>().is_err());
+        assert!("B0 07 7F; xms".parse::<RawMidiSequence>().is_err());
+        assert!("B0 07 7F; 100ms".parse::<RawMidiSequence>().is_err());
+    }
+
+    #[test]
+    fn create_events_with_accumulated_delays() {
+        // Given
+        let sequence: RawMidiSequence = "90 3C 7F; 10ms; 90 3E 7F; 90 40 7F; 5ms; 90 41 7F"
+            .parse()
+            .unwrap();
+        // When
+        let mut offsets = vec![];
+        sequence.create_midi_events(AbsoluteValue::Continuous(UnitValue::MAX), |offset, e| {
+            offsets.push((offset, e.bytes()[1]))
+        });
+        // Then
+        assert_eq!(
+            offsets,
+            vec![
+                (ms(0), 0x3C),
+                (ms(10), 0x3E),
+                (ms(10), 0x40),
+                (ms(15), 0x41)
+            ]
+        );
+    }
+
+    #[test]
+    fn send_due_events() {
+        // Given
+        let mut scheduler = DelayedMidiEventScheduler::default();
+        let device = MidiDestination::Device(MidiOutputDeviceId::new(0));
+        let now = Instant::now();
+        scheduler.schedule(now + ms(20), device, note_on(1));
+        scheduler.schedule(now + ms(10), device, note_on(2));
+        let mut sent = vec![];
+        // When
+        scheduler.send_due(now + ms(15), |_, e| {
+            sent.push(e.bytes()[1]);
+            true
+        });
+        // Then
+        assert_eq!(sent, vec![2]);
+        scheduler.send_due(now + ms(20), |_, e| {
+            sent.push(e.bytes()[1]);
+            true
+        });
+        assert_eq!(sent, vec![2, 1]);
+    }
+
+    #[test]
+    fn keep_events_for_other_destinations() {
+        // Given
+        let mut scheduler = DelayedMidiEventScheduler::default();
+        let device = MidiDestination::Device(MidiOutputDeviceId::new(0));
+        let now = Instant::now();
+        scheduler.schedule(now, MidiDestination::FxOutput, note_on(1));
+        scheduler.schedule(now, device, note_on(2));
+        let mut sent = vec![];
+        // When
+        scheduler.send_due(now, |d, e| {
+            if d != device {
+                return false;
+            }
+            sent.push(e.bytes()[1]);
+            true
+        });
+        // Then
+        assert_eq!(sent, vec![2]);
+        scheduler.send_due(now, |_, e| {
+            sent.push(e.bytes()[1]);
+            true
+        });
+        assert_eq!(sent, vec![2, 1]);
+    }
+
+    #[test]
+    fn send_due_events_in_due_order() {
+        // Given
+        let mut scheduler = DelayedMidiEventScheduler::default();
+        let device = MidiDestination::Device(MidiOutputDeviceId::new(0));
+        let now = Instant::now();
+        scheduler.schedule(now + ms(30), device, note_on(1));
+        scheduler.schedule(now + ms(10), device, note_on(2));
+        scheduler.schedule(now + ms(20), device, note_on(3));
+        scheduler.schedule(now + ms(10), device, note_on(4));
+        let mut sent = vec![];
+        // When
+        scheduler.send_due(now + ms(30), |_, e| {
+            sent.push(e.bytes()[1]);
+            true
+        });
+        // Then
+        assert_eq!(sent, vec![2, 4, 3, 1]);
+    }
+
+    #[test]
+    fn drop_stale_events() {
+        // Given
+        let mut scheduler = DelayedMidiEventScheduler::default();
+        let now = Instant::now();
+        scheduler.schedule(now, MidiDestination::FxOutput, note_on(1));
+        // When
+        scheduler.send_due(now + MAX_DELAYED_OVERDUE + ms(1), |_, _| false);
+        // Then
+        let mut sent = vec![];
+        scheduler.send_due(now + MAX_DELAYED_OVERDUE + ms(2), |_, e| {
+            sent.push(e.bytes()[1]);
+            true
+        });
+        assert!(sent.is_empty());
+    }
+
+    #[test]
+    fn limit_fx_output_events() {
+        // Given
+        let mut scheduler = DelayedMidiEventScheduler::default();
+        let device = MidiDestination::Device(MidiOutputDeviceId::new(0));
+        let now = Instant::now();
+        // When
+        for _ in 0..DELAYED_FX_OUTPUT_CAPACITY {
+            assert!(scheduler.schedule(now, MidiDestination::FxOutput, note_on(1)));
+        }
+        // Then
+        assert!(!scheduler.schedule(now, MidiDestination::FxOutput, note_on(1)));
+        assert!(scheduler.schedule(now, device, note_on(2)));
+        scheduler.send_due(now, |d, _| d == MidiDestination::FxOutput);
+        assert!(scheduler.schedule(now, MidiDestination::FxOutput, note_on(1)));
+    }
+
+    fn note_on(note: u8) -> RawMidiEvent {
+        RawMidiEvent::try_from_slice(0, &[0x90, note, 127]).unwrap()
+    }
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+}
@@ -1,13 +1,14 @@
 use crate::domain::{
     classify_midi_message, CompoundMappingSource, ControlMainTask, ControlMode, ControlOptions,
-    Event, FeedbackSendBehavior, Garbage, GarbageBin, InputMatchResult, InstanceId,
-    LifecycleMidiMessage, LifecyclePhase, MappingCompartment, MappingId, MidiClockCalculator,
-    MidiMessageClassification, MidiScanResult, MidiScanner, NormalRealTimeToMainThreadTask,
-    OrderedMappingMap, OwnedIncomingMidiMessage, PartialControlMatch,
-    PersistentMappingProcessingState, QualifiedMappingId, RealTimeCompoundMappingTarget,
-    RealTimeMapping, RealTimeReaperTarget, SampleOffset, SendMidiDestination, VirtualSourceValue,
+    DelayedMidiEventScheduler, Event, FeedbackSendBehavior, Garbage, GarbageBin, InputMatchResult,
+    InstanceId, LayerId, LifecycleMidiMessage, LifecyclePhase, MappingCompartment, MappingId,
+    MidiClockCalculator, MidiMessageClassification, MidiScanResult, MidiScanner, MidiSendTarget,
+    MmcMessage, NormalRealTimeToMainThreadTask, OrderedMappingMap, OwnedIncomingMidiMessage,
+    PartialControlMatch, PersistentMappingProcessingState, QualifiedMappingId,
+    RealTimeCompoundMappingTarget, RealTimeMapping, RealTimeReaperTarget, RtpMidiDeviceId,
+    SampleOffset, ScaleQuantizer, SendMidiDestination, VirtualSourceValue,
 };
-use helgoboss_learn::{ControlValue, MidiSourceValue};
+use helgoboss_learn::{AbsoluteValue, ControlValue, MidiSourceValue, RawMidiEvent};
 use helgoboss_midi::{
     Channel, ControlChange14BitMessage, ControlChange14BitMessageScanner, DataEntryByteOrder,
     ParameterNumberMessage, PollingParameterNumberMessageScanner, RawShortMessage, ShortMessage,
@@ -22,7 +23,7 @@ use assert_no_alloc::permit_alloc;
 use enum_map::{enum_map, EnumMap};
 use std::convert::TryInto;
 use std::ptr::null_mut;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use vst::api::{EventType, Events, SysExEvent};
 use vst::host::Host;
 use vst::plugin::HostCallback;
@@ -41,9 +42,16 @@ pub struct RealTimeProcessor {
     mappings: EnumMap<MappingCompartment, OrderedMappingMap<RealTimeMapping>>,
     let_matched_events_through: bool,
     let_unmatched_events_through: bool,
+    /// MIDI input devices which some mappings listen to instead of the instance's control input.
+    ///
+    /// Memory is reserved up-front (one slot per possible device), so updating doesn't allocate.
+    midi_input_device_overrides: Vec<MidiInputDeviceId>,
     // State
+    /// Delayed messages of MIDI send targets with raw MIDI sequences.
+    delayed_midi_event_scheduler: DelayedMidiEventScheduler,
     control_is_globally_enabled: bool,
     feedback_is_globally_enabled: bool,
+    virtual_control_element_offset: u32,
     // Inter-thread communication
     normal_task_receiver: crossbeam_channel::Receiver<NormalRealTimeTask>,
     feedback_task_receiver: crossbeam_channel::Receiver<FeedbackRealTimeTask>,
@@ -53,6 +61,8 @@ pub struct RealTimeProcessor {
     garbage_bin: GarbageBin,
     // Scanners for more complex MIDI message types
     nrpn_scanner: PollingParameterNumberMessageScanner,
+    /// Input from which the messages fed into the (N)RPN scanner most recently arrived.
+    nrpn_scanner_input: MidiControlInput,
     cc_14_bit_scanner: ControlChange14BitMessageScanner,
     // For MIDI capturing
     midi_scanner: MidiScanner,
@@ -91,7 +101,12 @@ impl RealTimeProcessor {
             },
             let_matched_events_through: false,
             let_unmatched_events_through: false,
+            midi_input_device_overrides: Vec::with_capacity(
+                MidiInputDeviceId::MAX_DEVICE_COUNT as usize,
+            ),
+            delayed_midi_event_scheduler: Default::default(),
             nrpn_scanner: PollingParameterNumberMessageScanner::new(Duration::from_millis(1)),
+            nrpn_scanner_input: MidiControlInput::FxInput,
             cc_14_bit_scanner: Default::default(),
             midi_control_input: MidiControlInput::FxInput,
             midi_feedback_output: None,
@@ -99,6 +114,7 @@ impl RealTimeProcessor {
             midi_clock_calculator: Default::default(),
             control_is_globally_enabled: true,
             feedback_is_globally_enabled: true,
+            virtual_control_element_offset: 0,
             garbage_bin,
             input_logging_enabled: false,
             output_logging_enabled: false,
@@ -119,10 +135,10 @@ impl RealTimeProcessor {
                 // are generated by REAPER in order to stop instruments from sounding. But ReaLearn
                 // is not an instrument in the classical sense. We don't want to reset target values
                 // just because play has been pressed!
-                self.process_unmatched(event, Caller::Vst(host));
+                self.process_unmatched(event, MidiControlInput::FxInput, Caller::Vst(host));
                 return;
             }
-            self.process_incoming_midi(event, Caller::Vst(host));
+            self.process_incoming_midi(event, MidiControlInput::FxInput, Caller::Vst(host));
         } else {
             // #33, #290 If MIDI input device is not set to <FX input>, we want to pass through all
             // messages that arrive on FX input.
@@ -131,6 +147,7 @@ impl RealTimeProcessor {
     }
 
     pub fn run_from_vst(&mut self, _sample_count: usize, host: &HostCallback) {
+        self.send_due_delayed_midi_events(Caller::Vst(host));
         if self.get_feedback_driver() == Driver::Vst {
             self.process_feedback_tasks(Caller::Vst(host));
         }
@@ -146,6 +163,19 @@ impl RealTimeProcessor {
         self.midi_control_input
     }
 
+    /// Returns the MIDI input devices which some mappings listen to in addition to the instance's
+    /// control input.
+    pub fn midi_input_device_overrides(&self) -> &[MidiInputDeviceId] {
+        &self.midi_input_device_overrides
+    }
+
+    /// Returns whether this instance is interested in MIDI messages coming from the given device,
+    /// either because it's the control input or because some mappings listen to it.
+    pub fn listens_to_midi_input_device(&self, dev_id: MidiInputDeviceId) -> bool {
+        self.midi_control_input == MidiControlInput::Device(dev_id)
+            || self.midi_input_device_overrides.contains(&dev_id)
+    }
+
     pub fn control_is_globally_enabled(&self) -> bool {
         self.control_is_globally_enabled
     }
@@ -153,12 +183,16 @@ impl RealTimeProcessor {
     /// This should be called by audio hook in normal mode whenever it receives a MIDI message that
     /// is relevant *for this ReaLearn instance* (the input device is not checked again).
     ///
+    /// The given input is the one via which the message arrived. It decides which mappings get to
+    /// see the message.
+    ///
     /// Returns whether this message should be filtered out from the global MIDI stream.
     pub fn process_incoming_midi_from_audio_hook(
         &mut self,
         event: Event<IncomingMidiMessage>,
+        input: MidiControlInput,
     ) -> bool {
-        let matched = self.process_incoming_midi(event, Caller::AudioHook);
+        let matched = self.process_incoming_midi(event, input, Caller::AudioHook);
         let let_through = (matched && self.let_matched_events_through)
             || (!matched && self.let_unmatched_events_through);
         !let_through
@@ -208,6 +242,7 @@ impl RealTimeProcessor {
         // Increase MIDI clock calculator's sample counter
         self.midi_clock_calculator
             .increase_sample_counter_by(sample_count as u64);
+        self.send_due_delayed_midi_events(Caller::AudioHook);
         // Process occasional tasks sent from other thread (probably main thread)
         if might_be_rebirth {
             self.request_full_sync_and_discard_tasks_if_successful();
@@ -219,6 +254,29 @@ impl RealTimeProcessor {
                 UpdateControlIsGloballyEnabled(is_enabled) => {
                     self.control_is_globally_enabled = is_enabled;
                 }
+                UpdateSoloedMapping(soloed_mapping) => {
+                    for compartment in MappingCompartment::enum_iter() {
+                        for m in self.mappings[compartment].values_mut() {
+                            m.update_soloed_mapping(soloed_mapping);
+                        }
+                    }
+                }
+                UpdateActiveLayer(compartment, layer) => {
+                    for m in self.mappings[compartment].values_mut() {
+                        m.update_active_layer(layer);
+                    }
+                }
+                UpdateVirtualControlElementOffset(offset) => {
+                    self.virtual_control_element_offset = offset;
+                }
+                ScheduleDelayedMidiEvents(destination, events) => {
+                    for (due, event) in events.iter() {
+                        // If the scheduler is full, the message is dropped.
+                        self.delayed_midi_event_scheduler
+                            .schedule(*due, destination, *event);
+                    }
+                    self.garbage_bin.dispose(Garbage::DelayedMidiEvents(events));
+                }
                 UpdateFeedbackIsGloballyEnabled(is_enabled) => {
                     // Handle lifecycle MIDI
                     if self.midi_feedback_output.is_some()
@@ -245,6 +303,12 @@ impl RealTimeProcessor {
                             LifecyclePhase::Deactivation,
                         );
                     }
+                    // Take over chords which are still sounding, otherwise they would get stuck
+                    for m in mappings.iter_mut() {
+                        if let Some(old_mapping) = self.mappings[compartment].get_mut(&m.id()) {
+                            m.take_over_held_chord(old_mapping);
+                        }
+                    }
                     // Clear existing mappings (without deallocating)
                     for (_, m) in self.mappings[compartment].drain(..) {
                         self.garbage_bin.dispose_real_time_mapping(m);
@@ -254,6 +318,7 @@ impl RealTimeProcessor {
                     self.mappings[compartment].extend(drained_mappings);
                     self.garbage_bin
                         .dispose(Garbage::RealTimeMappings(mappings));
+                    self.update_midi_input_device_overrides();
                     // Handle activation MIDI
                     if self.processor_feedback_is_effectively_on() {
                         self.send_lifecycle_midi_for_all_mappings_in(
@@ -263,7 +328,7 @@ impl RealTimeProcessor {
                     }
                 }
                 UpdateSingleMapping(compartment, mut mapping) => {
-                    let m = std::mem::replace(&mut *mapping, None)
+                    let mut m = std::mem::replace(&mut *mapping, None)
                         .expect("must send a mapping when updating single mapping");
                     self.garbage_bin
                         .dispose(Garbage::BoxedRealTimeMapping(mapping));
@@ -283,11 +348,16 @@ impl RealTimeProcessor {
                         let is_on_now = m.feedback_is_effectively_on();
                         self.send_lifecycle_midi_diff(&m, was_on_before, is_on_now)
                     }
+                    // Take over chord which is still sounding, otherwise it would get stuck
+                    if let Some(old_mapping) = self.mappings[compartment].get_mut(&m.id()) {
+                        m.take_over_held_chord(old_mapping);
+                    }
                     // Update
                     let old_mapping = self.mappings[compartment].insert(m.id(), m);
                     if let Some(m) = old_mapping {
                         self.garbage_bin.dispose_real_time_mapping(m);
                     }
+                    self.update_midi_input_device_overrides();
                 }
                 UpdatePersistentMappingProcessingState { id, state } => {
                     permit_alloc(|| {
@@ -464,6 +534,19 @@ impl RealTimeProcessor {
         }
     }
 
+    fn update_midi_input_device_overrides(&mut self) {
+        self.midi_input_device_overrides.clear();
+        for compartment in MappingCompartment::enum_iter() {
+            for m in self.mappings[compartment].values() {
+                if let Some(dev_id) = m.options().midi_input_device {
+                    if !self.midi_input_device_overrides.contains(&dev_id) {
+                        self.midi_input_device_overrides.push(dev_id);
+                    }
+                }
+            }
+        }
+    }
+
     fn processor_feedback_is_effectively_on(&self) -> bool {
         self.feedback_is_globally_enabled && self.midi_feedback_output.is_some()
     }
@@ -500,7 +583,12 @@ impl RealTimeProcessor {
                             // TODO-medium We should memorize the offset of the latest short message
                             //  making up the NRPN message instead!
                             let event = Event::without_offset(nrpn_msg);
-                            self.process_incoming_midi_normal_nrpn(event, Caller::AudioHook);
+                            let filter = self.input_filter(self.nrpn_scanner_input);
+                            self.process_incoming_midi_normal_nrpn(
+                                event,
+                                filter,
+                                Caller::AudioHook,
+                            );
                         }
                     }
                 }
@@ -536,6 +624,8 @@ impl RealTimeProcessor {
             // Feedback sent directly to device. Same here: We let the audio hook do everything in
             // order to not run into surprising situations where control or feedback don't work.
             Some(MidiDestination::Device(_)) => AudioHook,
+            // Feedback sent to network. Also goes through the audio hook for the same reasons.
+            Some(MidiDestination::RtpMidi(_)) => AudioHook,
             // Feedback sent to FX output. Here we have to be more careful because sending feedback
             // to FX output involves host callback invocation. This can only be done from the VST
             // plug-in.
@@ -543,6 +633,35 @@ impl RealTimeProcessor {
         }
     }
 
+    /// Sends delayed messages of raw MIDI sequences which are due.
+    ///
+    /// Messages for the FX output stay pending until we are called from the VST plug-in.
+    fn send_due_delayed_midi_events(&mut self, caller: Caller) {
+        let sender = &self.control_main_task_sender;
+        let output_logging_enabled = self.output_logging_enabled;
+        self.delayed_midi_event_scheduler
+            .send_due(Instant::now(), |destination, event| {
+                let is_fx_output = destination == MidiDestination::FxOutput;
+                let can_send = match caller {
+                    Caller::Vst(_) => is_fx_output,
+                    Caller::AudioHook => !is_fx_output,
+                };
+                if !can_send {
+                    return false;
+                }
+                // If the device is gone in the meantime, the message is dropped.
+                send_raw_midi_event(
+                    Some(destination),
+                    event,
+                    SampleOffset::ZERO,
+                    caller,
+                    sender,
+                    output_logging_enabled,
+                );
+                true
+            });
+    }
+
     fn process_feedback_tasks(&self, caller: Caller) {
         // Process (frequent) feedback tasks sent from other thread (probably main thread)
         for task in self
@@ -635,14 +754,27 @@ impl RealTimeProcessor {
         });
     }
 
+    fn input_filter(&self, input: MidiControlInput) -> InputFilter {
+        InputFilter {
+            input,
+            instance_input: self.midi_control_input,
+        }
+    }
+
     /// Returns if this MIDI event matched somehow.
-    fn process_incoming_midi(&mut self, event: Event<IncomingMidiMessage>, caller: Caller) -> bool {
+    fn process_incoming_midi(
+        &mut self,
+        event: Event<IncomingMidiMessage>,
+        input: MidiControlInput,
+        caller: Caller,
+    ) -> bool {
         use MidiMessageClassification::*;
+        let filter = self.input_filter(input);
         match classify_midi_message(event.payload()) {
-            Normal => self.process_incoming_midi_normal(event, caller),
+            Normal => self.process_incoming_midi_normal(event, filter, caller),
             Ignored => {
                 // ReaLearn doesn't process those. Forward them if user wants it.
-                self.process_unmatched(event, caller);
+                self.process_unmatched(event, input, caller);
                 false
             }
             Timing => {
@@ -651,7 +783,7 @@ impl RealTimeProcessor {
                 if self.control_is_globally_enabled {
                     if let Some(bpm) = self.midi_clock_calculator.feed(event.offset()) {
                         let source_value = MidiSourceValue::<RawShortMessage>::Tempo(bpm);
-                        self.control_midi(Event::new(event.offset(), &source_value), caller)
+                        self.control_midi(Event::new(event.offset(), &source_value), filter, caller)
                     } else {
                         false
                     }
@@ -672,6 +804,7 @@ impl RealTimeProcessor {
     fn process_incoming_midi_normal(
         &mut self,
         event: Event<IncomingMidiMessage>,
+        filter: InputFilter,
         caller: Caller,
     ) -> bool {
         match self.control_mode {
@@ -689,23 +822,36 @@ impl RealTimeProcessor {
                     // confusing. They are consumed. That's the reason why
                     // we do the consumption check at a later state.
                     let matched_or_consumed_plain =
-                        self.process_incoming_midi_normal_plain(event, caller);
+                        self.process_incoming_midi_normal_plain(event, filter, caller);
+                    if let IncomingMidiMessage::SysEx(bytes) = event.payload() {
+                        self.forward_mmc_to_main_processor(bytes, filter);
+                    }
                     let (matched_nrpn, matched_cc14) = match event.payload() {
                         IncomingMidiMessage::Short(short_msg) => {
                             let mut matched_nrpn = false;
+                            if filter.input != self.nrpn_scanner_input {
+                                // Half-received composite messages from another input must not
+                                // be completed with messages from this one.
+                                self.nrpn_scanner.reset();
+                                self.cc_14_bit_scanner.reset();
+                                self.nrpn_scanner_input = filter.input;
+                            }
                             for nrpn_msg in self.nrpn_scanner.feed(&short_msg).iter().flatten() {
                                 let nrpn_event = Event::new(event.offset(), *nrpn_msg);
-                                if self.process_incoming_midi_normal_nrpn(nrpn_event, caller) {
+                                if self
+                                    .process_incoming_midi_normal_nrpn(nrpn_event, filter, caller)
+                                {
                                     matched_nrpn = true;
                                 }
                             }
-                            let matched_cc14 =
-                                if let Some(cc14_msg) = self.cc_14_bit_scanner.feed(&short_msg) {
-                                    let cc14_event = Event::new(event.offset(), cc14_msg);
-                                    self.process_incoming_midi_normal_cc14(cc14_event, caller)
-                                } else {
-                                    false
-                                };
+                            let matched_cc14 = if let Some(cc14_msg) =
+                                self.cc_14_bit_scanner.feed(&short_msg)
+                            {
+                                let cc14_event = Event::new(event.offset(), cc14_msg);
+                                self.process_incoming_midi_normal_cc14(cc14_event, filter, caller)
+                            } else {
+                                false
+                            };
                             (matched_nrpn, matched_cc14)
                         }
                         // A sys-ex message is never part of a compound message.
@@ -746,18 +892,31 @@ impl RealTimeProcessor {
         }
     }
 
+    fn forward_mmc_to_main_processor(&self, bytes: &[u8], filter: InputFilter) {
+        // MMC sources don't support MIDI input device overrides.
+        if filter.input != filter.instance_input {
+            return;
+        }
+        if let Some(msg) = MmcMessage::parse(bytes) {
+            let _ = self
+                .control_main_task_sender
+                .try_send(ControlMainTask::ProcessMmc { msg });
+        }
+    }
+
     /// Returns whether this message matched.
     fn process_incoming_midi_normal_nrpn(
         &mut self,
         event: Event<ParameterNumberMessage>,
+        filter: InputFilter,
         caller: Caller,
     ) -> bool {
         let source_value = MidiSourceValue::<RawShortMessage>::ParameterNumber(event.payload());
-        let matched = self.control_midi(Event::new(event.offset(), &source_value), caller);
+        let matched = self.control_midi(Event::new(event.offset(), &source_value), filter, caller);
         if self.input_logging_enabled {
             self.log_control_input(source_value, false, matched);
         }
-        if self.midi_control_input == MidiControlInput::FxInput
+        if filter.input == MidiControlInput::FxInput
             && ((matched && self.let_matched_events_through)
                 || (!matched && self.let_unmatched_events_through))
         {
@@ -831,14 +990,15 @@ impl RealTimeProcessor {
     fn process_incoming_midi_normal_cc14(
         &mut self,
         event: Event<ControlChange14BitMessage>,
+        filter: InputFilter,
         caller: Caller,
     ) -> bool {
         let source_value = MidiSourceValue::<RawShortMessage>::ControlChange14Bit(event.payload());
-        let matched = self.control_midi(Event::new(event.offset(), &source_value), caller);
+        let matched = self.control_midi(Event::new(event.offset(), &source_value), filter, caller);
         if self.input_logging_enabled {
             self.log_control_input(source_value, false, matched);
         }
-        if self.midi_control_input == MidiControlInput::FxInput
+        if filter.input == MidiControlInput::FxInput
             && ((matched && self.let_matched_events_through)
                 || (!matched && self.let_unmatched_events_through))
         {
@@ -859,10 +1019,11 @@ impl RealTimeProcessor {
     fn process_incoming_midi_normal_plain(
         &mut self,
         event: Event<IncomingMidiMessage>,
+        filter: InputFilter,
         caller: Caller,
     ) -> bool {
         let source_value = event.payload().to_source_value();
-        if self.is_consumed_by_at_least_one_source(event.payload()) {
+        if self.is_consumed_by_at_least_one_source(event.payload(), filter) {
             if self.input_logging_enabled {
                 self.log_control_input(source_value, true, false);
             }
@@ -872,14 +1033,14 @@ impl RealTimeProcessor {
             // and therefore doesn't qualify anymore as a candidate for normal CC sources.
             return true;
         }
-        let matched = self.control_midi(Event::new(event.offset(), &source_value), caller);
+        let matched = self.control_midi(Event::new(event.offset(), &source_value), filter, caller);
         if self.input_logging_enabled {
             self.log_control_input(source_value, false, matched);
         }
         if matched {
-            self.process_matched_short(event, caller);
+            self.process_matched_short(event, filter.input, caller);
         } else {
-            self.process_unmatched(event, caller);
+            self.process_unmatched(event, filter.input, caller);
         }
         matched
     }
@@ -894,6 +1055,7 @@ impl RealTimeProcessor {
     fn control_midi(
         &mut self,
         value_event: Event<&MidiSourceValue<RawShortMessage>>,
+        filter: InputFilter,
         caller: Caller,
     ) -> bool {
         // We do pattern matching in order to use Rust's borrow splitting.
@@ -905,20 +1067,24 @@ impl RealTimeProcessor {
                 controller_mappings,
                 main_mappings,
                 value_event,
+                filter,
                 caller,
                 self.midi_feedback_output,
                 self.output_logging_enabled,
+                self.virtual_control_element_offset,
+                &mut self.delayed_midi_event_scheduler,
             )
         } else {
             unreachable!()
         };
-        let matched_main = self.control_main_mappings_midi(value_event, caller);
+        let matched_main = self.control_main_mappings_midi(value_event, filter, caller);
         matched_main || matched_controller
     }
 
     fn control_main_mappings_midi(
         &mut self,
         source_value_event: Event<&MidiSourceValue<RawShortMessage>>,
+        filter: InputFilter,
         caller: Caller,
     ) -> bool {
         let compartment = MappingCompartment::MainMappings;
@@ -927,7 +1093,9 @@ impl RealTimeProcessor {
             .values_mut()
             // The UI prevents creating main mappings with virtual targets but a JSON import
             // doesn't. Check again that it's a REAPER target.
-            .filter(|m| m.control_is_effectively_on() && m.has_reaper_target())
+            .filter(|m| {
+                m.control_is_effectively_on() && m.has_reaper_target() && filter.lets_through(m)
+            })
         {
             if let CompoundMappingSource::Midi(s) = &m.source() {
                 if let Some(control_value) = s.control(source_value_event.payload()) {
@@ -943,6 +1111,7 @@ impl RealTimeProcessor {
                         caller,
                         self.midi_feedback_output,
                         self.output_logging_enabled,
+                        &mut self.delayed_midi_event_scheduler,
                     );
                     matched = true;
                 }
@@ -951,8 +1120,13 @@ impl RealTimeProcessor {
         matched
     }
 
-    fn process_matched_short(&self, event: Event<IncomingMidiMessage>, caller: Caller) {
-        if self.midi_control_input != MidiControlInput::FxInput {
+    fn process_matched_short(
+        &self,
+        event: Event<IncomingMidiMessage>,
+        input: MidiControlInput,
+        caller: Caller,
+    ) {
+        if input != MidiControlInput::FxInput {
             return;
         }
         if !self.let_matched_events_through {
@@ -961,8 +1135,13 @@ impl RealTimeProcessor {
         self.send_incoming_midi_to_fx_output(event, caller);
     }
 
-    fn process_unmatched(&self, event: Event<IncomingMidiMessage>, caller: Caller) {
-        if self.midi_control_input != MidiControlInput::FxInput {
+    fn process_unmatched(
+        &self,
+        event: Event<IncomingMidiMessage>,
+        input: MidiControlInput,
+        caller: Caller,
+    ) {
+        if input != MidiControlInput::FxInput {
             return;
         }
         if !self.let_unmatched_events_through {
@@ -971,12 +1150,16 @@ impl RealTimeProcessor {
         self.send_incoming_midi_to_fx_output(event, caller);
     }
 
-    fn is_consumed_by_at_least_one_source(&self, msg: IncomingMidiMessage) -> bool {
+    fn is_consumed_by_at_least_one_source(
+        &self,
+        msg: IncomingMidiMessage,
+        filter: InputFilter,
+    ) -> bool {
         use IncomingMidiMessage::*;
         match msg {
-            Short(msg) => self
-                .all_mappings()
-                .any(|m| m.control_is_effectively_on() && m.consumes(msg)),
+            Short(msg) => self.all_mappings().any(|m| {
+                m.control_is_effectively_on() && filter.lets_through(m) && m.consumes(msg)
+            }),
             // Sys-ex is never part of a compound message.
             SysEx(_) => false,
         }
@@ -1046,6 +1229,10 @@ impl RealTimeProcessor {
                         }
                     });
                 }
+                MidiDestination::RtpMidi(_) => {
+                    // Not supported currently. The real-time processor doesn't have access to the
+                    // RTP-MIDI output channel.
+                }
             };
         }
     }
@@ -1241,6 +1428,11 @@ pub enum NormalRealTimeTask {
     ReturnToControlMode,
     UpdateControlIsGloballyEnabled(bool),
     UpdateFeedbackIsGloballyEnabled(bool),
+    UpdateSoloedMapping(Option<QualifiedMappingId>),
+    UpdateActiveLayer(MappingCompartment, LayerId),
+    UpdateVirtualControlElementOffset(u32),
+    /// Delayed messages of a raw MIDI sequence sent by a target which was hit in the main thread.
+    ScheduleDelayedMidiEvents(MidiDestination, Vec<(Instant, RawMidiEvent)>),
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -1304,6 +1496,26 @@ pub enum MidiControlInput {
     FxInput,
     /// Processes MIDI messages coming directly from a MIDI input device.
     Device(MidiInputDeviceId),
+    /// Processes MIDI messages coming from peers connected to an RTP-MIDI session.
+    RtpMidi(RtpMidiDeviceId),
+}
+
+/// Decides which mappings get to see a MIDI message, depending on the input via which it arrived.
+#[derive(Copy, Clone, Debug)]
+struct InputFilter {
+    /// Input via which the message arrived.
+    input: MidiControlInput,
+    /// Control input of the instance, listened to by all mappings without input device override.
+    instance_input: MidiControlInput,
+}
+
+impl InputFilter {
+    fn lets_through(&self, mapping: &RealTimeMapping) -> bool {
+        match mapping.options().midi_input_device {
+            None => self.input == self.instance_input,
+            Some(dev_id) => self.input == MidiControlInput::Device(dev_id),
+        }
+    }
 }
 
 /// MIDI destination to which e.g. ReaLearn's feedback data can be sent.
@@ -1313,8 +1525,11 @@ pub enum MidiDestination {
     FxOutput,
     /// Routes messages directly to a MIDI output device.
     Device(MidiOutputDeviceId),
+    /// Routes messages to all peers connected to an RTP-MIDI session.
+    RtpMidi(RtpMidiDeviceId),
 }
 
+#[allow(clippy::too_many_arguments)]
 fn control_controller_mappings_midi(
     sender: &crossbeam_channel::Sender<ControlMainTask>,
     // Mappings with virtual targets
@@ -1322,15 +1537,18 @@ fn control_controller_mappings_midi(
     // Mappings with virtual sources
     main_mappings: &mut OrderedMappingMap<RealTimeMapping>,
     value_event: Event<&MidiSourceValue<RawShortMessage>>,
+    filter: InputFilter,
     caller: Caller,
     midi_feedback_output: Option<MidiDestination>,
     output_logging_enabled: bool,
+    virtual_control_element_offset: u32,
+    delayed_midi_event_scheduler: &mut DelayedMidiEventScheduler,
 ) -> bool {
     let mut matched = false;
     let mut enforce_target_refresh = false;
     for m in controller_mappings
         .values_mut()
-        .filter(|m| m.control_is_effectively_on())
+        .filter(|m| m.control_is_effectively_on() && filter.lets_through(m))
     {
         if let Some(control_match) = m.control_midi_virtualizing(value_event.payload()) {
             use PartialControlMatch::*;
@@ -1338,7 +1556,10 @@ fn control_controller_mappings_midi(
                 ProcessVirtual(virtual_source_value) => control_main_mappings_virtual(
                     sender,
                     main_mappings,
-                    Event::new(value_event.offset(), virtual_source_value),
+                    Event::new(
+                        value_event.offset(),
+                        virtual_source_value.with_offset(virtual_control_element_offset),
+                    ),
                     ControlOptions {
                         // We inherit "Send feedback after control" to the main processor if it's
                         // enabled for the virtual mapping. That's the easy way to do it.
@@ -1356,6 +1577,7 @@ fn control_controller_mappings_midi(
                     caller,
                     midi_feedback_output,
                     output_logging_enabled,
+                    delayed_midi_event_scheduler,
                 ),
                 ProcessDirect(control_value) => {
                     let _ = process_real_mapping(
@@ -1370,6 +1592,7 @@ fn control_controller_mappings_midi(
                         caller,
                         midi_feedback_output,
                         output_logging_enabled,
+                        delayed_midi_event_scheduler,
                     );
                     // We do this only for transactions of *real* targets matches.
                     enforce_target_refresh = true;
@@ -1394,16 +1617,25 @@ fn process_real_mapping(
     caller: Caller,
     midi_feedback_output: Option<MidiDestination>,
     output_logging_enabled: bool,
+    delayed_midi_event_scheduler: &mut DelayedMidiEventScheduler,
 ) -> Result<(), &'static str> {
     if let Some(RealTimeCompoundMappingTarget::Reaper(reaper_target)) =
         mapping.resolved_target.as_mut()
     {
         // Must be processed here in real-time processor.
+        let source_value = mapping
+            .core
+            .process_velocity_threshold(value_event.payload())
+            .ok_or("ignored because of velocity threshold")?;
+        let source_value = mapping
+            .core
+            .process_noise_filter(source_value)
+            .ok_or("ignored because of noise filter")?;
         let control_value: Option<ControlValue> = mapping
             .core
             .mode
             .control_with_options(
-                value_event.payload(),
+                source_value,
                 reaper_target,
                 (),
                 options.mode_control_options,
@@ -1413,67 +1645,37 @@ fn process_real_mapping(
         let v = control_value
             .ok_or("target already has desired value")?
             .to_absolute_value()?;
+        let quantizer = mapping.core.options.scale_quantizer;
+        let mut send_midi = |t: &mut MidiSendTarget, v: AbsoluteValue| {
+            send_midi_in_real_time(
+                t,
+                v,
+                quantizer,
+                caller,
+                midi_feedback_output,
+                sender,
+                value_event.offset(),
+                output_logging_enabled,
+                delayed_midi_event_scheduler,
+            )
+        };
         match reaper_target {
-            RealTimeReaperTarget::SendMidi(t) => {
-                // This is a type of mapping that we should process right here because we want to
-                // send a MIDI message and this needs to happen in the audio thread.
-                // Going to the main thread and back would be such a waste!
-                let raw_midi_event = t.pattern().to_concrete_midi_event(v);
-                let midi_destination = match caller {
-                    Caller::Vst(_) => match t.destination() {
-                        SendMidiDestination::FxOutput => Some(MidiDestination::FxOutput),
-                        SendMidiDestination::FeedbackOutput => {
-                            Some(midi_feedback_output.ok_or("no feedback output set")?)
-                        }
-                    },
-                    Caller::AudioHook => {
-                        match t.destination() {
-                            SendMidiDestination::FxOutput => {
-                                // Control input = Device | Destination = FX output.
-                                // Not supported currently. It could be by introducing a new
-                                // `FxOutputTask` with a SendMidiToFxOutput variant, tasks being
-                                // processed by `run_from_vst()` only no matter the feedback driver.
-                                None
-                            }
-                            SendMidiDestination::FeedbackOutput => {
-                                Some(midi_feedback_output.ok_or("no feedback output set")?)
-                            }
-                        }
+            RealTimeReaperTarget::SendMidi(t) => send_midi(t, v),
+            RealTimeReaperTarget::Multi(t) => {
+                // Like in the main thread, an error only counts if no child could be hit at all.
+                let mut error = None;
+                let mut at_least_one_child_was_hit = false;
+                for child in &mut t.children {
+                    let child_value = child.scale_value(v);
+                    match send_midi(&mut child.target, child_value) {
+                        Ok(_) => at_least_one_child_was_hit = true,
+                        Err(msg) => error = Some(msg),
                     }
-                };
-                if output_logging_enabled && midi_destination.is_some() {
-                    permit_alloc(|| {
-                        sender
-                            .try_send(ControlMainTask::LogTargetOutput {
-                                event: Box::new(raw_midi_event),
-                            })
-                            .unwrap();
-                    });
                 }
-                let successful = match midi_destination {
-                    Some(MidiDestination::FxOutput) => {
-                        send_raw_midi_to_fx_output(
-                            raw_midi_event.bytes(),
-                            value_event.offset(),
-                            caller,
-                        );
-                        true
-                    }
-                    Some(MidiDestination::Device(dev_id)) => MidiOutputDevice::new(dev_id)
-                        .with_midi_output(|mo| {
-                            if let Some(mo) = mo {
-                                mo.send_msg(&raw_midi_event, SendMidiTime::Instantly);
-                                true
-                            } else {
-                                false
-                            }
-                        }),
-                    _ => false,
-                };
-                if successful {
-                    t.set_artificial_value(v);
+                match error {
+                    Some(msg) if !at_least_one_child_was_hit => Err(msg),
+                    _ => Ok(()),
                 }
-                Ok(())
             }
         }
     } else {
@@ -1488,6 +1690,68 @@ fn process_real_mapping(
     }
 }
 
+/// Sends the MIDI messages of a "Send MIDI" target right here in the audio thread.
+#[allow(clippy::too_many_arguments)]
+fn send_midi_in_real_time(
+    t: &mut MidiSendTarget,
+    v: AbsoluteValue,
+    quantizer: Option<ScaleQuantizer>,
+    caller: Caller,
+    midi_feedback_output: Option<MidiDestination>,
+    sender: &crossbeam_channel::Sender<ControlMainTask>,
+    offset: SampleOffset,
+    output_logging_enabled: bool,
+    delayed_midi_event_scheduler: &mut DelayedMidiEventScheduler,
+) -> Result<(), &'static str> {
+    // This is a type of mapping that we should process right here because we want to send a MIDI
+    // message and this needs to happen in the audio thread. Going to the main thread and back
+    // would be such a waste!
+    let midi_destination = match caller {
+        Caller::Vst(_) => match t.destination() {
+            SendMidiDestination::FxOutput => Some(MidiDestination::FxOutput),
+            SendMidiDestination::FeedbackOutput => {
+                Some(midi_feedback_output.ok_or("no feedback output set")?)
+            }
+        },
+        Caller::AudioHook => {
+            match t.destination() {
+                SendMidiDestination::FxOutput => {
+                    // Control input = Device | Destination = FX output.
+                    // Not supported currently. It could be by introducing a new `FxOutputTask`
+                    // with a SendMidiToFxOutput variant, tasks being processed by `run_from_vst()`
+                    // only no matter the feedback driver.
+                    None
+                }
+                SendMidiDestination::FeedbackOutput => {
+                    Some(midi_feedback_output.ok_or("no feedback output set")?)
+                }
+            }
+        }
+    };
+    let mut successful = midi_destination.is_some();
+    let now = Instant::now();
+    let v = t.create_midi_events(v, quantizer, |delay, raw_midi_event| {
+        let sent = match midi_destination {
+            Some(d) if delay > Duration::default() => {
+                delayed_midi_event_scheduler.schedule(now + delay, d, raw_midi_event)
+            }
+            _ => send_raw_midi_event(
+                midi_destination,
+                &raw_midi_event,
+                offset,
+                caller,
+                sender,
+                output_logging_enabled,
+            ),
+        };
+        successful &= sent;
+    });
+    if successful {
+        t.set_artificial_value(v);
+    }
+    Ok(())
+}
+
 fn forward_control_to_main_processor(
     sender: &crossbeam_channel::Sender<ControlMainTask>,
     compartment: MappingCompartment,
@@ -1500,6 +1764,7 @@ fn forward_control_to_main_processor(
         mapping_id,
         value,
         options,
+        received_at: Instant::now(),
     };
     // If plug-in dropped, the receiver might be gone already because main processor is
     // unregistered synchronously.
@@ -1507,6 +1772,7 @@ fn forward_control_to_main_processor(
 }
 
 /// Returns whether this source value matched one of the mappings.
+#[allow(clippy::too_many_arguments)]
 fn control_main_mappings_virtual(
     sender: &crossbeam_channel::Sender<ControlMainTask>,
     main_mappings: &mut OrderedMappingMap<RealTimeMapping>,
@@ -1515,6 +1781,7 @@ fn control_main_mappings_virtual(
     caller: Caller,
     midi_feedback_output: Option<MidiDestination>,
     output_logging_enabled: bool,
+    delayed_midi_event_scheduler: &mut DelayedMidiEventScheduler,
 ) -> bool {
     // Controller mappings can't have virtual sources, so for now we only need to check
     // main mappings.
@@ -1537,6 +1804,7 @@ fn control_main_mappings_virtual(
                     caller,
                     midi_feedback_output,
                     output_logging_enabled,
+                    delayed_midi_event_scheduler,
                 );
                 matched = true;
             }
@@ -1551,6 +1819,43 @@ enum Driver {
     Vst,
 }
 
+/// Returns whether the event could be sent.
+fn send_raw_midi_event(
+    destination: Option<MidiDestination>,
+    event: &RawMidiEvent,
+    offset: SampleOffset,
+    caller: Caller,
+    sender: &crossbeam_channel::Sender<ControlMainTask>,
+    output_logging_enabled: bool,
+) -> bool {
+    if output_logging_enabled && destination.is_some() {
+        permit_alloc(|| {
+            sender
+                .try_send(ControlMainTask::LogTargetOutput {
+                    event: Box::new(*event),
+                })
+                .unwrap();
+        });
+    }
+    match destination {
+        Some(MidiDestination::FxOutput) => {
+            send_raw_midi_to_fx_output(event.bytes(), offset, caller);
+            true
+        }
+        Some(MidiDestination::Device(dev_id)) => {
+            MidiOutputDevice::new(dev_id).with_midi_output(|mo| {
+                if let Some(mo) = mo {
+                    mo.send_msg(event, SendMidiTime::Instantly);
+                    true
+                } else {
+                    false
+                }
+            })
+        }
+        _ => false,
+    }
+}
+
 fn send_raw_midi_to_fx_output(bytes: &[u8], offset: SampleOffset, caller: Caller) {
     let host = match caller {
         Caller::Vst(h) => h,
@@ -1624,6 +1929,25 @@ impl<'a> IncomingMidiMessage<'a> {
         Ok(res)
     }
 
+    /// Interprets the given complete MIDI message, e.g. one which has been received via network.
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Self, &'static str> {
+        let status = *bytes.first().ok_or("empty MIDI message")?;
+        if status == 0xF0 {
+            return Ok(Self::SysEx(bytes));
+        }
+        let data_byte = |i: usize| -> Result<_, &'static str> {
+            bytes
+                .get(i)
+                .copied()
+                .unwrap_or(0)
+                .try_into()
+                .map_err(|_| "data byte invalid")
+        };
+        let short_msg = RawShortMessage::from_bytes((status, data_byte(1)?, data_byte(2)?))
+            .map_err(|_| "invalid status byte")?;
+        Ok(Self::Short(short_msg))
+    }
+
     pub fn from_reaper(m: &'a reaper_medium::MidiMessage) -> Result<Self, &'static str> {
         let res = if m.r#type() == ShortMessageType::SystemExclusiveStart {
             Self::SysEx(m.as_slice())
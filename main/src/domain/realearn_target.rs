@@ -5,20 +5,30 @@ use crate::domain::ui_util::{
 use crate::domain::{
     AdditionalEelTransformationInput, AdditionalFeedbackEvent, DomainEventHandler, Exclusivity,
     ExtendedProcessorContext, FeedbackAudioHookTask, FeedbackOutput, GroupId, InstanceId,
-    InstanceStateChanged, MainMapping, MappingControlResult, MappingId, OrderedMappingMap,
+    InstanceMessage, InstanceMessageRecipients, InstanceStateChanged, MainMapping,
+    MappingControlResult, MappingId, MidiDestination, NormalRealTimeTask, OrderedMappingMap,
     OscFeedbackTask, ProcessorContext, RealTimeReaperTarget, RealTimeSender, ReaperTarget,
-    SharedInstanceState, Tag, TagScope, TargetCharacter, TrackExclusivity, ACTION_TARGET,
-    ALL_TRACK_FX_ENABLE_TARGET, ANY_ON_TARGET, AUTOMATION_MODE_OVERRIDE_TARGET,
-    AUTOMATION_TOUCH_STATE_TARGET, CLIP_SEEK_TARGET, CLIP_TRANSPORT_TARGET, CLIP_VOLUME_TARGET,
-    ENABLE_INSTANCES_TARGET, ENABLE_MAPPINGS_TARGET, FX_ENABLE_TARGET, FX_NAVIGATE_TARGET,
-    FX_OPEN_TARGET, FX_PARAMETER_TARGET, FX_PRESET_TARGET, GO_TO_BOOKMARK_TARGET,
-    LOAD_FX_SNAPSHOT_TARGET, LOAD_MAPPING_SNAPSHOT_TARGET, MIDI_SEND_TARGET,
-    NAVIGATE_WITHIN_GROUP_TARGET, OSC_SEND_TARGET, PLAYRATE_TARGET, ROUTE_AUTOMATION_MODE_TARGET,
-    ROUTE_MONO_TARGET, ROUTE_MUTE_TARGET, ROUTE_PAN_TARGET, ROUTE_PHASE_TARGET,
-    ROUTE_VOLUME_TARGET, SEEK_TARGET, SELECTED_TRACK_TARGET, TEMPO_TARGET, TRACK_ARM_TARGET,
-    TRACK_AUTOMATION_MODE_TARGET, TRACK_MUTE_TARGET, TRACK_PAN_TARGET, TRACK_PEAK_TARGET,
-    TRACK_PHASE_TARGET, TRACK_SELECTION_TARGET, TRACK_SHOW_TARGET, TRACK_SOLO_TARGET,
-    TRACK_TOOL_TARGET, TRACK_VOLUME_TARGET, TRACK_WIDTH_TARGET, TRANSPORT_TARGET,
+    ScaleQuantizer, SharedInstanceState, Tag, TagScope, TargetCharacter, TrackExclusivity,
+    ACTION_TARGET, ADJUST_MAPPING_MODE_TARGET, ALL_TRACK_FX_ENABLE_TARGET, ANY_ON_TARGET,
+    AUTOMATION_MODE_OVERRIDE_TARGET, AUTOMATION_TOUCH_STATE_TARGET, CLIP_COLUMN_STOP_TARGET,
+    CLIP_CUE_TARGET, CLIP_SCENE_LAUNCH_TARGET, CLIP_SEEK_TARGET, CLIP_TRANSPORT_TARGET,
+    CLIP_VOLUME_TARGET, ENABLE_GROUP_TARGET, ENABLE_INSTANCES_TARGET, ENABLE_MAPPINGS_TARGET,
+    FX_ENABLE_TARGET, FX_NAVIGATE_TARGET, FX_OPEN_TARGET, FX_PARAMETER_BANK_TARGET,
+    FX_PARAMETER_TARGET, FX_PRESET_TARGET, GO_TO_BOOKMARK_TARGET, INVOKE_INSTANCE_TARGET,
+    LOAD_FX_SNAPSHOT_TARGET, LOAD_MAPPING_SNAPSHOT_TARGET, METRONOME_TARGET,
+    MIDI_CLOCK_SEND_TARGET, MIDI_MACRO_SEND_TARGET, MIDI_SEND_TARGET, MONITORING_VOLUME_TARGET,
+    MULTI_TARGET, NAVIGATE_BOOKMARKS_TARGET, NAVIGATE_WITHIN_GROUP_TARGET, OSC_SEND_TARGET,
+    PANIC_TARGET, PLAYRATE_TARGET, PROGRAM_CHANGE_SEND_TARGET, PUNCH_TARGET,
+    ROUTE_AUTOMATION_MODE_TARGET, ROUTE_MONO_TARGET, ROUTE_MUTE_TARGET, ROUTE_PAN_TARGET,
+    ROUTE_PHASE_TARGET, ROUTE_VOLUME_TARGET, RUN_SCRIPT_TARGET, SAVE_MAPPING_SNAPSHOT_TARGET,
+    SEEK_TARGET, SELECTED_TRACK_TARGET, SELECT_LAYER_TARGET, SET_BANK_OFFSET_TARGET,
+    SET_EXT_STATE_TARGET, TEMPO_TARGET, TIME_SELECTION_TARGET, TRACK_ARM_TARGET,
+    TRACK_AUTOMATION_MODE_TARGET, TRACK_CUE_TARGET, TRACK_DELETE_TARGET, TRACK_DUAL_PAN_TARGET,
+    TRACK_DUPLICATE_TARGET, TRACK_FOLDER_SPILL_TARGET, TRACK_INPUT_MONITORING_TARGET,
+    TRACK_INSERT_TARGET, TRACK_MUTE_TARGET, TRACK_PAN_TARGET, TRACK_PEAK_TARGET,
+    TRACK_PHASE_TARGET, TRACK_RECORD_INPUT_TARGET, TRACK_SELECTION_TARGET, TRACK_SHOW_TARGET,
+    TRACK_SOLO_TARGET, TRACK_TOOL_TARGET, TRACK_VOLUME_TARGET, TRACK_WIDTH_TARGET,
+    TRANSPORT_TARGET, UNDO_REDO_TARGET,
 };
 use enum_dispatch::enum_dispatch;
 use enum_iterator::IntoEnumIterator;
@@ -33,6 +43,7 @@ use serde_repr::*;
 use std::collections::HashSet;
 use std::convert::TryInto;
 use std::fmt::{Debug, Display, Formatter};
+use std::time::Instant;
 
 #[enum_dispatch(ReaperTarget)]
 pub trait RealearnTarget {
@@ -273,6 +284,17 @@ pub trait RealearnTarget {
         None
     }
 
+    /// Whether the target needs to be polled regularly because it controls something over time
+    /// (e.g. an arpeggiator).
+    fn wants_to_be_polled_for_control(&self) -> bool {
+        false
+    }
+
+    /// Called regularly in the main thread if `wants_to_be_polled_for_control()` returns `true`.
+    fn poll_control(&mut self, context: MappingControlContext) {
+        let _ = context;
+    }
+
     /// Like `convert_unit_value_to_discrete_value()` but in the other direction.
     ///
     /// Used for parsing discrete values of discrete targets that can't do real parsing according to
@@ -347,6 +369,13 @@ pub fn get_track_color(t: &Track) -> Option<RgbColor> {
 pub trait InstanceContainer: Debug {
     /// Returns activated tags if they don't correspond to the tags in the args.
     fn enable_instances(&self, args: EnableInstancesArgs) -> Option<HashSet<Tag>>;
+
+    /// Lets the given instance clear and resend all feedback, reset its MIDI feedback output
+    /// device and stop all clips.
+    fn panic(&self, instance_id: &InstanceId) -> Result<(), &'static str>;
+
+    /// Delivers the given message to the other instances addressed in the args.
+    fn send_instance_message(&self, args: SendInstanceMessageArgs) -> Result<(), &'static str>;
 }
 
 pub struct EnableInstancesArgs<'a> {
@@ -358,9 +387,18 @@ pub struct EnableInstancesArgs<'a> {
     pub exclusivity: Exclusivity,
 }
 
+pub struct SendInstanceMessageArgs<'a> {
+    pub initiator_instance_id: InstanceId,
+    /// `None` if monitoring FX.
+    pub initiator_project: Option<Project>,
+    pub recipients: &'a InstanceMessageRecipients,
+    pub message: InstanceMessage,
+}
+
 #[derive(Copy, Clone, Debug)]
 pub struct ControlContext<'a> {
     pub feedback_audio_hook_task_sender: &'a RealTimeSender<FeedbackAudioHookTask>,
+    pub normal_real_time_task_sender: &'a RealTimeSender<NormalRealTimeTask>,
     pub osc_feedback_task_sender: &'a crossbeam_channel::Sender<OscFeedbackTask>,
     pub feedback_output: Option<FeedbackOutput>,
     pub instance_container: &'a dyn InstanceContainer,
@@ -368,6 +406,10 @@ pub struct ControlContext<'a> {
     pub instance_id: &'a InstanceId,
     pub output_logging_enabled: bool,
     pub processor_context: &'a ProcessorContext,
+    /// Set while processing an instance message received from another instance.
+    ///
+    /// Contains the number of hops that message has taken so far.
+    pub instance_message_hop_count: Option<u32>,
 }
 
 impl<'a> ControlContext<'a> {
@@ -387,6 +429,21 @@ impl<'a> ControlContext<'a> {
             .send(FeedbackAudioHookTask::SendMidi(dev_id, events))
             .unwrap();
     }
+
+    /// Lets the real-time processor of this instance send the given events as soon as they are
+    /// due.
+    pub fn schedule_delayed_midi(
+        &self,
+        destination: MidiDestination,
+        events: Vec<(Instant, RawMidiEvent)>,
+    ) -> Result<(), &'static str> {
+        self.normal_real_time_task_sender
+            .send(NormalRealTimeTask::ScheduleDelayedMidiEvents(
+                destination,
+                events,
+            ))
+            .map_err(|_| "couldn't schedule delayed MIDI events")
+    }
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -420,6 +477,7 @@ pub struct MappingData {
     pub mapping_id: MappingId,
     pub group_id: GroupId,
     pub last_non_performance_target_value: Option<AbsoluteValue>,
+    pub scale_quantizer: Option<ScaleQuantizer>,
 }
 
 pub type HitInstructionReturnValue = Option<Box<dyn HitInstruction>>;
@@ -466,13 +524,21 @@ pub enum ReaperTargetType {
     AnyOn = 43,
     Action = 0,
     Transport = 16,
+    Punch = 48,
+    Metronome = 49,
     SelectedTrack = 14,
     Seek = 23,
     Playrate = 11,
     Tempo = 10,
+    SetExtState = 65,
+    RunScript = 66,
+    UndoRedo = 69,
+    TimeSelection = 70,
+    MonitoringVolume = 72,
 
     // Marker/region targets
     GoToBookmark = 22,
+    NavigateBookmarks = 55,
 
     // Track targets
     TrackArm = 5,
@@ -485,10 +551,18 @@ pub enum ReaperTargetType {
     TrackAutomationMode = 25,
     AutomationTouchState = 21,
     TrackPan = 4,
+    TrackDualPan = 71,
     TrackWidth = 17,
     TrackVolume = 2,
     TrackShow = 24,
     TrackSolo = 8,
+    TrackInsert = 45,
+    TrackDuplicate = 46,
+    TrackDelete = 47,
+    TrackCue = 51,
+    TrackFolderSpill = 64,
+    TrackRecordInput = 67,
+    TrackInputMonitoring = 68,
 
     // FX chain targets
     FxNavigate = 28,
@@ -498,6 +572,7 @@ pub enum ReaperTargetType {
     FxPreset = 13,
     FxOpen = 27,
     FxParameter = 1,
+    FxParameterBank = 63,
 
     // Send targets
     TrackSendAutomationMode = 42,
@@ -511,16 +586,30 @@ pub enum ReaperTargetType {
     ClipTransport = 31,
     ClipSeek = 32,
     ClipVolume = 33,
+    ClipColumnStop = 50,
+    ClipSceneLaunch = 59,
+    ClipCue = 52,
 
     // Misc
     SendMidi = 29,
+    SendMidiMacro = 53,
+    SendProgramChange = 56,
+    SendMidiClock = 57,
     SendOsc = 30,
 
     // ReaLearn targets
     EnableInstances = 38,
     EnableMappings = 36,
+    EnableGroup = 60,
     LoadMappingSnapshot = 35,
+    SaveMappingSnapshot = 54,
     NavigateWithinGroup = 37,
+    SelectLayer = 61,
+    SetBankOffset = 62,
+    AdjustMappingMode = 58,
+    Multi = 73,
+    Panic = 74,
+    InvokeInstance = 75,
 }
 
 impl Display for ReaperTargetType {
@@ -570,11 +659,19 @@ impl ReaperTargetType {
             AnyOn => &ANY_ON_TARGET,
             Action => &ACTION_TARGET,
             Transport => &TRANSPORT_TARGET,
+            Punch => &PUNCH_TARGET,
+            UndoRedo => &UNDO_REDO_TARGET,
+            TimeSelection => &TIME_SELECTION_TARGET,
+            MonitoringVolume => &MONITORING_VOLUME_TARGET,
+            Metronome => &METRONOME_TARGET,
             SelectedTrack => &SELECTED_TRACK_TARGET,
             Seek => &SEEK_TARGET,
             Playrate => &PLAYRATE_TARGET,
             Tempo => &TEMPO_TARGET,
+            SetExtState => &SET_EXT_STATE_TARGET,
+            RunScript => &RUN_SCRIPT_TARGET,
             GoToBookmark => &GO_TO_BOOKMARK_TARGET,
+            NavigateBookmarks => &NAVIGATE_BOOKMARKS_TARGET,
             TrackArm => &TRACK_ARM_TARGET,
             AllTrackFxEnable => &ALL_TRACK_FX_ENABLE_TARGET,
             TrackTool => &TRACK_TOOL_TARGET,
@@ -585,16 +682,25 @@ impl ReaperTargetType {
             TrackAutomationMode => &TRACK_AUTOMATION_MODE_TARGET,
             AutomationTouchState => &AUTOMATION_TOUCH_STATE_TARGET,
             TrackPan => &TRACK_PAN_TARGET,
+            TrackDualPan => &TRACK_DUAL_PAN_TARGET,
             TrackWidth => &TRACK_WIDTH_TARGET,
             TrackVolume => &TRACK_VOLUME_TARGET,
             TrackShow => &TRACK_SHOW_TARGET,
             TrackSolo => &TRACK_SOLO_TARGET,
+            TrackInsert => &TRACK_INSERT_TARGET,
+            TrackDuplicate => &TRACK_DUPLICATE_TARGET,
+            TrackDelete => &TRACK_DELETE_TARGET,
+            TrackFolderSpill => &TRACK_FOLDER_SPILL_TARGET,
+            TrackRecordInput => &TRACK_RECORD_INPUT_TARGET,
+            TrackInputMonitoring => &TRACK_INPUT_MONITORING_TARGET,
+            TrackCue => &TRACK_CUE_TARGET,
             FxNavigate => &FX_NAVIGATE_TARGET,
             FxEnable => &FX_ENABLE_TARGET,
             LoadFxSnapshot => &LOAD_FX_SNAPSHOT_TARGET,
             FxPreset => &FX_PRESET_TARGET,
             FxOpen => &FX_OPEN_TARGET,
             FxParameter => &FX_PARAMETER_TARGET,
+            FxParameterBank => &FX_PARAMETER_BANK_TARGET,
             TrackSendAutomationMode => &ROUTE_AUTOMATION_MODE_TARGET,
             TrackSendMono => &ROUTE_MONO_TARGET,
             TrackSendMute => &ROUTE_MUTE_TARGET,
@@ -604,12 +710,26 @@ impl ReaperTargetType {
             ClipTransport => &CLIP_TRANSPORT_TARGET,
             ClipSeek => &CLIP_SEEK_TARGET,
             ClipVolume => &CLIP_VOLUME_TARGET,
+            ClipColumnStop => &CLIP_COLUMN_STOP_TARGET,
+            ClipSceneLaunch => &CLIP_SCENE_LAUNCH_TARGET,
+            ClipCue => &CLIP_CUE_TARGET,
             SendMidi => &MIDI_SEND_TARGET,
+            SendMidiMacro => &MIDI_MACRO_SEND_TARGET,
+            SendProgramChange => &PROGRAM_CHANGE_SEND_TARGET,
+            SendMidiClock => &MIDI_CLOCK_SEND_TARGET,
             SendOsc => &OSC_SEND_TARGET,
             EnableInstances => &ENABLE_INSTANCES_TARGET,
             EnableMappings => &ENABLE_MAPPINGS_TARGET,
+            EnableGroup => &ENABLE_GROUP_TARGET,
             LoadMappingSnapshot => &LOAD_MAPPING_SNAPSHOT_TARGET,
+            SaveMappingSnapshot => &SAVE_MAPPING_SNAPSHOT_TARGET,
             NavigateWithinGroup => &NAVIGATE_WITHIN_GROUP_TARGET,
+            SelectLayer => &SELECT_LAYER_TARGET,
+            SetBankOffset => &SET_BANK_OFFSET_TARGET,
+            AdjustMappingMode => &ADJUST_MAPPING_MODE_TARGET,
+            Multi => &MULTI_TARGET,
+            Panic => &PANIC_TARGET,
+            InvokeInstance => &INVOKE_INSTANCE_TARGET,
         }
     }
 
@@ -657,6 +777,10 @@ impl ReaperTargetType {
         self.definition().supports_exclusivity()
     }
 
+    pub fn supports_cue_output(self) -> bool {
+        self.definition().supports_cue_output()
+    }
+
     pub fn supports_control(&self) -> bool {
         self.definition().supports_control()
     }
@@ -692,6 +816,7 @@ pub struct TargetTypeDef {
     pub supports_send: bool,
     pub supports_track_exclusivity: bool,
     pub supports_exclusivity: bool,
+    pub supports_cue_output: bool,
     pub supports_poll_for_feedback: bool,
     pub supports_feedback_resolution: bool,
     pub supports_control: bool,
@@ -741,6 +866,9 @@ impl TargetTypeDef {
     pub const fn supports_exclusivity(&self) -> bool {
         self.supports_exclusivity
     }
+    pub const fn supports_cue_output(&self) -> bool {
+        self.supports_cue_output
+    }
     pub const fn supports_poll_for_feedback(&self) -> bool {
         self.supports_poll_for_feedback
     }
@@ -772,6 +900,7 @@ pub const DEFAULT_TARGET: TargetTypeDef = TargetTypeDef {
     supports_send: false,
     supports_track_exclusivity: false,
     supports_exclusivity: false,
+    supports_cue_output: false,
     supports_poll_for_feedback: false,
     supports_feedback_resolution: false,
 };
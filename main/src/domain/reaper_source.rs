@@ -1,14 +1,89 @@
+use crate::domain::{GameControllerElement, GameControllerEvent, MmcMessage, MmcSource};
 use derive_more::Display;
 use helgoboss_learn::{
     format_percentage_without_unit, parse_percentage_without_unit, ControlValue,
     DetailedSourceCharacter, SourceCharacter, UnitValue,
 };
 use std::convert::TryInto;
+use std::fmt;
+use std::hash::{Hash, Hasher};
 
-#[derive(Copy, Clone, PartialEq, Debug)]
+#[derive(Clone, PartialEq, Debug)]
 pub enum ReaperSource {
     MidiDeviceChanges,
     RealearnInstanceStart,
+    GameController(GameControllerSource),
+    InstanceMessage(InstanceMessageSource),
+    Mmc(MmcSource),
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct GameControllerSource {
+    /// If `None`, events from any controller are accepted.
+    pub controller_index: Option<u32>,
+    pub element: GameControllerElement,
+}
+
+impl GameControllerSource {
+    fn control(&self, evt: &GameControllerEvent) -> Option<ControlValue> {
+        if evt.element != self.element {
+            return None;
+        }
+        if let Some(i) = self.controller_index {
+            if evt.controller_index != i {
+                return None;
+            }
+        }
+        Some(ControlValue::AbsoluteContinuous(evt.value))
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct InstanceMessageSource {
+    /// Only messages with this name are accepted.
+    pub name: String,
+}
+
+impl InstanceMessageSource {
+    fn control(&self, msg: &InstanceMessage) -> Option<ControlValue> {
+        if msg.name != self.name {
+            return None;
+        }
+        Some(ControlValue::AbsoluteContinuous(msg.value))
+    }
+}
+
+/// A named value sent from one ReaLearn instance to other ones.
+#[derive(Clone, PartialEq, Debug)]
+pub struct InstanceMessage {
+    pub name: String,
+    pub value: UnitValue,
+    /// How many instances passed the message on before (0 if sent by an instance directly).
+    ///
+    /// Instances which invoke each other (A → B → A) would otherwise send messages back and forth
+    /// forever.
+    pub hop_count: u32,
+}
+
+// Unit values are never NaN, so comparing and hashing the bits is fine.
+impl Eq for InstanceMessage {}
+
+impl Hash for InstanceMessage {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+        self.value.get().to_bits().hash(state);
+    }
+}
+
+impl fmt::Display for InstanceMessage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Instance message \"{}\" with value {}%",
+            self.name,
+            format_percentage_without_unit(self.value.get())
+        )
+    }
 }
 
 impl ReaperSource {
@@ -17,6 +92,19 @@ impl ReaperSource {
         match self {
             MidiDeviceChanges => vec![DetailedSourceCharacter::MomentaryOnOffButton],
             RealearnInstanceStart => vec![DetailedSourceCharacter::MomentaryOnOffButton],
+            GameController(s) => {
+                if s.element.is_axis() {
+                    vec![DetailedSourceCharacter::RangeControl]
+                } else {
+                    vec![DetailedSourceCharacter::MomentaryOnOffButton]
+                }
+            }
+            // Explicitly qualified because the message struct has the same name.
+            ReaperSource::InstanceMessage(_) => vec![
+                DetailedSourceCharacter::RangeControl,
+                DetailedSourceCharacter::MomentaryOnOffButton,
+            ],
+            Mmc(s) => s.possible_detailed_characters(),
         }
     }
 
@@ -30,7 +118,12 @@ impl ReaperSource {
     }
 
     pub fn character(&self) -> SourceCharacter {
-        SourceCharacter::MomentaryButton
+        match self {
+            ReaperSource::GameController(s) if s.element.is_axis() => SourceCharacter::RangeElement,
+            ReaperSource::InstanceMessage(_) => SourceCharacter::RangeElement,
+            ReaperSource::Mmc(s) => s.character(),
+            _ => SourceCharacter::MomentaryButton,
+        }
     }
 
     pub fn control(&self, msg: &ReaperMessage) -> Option<ControlValue> {
@@ -50,6 +143,18 @@ impl ReaperSource {
                 }
                 _ => return None,
             },
+            GameController(evt) => match self {
+                ReaperSource::GameController(s) => return s.control(evt),
+                _ => return None,
+            },
+            ReaperMessage::InstanceMessage(msg) => match self {
+                ReaperSource::InstanceMessage(s) => return s.control(msg),
+                _ => return None,
+            },
+            Mmc(msg) => match self {
+                ReaperSource::Mmc(s) => return s.control(msg),
+                _ => return None,
+            },
         };
         Some(control_value)
     }
@@ -60,4 +165,11 @@ pub enum ReaperMessage {
     MidiDevicesConnected,
     MidiDevicesDisconnected,
     RealearnInstanceStarted,
+    #[display(fmt = "{}", _0)]
+    GameController(GameControllerEvent),
+    #[display(fmt = "{}", _0)]
+    InstanceMessage(InstanceMessage),
+    /// MIDI Machine Control command received via the MIDI control input of the instance.
+    #[display(fmt = "{}", _0)]
+    Mmc(MmcMessage),
 }
@@ -10,16 +10,20 @@ use reaper_high::{
     Reaper, Tempo, Track, TrackRoute, Width,
 };
 use reaper_medium::{
-    AutomationMode, Bpm, GlobalAutomationModeOverride, NormalizedPlayRate, PlaybackSpeedFactor,
-    PositionInSeconds, ReaperPanValue, ReaperWidthValue,
+    AutomationMode, Bpm, GlobalAutomationModeOverride, Guid, NormalizedPlayRate,
+    PlaybackSpeedFactor, PositionInSeconds, ReaperPanValue, ReaperWidthValue,
 };
 use rxrust::prelude::*;
 
 use crate::domain::{
-    AnyOnTarget, CompoundChangeEvent, EnableInstancesTarget, EnableMappingsTarget,
-    HitInstructionReturnValue, LoadMappingSnapshotTarget, NavigateWithinGroupTarget,
+    AdjustMappingModeTarget, AnyOnTarget, CompoundChangeEvent, EnableGroupTarget,
+    EnableInstancesTarget, EnableMappingsTarget, FxParameterBankTarget, HitInstructionReturnValue,
+    InvokeInstanceTarget, LoadMappingSnapshotTarget, MetronomeTarget, MonitoringVolumeTarget,
+    MultiTarget, NavigateWithinGroupTarget, PanicTarget, PunchTarget, RealTimeMultiTarget,
     RealearnTarget, ReaperTargetType, RouteAutomationModeTarget, RouteMonoTarget, RoutePhaseTarget,
-    TrackPhaseTarget, TrackToolTarget,
+    SaveMappingSnapshotTarget, SelectLayerTarget, SetBankOffsetTarget, TrackCueTarget,
+    TrackDeleteTarget, TrackDuplicateTarget, TrackFolderSpillTarget, TrackInputMonitoringTarget,
+    TrackInsertTarget, TrackPhaseTarget, TrackRecordInputTarget, TrackToolTarget,
 };
 use serde::{Deserialize, Serialize};
 use serde_repr::{Deserialize_repr, Serialize_repr};
@@ -28,14 +32,17 @@ use crate::base::Global;
 use crate::domain::ui_util::convert_bool_to_unit_value;
 use crate::domain::{
     handle_exclusivity, ActionTarget, AllTrackFxEnableTarget, AutomationModeOverrideTarget,
-    AutomationTouchStateTarget, ClipPlayState, ClipSeekTarget, ClipTransportTarget,
-    ClipVolumeTarget, ControlContext, FxEnableTarget, FxNavigateTarget, FxOpenTarget,
-    FxParameterTarget, FxPresetTarget, GoToBookmarkTarget, HierarchyEntry, HierarchyEntryProvider,
-    LoadFxSnapshotTarget, MappingControlContext, MidiSendTarget, OscSendTarget, PlayrateTarget,
-    RouteMuteTarget, RoutePanTarget, RouteVolumeTarget, SeekTarget, SelectedTrackTarget,
-    TempoTarget, TrackArmTarget, TrackAutomationModeTarget, TrackMuteTarget, TrackPanTarget,
+    AutomationTouchStateTarget, ClipColumnStopTarget, ClipCueTarget, ClipPlayState,
+    ClipSceneLaunchTarget, ClipSeekTarget, ClipTransportTarget, ClipVolumeTarget, ControlContext,
+    DualPanComponent, FxEnableTarget, FxNavigateTarget, FxOpenTarget, FxParameterTarget,
+    FxPresetTarget, GoToBookmarkTarget, HierarchyEntry, HierarchyEntryProvider,
+    LoadFxSnapshotTarget, MappingControlContext, MidiClockSendTarget, MidiMacroSendTarget,
+    MidiSendTarget, NavigateBookmarksTarget, OscSendTarget, PlayrateTarget,
+    ProgramChangeSendTarget, RouteMuteTarget, RoutePanTarget, RouteVolumeTarget, RunScriptTarget,
+    SeekTarget, SelectedTrackTarget, SetExtStateTarget, TempoTarget, TimeSelectionTarget,
+    TrackArmTarget, TrackAutomationModeTarget, TrackDualPanTarget, TrackMuteTarget, TrackPanTarget,
     TrackPeakTarget, TrackSelectionTarget, TrackShowTarget, TrackSoloTarget, TrackVolumeTarget,
-    TrackWidthTarget, TransportTarget,
+    TrackWidthTarget, TransportTarget, UndoRedoAction, UndoRedoTarget,
 };
 use enum_dispatch::enum_dispatch;
 use std::convert::TryInto;
@@ -80,11 +87,13 @@ impl TargetCharacter {
 pub enum ReaperTarget {
     Action(ActionTarget),
     FxParameter(FxParameterTarget),
+    FxParameterBank(FxParameterBankTarget),
     TrackVolume(TrackVolumeTarget),
     TrackTool(TrackToolTarget),
     TrackPeak(TrackPeakTarget),
     TrackRouteVolume(RouteVolumeTarget),
     TrackPan(TrackPanTarget),
+    TrackDualPan(TrackDualPanTarget),
     TrackWidth(TrackWidthTarget),
     TrackArm(TrackArmTarget),
     TrackSelection(TrackSelectionTarget),
@@ -92,6 +101,13 @@ pub enum ReaperTarget {
     TrackPhase(TrackPhaseTarget),
     TrackShow(TrackShowTarget),
     TrackSolo(TrackSoloTarget),
+    TrackInsert(TrackInsertTarget),
+    TrackDuplicate(TrackDuplicateTarget),
+    TrackDelete(TrackDeleteTarget),
+    TrackFolderSpill(TrackFolderSpillTarget),
+    TrackRecordInput(TrackRecordInputTarget),
+    TrackInputMonitoring(TrackInputMonitoringTarget),
+    TrackCue(TrackCueTarget),
     TrackAutomationMode(TrackAutomationModeTarget),
     TrackRoutePan(RoutePanTarget),
     TrackRouteMute(RouteMuteTarget),
@@ -108,20 +124,42 @@ pub enum ReaperTarget {
     FxNavigate(FxNavigateTarget),
     AllTrackFxEnable(AllTrackFxEnableTarget),
     Transport(TransportTarget),
+    Punch(PunchTarget),
+    UndoRedo(UndoRedoTarget),
+    TimeSelection(TimeSelectionTarget),
+    MonitoringVolume(MonitoringVolumeTarget),
+    Metronome(MetronomeTarget),
     AnyOn(AnyOnTarget),
     LoadFxSnapshot(LoadFxSnapshotTarget),
     AutomationTouchState(AutomationTouchStateTarget),
     GoToBookmark(GoToBookmarkTarget),
+    NavigateBookmarks(NavigateBookmarksTarget),
     Seek(SeekTarget),
+    SetExtState(SetExtStateTarget),
+    RunScript(RunScriptTarget),
     SendMidi(MidiSendTarget),
+    SendMidiMacro(MidiMacroSendTarget),
+    SendProgramChange(ProgramChangeSendTarget),
+    SendMidiClock(MidiClockSendTarget),
     SendOsc(OscSendTarget),
     ClipTransport(ClipTransportTarget),
     ClipSeek(ClipSeekTarget),
     ClipVolume(ClipVolumeTarget),
+    ClipColumnStop(ClipColumnStopTarget),
+    ClipSceneLaunch(ClipSceneLaunchTarget),
+    ClipCue(ClipCueTarget),
     LoadMappingSnapshot(LoadMappingSnapshotTarget),
+    SaveMappingSnapshot(SaveMappingSnapshotTarget),
     EnableMappings(EnableMappingsTarget),
+    EnableGroup(EnableGroupTarget),
     EnableInstances(EnableInstancesTarget),
     NavigateWithinGroup(NavigateWithinGroupTarget),
+    SelectLayer(SelectLayerTarget),
+    SetBankOffset(SetBankOffsetTarget),
+    AdjustMappingMode(AdjustMappingModeTarget),
+    Multi(MultiTarget),
+    Panic(PanicTarget),
+    InvokeInstance(InvokeInstanceTarget),
 }
 
 #[derive(
@@ -287,6 +325,26 @@ impl ReaperTarget {
         )
     }
 
+    /// Returns the GUIDs of the tracks and FXs which the given potential change event concerns.
+    ///
+    /// Returns `None` if the event can affect the resolution of any target (e.g. track removal or
+    /// project switch), in which case all targets need to be refreshed.
+    pub fn objects_affected_by_change_event(evt: &ChangeEvent) -> Option<Vec<Guid>> {
+        use ChangeEvent::*;
+        let track = match evt {
+            TrackSelectedChanged(e) => &e.track,
+            TrackNameChanged(e) => &e.track,
+            FxReordered(e) => &e.track,
+            ReceiveCountChanged(e) => &e.track,
+            TrackSendCountChanged(e) => &e.track,
+            HardwareOutputSendCountChanged(e) => &e.track,
+            FxAdded(e) => return Some(fx_and_track_guids(&e.fx)),
+            FxRemoved(e) => return Some(fx_and_track_guids(&e.fx)),
+            _ => return None,
+        };
+        Some(vec![*track.guid()])
+    }
+
     /// This contains all potential target-changing events which could also be fired by targets
     /// themselves. Be careful with those. Reentrancy very likely.
     ///
@@ -347,6 +405,7 @@ impl ReaperTarget {
                     track: e.track,
                     behavior: Default::default(),
                     exclusivity: Default::default(),
+                    previous_solos: vec![],
                 })
             }
             TrackSelectedChanged(e) if e.new_value => {
@@ -478,6 +537,7 @@ impl ReaperTarget {
                             track,
                             behavior: Default::default(),
                             exclusivity: Default::default(),
+                            previous_solos: vec![],
                         })
                         .into()
                     }),
@@ -536,12 +596,17 @@ impl<'a> Target<'a> for ReaperTarget {
         match self {
             SendOsc(t) => t.current_value(context),
             SendMidi(t) => t.current_value(()),
+            SendMidiMacro(t) => t.current_value(context),
+            SendProgramChange(t) => t.current_value(context),
+            SendMidiClock(t) => t.current_value(context),
             TrackPeak(t) => t.current_value(context),
             Action(t) => t.current_value(context),
             FxParameter(t) => t.current_value(context),
+            FxParameterBank(t) => t.current_value(context),
             TrackVolume(t) => t.current_value(context),
             TrackTool(t) => t.current_value(context),
             TrackPan(t) => t.current_value(context),
+            TrackDualPan(t) => t.current_value(context),
             TrackWidth(t) => t.current_value(context),
             TrackArm(t) => t.current_value(context),
             TrackRouteVolume(t) => t.current_value(context),
@@ -550,6 +615,13 @@ impl<'a> Target<'a> for ReaperTarget {
             TrackPhase(t) => t.current_value(context),
             TrackShow(t) => t.current_value(context),
             TrackSolo(t) => t.current_value(context),
+            TrackInsert(t) => t.current_value(context),
+            TrackDuplicate(t) => t.current_value(context),
+            TrackDelete(t) => t.current_value(context),
+            TrackFolderSpill(t) => t.current_value(context),
+            TrackRecordInput(t) => t.current_value(context),
+            TrackInputMonitoring(t) => t.current_value(context),
+            TrackCue(t) => t.current_value(context),
             TrackAutomationMode(t) => t.current_value(context),
             TrackRoutePan(t) => t.current_value(context),
             TrackRouteMute(t) => t.current_value(context),
@@ -570,17 +642,36 @@ impl<'a> Target<'a> for ReaperTarget {
             FxNavigate(t) => t.current_value(context),
             AllTrackFxEnable(t) => t.current_value(context),
             Transport(t) => t.current_value(context),
+            Punch(t) => t.current_value(context),
+            UndoRedo(t) => t.current_value(context),
+            TimeSelection(t) => t.current_value(context),
+            MonitoringVolume(t) => t.current_value(context),
+            Metronome(t) => t.current_value(context),
             AnyOn(t) => t.current_value(context),
             AutomationTouchState(t) => t.current_value(context),
             GoToBookmark(t) => t.current_value(context),
+            NavigateBookmarks(t) => t.current_value(context),
             Seek(t) => t.current_value(context),
+            SetExtState(t) => t.current_value(context),
+            RunScript(t) => t.current_value(context),
             ClipTransport(t) => t.current_value(context),
             ClipSeek(t) => t.current_value(context),
             ClipVolume(t) => t.current_value(context),
+            ClipColumnStop(t) => t.current_value(context),
+            ClipSceneLaunch(t) => t.current_value(context),
+            ClipCue(t) => t.current_value(context),
             LoadMappingSnapshot(t) => t.current_value(context),
+            SaveMappingSnapshot(t) => t.current_value(context),
             EnableMappings(t) => t.current_value(context),
+            EnableGroup(t) => t.current_value(context),
             EnableInstances(t) => t.current_value(context),
             NavigateWithinGroup(t) => t.current_value(context),
+            SelectLayer(t) => t.current_value(context),
+            SetBankOffset(t) => t.current_value(context),
+            AdjustMappingMode(t) => t.current_value(context),
+            Multi(t) => t.current_value(context),
+            Panic(t) => t.current_value(context),
+            InvokeInstance(t) => t.current_value(context),
         }
     }
 
@@ -595,6 +686,7 @@ impl<'a> Target<'a> for RealTimeReaperTarget {
         use RealTimeReaperTarget::*;
         match self {
             SendMidi(t) => t.current_value(()),
+            Multi(t) => t.current_value(()),
         }
     }
 
@@ -602,6 +694,7 @@ impl<'a> Target<'a> for RealTimeReaperTarget {
         use RealTimeReaperTarget::*;
         match self {
             SendMidi(t) => t.control_type(()),
+            Multi(t) => t.control_type(()),
         }
     }
 }
@@ -714,6 +807,10 @@ pub fn convert_unit_value_to_fx_index(fx_chain: &FxChain, value: UnitValue) -> O
     convert_unit_to_discrete_value_with_none(value, fx_chain.fx_count())
 }
 
+pub fn convert_unit_value_to_bookmark_index(bookmark_count: u32, value: UnitValue) -> Option<u32> {
+    convert_unit_to_discrete_value_with_none(value, bookmark_count)
+}
+
 fn convert_unit_to_discrete_value_with_none(value: UnitValue, count: u32) -> Option<u32> {
     // Example: <no preset> + 4 presets
     if value.is_zero() {
@@ -758,6 +855,10 @@ pub fn fx_preset_unit_value(fx: &Fx, index: Option<u32>) -> UnitValue {
     convert_discrete_to_unit_value_with_none(index, fx.preset_count().unwrap_or(0))
 }
 
+pub fn bookmark_unit_value(bookmark_count: u32, index: Option<u32>) -> UnitValue {
+    convert_discrete_to_unit_value_with_none(index, bookmark_count)
+}
+
 fn convert_discrete_to_unit_value_with_none(value: Option<u32>, count: u32) -> UnitValue {
     // Example: <no preset> + 4 presets
     match value {
@@ -921,6 +1022,16 @@ fn determine_target_for_action(action: Action) -> ReaperTarget {
             project,
             action: TransportAction::Repeat,
         }),
+        // Edit: Undo
+        40029 => ReaperTarget::UndoRedo(UndoRedoTarget {
+            project,
+            action: UndoRedoAction::Undo,
+        }),
+        // Edit: Redo
+        40030 => ReaperTarget::UndoRedo(UndoRedoTarget {
+            project,
+            action: UndoRedoAction::Redo,
+        }),
         _ => ReaperTarget::Action(ActionTarget {
             action,
             invocation_type: ActionInvocationType::Trigger,
@@ -963,6 +1074,15 @@ fn figure_out_touched_pan_component(
     old: reaper_medium::Pan,
     new: reaper_medium::Pan,
 ) -> ReaperTarget {
+    use reaper_medium::Pan::DualPan;
+    if let (DualPan { left: old_left, .. }, DualPan { left: new_left, .. }) = (old, new) {
+        let component = if old_left != new_left {
+            DualPanComponent::Left
+        } else {
+            DualPanComponent::Right
+        };
+        return ReaperTarget::TrackDualPan(TrackDualPanTarget { track, component });
+    }
     if old.width() != new.width() {
         ReaperTarget::TrackWidth(TrackWidthTarget { track })
     } else {
@@ -1054,6 +1174,13 @@ pub enum SoloBehavior {
     IgnoreRouting,
     #[display(fmt = "Use REAPER preference")]
     ReaperPreference,
+    /// Solos the track in place and unsolos all other tracks. Restores the previous solos when
+    /// switched off again.
+    #[display(fmt = "Solo exclusively (restore previous solos)")]
+    ExclusiveRestoring,
+    /// Doesn't solo the track but makes it keep playing when other tracks are soloed.
+    #[display(fmt = "Solo defeat")]
+    Defeat,
 }
 
 impl Default for SoloBehavior {
@@ -1311,6 +1438,7 @@ pub fn change_track_prop(
 #[derive(Clone, Debug, PartialEq)]
 pub enum RealTimeReaperTarget {
     SendMidi(MidiSendTarget),
+    Multi(RealTimeMultiTarget),
 }
 
 pub fn get_control_type_and_character_for_track_exclusivity(
@@ -1325,3 +1453,10 @@ pub fn get_control_type_and_character_for_track_exclusivity(
         )
     }
 }
+
+/// Monitoring FX don't have a track, so they are identified by their own GUID only.
+fn fx_and_track_guids(fx: &Fx) -> Vec<Guid> {
+    let mut guids: Vec<_> = fx.chain().track().map(|t| *t.guid()).into_iter().collect();
+    guids.extend(fx.guid());
+    guids
+}
@@ -0,0 +1,313 @@
+use derive_more::Display;
+use enum_iterator::IntoEnumIterator;
+use helgoboss_learn::{AbsoluteValue, ControlValue, UnitValue};
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+/// Point-based curve which shapes the response of absolute control values.
+///
+/// An alternative to control transformations for users who don't want to write formulas. Being
+/// plain data, it can also be rendered by user interfaces.
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct ResponseCurve {
+    /// Anchor points, sorted by their x coordinate. If empty, values pass unchanged.
+    #[serde(default)]
+    pub points: Vec<ResponseCurvePoint>,
+    #[serde(default)]
+    pub interpolation: CurveInterpolation,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ResponseCurvePoint {
+    /// Incoming control value.
+    pub x: UnitValue,
+    /// Resulting control value.
+    pub y: UnitValue,
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    Debug,
+    Serialize,
+    Deserialize,
+    IntoEnumIterator,
+    TryFromPrimitive,
+    IntoPrimitive,
+    Display,
+)]
+#[repr(usize)]
+pub enum CurveInterpolation {
+    #[serde(rename = "linear")]
+    #[display(fmt = "Linear")]
+    Linear,
+    /// Eases in and out of each point.
+    #[serde(rename = "smooth")]
+    #[display(fmt = "Smooth")]
+    Smooth,
+    /// Keeps the value of a point until the next point is reached.
+    #[serde(rename = "step")]
+    #[display(fmt = "Step")]
+    Step,
+}
+
+impl Default for CurveInterpolation {
+    fn default() -> Self {
+        CurveInterpolation::Linear
+    }
+}
+
+impl ResponseCurve {
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Returns a copy with the points sorted by their x coordinate.
+    pub fn normalized(&self) -> Self {
+        let mut points = self.points.clone();
+        points.sort_by(|a, b| {
+            a.x.get()
+                .partial_cmp(&b.x.get())
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        Self {
+            points,
+            interpolation: self.interpolation,
+        }
+    }
+
+    /// Applies the curve to absolute control values. Relative control values pass unchanged.
+    pub fn process(&self, value: ControlValue) -> ControlValue {
+        // The curve reshapes the value range, so discrete values don't stay discrete.
+        match value.to_unit_value() {
+            Ok(v) => ControlValue::AbsoluteContinuous(self.apply(v)),
+            Err(_) => value,
+        }
+    }
+
+    /// Expects the points to be sorted.
+    pub fn apply(&self, x: UnitValue) -> UnitValue {
+        let (first, last) = match (self.points.first(), self.points.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return x,
+        };
+        let x = x.get();
+        if x <= first.x.get() {
+            return first.y;
+        }
+        if x >= last.x.get() {
+            return last.y;
+        }
+        let right_index = self
+            .points
+            .iter()
+            .position(|p| p.x.get() > x)
+            .unwrap_or(self.points.len() - 1);
+        let left = self.points[right_index - 1];
+        let right = self.points[right_index];
+        let width = right.x.get() - left.x.get();
+        let position = if width > 0.0 {
+            (x - left.x.get()) / width
+        } else {
+            1.0
+        };
+        use CurveInterpolation::*;
+        let factor = match self.interpolation {
+            Linear => position,
+            Smooth => (1.0 - (position * PI).cos()) / 2.0,
+            Step => 0.0,
+        };
+        let y = left.y.get() + (right.y.get() - left.y.get()) * factor;
+        UnitValue::new_clamped(y)
+    }
+
+    /// Sends feedback back through the curve, so the source shows the position which leads to the
+    /// current target value.
+    pub fn process_feedback(&self, value: AbsoluteValue) -> AbsoluteValue {
+        AbsoluteValue::Continuous(self.apply_inverse(value.to_unit_value()))
+    }
+
+    /// Returns the incoming control value which results in the given value. Expects the points to
+    /// be sorted.
+    ///
+    /// If several control values result in the given value, the lowest one wins. If none does
+    /// (always the case for values between two steps), the control value of the point whose value
+    /// is nearest is returned.
+    pub fn apply_inverse(&self, y: UnitValue) -> UnitValue {
+        if self.points.is_empty() {
+            return y;
+        }
+        let y = y.get();
+        if self.interpolation != CurveInterpolation::Step {
+            for pair in self.points.windows(2) {
+                let (left, right) = (pair[0], pair[1]);
+                let (left_y, right_y) = (left.y.get(), right.y.get());
+                if y < left_y.min(right_y) || y > left_y.max(right_y) {
+                    continue;
+                }
+                if left_y == right_y {
+                    return left.x;
+                }
+                let factor = (y - left_y) / (right_y - left_y);
+                let position = match self.interpolation {
+                    CurveInterpolation::Smooth => (1.0 - 2.0 * factor).acos() / PI,
+                    _ => factor,
+                };
+                let x = left.x.get() + (right.x.get() - left.x.get()) * position;
+                return UnitValue::new_clamped(x);
+            }
+        }
+        let distance = |p: &&ResponseCurvePoint| (p.y.get() - y).abs();
+        self.points
+            .iter()
+            .min_by(|a, b| {
+                distance(a)
+                    .partial_cmp(&distance(b))
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+            .map(|p| p.x)
+            .unwrap_or(UnitValue::MIN)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_curve() {
+        // Given
+        let curve = ResponseCurve::default();
+        // When
+        let value = curve.apply(UnitValue::new(0.3));
+        // Then
+        assert_eq!(value, UnitValue::new(0.3));
+    }
+
+    #[test]
+    fn linear() {
+        // Given
+        let curve = curve(CurveInterpolation::Linear);
+        // When
+        let values = apply_all(&curve, &[0.0, 0.25, 0.75, 1.0]);
+        // Then
+        assert_eq!(values[0], 0.0);
+        assert_eq!(values[1], 0.4);
+        assert!((values[2] - 0.9).abs() < 0.00001);
+        assert_eq!(values[3], 1.0);
+    }
+
+    #[test]
+    fn step() {
+        // Given
+        let curve = curve(CurveInterpolation::Step);
+        // When
+        let values = apply_all(&curve, &[0.25, 0.75]);
+        // Then
+        assert_eq!(values, vec![0.0, 0.8]);
+    }
+
+    #[test]
+    fn smooth() {
+        // Given
+        let curve = curve(CurveInterpolation::Smooth);
+        // When
+        let value = curve.apply(UnitValue::new(0.25));
+        // Then
+        assert!((value.get() - 0.4).abs() < 0.00001);
+    }
+
+    #[test]
+    fn clamp_outside_of_points() {
+        // Given
+        let curve = ResponseCurve {
+            points: vec![point(0.2, 0.1), point(0.8, 0.9)],
+            interpolation: CurveInterpolation::Linear,
+        };
+        // When
+        let values = apply_all(&curve, &[0.0, 1.0]);
+        // Then
+        assert_eq!(values, vec![0.1, 0.9]);
+    }
+
+    #[test]
+    fn normalize() {
+        // Given
+        let curve = ResponseCurve {
+            points: vec![point(1.0, 1.0), point(0.0, 0.0)],
+            interpolation: CurveInterpolation::Linear,
+        };
+        // When
+        let curve = curve.normalized();
+        // Then
+        assert_eq!(curve.apply(UnitValue::new(0.5)), UnitValue::new(0.5));
+    }
+
+    #[test]
+    fn inverse_linear() {
+        // Given
+        let curve = curve(CurveInterpolation::Linear);
+        // When
+        let value = curve.apply_inverse(UnitValue::new(0.4));
+        // Then
+        assert!((value.get() - 0.25).abs() < 0.00001);
+    }
+
+    #[test]
+    fn inverse_smooth_round_trip() {
+        // Given
+        let curve = curve(CurveInterpolation::Smooth);
+        // When
+        let value = curve.apply_inverse(curve.apply(UnitValue::new(0.6)));
+        // Then
+        assert!((value.get() - 0.6).abs() < 0.00001);
+    }
+
+    #[test]
+    fn inverse_step() {
+        // Given
+        let curve = curve(CurveInterpolation::Step);
+        // When
+        let value = curve.apply_inverse(UnitValue::new(0.7));
+        // Then
+        assert_eq!(value, UnitValue::new(0.5));
+    }
+
+    #[test]
+    fn inverse_unreachable_value() {
+        // Given
+        let curve = ResponseCurve {
+            points: vec![point(0.2, 0.1), point(0.8, 0.9)],
+            interpolation: CurveInterpolation::Linear,
+        };
+        // When
+        let value = curve.apply_inverse(UnitValue::MAX);
+        // Then
+        assert_eq!(value, UnitValue::new(0.8));
+    }
+
+    /// Steep at the beginning, flat at the end.
+    fn curve(interpolation: CurveInterpolation) -> ResponseCurve {
+        ResponseCurve {
+            points: vec![point(0.0, 0.0), point(0.5, 0.8), point(1.0, 1.0)],
+            interpolation,
+        }
+    }
+
+    fn apply_all(curve: &ResponseCurve, values: &[f64]) -> Vec<f64> {
+        values
+            .iter()
+            .map(|v| curve.apply(UnitValue::new(*v)).get())
+            .collect()
+    }
+
+    fn point(x: f64, y: f64) -> ResponseCurvePoint {
+        ResponseCurvePoint {
+            x: UnitValue::new(x),
+            y: UnitValue::new(y),
+        }
+    }
+}
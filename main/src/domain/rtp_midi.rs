@@ -0,0 +1,815 @@
+use crossbeam_channel::{Receiver, Sender};
+use derive_more::Display;
+use helgoboss_learn::RawMidiEvent;
+use serde::{Deserialize, Serialize};
+
+use slog::{debug, trace, warn};
+
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::error::Error;
+use std::io;
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4, UdpSocket};
+
+use core::mem;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+const MAX_INCOMING_PACKET_SIZE: usize = 10_000;
+const RTP_MIDI_INCOMING_BULK_SIZE: usize = 32;
+const RTP_MIDI_OUTGOING_BULK_SIZE: usize = 64;
+/// Keeps outgoing packets well below the typical network MTU.
+const MAX_OUTGOING_MIDI_LIST_SIZE: usize = 1000;
+
+const APPLE_MIDI_SIGNATURE: [u8; 2] = [0xFF, 0xFF];
+const APPLE_MIDI_PROTOCOL_VERSION: u32 = 2;
+const RTP_VERSION: u8 = 0x80;
+const RTP_MIDI_PAYLOAD_TYPE: u8 = 0x61;
+const RTP_HEADER_SIZE: usize = 12;
+/// MIDI command section flag: Long header (12-bit length).
+const FLAG_B: u8 = 0x80;
+/// MIDI command section flag: First command is preceded by a delta time.
+const FLAG_Z: u8 = 0x20;
+
+/// A single MIDI message which has been received from or should be sent to an RTP-MIDI device.
+#[derive(Debug)]
+pub struct RtpMidiEvent {
+    dev_id: RtpMidiDeviceId,
+    event: RawMidiEvent,
+}
+
+impl RtpMidiEvent {
+    pub fn new(dev_id: RtpMidiDeviceId, event: RawMidiEvent) -> Self {
+        Self { dev_id, event }
+    }
+
+    pub fn dev_id(&self) -> RtpMidiDeviceId {
+        self.dev_id
+    }
+
+    pub fn event(&self) -> &RawMidiEvent {
+        &self.event
+    }
+}
+
+/// Names of the peers which are currently connected to each RTP-MIDI session.
+pub type RtpMidiPeerNames = HashMap<RtpMidiDeviceId, Vec<String>>;
+
+/// Runs all RTP-MIDI sessions in a separate thread.
+///
+/// Sessions need to answer clock synchronization requests in time, so they can't be driven by
+/// the main thread. Incoming MIDI messages are forwarded to the audio hook, which distributes
+/// them to the real-time processors just like messages from hardware MIDI input devices.
+#[derive(Debug)]
+pub struct RtpMidiProcessor {
+    state: State,
+    peer_names: Arc<Mutex<RtpMidiPeerNames>>,
+}
+
+#[derive(Debug)]
+enum State {
+    Stopped(StoppedState),
+    Starting,
+    Running(RunningState),
+    Stopping,
+}
+
+#[derive(Debug)]
+struct StoppedState {
+    incoming_sender: Sender<RtpMidiEvent>,
+    outgoing_receiver: Receiver<RtpMidiEvent>,
+}
+
+#[derive(Debug)]
+struct RunningState {
+    request_stop: Arc<AtomicBool>,
+    join_handle: JoinHandle<RtpMidiHandler>,
+}
+
+impl RtpMidiProcessor {
+    pub fn new(
+        incoming_sender: Sender<RtpMidiEvent>,
+        outgoing_receiver: Receiver<RtpMidiEvent>,
+    ) -> Self {
+        Self {
+            state: State::Stopped(StoppedState {
+                incoming_sender,
+                outgoing_receiver,
+            }),
+            peer_names: Default::default(),
+        }
+    }
+
+    pub fn start(&mut self, sessions: Vec<RtpMidiSession>) {
+        if sessions.is_empty() || !matches!(&self.state, State::Stopped(_)) {
+            return;
+        }
+        let state = if let State::Stopped(s) = mem::replace(&mut self.state, State::Starting) {
+            s
+        } else {
+            panic!("processor was not stopped");
+        };
+        let mut handler = RtpMidiHandler {
+            incoming_sender: state.incoming_sender,
+            outgoing_receiver: state.outgoing_receiver,
+            sessions,
+            peer_names: self.peer_names.clone(),
+        };
+        let request_stop = Arc::new(AtomicBool::new(false));
+        let request_stop_clone = request_stop.clone();
+        let join_handle = std::thread::Builder::new()
+            .name("ReaLearn RTP-MIDI".to_owned())
+            .spawn(move || {
+                while !request_stop_clone.load(Ordering::SeqCst) {
+                    handler.cycle();
+                }
+                handler.end_sessions();
+                handler
+            })
+            .unwrap();
+        self.state = State::Running(RunningState {
+            request_stop,
+            join_handle,
+        });
+    }
+
+    pub fn stop(&mut self) {
+        if !matches!(&self.state, State::Running(_)) {
+            return;
+        }
+        let state = if let State::Running(s) = mem::replace(&mut self.state, State::Stopping) {
+            s
+        } else {
+            panic!("processor was not started");
+        };
+        state.request_stop.store(true, Ordering::SeqCst);
+        let handler = state.join_handle.join().unwrap();
+        self.peer_names.lock().unwrap().clear();
+        self.state = State::Stopped(handler.into_stopped_state());
+    }
+
+    /// Returns the names of the peers currently connected to the given session.
+    pub fn peer_names(&self, dev_id: RtpMidiDeviceId) -> Vec<String> {
+        self.peer_names
+            .lock()
+            .unwrap()
+            .get(&dev_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+struct RtpMidiHandler {
+    incoming_sender: Sender<RtpMidiEvent>,
+    outgoing_receiver: Receiver<RtpMidiEvent>,
+    sessions: Vec<RtpMidiSession>,
+    peer_names: Arc<Mutex<RtpMidiPeerNames>>,
+}
+
+impl RtpMidiHandler {
+    pub fn cycle(&mut self) {
+        use itertools::Itertools;
+        for session in &mut self.sessions {
+            let incoming_sender = &self.incoming_sender;
+            let dev_id = session.id();
+            let peers_changed = session.poll(RTP_MIDI_INCOMING_BULK_SIZE, |event| {
+                // If the audio hook doesn't keep up, dropping is better than blocking the session.
+                let _ = incoming_sender.try_send(RtpMidiEvent::new(dev_id, event));
+            });
+            if peers_changed {
+                self.peer_names
+                    .lock()
+                    .unwrap()
+                    .insert(dev_id, session.peer_names());
+            }
+        }
+        let grouped_by_device = self
+            .outgoing_receiver
+            .try_iter()
+            .take(RTP_MIDI_OUTGOING_BULK_SIZE)
+            .group_by(|e| e.dev_id);
+        for (dev_id, group) in grouped_by_device.into_iter() {
+            if let Some(session) = self.sessions.iter_mut().find(|s| s.id() == dev_id) {
+                let events: Vec<_> = group.map(|e| e.event).collect();
+                let _ = session.send(&events);
+            }
+        }
+        std::thread::sleep(Duration::from_millis(1));
+    }
+
+    pub fn end_sessions(&mut self) {
+        for session in &mut self.sessions {
+            session.end();
+        }
+    }
+
+    fn into_stopped_state(self) -> StoppedState {
+        StoppedState {
+            incoming_sender: self.incoming_sender,
+            outgoing_receiver: self.outgoing_receiver,
+        }
+    }
+}
+
+/// An AppleMIDI session which waits for invitations from network MIDI peers.
+///
+/// Occupies two consecutive UDP ports: The given one for session control and the next one for
+/// MIDI data and clock synchronization. Invitations are always accepted, so whatever is connected
+/// to this session acts as one MIDI device. ReaLearn never initiates sessions itself.
+#[derive(Debug)]
+pub struct RtpMidiSession {
+    id: RtpMidiDeviceId,
+    name: String,
+    ssrc: u32,
+    control_socket: UdpSocket,
+    data_socket: UdpSocket,
+    peers: Vec<RtpMidiPeer>,
+    start_time: Instant,
+    sequence_number: u16,
+    logger: slog::Logger,
+    buffer: [u8; MAX_INCOMING_PACKET_SIZE],
+}
+
+#[derive(Debug)]
+struct RtpMidiPeer {
+    ssrc: u32,
+    name: String,
+    control_addr: SocketAddr,
+    data_addr: Option<SocketAddr>,
+}
+
+impl RtpMidiSession {
+    pub fn bind(
+        id: RtpMidiDeviceId,
+        name: String,
+        port: u16,
+        logger: slog::Logger,
+    ) -> Result<RtpMidiSession, Box<dyn Error>> {
+        let data_port = port.checked_add(1).ok_or("port too high")?;
+        let control_socket = UdpSocket::bind(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, port))?;
+        control_socket.set_nonblocking(true)?;
+        let data_socket = UdpSocket::bind(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, data_port))?;
+        data_socket.set_nonblocking(true)?;
+        let session = RtpMidiSession {
+            id,
+            name,
+            ssrc: Uuid::new_v4().as_u128() as u32,
+            control_socket,
+            data_socket,
+            peers: vec![],
+            start_time: Instant::now(),
+            sequence_number: 0,
+            logger,
+            buffer: [0; MAX_INCOMING_PACKET_SIZE],
+        };
+        Ok(session)
+    }
+
+    pub fn id(&self) -> RtpMidiDeviceId {
+        self.id
+    }
+
+    pub fn peer_names(&self) -> Vec<String> {
+        self.peers.iter().map(|p| p.name.clone()).collect()
+    }
+
+    /// Processes up to `n` packets per socket and passes received MIDI messages to the given
+    /// function.
+    ///
+    /// Returns whether peers have connected or disconnected.
+    pub fn poll(&mut self, n: usize, mut on_event: impl FnMut(RawMidiEvent)) -> bool {
+        let mut peers_changed = false;
+        for _ in 0..n {
+            match receive(&self.control_socket, &mut self.buffer, &self.logger) {
+                Some((num_bytes, addr)) => {
+                    if let Some(cmd) = decode_session_command(&self.buffer[..num_bytes]) {
+                        peers_changed |= self.process_control_command(cmd, addr);
+                    }
+                }
+                None => break,
+            }
+        }
+        for _ in 0..n {
+            match receive(&self.data_socket, &mut self.buffer, &self.logger) {
+                Some((num_bytes, addr)) => {
+                    let bytes = &self.buffer[..num_bytes];
+                    if bytes.starts_with(&APPLE_MIDI_SIGNATURE) {
+                        if let Some(cmd) = decode_session_command(bytes) {
+                            peers_changed |= self.process_data_command(cmd, addr);
+                        }
+                        continue;
+                    }
+                    match decode_rtp_midi_packet(bytes) {
+                        Ok(events) => events.into_iter().for_each(&mut on_event),
+                        Err(e) => {
+                            warn!(self.logger, "Error trying to decode RTP-MIDI packet: {}", e)
+                        }
+                    }
+                }
+                None => break,
+            }
+        }
+        peers_changed
+    }
+
+    /// Sends the given MIDI messages to all connected peers.
+    pub fn send(&mut self, events: &[RawMidiEvent]) -> Result<(), &'static str> {
+        if self.peers.is_empty() {
+            return Ok(());
+        }
+        let mut start = 0;
+        while start < events.len() {
+            let mut end = start + 1;
+            let mut size = events[start].bytes().len();
+            while end < events.len()
+                && size + events[end].bytes().len() < MAX_OUTGOING_MIDI_LIST_SIZE
+            {
+                size += events[end].bytes().len() + 1;
+                end += 1;
+            }
+            let packet = encode_rtp_midi_packet(
+                self.ssrc,
+                self.sequence_number,
+                self.timestamp() as u32,
+                &events[start..end],
+            )?;
+            self.sequence_number = self.sequence_number.wrapping_add(1);
+            for addr in self.peers.iter().flat_map(|p| p.data_addr) {
+                trace!(
+                    self.logger,
+                    "Sending RTP-MIDI packet with {} bytes to {}",
+                    packet.len(),
+                    addr
+                );
+                self.data_socket
+                    .send_to(&packet, addr)
+                    .map_err(|_| "error trying to send RTP-MIDI packet")?;
+            }
+            start = end;
+        }
+        Ok(())
+    }
+
+    /// Says goodbye to all connected peers.
+    pub fn end(&mut self) {
+        let cmd = SessionCommand::End(self.own_exchange(0));
+        let bytes = encode_session_command(&cmd);
+        for peer in self.peers.drain(..) {
+            let _ = self.control_socket.send_to(&bytes, peer.control_addr);
+        }
+    }
+
+    fn process_control_command(&mut self, cmd: SessionCommand, addr: SocketAddr) -> bool {
+        match cmd {
+            SessionCommand::Invitation(ex) => {
+                debug!(
+                    self.logger,
+                    "Accepting RTP-MIDI invitation from {} ({})", ex.name, addr
+                );
+                self.reply(
+                    &self.control_socket,
+                    &SessionCommand::Accepted(self.own_exchange(ex.token)),
+                    addr,
+                );
+                self.peers.retain(|p| p.ssrc != ex.ssrc);
+                self.peers.push(RtpMidiPeer {
+                    ssrc: ex.ssrc,
+                    name: ex.name,
+                    control_addr: addr,
+                    data_addr: None,
+                });
+                true
+            }
+            SessionCommand::End(ex) => self.remove_peer(ex.ssrc),
+            _ => false,
+        }
+    }
+
+    fn process_data_command(&mut self, cmd: SessionCommand, addr: SocketAddr) -> bool {
+        match cmd {
+            SessionCommand::Invitation(ex) => {
+                let peer = match self.peers.iter_mut().find(|p| p.ssrc == ex.ssrc) {
+                    None => {
+                        // Data port invitation without preceding control port invitation.
+                        self.reply(
+                            &self.data_socket,
+                            &SessionCommand::Rejected(self.own_exchange(ex.token)),
+                            addr,
+                        );
+                        return false;
+                    }
+                    Some(p) => p,
+                };
+                peer.data_addr = Some(addr);
+                self.reply(
+                    &self.data_socket,
+                    &SessionCommand::Accepted(self.own_exchange(ex.token)),
+                    addr,
+                );
+                false
+            }
+            SessionCommand::Sync(sync) if sync.count == 0 => {
+                let mut timestamps = sync.timestamps;
+                timestamps[1] = self.timestamp();
+                let answer = ClockSync {
+                    ssrc: self.ssrc,
+                    count: 1,
+                    timestamps,
+                };
+                self.reply(&self.data_socket, &SessionCommand::Sync(answer), addr);
+                false
+            }
+            SessionCommand::End(ex) => self.remove_peer(ex.ssrc),
+            _ => false,
+        }
+    }
+
+    fn remove_peer(&mut self, ssrc: u32) -> bool {
+        let count_before = self.peers.len();
+        self.peers.retain(|p| p.ssrc != ssrc);
+        self.peers.len() != count_before
+    }
+
+    fn reply(&self, socket: &UdpSocket, cmd: &SessionCommand, addr: SocketAddr) {
+        if let Err(e) = socket.send_to(&encode_session_command(cmd), addr) {
+            warn!(self.logger, "Error trying to answer RTP-MIDI peer: {}", e);
+        }
+    }
+
+    fn own_exchange(&self, token: u32) -> Exchange {
+        Exchange {
+            token,
+            ssrc: self.ssrc,
+            name: self.name.clone(),
+        }
+    }
+
+    /// Session time in units of 100 microseconds, as required by the clock synchronization.
+    fn timestamp(&self) -> u64 {
+        (self.start_time.elapsed().as_micros() / 100) as u64
+    }
+}
+
+fn receive(
+    socket: &UdpSocket,
+    buffer: &mut [u8],
+    logger: &slog::Logger,
+) -> Option<(usize, SocketAddr)> {
+    match socket.recv_from(buffer) {
+        Ok(res) => Some(res),
+        Err(ref err) if err.kind() != io::ErrorKind::WouldBlock => {
+            warn!(logger, "Error trying to receive RTP-MIDI packet: {}", err);
+            None
+        }
+        // We don't need to handle "would block" because we are running in a loop anyway.
+        _ => None,
+    }
+}
+
+/// AppleMIDI session protocol command.
+#[derive(Clone, Eq, PartialEq, Debug)]
+enum SessionCommand {
+    /// "IN"
+    Invitation(Exchange),
+    /// "OK"
+    Accepted(Exchange),
+    /// "NO"
+    Rejected(Exchange),
+    /// "BY"
+    End(Exchange),
+    /// "CK"
+    Sync(ClockSync),
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+struct Exchange {
+    token: u32,
+    ssrc: u32,
+    name: String,
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+struct ClockSync {
+    ssrc: u32,
+    count: u8,
+    timestamps: [u64; 3],
+}
+
+fn decode_session_command(bytes: &[u8]) -> Option<SessionCommand> {
+    if !bytes.starts_with(&APPLE_MIDI_SIGNATURE) {
+        return None;
+    }
+    let cmd = match bytes.get(2..4)? {
+        b"CK" => {
+            let mut timestamps = [0; 3];
+            for (i, t) in timestamps.iter_mut().enumerate() {
+                *t = read_u64(bytes, 12 + i * 8)?;
+            }
+            let sync = ClockSync {
+                ssrc: read_u32(bytes, 4)?,
+                count: *bytes.get(8)?,
+                timestamps,
+            };
+            return Some(SessionCommand::Sync(sync));
+        }
+        b"IN" => SessionCommand::Invitation,
+        b"OK" => SessionCommand::Accepted,
+        b"NO" => SessionCommand::Rejected,
+        b"BY" => SessionCommand::End,
+        _ => return None,
+    };
+    let name = bytes.get(16..).unwrap_or_default();
+    let name = name.split(|b| *b == 0).next().unwrap_or_default();
+    let exchange = Exchange {
+        token: read_u32(bytes, 8)?,
+        ssrc: read_u32(bytes, 12)?,
+        name: String::from_utf8_lossy(name).into_owned(),
+    };
+    Some(cmd(exchange))
+}
+
+fn encode_session_command(cmd: &SessionCommand) -> Vec<u8> {
+    let mut bytes = APPLE_MIDI_SIGNATURE.to_vec();
+    use SessionCommand::*;
+    let (name, ex) = match cmd {
+        Sync(sync) => {
+            bytes.extend_from_slice(b"CK");
+            bytes.extend_from_slice(&sync.ssrc.to_be_bytes());
+            bytes.extend_from_slice(&[sync.count, 0, 0, 0]);
+            for t in &sync.timestamps {
+                bytes.extend_from_slice(&t.to_be_bytes());
+            }
+            return bytes;
+        }
+        Invitation(ex) => (b"IN", ex),
+        Accepted(ex) => (b"OK", ex),
+        Rejected(ex) => (b"NO", ex),
+        End(ex) => (b"BY", ex),
+    };
+    bytes.extend_from_slice(name);
+    bytes.extend_from_slice(&APPLE_MIDI_PROTOCOL_VERSION.to_be_bytes());
+    bytes.extend_from_slice(&ex.token.to_be_bytes());
+    bytes.extend_from_slice(&ex.ssrc.to_be_bytes());
+    if !matches!(cmd, End(_)) {
+        bytes.extend_from_slice(ex.name.as_bytes());
+        bytes.push(0);
+    }
+    bytes
+}
+
+/// Extracts the MIDI messages from the MIDI command section of an RTP-MIDI packet.
+///
+/// The recovery journal is ignored.
+fn decode_rtp_midi_packet(bytes: &[u8]) -> Result<Vec<RawMidiEvent>, &'static str> {
+    if bytes.len() <= RTP_HEADER_SIZE {
+        return Err("packet too short");
+    }
+    if bytes[0] & 0xC0 != RTP_VERSION || bytes[1] & 0x7F != RTP_MIDI_PAYLOAD_TYPE {
+        return Err("not an RTP-MIDI packet");
+    }
+    let section = &bytes[RTP_HEADER_SIZE..];
+    let flags = section[0];
+    let (len, list_start) = if flags & FLAG_B == 0 {
+        ((flags & 0x0F) as usize, 1)
+    } else {
+        let low = *section
+            .get(1)
+            .ok_or("MIDI command section header truncated")?;
+        ((((flags & 0x0F) as usize) << 8) | low as usize, 2)
+    };
+    let list = section
+        .get(list_start..list_start + len)
+        .ok_or("MIDI command section truncated")?;
+    decode_midi_list(list, flags & FLAG_Z != 0)
+}
+
+fn decode_midi_list(
+    list: &[u8],
+    first_has_delta_time: bool,
+) -> Result<Vec<RawMidiEvent>, &'static str> {
+    let mut events = vec![];
+    let mut running_status = None;
+    let mut i = 0;
+    while i < list.len() {
+        if i > 0 || first_has_delta_time {
+            i = skip_delta_time(list, i)?;
+        }
+        let first_byte = *list.get(i).ok_or("missing MIDI command after delta time")?;
+        let status = if first_byte & 0x80 == 0 {
+            running_status.ok_or("running status without preceding status byte")?
+        } else {
+            i += 1;
+            first_byte
+        };
+        if status == 0xF0 {
+            let len = list[i..]
+                .iter()
+                .position(|b| *b == 0xF7)
+                .ok_or("segmented sys-ex messages are not supported")?;
+            let sysex = &list[i - 1..=i + len];
+            events.push(RawMidiEvent::try_from_slice(0, sysex).map_err(|_| "sys-ex too long")?);
+            i += len + 1;
+            running_status = None;
+            continue;
+        }
+        let data_len = midi_data_length(status);
+        let data = list.get(i..i + data_len).ok_or("MIDI command truncated")?;
+        i += data_len;
+        let mut msg = [status, 0, 0];
+        msg[1..=data_len].copy_from_slice(data);
+        events.push(
+            RawMidiEvent::try_from_slice(0, &msg[..=data_len])
+                .map_err(|_| "invalid MIDI command")?,
+        );
+        if status < 0xF0 {
+            running_status = Some(status);
+        } else if status < 0xF8 {
+            // System common messages cancel running status, real-time messages don't.
+            running_status = None;
+        }
+    }
+    Ok(events)
+}
+
+fn skip_delta_time(list: &[u8], mut i: usize) -> Result<usize, &'static str> {
+    for _ in 0..4 {
+        let b = *list.get(i).ok_or("delta time truncated")?;
+        i += 1;
+        if b & 0x80 == 0 {
+            return Ok(i);
+        }
+    }
+    Err("invalid delta time")
+}
+
+fn midi_data_length(status: u8) -> usize {
+    match status {
+        0x80..=0xBF | 0xE0..=0xEF | 0xF2 => 2,
+        0xC0..=0xDF | 0xF1 | 0xF3 => 1,
+        _ => 0,
+    }
+}
+
+fn encode_rtp_midi_packet(
+    ssrc: u32,
+    sequence_number: u16,
+    timestamp: u32,
+    events: &[RawMidiEvent],
+) -> Result<Vec<u8>, &'static str> {
+    let mut list = vec![];
+    for (i, e) in events.iter().enumerate() {
+        if i > 0 {
+            // Delta time zero. The first command doesn't have one because we don't set Z.
+            list.push(0);
+        }
+        list.extend_from_slice(e.bytes());
+    }
+    if list.len() > 0x0FFF {
+        return Err("too many MIDI messages for one RTP-MIDI packet");
+    }
+    let mut packet = Vec::with_capacity(RTP_HEADER_SIZE + 2 + list.len());
+    packet.push(RTP_VERSION);
+    packet.push(RTP_MIDI_PAYLOAD_TYPE);
+    packet.extend_from_slice(&sequence_number.to_be_bytes());
+    packet.extend_from_slice(&timestamp.to_be_bytes());
+    packet.extend_from_slice(&ssrc.to_be_bytes());
+    if list.len() <= 0x0F {
+        packet.push(list.len() as u8);
+    } else {
+        packet.push(FLAG_B | (list.len() >> 8) as u8);
+        packet.push(list.len() as u8);
+    }
+    packet.extend_from_slice(&list);
+    Ok(packet)
+}
+
+fn read_u32(bytes: &[u8], index: usize) -> Option<u32> {
+    let slice = bytes.get(index..index + 4)?;
+    Some(u32::from_be_bytes(slice.try_into().ok()?))
+}
+
+fn read_u64(bytes: &[u8], index: usize) -> Option<u64> {
+    let slice = bytes.get(index..index + 8)?;
+    Some(u64::from_be_bytes(slice.try_into().ok()?))
+}
+
+/// An RTP-MIDI device ID.
+///
+/// This uniquely identifies an RTP-MIDI session according to ReaLearn's device configuration.
+#[derive(
+    Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Display, Serialize, Deserialize,
+)]
+#[serde(transparent)]
+pub struct RtpMidiDeviceId(uuid::Uuid);
+
+impl RtpMidiDeviceId {
+    pub fn random() -> RtpMidiDeviceId {
+        RtpMidiDeviceId(Uuid::new_v4())
+    }
+
+    pub fn fmt_short(&self) -> String {
+        self.0.to_string().chars().take(5).collect()
+    }
+}
+
+impl FromStr for RtpMidiDeviceId {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(RtpMidiDeviceId(
+            s.parse().map_err(|_| "invalid RTP-MIDI device ID")?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_invitation() {
+        // Given
+        let mut bytes = vec![0xFF, 0xFF, b'I', b'N', 0, 0, 0, 2];
+        bytes.extend_from_slice(&[0, 0, 0, 7, 0x12, 0x34, 0x56, 0x78]);
+        bytes.extend_from_slice(b"iPad\0");
+        // When
+        let cmd = decode_session_command(&bytes);
+        // Then
+        assert_eq!(
+            cmd,
+            Some(SessionCommand::Invitation(Exchange {
+                token: 7,
+                ssrc: 0x12345678,
+                name: "iPad".to_owned()
+            }))
+        );
+    }
+
+    #[test]
+    fn session_command_roundtrip() {
+        // Given
+        let commands = vec![
+            SessionCommand::Accepted(Exchange {
+                token: 1,
+                ssrc: 2,
+                name: "ReaLearn".to_owned(),
+            }),
+            SessionCommand::Sync(ClockSync {
+                ssrc: 3,
+                count: 1,
+                timestamps: [4, 5, 0],
+            }),
+        ];
+        for cmd in commands {
+            // When
+            let bytes = encode_session_command(&cmd);
+            // Then
+            assert_eq!(decode_session_command(&bytes), Some(cmd));
+        }
+    }
+
+    #[test]
+    fn decode_running_status_and_sysex() {
+        // Given
+        let mut packet = vec![0x80, 0x61, 0, 1, 0, 0, 0, 0, 0, 0, 0, 1];
+        // Z flag set, so the first command has a delta time, too.
+        let list = [
+            0x00, 0x90, 60, 100, 0x05, 62, 0, 0x00, 0xF0, 0x7E, 0x01, 0xF7,
+        ];
+        packet.push(FLAG_Z | list.len() as u8);
+        packet.extend_from_slice(&list);
+        // When
+        let events = decode_rtp_midi_packet(&packet).unwrap();
+        // Then
+        assert_eq!(
+            bytes(&events),
+            vec![
+                vec![0x90, 60, 100],
+                vec![0x90, 62, 0],
+                vec![0xF0, 0x7E, 0x01, 0xF7]
+            ]
+        );
+    }
+
+    #[test]
+    fn encode_decode_roundtrip() {
+        // Given
+        let events: Vec<_> = (0..10u8)
+            .map(|i| RawMidiEvent::try_from_slice(0, &[0xB0, i, 127]).unwrap())
+            .collect();
+        // When
+        let packet = encode_rtp_midi_packet(1, 2, 3, &events).unwrap();
+        let decoded = decode_rtp_midi_packet(&packet).unwrap();
+        // Then
+        assert_eq!(packet[RTP_HEADER_SIZE] & FLAG_B, FLAG_B);
+        assert_eq!(bytes(&decoded), bytes(&events));
+    }
+
+    fn bytes(events: &[RawMidiEvent]) -> Vec<Vec<u8>> {
+        events.iter().map(|e| e.bytes().to_vec()).collect()
+    }
+}
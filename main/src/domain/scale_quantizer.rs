@@ -0,0 +1,184 @@
+use derive_more::Display;
+use enum_iterator::IntoEnumIterator;
+use helgoboss_learn::{AbsoluteValue, Fraction};
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+use serde::{Deserialize, Serialize};
+
+/// Musical scale to which generated notes can be snapped.
+#[derive(
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    Debug,
+    Serialize,
+    Deserialize,
+    IntoEnumIterator,
+    TryFromPrimitive,
+    IntoPrimitive,
+    Display,
+)]
+#[repr(usize)]
+pub enum MusicalScale {
+    #[serde(rename = "major")]
+    #[display(fmt = "Major")]
+    Major,
+    #[serde(rename = "natural-minor")]
+    #[display(fmt = "Natural minor")]
+    NaturalMinor,
+    #[serde(rename = "harmonic-minor")]
+    #[display(fmt = "Harmonic minor")]
+    HarmonicMinor,
+    #[serde(rename = "dorian")]
+    #[display(fmt = "Dorian")]
+    Dorian,
+    #[serde(rename = "mixolydian")]
+    #[display(fmt = "Mixolydian")]
+    Mixolydian,
+    #[serde(rename = "major-pentatonic")]
+    #[display(fmt = "Major pentatonic")]
+    MajorPentatonic,
+    #[serde(rename = "minor-pentatonic")]
+    #[display(fmt = "Minor pentatonic")]
+    MinorPentatonic,
+    #[serde(rename = "blues")]
+    #[display(fmt = "Blues")]
+    Blues,
+}
+
+impl Default for MusicalScale {
+    fn default() -> Self {
+        Self::Major
+    }
+}
+
+impl MusicalScale {
+    /// Semitone offsets from the root note.
+    fn intervals(&self) -> &'static [u32] {
+        use MusicalScale::*;
+        match self {
+            Major => &[0, 2, 4, 5, 7, 9, 11],
+            NaturalMinor => &[0, 2, 3, 5, 7, 8, 10],
+            HarmonicMinor => &[0, 2, 3, 5, 7, 8, 11],
+            Dorian => &[0, 2, 3, 5, 7, 9, 10],
+            Mixolydian => &[0, 2, 4, 5, 7, 9, 10],
+            MajorPentatonic => &[0, 2, 4, 7, 9],
+            MinorPentatonic => &[0, 3, 5, 7, 10],
+            Blues => &[0, 3, 5, 6, 7, 10],
+        }
+    }
+
+    fn contains_pitch_class(&self, pitch_class: u32) -> bool {
+        self.intervals().contains(&pitch_class)
+    }
+}
+
+/// Snaps note numbers to the nearest note of a scale in a certain key.
+///
+/// Used by the "Send MIDI" target before the value is substituted into the MIDI pattern. The
+/// discrete value of the pattern is interpreted as note number, so this only makes sense for
+/// patterns whose variable bits hold the note number (e.g. `90 [0bbb bbbb] 7F`).
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct ScaleQuantizer {
+    pub scale: MusicalScale,
+    /// Pitch class of the root note (0 = C, 1 = C#, ..., 11 = B).
+    pub root: u8,
+}
+
+impl ScaleQuantizer {
+    pub fn quantize(&self, value: AbsoluteValue, max_discrete_value: u32) -> AbsoluteValue {
+        if max_discrete_value == 0 {
+            return value;
+        }
+        let note = match value {
+            AbsoluteValue::Continuous(v) => (v.get() * max_discrete_value as f64).round() as u32,
+            AbsoluteValue::Discrete(f) => f.actual(),
+        };
+        let quantized_note = self.quantize_note(note.min(max_discrete_value), max_discrete_value);
+        AbsoluteValue::Discrete(Fraction::new(quantized_note, max_discrete_value))
+    }
+
+    /// Returns the nearest note within the scale. If there are two candidates with the same
+    /// distance, the lower one wins.
+    pub fn quantize_note(&self, note: u32, max_note: u32) -> u32 {
+        // A scale can't have gaps larger than an octave, so this is enough.
+        for distance in 0..12 {
+            if let Some(lower) = note.checked_sub(distance) {
+                if self.contains_note(lower) {
+                    return lower;
+                }
+            }
+            let upper = note + distance;
+            if upper <= max_note && self.contains_note(upper) {
+                return upper;
+            }
+        }
+        note
+    }
+
+    fn contains_note(&self, note: u32) -> bool {
+        let root = (self.root % 12) as u32;
+        let pitch_class = (note + 12 - root) % 12;
+        self.scale.contains_pitch_class(pitch_class)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use helgoboss_learn::UnitValue;
+
+    #[test]
+    fn c_major() {
+        // Given
+        let q = ScaleQuantizer {
+            scale: MusicalScale::Major,
+            root: 0,
+        };
+        // Then
+        assert_eq!(q.quantize_note(60, 127), 60);
+        assert_eq!(q.quantize_note(61, 127), 60);
+        assert_eq!(q.quantize_note(63, 127), 62);
+        assert_eq!(q.quantize_note(66, 127), 65);
+        assert_eq!(q.quantize_note(71, 127), 71);
+    }
+
+    #[test]
+    fn transposed_pentatonic() {
+        // Given
+        let q = ScaleQuantizer {
+            scale: MusicalScale::MinorPentatonic,
+            root: 9,
+        };
+        // Then
+        assert_eq!(q.quantize_note(57, 127), 57);
+        assert_eq!(q.quantize_note(58, 127), 57);
+        assert_eq!(q.quantize_note(59, 127), 60);
+        assert_eq!(q.quantize_note(61, 127), 60);
+    }
+
+    #[test]
+    fn respects_range() {
+        // Given
+        let q = ScaleQuantizer {
+            scale: MusicalScale::Major,
+            root: 1,
+        };
+        // Then
+        assert_eq!(q.quantize_note(0, 127), 1);
+        assert_eq!(q.quantize_note(127, 127), 126);
+    }
+
+    #[test]
+    fn continuous_value() {
+        // Given
+        let q = ScaleQuantizer {
+            scale: MusicalScale::Major,
+            root: 0,
+        };
+        // When
+        let result = q.quantize(AbsoluteValue::Continuous(UnitValue::new(61.0 / 127.0)), 127);
+        // Then
+        assert_eq!(result, AbsoluteValue::Discrete(Fraction::new(60, 127)));
+    }
+}
@@ -0,0 +1,157 @@
+use helgoboss_learn::{Interval, UnitValue};
+
+/// Settings for the value-scaling takeover with catch range.
+///
+/// Meant for non-motorized controllers whose knobs and faders are shared across layers or
+/// instances and therefore often don't match the current target value.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct ScalingTakeoverSettings {
+    /// If the control value is at most this far away from the target value, the target value
+    /// follows the control value directly.
+    pub catch_range: UnitValue,
+}
+
+/// Keeps track of the previous control value, which is needed for scaling.
+///
+/// As long as control and target value are further apart than the catch range, the target value
+/// moves in the same direction as the control value but scaled so that both arrive at the
+/// minimum or maximum at the same time. That way they converge without any parameter jump.
+#[derive(Clone, Debug, Default)]
+pub struct ScalingTakeover {
+    last_control_value: Option<UnitValue>,
+}
+
+impl ScalingTakeover {
+    /// Returns the new target value (normalized to the target interval).
+    ///
+    /// Returns `None` if the target should not be hit, e.g. because the control value didn't
+    /// move or because it's the first control value and it's not within the catch range.
+    pub fn process(
+        &mut self,
+        settings: &ScalingTakeoverSettings,
+        control_value: UnitValue,
+        current_target_value: Option<UnitValue>,
+    ) -> Option<UnitValue> {
+        let previous = self.last_control_value.replace(control_value);
+        let target = match current_target_value {
+            // Nothing we could take over from.
+            None => return Some(control_value),
+            Some(v) => v.get(),
+        };
+        let control = control_value.get();
+        if (control - target).abs() <= settings.catch_range.get() {
+            return Some(control_value);
+        }
+        let previous = previous?.get();
+        let delta = control - previous;
+        let new_target = if delta > 0.0 {
+            if previous >= 1.0 {
+                return None;
+            }
+            target + delta * (1.0 - target) / (1.0 - previous)
+        } else if delta < 0.0 {
+            if previous <= 0.0 {
+                return None;
+            }
+            target + delta * target / previous
+        } else {
+            return None;
+        };
+        Some(UnitValue::new_clamped(new_target))
+    }
+}
+
+/// Maps the given value from the given interval to the unit interval.
+pub fn normalize_to_interval(value: UnitValue, interval: &Interval<UnitValue>) -> UnitValue {
+    let min = interval.min_val().get();
+    let span = interval.max_val().get() - min;
+    if span <= 0.0 {
+        return UnitValue::MIN;
+    }
+    UnitValue::new_clamped((value.get() - min) / span)
+}
+
+/// Maps the given value from the unit interval to the given interval.
+pub fn denormalize_from_interval(value: UnitValue, interval: &Interval<UnitValue>) -> UnitValue {
+    let min = interval.min_val().get();
+    let span = interval.max_val().get() - min;
+    UnitValue::new_clamped(min + value.get() * span)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn follow_within_catch_range() {
+        // Given
+        let mut takeover = ScalingTakeover::default();
+        // When
+        let value = takeover.process(&settings(0.1), unit(0.45), Some(unit(0.5)));
+        // Then
+        assert_eq!(value, Some(unit(0.45)));
+    }
+
+    #[test]
+    fn wait_for_movement_outside_of_catch_range() {
+        // Given
+        let mut takeover = ScalingTakeover::default();
+        // When
+        let value = takeover.process(&settings(0.1), unit(0.2), Some(unit(0.6)));
+        // Then
+        assert_eq!(value, None);
+    }
+
+    #[test]
+    fn scale_upwards() {
+        // Given
+        let mut takeover = ScalingTakeover::default();
+        // When
+        takeover.process(&settings(0.05), unit(0.0), Some(unit(0.5)));
+        let value = takeover.process(&settings(0.05), unit(0.2), Some(unit(0.5)));
+        // Then
+        assert_approx(value, 0.6);
+    }
+
+    #[test]
+    fn scale_downwards() {
+        // Given
+        let mut takeover = ScalingTakeover::default();
+        // When
+        takeover.process(&settings(0.01), unit(0.8), Some(unit(0.6)));
+        let value = takeover.process(&settings(0.01), unit(0.4), Some(unit(0.6)));
+        // Then
+        assert_approx(value, 0.3);
+    }
+
+    #[test]
+    fn converge_at_max() {
+        // Given
+        let mut takeover = ScalingTakeover::default();
+        // When
+        takeover.process(&settings(0.01), unit(0.2), Some(unit(0.6)));
+        let value = takeover.process(&settings(0.01), unit(1.0), Some(unit(0.6)));
+        // Then
+        assert_approx(value, 1.0);
+    }
+
+    fn assert_approx(actual: Option<UnitValue>, expected: f64) {
+        let actual = actual.expect("target should be hit").get();
+        assert!(
+            (actual - expected).abs() < 0.00001,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    fn settings(catch_range: f64) -> ScalingTakeoverSettings {
+        ScalingTakeoverSettings {
+            catch_range: unit(catch_range),
+        }
+    }
+
+    fn unit(v: f64) -> UnitValue {
+        UnitValue::new(v)
+    }
+}
@@ -0,0 +1,228 @@
+use crate::domain::SourceFeedbackValue;
+use helgoboss_learn::{MidiSourceValue, RawMidiEvent};
+use std::str::FromStr;
+
+/// Checksum algorithms used in the SysEx implementations of common devices.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum SysExChecksumAlgorithm {
+    /// 128 minus the 7-bit sum of the covered bytes (Roland, also many Yamaha devices).
+    Roland,
+    /// XOR of the covered bytes, masked to 7 bits.
+    Xor,
+}
+
+impl FromStr for SysExChecksumAlgorithm {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use SysExChecksumAlgorithm::*;
+        let algorithm = match s.trim() {
+            "roland" => Roland,
+            "xor" => Xor,
+            _ => return Err("unknown checksum algorithm"),
+        };
+        Ok(algorithm)
+    }
+}
+
+impl SysExChecksumAlgorithm {
+    fn compute(self, covered_bytes: &[u8]) -> u8 {
+        use SysExChecksumAlgorithm::*;
+        match self {
+            Roland => {
+                let sum = covered_bytes
+                    .iter()
+                    .fold(0u8, |acc, b| acc.wrapping_add(*b))
+                    & 0x7f;
+                (0x80 - sum) & 0x7f
+            }
+            Xor => covered_bytes.iter().fold(0u8, |acc, b| acc ^ *b) & 0x7f,
+        }
+    }
+}
+
+/// A checksum which is computed over a range of bytes of a raw MIDI message and written to a
+/// particular byte of the same message.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct SysExChecksum {
+    algorithm: SysExChecksumAlgorithm,
+    /// Index of the first covered byte.
+    start: usize,
+    /// Index after the last covered byte.
+    end: usize,
+    /// Index of the checksum byte.
+    position: usize,
+}
+
+impl SysExChecksum {
+    /// Writes the checksum into the given message. Leaves messages alone which are too short.
+    pub fn apply(&self, bytes: &mut [u8]) {
+        if self.end > bytes.len() || self.position >= bytes.len() {
+            return;
+        }
+        bytes[self.position] = self.algorithm.compute(&bytes[self.start..self.end]);
+    }
+
+    /// Writes the checksum into all raw MIDI events of the given feedback value.
+    pub fn apply_to_feedback_value(&self, value: &mut SourceFeedbackValue) {
+        let events = match value {
+            SourceFeedbackValue::Midi(MidiSourceValue::Raw { events, .. }) => events,
+            _ => return,
+        };
+        for e in events.iter_mut() {
+            let mut bytes = e.bytes().to_vec();
+            self.apply(&mut bytes);
+            if let Ok(new_event) = RawMidiEvent::try_from_slice(0, &bytes) {
+                *e = new_event;
+            }
+        }
+    }
+}
+
+/// Splits a raw MIDI pattern into the part understood by the raw MIDI source and an optional
+/// checksum.
+///
+/// The bytes covered by the checksum are enclosed in parentheses, the checksum byte itself is
+/// written as algorithm name in curly braces, e.g. `F0 41 10 42 12 (40 00 04 [0gfe dcba])
+/// {roland} F7`. The checksum byte is replaced with `00` in the returned pattern.
+pub fn extract_sysex_checksum(
+    pattern: &str,
+) -> Result<(String, Option<SysExChecksum>), &'static str> {
+    if !pattern.contains(|c| c == '(' || c == ')' || c == '{' || c == '}') {
+        return Ok((pattern.to_owned(), None));
+    }
+    let mut result = String::with_capacity(pattern.len());
+    let mut hex_digit_count = 0;
+    let mut binary_byte_count = 0;
+    let mut start = None;
+    let mut end = None;
+    let mut checksum = None;
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        let byte_index = hex_digit_count / 2 + binary_byte_count;
+        let at_byte_boundary = hex_digit_count % 2 == 0;
+        match c {
+            '[' => {
+                result.push(c);
+                for c in chars.by_ref() {
+                    result.push(c);
+                    if c == ']' {
+                        break;
+                    }
+                }
+                binary_byte_count += 1;
+            }
+            '(' => {
+                if !at_byte_boundary || start.is_some() {
+                    return Err("misplaced opening parenthesis");
+                }
+                start = Some(byte_index);
+                result.push(' ');
+            }
+            ')' => {
+                if !at_byte_boundary || start.is_none() || end.is_some() {
+                    return Err("misplaced closing parenthesis");
+                }
+                end = Some(byte_index);
+                result.push(' ');
+            }
+            '{' => {
+                if !at_byte_boundary || checksum.is_some() {
+                    return Err("misplaced checksum");
+                }
+                let name: String = chars.by_ref().take_while(|c| *c != '}').collect();
+                let algorithm: SysExChecksumAlgorithm = name.parse()?;
+                let (start, end) = match (start, end) {
+                    (Some(s), Some(e)) if e <= byte_index => (s, e),
+                    _ => return Err("checksum must follow the parenthesized bytes it covers"),
+                };
+                checksum = Some(SysExChecksum {
+                    algorithm,
+                    start,
+                    end,
+                    position: byte_index,
+                });
+                result.push_str(" 00 ");
+                binary_byte_count += 1;
+            }
+            '}' => return Err("misplaced closing curly brace"),
+            c if c.is_ascii_hexdigit() => {
+                hex_digit_count += 1;
+                result.push(c);
+            }
+            _ => result.push(c),
+        }
+    }
+    if checksum.is_none() {
+        return Err("parenthesized bytes without checksum");
+    }
+    Ok((result, checksum))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_checksum() {
+        // Given
+        let pattern = "F0 00 20 6B 7F 42 02 00 10 77 [0000 dcba] F7";
+        // When
+        let (pattern_without_checksum, checksum) = extract_sysex_checksum(pattern).unwrap();
+        // Then
+        assert_eq!(pattern_without_checksum, pattern);
+        assert_eq!(checksum, None);
+    }
+
+    #[test]
+    fn roland_checksum() {
+        // Given
+        let pattern = "F0 41 10 42 12 (40 00 7F [0gfe dcba]) {roland} F7";
+        // When
+        let (pattern_without_checksum, checksum) = extract_sysex_checksum(pattern).unwrap();
+        let checksum = checksum.unwrap();
+        // Then
+        assert_eq!(
+            checksum,
+            SysExChecksum {
+                algorithm: SysExChecksumAlgorithm::Roland,
+                start: 5,
+                end: 9,
+                position: 9
+            }
+        );
+        assert_eq!(
+            pattern_without_checksum
+                .split_whitespace()
+                .collect::<Vec<_>>(),
+            vec!["F0", "41", "10", "42", "12", "40", "00", "7F", "[0gfe", "dcba]", "00", "F7"]
+        );
+        // GS reset
+        let mut bytes = [
+            0xF0, 0x41, 0x10, 0x42, 0x12, 0x40, 0x00, 0x7F, 0x00, 0x00, 0xF7,
+        ];
+        checksum.apply(&mut bytes);
+        assert_eq!(bytes[9], 0x41);
+    }
+
+    #[test]
+    fn xor_checksum_without_spaces() {
+        // Given
+        let pattern = "F0(010204){xor}F7";
+        // When
+        let (_, checksum) = extract_sysex_checksum(pattern).unwrap();
+        let checksum = checksum.unwrap();
+        // Then
+        let mut bytes = [0xF0, 0x01, 0x02, 0x04, 0x00, 0xF7];
+        checksum.apply(&mut bytes);
+        assert_eq!(bytes, [0xF0, 0x01, 0x02, 0x04, 0x07, 0xF7]);
+    }
+
+    #[test]
+    fn invalid_patterns() {
+        assert!(extract_sysex_checksum("F0 (41 10) F7").is_err());
+        assert!(extract_sysex_checksum("F0 41 10 {roland} F7").is_err());
+        assert!(extract_sysex_checksum("F0 (41 10) {foo} F7").is_err());
+        assert!(extract_sysex_checksum("F0 (4 1) {roland} F7").is_err());
+    }
+}
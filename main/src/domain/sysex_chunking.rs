@@ -0,0 +1,269 @@
+use helgoboss_learn::RawMidiEvent;
+use helgoboss_midi::{RawShortMessage, ShortMessage};
+use reaper_medium::MidiOutputDeviceId;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Maximum number of chunks which can wait for being sent at the same time.
+///
+/// Memory for them is reserved up-front because the scheduler is used in the audio hook.
+const PENDING_CAPACITY: usize = 1000;
+
+/// Device-specific settings for sending large sys-ex messages.
+///
+/// Some class-compliant devices silently drop sys-ex messages which exceed a certain size (typical
+/// for display messages). Splitting them into smaller chunks and waiting a bit between the chunks
+/// fixes this.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub struct SysexChunkingSettings {
+    /// Maximum number of bytes sent at once. 0 means unlimited.
+    pub max_size: usize,
+    /// Pause after each chunk.
+    pub chunk_delay: Duration,
+}
+
+pub type SysexChunkingSettingsMap = HashMap<MidiOutputDeviceId, SysexChunkingSettings>;
+
+/// Sends MIDI messages to output devices and takes care of splitting sys-ex messages into chunks.
+///
+/// Chunks which can't be sent immediately are kept and sent in a later audio hook cycle. All
+/// messages for a device which has pending chunks are delayed as well, so they can't end up in
+/// the middle of a sys-ex message. Doesn't allocate after creation.
+#[derive(Debug)]
+pub struct MidiOutputScheduler {
+    settings: SysexChunkingSettingsMap,
+    pending: Vec<PendingMidiEvent>,
+}
+
+#[derive(Debug)]
+struct PendingMidiEvent {
+    dev_id: MidiOutputDeviceId,
+    due: Instant,
+    event: RawMidiEvent,
+}
+
+impl Default for MidiOutputScheduler {
+    fn default() -> Self {
+        Self {
+            settings: Default::default(),
+            pending: Vec::with_capacity(PENDING_CAPACITY),
+        }
+    }
+}
+
+impl MidiOutputScheduler {
+    /// Returns the previous settings (so they can be disposed outside of the audio thread).
+    pub fn replace_settings(
+        &mut self,
+        settings: SysexChunkingSettingsMap,
+    ) -> SysexChunkingSettingsMap {
+        std::mem::replace(&mut self.settings, settings)
+    }
+
+    pub fn has_pending(&self, dev_id: MidiOutputDeviceId) -> bool {
+        self.pending.iter().any(|e| e.dev_id == dev_id)
+    }
+
+    /// Sends the given event right now via `send_now` or schedules it for later (if it needs to be
+    /// split into chunks or the device is still busy with a previous message).
+    ///
+    /// If a chunk can't be created, the remaining chunks are dropped and an error is returned.
+    pub fn send(
+        &mut self,
+        dev_id: MidiOutputDeviceId,
+        event: &RawMidiEvent,
+        now: Instant,
+        mut send_now: impl FnMut(&RawMidiEvent),
+    ) -> Result<(), &'static str> {
+        let settings = self.settings.get(&dev_id).copied().unwrap_or_default();
+        let bytes = event.bytes();
+        let is_sysex = bytes.first() == Some(&0xF0);
+        let last_due = self
+            .pending
+            .iter()
+            .rev()
+            .find(|e| e.dev_id == dev_id)
+            .map(|e| e.due);
+        if !is_sysex || settings.max_size == 0 || bytes.len() <= settings.max_size {
+            match last_due {
+                None => send_now(event),
+                Some(due) => self.schedule(dev_id, due + settings.chunk_delay, *event, send_now),
+            }
+            return Ok(());
+        }
+        let mut queueing = last_due.is_some();
+        let mut next_due = last_due.map(|d| d + settings.chunk_delay);
+        for chunk in bytes.chunks(settings.max_size) {
+            let chunk_event = RawMidiEvent::try_from_slice(0, chunk)
+                .map_err(|_| "couldn't split sys-ex message into chunks")?;
+            let due = next_due.unwrap_or(now);
+            if !queueing && due <= now {
+                send_now(&chunk_event);
+            } else {
+                queueing = true;
+                self.schedule(dev_id, due, chunk_event, &mut send_now);
+            }
+            next_due = Some(due + settings.chunk_delay);
+        }
+        Ok(())
+    }
+
+    /// Sends all scheduled events which are due.
+    pub fn send_due(
+        &mut self,
+        now: Instant,
+        mut send_now: impl FnMut(MidiOutputDeviceId, &RawMidiEvent),
+    ) {
+        // Due times of events for the same device never decrease, so this keeps the order.
+        self.pending.retain(|e| {
+            if e.due <= now {
+                send_now(e.dev_id, &e.event);
+                false
+            } else {
+                true
+            }
+        });
+    }
+
+    fn schedule(
+        &mut self,
+        dev_id: MidiOutputDeviceId,
+        due: Instant,
+        event: RawMidiEvent,
+        mut send_now: impl FnMut(&RawMidiEvent),
+    ) {
+        if self.pending.len() == self.pending.capacity() {
+            // Better send it too early than not at all (and we must not allocate).
+            send_now(&event);
+            return;
+        }
+        self.pending.push(PendingMidiEvent { dev_id, due, event });
+    }
+}
+
+/// Converts a short message into a raw event with the correct number of bytes.
+pub fn short_message_to_raw_midi_event(msg: RawShortMessage) -> Option<RawMidiEvent> {
+    let (status, data_1, data_2) = msg.to_bytes();
+    let bytes = [status, data_1.get(), data_2.get()];
+    let len = match status {
+        0xC0..=0xDF | 0xF1 | 0xF3 => 2,
+        0xF6..=0xFF => 1,
+        _ => 3,
+    };
+    RawMidiEvent::try_from_slice(0, &bytes[..len]).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_settings() {
+        // Given
+        let mut scheduler = MidiOutputScheduler::default();
+        let dev_id = MidiOutputDeviceId::new(0);
+        let now = Instant::now();
+        let mut sent = vec![];
+        // When
+        scheduler
+            .send(dev_id, &sysex(10), now, |e| sent.push(e.bytes().to_vec()))
+            .unwrap();
+        // Then
+        assert_eq!(sent, vec![sysex(10).bytes().to_vec()]);
+        assert!(!scheduler.has_pending(dev_id));
+    }
+
+    #[test]
+    fn chunks_with_delay() {
+        // Given
+        let mut scheduler = chunking_scheduler(4, 10);
+        let dev_id = MidiOutputDeviceId::new(0);
+        let now = Instant::now();
+        let mut sent = vec![];
+        // When
+        scheduler
+            .send(dev_id, &sysex(10), now, |e| sent.push(e.bytes().to_vec()))
+            .unwrap();
+        // Then
+        assert_eq!(sent, vec![vec![0xF0, 1, 2, 3]]);
+        assert!(scheduler.has_pending(dev_id));
+        scheduler.send_due(now + ms(5), |_, e| sent.push(e.bytes().to_vec()));
+        assert_eq!(sent.len(), 1);
+        scheduler.send_due(now + ms(10), |_, e| sent.push(e.bytes().to_vec()));
+        assert_eq!(sent[1], vec![4, 5, 6, 7]);
+        scheduler.send_due(now + ms(20), |_, e| sent.push(e.bytes().to_vec()));
+        assert_eq!(sent[2], vec![8, 0xF7]);
+        assert!(!scheduler.has_pending(dev_id));
+    }
+
+    #[test]
+    fn chunks_without_delay() {
+        // Given
+        let mut scheduler = chunking_scheduler(4, 0);
+        let dev_id = MidiOutputDeviceId::new(0);
+        let mut sent = vec![];
+        // When
+        scheduler
+            .send(dev_id, &sysex(10), Instant::now(), |e| {
+                sent.push(e.bytes().to_vec())
+            })
+            .unwrap();
+        // Then
+        assert_eq!(sent.len(), 3);
+        assert!(!scheduler.has_pending(dev_id));
+    }
+
+    #[test]
+    fn keep_order_while_busy() {
+        // Given
+        let mut scheduler = chunking_scheduler(4, 10);
+        let dev_id = MidiOutputDeviceId::new(0);
+        let other_dev_id = MidiOutputDeviceId::new(1);
+        let now = Instant::now();
+        let note_on = RawMidiEvent::try_from_slice(0, &[0x90, 60, 127]).unwrap();
+        let mut sent = vec![];
+        scheduler
+            .send(dev_id, &sysex(6), now, |e| sent.push(e.bytes().to_vec()))
+            .unwrap();
+        // When
+        scheduler
+            .send(dev_id, &note_on, now, |e| sent.push(e.bytes().to_vec()))
+            .unwrap();
+        scheduler
+            .send(other_dev_id, &note_on, now, |e| {
+                sent.push(e.bytes().to_vec())
+            })
+            .unwrap();
+        // Then
+        assert_eq!(sent, vec![vec![0xF0, 1, 2, 3], vec![0x90, 60, 127]]);
+        let mut later_sent = vec![];
+        scheduler.send_due(now + ms(10), |_, e| later_sent.push(e.bytes().to_vec()));
+        assert_eq!(later_sent, vec![vec![4, 0xF7]]);
+        scheduler.send_due(now + ms(20), |_, e| later_sent.push(e.bytes().to_vec()));
+        assert_eq!(later_sent[1], vec![0x90, 60, 127]);
+    }
+
+    fn chunking_scheduler(max_size: usize, delay_millis: u64) -> MidiOutputScheduler {
+        let mut scheduler = MidiOutputScheduler::default();
+        let settings = SysexChunkingSettings {
+            max_size,
+            chunk_delay: ms(delay_millis),
+        };
+        scheduler
+            .replace_settings(std::iter::once((MidiOutputDeviceId::new(0), settings)).collect());
+        scheduler
+    }
+
+    /// Creates a sys-ex message with the given total length.
+    fn sysex(len: u8) -> RawMidiEvent {
+        let bytes: Vec<u8> = std::iter::once(0xF0)
+            .chain(1..len - 1)
+            .chain(std::iter::once(0xF7))
+            .collect();
+        RawMidiEvent::try_from_slice(0, &bytes).unwrap()
+    }
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+}
@@ -0,0 +1,135 @@
+use crate::domain::MappingId;
+use reaper_high::Guid;
+use std::collections::{HashMap, HashSet};
+
+/// Maps GUIDs of tracks and FXs to the mappings whose targets are bound to them.
+///
+/// Resolved targets can change in many places, so the index is just invalidated there and
+/// rebuilt lazily as soon as it's needed.
+#[derive(Debug, Default)]
+pub struct TargetDependencyIndex {
+    is_up_to_date: bool,
+    mappings_by_object: HashMap<Guid, HashSet<MappingId>>,
+    /// Mappings which need to be refreshed on changes of any track or FX (e.g. `<Selected>`).
+    unbound_mappings: HashSet<MappingId>,
+}
+
+impl TargetDependencyIndex {
+    pub fn is_up_to_date(&self) -> bool {
+        self.is_up_to_date
+    }
+
+    pub fn invalidate(&mut self) {
+        self.is_up_to_date = false;
+    }
+
+    /// Rebuilds the index from the target dependencies of each mapping.
+    ///
+    /// `None` as dependencies means that the mapping depends on any track or FX.
+    pub fn rebuild(&mut self, dependencies: impl Iterator<Item = (MappingId, Option<Vec<Guid>>)>) {
+        self.mappings_by_object.clear();
+        self.unbound_mappings.clear();
+        for (mapping_id, guids) in dependencies {
+            match guids {
+                None => {
+                    self.unbound_mappings.insert(mapping_id);
+                }
+                Some(guids) => {
+                    for guid in guids {
+                        self.mappings_by_object
+                            .entry(guid)
+                            .or_default()
+                            .insert(mapping_id);
+                    }
+                }
+            }
+        }
+        self.is_up_to_date = true;
+    }
+
+    pub fn affected_mappings(&self, objects: &HashSet<Guid>) -> HashSet<MappingId> {
+        let mut affected = self.unbound_mappings.clone();
+        for guid in objects {
+            if let Some(ids) = self.mappings_by_object.get(guid) {
+                affected.extend(ids);
+            }
+        }
+        affected
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reaper_low::raw::GUID;
+
+    #[test]
+    fn bound_mapping_affected_only_by_its_objects() {
+        // Given
+        let mapping_id = MappingId::random();
+        let mut index = TargetDependencyIndex::default();
+        index.rebuild(std::iter::once((mapping_id, Some(vec![guid(1), guid(2)]))));
+        // When
+        let affected_by_own = index.affected_mappings(&set(&[guid(2)]));
+        let affected_by_foreign = index.affected_mappings(&set(&[guid(3)]));
+        // Then
+        assert_eq!(affected_by_own, std::iter::once(mapping_id).collect());
+        assert!(affected_by_foreign.is_empty());
+    }
+
+    #[test]
+    fn unbound_mapping_affected_by_any_object() {
+        // Given
+        let unbound_id = MappingId::random();
+        let bound_id = MappingId::random();
+        let mut index = TargetDependencyIndex::default();
+        index.rebuild(vec![(unbound_id, None), (bound_id, Some(vec![guid(1)]))].into_iter());
+        // When
+        let affected = index.affected_mappings(&set(&[guid(3)]));
+        // Then
+        assert_eq!(affected, std::iter::once(unbound_id).collect());
+    }
+
+    #[test]
+    fn mapping_without_dependencies_never_affected() {
+        // Given
+        let mapping_id = MappingId::random();
+        let mut index = TargetDependencyIndex::default();
+        index.rebuild(std::iter::once((mapping_id, Some(vec![]))));
+        // When
+        let affected = index.affected_mappings(&set(&[guid(1), guid(2)]));
+        // Then
+        assert!(affected.is_empty());
+    }
+
+    #[test]
+    fn rebuild_replaces_previous_dependencies() {
+        // Given
+        let mapping_id = MappingId::random();
+        let mut index = TargetDependencyIndex::default();
+        index.rebuild(std::iter::once((mapping_id, None)));
+        index.invalidate();
+        // When
+        index.rebuild(std::iter::once((mapping_id, Some(vec![guid(1)]))));
+        // Then
+        assert!(index.is_up_to_date());
+        assert!(index.affected_mappings(&set(&[guid(2)])).is_empty());
+        assert_eq!(
+            index.affected_mappings(&set(&[guid(1)])),
+            std::iter::once(mapping_id).collect()
+        );
+    }
+
+    fn guid(n: u32) -> Guid {
+        Guid::new(GUID {
+            Data1: n,
+            Data2: 0,
+            Data3: 0,
+            Data4: [0; 8],
+        })
+    }
+
+    fn set(guids: &[Guid]) -> HashSet<Guid> {
+        guids.iter().copied().collect()
+    }
+}
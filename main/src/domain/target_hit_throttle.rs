@@ -0,0 +1,132 @@
+use crate::domain::coalesce_control_values;
+use helgoboss_learn::ControlValue;
+use std::time::{Duration, Instant};
+
+/// Makes sure that the target of a mapping is not hit more often than allowed.
+///
+/// In contrast to the control rate limiter, this works on the output of the mode, so it also
+/// catches target hits caused by group interaction or timers. Target values which arrive too
+/// early are not dropped but kept: The latest one is guaranteed to be applied as soon as the
+/// interval has elapsed.
+#[derive(Debug, Default)]
+pub struct TargetHitThrottle {
+    last_hit_at: Option<Instant>,
+    pending: Option<ControlValue>,
+}
+
+impl TargetHitThrottle {
+    /// Returns the value with which the target should be hit right now. Otherwise keeps it for
+    /// later.
+    pub fn process(
+        &mut self,
+        value: ControlValue,
+        min_interval: Duration,
+        now: Instant,
+    ) -> Option<ControlValue> {
+        let value = match self.pending.take() {
+            None => Some(value),
+            Some(p) => coalesce_control_values(p, value),
+        };
+        if !self.interval_elapsed(min_interval, now) {
+            self.pending = value;
+            return None;
+        }
+        let value = value?;
+        self.last_hit_at = Some(now);
+        Some(value)
+    }
+
+    /// Returns the kept value if the interval has elapsed in the meantime.
+    pub fn take_due(&mut self, min_interval: Duration, now: Instant) -> Option<ControlValue> {
+        if !self.interval_elapsed(min_interval, now) {
+            return None;
+        }
+        self.pending.take()
+    }
+
+    pub fn has_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    fn interval_elapsed(&self, min_interval: Duration, now: Instant) -> bool {
+        match self.last_hit_at {
+            None => true,
+            Some(t) => now.saturating_duration_since(t) >= min_interval,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use helgoboss_learn::{DiscreteIncrement, UnitValue};
+
+    const INTERVAL: Duration = Duration::from_millis(100);
+
+    fn relative(increment: i32) -> ControlValue {
+        ControlValue::Relative(DiscreteIncrement::new(increment))
+    }
+
+    fn absolute(value: f64) -> ControlValue {
+        ControlValue::AbsoluteContinuous(UnitValue::new(value))
+    }
+
+    #[test]
+    fn hit_first_value_immediately() {
+        // Given
+        let mut throttle = TargetHitThrottle::default();
+        let start = Instant::now();
+        // When
+        let result = throttle.process(absolute(0.5), INTERVAL, start);
+        // Then
+        assert_eq!(result, Some(absolute(0.5)));
+        assert!(!throttle.has_pending());
+    }
+
+    #[test]
+    fn apply_latest_value_after_interval() {
+        // Given
+        let mut throttle = TargetHitThrottle::default();
+        let start = Instant::now();
+        throttle.process(absolute(0.1), INTERVAL, start);
+        // When
+        let second = throttle.process(absolute(0.2), INTERVAL, start + Duration::from_millis(10));
+        let third = throttle.process(absolute(0.3), INTERVAL, start + Duration::from_millis(20));
+        let too_early = throttle.take_due(INTERVAL, start + Duration::from_millis(50));
+        let due = throttle.take_due(INTERVAL, start + INTERVAL);
+        // Then
+        assert!(second.is_none());
+        assert!(third.is_none());
+        assert!(too_early.is_none());
+        assert_eq!(due, Some(absolute(0.3)));
+        assert!(!throttle.has_pending());
+    }
+
+    #[test]
+    fn sum_relative_increments() {
+        // Given
+        let mut throttle = TargetHitThrottle::default();
+        let start = Instant::now();
+        throttle.process(relative(1), INTERVAL, start);
+        // When
+        throttle.process(relative(2), INTERVAL, start + Duration::from_millis(10));
+        throttle.process(relative(3), INTERVAL, start + Duration::from_millis(20));
+        let due = throttle.take_due(INTERVAL, start + INTERVAL);
+        // Then
+        assert_eq!(due, Some(relative(5)));
+    }
+
+    #[test]
+    fn merge_kept_value_into_next_hit() {
+        // Given
+        let mut throttle = TargetHitThrottle::default();
+        let start = Instant::now();
+        throttle.process(relative(1), INTERVAL, start);
+        throttle.process(relative(2), INTERVAL, start + Duration::from_millis(10));
+        // When
+        let result = throttle.process(relative(4), INTERVAL, start + INTERVAL);
+        // Then
+        assert_eq!(result, Some(relative(6)));
+        assert!(!throttle.has_pending());
+    }
+}
@@ -0,0 +1,171 @@
+use crate::domain::{
+    ControlContext, DomainEvent, ExtendedProcessorContext, HitInstruction, HitInstructionContext,
+    HitInstructionReturnValue, MappingCompartment, MappingControlContext, MappingControlResult,
+    MappingId, MappingModeChangeRequestedEvent, RealearnTarget, ReaperTarget, ReaperTargetType,
+    TargetCharacter, TargetTypeDef, UnresolvedReaperTargetDef, DEFAULT_TARGET,
+};
+use derive_more::Display;
+use enum_iterator::IntoEnumIterator;
+use helgoboss_learn::{AbsoluteValue, ControlType, ControlValue, Target, UnitValue};
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug)]
+pub struct UnresolvedAdjustMappingModeTarget {
+    pub mapping_key: String,
+    pub setting: MappingModeSetting,
+}
+
+impl UnresolvedReaperTargetDef for UnresolvedAdjustMappingModeTarget {
+    fn resolve(
+        &self,
+        _: ExtendedProcessorContext,
+        _: MappingCompartment,
+    ) -> Result<Vec<ReaperTarget>, &'static str> {
+        Ok(vec![ReaperTarget::AdjustMappingMode(
+            AdjustMappingModeTarget {
+                mapping_key: self.mapping_key.clone(),
+                setting: self.setting,
+            },
+        )])
+    }
+}
+
+/// Changes a mode setting of another mapping (in the same compartment) at runtime.
+///
+/// The change is applied to the mapping model, so it's persisted and the affected mapping gets
+/// synced to the processors again.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AdjustMappingModeTarget {
+    pub mapping_key: String,
+    pub setting: MappingModeSetting,
+}
+
+/// Mode settings which can be adjusted by the "Adjust mapping mode" target.
+#[derive(
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    Debug,
+    Serialize,
+    Deserialize,
+    IntoEnumIterator,
+    TryFromPrimitive,
+    IntoPrimitive,
+    Display,
+)]
+#[repr(usize)]
+pub enum MappingModeSetting {
+    #[serde(rename = "sourceMin")]
+    #[display(fmt = "Source min")]
+    SourceMin,
+    #[serde(rename = "sourceMax")]
+    #[display(fmt = "Source max")]
+    SourceMax,
+    #[serde(rename = "targetMin")]
+    #[display(fmt = "Target min")]
+    TargetMin,
+    #[serde(rename = "targetMax")]
+    #[display(fmt = "Target max")]
+    TargetMax,
+    #[serde(rename = "stepSizeMin")]
+    #[display(fmt = "Step size min")]
+    StepSizeMin,
+    #[serde(rename = "stepSizeMax")]
+    #[display(fmt = "Step size max")]
+    StepSizeMax,
+    #[serde(rename = "jumpMin")]
+    #[display(fmt = "Jump min")]
+    JumpMin,
+    #[serde(rename = "jumpMax")]
+    #[display(fmt = "Jump max")]
+    JumpMax,
+    #[serde(rename = "deadZone")]
+    #[display(fmt = "Dead zone")]
+    DeadZone,
+}
+
+impl Default for MappingModeSetting {
+    fn default() -> Self {
+        MappingModeSetting::TargetMax
+    }
+}
+
+impl RealearnTarget for AdjustMappingModeTarget {
+    fn control_type_and_character(&self, _: ControlContext) -> (ControlType, TargetCharacter) {
+        (ControlType::AbsoluteContinuous, TargetCharacter::Continuous)
+    }
+
+    fn hit(
+        &mut self,
+        value: ControlValue,
+        context: MappingControlContext,
+    ) -> Result<HitInstructionReturnValue, &'static str> {
+        struct AdjustMappingModeInstruction {
+            own_mapping_id: MappingId,
+            mapping_key: String,
+            setting: MappingModeSetting,
+            value: UnitValue,
+        }
+        impl HitInstruction for AdjustMappingModeInstruction {
+            fn execute(
+                self: Box<Self>,
+                context: HitInstructionContext,
+            ) -> Vec<MappingControlResult> {
+                let mapping = context
+                    .mappings
+                    .values()
+                    .find(|m| m.key() == self.mapping_key && m.id() != self.own_mapping_id);
+                if let Some(m) = mapping {
+                    context.domain_event_handler.handle_event(
+                        DomainEvent::MappingModeChangeRequested(MappingModeChangeRequestedEvent {
+                            compartment: m.compartment(),
+                            mapping_id: m.id(),
+                            setting: self.setting,
+                            value: self.value,
+                        }),
+                    );
+                }
+                vec![]
+            }
+        }
+        let instruction = AdjustMappingModeInstruction {
+            own_mapping_id: context.mapping_data.mapping_id,
+            mapping_key: self.mapping_key.clone(),
+            setting: self.setting,
+            value: value.to_unit_value()?,
+        };
+        Ok(Some(Box::new(instruction)))
+    }
+
+    fn can_report_current_value(&self) -> bool {
+        false
+    }
+
+    fn is_available(&self, _: ControlContext) -> bool {
+        !self.mapping_key.is_empty()
+    }
+
+    fn reaper_target_type(&self) -> Option<ReaperTargetType> {
+        Some(ReaperTargetType::AdjustMappingMode)
+    }
+}
+
+impl<'a> Target<'a> for AdjustMappingModeTarget {
+    type Context = ControlContext<'a>;
+
+    fn current_value(&self, _: Self::Context) -> Option<AbsoluteValue> {
+        None
+    }
+
+    fn control_type(&self, context: Self::Context) -> ControlType {
+        self.control_type_and_character(context).0
+    }
+}
+
+pub const ADJUST_MAPPING_MODE_TARGET: TargetTypeDef = TargetTypeDef {
+    name: "ReaLearn: Adjust mapping mode",
+    short_name: "Adjust mapping mode",
+    ..DEFAULT_TARGET
+};
@@ -0,0 +1,144 @@
+use crate::domain::{
+    format_value_as_on_off, get_effective_tracks, modify_clip_matrix, with_clip_matrix,
+    ClipChangedEvent, ClipMatrixId, CompoundChangeEvent, ControlContext, ExtendedProcessorContext,
+    HitInstructionReturnValue, InstanceStateChanged, MappingCompartment, MappingControlContext,
+    RealearnTarget, ReaperTarget, ReaperTargetType, SlotStopQuantization, TargetCharacter,
+    TargetTypeDef, TrackDescriptor, UnresolvedReaperTargetDef, DEFAULT_TARGET,
+};
+use helgoboss_learn::{AbsoluteValue, ControlType, ControlValue, Target, UnitValue};
+use reaper_high::{Project, Track};
+
+#[derive(Debug)]
+pub struct UnresolvedClipColumnStopTarget {
+    pub track_descriptor: TrackDescriptor,
+    pub matrix_id: Option<ClipMatrixId>,
+}
+
+impl UnresolvedReaperTargetDef for UnresolvedClipColumnStopTarget {
+    fn resolve(
+        &self,
+        context: ExtendedProcessorContext,
+        compartment: MappingCompartment,
+    ) -> Result<Vec<ReaperTarget>, &'static str> {
+        Ok(
+            get_effective_tracks(context, &self.track_descriptor.track, compartment)?
+                .into_iter()
+                .map(|track| {
+                    ReaperTarget::ClipColumnStop(ClipColumnStopTarget {
+                        track,
+                        matrix_id: self.matrix_id.clone(),
+                    })
+                })
+                .collect(),
+        )
+    }
+
+    fn track_descriptor(&self) -> Option<&TrackDescriptor> {
+        Some(&self.track_descriptor)
+    }
+}
+
+/// Stops all clips playing in the column which corresponds to the given track.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClipColumnStopTarget {
+    pub track: Track,
+    pub matrix_id: Option<ClipMatrixId>,
+}
+
+impl RealearnTarget for ClipColumnStopTarget {
+    fn control_type_and_character(&self, _: ControlContext) -> (ControlType, TargetCharacter) {
+        (
+            ControlType::AbsoluteContinuousRetriggerable,
+            TargetCharacter::Trigger,
+        )
+    }
+
+    fn format_value(&self, value: UnitValue, _: ControlContext) -> String {
+        format_value_as_on_off(value).to_string()
+    }
+
+    fn hit(
+        &mut self,
+        value: ControlValue,
+        context: MappingControlContext,
+    ) -> Result<HitInstructionReturnValue, &'static str> {
+        if value.to_unit_value()?.is_zero() {
+            return Ok(None);
+        }
+        let quantization = context
+            .control_context
+            .instance_state
+            .borrow()
+            .clip_stop_quantization()
+            .unwrap_or(SlotStopQuantization::Immediately);
+        modify_clip_matrix(
+            context.control_context.instance_state,
+            self.matrix_id.as_ref(),
+            |matrix| Ok(matrix.stop_column(&self.track, quantization)),
+        )?;
+        Ok(None)
+    }
+
+    fn is_available(&self, _: ControlContext) -> bool {
+        self.track.is_available()
+    }
+
+    fn project(&self) -> Option<Project> {
+        Some(self.track.project())
+    }
+
+    fn track(&self) -> Option<&Track> {
+        Some(&self.track)
+    }
+
+    fn process_change_event(
+        &self,
+        evt: CompoundChangeEvent,
+        _: ControlContext,
+    ) -> (bool, Option<AbsoluteValue>) {
+        match evt {
+            CompoundChangeEvent::Instance(InstanceStateChanged::Clip {
+                matrix_id,
+                event: ClipChangedEvent::PlayState(_),
+                ..
+            }) if matrix_id == &self.matrix_id => (true, None),
+            _ => (false, None),
+        }
+    }
+
+    fn text_value(&self, context: ControlContext) -> Option<String> {
+        Some(format_value_as_on_off(self.current_value(context)?.to_unit_value()).to_string())
+    }
+
+    fn reaper_target_type(&self) -> Option<ReaperTargetType> {
+        Some(ReaperTargetType::ClipColumnStop)
+    }
+}
+
+impl<'a> Target<'a> for ClipColumnStopTarget {
+    type Context = ControlContext<'a>;
+
+    fn current_value(&self, context: ControlContext<'a>) -> Option<AbsoluteValue> {
+        let is_playing = with_clip_matrix(context.instance_state, self.matrix_id.as_ref(), |m| {
+            m.column_is_playing(&self.track)
+        });
+        let val = if is_playing {
+            UnitValue::MAX
+        } else {
+            UnitValue::MIN
+        };
+        Some(AbsoluteValue::Continuous(val))
+    }
+
+    fn control_type(&self, context: Self::Context) -> ControlType {
+        self.control_type_and_character(context).0
+    }
+}
+
+pub const CLIP_COLUMN_STOP_TARGET: TargetTypeDef = TargetTypeDef {
+    name: "Clip: Stop column",
+    short_name: "Clip column stop",
+    hint: "Column = track on which the clips play",
+    supports_track: true,
+    ..DEFAULT_TARGET
+};
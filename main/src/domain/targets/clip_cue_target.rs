@@ -0,0 +1,157 @@
+use crate::domain::ui_util::{
+    format_value_as_db, format_value_as_db_without_unit, parse_value_from_db, volume_unit_value,
+};
+use crate::domain::{
+    cue_volume, resolve_clip_slot_index, with_clip_matrix, ClipMatrixId, ClipSlotAddress,
+    ControlContext, ExtendedProcessorContext, HitInstructionReturnValue, MappingCompartment,
+    MappingControlContext, RealearnTarget, ReaperTarget, ReaperTargetType, TargetCharacter,
+    TargetTypeDef, UnresolvedReaperTargetDef, DEFAULT_TARGET,
+};
+use helgoboss_learn::{AbsoluteValue, ControlType, ControlValue, NumericValue, Target, UnitValue};
+use reaper_high::{Track, Volume};
+
+#[derive(Debug)]
+pub struct UnresolvedClipCueTarget {
+    pub matrix_id: Option<ClipMatrixId>,
+    pub slot: ClipSlotAddress,
+    pub cue_output: u32,
+}
+
+impl UnresolvedReaperTargetDef for UnresolvedClipCueTarget {
+    fn resolve(
+        &self,
+        context: ExtendedProcessorContext,
+        _: MappingCompartment,
+    ) -> Result<Vec<ReaperTarget>, &'static str> {
+        let slot_index = resolve_clip_slot_index(
+            context.control_context().instance_state,
+            self.matrix_id.as_ref(),
+            self.slot,
+        )?;
+        Ok(vec![ReaperTarget::ClipCue(ClipCueTarget {
+            matrix_id: self.matrix_id.clone(),
+            slot_index,
+            cue_output: self.cue_output,
+        })])
+    }
+}
+
+/// Pre-listens a clip on the cue output (PFL-style) by cueing the track on which it plays.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClipCueTarget {
+    pub matrix_id: Option<ClipMatrixId>,
+    pub slot_index: usize,
+    pub cue_output: u32,
+}
+
+impl RealearnTarget for ClipCueTarget {
+    fn control_type_and_character(&self, _: ControlContext) -> (ControlType, TargetCharacter) {
+        (ControlType::AbsoluteContinuous, TargetCharacter::Continuous)
+    }
+
+    fn parse_as_value(&self, text: &str, _: ControlContext) -> Result<UnitValue, &'static str> {
+        parse_value_from_db(text)
+    }
+
+    fn format_value_without_unit(&self, value: UnitValue, _: ControlContext) -> String {
+        format_value_as_db_without_unit(value)
+    }
+
+    fn hide_formatted_value(&self, _: ControlContext) -> bool {
+        true
+    }
+
+    fn hide_formatted_step_size(&self, _: ControlContext) -> bool {
+        true
+    }
+
+    fn value_unit(&self, _: ControlContext) -> &'static str {
+        "dB"
+    }
+
+    fn format_value(&self, value: UnitValue, _: ControlContext) -> String {
+        format_value_as_db(value)
+    }
+
+    fn hit(
+        &mut self,
+        value: ControlValue,
+        context: MappingControlContext,
+    ) -> Result<HitInstructionReturnValue, &'static str> {
+        let volume = cue_volume(value.to_unit_value()?);
+        let track = self
+            .playing_track(context.control_context)
+            .ok_or("clip not playing")?;
+        context
+            .control_context
+            .instance_state
+            .borrow_mut()
+            .cue_sends_mut()
+            .cue(
+                context.mapping_data.mapping_id,
+                &track,
+                self.cue_output,
+                volume,
+            )?;
+        Ok(None)
+    }
+
+    /// The cue state is attached to the mappings, so feedback is sent right after controlling.
+    fn supports_automatic_feedback(&self) -> bool {
+        false
+    }
+
+    fn text_value(&self, context: ControlContext) -> Option<String> {
+        Some(self.volume(context)?.to_string())
+    }
+
+    fn numeric_value(&self, context: ControlContext) -> Option<NumericValue> {
+        Some(NumericValue::Decimal(self.volume(context)?.db().get()))
+    }
+
+    fn reaper_target_type(&self) -> Option<ReaperTargetType> {
+        Some(ReaperTargetType::ClipCue)
+    }
+}
+
+impl ClipCueTarget {
+    fn playing_track(&self, context: ControlContext) -> Option<Track> {
+        with_clip_matrix(context.instance_state, self.matrix_id.as_ref(), |m| {
+            m.get_slot(self.slot_index).ok()?.playing_track().cloned()
+        })
+    }
+
+    fn volume(&self, context: ControlContext) -> Option<Volume> {
+        let track = self.playing_track(context)?;
+        context
+            .instance_state
+            .borrow()
+            .cue_sends()
+            .volume(&track, self.cue_output)
+    }
+}
+
+impl<'a> Target<'a> for ClipCueTarget {
+    type Context = ControlContext<'a>;
+
+    fn current_value(&self, context: Self::Context) -> Option<AbsoluteValue> {
+        let val = self
+            .volume(context)
+            .map(volume_unit_value)
+            .unwrap_or(UnitValue::MIN);
+        Some(AbsoluteValue::Continuous(val))
+    }
+
+    fn control_type(&self, context: Self::Context) -> ControlType {
+        self.control_type_and_character(context).0
+    }
+}
+
+pub const CLIP_CUE_TARGET: TargetTypeDef = TargetTypeDef {
+    name: "Clip: Cue",
+    short_name: "Clip cue",
+    hint: "Pre-listens the track the clip plays on, 0 = off",
+    supports_slot: true,
+    supports_cue_output: true,
+    ..DEFAULT_TARGET
+};
@@ -0,0 +1,125 @@
+use crate::domain::{
+    format_value_as_on_off, modify_clip_matrix, with_clip_matrix, ClipChangedEvent, ClipMatrixId,
+    CompoundChangeEvent, ControlContext, ExtendedProcessorContext, HitInstructionReturnValue,
+    InstanceStateChanged, MappingCompartment, MappingControlContext, RealearnTarget, ReaperTarget,
+    ReaperTargetType, SlotPlayOptions, TargetCharacter, TargetTypeDef, UnresolvedReaperTargetDef,
+    DEFAULT_TARGET,
+};
+use helgoboss_learn::{AbsoluteValue, ControlType, ControlValue, Target, UnitValue};
+
+#[derive(Debug)]
+pub struct UnresolvedClipSceneLaunchTarget {
+    pub matrix_id: Option<ClipMatrixId>,
+    pub row: usize,
+    pub play_options: SlotPlayOptions,
+}
+
+impl UnresolvedReaperTargetDef for UnresolvedClipSceneLaunchTarget {
+    fn resolve(
+        &self,
+        _: ExtendedProcessorContext,
+        _: MappingCompartment,
+    ) -> Result<Vec<ReaperTarget>, &'static str> {
+        Ok(vec![ReaperTarget::ClipSceneLaunch(ClipSceneLaunchTarget {
+            matrix_id: self.matrix_id.clone(),
+            row: self.row,
+            play_options: self.play_options,
+        })])
+    }
+}
+
+/// Plays all filled slots in one row of the clip matrix at once.
+///
+/// The play options are only used for slots which haven't been played before. All other slots
+/// are played the way they were played most recently.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClipSceneLaunchTarget {
+    pub matrix_id: Option<ClipMatrixId>,
+    pub row: usize,
+    pub play_options: SlotPlayOptions,
+}
+
+impl RealearnTarget for ClipSceneLaunchTarget {
+    fn control_type_and_character(&self, _: ControlContext) -> (ControlType, TargetCharacter) {
+        (
+            ControlType::AbsoluteContinuousRetriggerable,
+            TargetCharacter::Trigger,
+        )
+    }
+
+    fn format_value(&self, value: UnitValue, _: ControlContext) -> String {
+        format_value_as_on_off(value).to_string()
+    }
+
+    fn hit(
+        &mut self,
+        value: ControlValue,
+        context: MappingControlContext,
+    ) -> Result<HitInstructionReturnValue, &'static str> {
+        if value.to_unit_value()?.is_zero() {
+            return Ok(None);
+        }
+        modify_clip_matrix(
+            context.control_context.instance_state,
+            self.matrix_id.as_ref(),
+            |matrix| matrix.play_row(self.row, self.play_options),
+        )?;
+        Ok(None)
+    }
+
+    fn is_available(&self, context: ControlContext) -> bool {
+        with_clip_matrix(context.instance_state, self.matrix_id.as_ref(), |m| {
+            self.row < m.size().rows
+        })
+    }
+
+    fn process_change_event(
+        &self,
+        evt: CompoundChangeEvent,
+        _: ControlContext,
+    ) -> (bool, Option<AbsoluteValue>) {
+        match evt {
+            CompoundChangeEvent::Instance(InstanceStateChanged::Clip {
+                matrix_id,
+                event: ClipChangedEvent::PlayState(_),
+                ..
+            }) if matrix_id == &self.matrix_id => (true, None),
+            _ => (false, None),
+        }
+    }
+
+    fn text_value(&self, context: ControlContext) -> Option<String> {
+        Some(format_value_as_on_off(self.current_value(context)?.to_unit_value()).to_string())
+    }
+
+    fn reaper_target_type(&self) -> Option<ReaperTargetType> {
+        Some(ReaperTargetType::ClipSceneLaunch)
+    }
+}
+
+impl<'a> Target<'a> for ClipSceneLaunchTarget {
+    type Context = ControlContext<'a>;
+
+    fn current_value(&self, context: ControlContext<'a>) -> Option<AbsoluteValue> {
+        let is_playing = with_clip_matrix(context.instance_state, self.matrix_id.as_ref(), |m| {
+            m.row_is_playing(self.row)
+        });
+        let val = if is_playing {
+            UnitValue::MAX
+        } else {
+            UnitValue::MIN
+        };
+        Some(AbsoluteValue::Continuous(val))
+    }
+
+    fn control_type(&self, context: Self::Context) -> ControlType {
+        self.control_type_and_character(context).0
+    }
+}
+
+pub const CLIP_SCENE_LAUNCH_TARGET: TargetTypeDef = TargetTypeDef {
+    name: "Clip: Launch scene",
+    short_name: "Clip scene launch",
+    hint: "Scene = row of the clip matrix",
+    ..DEFAULT_TARGET
+};
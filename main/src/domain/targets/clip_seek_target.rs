@@ -1,26 +1,34 @@
 use crate::domain::{
-    AdditionalFeedbackEvent, ClipChangedEvent, ClipPlayState, CompoundChangeEvent, ControlContext,
-    ExtendedProcessorContext, FeedbackResolution, HitInstructionReturnValue, InstanceStateChanged,
-    MappingCompartment, MappingControlContext, RealearnTarget, ReaperTarget, ReaperTargetType,
-    TargetCharacter, TargetTypeDef, UnresolvedReaperTargetDef, DEFAULT_TARGET,
+    modify_clip_slot, resolve_clip_slot_index, with_clip_matrix, AdditionalFeedbackEvent,
+    ClipChangedEvent, ClipMatrixId, ClipPlayState, ClipSlotAddress, CompoundChangeEvent,
+    ControlContext, ExtendedProcessorContext, FeedbackResolution, HitInstructionReturnValue,
+    InstanceStateChanged, MappingCompartment, MappingControlContext, RealearnTarget, ReaperTarget,
+    ReaperTargetType, TargetCharacter, TargetTypeDef, UnresolvedReaperTargetDef, DEFAULT_TARGET,
 };
 use helgoboss_learn::{AbsoluteValue, ControlType, ControlValue, NumericValue, Target, UnitValue};
 use reaper_medium::PositionInSeconds;
 
 #[derive(Debug)]
 pub struct UnresolvedClipSeekTarget {
-    pub slot_index: usize,
+    pub matrix_id: Option<ClipMatrixId>,
+    pub slot: ClipSlotAddress,
     pub feedback_resolution: FeedbackResolution,
 }
 
 impl UnresolvedReaperTargetDef for UnresolvedClipSeekTarget {
     fn resolve(
         &self,
-        _: ExtendedProcessorContext,
+        context: ExtendedProcessorContext,
         _: MappingCompartment,
     ) -> Result<Vec<ReaperTarget>, &'static str> {
+        let slot_index = resolve_clip_slot_index(
+            context.control_context().instance_state,
+            self.matrix_id.as_ref(),
+            self.slot,
+        )?;
         Ok(vec![ReaperTarget::ClipSeek(ClipSeekTarget {
-            slot_index: self.slot_index,
+            matrix_id: self.matrix_id.clone(),
+            slot_index,
             feedback_resolution: self.feedback_resolution,
         })])
     }
@@ -32,6 +40,7 @@ impl UnresolvedReaperTargetDef for UnresolvedClipSeekTarget {
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct ClipSeekTarget {
+    pub matrix_id: Option<ClipMatrixId>,
     pub slot_index: usize,
     pub feedback_resolution: FeedbackResolution,
 }
@@ -47,8 +56,12 @@ impl RealearnTarget for ClipSeekTarget {
         context: MappingControlContext,
     ) -> Result<HitInstructionReturnValue, &'static str> {
         let value = value.to_unit_value()?;
-        let mut instance_state = context.control_context.instance_state.borrow_mut();
-        instance_state.seek_slot(self.slot_index, value)?;
+        modify_clip_slot(
+            context.control_context.instance_state,
+            self.matrix_id.as_ref(),
+            self.slot_index,
+            |slot| slot.set_position(value),
+        )?;
         Ok(None)
     }
 
@@ -72,10 +85,12 @@ impl RealearnTarget for ClipSeekTarget {
             // If feedback resolution is high, we use the special ClipChangedEvent to do our job
             // (in order to not lock mutex of playing clips more than once per main loop cycle).
             CompoundChangeEvent::Instance(InstanceStateChanged::Clip {
+                matrix_id,
                 slot_index: si,
                 event,
             }) if self.feedback_resolution == FeedbackResolution::High
-                && *si == self.slot_index =>
+                && *si == self.slot_index
+                && matrix_id == &self.matrix_id =>
             {
                 match event {
                     ClipChangedEvent::ClipPosition(new_position) => {
@@ -112,12 +127,9 @@ impl RealearnTarget for ClipSeekTarget {
 
 impl ClipSeekTarget {
     fn position_in_seconds(&self, context: ControlContext) -> Option<PositionInSeconds> {
-        let instance_state = context.instance_state.borrow();
-        let secs = instance_state
-            .get_slot(self.slot_index)
-            .ok()?
-            .position_in_seconds();
-        Some(secs)
+        with_clip_matrix(context.instance_state, self.matrix_id.as_ref(), |m| {
+            Some(m.get_slot(self.slot_index).ok()?.position_in_seconds())
+        })
     }
 }
 
@@ -125,12 +137,9 @@ impl<'a> Target<'a> for ClipSeekTarget {
     type Context = ControlContext<'a>;
 
     fn current_value(&self, context: ControlContext<'a>) -> Option<AbsoluteValue> {
-        let instance_state = context.instance_state.borrow();
-        let val = instance_state
-            .get_slot(self.slot_index)
-            .ok()?
-            .position()
-            .ok()?;
+        let val = with_clip_matrix(context.instance_state, self.matrix_id.as_ref(), |m| {
+            m.get_slot(self.slot_index).ok()?.position().ok()
+        })?;
         Some(AbsoluteValue::Continuous(val))
     }
 
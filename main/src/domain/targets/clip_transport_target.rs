@@ -1,10 +1,11 @@
 use crate::domain::{
-    clip_play_state_unit_value, format_value_as_on_off, get_effective_tracks,
-    transport_is_enabled_unit_value, ClipChangedEvent, CompoundChangeEvent, ControlContext,
-    ExtendedProcessorContext, HitInstructionReturnValue, InstanceStateChanged, MappingCompartment,
-    MappingControlContext, RealearnTarget, ReaperTarget, ReaperTargetType, SlotPlayOptions,
-    TargetCharacter, TargetTypeDef, TrackDescriptor, TransportAction, UnresolvedReaperTargetDef,
-    DEFAULT_TARGET,
+    clip_play_state_unit_value, format_value_as_on_off, get_effective_tracks, modify_clip_matrix,
+    modify_clip_slot, process_clip_transport_change, resolve_clip_slot_index,
+    transport_is_enabled_unit_value, with_clip_matrix, ClipChangedEvent, ClipMatrixId, ClipSlot,
+    ClipSlotAddress, CompoundChangeEvent, ControlContext, ExtendedProcessorContext,
+    HitInstructionReturnValue, InstanceStateChanged, MappingCompartment, MappingControlContext,
+    RealearnTarget, ReaperTarget, ReaperTargetType, SlotPlayOptions, TargetCharacter,
+    TargetTypeDef, TrackDescriptor, TransportAction, UnresolvedReaperTargetDef, DEFAULT_TARGET,
 };
 use helgoboss_learn::{AbsoluteValue, ControlType, ControlValue, Target, UnitValue};
 use reaper_high::{ChangeEvent, Project, Track};
@@ -12,7 +13,8 @@ use reaper_high::{ChangeEvent, Project, Track};
 #[derive(Debug)]
 pub struct UnresolvedClipTransportTarget {
     pub track_descriptor: Option<TrackDescriptor>,
-    pub slot_index: usize,
+    pub matrix_id: Option<ClipMatrixId>,
+    pub slot: ClipSlotAddress,
     pub action: TransportAction,
     pub play_options: SlotPlayOptions,
 }
@@ -23,13 +25,19 @@ impl UnresolvedReaperTargetDef for UnresolvedClipTransportTarget {
         context: ExtendedProcessorContext,
         compartment: MappingCompartment,
     ) -> Result<Vec<ReaperTarget>, &'static str> {
+        let slot_index = resolve_clip_slot_index(
+            context.control_context().instance_state,
+            self.matrix_id.as_ref(),
+            self.slot,
+        )?;
         let targets = if let Some(desc) = self.track_descriptor.as_ref() {
             get_effective_tracks(context, &desc.track, compartment)?
                 .into_iter()
                 .map(|track| {
                     ReaperTarget::ClipTransport(ClipTransportTarget {
                         track: Some(track),
-                        slot_index: self.slot_index,
+                        matrix_id: self.matrix_id.clone(),
+                        slot_index,
                         action: self.action,
                         play_options: self.play_options,
                     })
@@ -38,7 +46,8 @@ impl UnresolvedReaperTargetDef for UnresolvedClipTransportTarget {
         } else {
             vec![ReaperTarget::ClipTransport(ClipTransportTarget {
                 track: None,
-                slot_index: self.slot_index,
+                matrix_id: self.matrix_id.clone(),
+                slot_index,
                 action: self.action,
                 play_options: self.play_options,
             })]
@@ -54,11 +63,51 @@ impl UnresolvedReaperTargetDef for UnresolvedClipTransportTarget {
 #[derive(Clone, Debug, PartialEq)]
 pub struct ClipTransportTarget {
     pub track: Option<Track>,
+    pub matrix_id: Option<ClipMatrixId>,
     pub slot_index: usize,
     pub action: TransportAction,
     pub play_options: SlotPlayOptions,
 }
 
+impl ClipTransportTarget {
+    fn play(&self, context: MappingControlContext) -> Result<(), &'static str> {
+        modify_clip_matrix(
+            context.control_context.instance_state,
+            self.matrix_id.as_ref(),
+            |matrix| matrix.play_slot(self.slot_index, self.track.clone(), self.play_options),
+        )
+    }
+
+    fn stop(&self, context: MappingControlContext) -> Result<(), &'static str> {
+        let instance_default = context
+            .control_context
+            .instance_state
+            .borrow()
+            .clip_stop_quantization();
+        let quantization = self
+            .play_options
+            .effective_stop_quantization(instance_default);
+        self.modify_slot(context, |slot| slot.stop(quantization))
+    }
+
+    fn pause(&self, context: MappingControlContext) -> Result<(), &'static str> {
+        self.modify_slot(context, |slot| slot.pause())
+    }
+
+    fn modify_slot(
+        &self,
+        context: MappingControlContext,
+        op: impl FnOnce(&mut ClipSlot) -> Result<ClipChangedEvent, &'static str>,
+    ) -> Result<(), &'static str> {
+        modify_clip_slot(
+            context.control_context.instance_state,
+            self.matrix_id.as_ref(),
+            self.slot_index,
+            op,
+        )
+    }
+}
+
 impl RealearnTarget for ClipTransportTarget {
     fn control_type_and_character(&self, _: ControlContext) -> (ControlType, TargetCharacter) {
         (
@@ -78,37 +127,36 @@ impl RealearnTarget for ClipTransportTarget {
     ) -> Result<HitInstructionReturnValue, &'static str> {
         use TransportAction::*;
         let on = !value.to_unit_value()?.is_zero();
-        let mut instance_state = context.control_context.instance_state.borrow_mut();
         match self.action {
             PlayStop => {
                 if on {
-                    instance_state.play(self.slot_index, self.track.clone(), self.play_options)?;
+                    self.play(context)?;
                 } else {
-                    instance_state.stop(self.slot_index, !self.play_options.next_bar)?;
+                    self.stop(context)?;
                 }
             }
             PlayPause => {
                 if on {
-                    instance_state.play(self.slot_index, self.track.clone(), self.play_options)?;
+                    self.play(context)?;
                 } else {
-                    instance_state.pause(self.slot_index)?;
+                    self.pause(context)?;
                 }
             }
             Stop => {
                 if on {
-                    instance_state.stop(self.slot_index, !self.play_options.next_bar)?;
+                    self.stop(context)?;
                 }
             }
             Pause => {
                 if on {
-                    instance_state.pause(self.slot_index)?;
+                    self.pause(context)?;
                 }
             }
             Record => {
                 return Err("not supported at the moment");
             }
             Repeat => {
-                instance_state.toggle_repeat(self.slot_index)?;
+                self.modify_slot(context, |slot| Ok(slot.toggle_repeat()))?;
             }
         };
         Ok(None)
@@ -141,14 +189,18 @@ impl RealearnTarget for ClipTransportTarget {
         match evt {
             CompoundChangeEvent::Reaper(ChangeEvent::PlayStateChanged(e)) => {
                 // Feedback handled from instance-scoped feedback events.
-                let mut instance_state = context.instance_state.borrow_mut();
-                instance_state.process_transport_change(e.new_value);
+                process_clip_transport_change(
+                    context.instance_state,
+                    self.matrix_id.as_ref(),
+                    e.new_value,
+                );
                 (false, None)
             }
             CompoundChangeEvent::Instance(InstanceStateChanged::Clip {
+                matrix_id,
                 slot_index: si,
                 event,
-            }) if *si == self.slot_index => {
+            }) if *si == self.slot_index && matrix_id == &self.matrix_id => {
                 use TransportAction::*;
                 match self.action {
                     PlayStop | PlayPause | Stop | Pause => match event {
@@ -191,22 +243,18 @@ impl<'a> Target<'a> for ClipTransportTarget {
     type Context = ControlContext<'a>;
 
     fn current_value(&self, context: ControlContext<'a>) -> Option<AbsoluteValue> {
-        let instance_state = context.instance_state.borrow();
         use TransportAction::*;
-        let val = match self.action {
-            PlayStop | PlayPause | Stop | Pause => {
-                let play_state = instance_state.get_slot(self.slot_index).ok()?.play_state();
-                clip_play_state_unit_value(self.action, play_state)
-            }
-            Repeat => {
-                let is_looped = instance_state
-                    .get_slot(self.slot_index)
-                    .ok()?
-                    .repeat_is_enabled();
-                transport_is_enabled_unit_value(is_looped)
-            }
-            Record => return None,
-        };
+        let val = with_clip_matrix(context.instance_state, self.matrix_id.as_ref(), |m| {
+            let slot = m.get_slot(self.slot_index).ok()?;
+            let val = match self.action {
+                PlayStop | PlayPause | Stop | Pause => {
+                    clip_play_state_unit_value(self.action, slot.play_state())
+                }
+                Repeat => transport_is_enabled_unit_value(slot.repeat_is_enabled()),
+                Record => return None,
+            };
+            Some(val)
+        })?;
         Some(AbsoluteValue::Continuous(val))
     }
 
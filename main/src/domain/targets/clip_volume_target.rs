@@ -3,7 +3,8 @@ use crate::domain::ui_util::{
     reaper_volume_unit_value, volume_unit_value,
 };
 use crate::domain::{
-    ClipChangedEvent, CompoundChangeEvent, ControlContext, ExtendedProcessorContext,
+    modify_clip_slot, resolve_clip_slot_index, with_clip_matrix, ClipChangedEvent, ClipMatrixId,
+    ClipSlotAddress, CompoundChangeEvent, ControlContext, ExtendedProcessorContext,
     HitInstructionReturnValue, InstanceStateChanged, MappingCompartment, MappingControlContext,
     RealearnTarget, ReaperTarget, ReaperTargetType, TargetCharacter, TargetTypeDef,
     UnresolvedReaperTargetDef, DEFAULT_TARGET,
@@ -13,23 +14,31 @@ use reaper_high::Volume;
 
 #[derive(Debug)]
 pub struct UnresolvedClipVolumeTarget {
-    pub slot_index: usize,
+    pub matrix_id: Option<ClipMatrixId>,
+    pub slot: ClipSlotAddress,
 }
 
 impl UnresolvedReaperTargetDef for UnresolvedClipVolumeTarget {
     fn resolve(
         &self,
-        _: ExtendedProcessorContext,
+        context: ExtendedProcessorContext,
         _: MappingCompartment,
     ) -> Result<Vec<ReaperTarget>, &'static str> {
+        let slot_index = resolve_clip_slot_index(
+            context.control_context().instance_state,
+            self.matrix_id.as_ref(),
+            self.slot,
+        )?;
         Ok(vec![ReaperTarget::ClipVolume(ClipVolumeTarget {
-            slot_index: self.slot_index,
+            matrix_id: self.matrix_id.clone(),
+            slot_index,
         })])
     }
 }
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct ClipVolumeTarget {
+    pub matrix_id: Option<ClipMatrixId>,
     pub slot_index: usize,
 }
 
@@ -60,10 +69,12 @@ impl RealearnTarget for ClipVolumeTarget {
         context: MappingControlContext,
     ) -> Result<HitInstructionReturnValue, &'static str> {
         let volume = Volume::try_from_soft_normalized_value(value.to_unit_value()?.get());
-        let mut instance_state = context.control_context.instance_state.borrow_mut();
-        instance_state.set_volume(
+        let volume = volume.unwrap_or(Volume::MIN).reaper_value();
+        modify_clip_slot(
+            context.control_context.instance_state,
+            self.matrix_id.as_ref(),
             self.slot_index,
-            volume.unwrap_or(Volume::MIN).reaper_value(),
+            |slot| Ok(slot.set_volume(volume)),
         )?;
         Ok(None)
     }
@@ -81,9 +92,10 @@ impl RealearnTarget for ClipVolumeTarget {
     ) -> (bool, Option<AbsoluteValue>) {
         match evt {
             CompoundChangeEvent::Instance(InstanceStateChanged::Clip {
+                matrix_id,
                 slot_index: si,
                 event: ClipChangedEvent::ClipVolume(new_value),
-            }) if *si == self.slot_index => (
+            }) if *si == self.slot_index && matrix_id == &self.matrix_id => (
                 true,
                 Some(AbsoluteValue::Continuous(reaper_volume_unit_value(
                     *new_value,
@@ -108,8 +120,10 @@ impl RealearnTarget for ClipVolumeTarget {
 
 impl ClipVolumeTarget {
     fn volume(&self, context: ControlContext) -> Option<Volume> {
-        let instance_state = context.instance_state.borrow();
-        let reaper_volume = instance_state.get_slot(self.slot_index).ok()?.volume();
+        let reaper_volume =
+            with_clip_matrix(context.instance_state, self.matrix_id.as_ref(), |m| {
+                Some(m.get_slot(self.slot_index).ok()?.volume())
+            })?;
         Some(Volume::from_reaper_value(reaper_volume))
     }
 }
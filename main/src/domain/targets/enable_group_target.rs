@@ -0,0 +1,208 @@
+use crate::domain::{
+    format_value_as_on_off, CompoundChangeEvent, ControlContext, DomainEvent,
+    ExtendedProcessorContext, GroupId, HitInstruction, HitInstructionContext,
+    HitInstructionReturnValue, InstanceStateChanged, MappingCompartment, MappingControlContext,
+    MappingControlResult, MappingEnabledChangeRequestedEvent, MappingId, RealearnTarget,
+    ReaperTarget, ReaperTargetType, TargetCharacter, TargetTypeDef, UnresolvedReaperTargetDef,
+    DEFAULT_TARGET,
+};
+use derive_more::Display;
+use enum_iterator::IntoEnumIterator;
+use helgoboss_learn::{AbsoluteValue, ControlType, ControlValue, Target, UnitValue};
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug)]
+pub struct UnresolvedEnableGroupTarget {
+    pub compartment: MappingCompartment,
+    pub group_id: GroupId,
+    pub mode: GroupEnableMode,
+}
+
+impl UnresolvedReaperTargetDef for UnresolvedEnableGroupTarget {
+    fn resolve(
+        &self,
+        _: ExtendedProcessorContext,
+        _: MappingCompartment,
+    ) -> Result<Vec<ReaperTarget>, &'static str> {
+        Ok(vec![ReaperTarget::EnableGroup(EnableGroupTarget {
+            compartment: self.compartment,
+            group_id: self.group_id,
+            mode: self.mode,
+        })])
+    }
+}
+
+/// Enables or disables all mappings of one group (except the mapping which owns this target).
+#[derive(Clone, Debug, PartialEq)]
+pub struct EnableGroupTarget {
+    /// This must always correspond to the compartment of the containing mapping, otherwise it will
+    /// lead to strange behavior.
+    pub compartment: MappingCompartment,
+    pub group_id: GroupId,
+    pub mode: GroupEnableMode,
+}
+
+/// How the "Enable/disable group" target reacts to incoming values.
+#[derive(
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    Debug,
+    Serialize,
+    Deserialize,
+    IntoEnumIterator,
+    TryFromPrimitive,
+    IntoPrimitive,
+    Display,
+)]
+#[repr(usize)]
+pub enum GroupEnableMode {
+    /// Values greater than 0% enable the group, 0% disables it.
+    #[serde(rename = "momentary")]
+    #[display(fmt = "Momentary")]
+    Momentary,
+    /// Each value greater than 0% switches the group on or off. 0% is ignored.
+    #[serde(rename = "toggle")]
+    #[display(fmt = "Toggle")]
+    Toggle,
+}
+
+impl Default for GroupEnableMode {
+    fn default() -> Self {
+        GroupEnableMode::Momentary
+    }
+}
+
+impl RealearnTarget for EnableGroupTarget {
+    fn control_type_and_character(&self, _: ControlContext) -> (ControlType, TargetCharacter) {
+        let character = match self.mode {
+            GroupEnableMode::Momentary => TargetCharacter::Switch,
+            GroupEnableMode::Toggle => TargetCharacter::Trigger,
+        };
+        (ControlType::AbsoluteContinuousRetriggerable, character)
+    }
+
+    fn format_value(&self, value: UnitValue, _: ControlContext) -> String {
+        format_value_as_on_off(value).to_string()
+    }
+
+    fn hit(
+        &mut self,
+        value: ControlValue,
+        context: MappingControlContext,
+    ) -> Result<HitInstructionReturnValue, &'static str> {
+        let is_on = !value.to_unit_value()?.is_zero();
+        let is_enable = match self.mode {
+            GroupEnableMode::Momentary => is_on,
+            GroupEnableMode::Toggle => {
+                if !is_on {
+                    return Ok(None);
+                }
+                !context
+                    .control_context
+                    .instance_state
+                    .borrow()
+                    .group_is_enabled(self.compartment, self.group_id)
+            }
+        };
+        struct EnableGroupInstruction {
+            compartment: MappingCompartment,
+            group_id: GroupId,
+            own_mapping_id: MappingId,
+            is_enable: bool,
+        }
+        impl HitInstruction for EnableGroupInstruction {
+            fn execute(
+                self: Box<Self>,
+                context: HitInstructionContext,
+            ) -> Vec<MappingControlResult> {
+                for m in context.mappings.values() {
+                    if m.group_id() != self.group_id {
+                        continue;
+                    }
+                    // Don't touch ourselves.
+                    if m.id() == self.own_mapping_id {
+                        continue;
+                    }
+                    context.domain_event_handler.handle_event(
+                        DomainEvent::MappingEnabledChangeRequested(
+                            MappingEnabledChangeRequestedEvent {
+                                compartment: m.compartment(),
+                                mapping_id: m.id(),
+                                is_enabled: self.is_enable,
+                            },
+                        ),
+                    );
+                }
+                context
+                    .control_context
+                    .instance_state
+                    .borrow_mut()
+                    .set_group_enabled(self.compartment, self.group_id, self.is_enable);
+                vec![]
+            }
+        }
+        let instruction = EnableGroupInstruction {
+            compartment: self.compartment,
+            group_id: self.group_id,
+            own_mapping_id: context.mapping_data.mapping_id,
+            is_enable,
+        };
+        Ok(Some(Box::new(instruction)))
+    }
+
+    fn is_available(&self, _: ControlContext) -> bool {
+        true
+    }
+
+    fn process_change_event(
+        &self,
+        evt: CompoundChangeEvent,
+        _: ControlContext,
+    ) -> (bool, Option<AbsoluteValue>) {
+        match evt {
+            CompoundChangeEvent::Instance(InstanceStateChanged::GroupEnabled {
+                compartment,
+                group_id,
+            }) if *compartment == self.compartment && *group_id == self.group_id => (true, None),
+            _ => (false, None),
+        }
+    }
+
+    fn text_value(&self, context: ControlContext) -> Option<String> {
+        Some(format_value_as_on_off(self.current_value(context)?.to_unit_value()).to_string())
+    }
+
+    fn reaper_target_type(&self) -> Option<ReaperTargetType> {
+        Some(ReaperTargetType::EnableGroup)
+    }
+}
+
+impl<'a> Target<'a> for EnableGroupTarget {
+    type Context = ControlContext<'a>;
+
+    fn current_value(&self, context: Self::Context) -> Option<AbsoluteValue> {
+        let is_enabled = context
+            .instance_state
+            .borrow()
+            .group_is_enabled(self.compartment, self.group_id);
+        let uv = if is_enabled {
+            UnitValue::MAX
+        } else {
+            UnitValue::MIN
+        };
+        Some(AbsoluteValue::Continuous(uv))
+    }
+
+    fn control_type(&self, context: Self::Context) -> ControlType {
+        self.control_type_and_character(context).0
+    }
+}
+
+pub const ENABLE_GROUP_TARGET: TargetTypeDef = TargetTypeDef {
+    name: "ReaLearn: Enable/disable group",
+    short_name: "Enable/disable group",
+    ..DEFAULT_TARGET
+};
@@ -0,0 +1,180 @@
+use crate::domain::{
+    convert_count_to_step_size, convert_discrete_to_unit_value, convert_unit_to_discrete_value,
+    CompoundChangeEvent, ControlContext, ExtendedProcessorContext, HitInstructionReturnValue,
+    InstanceStateChanged, MappingCompartment, MappingControlContext, RealearnTarget, ReaperTarget,
+    ReaperTargetType, TargetCharacter, TargetTypeDef, UnresolvedReaperTargetDef, DEFAULT_TARGET,
+};
+use helgoboss_learn::{
+    AbsoluteValue, ControlType, ControlValue, Fraction, NumericValue, Target, UnitValue,
+};
+
+pub const DEFAULT_FX_PARAMETER_BANK_SIZE: u32 = 8;
+
+/// Number of banks which can be reached via this target.
+///
+/// The target doesn't know which FX the paged mappings control, so it can't derive the number of
+/// banks from the parameter count. With a bank size of 8, this covers 2048 parameters.
+const BANK_COUNT: u32 = 256;
+
+#[derive(Debug)]
+pub struct UnresolvedFxParameterBankTarget {
+    pub bank_size: u32,
+}
+
+impl UnresolvedReaperTargetDef for UnresolvedFxParameterBankTarget {
+    fn resolve(
+        &self,
+        _: ExtendedProcessorContext,
+        _: MappingCompartment,
+    ) -> Result<Vec<ReaperTarget>, &'static str> {
+        if self.bank_size == 0 {
+            return Err("bank size must be greater than zero");
+        }
+        // Otherwise the offset of the last bank wouldn't fit into the parameter index range.
+        if (BANK_COUNT - 1).checked_mul(self.bank_size).is_none() {
+            return Err("bank size too large");
+        }
+        Ok(vec![ReaperTarget::FxParameterBank(FxParameterBankTarget {
+            bank_size: self.bank_size,
+        })])
+    }
+
+    fn can_be_affected_by_change_events(&self) -> bool {
+        false
+    }
+}
+
+/// Pages all FX parameter targets which address their parameter by index, e.g. so that 8 knobs
+/// mapped to parameters #1 to #8 control parameters #9 to #16 when switching to the next bank.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FxParameterBankTarget {
+    /// Number of parameters per bank, always greater than zero and small enough that the offset of
+    /// the last bank doesn't overflow.
+    pub bank_size: u32,
+}
+
+impl FxParameterBankTarget {
+    fn current_bank_index(&self, context: ControlContext) -> u32 {
+        let offset = context.instance_state.borrow().fx_parameter_bank_offset();
+        offset / self.bank_size
+    }
+}
+
+impl RealearnTarget for FxParameterBankTarget {
+    fn control_type_and_character(&self, _: ControlContext) -> (ControlType, TargetCharacter) {
+        (
+            ControlType::AbsoluteDiscrete {
+                atomic_step_size: convert_count_to_step_size(BANK_COUNT),
+            },
+            TargetCharacter::Discrete,
+        )
+    }
+
+    fn hit(
+        &mut self,
+        value: ControlValue,
+        context: MappingControlContext,
+    ) -> Result<HitInstructionReturnValue, &'static str> {
+        let bank_index = match value.to_absolute_value()? {
+            AbsoluteValue::Continuous(v) => convert_unit_to_discrete_value(v, BANK_COUNT),
+            AbsoluteValue::Discrete(f) => f.actual().min(BANK_COUNT - 1),
+        };
+        let offset = bank_index
+            .checked_mul(self.bank_size)
+            .ok_or("bank offset too large")?;
+        context
+            .control_context
+            .instance_state
+            .borrow_mut()
+            .set_fx_parameter_bank_offset(offset);
+        Ok(None)
+    }
+
+    fn parse_as_value(
+        &self,
+        text: &str,
+        context: ControlContext,
+    ) -> Result<UnitValue, &'static str> {
+        self.parse_value_from_discrete_value(text, context)
+    }
+
+    fn parse_as_step_size(
+        &self,
+        text: &str,
+        context: ControlContext,
+    ) -> Result<UnitValue, &'static str> {
+        self.parse_value_from_discrete_value(text, context)
+    }
+
+    fn convert_unit_value_to_discrete_value(
+        &self,
+        input: UnitValue,
+        _: ControlContext,
+    ) -> Result<u32, &'static str> {
+        Ok(convert_unit_to_discrete_value(input, BANK_COUNT))
+    }
+
+    fn convert_discrete_value_to_unit_value(
+        &self,
+        value: u32,
+        _: ControlContext,
+    ) -> Result<UnitValue, &'static str> {
+        Ok(convert_discrete_to_unit_value(value, BANK_COUNT))
+    }
+
+    fn is_available(&self, _: ControlContext) -> bool {
+        true
+    }
+
+    fn process_change_event(
+        &self,
+        evt: CompoundChangeEvent,
+        _: ControlContext,
+    ) -> (bool, Option<AbsoluteValue>) {
+        match evt {
+            CompoundChangeEvent::Instance(InstanceStateChanged::FxParameterBankOffset {
+                offset,
+            }) => {
+                let bank_index = (offset / self.bank_size).min(BANK_COUNT - 1);
+                let fraction = Fraction::new(bank_index, BANK_COUNT - 1);
+                (true, Some(AbsoluteValue::Discrete(fraction)))
+            }
+            _ => (false, None),
+        }
+    }
+
+    fn text_value(&self, context: ControlContext) -> Option<String> {
+        Some(format!("Bank {}", self.current_bank_index(context) + 1))
+    }
+
+    fn numeric_value(&self, context: ControlContext) -> Option<NumericValue> {
+        Some(NumericValue::Discrete(
+            self.current_bank_index(context) as i32 + 1,
+        ))
+    }
+
+    fn reaper_target_type(&self) -> Option<ReaperTargetType> {
+        Some(ReaperTargetType::FxParameterBank)
+    }
+}
+
+impl<'a> Target<'a> for FxParameterBankTarget {
+    type Context = ControlContext<'a>;
+
+    fn current_value(&self, context: Self::Context) -> Option<AbsoluteValue> {
+        let bank_index = self.current_bank_index(context).min(BANK_COUNT - 1);
+        let fraction = Fraction::new(bank_index, BANK_COUNT - 1);
+        Some(AbsoluteValue::Discrete(fraction))
+    }
+
+    fn control_type(&self, context: Self::Context) -> ControlType {
+        self.control_type_and_character(context).0
+    }
+}
+
+pub const FX_PARAMETER_BANK_TARGET: TargetTypeDef = TargetTypeDef {
+    name: "FX: Navigate between parameter banks",
+    short_name: "Parameter bank",
+    hint: "Pages FX parameter targets by index",
+    ..DEFAULT_TARGET
+};
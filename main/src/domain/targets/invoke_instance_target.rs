@@ -0,0 +1,121 @@
+use crate::domain::{
+    ControlContext, ExtendedProcessorContext, HitInstructionReturnValue, InstanceMessage,
+    MappingCompartment, MappingControlContext, RealearnTarget, ReaperTarget, ReaperTargetType,
+    SendInstanceMessageArgs, TagScope, TargetCharacter, TargetTypeDef, UnresolvedReaperTargetDef,
+    DEFAULT_TARGET,
+};
+use helgoboss_learn::{AbsoluteValue, ControlType, ControlValue, Target};
+
+/// Maximum number of times an instance message is passed on from one instance to the next.
+const MAX_INSTANCE_MESSAGE_HOP_COUNT: u32 = 8;
+
+/// Decides which instances receive the messages of an "Invoke instance" target.
+#[derive(Clone, Debug, PartialEq)]
+pub enum InstanceMessageRecipients {
+    /// All other instances in the same project whose tags match the scope (all other instances
+    /// in the same project if the scope doesn't contain any tags).
+    Tagged(TagScope),
+    /// The instance with the given session ID.
+    Session(String),
+}
+
+#[derive(Debug)]
+pub struct UnresolvedInvokeInstanceTarget {
+    pub recipients: InstanceMessageRecipients,
+    pub message_name: String,
+}
+
+impl UnresolvedReaperTargetDef for UnresolvedInvokeInstanceTarget {
+    fn resolve(
+        &self,
+        _: ExtendedProcessorContext,
+        _: MappingCompartment,
+    ) -> Result<Vec<ReaperTarget>, &'static str> {
+        Ok(vec![ReaperTarget::InvokeInstance(InvokeInstanceTarget {
+            recipients: self.recipients.clone(),
+            message_name: self.message_name.clone(),
+        })])
+    }
+}
+
+/// Sends a named message with the control value to other instances.
+///
+/// The receiving instances process it like any other incoming source value, so mappings with a
+/// matching "Instance message" source get controlled.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InvokeInstanceTarget {
+    pub recipients: InstanceMessageRecipients,
+    pub message_name: String,
+}
+
+impl RealearnTarget for InvokeInstanceTarget {
+    fn control_type_and_character(&self, _: ControlContext) -> (ControlType, TargetCharacter) {
+        (
+            ControlType::AbsoluteContinuousRetriggerable,
+            TargetCharacter::Continuous,
+        )
+    }
+
+    fn hit(
+        &mut self,
+        value: ControlValue,
+        context: MappingControlContext,
+    ) -> Result<HitInstructionReturnValue, &'static str> {
+        if self.message_name.is_empty() {
+            return Err("no message name given");
+        }
+        let hop_count = match context.control_context.instance_message_hop_count {
+            None => 0,
+            Some(c) => c + 1,
+        };
+        if hop_count > MAX_INSTANCE_MESSAGE_HOP_COUNT {
+            return Err("instance message went through too many instances");
+        }
+        let args = SendInstanceMessageArgs {
+            initiator_instance_id: *context.control_context.instance_id,
+            initiator_project: context.control_context.processor_context.project(),
+            recipients: &self.recipients,
+            message: InstanceMessage {
+                name: self.message_name.clone(),
+                value: value.to_unit_value()?,
+                hop_count,
+            },
+        };
+        context
+            .control_context
+            .instance_container
+            .send_instance_message(args)?;
+        Ok(None)
+    }
+
+    fn can_report_current_value(&self) -> bool {
+        false
+    }
+
+    fn is_available(&self, _: ControlContext) -> bool {
+        true
+    }
+
+    fn reaper_target_type(&self) -> Option<ReaperTargetType> {
+        Some(ReaperTargetType::InvokeInstance)
+    }
+}
+
+impl<'a> Target<'a> for InvokeInstanceTarget {
+    type Context = ControlContext<'a>;
+
+    fn current_value(&self, _: Self::Context) -> Option<AbsoluteValue> {
+        None
+    }
+
+    fn control_type(&self, context: Self::Context) -> ControlType {
+        self.control_type_and_character(context).0
+    }
+}
+
+pub const INVOKE_INSTANCE_TARGET: TargetTypeDef = TargetTypeDef {
+    name: "ReaLearn: Invoke instance",
+    short_name: "Invoke instance",
+    supports_tags: true,
+    ..DEFAULT_TARGET
+};
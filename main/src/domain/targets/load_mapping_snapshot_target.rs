@@ -1,15 +1,20 @@
 use crate::domain::{
-    ControlContext, ExtendedProcessorContext, HitInstruction, HitInstructionContext,
-    HitInstructionReturnValue, MappingCompartment, MappingControlContext, MappingControlResult,
-    RealearnTarget, ReaperTarget, ReaperTargetType, TagScope, TargetCharacter, TargetTypeDef,
-    UnresolvedReaperTargetDef, DEFAULT_TARGET,
+    ControlContext, DomainEvent, DomainEventHandler, ExtendedProcessorContext, HitInstruction,
+    HitInstructionContext, HitInstructionReturnValue, MainMapping, MappingCompartment,
+    MappingControlContext, MappingControlResult, MappingSnapshotFadeEntry, RealearnTarget,
+    ReaperTarget, ReaperTargetType, TagScope, TargetCharacter, TargetTypeDef,
+    TargetValueChangeOrigin, TargetValueChangedEvent, UnresolvedReaperTargetDef, DEFAULT_TARGET,
 };
 use helgoboss_learn::{AbsoluteValue, ControlType, ControlValue, Target};
+use std::time::{Duration, Instant};
 
 #[derive(Debug)]
 pub struct UnresolvedLoadMappingSnapshotTarget {
     pub scope: TagScope,
     pub active_mappings_only: bool,
+    /// `None` means the initial snapshot (target values when the mappings were loaded).
+    pub snapshot_id: Option<String>,
+    pub fade_time: Duration,
 }
 
 impl UnresolvedReaperTargetDef for UnresolvedLoadMappingSnapshotTarget {
@@ -22,6 +27,8 @@ impl UnresolvedReaperTargetDef for UnresolvedLoadMappingSnapshotTarget {
             LoadMappingSnapshotTarget {
                 scope: self.scope.clone(),
                 active_mappings_only: self.active_mappings_only,
+                snapshot_id: self.snapshot_id.clone(),
+                fade_time: self.fade_time,
             },
         )])
     }
@@ -31,6 +38,8 @@ impl UnresolvedReaperTargetDef for UnresolvedLoadMappingSnapshotTarget {
 pub struct LoadMappingSnapshotTarget {
     pub scope: TagScope,
     pub active_mappings_only: bool,
+    pub snapshot_id: Option<String>,
+    pub fade_time: Duration,
 }
 
 impl RealearnTarget for LoadMappingSnapshotTarget {
@@ -56,6 +65,8 @@ impl RealearnTarget for LoadMappingSnapshotTarget {
         struct LoadMappingSnapshotInstruction {
             scope: TagScope,
             active_mappings_only: bool,
+            snapshot_id: Option<String>,
+            fade_time: Duration,
         }
         impl HitInstruction for LoadMappingSnapshotInstruction {
             fn execute(
@@ -63,6 +74,25 @@ impl RealearnTarget for LoadMappingSnapshotTarget {
                 context: HitInstructionContext,
             ) -> Vec<MappingControlResult> {
                 let mut control_results = vec![];
+                let mut fade_entries = vec![];
+                let compartment = match context.mappings.values().next() {
+                    None => return control_results,
+                    Some(m) => m.compartment(),
+                };
+                // Cloned because controlling targets might need to borrow the instance state.
+                let snapshot = match &self.snapshot_id {
+                    None => None,
+                    Some(id) => {
+                        let instance_state = context.control_context.instance_state.borrow();
+                        match instance_state
+                            .mapping_snapshots()
+                            .find_snapshot(compartment, id)
+                        {
+                            None => return control_results,
+                            Some(s) => Some(s.clone()),
+                        }
+                    }
+                };
                 for m in context.mappings.values_mut() {
                     if !m.control_is_enabled() {
                         // If "Control disabled", it doesn't make much sense because then it means
@@ -76,21 +106,46 @@ impl RealearnTarget for LoadMappingSnapshotTarget {
                     if self.active_mappings_only && !m.is_effectively_on() {
                         continue;
                     }
-                    if let Some(inital_value) = m.initial_target_value() {
-                        context
-                            .domain_event_handler
-                            .notify_mapping_matched(m.compartment(), m.id());
-                        let res = m.control_from_target_directly(
-                            context.control_context,
-                            context.logger,
-                            context.processor_context,
-                            inital_value,
-                        );
-                        if res.successful {
-                            m.update_last_non_performance_target_value(inital_value);
+                    let snapshot_value = match &snapshot {
+                        None => m.initial_target_value(),
+                        Some(s) => s.value(m.id()),
+                    };
+                    let snapshot_value = match snapshot_value {
+                        None => continue,
+                        Some(v) => v,
+                    };
+                    if self.fade_time > Duration::from_millis(0) {
+                        if let Some(current_value) =
+                            m.current_aggregated_target_value(context.control_context)
+                        {
+                            context
+                                .domain_event_handler
+                                .notify_mapping_matched(m.compartment(), m.id());
+                            fade_entries.push(MappingSnapshotFadeEntry {
+                                mapping_id: m.id(),
+                                from: current_value,
+                                to: snapshot_value,
+                            });
+                            continue;
                         }
-                        control_results.push(res);
                     }
+                    let res = load_mapping_snapshot_value(
+                        m,
+                        snapshot_value,
+                        context.control_context,
+                        context.logger,
+                        context.processor_context,
+                        context.domain_event_handler,
+                    );
+                    control_results.push(res);
+                }
+                if !fade_entries.is_empty() {
+                    context
+                        .control_context
+                        .instance_state
+                        .borrow_mut()
+                        .mapping_snapshots_mut()
+                        .start_fade(compartment, fade_entries, self.fade_time, Instant::now());
                 }
                 control_results
             }
@@ -100,6 +155,8 @@ impl RealearnTarget for LoadMappingSnapshotTarget {
             // every few milliseconds. No need to use a ref to this target.
             scope: self.scope.clone(),
             active_mappings_only: self.active_mappings_only,
+            snapshot_id: self.snapshot_id.clone(),
+            fade_time: self.fade_time,
         };
         Ok(Some(Box::new(instruction)))
     }
@@ -125,6 +182,36 @@ impl<'a> Target<'a> for LoadMappingSnapshotTarget {
     }
 }
 
+/// Sets the target of the given mapping to the given snapshot value.
+///
+/// Also used by the main processor for advancing snapshot fades.
+pub fn load_mapping_snapshot_value(
+    m: &mut MainMapping,
+    value: AbsoluteValue,
+    control_context: ControlContext,
+    logger: &slog::Logger,
+    processor_context: ExtendedProcessorContext,
+    domain_event_handler: &dyn DomainEventHandler,
+) -> MappingControlResult {
+    domain_event_handler.notify_mapping_matched(m.compartment(), m.id());
+    let res = m.control_from_target_directly(control_context, logger, processor_context, value);
+    if res.successful {
+        m.update_last_non_performance_target_value(value);
+    }
+    if let Some(new_value) = res.new_target_value {
+        domain_event_handler.handle_event(DomainEvent::TargetValueChanged(
+            TargetValueChangedEvent {
+                compartment: m.compartment(),
+                mapping_id: m.id(),
+                targets: m.targets(),
+                new_value,
+                origin: TargetValueChangeOrigin::SnapshotLoad,
+            },
+        ));
+    }
+    res
+}
+
 pub const LOAD_MAPPING_SNAPSHOT_TARGET: TargetTypeDef = TargetTypeDef {
     name: "ReaLearn: Load mapping snapshot",
     short_name: "Load mapping snapshot",
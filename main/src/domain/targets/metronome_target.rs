@@ -0,0 +1,352 @@
+use crate::domain::ui_util::{
+    convert_bool_to_unit_value, format_value_as_db, format_value_as_db_without_unit,
+    parse_unit_value_from_percentage, parse_value_from_db, volume_unit_value,
+};
+use crate::domain::{
+    convert_count_to_step_size, format_value_as_on_off, ControlContext, ExtendedProcessorContext,
+    FeedbackResolution, HitInstructionReturnValue, MappingCompartment, MappingControlContext,
+    RealearnTarget, ReaperTarget, ReaperTargetType, TargetCharacter, TargetTypeDef,
+    UnresolvedReaperTargetDef, AUTOMATIC_FEEDBACK_VIA_POLLING_ONLY, DEFAULT_TARGET,
+};
+use derive_more::Display;
+use enum_iterator::IntoEnumIterator;
+use helgoboss_learn::{
+    AbsoluteValue, ControlType, ControlValue, Fraction, NumericValue, Target, UnitValue,
+};
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+use reaper_high::{Project, Reaper, Volume};
+use reaper_medium::ReaperVolumeValue;
+use serde::{Deserialize, Serialize};
+use std::ptr::NonNull;
+
+/// Metronome flags (int).
+const METRONOME_FLAGS_VAR: &str = "projmetroen";
+/// Flag within the metronome flags which enables the metronome.
+const METRONOME_ENABLED_FLAG: i32 = 1;
+/// Flag within the metronome flags which enables count-in before recording.
+const COUNT_IN_ENABLED_FLAG: i32 = 16;
+/// Metronome volume of the first beat (double, linear).
+const PRIMARY_BEAT_VOLUME_VAR: &str = "projmetrov1";
+/// Metronome volume of the other beats (double, linear).
+const SECONDARY_BEAT_VOLUME_VAR: &str = "projmetrov2";
+/// Count-in/pre-roll length in measures (double).
+const COUNT_IN_LENGTH_VAR: &str = "prerollmeas";
+
+const MAX_COUNT_IN_MEASURES: u32 = 16;
+
+#[derive(
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    Debug,
+    Serialize,
+    Deserialize,
+    IntoEnumIterator,
+    TryFromPrimitive,
+    IntoPrimitive,
+    Display,
+)]
+#[repr(usize)]
+pub enum MetronomeParameter {
+    #[serde(rename = "enable")]
+    #[display(fmt = "Metronome on/off")]
+    Enable,
+    #[serde(rename = "volume")]
+    #[display(fmt = "Metronome volume")]
+    Volume,
+    #[serde(rename = "countIn")]
+    #[display(fmt = "Count-in on/off")]
+    CountIn,
+    #[serde(rename = "countInLength")]
+    #[display(fmt = "Count-in length")]
+    CountInLength,
+}
+
+impl Default for MetronomeParameter {
+    fn default() -> Self {
+        MetronomeParameter::Enable
+    }
+}
+
+#[derive(Debug)]
+pub struct UnresolvedMetronomeTarget {
+    pub parameter: MetronomeParameter,
+}
+
+impl UnresolvedReaperTargetDef for UnresolvedMetronomeTarget {
+    fn resolve(
+        &self,
+        context: ExtendedProcessorContext,
+        _: MappingCompartment,
+    ) -> Result<Vec<ReaperTarget>, &'static str> {
+        Ok(vec![ReaperTarget::Metronome(MetronomeTarget {
+            project: context.context().project_or_current_project(),
+            parameter: self.parameter,
+        })])
+    }
+
+    fn feedback_resolution(&self) -> Option<FeedbackResolution> {
+        // REAPER doesn't notify us about metronome setting changes.
+        Some(FeedbackResolution::High)
+    }
+}
+
+/// Controls the metronome settings.
+///
+/// The settings are accessed as config variables, which always refer to the current project.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MetronomeTarget {
+    pub project: Project,
+    pub parameter: MetronomeParameter,
+}
+
+impl MetronomeTarget {
+    fn volume(&self) -> Option<Volume> {
+        let v = read_config_var::<f64>(PRIMARY_BEAT_VOLUME_VAR)?;
+        Some(Volume::from_reaper_value(ReaperVolumeValue::new(v)))
+    }
+
+    fn set_volume(&self, volume: Volume) -> Result<(), &'static str> {
+        let old = read_config_var::<f64>(PRIMARY_BEAT_VOLUME_VAR)
+            .ok_or("metronome volume not available")?;
+        let new = volume.reaper_value().get();
+        write_config_var(PRIMARY_BEAT_VOLUME_VAR, new)?;
+        // Keep the accent of the first beat by scaling the other beats accordingly.
+        if let Some(secondary) = read_config_var::<f64>(SECONDARY_BEAT_VOLUME_VAR) {
+            let new_secondary = if old > 0.0 {
+                secondary * new / old
+            } else {
+                new
+            };
+            write_config_var(SECONDARY_BEAT_VOLUME_VAR, new_secondary)?;
+        }
+        Ok(())
+    }
+
+    fn count_in_measures(&self) -> Option<u32> {
+        let measures = read_config_var::<f64>(COUNT_IN_LENGTH_VAR)?;
+        Some((measures.round() as u32).clamp(1, MAX_COUNT_IN_MEASURES))
+    }
+}
+
+impl RealearnTarget for MetronomeTarget {
+    fn control_type_and_character(&self, _: ControlContext) -> (ControlType, TargetCharacter) {
+        use MetronomeParameter::*;
+        match self.parameter {
+            Enable | CountIn => (ControlType::AbsoluteContinuous, TargetCharacter::Switch),
+            Volume => (ControlType::AbsoluteContinuous, TargetCharacter::Continuous),
+            CountInLength => (
+                ControlType::AbsoluteDiscrete {
+                    atomic_step_size: convert_count_to_step_size(MAX_COUNT_IN_MEASURES),
+                },
+                TargetCharacter::Discrete,
+            ),
+        }
+    }
+
+    fn parse_as_value(
+        &self,
+        text: &str,
+        context: ControlContext,
+    ) -> Result<UnitValue, &'static str> {
+        use MetronomeParameter::*;
+        match self.parameter {
+            Volume => parse_value_from_db(text),
+            CountInLength => self.parse_value_from_discrete_value(text, context),
+            Enable | CountIn => parse_unit_value_from_percentage(text),
+        }
+    }
+
+    fn convert_unit_value_to_discrete_value(
+        &self,
+        input: UnitValue,
+        _: ControlContext,
+    ) -> Result<u32, &'static str> {
+        if self.parameter != MetronomeParameter::CountInLength {
+            return Err("not supported");
+        }
+        Ok(convert_unit_value_to_measures(input))
+    }
+
+    fn convert_discrete_value_to_unit_value(
+        &self,
+        value: u32,
+        _: ControlContext,
+    ) -> Result<UnitValue, &'static str> {
+        if self.parameter != MetronomeParameter::CountInLength {
+            return Err("not supported");
+        }
+        if !(1..=MAX_COUNT_IN_MEASURES).contains(&value) {
+            return Err("invalid number of measures");
+        }
+        Ok(measures_unit_value(value))
+    }
+
+    fn format_value_without_unit(&self, value: UnitValue, context: ControlContext) -> String {
+        match self.parameter {
+            MetronomeParameter::Volume => format_value_as_db_without_unit(value),
+            _ => self.format_as_discrete_or_percentage(value, context),
+        }
+    }
+
+    fn value_unit(&self, _: ControlContext) -> &'static str {
+        use MetronomeParameter::*;
+        match self.parameter {
+            Enable | CountIn => "%",
+            Volume => "dB",
+            CountInLength => "",
+        }
+    }
+
+    fn format_value(&self, value: UnitValue, _: ControlContext) -> String {
+        use MetronomeParameter::*;
+        match self.parameter {
+            Enable | CountIn => format_value_as_on_off(value).to_string(),
+            Volume => format_value_as_db(value),
+            CountInLength => format_measures(convert_unit_value_to_measures(value)),
+        }
+    }
+
+    fn hit(
+        &mut self,
+        value: ControlValue,
+        _: MappingControlContext,
+    ) -> Result<HitInstructionReturnValue, &'static str> {
+        use MetronomeParameter::*;
+        match self.parameter {
+            Enable => set_flag(METRONOME_ENABLED_FLAG, !value.to_unit_value()?.is_zero())?,
+            CountIn => set_flag(COUNT_IN_ENABLED_FLAG, !value.to_unit_value()?.is_zero())?,
+            Volume => {
+                // Fully qualified because `Volume` refers to the parameter here.
+                let volume = reaper_high::Volume::try_from_soft_normalized_value(
+                    value.to_unit_value()?.get(),
+                );
+                self.set_volume(volume.unwrap_or(reaper_high::Volume::MIN))?;
+            }
+            CountInLength => {
+                let measures = match value.to_absolute_value()? {
+                    AbsoluteValue::Continuous(v) => convert_unit_value_to_measures(v),
+                    AbsoluteValue::Discrete(f) => (f.actual() + 1).min(MAX_COUNT_IN_MEASURES),
+                };
+                write_config_var(COUNT_IN_LENGTH_VAR, measures as f64)?;
+            }
+        }
+        Ok(None)
+    }
+
+    fn is_available(&self, _: ControlContext) -> bool {
+        self.project.is_available()
+    }
+
+    fn project(&self) -> Option<Project> {
+        Some(self.project)
+    }
+
+    fn text_value(&self, context: ControlContext) -> Option<String> {
+        use MetronomeParameter::*;
+        let text = match self.parameter {
+            Enable | CountIn => {
+                format_value_as_on_off(self.current_value(context)?.to_unit_value()).to_string()
+            }
+            Volume => self.volume()?.to_string(),
+            CountInLength => format_measures(self.count_in_measures()?),
+        };
+        Some(text)
+    }
+
+    fn numeric_value(&self, _: ControlContext) -> Option<NumericValue> {
+        use MetronomeParameter::*;
+        match self.parameter {
+            Enable | CountIn => None,
+            Volume => Some(NumericValue::Decimal(self.volume()?.db().get())),
+            CountInLength => Some(NumericValue::Discrete(self.count_in_measures()? as i32)),
+        }
+    }
+
+    fn reaper_target_type(&self) -> Option<ReaperTargetType> {
+        Some(ReaperTargetType::Metronome)
+    }
+}
+
+impl<'a> Target<'a> for MetronomeTarget {
+    type Context = ControlContext<'a>;
+
+    fn current_value(&self, _: Self::Context) -> Option<AbsoluteValue> {
+        use MetronomeParameter::*;
+        let value = match self.parameter {
+            Enable => AbsoluteValue::Continuous(convert_bool_to_unit_value(flag_is_set(
+                METRONOME_ENABLED_FLAG,
+            )?)),
+            CountIn => AbsoluteValue::Continuous(convert_bool_to_unit_value(flag_is_set(
+                COUNT_IN_ENABLED_FLAG,
+            )?)),
+            Volume => AbsoluteValue::Continuous(volume_unit_value(self.volume()?)),
+            CountInLength => AbsoluteValue::Discrete(Fraction::new(
+                self.count_in_measures()? - 1,
+                MAX_COUNT_IN_MEASURES - 1,
+            )),
+        };
+        Some(value)
+    }
+
+    fn control_type(&self, context: Self::Context) -> ControlType {
+        self.control_type_and_character(context).0
+    }
+}
+
+fn convert_unit_value_to_measures(value: UnitValue) -> u32 {
+    (value.get() * (MAX_COUNT_IN_MEASURES - 1) as f64).round() as u32 + 1
+}
+
+fn measures_unit_value(measures: u32) -> UnitValue {
+    UnitValue::new_clamped((measures - 1) as f64 / (MAX_COUNT_IN_MEASURES - 1) as f64)
+}
+
+fn format_measures(measures: u32) -> String {
+    if measures == 1 {
+        "1 measure".to_string()
+    } else {
+        format!("{} measures", measures)
+    }
+}
+
+fn flag_is_set(flag: i32) -> Option<bool> {
+    let flags = read_config_var::<i32>(METRONOME_FLAGS_VAR)?;
+    Some(flags & flag != 0)
+}
+
+fn set_flag(flag: i32, on: bool) -> Result<(), &'static str> {
+    let flags = read_config_var::<i32>(METRONOME_FLAGS_VAR).ok_or("metronome not available")?;
+    let new_flags = if on { flags | flag } else { flags & !flag };
+    write_config_var(METRONOME_FLAGS_VAR, new_flags)
+}
+
+fn config_var_ptr<T>(name: &str) -> Option<NonNull<T>> {
+    let res = Reaper::get().medium_reaper().get_config_var(name)?;
+    if res.size as usize != std::mem::size_of::<T>() {
+        // Shouldn't be.
+        return None;
+    }
+    Some(res.value.cast())
+}
+
+fn read_config_var<T: Copy>(name: &str) -> Option<T> {
+    let ptr = config_var_ptr::<T>(name)?;
+    Some(unsafe { *ptr.as_ptr() })
+}
+
+fn write_config_var<T: Copy>(name: &str, value: T) -> Result<(), &'static str> {
+    let ptr = config_var_ptr::<T>(name).ok_or("config variable not available")?;
+    unsafe {
+        *ptr.as_ptr() = value;
+    }
+    Ok(())
+}
+
+pub const METRONOME_TARGET: TargetTypeDef = TargetTypeDef {
+    name: "Project: Metronome",
+    short_name: "Metronome",
+    hint: AUTOMATIC_FEEDBACK_VIA_POLLING_ONLY,
+    ..DEFAULT_TARGET
+};
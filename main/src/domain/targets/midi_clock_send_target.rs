@@ -0,0 +1,130 @@
+use crate::domain::ui_util::convert_bool_to_unit_value;
+use crate::domain::{
+    format_value_as_on_off, BackboneState, ControlContext, ExtendedProcessorContext,
+    FeedbackOutput, FeedbackResolution, HitInstructionReturnValue, MappingCompartment,
+    MappingControlContext, MidiClockSettings, MidiDestination, MtcFrameRate, RealearnTarget,
+    ReaperTarget, ReaperTargetType, TargetCharacter, TargetTypeDef, UnresolvedReaperTargetDef,
+    AUTOMATIC_FEEDBACK_VIA_POLLING_ONLY, DEFAULT_TARGET,
+};
+use helgoboss_learn::{AbsoluteValue, ControlType, ControlValue, Target, UnitValue};
+use reaper_medium::MidiOutputDeviceId;
+
+#[derive(Debug)]
+pub struct UnresolvedMidiClockSendTarget {
+    pub output: Option<MidiOutputDeviceId>,
+    pub mtc_frame_rate: Option<MtcFrameRate>,
+}
+
+impl UnresolvedReaperTargetDef for UnresolvedMidiClockSendTarget {
+    fn resolve(
+        &self,
+        _: ExtendedProcessorContext,
+        _: MappingCompartment,
+    ) -> Result<Vec<ReaperTarget>, &'static str> {
+        Ok(vec![ReaperTarget::SendMidiClock(MidiClockSendTarget {
+            output: self.output,
+            settings: MidiClockSettings {
+                mtc_frame_rate: self.mtc_frame_rate,
+            },
+        })])
+    }
+
+    fn feedback_resolution(&self) -> Option<FeedbackResolution> {
+        // Another instance might switch the clock of the same device.
+        Some(FeedbackResolution::High)
+    }
+}
+
+/// Switches generation of MIDI clock (and optionally MIDI time code) on a MIDI output device.
+///
+/// The clock itself is generated in the audio hook and follows the transport of the current
+/// project, so hardware sequencers can be slaved to REAPER.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MidiClockSendTarget {
+    /// `None` means the feedback output.
+    pub output: Option<MidiOutputDeviceId>,
+    pub settings: MidiClockSettings,
+}
+
+impl MidiClockSendTarget {
+    fn resolve_device(&self, context: ControlContext) -> Result<MidiOutputDeviceId, &'static str> {
+        if let Some(dev_id) = self.output {
+            return Ok(dev_id);
+        }
+        match context.feedback_output.ok_or("no feedback output set")? {
+            FeedbackOutput::Midi(MidiDestination::Device(dev_id)) => Ok(dev_id),
+            _ => Err("feedback output is not a MIDI device"),
+        }
+    }
+
+    fn is_on(&self, context: ControlContext) -> Option<bool> {
+        let dev_id = self.resolve_device(context).ok()?;
+        Some(BackboneState::get().midi_clock_output(dev_id).is_some())
+    }
+}
+
+impl RealearnTarget for MidiClockSendTarget {
+    fn control_type_and_character(&self, _: ControlContext) -> (ControlType, TargetCharacter) {
+        (ControlType::AbsoluteContinuous, TargetCharacter::Switch)
+    }
+
+    fn format_value(&self, value: UnitValue, _: ControlContext) -> String {
+        format_value_as_on_off(value).to_string()
+    }
+
+    fn hit(
+        &mut self,
+        value: ControlValue,
+        context: MappingControlContext,
+    ) -> Result<HitInstructionReturnValue, &'static str> {
+        let on = !value.to_unit_value()?.is_zero();
+        let settings = if on { Some(self.settings) } else { None };
+        let owner = context.mapping_data.mapping_id;
+        let control_context = context.control_context;
+        let dev_id = self.resolve_device(control_context)?;
+        control_context
+            .instance_state
+            .borrow_mut()
+            .midi_clock_outputs_mut()
+            .set(
+                owner,
+                *control_context.instance_id,
+                dev_id,
+                settings,
+                control_context.feedback_audio_hook_task_sender,
+            )?;
+        Ok(None)
+    }
+
+    fn is_available(&self, context: ControlContext) -> bool {
+        self.resolve_device(context).is_ok()
+    }
+
+    fn text_value(&self, context: ControlContext) -> Option<String> {
+        Some(format_value_as_on_off(self.current_value(context)?.to_unit_value()).to_string())
+    }
+
+    fn reaper_target_type(&self) -> Option<ReaperTargetType> {
+        Some(ReaperTargetType::SendMidiClock)
+    }
+}
+
+impl<'a> Target<'a> for MidiClockSendTarget {
+    type Context = ControlContext<'a>;
+
+    fn current_value(&self, context: ControlContext) -> Option<AbsoluteValue> {
+        let is_on = self.is_on(context)?;
+        Some(AbsoluteValue::Continuous(convert_bool_to_unit_value(is_on)))
+    }
+
+    fn control_type(&self, context: Self::Context) -> ControlType {
+        self.control_type_and_character(context).0
+    }
+}
+
+pub const MIDI_CLOCK_SEND_TARGET: TargetTypeDef = TargetTypeDef {
+    name: "MIDI: Send clock",
+    short_name: "Send clock",
+    hint: AUTOMATIC_FEEDBACK_VIA_POLLING_ONLY,
+    ..DEFAULT_TARGET
+};
@@ -0,0 +1,99 @@
+use crate::domain::ui_util::OutputReason;
+use crate::domain::{
+    ControlContext, ExtendedProcessorContext, FeedbackOutput, HitInstructionReturnValue,
+    MappingCompartment, MappingControlContext, MidiDestination, RealearnTarget, ReaperTarget,
+    ReaperTargetType, TargetCharacter, TargetTypeDef, UnresolvedReaperTargetDef, DEFAULT_TARGET,
+};
+use helgoboss_learn::{AbsoluteValue, ControlType, ControlValue, Target, UnitValue};
+
+#[derive(Debug)]
+pub struct UnresolvedMidiMacroSendTarget {
+    pub macro_name: String,
+}
+
+impl UnresolvedReaperTargetDef for UnresolvedMidiMacroSendTarget {
+    fn resolve(
+        &self,
+        _: ExtendedProcessorContext,
+        _: MappingCompartment,
+    ) -> Result<Vec<ReaperTarget>, &'static str> {
+        Ok(vec![ReaperTarget::SendMidiMacro(MidiMacroSendTarget {
+            macro_name: self.macro_name.clone(),
+        })])
+    }
+}
+
+/// Sends a MIDI macro of the active controller preset to the feedback output.
+///
+/// The macro is looked up by name when the target is hit, so it always reflects the controller
+/// preset which is active at that time.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MidiMacroSendTarget {
+    pub macro_name: String,
+}
+
+impl RealearnTarget for MidiMacroSendTarget {
+    fn control_type_and_character(&self, _: ControlContext) -> (ControlType, TargetCharacter) {
+        (
+            ControlType::AbsoluteContinuousRetriggerable,
+            TargetCharacter::Trigger,
+        )
+    }
+
+    fn format_value(&self, _: UnitValue, _: ControlContext) -> String {
+        "".to_owned()
+    }
+
+    fn hit(
+        &mut self,
+        value: ControlValue,
+        context: MappingControlContext,
+    ) -> Result<HitInstructionReturnValue, &'static str> {
+        if value.to_unit_value()?.is_zero() {
+            return Ok(None);
+        }
+        let context = context.control_context;
+        let feedback_output = context.feedback_output.ok_or("no feedback output set")?;
+        let dev_id = match feedback_output {
+            FeedbackOutput::Midi(MidiDestination::Device(dev_id)) => dev_id,
+            _ => return Err("feedback output is not a MIDI device"),
+        };
+        let events = context
+            .instance_state
+            .borrow()
+            .midi_macros()
+            .get(&self.macro_name)
+            .ok_or("MIDI macro not defined in controller preset")?
+            .to_vec();
+        context.send_raw_midi(OutputReason::Target, dev_id, events);
+        Ok(None)
+    }
+
+    fn is_available(&self, _: ControlContext) -> bool {
+        true
+    }
+
+    fn reaper_target_type(&self) -> Option<ReaperTargetType> {
+        Some(ReaperTargetType::SendMidiMacro)
+    }
+}
+
+impl<'a> Target<'a> for MidiMacroSendTarget {
+    type Context = ControlContext<'a>;
+
+    fn current_value(&self, _: Self::Context) -> Option<AbsoluteValue> {
+        None
+    }
+
+    fn control_type(&self, context: Self::Context) -> ControlType {
+        self.control_type_and_character(context).0
+    }
+}
+
+pub const MIDI_MACRO_SEND_TARGET: TargetTypeDef = TargetTypeDef {
+    name: "MIDI: Send macro",
+    short_name: "Send MIDI macro",
+    hint: "Macro must be defined in controller preset",
+    supports_feedback: false,
+    ..DEFAULT_TARGET
+};
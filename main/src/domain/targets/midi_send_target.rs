@@ -1,19 +1,25 @@
 use crate::domain::ui_util::OutputReason;
 use crate::domain::{
-    ControlContext, ExtendedProcessorContext, FeedbackOutput, HitInstructionReturnValue,
-    MappingCompartment, MappingControlContext, MidiDestination, RealTimeReaperTarget,
-    RealearnTarget, ReaperTarget, ReaperTargetType, SendMidiDestination, TargetCharacter,
-    TargetTypeDef, UnresolvedReaperTargetDef, DEFAULT_TARGET,
+    root_note, Arpeggiator, ArpeggiatorSettings, ChordShape, ControlContext,
+    ExtendedProcessorContext, FeedbackOutput, HeldChord, HitInstructionReturnValue,
+    MappingCompartment, MappingControlContext, MidiDestination, RawMidiSequence,
+    RealTimeReaperTarget, RealearnTarget, ReaperTarget, ReaperTargetType, ScaleQuantizer,
+    SendMidiDestination, TargetCharacter, TargetTypeDef, UnresolvedReaperTargetDef, DEFAULT_TARGET,
 };
 use helgoboss_learn::{
-    AbsoluteValue, ControlType, ControlValue, Fraction, RawMidiPattern, Target, UnitValue,
+    AbsoluteValue, ControlType, ControlValue, Fraction, RawMidiEvent, RawMidiPattern, Target,
+    UnitValue,
 };
+use reaper_medium::MidiOutputDeviceId;
 use std::convert::TryInto;
+use std::time::{Duration, Instant};
 
 #[derive(Debug)]
 pub struct UnresolvedMidiSendTarget {
-    pub pattern: RawMidiPattern,
+    pub sequence: RawMidiSequence,
     pub destination: SendMidiDestination,
+    pub chord: Option<ChordShape>,
+    pub arpeggiator: Option<ArpeggiatorSettings>,
 }
 
 impl UnresolvedReaperTargetDef for UnresolvedMidiSendTarget {
@@ -22,9 +28,15 @@ impl UnresolvedReaperTargetDef for UnresolvedMidiSendTarget {
         _: ExtendedProcessorContext,
         _: MappingCompartment,
     ) -> Result<Vec<ReaperTarget>, &'static str> {
+        if self.arpeggiator.is_some() && self.destination == SendMidiDestination::FxOutput {
+            // The arpeggiator is driven from the main thread, which can't send to the FX output.
+            return Err("arpeggiator can't send to FX output");
+        }
         Ok(vec![ReaperTarget::SendMidi(MidiSendTarget::new(
-            self.pattern.clone(),
+            self.sequence.clone(),
             self.destination,
+            self.chord,
+            self.arpeggiator,
         ))])
     }
 
@@ -36,42 +48,156 @@ impl UnresolvedReaperTargetDef for UnresolvedMidiSendTarget {
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct MidiSendTarget {
-    pattern: RawMidiPattern,
+    sequence: RawMidiSequence,
     destination: SendMidiDestination,
     // For making basic toggle/relative control possible.
     artificial_value: AbsoluteValue,
+    /// If set, the discrete value is interpreted as root note and a whole chord is sent.
+    chord: Option<ChordShape>,
+    /// Notes of the chord which is currently sounding (only relevant in chord mode).
+    held_chord: HeldChord,
+    /// If set, the notes are not sent at once but one after the other, driven by polling.
+    arpeggiator: Option<Arpeggiator>,
 }
 
 impl MidiSendTarget {
-    pub fn new(pattern: RawMidiPattern, destination: SendMidiDestination) -> Self {
-        let max_discrete_value = pattern.max_discrete_value();
+    pub fn new(
+        sequence: RawMidiSequence,
+        destination: SendMidiDestination,
+        chord: Option<ChordShape>,
+        arpeggiator: Option<ArpeggiatorSettings>,
+    ) -> Self {
+        let max_discrete_value = sequence.main_pattern().max_discrete_value();
         Self {
-            pattern,
+            sequence,
             destination,
             artificial_value: AbsoluteValue::Discrete(Fraction::new(0, max_discrete_value as _)),
+            chord,
+            held_chord: Default::default(),
+            arpeggiator: arpeggiator.map(Arpeggiator::new),
         }
     }
 
-    pub fn pattern(&self) -> &RawMidiPattern {
-        &self.pattern
+    pub fn sequence(&self) -> &RawMidiSequence {
+        &self.sequence
+    }
+
+    fn pattern(&self) -> &RawMidiPattern {
+        self.sequence.main_pattern()
     }
 
     pub fn destination(&self) -> SendMidiDestination {
         self.destination
     }
 
+    /// Snaps the value to the given scale (if any) before it gets substituted into the pattern.
+    fn quantize(&self, value: AbsoluteValue, quantizer: Option<ScaleQuantizer>) -> AbsoluteValue {
+        match quantizer {
+            None => value,
+            Some(q) => q.quantize(value, self.pattern().max_discrete_value() as _),
+        }
+    }
+
+    /// Takes over the chord which is still sounding from the target this one replaces.
+    pub fn take_over_held_chord(&mut self, previous: &mut MidiSendTarget) {
+        self.held_chord.take_over(&mut previous.held_chord);
+    }
+
     pub fn set_artificial_value(&mut self, value: AbsoluteValue) {
         self.artificial_value = value;
     }
 
+    /// Creates the MIDI events resulting from the given value and passes them to `emit`, together
+    /// with the delay after which each of them should be sent. Returns the value after snapping it
+    /// to the scale.
+    ///
+    /// Chords and arpeggios are built from the main pattern of the sequence only and are never
+    /// delayed.
+    ///
+    /// Doesn't allocate unless an arpeggiator is used, so it's safe to call in the real-time
+    /// thread (arpeggiator targets are never splintered to the real-time processor anyway).
+    pub fn create_midi_events(
+        &mut self,
+        value: AbsoluteValue,
+        quantizer: Option<ScaleQuantizer>,
+        mut emit: impl FnMut(Duration, RawMidiEvent),
+    ) -> AbsoluteValue {
+        let quantized_value = self.quantize(value, quantizer);
+        if self.chord.is_none() && self.arpeggiator.is_none() {
+            self.sequence.create_midi_events(quantized_value, &mut emit);
+            return quantized_value;
+        }
+        let max_note = self.max_note();
+        let root = root_note(value, quantizer, max_note);
+        let chord = self.chord;
+        if let Some(arp) = &mut self.arpeggiator {
+            let note_to_release = match (root, chord) {
+                (None, _) => arp.stop(),
+                (Some(r), None) => arp.start(std::iter::once(r), Instant::now()),
+                (Some(r), Some(c)) => arp.start(c.notes(r, max_note), Instant::now()),
+            };
+            if let Some(e) = note_to_release.and_then(|n| self.create_note_off_event(n)) {
+                emit(Duration::default(), e);
+            }
+            return quantized_value;
+        }
+        for note in self.held_chord.release() {
+            if let Some(e) = self.create_note_off_event(note) {
+                emit(Duration::default(), e);
+            }
+        }
+        if let (Some(root), Some(chord)) = (root, chord) {
+            for note in chord.notes(root, max_note) {
+                emit(Duration::default(), self.create_note_event(note));
+            }
+            self.held_chord.hold(chord.notes(root, max_note));
+        }
+        quantized_value
+    }
+
+    fn max_note(&self) -> u32 {
+        self.pattern().max_discrete_value() as _
+    }
+
+    fn create_note_event(&self, note: u32) -> RawMidiEvent {
+        let value = AbsoluteValue::Discrete(Fraction::new(note, self.max_note()));
+        self.pattern().to_concrete_midi_event(value)
+    }
+
+    /// Returns `None` if the pattern doesn't describe a note-on message.
+    fn create_note_off_event(&self, note: u32) -> Option<RawMidiEvent> {
+        let note_on = self.create_note_event(note);
+        match note_on.bytes() {
+            [status, note_byte, _] if status & 0xF0 == 0x90 => {
+                RawMidiEvent::try_from_slice(0, &[*status, *note_byte, 0]).ok()
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the device to which messages go if the target is hit in the main thread.
+    fn output_device(&self, context: ControlContext) -> Result<MidiOutputDeviceId, &'static str> {
+        match self.destination {
+            SendMidiDestination::FxOutput => Err("OSC => MIDI FX output not supported"),
+            SendMidiDestination::FeedbackOutput => {
+                let feedback_output = context.feedback_output.ok_or("no feedback output set")?;
+                if let FeedbackOutput::Midi(MidiDestination::Device(dev_id)) = feedback_output {
+                    Ok(dev_id)
+                } else {
+                    Err("feedback output is not a MIDI device")
+                }
+            }
+        }
+    }
+
     fn control_type_and_character_simple(&self) -> (ControlType, TargetCharacter) {
-        match self.pattern.step_size() {
+        match self.pattern().step_size() {
             None => (
                 ControlType::AbsoluteContinuousRetriggerable,
                 TargetCharacter::Trigger,
             ),
             Some(step_size) => {
-                if self.pattern.resolution() == 1 {
+                if self.pattern().resolution() == 1 {
                     (
                         ControlType::AbsoluteContinuousRetriggerable,
                         TargetCharacter::Switch,
@@ -115,7 +241,7 @@ impl RealearnTarget for MidiSendTarget {
         input: UnitValue,
         _: ControlContext,
     ) -> Result<u32, &'static str> {
-        let step_size = self.pattern.step_size().ok_or("not supported")?;
+        let step_size = self.pattern().step_size().ok_or("not supported")?;
         let discrete_value = (input.get() / step_size.get()).round() as _;
         Ok(discrete_value)
     }
@@ -157,30 +283,67 @@ impl RealearnTarget for MidiSendTarget {
         // We arrive here only if controlled via OSC, group interaction (as follower), mapping
         // snapshot or autoload. Sending MIDI in response to incoming MIDI messages is handled
         // directly in the real-time processor.
-        let raw_midi_event = self.pattern.to_concrete_midi_event(value);
-        let result = match self.destination {
-            SendMidiDestination::FxOutput => Err("OSC => MIDI FX output not supported"),
-            SendMidiDestination::FeedbackOutput => {
-                let feedback_output = context
-                    .control_context
-                    .feedback_output
-                    .ok_or("no feedback output set")?;
-                if let FeedbackOutput::Midi(MidiDestination::Device(dev_id)) = feedback_output {
-                    context.control_context.send_raw_midi(
-                        OutputReason::Target,
-                        dev_id,
-                        vec![raw_midi_event],
-                    );
-                    Ok(None)
+        let dev_id = self.output_device(context.control_context)?;
+        let now = Instant::now();
+        let mut events = vec![];
+        let mut delayed_events = vec![];
+        let value =
+            self.create_midi_events(value, context.mapping_data.scale_quantizer, |delay, e| {
+                if delay > Duration::default() {
+                    delayed_events.push((now + delay, e));
                 } else {
-                    Err("feedback output is not a MIDI device")
+                    events.push(e);
                 }
-            }
-        };
-        if result.is_ok() {
-            self.artificial_value = value;
+            });
+        context
+            .control_context
+            .send_raw_midi(OutputReason::Target, dev_id, events);
+        if !delayed_events.is_empty() {
+            // The real-time processor takes care of the delays, exactly as if the target had
+            // been hit in the real-time thread.
+            context
+                .control_context
+                .schedule_delayed_midi(MidiDestination::Device(dev_id), delayed_events)?;
         }
-        result
+        self.artificial_value = value;
+        Ok(None)
+    }
+
+    fn wants_to_be_polled_for_control(&self) -> bool {
+        self.arpeggiator.is_some()
+    }
+
+    fn poll_control(&mut self, context: MappingControlContext) {
+        let arp = match &mut self.arpeggiator {
+            Some(arp) if arp.is_running() => arp,
+            _ => return,
+        };
+        let bpm = context
+            .control_context
+            .processor_context
+            .project_or_current_project()
+            .tempo()
+            .bpm()
+            .get();
+        let step = match arp.poll(Instant::now(), bpm) {
+            None => return,
+            Some(s) => s,
+        };
+        // The FX output is ruled out when resolving, but the feedback output could still be
+        // something else than a MIDI device.
+        let dev_id = match self.output_device(context.control_context) {
+            Ok(id) => id,
+            Err(_) => return,
+        };
+        let events = step
+            .note_off
+            .and_then(|n| self.create_note_off_event(n))
+            .into_iter()
+            .chain(std::iter::once(self.create_note_event(step.note_on)))
+            .collect();
+        context
+            .control_context
+            .send_raw_midi(OutputReason::Target, dev_id, events);
     }
 
     fn is_available(&self, _: ControlContext) -> bool {
@@ -196,7 +359,7 @@ impl RealearnTarget for MidiSendTarget {
         value: u32,
         _: ControlContext,
     ) -> Result<UnitValue, &'static str> {
-        let unit_value = if let Some(step_size) = self.pattern.step_size() {
+        let unit_value = if let Some(step_size) = self.pattern().step_size() {
             (value as f64 * step_size.get()).try_into()?
         } else {
             UnitValue::MIN
@@ -205,6 +368,10 @@ impl RealearnTarget for MidiSendTarget {
     }
 
     fn splinter_real_time_target(&self) -> Option<RealTimeReaperTarget> {
+        if self.arpeggiator.is_some() {
+            // The arpeggiator is driven by polling in the main thread.
+            return None;
+        }
         Some(RealTimeReaperTarget::SendMidi(self.clone()))
     }
 
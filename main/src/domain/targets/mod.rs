@@ -1,6 +1,15 @@
 mod midi_send_target;
 pub use midi_send_target::*;
 
+mod midi_macro_send_target;
+pub use midi_macro_send_target::*;
+
+mod program_change_send_target;
+pub use program_change_send_target::*;
+
+mod midi_clock_send_target;
+pub use midi_clock_send_target::*;
+
 mod osc_send_target;
 pub use osc_send_target::*;
 
@@ -13,6 +22,14 @@ pub use clip_seek_target::*;
 mod clip_volume_target;
 pub use clip_volume_target::*;
 
+mod clip_column_stop_target;
+pub use clip_column_stop_target::*;
+mod clip_scene_launch_target;
+pub use clip_scene_launch_target::*;
+
+mod clip_cue_target;
+pub use clip_cue_target::*;
+
 mod track_peak_target;
 pub use track_peak_target::*;
 
@@ -31,6 +48,9 @@ pub use automation_mode_override_target::*;
 mod fx_parameter_target;
 pub use fx_parameter_target::*;
 
+mod fx_parameter_bank_target;
+pub use fx_parameter_bank_target::*;
+
 mod fx_enable_target;
 pub use fx_enable_target::*;
 
@@ -55,21 +75,66 @@ pub use all_track_fx_enable_target::*;
 mod transport_target;
 pub use transport_target::*;
 
+mod punch_target;
+pub use punch_target::*;
+
+mod undo_redo_target;
+pub use undo_redo_target::*;
+
+mod time_selection_target;
+pub use time_selection_target::*;
+
+mod monitoring_volume_target;
+pub use monitoring_volume_target::*;
+
+mod metronome_target;
+pub use metronome_target::*;
+
 mod automation_touch_state_target;
 pub use automation_touch_state_target::*;
 
 mod go_to_bookmark_target;
 pub use go_to_bookmark_target::*;
 
+mod navigate_bookmarks_target;
+pub use navigate_bookmarks_target::*;
+
 mod seek_target;
 pub use seek_target::*;
 
+mod set_ext_state_target;
+pub use set_ext_state_target::*;
+
+mod run_script_target;
+pub use run_script_target::*;
+
 mod track_volume_target;
 pub use track_volume_target::*;
 
+mod track_cue_target;
+pub use track_cue_target::*;
+
 mod track_tool_target;
 pub use track_tool_target::*;
 
+mod track_insert_target;
+pub use track_insert_target::*;
+
+mod track_duplicate_target;
+pub use track_duplicate_target::*;
+
+mod track_delete_target;
+pub use track_delete_target::*;
+
+mod track_folder_spill_target;
+pub use track_folder_spill_target::*;
+
+mod track_record_input_target;
+pub use track_record_input_target::*;
+
+mod track_input_monitoring_target;
+pub use track_input_monitoring_target::*;
+
 mod route_volume_target;
 pub use route_volume_target::*;
 
@@ -91,6 +156,9 @@ pub use route_automation_mode_target::*;
 mod track_pan_target;
 pub use track_pan_target::*;
 
+mod track_dual_pan_target;
+pub use track_dual_pan_target::*;
+
 mod track_width_target;
 pub use track_width_target::*;
 
@@ -118,15 +186,39 @@ pub use track_automation_mode_target::*;
 mod load_mapping_snapshot_target;
 pub use load_mapping_snapshot_target::*;
 
+mod save_mapping_snapshot_target;
+pub use save_mapping_snapshot_target::*;
+
 mod enable_mappings_target;
 pub use enable_mappings_target::*;
 
+mod enable_group_target;
+pub use enable_group_target::*;
+
+mod select_layer_target;
+pub use select_layer_target::*;
+
+mod set_bank_offset_target;
+pub use set_bank_offset_target::*;
+
 mod enable_instances_target;
 pub use enable_instances_target::*;
 
 mod navigate_within_group_target;
 pub use navigate_within_group_target::*;
 
+mod adjust_mapping_mode_target;
+pub use adjust_mapping_mode_target::*;
+
+mod multi_target;
+pub use multi_target::*;
+
+mod panic_target;
+pub use panic_target::*;
+
+mod invoke_instance_target;
+pub use invoke_instance_target::*;
+
 mod any_on_target;
 pub use any_on_target::*;
 
@@ -0,0 +1,183 @@
+use crate::domain::ui_util::{
+    format_value_as_db, format_value_as_db_without_unit, parse_value_from_db, volume_unit_value,
+};
+use crate::domain::{
+    CompoundChangeEvent, ControlContext, ExtendedProcessorContext, HitInstructionReturnValue,
+    MappingCompartment, MappingControlContext, RealearnTarget, ReaperTarget, ReaperTargetType,
+    TargetCharacter, TargetTypeDef, UnresolvedReaperTargetDef, DEFAULT_TARGET,
+};
+use helgoboss_learn::{AbsoluteValue, ControlType, ControlValue, NumericValue, Target, UnitValue};
+use reaper_high::{
+    ChangeEvent, Project, SendPartnerType, Track, TrackRoute, TrackRoutePartner, Volume,
+};
+use reaper_medium::ReaperVolumeValue;
+
+#[derive(Debug)]
+pub struct UnresolvedMonitoringVolumeTarget;
+
+impl UnresolvedReaperTargetDef for UnresolvedMonitoringVolumeTarget {
+    fn resolve(
+        &self,
+        context: ExtendedProcessorContext,
+        _: MappingCompartment,
+    ) -> Result<Vec<ReaperTarget>, &'static str> {
+        let master_track = context
+            .context()
+            .project_or_current_project()
+            .master_track();
+        Ok(vec![ReaperTarget::MonitoringVolume(
+            MonitoringVolumeTarget { master_track },
+        )])
+    }
+}
+
+/// Controls the volume of all hardware outputs of the master track at once.
+///
+/// The first hardware output determines the value. The other ones follow relatively.
+///
+/// This is the volume you hear through your speakers or headphones, without touching the master
+/// fader (which would affect rendering).
+#[derive(Clone, Debug, PartialEq)]
+pub struct MonitoringVolumeTarget {
+    pub master_track: Track,
+}
+
+impl MonitoringVolumeTarget {
+    fn hardware_outputs(&self) -> impl Iterator<Item = TrackRoute> {
+        self.master_track
+            .typed_sends(SendPartnerType::HardwareOutput)
+    }
+
+    /// Returns the volume of the first hardware output.
+    fn volume(&self) -> Option<Volume> {
+        self.hardware_outputs().next()?.volume().ok()
+    }
+}
+
+impl RealearnTarget for MonitoringVolumeTarget {
+    fn control_type_and_character(&self, _: ControlContext) -> (ControlType, TargetCharacter) {
+        (ControlType::AbsoluteContinuous, TargetCharacter::Continuous)
+    }
+
+    fn parse_as_value(&self, text: &str, _: ControlContext) -> Result<UnitValue, &'static str> {
+        parse_value_from_db(text)
+    }
+
+    fn format_value_without_unit(&self, value: UnitValue, _: ControlContext) -> String {
+        format_value_as_db_without_unit(value)
+    }
+
+    fn hide_formatted_value(&self, _: ControlContext) -> bool {
+        true
+    }
+
+    fn hide_formatted_step_size(&self, _: ControlContext) -> bool {
+        true
+    }
+
+    fn value_unit(&self, _: ControlContext) -> &'static str {
+        "dB"
+    }
+
+    fn format_value(&self, value: UnitValue, _: ControlContext) -> String {
+        format_value_as_db(value)
+    }
+
+    fn hit(
+        &mut self,
+        value: ControlValue,
+        _: MappingControlContext,
+    ) -> Result<HitInstructionReturnValue, &'static str> {
+        let volume = Volume::try_from_soft_normalized_value(value.to_unit_value()?.get())
+            .unwrap_or(Volume::MIN);
+        let outputs: Vec<_> = self.hardware_outputs().collect();
+        let first_output = outputs
+            .first()
+            .ok_or("master track has no hardware outputs")?;
+        // The first hardware output is the reference. The other ones are changed by the same
+        // factor, so that differences between the output levels are preserved.
+        let reference = first_output
+            .volume()
+            .map_err(|_| "couldn't get hardware output volume")?
+            .reaper_value()
+            .get();
+        for output in &outputs {
+            let new_volume = if reference > 0.0 {
+                let current = output
+                    .volume()
+                    .map_err(|_| "couldn't get hardware output volume")?
+                    .reaper_value()
+                    .get();
+                let factor = volume.reaper_value().get() / reference;
+                Volume::from_reaper_value(ReaperVolumeValue::new(current * factor))
+            } else {
+                // Silent reference output. There's no factor, so align all outputs.
+                volume
+            };
+            output
+                .set_volume(new_volume)
+                .map_err(|_| "couldn't set hardware output volume")?;
+        }
+        Ok(None)
+    }
+
+    fn is_available(&self, _: ControlContext) -> bool {
+        self.master_track.is_available()
+    }
+
+    fn project(&self) -> Option<Project> {
+        Some(self.master_track.project())
+    }
+
+    fn process_change_event(
+        &self,
+        evt: CompoundChangeEvent,
+        _: ControlContext,
+    ) -> (bool, Option<AbsoluteValue>) {
+        match evt {
+            CompoundChangeEvent::Reaper(ChangeEvent::TrackRouteVolumeChanged(e))
+                if e.route.track() == &self.master_track
+                    && matches!(
+                        e.route.partner(),
+                        Some(TrackRoutePartner::HardwareOutput(_))
+                    ) =>
+            {
+                // Feedback reflects the first hardware output only, so we query it anew.
+                (true, None)
+            }
+            _ => (false, None),
+        }
+    }
+
+    fn text_value(&self, _: ControlContext) -> Option<String> {
+        Some(self.volume()?.to_string())
+    }
+
+    fn numeric_value(&self, _: ControlContext) -> Option<NumericValue> {
+        Some(NumericValue::Decimal(self.volume()?.db().get()))
+    }
+
+    fn reaper_target_type(&self) -> Option<ReaperTargetType> {
+        Some(ReaperTargetType::MonitoringVolume)
+    }
+}
+
+impl<'a> Target<'a> for MonitoringVolumeTarget {
+    type Context = ControlContext<'a>;
+
+    fn current_value(&self, _: Self::Context) -> Option<AbsoluteValue> {
+        let val = volume_unit_value(self.volume()?);
+        Some(AbsoluteValue::Continuous(val))
+    }
+
+    fn control_type(&self, context: Self::Context) -> ControlType {
+        self.control_type_and_character(context).0
+    }
+}
+
+pub const MONITORING_VOLUME_TARGET: TargetTypeDef = TargetTypeDef {
+    name: "Project: Set monitoring volume",
+    short_name: "Monitoring volume",
+    hint: "Master hardware outputs",
+    ..DEFAULT_TARGET
+};
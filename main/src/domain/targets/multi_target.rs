@@ -0,0 +1,493 @@
+use crate::domain::{
+    aggregate_target_values, CompoundChangeEvent, ControlContext, ExtendedProcessorContext,
+    FeedbackResolution, HitInstruction, HitInstructionContext, HitInstructionReturnValue,
+    MappingCompartment, MappingControlContext, MappingControlResult, MidiSendTarget,
+    RealTimeReaperTarget, RealearnTarget, ReaperTarget, ReaperTargetType, TargetCharacter,
+    TargetTypeDef, UnresolvedReaperTarget, UnresolvedReaperTargetDef, DEFAULT_TARGET,
+};
+use helgoboss_learn::{AbsoluteValue, ControlType, ControlValue, NumericValue, Target, UnitValue};
+use reaper_high::{Fx, Project, Track, TrackRoute};
+use reaper_medium::Guid;
+
+#[derive(Debug)]
+pub struct UnresolvedMultiTarget {
+    pub children: Vec<UnresolvedMultiTargetChild>,
+}
+
+#[derive(Debug)]
+pub struct UnresolvedMultiTargetChild {
+    pub target: UnresolvedReaperTarget,
+    /// Factor applied to absolute control values before they are passed to this child.
+    pub scale: UnitValue,
+}
+
+impl UnresolvedMultiTarget {
+    pub fn can_be_affected_by_parameters(&self) -> bool {
+        self.children
+            .iter()
+            .any(|c| c.target.can_be_affected_by_parameters())
+    }
+
+    pub fn can_be_affected_by_foreign_change_events(&self) -> bool {
+        self.children
+            .iter()
+            .any(|c| c.target.can_be_affected_by_foreign_change_events())
+    }
+}
+
+impl UnresolvedReaperTargetDef for UnresolvedMultiTarget {
+    fn resolve(
+        &self,
+        context: ExtendedProcessorContext,
+        compartment: MappingCompartment,
+    ) -> Result<Vec<ReaperTarget>, &'static str> {
+        // Children which can't be resolved at the moment are skipped. The remaining ones should
+        // still work.
+        let children: Vec<_> = self
+            .children
+            .iter()
+            .flat_map(|child| {
+                let targets = child
+                    .target
+                    .resolve(context, compartment)
+                    .unwrap_or_default();
+                targets.into_iter().map(move |target| MultiTargetChild {
+                    target,
+                    scale: child.scale,
+                })
+            })
+            .collect();
+        if children.is_empty() {
+            return Err("none of the child targets could be resolved");
+        }
+        Ok(vec![ReaperTarget::Multi(MultiTarget { children })])
+    }
+
+    fn feedback_resolution(&self) -> Option<FeedbackResolution> {
+        self.children
+            .iter()
+            .filter_map(|c| c.target.feedback_resolution())
+            .max()
+    }
+
+    fn can_be_affected_by_change_events(&self) -> bool {
+        self.children
+            .iter()
+            .any(|c| c.target.can_be_affected_by_change_events())
+    }
+}
+
+/// Hits an ordered list of child targets with the same control value.
+///
+/// The first child is the leading one. It determines the control type and how values are
+/// formatted and parsed. Feedback is the aggregate of the (unscaled) values of all children.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MultiTarget {
+    /// Never empty.
+    pub children: Vec<MultiTargetChild>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MultiTargetChild {
+    pub target: ReaperTarget,
+    pub scale: UnitValue,
+}
+
+impl MultiTargetChild {
+    fn scale_control_value(&self, value: ControlValue) -> ControlValue {
+        scale_control_value(value, self.scale)
+    }
+
+    fn unscaled_current_value(&self, context: ControlContext) -> Option<AbsoluteValue> {
+        unscale_value(self.target.current_value(context)?, self.scale)
+    }
+}
+
+/// Scales absolute control values. Relative control values are passed through unchanged.
+fn scale_control_value(value: ControlValue, scale: UnitValue) -> ControlValue {
+    if scale == UnitValue::MAX {
+        return value;
+    }
+    match value.to_unit_value() {
+        Ok(v) => ControlValue::AbsoluteContinuous(UnitValue::new_clamped(v.get() * scale.get())),
+        Err(_) => value,
+    }
+}
+
+/// Reverses the scaling so that a fully scaled child reports 100%.
+///
+/// Returns `None` if the child is scaled to zero, because its value doesn't say anything then.
+fn unscale_value(value: AbsoluteValue, scale: UnitValue) -> Option<AbsoluteValue> {
+    if scale == UnitValue::MAX {
+        return Some(value);
+    }
+    if scale.is_zero() {
+        return None;
+    }
+    let unscaled = value.to_unit_value().get() / scale.get();
+    Some(AbsoluteValue::Continuous(UnitValue::new_clamped(unscaled)))
+}
+
+impl MultiTarget {
+    fn leader(&self) -> &ReaperTarget {
+        &self.children[0].target
+    }
+
+    /// GUIDs of the tracks and FXs which any of the children is bound to.
+    pub fn dependencies(&self) -> impl Iterator<Item = Guid> + '_ {
+        self.children.iter().flat_map(|c| {
+            let track_guid = c.target.track().map(|t| *t.guid());
+            let fx_guid = c.target.fx().and_then(|fx| fx.guid());
+            track_guid.into_iter().chain(fx_guid)
+        })
+    }
+}
+
+/// Executes the hit instructions of several children one after the other.
+struct MultiHitInstruction {
+    instructions: Vec<Box<dyn HitInstruction>>,
+}
+
+impl HitInstruction for MultiHitInstruction {
+    fn execute(self: Box<Self>, context: HitInstructionContext) -> Vec<MappingControlResult> {
+        let mut results = vec![];
+        for instruction in self.instructions {
+            let child_results = instruction.execute(HitInstructionContext {
+                mappings: &mut *context.mappings,
+                control_context: context.control_context,
+                domain_event_handler: context.domain_event_handler,
+                logger: context.logger,
+                processor_context: context.processor_context,
+            });
+            results.extend(child_results);
+        }
+        results
+    }
+}
+
+/// Real-time variant, used if all children are "Send MIDI" targets.
+///
+/// That's necessary for sending to the FX output, which is possible in the audio thread only.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RealTimeMultiTarget {
+    /// Never empty.
+    pub children: Vec<RealTimeMultiTargetChild>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RealTimeMultiTargetChild {
+    pub target: MidiSendTarget,
+    pub scale: UnitValue,
+}
+
+impl RealTimeMultiTargetChild {
+    pub fn scale_value(&self, value: AbsoluteValue) -> AbsoluteValue {
+        match scale_control_value(ControlValue::from_absolute(value), self.scale) {
+            ControlValue::AbsoluteContinuous(v) => AbsoluteValue::Continuous(v),
+            _ => value,
+        }
+    }
+}
+
+impl<'a> Target<'a> for RealTimeMultiTarget {
+    type Context = ();
+
+    fn current_value(&self, _: ()) -> Option<AbsoluteValue> {
+        let values = self
+            .children
+            .iter()
+            .map(|c| unscale_value(c.target.current_value(())?, c.scale));
+        aggregate_target_values(values)
+    }
+
+    fn control_type(&self, _: ()) -> ControlType {
+        self.children[0].target.control_type(())
+    }
+}
+
+impl RealearnTarget for MultiTarget {
+    fn control_type_and_character(
+        &self,
+        context: ControlContext,
+    ) -> (ControlType, TargetCharacter) {
+        self.leader().control_type_and_character(context)
+    }
+
+    fn open(&self, context: ControlContext) {
+        self.leader().open(context);
+    }
+
+    fn parse_as_value(
+        &self,
+        text: &str,
+        context: ControlContext,
+    ) -> Result<UnitValue, &'static str> {
+        self.leader().parse_as_value(text, context)
+    }
+
+    fn parse_as_step_size(
+        &self,
+        text: &str,
+        context: ControlContext,
+    ) -> Result<UnitValue, &'static str> {
+        self.leader().parse_as_step_size(text, context)
+    }
+
+    fn convert_unit_value_to_discrete_value(
+        &self,
+        input: UnitValue,
+        context: ControlContext,
+    ) -> Result<u32, &'static str> {
+        self.leader()
+            .convert_unit_value_to_discrete_value(input, context)
+    }
+
+    fn format_value_without_unit(&self, value: UnitValue, context: ControlContext) -> String {
+        self.leader().format_value_without_unit(value, context)
+    }
+
+    fn format_step_size_without_unit(
+        &self,
+        step_size: UnitValue,
+        context: ControlContext,
+    ) -> String {
+        self.leader()
+            .format_step_size_without_unit(step_size, context)
+    }
+
+    fn hide_formatted_value(&self, context: ControlContext) -> bool {
+        self.leader().hide_formatted_value(context)
+    }
+
+    fn hide_formatted_step_size(&self, context: ControlContext) -> bool {
+        self.leader().hide_formatted_step_size(context)
+    }
+
+    fn value_unit(&self, context: ControlContext) -> &'static str {
+        self.leader().value_unit(context)
+    }
+
+    fn step_size_unit(&self, context: ControlContext) -> &'static str {
+        self.leader().step_size_unit(context)
+    }
+
+    fn format_value(&self, value: UnitValue, context: ControlContext) -> String {
+        self.leader().format_value(value, context)
+    }
+
+    fn hit(
+        &mut self,
+        value: ControlValue,
+        context: MappingControlContext,
+    ) -> Result<HitInstructionReturnValue, &'static str> {
+        let mut hit_instructions = vec![];
+        let mut error = None;
+        let mut at_least_one_child_was_hit = false;
+        for child in &mut self.children {
+            let child_value = child.scale_control_value(value);
+            match child.target.hit(child_value, context) {
+                Ok(hi) => {
+                    at_least_one_child_was_hit = true;
+                    hit_instructions.extend(hi);
+                }
+                Err(msg) => {
+                    error = Some(msg);
+                }
+            }
+        }
+        if let Some(msg) = error {
+            if !at_least_one_child_was_hit {
+                return Err(msg);
+            }
+        }
+        let hit_instruction: HitInstructionReturnValue = match hit_instructions.len() {
+            0 => None,
+            1 => hit_instructions.pop(),
+            _ => Some(Box::new(MultiHitInstruction {
+                instructions: hit_instructions,
+            })),
+        };
+        Ok(hit_instruction)
+    }
+
+    fn can_report_current_value(&self) -> bool {
+        self.children
+            .iter()
+            .any(|c| c.target.can_report_current_value())
+    }
+
+    fn is_available(&self, context: ControlContext) -> bool {
+        self.children.iter().any(|c| c.target.is_available(context))
+    }
+
+    fn project(&self) -> Option<Project> {
+        self.leader().project()
+    }
+
+    /// Only the leading child's track. Refreshing considers the tracks and FXs of all children
+    /// (see [`MultiTarget::dependencies`]).
+    fn track(&self) -> Option<&Track> {
+        self.leader().track()
+    }
+
+    fn fx(&self) -> Option<&Fx> {
+        self.leader().fx()
+    }
+
+    fn route(&self) -> Option<&TrackRoute> {
+        self.leader().route()
+    }
+
+    fn supports_automatic_feedback(&self) -> bool {
+        self.children
+            .iter()
+            .any(|c| c.target.supports_automatic_feedback())
+    }
+
+    fn process_change_event(
+        &self,
+        evt: CompoundChangeEvent,
+        context: ControlContext,
+    ) -> (bool, Option<AbsoluteValue>) {
+        // Each child needs to see the event (some targets react to events in general, e.g. to
+        // transport stop). The new aggregated value needs to be queried.
+        let mut is_affected = false;
+        for child in &self.children {
+            let (child_is_affected, _) = child.target.process_change_event(evt, context);
+            is_affected |= child_is_affected;
+        }
+        (is_affected, None)
+    }
+
+    fn wants_to_be_polled_for_control(&self) -> bool {
+        self.children
+            .iter()
+            .any(|c| c.target.wants_to_be_polled_for_control())
+    }
+
+    fn poll_control(&mut self, context: MappingControlContext) {
+        for child in &mut self.children {
+            if child.target.wants_to_be_polled_for_control() {
+                child.target.poll_control(context);
+            }
+        }
+    }
+
+    fn convert_discrete_value_to_unit_value(
+        &self,
+        value: u32,
+        context: ControlContext,
+    ) -> Result<UnitValue, &'static str> {
+        self.leader()
+            .convert_discrete_value_to_unit_value(value, context)
+    }
+
+    fn text_value(&self, context: ControlContext) -> Option<String> {
+        self.leader().text_value(context)
+    }
+
+    fn numeric_value(&self, context: ControlContext) -> Option<NumericValue> {
+        self.leader().numeric_value(context)
+    }
+
+    fn numeric_value_unit(&self, context: ControlContext) -> &'static str {
+        self.leader().numeric_value_unit(context)
+    }
+
+    fn splinter_real_time_target(&self) -> Option<RealTimeReaperTarget> {
+        // Mixing real-time and main-thread processing within one mapping is not possible, so
+        // this works only if all children can be processed in real-time.
+        let children: Option<Vec<_>> = self
+            .children
+            .iter()
+            .map(|c| match c.target.splinter_real_time_target()? {
+                RealTimeReaperTarget::SendMidi(t) => Some(RealTimeMultiTargetChild {
+                    target: t,
+                    scale: c.scale,
+                }),
+                RealTimeReaperTarget::Multi(_) => None,
+            })
+            .collect();
+        Some(RealTimeReaperTarget::Multi(RealTimeMultiTarget {
+            children: children?,
+        }))
+    }
+
+    fn reaper_target_type(&self) -> Option<ReaperTargetType> {
+        Some(ReaperTargetType::Multi)
+    }
+}
+
+impl<'a> Target<'a> for MultiTarget {
+    type Context = ControlContext<'a>;
+
+    fn current_value(&self, context: Self::Context) -> Option<AbsoluteValue> {
+        let values = self
+            .children
+            .iter()
+            .map(|c| c.unscaled_current_value(context));
+        aggregate_target_values(values)
+    }
+
+    fn control_type(&self, context: Self::Context) -> ControlType {
+        self.control_type_and_character(context).0
+    }
+}
+
+pub const MULTI_TARGET: TargetTypeDef = TargetTypeDef {
+    name: "ReaLearn: Multi target",
+    short_name: "Multi target",
+    ..DEFAULT_TARGET
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use helgoboss_learn::DiscreteIncrement;
+
+    #[test]
+    fn scale_absolute_control_values() {
+        // Given
+        let value = ControlValue::AbsoluteContinuous(UnitValue::new(0.8));
+        // When
+        let half = scale_control_value(value, UnitValue::new(0.5));
+        let full = scale_control_value(value, UnitValue::MAX);
+        // Then
+        assert_eq!(half, ControlValue::AbsoluteContinuous(UnitValue::new(0.4)));
+        assert_eq!(full, value);
+    }
+
+    #[test]
+    fn pass_relative_control_values_through() {
+        // Given
+        let value = ControlValue::Relative(DiscreteIncrement::new(-2));
+        // When
+        let scaled = scale_control_value(value, UnitValue::new(0.5));
+        // Then
+        assert_eq!(scaled, value);
+    }
+
+    #[test]
+    fn unscale_current_values() {
+        // Given
+        let value = AbsoluteValue::Continuous(UnitValue::new(0.25));
+        // When
+        let from_half = unscale_value(value, UnitValue::new(0.5));
+        let from_quarter = unscale_value(
+            AbsoluteValue::Continuous(UnitValue::new(0.5)),
+            UnitValue::new(0.25),
+        );
+        let from_zero = unscale_value(value, UnitValue::MIN);
+        // Then
+        assert_eq!(
+            from_half,
+            Some(AbsoluteValue::Continuous(UnitValue::new(0.5)))
+        );
+        // A child which is beyond its scaled maximum reports 100%.
+        assert_eq!(
+            from_quarter,
+            Some(AbsoluteValue::Continuous(UnitValue::MAX))
+        );
+        assert_eq!(from_zero, None);
+    }
+}
@@ -0,0 +1,291 @@
+use crate::domain::{
+    bookmark_unit_value, convert_count_to_step_size, convert_unit_value_to_bookmark_index,
+    AdditionalFeedbackEvent, CompoundChangeEvent, ControlContext, ExtendedProcessorContext,
+    FeedbackResolution, HitInstructionReturnValue, MappingCompartment, MappingControlContext,
+    RealearnTarget, ReaperTarget, ReaperTargetType, TargetCharacter, TargetTypeDef,
+    UnresolvedReaperTargetDef, DEFAULT_TARGET,
+};
+use derive_more::Display;
+use enum_iterator::IntoEnumIterator;
+use helgoboss_learn::{
+    AbsoluteValue, ControlType, ControlValue, Fraction, NumericValue, Target, UnitValue,
+};
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+use reaper_high::{BookmarkType, ChangeEvent, Project};
+use reaper_medium::BookmarkRef;
+use serde::{Deserialize, Serialize};
+use std::num::NonZeroU32;
+
+#[derive(
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    Debug,
+    Serialize,
+    Deserialize,
+    IntoEnumIterator,
+    TryFromPrimitive,
+    IntoPrimitive,
+    Display,
+)]
+#[repr(usize)]
+pub enum BookmarkNavigationMode {
+    /// Encoders move by the given number of bookmarks, absolute control values select a bookmark
+    /// by its position.
+    #[serde(rename = "relative")]
+    #[display(fmt = "Relative")]
+    Relative,
+    #[serde(rename = "next")]
+    #[display(fmt = "Next")]
+    Next,
+    #[serde(rename = "previous")]
+    #[display(fmt = "Previous")]
+    Previous,
+}
+
+impl Default for BookmarkNavigationMode {
+    fn default() -> Self {
+        BookmarkNavigationMode::Relative
+    }
+}
+
+#[derive(Debug)]
+pub struct UnresolvedNavigateBookmarksTarget {
+    pub bookmark_type: BookmarkType,
+    pub mode: BookmarkNavigationMode,
+}
+
+impl UnresolvedReaperTargetDef for UnresolvedNavigateBookmarksTarget {
+    fn resolve(
+        &self,
+        context: ExtendedProcessorContext,
+        _: MappingCompartment,
+    ) -> Result<Vec<ReaperTarget>, &'static str> {
+        Ok(vec![ReaperTarget::NavigateBookmarks(
+            NavigateBookmarksTarget {
+                project: context.context().project_or_current_project(),
+                bookmark_type: self.bookmark_type,
+                mode: self.mode,
+            },
+        )])
+    }
+
+    fn feedback_resolution(&self) -> Option<FeedbackResolution> {
+        Some(FeedbackResolution::Beat)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct NavigateBookmarksTarget {
+    pub project: Project,
+    pub bookmark_type: BookmarkType,
+    pub mode: BookmarkNavigationMode,
+}
+
+impl RealearnTarget for NavigateBookmarksTarget {
+    fn control_type_and_character(&self, _: ControlContext) -> (ControlType, TargetCharacter) {
+        use BookmarkNavigationMode::*;
+        match self.mode {
+            // `+ 1` because "<None>" (before the first bookmark) is also a possible value.
+            Relative => (
+                ControlType::AbsoluteDiscrete {
+                    atomic_step_size: convert_count_to_step_size(self.bookmark_count() + 1),
+                },
+                TargetCharacter::Discrete,
+            ),
+            Next | Previous => (
+                ControlType::AbsoluteContinuousRetriggerable,
+                TargetCharacter::Trigger,
+            ),
+        }
+    }
+
+    fn parse_as_value(
+        &self,
+        text: &str,
+        context: ControlContext,
+    ) -> Result<UnitValue, &'static str> {
+        self.parse_value_from_discrete_value(text, context)
+    }
+
+    fn parse_as_step_size(
+        &self,
+        text: &str,
+        context: ControlContext,
+    ) -> Result<UnitValue, &'static str> {
+        self.parse_value_from_discrete_value(text, context)
+    }
+
+    fn convert_unit_value_to_discrete_value(
+        &self,
+        input: UnitValue,
+        _: ControlContext,
+    ) -> Result<u32, &'static str> {
+        let value = convert_unit_value_to_bookmark_index(self.bookmark_count(), input)
+            .map(|i| i + 1)
+            .unwrap_or(0);
+        Ok(value)
+    }
+
+    fn format_value(&self, value: UnitValue, _: ControlContext) -> String {
+        match convert_unit_value_to_bookmark_index(self.bookmark_count(), value) {
+            None => "<None>".to_string(),
+            Some(i) => (i + 1).to_string(),
+        }
+    }
+
+    fn hit(
+        &mut self,
+        value: ControlValue,
+        _: MappingControlContext,
+    ) -> Result<HitInstructionReturnValue, &'static str> {
+        use BookmarkNavigationMode::*;
+        let index = match self.mode {
+            Relative => match value.to_absolute_value()? {
+                AbsoluteValue::Continuous(v) => {
+                    convert_unit_value_to_bookmark_index(self.bookmark_count(), v)
+                }
+                AbsoluteValue::Discrete(f) => f.actual().checked_sub(1),
+            },
+            Next => {
+                if value.to_unit_value()?.is_zero() {
+                    return Ok(None);
+                }
+                let next_index = self.current_index().map(|i| i + 1).unwrap_or(0);
+                if next_index >= self.bookmark_count() {
+                    return Err("already at last bookmark");
+                }
+                Some(next_index)
+            }
+            Previous => {
+                if value.to_unit_value()?.is_zero() {
+                    return Ok(None);
+                }
+                let previous_index = self
+                    .current_index()
+                    .and_then(|i| i.checked_sub(1))
+                    .ok_or("already at first bookmark")?;
+                Some(previous_index)
+            }
+        };
+        let index = index.ok_or("no bookmark selected")?;
+        let position = NonZeroU32::new(index + 1).unwrap();
+        match self.bookmark_type {
+            BookmarkType::Marker => self.project.go_to_marker(BookmarkRef::Position(position)),
+            BookmarkType::Region => self
+                .project
+                .go_to_region_with_smooth_seek(BookmarkRef::Position(position)),
+        }
+        Ok(None)
+    }
+
+    fn is_available(&self, _: ControlContext) -> bool {
+        self.project.is_available()
+    }
+
+    fn project(&self) -> Option<Project> {
+        Some(self.project)
+    }
+
+    fn process_change_event(
+        &self,
+        evt: CompoundChangeEvent,
+        _: ControlContext,
+    ) -> (bool, Option<AbsoluteValue>) {
+        // The current bookmark depends on the play position, so we need to check on each beat.
+        use CompoundChangeEvent::*;
+        match evt {
+            Reaper(ChangeEvent::BookmarksChanged(e)) if e.project == self.project => (true, None),
+            Additional(AdditionalFeedbackEvent::BeatChanged(e)) if e.project == self.project => {
+                (true, None)
+            }
+            _ => (false, None),
+        }
+    }
+
+    fn convert_discrete_value_to_unit_value(
+        &self,
+        value: u32,
+        _: ControlContext,
+    ) -> Result<UnitValue, &'static str> {
+        let index = if value == 0 { None } else { Some(value - 1) };
+        Ok(bookmark_unit_value(self.bookmark_count(), index))
+    }
+
+    fn text_value(&self, _: ControlContext) -> Option<String> {
+        let index = self.current_index()?;
+        let res = self
+            .project
+            .find_bookmark_by_type_and_index(self.bookmark_type, index)?;
+        Some(res.bookmark.name())
+    }
+
+    fn numeric_value(&self, _: ControlContext) -> Option<NumericValue> {
+        let index = self.current_index()?;
+        Some(NumericValue::Discrete(index as i32 + 1))
+    }
+
+    fn reaper_target_type(&self) -> Option<ReaperTargetType> {
+        Some(ReaperTargetType::NavigateBookmarks)
+    }
+}
+
+impl NavigateBookmarksTarget {
+    fn bookmark_count(&self) -> u32 {
+        self.project
+            .bookmarks()
+            .filter(|b| b.basic_info().bookmark_type() == self.bookmark_type)
+            .count() as u32
+    }
+
+    /// Returns the index (counting only bookmarks of the relevant type) of the marker or region at
+    /// the play or edit cursor position.
+    fn current_index(&self) -> Option<u32> {
+        let current_bookmark = self
+            .project
+            .current_bookmark_at(self.project.play_or_edit_cursor_position());
+        let overall_index = match self.bookmark_type {
+            BookmarkType::Marker => current_bookmark.marker_index,
+            BookmarkType::Region => current_bookmark.region_index,
+        }?;
+        let index = self
+            .project
+            .bookmarks()
+            .take(overall_index as usize)
+            .filter(|b| b.basic_info().bookmark_type() == self.bookmark_type)
+            .count();
+        Some(index as u32)
+    }
+}
+
+impl<'a> Target<'a> for NavigateBookmarksTarget {
+    type Context = ControlContext<'a>;
+
+    fn current_value(&self, _: Self::Context) -> Option<AbsoluteValue> {
+        let bookmark_count = self.bookmark_count();
+        let index = self.current_index();
+        let value = match self.mode {
+            BookmarkNavigationMode::Relative => {
+                // Because we count "<None>" as a possible value, this is equal.
+                let max_value = bookmark_count;
+                let actual_value = index.map(|i| i + 1).unwrap_or(0);
+                AbsoluteValue::Discrete(Fraction::new(actual_value, max_value))
+            }
+            BookmarkNavigationMode::Next | BookmarkNavigationMode::Previous => {
+                AbsoluteValue::Continuous(bookmark_unit_value(bookmark_count, index))
+            }
+        };
+        Some(value)
+    }
+
+    fn control_type(&self, context: Self::Context) -> ControlType {
+        self.control_type_and_character(context).0
+    }
+}
+
+pub const NAVIGATE_BOOKMARKS_TARGET: TargetTypeDef = TargetTypeDef {
+    name: "Marker/region: Navigate",
+    short_name: "Navigate bookmarks",
+    ..DEFAULT_TARGET
+};
@@ -0,0 +1,81 @@
+use crate::domain::{
+    ControlContext, ExtendedProcessorContext, HitInstructionReturnValue, MappingCompartment,
+    MappingControlContext, RealearnTarget, ReaperTarget, ReaperTargetType, TargetCharacter,
+    TargetTypeDef, UnresolvedReaperTargetDef, DEFAULT_TARGET,
+};
+use helgoboss_learn::{AbsoluteValue, ControlType, ControlValue, Target};
+
+#[derive(Debug)]
+pub struct UnresolvedPanicTarget;
+
+impl UnresolvedReaperTargetDef for UnresolvedPanicTarget {
+    fn resolve(
+        &self,
+        _: ExtendedProcessorContext,
+        _: MappingCompartment,
+    ) -> Result<Vec<ReaperTarget>, &'static str> {
+        Ok(vec![ReaperTarget::Panic(PanicTarget)])
+    }
+}
+
+/// Recovers from stuck controller state.
+///
+/// Clears all feedback of this instance, sends "All notes off" and "Reset all controllers" to the
+/// MIDI feedback output, stops all clips and finally sends fresh feedback.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PanicTarget;
+
+impl RealearnTarget for PanicTarget {
+    fn control_type_and_character(&self, _: ControlContext) -> (ControlType, TargetCharacter) {
+        (
+            ControlType::AbsoluteContinuousRetriggerable,
+            TargetCharacter::Trigger,
+        )
+    }
+
+    fn hit(
+        &mut self,
+        value: ControlValue,
+        context: MappingControlContext,
+    ) -> Result<HitInstructionReturnValue, &'static str> {
+        if value.to_unit_value()?.is_zero() {
+            return Ok(None);
+        }
+        // The main processor is busy controlling this mapping, so it does the actual work later.
+        context
+            .control_context
+            .instance_container
+            .panic(context.control_context.instance_id)?;
+        Ok(None)
+    }
+
+    fn can_report_current_value(&self) -> bool {
+        false
+    }
+
+    fn is_available(&self, _: ControlContext) -> bool {
+        true
+    }
+
+    fn reaper_target_type(&self) -> Option<ReaperTargetType> {
+        Some(ReaperTargetType::Panic)
+    }
+}
+
+impl<'a> Target<'a> for PanicTarget {
+    type Context = ControlContext<'a>;
+
+    fn current_value(&self, _: Self::Context) -> Option<AbsoluteValue> {
+        None
+    }
+
+    fn control_type(&self, context: Self::Context) -> ControlType {
+        self.control_type_and_character(context).0
+    }
+}
+
+pub const PANIC_TARGET: TargetTypeDef = TargetTypeDef {
+    name: "ReaLearn: Panic",
+    short_name: "Panic",
+    ..DEFAULT_TARGET
+};
@@ -0,0 +1,207 @@
+use crate::domain::ui_util::OutputReason;
+use crate::domain::{
+    convert_count_to_step_size, convert_discrete_to_unit_value, convert_unit_to_discrete_value,
+    CompoundChangeEvent, ControlContext, ExtendedProcessorContext, FeedbackOutput,
+    HitInstructionReturnValue, InstanceStateChanged, MappingCompartment, MappingControlContext,
+    MidiDestination, ProgramChangePatch, RealearnTarget, ReaperTarget, ReaperTargetType,
+    TargetCharacter, TargetTypeDef, UnresolvedReaperTargetDef, DEFAULT_TARGET,
+};
+use helgoboss_learn::{
+    AbsoluteValue, ControlType, ControlValue, Fraction, NumericValue, Target, UnitValue,
+};
+use reaper_medium::MidiOutputDeviceId;
+
+#[derive(Debug)]
+pub struct UnresolvedProgramChangeSendTarget {
+    pub output: Option<MidiOutputDeviceId>,
+    pub channel: u8,
+    pub patches: Vec<ProgramChangePatch>,
+}
+
+impl UnresolvedReaperTargetDef for UnresolvedProgramChangeSendTarget {
+    fn resolve(
+        &self,
+        _: ExtendedProcessorContext,
+        _: MappingCompartment,
+    ) -> Result<Vec<ReaperTarget>, &'static str> {
+        if self.patches.is_empty() {
+            return Err("no patches defined");
+        }
+        Ok(vec![ReaperTarget::SendProgramChange(
+            ProgramChangeSendTarget {
+                output: self.output,
+                channel: self.channel,
+                patches: self.patches.clone(),
+            },
+        )])
+    }
+}
+
+/// Selects a patch of an external MIDI device by sending bank select and program change messages.
+///
+/// The device doesn't tell us which patch is active, so the current value is derived from the
+/// patch which this instance has sent most recently to the same device and channel.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProgramChangeSendTarget {
+    /// `None` means the feedback output.
+    pub output: Option<MidiOutputDeviceId>,
+    pub channel: u8,
+    pub patches: Vec<ProgramChangePatch>,
+}
+
+impl ProgramChangeSendTarget {
+    fn count(&self) -> u32 {
+        self.patches.len() as _
+    }
+
+    fn resolve_device(&self, context: ControlContext) -> Result<MidiOutputDeviceId, &'static str> {
+        if let Some(dev_id) = self.output {
+            return Ok(dev_id);
+        }
+        match context.feedback_output.ok_or("no feedback output set")? {
+            FeedbackOutput::Midi(MidiDestination::Device(dev_id)) => Ok(dev_id),
+            _ => Err("feedback output is not a MIDI device"),
+        }
+    }
+
+    fn current_patch_with_position(
+        &self,
+        context: ControlContext,
+    ) -> Option<(&ProgramChangePatch, Fraction)> {
+        let dev_id = self.resolve_device(context).ok()?;
+        let instance_state = context.instance_state.borrow();
+        let sent_patch = instance_state.sent_program_change(dev_id, self.channel)?;
+        let index = self
+            .patches
+            .iter()
+            .position(|p| p.has_same_address(sent_patch))?;
+        let max_value = self.count() - 1;
+        Some((&self.patches[index], Fraction::new(index as _, max_value)))
+    }
+}
+
+impl RealearnTarget for ProgramChangeSendTarget {
+    fn control_type_and_character(&self, _: ControlContext) -> (ControlType, TargetCharacter) {
+        (
+            ControlType::AbsoluteDiscrete {
+                atomic_step_size: convert_count_to_step_size(self.count()),
+            },
+            TargetCharacter::Discrete,
+        )
+    }
+
+    fn hit(
+        &mut self,
+        value: ControlValue,
+        context: MappingControlContext,
+    ) -> Result<HitInstructionReturnValue, &'static str> {
+        let value = value.to_absolute_value()?;
+        let index = match value {
+            AbsoluteValue::Continuous(v) => convert_unit_to_discrete_value(v, self.count()),
+            AbsoluteValue::Discrete(f) => f.actual(),
+        };
+        let patch = self
+            .patches
+            .get(index as usize)
+            .ok_or("patch index out of bounds")?;
+        let context = context.control_context;
+        let dev_id = self.resolve_device(context)?;
+        let events = patch.create_midi_events(self.channel);
+        context.send_raw_midi(OutputReason::Target, dev_id, events);
+        context.instance_state.borrow_mut().set_sent_program_change(
+            dev_id,
+            self.channel,
+            patch.clone(),
+        );
+        Ok(None)
+    }
+
+    fn parse_as_value(
+        &self,
+        text: &str,
+        context: ControlContext,
+    ) -> Result<UnitValue, &'static str> {
+        self.parse_value_from_discrete_value(text, context)
+    }
+
+    fn parse_as_step_size(
+        &self,
+        text: &str,
+        context: ControlContext,
+    ) -> Result<UnitValue, &'static str> {
+        self.parse_value_from_discrete_value(text, context)
+    }
+
+    fn convert_unit_value_to_discrete_value(
+        &self,
+        input: UnitValue,
+        _: ControlContext,
+    ) -> Result<u32, &'static str> {
+        Ok(convert_unit_to_discrete_value(input, self.count()))
+    }
+
+    fn convert_discrete_value_to_unit_value(
+        &self,
+        value: u32,
+        _: ControlContext,
+    ) -> Result<UnitValue, &'static str> {
+        Ok(convert_discrete_to_unit_value(value, self.count()))
+    }
+
+    fn is_available(&self, _: ControlContext) -> bool {
+        true
+    }
+
+    fn process_change_event(
+        &self,
+        evt: CompoundChangeEvent,
+        context: ControlContext,
+    ) -> (bool, Option<AbsoluteValue>) {
+        match evt {
+            CompoundChangeEvent::Instance(InstanceStateChanged::SentProgramChange {
+                dev_id,
+                channel,
+            }) if *channel == self.channel && self.resolve_device(context) == Ok(*dev_id) => {
+                (true, None)
+            }
+            _ => (false, None),
+        }
+    }
+
+    fn text_value(&self, context: ControlContext) -> Option<String> {
+        let (patch, _) = self.current_patch_with_position(context)?;
+        Some(patch.name.clone())
+    }
+
+    fn numeric_value(&self, context: ControlContext) -> Option<NumericValue> {
+        let (patch, _) = self.current_patch_with_position(context)?;
+        Some(NumericValue::Discrete(patch.program as i32 + 1))
+    }
+
+    fn reaper_target_type(&self) -> Option<ReaperTargetType> {
+        Some(ReaperTargetType::SendProgramChange)
+    }
+}
+
+impl<'a> Target<'a> for ProgramChangeSendTarget {
+    type Context = ControlContext<'a>;
+
+    fn current_value(&self, context: ControlContext) -> Option<AbsoluteValue> {
+        let fraction = self
+            .current_patch_with_position(context)
+            .map(|(_, f)| f)
+            .unwrap_or(Fraction::MIN);
+        Some(AbsoluteValue::Discrete(fraction))
+    }
+
+    fn control_type(&self, context: Self::Context) -> ControlType {
+        self.control_type_and_character(context).0
+    }
+}
+
+pub const PROGRAM_CHANGE_SEND_TARGET: TargetTypeDef = TargetTypeDef {
+    name: "MIDI: Send program change",
+    short_name: "Send program change",
+    hint: "Remembers the last sent patch for feedback",
+    ..DEFAULT_TARGET
+};
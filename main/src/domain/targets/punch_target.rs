@@ -0,0 +1,268 @@
+use crate::domain::{
+    format_value_as_on_off, transport_is_enabled_unit_value, AdditionalFeedbackEvent,
+    CompoundChangeEvent, ControlContext, ExtendedProcessorContext, FeedbackResolution,
+    HitInstructionReturnValue, MappingCompartment, MappingControlContext, RealearnTarget,
+    ReaperTarget, ReaperTargetType, TargetCharacter, TargetTypeDef, UnresolvedReaperTargetDef,
+    DEFAULT_TARGET,
+};
+use derive_more::Display;
+use enum_iterator::IntoEnumIterator;
+use helgoboss_learn::{AbsoluteValue, ControlType, ControlValue, Target, UnitValue};
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+use reaper_high::{ChangeEvent, Project, Reaper};
+use reaper_medium::{CommandId, PositionInSeconds};
+use serde::{Deserialize, Serialize};
+
+/// "Options: Record mode: time selection auto punch"
+const AUTO_PUNCH_RECORD_MODE_COMMAND_ID: u32 = 40076;
+/// "Options: Record mode: normal"
+const NORMAL_RECORD_MODE_COMMAND_ID: u32 = 40252;
+/// "Options: Record mode: auto punch selected items"
+const ITEM_PUNCH_RECORD_MODE_COMMAND_ID: u32 = 40253;
+
+#[derive(
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    Debug,
+    Serialize,
+    Deserialize,
+    IntoEnumIterator,
+    TryFromPrimitive,
+    IntoPrimitive,
+    Display,
+)]
+#[repr(usize)]
+pub enum PunchAction {
+    #[serde(rename = "setPunchIn")]
+    #[display(fmt = "Set punch-in at cursor")]
+    SetPunchIn,
+    #[serde(rename = "setPunchOut")]
+    #[display(fmt = "Set punch-out at cursor")]
+    SetPunchOut,
+    #[serde(rename = "autoPunch")]
+    #[display(fmt = "Auto-punch")]
+    AutoPunch,
+    #[serde(rename = "recordFromCursor")]
+    #[display(fmt = "Record from cursor (auto-punch)")]
+    RecordFromCursor,
+}
+
+impl Default for PunchAction {
+    fn default() -> Self {
+        PunchAction::SetPunchIn
+    }
+}
+
+#[derive(Debug)]
+pub struct UnresolvedPunchTarget {
+    pub action: PunchAction,
+}
+
+impl UnresolvedReaperTargetDef for UnresolvedPunchTarget {
+    fn resolve(
+        &self,
+        context: ExtendedProcessorContext,
+        _: MappingCompartment,
+    ) -> Result<Vec<ReaperTarget>, &'static str> {
+        Ok(vec![ReaperTarget::Punch(PunchTarget {
+            project: context.context().project_or_current_project(),
+            action: self.action,
+        })])
+    }
+
+    fn feedback_resolution(&self) -> Option<FeedbackResolution> {
+        // The punch range (= time selection) doesn't fire change events, so we check it
+        // whenever the cursor moves.
+        Some(FeedbackResolution::Beat)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PunchTarget {
+    pub project: Project,
+    pub action: PunchAction,
+}
+
+impl PunchTarget {
+    fn set_punch_in_at_cursor(&self) -> Result<(), &'static str> {
+        let cursor = self.project.play_or_edit_cursor_position();
+        let end = match self.project.time_selection() {
+            Some(r) if r.end > cursor => r.end,
+            // No punch-out point after the cursor yet. Punch until the end of the project.
+            _ => {
+                let length = self.project.length();
+                if length.get() <= cursor.get() {
+                    return Err("no punch-out point after cursor");
+                }
+                PositionInSeconds::new(length.get())
+            }
+        };
+        self.project.set_time_selection(cursor, end);
+        Ok(())
+    }
+
+    fn set_punch_out_at_cursor(&self) -> Result<(), &'static str> {
+        let cursor = self.project.play_or_edit_cursor_position();
+        let start = match self.project.time_selection() {
+            Some(r) if r.start < cursor => r.start,
+            // No punch-in point before the cursor yet. Punch from the start of the project.
+            _ => {
+                if cursor.get() <= 0.0 {
+                    return Err("no punch-in point before cursor");
+                }
+                PositionInSeconds::new(0.0)
+            }
+        };
+        self.project.set_time_selection(start, cursor);
+        Ok(())
+    }
+
+    fn set_auto_punch_enabled(&self, enabled: bool) {
+        let command_id = if enabled {
+            AUTO_PUNCH_RECORD_MODE_COMMAND_ID
+        } else {
+            NORMAL_RECORD_MODE_COMMAND_ID
+        };
+        Reaper::get()
+            .main_section()
+            .action_by_command_id(CommandId::new(command_id))
+            .invoke_as_trigger(Some(self.project));
+    }
+
+    fn has_punch_range(&self) -> bool {
+        self.project.time_selection().is_some()
+    }
+
+    fn is_recording(&self) -> bool {
+        self.project.play_state().is_recording
+    }
+}
+
+fn auto_punch_is_enabled() -> bool {
+    Reaper::get()
+        .main_section()
+        .action_by_command_id(CommandId::new(AUTO_PUNCH_RECORD_MODE_COMMAND_ID))
+        .is_on()
+        .unwrap_or(false)
+}
+
+impl RealearnTarget for PunchTarget {
+    fn control_type_and_character(&self, _: ControlContext) -> (ControlType, TargetCharacter) {
+        use PunchAction::*;
+        match self.action {
+            SetPunchIn | SetPunchOut | RecordFromCursor => (
+                ControlType::AbsoluteContinuousRetriggerable,
+                TargetCharacter::Trigger,
+            ),
+            AutoPunch => (ControlType::AbsoluteContinuous, TargetCharacter::Switch),
+        }
+    }
+
+    fn format_value(&self, value: UnitValue, _: ControlContext) -> String {
+        format_value_as_on_off(value).to_string()
+    }
+
+    fn hit(
+        &mut self,
+        value: ControlValue,
+        _: MappingControlContext,
+    ) -> Result<HitInstructionReturnValue, &'static str> {
+        use PunchAction::*;
+        let on = !value.to_unit_value()?.is_zero();
+        match self.action {
+            SetPunchIn => {
+                if on {
+                    self.set_punch_in_at_cursor()?;
+                }
+            }
+            SetPunchOut => {
+                if on {
+                    self.set_punch_out_at_cursor()?;
+                }
+            }
+            AutoPunch => self.set_auto_punch_enabled(on),
+            RecordFromCursor => {
+                if on && !self.is_recording() {
+                    self.set_punch_in_at_cursor()?;
+                    self.set_auto_punch_enabled(true);
+                    Reaper::get().enable_record_in_current_project();
+                }
+            }
+        };
+        Ok(None)
+    }
+
+    fn is_available(&self, _: ControlContext) -> bool {
+        self.project.is_available()
+    }
+
+    fn project(&self) -> Option<Project> {
+        Some(self.project)
+    }
+
+    fn process_change_event(
+        &self,
+        evt: CompoundChangeEvent,
+        _: ControlContext,
+    ) -> (bool, Option<AbsoluteValue>) {
+        match evt {
+            CompoundChangeEvent::Reaper(ChangeEvent::PlayStateChanged(e))
+                if self.action == PunchAction::RecordFromCursor && e.project == self.project =>
+            {
+                (true, None)
+            }
+            CompoundChangeEvent::Additional(AdditionalFeedbackEvent::ActionInvoked(e))
+                if matches!(
+                    e.command_id.get(),
+                    AUTO_PUNCH_RECORD_MODE_COMMAND_ID
+                        | NORMAL_RECORD_MODE_COMMAND_ID
+                        | ITEM_PUNCH_RECORD_MODE_COMMAND_ID
+                ) =>
+            {
+                (true, None)
+            }
+            CompoundChangeEvent::Additional(AdditionalFeedbackEvent::BeatChanged(e))
+                if e.project == self.project =>
+            {
+                (true, None)
+            }
+            _ => (false, None),
+        }
+    }
+
+    fn text_value(&self, context: ControlContext) -> Option<String> {
+        Some(format_value_as_on_off(self.current_value(context)?.to_unit_value()).to_string())
+    }
+
+    fn reaper_target_type(&self) -> Option<ReaperTargetType> {
+        Some(ReaperTargetType::Punch)
+    }
+}
+
+impl<'a> Target<'a> for PunchTarget {
+    type Context = ControlContext<'a>;
+
+    fn current_value(&self, _: Self::Context) -> Option<AbsoluteValue> {
+        use PunchAction::*;
+        let is_on = match self.action {
+            SetPunchIn | SetPunchOut => self.has_punch_range(),
+            AutoPunch => auto_punch_is_enabled(),
+            RecordFromCursor => self.is_recording() && auto_punch_is_enabled(),
+        };
+        Some(AbsoluteValue::Continuous(transport_is_enabled_unit_value(
+            is_on,
+        )))
+    }
+
+    fn control_type(&self, context: Self::Context) -> ControlType {
+        self.control_type_and_character(context).0
+    }
+}
+
+pub const PUNCH_TARGET: TargetTypeDef = TargetTypeDef {
+    name: "Project: Punch in/out",
+    short_name: "Punch",
+    ..DEFAULT_TARGET
+};
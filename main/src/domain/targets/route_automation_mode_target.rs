@@ -1,5 +1,5 @@
 use crate::domain::{
-    automation_mode_unit_value, format_value_as_on_off, get_track_route, ControlContext,
+    automation_mode_unit_value, format_value_as_on_off, get_track_routes, ControlContext,
     ExtendedProcessorContext, FeedbackResolution, HitInstructionReturnValue, MappingCompartment,
     MappingControlContext, RealearnTarget, ReaperTarget, ReaperTargetType, TargetCharacter,
     TargetTypeDef, TrackRouteDescriptor, UnresolvedReaperTargetDef,
@@ -22,13 +22,16 @@ impl UnresolvedReaperTargetDef for UnresolvedRouteAutomationModeTarget {
         context: ExtendedProcessorContext,
         compartment: MappingCompartment,
     ) -> Result<Vec<ReaperTarget>, &'static str> {
-        Ok(vec![ReaperTarget::TrackRouteAutomationMode(
-            RouteAutomationModeTarget {
-                route: get_track_route(context, &self.descriptor, compartment)?,
-                poll_for_feedback: self.poll_for_feedback,
-                mode: self.mode,
-            },
-        )])
+        Ok(get_track_routes(context, &self.descriptor, compartment)?
+            .into_iter()
+            .map(|route| {
+                ReaperTarget::TrackRouteAutomationMode(RouteAutomationModeTarget {
+                    route,
+                    poll_for_feedback: self.poll_for_feedback,
+                    mode: self.mode,
+                })
+            })
+            .collect())
     }
 
     fn route_descriptor(&self) -> Option<&TrackRouteDescriptor> {
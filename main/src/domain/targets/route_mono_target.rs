@@ -1,5 +1,5 @@
 use crate::domain::{
-    format_value_as_on_off, get_track_route, mute_unit_value, ControlContext,
+    format_value_as_on_off, get_track_routes, mute_unit_value, ControlContext,
     ExtendedProcessorContext, FeedbackResolution, HitInstructionReturnValue, MappingCompartment,
     MappingControlContext, RealearnTarget, ReaperTarget, ReaperTargetType, TargetCharacter,
     TargetTypeDef, TrackRouteDescriptor, UnresolvedReaperTargetDef,
@@ -20,10 +20,15 @@ impl UnresolvedReaperTargetDef for UnresolvedRouteMonoTarget {
         context: ExtendedProcessorContext,
         compartment: MappingCompartment,
     ) -> Result<Vec<ReaperTarget>, &'static str> {
-        Ok(vec![ReaperTarget::TrackRouteMono(RouteMonoTarget {
-            route: get_track_route(context, &self.descriptor, compartment)?,
-            poll_for_feedback: self.poll_for_feedback,
-        })])
+        Ok(get_track_routes(context, &self.descriptor, compartment)?
+            .into_iter()
+            .map(|route| {
+                ReaperTarget::TrackRouteMono(RouteMonoTarget {
+                    route,
+                    poll_for_feedback: self.poll_for_feedback,
+                })
+            })
+            .collect())
     }
 
     fn route_descriptor(&self) -> Option<&TrackRouteDescriptor> {
@@ -1,5 +1,5 @@
 use crate::domain::{
-    format_value_as_pan, get_track_route, pan_unit_value, parse_value_from_pan,
+    format_value_as_pan, get_track_routes, pan_unit_value, parse_value_from_pan,
     CompoundChangeEvent, ControlContext, ExtendedProcessorContext, HitInstructionReturnValue,
     MappingCompartment, MappingControlContext, RealearnTarget, ReaperTarget, ReaperTargetType,
     TargetCharacter, TargetTypeDef, TrackRouteDescriptor, UnresolvedReaperTargetDef,
@@ -20,9 +20,10 @@ impl UnresolvedReaperTargetDef for UnresolvedRoutePanTarget {
         context: ExtendedProcessorContext,
         compartment: MappingCompartment,
     ) -> Result<Vec<ReaperTarget>, &'static str> {
-        Ok(vec![ReaperTarget::TrackRoutePan(RoutePanTarget {
-            route: get_track_route(context, &self.descriptor, compartment)?,
-        })])
+        Ok(get_track_routes(context, &self.descriptor, compartment)?
+            .into_iter()
+            .map(|route| ReaperTarget::TrackRoutePan(RoutePanTarget { route }))
+            .collect())
     }
 
     fn route_descriptor(&self) -> Option<&TrackRouteDescriptor> {
@@ -2,7 +2,7 @@ use crate::domain::ui_util::{
     format_value_as_db, format_value_as_db_without_unit, parse_value_from_db, volume_unit_value,
 };
 use crate::domain::{
-    get_track_route, CompoundChangeEvent, ControlContext, ExtendedProcessorContext,
+    get_track_routes, CompoundChangeEvent, ControlContext, ExtendedProcessorContext,
     HitInstructionReturnValue, MappingCompartment, MappingControlContext, RealearnTarget,
     ReaperTarget, ReaperTargetType, TargetCharacter, TargetTypeDef, TrackRouteDescriptor,
     UnresolvedReaperTargetDef, DEFAULT_TARGET,
@@ -22,9 +22,10 @@ impl UnresolvedReaperTargetDef for UnresolvedRouteVolumeTarget {
         context: ExtendedProcessorContext,
         compartment: MappingCompartment,
     ) -> Result<Vec<ReaperTarget>, &'static str> {
-        Ok(vec![ReaperTarget::TrackRouteVolume(RouteVolumeTarget {
-            route: get_track_route(context, &self.descriptor, compartment)?,
-        })])
+        Ok(get_track_routes(context, &self.descriptor, compartment)?
+            .into_iter()
+            .map(|route| ReaperTarget::TrackRouteVolume(RouteVolumeTarget { route }))
+            .collect())
     }
 
     fn route_descriptor(&self) -> Option<&TrackRouteDescriptor> {
@@ -0,0 +1,173 @@
+use crate::domain::{
+    ControlContext, ExtendedProcessorContext, HitInstructionReturnValue, MappingCompartment,
+    MappingControlContext, RealearnTarget, ReaperTarget, ReaperTargetType, TargetCharacter,
+    TargetTypeDef, UnresolvedReaperTargetDef, DEFAULT_TARGET,
+};
+use helgoboss_learn::{AbsoluteValue, ControlType, ControlValue, Target};
+use helgoboss_midi::U14;
+use reaper_high::{Project, Reaper};
+use reaper_medium::{ActionValueChange, CommandId, WindowContext};
+use std::ffi::CString;
+use std::path::PathBuf;
+
+/// Section of the (non-persistent) global ext state which scripts can read the control value from.
+pub const RUN_SCRIPT_EXT_STATE_SECTION: &str = "ReaLearn";
+/// Normalized control value, formatted as decimal number between 0.0 and 1.0.
+pub const RUN_SCRIPT_EXT_STATE_KEY_CONTROL_VALUE: &str = "control_value";
+/// Discrete control value (integer) or empty if the control value is continuous.
+pub const RUN_SCRIPT_EXT_STATE_KEY_DISCRETE_CONTROL_VALUE: &str = "discrete_control_value";
+
+#[derive(Debug)]
+pub struct UnresolvedRunScriptTarget {
+    pub script_path: PathBuf,
+}
+
+impl UnresolvedReaperTargetDef for UnresolvedRunScriptTarget {
+    fn resolve(
+        &self,
+        context: ExtendedProcessorContext,
+        _: MappingCompartment,
+    ) -> Result<Vec<ReaperTarget>, &'static str> {
+        if self.script_path.as_os_str().is_empty() {
+            return Err("no script set");
+        }
+        // Checked here and not in `is_available()` because that's queried very often.
+        if !self.script_path.exists() {
+            return Err("script not found");
+        }
+        Ok(vec![ReaperTarget::RunScript(RunScriptTarget {
+            project: context.context().project_or_current_project(),
+            script_path: self.script_path.clone(),
+            command_id: None,
+        })])
+    }
+}
+
+/// Runs a ReaScript (Lua, EEL or Python) and passes the control value to it.
+///
+/// The script gets registered in the main action section on first invocation (without
+/// persisting the registration). The control value is passed in two ways: As MIDI-like action
+/// value (readable via `get_action_context()`) and via the global ext state.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RunScriptTarget {
+    pub project: Project,
+    pub script_path: PathBuf,
+    /// Cached command ID of the registered script.
+    command_id: Option<CommandId>,
+}
+
+impl RunScriptTarget {
+    fn ensure_script_registered(&mut self) -> Result<CommandId, &'static str> {
+        if let Some(id) = self.command_id {
+            return Ok(id);
+        }
+        let path = self
+            .script_path
+            .to_str()
+            .ok_or("script path is not valid UTF-8")?;
+        let path = CString::new(path).map_err(|_| "invalid script path")?;
+        let raw_id = unsafe {
+            Reaper::get()
+                .medium_reaper()
+                .low()
+                .AddRemoveReaScript(true, 0, path.as_ptr(), false)
+        };
+        if raw_id <= 0 {
+            return Err("couldn't load script");
+        }
+        let id = CommandId::new(raw_id as u32);
+        self.command_id = Some(id);
+        Ok(id)
+    }
+}
+
+impl RealearnTarget for RunScriptTarget {
+    fn control_type_and_character(&self, _: ControlContext) -> (ControlType, TargetCharacter) {
+        (
+            ControlType::AbsoluteContinuousRetriggerable,
+            TargetCharacter::Continuous,
+        )
+    }
+
+    fn hit(
+        &mut self,
+        value: ControlValue,
+        _: MappingControlContext,
+    ) -> Result<HitInstructionReturnValue, &'static str> {
+        let (normalized, discrete) = match value {
+            ControlValue::AbsoluteContinuous(v) => (v, None),
+            ControlValue::AbsoluteDiscrete(f) => (f.to_unit_value(), Some(f.actual())),
+            ControlValue::Relative(_) => return Err("relative values not supported"),
+        };
+        let command_id = self.ensure_script_registered()?;
+        set_ext_state(
+            RUN_SCRIPT_EXT_STATE_KEY_CONTROL_VALUE,
+            &format!("{:.4}", normalized.get()),
+        )?;
+        set_ext_state(
+            RUN_SCRIPT_EXT_STATE_KEY_DISCRETE_CONTROL_VALUE,
+            &discrete.map(|d| d.to_string()).unwrap_or_default(),
+        )?;
+        let high_res_value = U14::new((normalized.get() * U14::MAX.get() as f64).round() as u16);
+        Reaper::get()
+            .main_section()
+            .action_by_command_id(command_id)
+            .invoke_directly(
+                ActionValueChange::AbsoluteHighRes(high_res_value),
+                WindowContext::Win(Reaper::get().main_window()),
+                self.project.context(),
+            );
+        Ok(None)
+    }
+
+    fn is_available(&self, _: ControlContext) -> bool {
+        self.project.is_available()
+    }
+
+    fn project(&self) -> Option<Project> {
+        Some(self.project)
+    }
+
+    fn supports_automatic_feedback(&self) -> bool {
+        false
+    }
+
+    fn reaper_target_type(&self) -> Option<ReaperTargetType> {
+        Some(ReaperTargetType::RunScript)
+    }
+}
+
+impl<'a> Target<'a> for RunScriptTarget {
+    type Context = ControlContext<'a>;
+
+    fn current_value(&self, _: Self::Context) -> Option<AbsoluteValue> {
+        None
+    }
+
+    fn control_type(&self, context: Self::Context) -> ControlType {
+        self.control_type_and_character(context).0
+    }
+}
+
+pub const RUN_SCRIPT_TARGET: TargetTypeDef = TargetTypeDef {
+    name: "Project: Run ReaScript",
+    short_name: "Run script",
+    hint: "Passes control value to script",
+    supports_feedback: false,
+    ..DEFAULT_TARGET
+};
+
+fn set_ext_state(key: &str, value: &str) -> Result<(), &'static str> {
+    let section = CString::new(RUN_SCRIPT_EXT_STATE_SECTION).unwrap();
+    let key = CString::new(key).unwrap();
+    let value = CString::new(value).map_err(|_| "invalid ext state value")?;
+    unsafe {
+        Reaper::get().medium_reaper().low().SetExtState(
+            section.as_ptr(),
+            key.as_ptr(),
+            value.as_ptr(),
+            false,
+        );
+    }
+    Ok(())
+}
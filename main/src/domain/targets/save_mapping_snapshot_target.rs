@@ -0,0 +1,132 @@
+use crate::domain::{
+    ControlContext, ExtendedProcessorContext, HitInstruction, HitInstructionContext,
+    HitInstructionReturnValue, MappingCompartment, MappingControlContext, MappingControlResult,
+    MappingSnapshot, RealearnTarget, ReaperTarget, ReaperTargetType, TagScope, TargetCharacter,
+    TargetTypeDef, UnresolvedReaperTargetDef, DEFAULT_TARGET,
+};
+use helgoboss_learn::{AbsoluteValue, ControlType, ControlValue, Target};
+use std::collections::HashMap;
+
+#[derive(Debug)]
+pub struct UnresolvedSaveMappingSnapshotTarget {
+    pub scope: TagScope,
+    pub active_mappings_only: bool,
+    pub snapshot_id: String,
+}
+
+impl UnresolvedReaperTargetDef for UnresolvedSaveMappingSnapshotTarget {
+    fn resolve(
+        &self,
+        _: ExtendedProcessorContext,
+        _: MappingCompartment,
+    ) -> Result<Vec<ReaperTarget>, &'static str> {
+        Ok(vec![ReaperTarget::SaveMappingSnapshot(
+            SaveMappingSnapshotTarget {
+                scope: self.scope.clone(),
+                active_mappings_only: self.active_mappings_only,
+                snapshot_id: self.snapshot_id.clone(),
+            },
+        )])
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SaveMappingSnapshotTarget {
+    pub scope: TagScope,
+    pub active_mappings_only: bool,
+    pub snapshot_id: String,
+}
+
+impl RealearnTarget for SaveMappingSnapshotTarget {
+    fn reaper_target_type(&self) -> Option<ReaperTargetType> {
+        Some(ReaperTargetType::SaveMappingSnapshot)
+    }
+
+    fn control_type_and_character(&self, _: ControlContext) -> (ControlType, TargetCharacter) {
+        (
+            ControlType::AbsoluteContinuousRetriggerable,
+            TargetCharacter::Trigger,
+        )
+    }
+
+    fn hit(
+        &mut self,
+        value: ControlValue,
+        _: MappingControlContext,
+    ) -> Result<HitInstructionReturnValue, &'static str> {
+        if value.to_unit_value()?.is_zero() {
+            return Ok(None);
+        }
+        struct SaveMappingSnapshotInstruction {
+            scope: TagScope,
+            active_mappings_only: bool,
+            snapshot_id: String,
+        }
+        impl HitInstruction for SaveMappingSnapshotInstruction {
+            fn execute(
+                self: Box<Self>,
+                context: HitInstructionContext,
+            ) -> Vec<MappingControlResult> {
+                let compartment = match context.mappings.values().next() {
+                    None => return vec![],
+                    Some(m) => m.compartment(),
+                };
+                let values: HashMap<_, _> = context
+                    .mappings
+                    .values()
+                    .filter(|m| {
+                        // Same filter as the one used when loading a snapshot, so that saving and
+                        // loading with the same settings refer to the same mappings.
+                        m.control_is_enabled()
+                            && (!self.scope.has_tags() || m.has_any_tag(&self.scope.tags))
+                            && (!self.active_mappings_only || m.is_effectively_on())
+                    })
+                    .filter_map(|m| {
+                        let value = m.current_aggregated_target_value(context.control_context)?;
+                        Some((m.id(), value))
+                    })
+                    .collect();
+                context
+                    .control_context
+                    .instance_state
+                    .borrow_mut()
+                    .mapping_snapshots_mut()
+                    .save_snapshot(compartment, self.snapshot_id, MappingSnapshot::new(values));
+                vec![]
+            }
+        }
+        let instruction = SaveMappingSnapshotInstruction {
+            scope: self.scope.clone(),
+            active_mappings_only: self.active_mappings_only,
+            snapshot_id: self.snapshot_id.clone(),
+        };
+        Ok(Some(Box::new(instruction)))
+    }
+
+    fn can_report_current_value(&self) -> bool {
+        false
+    }
+
+    fn is_available(&self, _: ControlContext) -> bool {
+        true
+    }
+}
+
+impl<'a> Target<'a> for SaveMappingSnapshotTarget {
+    type Context = ControlContext<'a>;
+
+    fn current_value(&self, _: Self::Context) -> Option<AbsoluteValue> {
+        None
+    }
+
+    fn control_type(&self, context: Self::Context) -> ControlType {
+        self.control_type_and_character(context).0
+    }
+}
+
+pub const SAVE_MAPPING_SNAPSHOT_TARGET: TargetTypeDef = TargetTypeDef {
+    name: "ReaLearn: Save mapping snapshot",
+    short_name: "Save mapping snapshot",
+    supports_tags: true,
+    ..DEFAULT_TARGET
+};
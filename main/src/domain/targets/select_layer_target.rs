@@ -0,0 +1,121 @@
+use crate::domain::{
+    format_value_as_on_off, CompoundChangeEvent, ControlContext, ExtendedProcessorContext,
+    HitInstructionReturnValue, InstanceStateChanged, LayerId, MappingCompartment,
+    MappingControlContext, RealearnTarget, ReaperTarget, ReaperTargetType, TargetCharacter,
+    TargetTypeDef, UnresolvedReaperTargetDef, DEFAULT_TARGET,
+};
+use helgoboss_learn::{AbsoluteValue, ControlType, ControlValue, Target, UnitValue};
+
+#[derive(Debug)]
+pub struct UnresolvedSelectLayerTarget {
+    pub compartment: MappingCompartment,
+    pub layer: LayerId,
+}
+
+impl UnresolvedReaperTargetDef for UnresolvedSelectLayerTarget {
+    fn resolve(
+        &self,
+        _: ExtendedProcessorContext,
+        _: MappingCompartment,
+    ) -> Result<Vec<ReaperTarget>, &'static str> {
+        Ok(vec![ReaperTarget::SelectLayer(SelectLayerTarget {
+            compartment: self.compartment,
+            layer: self.layer,
+        })])
+    }
+}
+
+/// Makes the given layer the active one in its compartment.
+///
+/// Switching off returns to the base layer (if this layer is still the active one), so a
+/// momentary button works like a shift key.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SelectLayerTarget {
+    /// This must always correspond to the compartment of the containing mapping, otherwise it will
+    /// lead to strange behavior.
+    pub compartment: MappingCompartment,
+    pub layer: LayerId,
+}
+
+impl RealearnTarget for SelectLayerTarget {
+    fn control_type_and_character(&self, _: ControlContext) -> (ControlType, TargetCharacter) {
+        (
+            ControlType::AbsoluteContinuousRetriggerable,
+            TargetCharacter::Switch,
+        )
+    }
+
+    fn format_value(&self, value: UnitValue, _: ControlContext) -> String {
+        format_value_as_on_off(value).to_string()
+    }
+
+    fn hit(
+        &mut self,
+        value: ControlValue,
+        context: MappingControlContext,
+    ) -> Result<HitInstructionReturnValue, &'static str> {
+        let is_on = !value.to_unit_value()?.is_zero();
+        let mut instance_state = context.control_context.instance_state.borrow_mut();
+        if is_on {
+            instance_state.set_active_layer(self.compartment, self.layer);
+        } else if instance_state.active_layer(self.compartment) == self.layer {
+            instance_state.set_active_layer(self.compartment, LayerId::default());
+        }
+        Ok(None)
+    }
+
+    fn is_available(&self, _: ControlContext) -> bool {
+        true
+    }
+
+    fn process_change_event(
+        &self,
+        evt: CompoundChangeEvent,
+        _: ControlContext,
+    ) -> (bool, Option<AbsoluteValue>) {
+        match evt {
+            CompoundChangeEvent::Instance(InstanceStateChanged::ActiveLayer {
+                compartment,
+                ..
+            }) if *compartment == self.compartment => (true, None),
+            _ => (false, None),
+        }
+    }
+
+    fn text_value(&self, context: ControlContext) -> Option<String> {
+        Some(format_value_as_on_off(self.current_value(context)?.to_unit_value()).to_string())
+    }
+
+    fn reaper_target_type(&self) -> Option<ReaperTargetType> {
+        Some(ReaperTargetType::SelectLayer)
+    }
+}
+
+impl<'a> Target<'a> for SelectLayerTarget {
+    type Context = ControlContext<'a>;
+
+    fn current_value(&self, context: Self::Context) -> Option<AbsoluteValue> {
+        let is_active = context
+            .instance_state
+            .borrow()
+            .active_layer(self.compartment)
+            == self.layer;
+        let uv = if is_active {
+            UnitValue::MAX
+        } else {
+            UnitValue::MIN
+        };
+        Some(AbsoluteValue::Continuous(uv))
+    }
+
+    fn control_type(&self, context: Self::Context) -> ControlType {
+        self.control_type_and_character(context).0
+    }
+}
+
+pub const SELECT_LAYER_TARGET: TargetTypeDef = TargetTypeDef {
+    name: "ReaLearn: Select layer",
+    short_name: "Select layer",
+    hint: "Layer 0 = base layer",
+    ..DEFAULT_TARGET
+};
@@ -0,0 +1,115 @@
+use crate::domain::{
+    format_value_as_on_off, CompoundChangeEvent, ControlContext, ExtendedProcessorContext,
+    HitInstructionReturnValue, InstanceStateChanged, MappingCompartment, MappingControlContext,
+    RealearnTarget, ReaperTarget, ReaperTargetType, TargetCharacter, TargetTypeDef,
+    UnresolvedReaperTargetDef, DEFAULT_TARGET,
+};
+use helgoboss_learn::{AbsoluteValue, ControlType, ControlValue, Target, UnitValue};
+
+#[derive(Debug)]
+pub struct UnresolvedSetBankOffsetTarget {
+    pub offset: u32,
+}
+
+impl UnresolvedReaperTargetDef for UnresolvedSetBankOffsetTarget {
+    fn resolve(
+        &self,
+        _: ExtendedProcessorContext,
+        _: MappingCompartment,
+    ) -> Result<Vec<ReaperTarget>, &'static str> {
+        Ok(vec![ReaperTarget::SetBankOffset(SetBankOffsetTarget {
+            offset: self.offset,
+        })])
+    }
+}
+
+/// Shifts all indexed virtual control elements by the given offset, e.g. so that controller
+/// mappings for "Multi 1" to "Multi 8" control main mappings for "Multi 9" to "Multi 16".
+///
+/// Switching off returns to offset 0 (if this offset is still the active one).
+#[derive(Clone, Debug, PartialEq)]
+pub struct SetBankOffsetTarget {
+    pub offset: u32,
+}
+
+impl RealearnTarget for SetBankOffsetTarget {
+    fn control_type_and_character(&self, _: ControlContext) -> (ControlType, TargetCharacter) {
+        (
+            ControlType::AbsoluteContinuousRetriggerable,
+            TargetCharacter::Switch,
+        )
+    }
+
+    fn format_value(&self, value: UnitValue, _: ControlContext) -> String {
+        format_value_as_on_off(value).to_string()
+    }
+
+    fn hit(
+        &mut self,
+        value: ControlValue,
+        context: MappingControlContext,
+    ) -> Result<HitInstructionReturnValue, &'static str> {
+        let is_on = !value.to_unit_value()?.is_zero();
+        let mut instance_state = context.control_context.instance_state.borrow_mut();
+        if is_on {
+            instance_state.set_virtual_control_element_offset(self.offset);
+        } else if instance_state.virtual_control_element_offset() == self.offset {
+            instance_state.set_virtual_control_element_offset(0);
+        }
+        Ok(None)
+    }
+
+    fn is_available(&self, _: ControlContext) -> bool {
+        true
+    }
+
+    fn process_change_event(
+        &self,
+        evt: CompoundChangeEvent,
+        _: ControlContext,
+    ) -> (bool, Option<AbsoluteValue>) {
+        match evt {
+            CompoundChangeEvent::Instance(InstanceStateChanged::VirtualControlElementOffset {
+                ..
+            }) => (true, None),
+            _ => (false, None),
+        }
+    }
+
+    fn text_value(&self, context: ControlContext) -> Option<String> {
+        Some(format_value_as_on_off(self.current_value(context)?.to_unit_value()).to_string())
+    }
+
+    fn reaper_target_type(&self) -> Option<ReaperTargetType> {
+        Some(ReaperTargetType::SetBankOffset)
+    }
+}
+
+impl<'a> Target<'a> for SetBankOffsetTarget {
+    type Context = ControlContext<'a>;
+
+    fn current_value(&self, context: Self::Context) -> Option<AbsoluteValue> {
+        let is_active = context
+            .instance_state
+            .borrow()
+            .virtual_control_element_offset()
+            == self.offset;
+        let uv = if is_active {
+            UnitValue::MAX
+        } else {
+            UnitValue::MIN
+        };
+        Some(AbsoluteValue::Continuous(uv))
+    }
+
+    fn control_type(&self, context: Self::Context) -> ControlType {
+        self.control_type_and_character(context).0
+    }
+}
+
+pub const SET_BANK_OFFSET_TARGET: TargetTypeDef = TargetTypeDef {
+    name: "ReaLearn: Set bank offset",
+    short_name: "Set bank offset",
+    hint: "Shifts indexed virtual control elements",
+    ..DEFAULT_TARGET
+};
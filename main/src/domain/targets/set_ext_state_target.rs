@@ -0,0 +1,268 @@
+use crate::domain::{
+    ControlContext, ExtendedProcessorContext, FeedbackResolution, HitInstructionReturnValue,
+    MappingCompartment, MappingControlContext, RealearnTarget, ReaperTarget, ReaperTargetType,
+    TargetCharacter, TargetTypeDef, UnresolvedReaperTargetDef, DEFAULT_TARGET,
+};
+use derive_more::Display;
+use enum_iterator::IntoEnumIterator;
+use helgoboss_learn::{AbsoluteValue, ControlType, ControlValue, Target, UnitValue};
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+use reaper_high::{Project, Reaper};
+use serde::{Deserialize, Serialize};
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+
+/// Maximum length of ext state values which can be read for feedback.
+const EXT_STATE_BUFFER_SIZE: usize = 1024;
+
+#[derive(Debug)]
+pub struct UnresolvedSetExtStateTarget {
+    pub section: String,
+    pub key: String,
+    pub format: ExtStateValueFormat,
+    pub feedback_resolution: FeedbackResolution,
+}
+
+impl UnresolvedReaperTargetDef for UnresolvedSetExtStateTarget {
+    fn resolve(
+        &self,
+        context: ExtendedProcessorContext,
+        _: MappingCompartment,
+    ) -> Result<Vec<ReaperTarget>, &'static str> {
+        if self.section.is_empty() || self.key.is_empty() {
+            return Err("ext state section or key not set");
+        }
+        let project = context.context().project_or_current_project();
+        Ok(vec![ReaperTarget::SetExtState(SetExtStateTarget {
+            project,
+            section: self.section.clone(),
+            key: self.key.clone(),
+            format: self.format,
+        })])
+    }
+
+    fn feedback_resolution(&self) -> Option<FeedbackResolution> {
+        // REAPER doesn't notify us when a script changes the ext state.
+        Some(self.feedback_resolution)
+    }
+}
+
+/// Writes the control value into the project ext state, e.g. for being picked up by a ReaScript.
+///
+/// Feedback reads the ext state, so values written by scripts are reflected as well.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SetExtStateTarget {
+    pub project: Project,
+    pub section: String,
+    pub key: String,
+    pub format: ExtStateValueFormat,
+}
+
+/// How the control value is written to (and read from) the ext state.
+#[derive(
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    Debug,
+    Serialize,
+    Deserialize,
+    IntoEnumIterator,
+    TryFromPrimitive,
+    IntoPrimitive,
+    Display,
+)]
+#[repr(usize)]
+pub enum ExtStateValueFormat {
+    /// E.g. "0.7500"
+    #[serde(rename = "normalized")]
+    #[display(fmt = "Normalized (0.0 - 1.0)")]
+    Normalized,
+    /// E.g. "75.00"
+    #[serde(rename = "percent")]
+    #[display(fmt = "Percent (0 - 100)")]
+    Percent,
+}
+
+impl Default for ExtStateValueFormat {
+    fn default() -> Self {
+        ExtStateValueFormat::Normalized
+    }
+}
+
+impl ExtStateValueFormat {
+    pub fn format(self, value: UnitValue) -> String {
+        use ExtStateValueFormat::*;
+        match self {
+            Normalized => format!("{:.4}", value.get()),
+            Percent => format!("{:.2}", value.get() * 100.0),
+        }
+    }
+
+    /// Returns `None` if the text is not a finite number. Numbers out of range are clamped.
+    pub fn parse(self, text: &str) -> Option<UnitValue> {
+        use ExtStateValueFormat::*;
+        let number: f64 = text.trim().parse().ok()?;
+        if !number.is_finite() {
+            return None;
+        }
+        let normalized = match self {
+            Normalized => number,
+            Percent => number / 100.0,
+        };
+        Some(UnitValue::new_clamped(normalized))
+    }
+}
+
+impl SetExtStateTarget {
+    fn read(&self) -> Option<String> {
+        get_proj_ext_state(self.project, &self.section, &self.key)
+    }
+}
+
+impl RealearnTarget for SetExtStateTarget {
+    fn control_type_and_character(&self, _: ControlContext) -> (ControlType, TargetCharacter) {
+        (ControlType::AbsoluteContinuous, TargetCharacter::Continuous)
+    }
+
+    fn hit(
+        &mut self,
+        value: ControlValue,
+        _: MappingControlContext,
+    ) -> Result<HitInstructionReturnValue, &'static str> {
+        let text = self.format.format(value.to_unit_value()?);
+        set_proj_ext_state(self.project, &self.section, &self.key, &text)?;
+        Ok(None)
+    }
+
+    fn is_available(&self, _: ControlContext) -> bool {
+        self.project.is_available()
+    }
+
+    fn project(&self) -> Option<Project> {
+        Some(self.project)
+    }
+
+    fn text_value(&self, _: ControlContext) -> Option<String> {
+        self.read()
+    }
+
+    fn reaper_target_type(&self) -> Option<ReaperTargetType> {
+        Some(ReaperTargetType::SetExtState)
+    }
+}
+
+impl<'a> Target<'a> for SetExtStateTarget {
+    type Context = ControlContext<'a>;
+
+    fn current_value(&self, _: Self::Context) -> Option<AbsoluteValue> {
+        let value = self.format.parse(&self.read()?)?;
+        Some(AbsoluteValue::Continuous(value))
+    }
+
+    fn control_type(&self, context: Self::Context) -> ControlType {
+        self.control_type_and_character(context).0
+    }
+}
+
+pub const SET_EXT_STATE_TARGET: TargetTypeDef = TargetTypeDef {
+    name: "Project: Set ext state",
+    short_name: "Set ext state",
+    hint: "For exchanging values with ReaScripts",
+    supports_feedback_resolution: true,
+    ..DEFAULT_TARGET
+};
+
+/// Returns `None` if the key doesn't exist or its value is empty.
+fn get_proj_ext_state(project: Project, section: &str, key: &str) -> Option<String> {
+    let section = CString::new(section).ok()?;
+    let key = CString::new(key).ok()?;
+    let mut buffer = vec![0 as c_char; EXT_STATE_BUFFER_SIZE];
+    let len = unsafe {
+        Reaper::get().medium_reaper().low().GetProjExtState(
+            project.raw().as_ptr(),
+            section.as_ptr(),
+            key.as_ptr(),
+            buffer.as_mut_ptr(),
+            buffer.len() as _,
+        )
+    };
+    if len <= 0 {
+        return None;
+    }
+    let value = unsafe { CStr::from_ptr(buffer.as_ptr()) };
+    Some(value.to_string_lossy().into_owned())
+}
+
+fn set_proj_ext_state(
+    project: Project,
+    section: &str,
+    key: &str,
+    value: &str,
+) -> Result<(), &'static str> {
+    let section = CString::new(section).map_err(|_| "invalid ext state section")?;
+    let key = CString::new(key).map_err(|_| "invalid ext state key")?;
+    let value = CString::new(value).map_err(|_| "invalid ext state value")?;
+    unsafe {
+        Reaper::get().medium_reaper().low().SetProjExtState(
+            project.raw().as_ptr(),
+            section.as_ptr(),
+            key.as_ptr(),
+            value.as_ptr(),
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        for format in ExtStateValueFormat::into_enum_iter() {
+            // Given
+            let value = UnitValue::new(0.75);
+            // When
+            let parsed = format.parse(&format.format(value));
+            // Then
+            assert_eq!(parsed, Some(value), "{}", format);
+        }
+    }
+
+    #[test]
+    fn format_values() {
+        // Given
+        let value = UnitValue::new(0.75);
+        // When
+        let normalized = ExtStateValueFormat::Normalized.format(value);
+        let percent = ExtStateValueFormat::Percent.format(value);
+        // Then
+        assert_eq!(normalized, "0.7500");
+        assert_eq!(percent, "75.00");
+    }
+
+    #[test]
+    fn clamp_out_of_range_values() {
+        // Given
+        let format = ExtStateValueFormat::Percent;
+        // When
+        let too_high = format.parse("150");
+        let too_low = format.parse(" -5 ");
+        // Then
+        assert_eq!(too_high, Some(UnitValue::MAX));
+        assert_eq!(too_low, Some(UnitValue::MIN));
+    }
+
+    #[test]
+    fn reject_invalid_input() {
+        for text in &["", "  ", "abc", "0,5", "NaN", "inf"] {
+            // Given
+            let format = ExtStateValueFormat::Normalized;
+            // When
+            let parsed = format.parse(text);
+            // Then
+            assert_eq!(parsed, None, "{:?}", text);
+        }
+    }
+}
@@ -0,0 +1,291 @@
+use crate::domain::{
+    format_value_as_on_off, transport_is_enabled_unit_value, ControlContext,
+    ExtendedProcessorContext, FeedbackResolution, HitInstructionReturnValue, MappingCompartment,
+    MappingControlContext, RealearnTarget, ReaperTarget, ReaperTargetType, TargetCharacter,
+    TargetTypeDef, UnresolvedReaperTargetDef, DEFAULT_TARGET,
+};
+use derive_more::Display;
+use enum_iterator::IntoEnumIterator;
+use helgoboss_learn::{AbsoluteValue, ControlType, ControlValue, NumericValue, Target, UnitValue};
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+use reaper_high::Project;
+use reaper_medium::{AutoSeekBehavior, GetLoopTimeRange2Result, PositionInSeconds};
+use serde::{Deserialize, Serialize};
+
+#[derive(
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    Debug,
+    Serialize,
+    Deserialize,
+    IntoEnumIterator,
+    TryFromPrimitive,
+    IntoPrimitive,
+    Display,
+)]
+#[repr(usize)]
+pub enum TimeSelectionAction {
+    #[serde(rename = "setStart")]
+    #[display(fmt = "Set start")]
+    SetStart,
+    #[serde(rename = "setEnd")]
+    #[display(fmt = "Set end")]
+    SetEnd,
+    #[serde(rename = "clear")]
+    #[display(fmt = "Clear")]
+    Clear,
+    #[serde(rename = "repeat")]
+    #[display(fmt = "Repeat")]
+    Repeat,
+}
+
+impl Default for TimeSelectionAction {
+    fn default() -> Self {
+        TimeSelectionAction::SetStart
+    }
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    Debug,
+    Serialize,
+    Deserialize,
+    IntoEnumIterator,
+    TryFromPrimitive,
+    IntoPrimitive,
+    Display,
+)]
+#[repr(usize)]
+pub enum TimeRangeKind {
+    #[serde(rename = "timeSelection")]
+    #[display(fmt = "Time selection")]
+    TimeSelection,
+    #[serde(rename = "loopPoints")]
+    #[display(fmt = "Loop points")]
+    LoopPoints,
+}
+
+impl Default for TimeRangeKind {
+    fn default() -> Self {
+        TimeRangeKind::TimeSelection
+    }
+}
+
+#[derive(Debug)]
+pub struct UnresolvedTimeSelectionTarget {
+    pub action: TimeSelectionAction,
+    pub range_kind: TimeRangeKind,
+}
+
+impl UnresolvedReaperTargetDef for UnresolvedTimeSelectionTarget {
+    fn resolve(
+        &self,
+        context: ExtendedProcessorContext,
+        _: MappingCompartment,
+    ) -> Result<Vec<ReaperTarget>, &'static str> {
+        Ok(vec![ReaperTarget::TimeSelection(TimeSelectionTarget {
+            project: context.context().project_or_current_project(),
+            action: self.action,
+            range_kind: self.range_kind,
+        })])
+    }
+
+    fn feedback_resolution(&self) -> Option<FeedbackResolution> {
+        // Time selection and loop points don't fire change events. Poll as frequently as
+        // possible so that scrubbing the start/end gives immediate feedback.
+        Some(FeedbackResolution::High)
+    }
+}
+
+/// Manipulates the time selection or loop points of a project.
+///
+/// Start and end are mapped linearly onto the whole project length.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TimeSelectionTarget {
+    pub project: Project,
+    pub action: TimeSelectionAction,
+    pub range_kind: TimeRangeKind,
+}
+
+impl TimeSelectionTarget {
+    fn range(&self) -> Option<GetLoopTimeRange2Result> {
+        match self.range_kind {
+            TimeRangeKind::TimeSelection => self.project.time_selection(),
+            TimeRangeKind::LoopPoints => self.project.loop_points(),
+        }
+    }
+
+    fn set_range(&self, start: PositionInSeconds, end: PositionInSeconds) {
+        match self.range_kind {
+            TimeRangeKind::TimeSelection => self.project.set_time_selection(start, end),
+            TimeRangeKind::LoopPoints => {
+                self.project
+                    .set_loop_points(start, end, AutoSeekBehavior::DenyAutoSeek)
+            }
+        }
+    }
+
+    fn project_length(&self) -> Option<f64> {
+        let length = self.project.length().get();
+        if length > 0.0 {
+            Some(length)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the start or end position (depending on the action).
+    fn current_position(&self) -> Option<PositionInSeconds> {
+        let range = self.range()?;
+        match self.action {
+            TimeSelectionAction::SetStart => Some(range.start),
+            TimeSelectionAction::SetEnd => Some(range.end),
+            TimeSelectionAction::Clear | TimeSelectionAction::Repeat => None,
+        }
+    }
+
+    fn convert_unit_value_to_position(&self, value: UnitValue) -> Option<PositionInSeconds> {
+        let length = self.project_length()?;
+        Some(PositionInSeconds::new(value.get() * length))
+    }
+
+    fn is_position_action(&self) -> bool {
+        matches!(
+            self.action,
+            TimeSelectionAction::SetStart | TimeSelectionAction::SetEnd
+        )
+    }
+}
+
+impl RealearnTarget for TimeSelectionTarget {
+    fn control_type_and_character(&self, _: ControlContext) -> (ControlType, TargetCharacter) {
+        use TimeSelectionAction::*;
+        match self.action {
+            SetStart | SetEnd => (ControlType::AbsoluteContinuous, TargetCharacter::Continuous),
+            Clear => (
+                ControlType::AbsoluteContinuousRetriggerable,
+                TargetCharacter::Trigger,
+            ),
+            Repeat => (ControlType::AbsoluteContinuous, TargetCharacter::Switch),
+        }
+    }
+
+    fn format_value(&self, value: UnitValue, _: ControlContext) -> String {
+        if self.is_position_action() {
+            match self.convert_unit_value_to_position(value) {
+                None => "-".to_owned(),
+                Some(pos) => format!("{:.3} s", pos.get()),
+            }
+        } else {
+            format_value_as_on_off(value).to_string()
+        }
+    }
+
+    fn hit(
+        &mut self,
+        value: ControlValue,
+        _: MappingControlContext,
+    ) -> Result<HitInstructionReturnValue, &'static str> {
+        use TimeSelectionAction::*;
+        let value = value.to_unit_value()?;
+        match self.action {
+            SetStart => {
+                let start = self
+                    .convert_unit_value_to_position(value)
+                    .ok_or("project is empty")?;
+                let end = match self.range() {
+                    Some(r) if r.end > start => r.end,
+                    // No end after the new start yet. Extend until the end of the project.
+                    _ => PositionInSeconds::new(self.project.length().get()),
+                };
+                self.set_range(start, end);
+            }
+            SetEnd => {
+                let end = self
+                    .convert_unit_value_to_position(value)
+                    .ok_or("project is empty")?;
+                let start = match self.range() {
+                    Some(r) if r.start < end => r.start,
+                    // No start before the new end yet. Start at the beginning of the project.
+                    _ => PositionInSeconds::new(0.0),
+                };
+                self.set_range(start, end);
+            }
+            Clear => {
+                if !value.is_zero() {
+                    let zero = PositionInSeconds::new(0.0);
+                    self.set_range(zero, zero);
+                }
+            }
+            Repeat => {
+                if value.is_zero() {
+                    self.project.disable_repeat();
+                } else {
+                    self.project.enable_repeat();
+                }
+            }
+        };
+        Ok(None)
+    }
+
+    fn is_available(&self, _: ControlContext) -> bool {
+        self.project.is_available()
+    }
+
+    fn project(&self) -> Option<Project> {
+        Some(self.project)
+    }
+
+    fn text_value(&self, context: ControlContext) -> Option<String> {
+        if self.is_position_action() {
+            Some(format!("{:.3} s", self.current_position()?.get()))
+        } else {
+            Some(format_value_as_on_off(self.current_value(context)?.to_unit_value()).to_string())
+        }
+    }
+
+    fn numeric_value(&self, _: ControlContext) -> Option<NumericValue> {
+        Some(NumericValue::Decimal(self.current_position()?.get()))
+    }
+
+    fn numeric_value_unit(&self, _: ControlContext) -> &'static str {
+        "s"
+    }
+
+    fn reaper_target_type(&self) -> Option<ReaperTargetType> {
+        Some(ReaperTargetType::TimeSelection)
+    }
+}
+
+impl<'a> Target<'a> for TimeSelectionTarget {
+    type Context = ControlContext<'a>;
+
+    fn current_value(&self, _: Self::Context) -> Option<AbsoluteValue> {
+        use TimeSelectionAction::*;
+        let value = match self.action {
+            SetStart | SetEnd => {
+                let pos = self.current_position()?;
+                let length = self.project_length()?;
+                UnitValue::new_clamped(pos.get() / length)
+            }
+            Clear => transport_is_enabled_unit_value(self.range().is_some()),
+            Repeat => transport_is_enabled_unit_value(self.project.repeat_is_enabled()),
+        };
+        Some(AbsoluteValue::Continuous(value))
+    }
+
+    fn control_type(&self, context: Self::Context) -> ControlType {
+        self.control_type_and_character(context).0
+    }
+}
+
+pub const TIME_SELECTION_TARGET: TargetTypeDef = TargetTypeDef {
+    name: "Project: Set time selection",
+    short_name: "Time selection",
+    ..DEFAULT_TARGET
+};
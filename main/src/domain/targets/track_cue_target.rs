@@ -0,0 +1,171 @@
+use crate::domain::ui_util::{
+    format_value_as_db, format_value_as_db_without_unit, parse_value_from_db, volume_unit_value,
+};
+use crate::domain::{
+    get_effective_tracks, ControlContext, ExtendedProcessorContext, HitInstructionReturnValue,
+    MappingCompartment, MappingControlContext, RealearnTarget, ReaperTarget, ReaperTargetType,
+    TargetCharacter, TargetTypeDef, TrackDescriptor, UnresolvedReaperTargetDef, DEFAULT_TARGET,
+};
+use helgoboss_learn::{AbsoluteValue, ControlType, ControlValue, NumericValue, Target, UnitValue};
+use reaper_high::{Project, Track, Volume};
+
+#[derive(Debug)]
+pub struct UnresolvedTrackCueTarget {
+    pub track_descriptor: TrackDescriptor,
+    pub cue_output: u32,
+}
+
+impl UnresolvedReaperTargetDef for UnresolvedTrackCueTarget {
+    fn resolve(
+        &self,
+        context: ExtendedProcessorContext,
+        compartment: MappingCompartment,
+    ) -> Result<Vec<ReaperTarget>, &'static str> {
+        Ok(
+            get_effective_tracks(context, &self.track_descriptor.track, compartment)?
+                .into_iter()
+                .map(|track| {
+                    ReaperTarget::TrackCue(TrackCueTarget {
+                        track,
+                        cue_output: self.cue_output,
+                    })
+                })
+                .collect(),
+        )
+    }
+
+    fn track_descriptor(&self) -> Option<&TrackDescriptor> {
+        Some(&self.track_descriptor)
+    }
+}
+
+/// Pre-listens a track on the cue output (PFL-style) via a temporary hardware output send.
+///
+/// The target value is the level of the cue send. Zero means the track is not cued.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TrackCueTarget {
+    pub track: Track,
+    pub cue_output: u32,
+}
+
+impl RealearnTarget for TrackCueTarget {
+    fn control_type_and_character(&self, _: ControlContext) -> (ControlType, TargetCharacter) {
+        (ControlType::AbsoluteContinuous, TargetCharacter::Continuous)
+    }
+
+    fn parse_as_value(&self, text: &str, _: ControlContext) -> Result<UnitValue, &'static str> {
+        parse_value_from_db(text)
+    }
+
+    fn format_value_without_unit(&self, value: UnitValue, _: ControlContext) -> String {
+        format_value_as_db_without_unit(value)
+    }
+
+    fn hide_formatted_value(&self, _: ControlContext) -> bool {
+        true
+    }
+
+    fn hide_formatted_step_size(&self, _: ControlContext) -> bool {
+        true
+    }
+
+    fn value_unit(&self, _: ControlContext) -> &'static str {
+        "dB"
+    }
+
+    fn format_value(&self, value: UnitValue, _: ControlContext) -> String {
+        format_value_as_db(value)
+    }
+
+    fn hit(
+        &mut self,
+        value: ControlValue,
+        context: MappingControlContext,
+    ) -> Result<HitInstructionReturnValue, &'static str> {
+        let volume = cue_volume(value.to_unit_value()?);
+        context
+            .control_context
+            .instance_state
+            .borrow_mut()
+            .cue_sends_mut()
+            .cue(
+                context.mapping_data.mapping_id,
+                &self.track,
+                self.cue_output,
+                volume,
+            )?;
+        Ok(None)
+    }
+
+    fn is_available(&self, _: ControlContext) -> bool {
+        self.track.is_available()
+    }
+
+    fn project(&self) -> Option<Project> {
+        Some(self.track.project())
+    }
+
+    fn track(&self) -> Option<&Track> {
+        Some(&self.track)
+    }
+
+    /// The cue state is attached to the mappings, so feedback is sent right after controlling.
+    fn supports_automatic_feedback(&self) -> bool {
+        false
+    }
+
+    fn text_value(&self, context: ControlContext) -> Option<String> {
+        Some(self.volume(context)?.to_string())
+    }
+
+    fn numeric_value(&self, context: ControlContext) -> Option<NumericValue> {
+        Some(NumericValue::Decimal(self.volume(context)?.db().get()))
+    }
+
+    fn reaper_target_type(&self) -> Option<ReaperTargetType> {
+        Some(ReaperTargetType::TrackCue)
+    }
+}
+
+impl TrackCueTarget {
+    fn volume(&self, context: ControlContext) -> Option<Volume> {
+        context
+            .instance_state
+            .borrow()
+            .cue_sends()
+            .volume(&self.track, self.cue_output)
+    }
+}
+
+impl<'a> Target<'a> for TrackCueTarget {
+    type Context = ControlContext<'a>;
+
+    fn current_value(&self, context: Self::Context) -> Option<AbsoluteValue> {
+        let val = self
+            .volume(context)
+            .map(volume_unit_value)
+            .unwrap_or(UnitValue::MIN);
+        Some(AbsoluteValue::Continuous(val))
+    }
+
+    fn control_type(&self, context: Self::Context) -> ControlType {
+        self.control_type_and_character(context).0
+    }
+}
+
+/// Translates the target value into the level of the cue send, `None` meaning "don't cue".
+pub fn cue_volume(value: UnitValue) -> Option<Volume> {
+    if value.is_zero() {
+        return None;
+    }
+    Some(Volume::try_from_soft_normalized_value(value.get()).unwrap_or(Volume::MIN))
+}
+
+pub const TRACK_CUE_TARGET: TargetTypeDef = TargetTypeDef {
+    name: "Track: Cue",
+    short_name: "Cue",
+    hint: "Pre-listens the track on the cue output, 0 = off",
+    supports_track: true,
+    supports_cue_output: true,
+    ..DEFAULT_TARGET
+};
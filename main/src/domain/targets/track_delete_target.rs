@@ -0,0 +1,128 @@
+use crate::domain::{
+    get_effective_tracks, ControlContext, ExtendedProcessorContext, HitInstructionReturnValue,
+    MappingCompartment, MappingControlContext, RealearnTarget, ReaperTarget, ReaperTargetType,
+    TargetCharacter, TargetTypeDef, TrackDescriptor, UnresolvedReaperTargetDef, DEFAULT_TARGET,
+};
+use helgoboss_learn::{AbsoluteValue, ControlType, ControlValue, Target, UnitValue};
+use reaper_high::{Project, Track};
+use std::time::{Duration, Instant};
+
+/// If confirmation is required, the second press must follow the first one within this time.
+const CONFIRMATION_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Debug)]
+pub struct UnresolvedTrackDeleteTarget {
+    pub track_descriptor: TrackDescriptor,
+    pub require_confirmation: bool,
+}
+
+impl UnresolvedReaperTargetDef for UnresolvedTrackDeleteTarget {
+    fn resolve(
+        &self,
+        context: ExtendedProcessorContext,
+        compartment: MappingCompartment,
+    ) -> Result<Vec<ReaperTarget>, &'static str> {
+        Ok(
+            get_effective_tracks(context, &self.track_descriptor.track, compartment)?
+                .into_iter()
+                .map(|track| {
+                    ReaperTarget::TrackDelete(TrackDeleteTarget {
+                        track,
+                        require_confirmation: self.require_confirmation,
+                        first_press: None,
+                    })
+                })
+                .collect(),
+        )
+    }
+
+    fn track_descriptor(&self) -> Option<&TrackDescriptor> {
+        Some(&self.track_descriptor)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TrackDeleteTarget {
+    pub track: Track,
+    /// If `true`, the track is only deleted when pressing twice in a row. Showing a dialog
+    /// wouldn't help much because the user is probably not sitting in front of the computer.
+    pub require_confirmation: bool,
+    /// Time of the press which is waiting for confirmation.
+    pub first_press: Option<Instant>,
+}
+
+impl RealearnTarget for TrackDeleteTarget {
+    fn control_type_and_character(&self, _: ControlContext) -> (ControlType, TargetCharacter) {
+        (
+            ControlType::AbsoluteContinuousRetriggerable,
+            TargetCharacter::Trigger,
+        )
+    }
+
+    fn format_value(&self, _: UnitValue, _: ControlContext) -> String {
+        "".to_owned()
+    }
+
+    fn hit(
+        &mut self,
+        value: ControlValue,
+        _: MappingControlContext,
+    ) -> Result<HitInstructionReturnValue, &'static str> {
+        if value.to_unit_value()?.is_zero() {
+            return Ok(None);
+        }
+        if self.track.is_master_track() {
+            return Err("master track can't be deleted");
+        }
+        if self.require_confirmation {
+            let now = Instant::now();
+            let is_confirmed = matches!(
+                self.first_press,
+                Some(t) if now.saturating_duration_since(t) <= CONFIRMATION_TIMEOUT
+            );
+            if !is_confirmed {
+                self.first_press = Some(now);
+                return Ok(None);
+            }
+            self.first_press = None;
+        }
+        self.track.project().remove_track(&self.track);
+        Ok(None)
+    }
+
+    fn is_available(&self, _: ControlContext) -> bool {
+        self.track.is_available()
+    }
+
+    fn project(&self) -> Option<Project> {
+        Some(self.track.project())
+    }
+
+    fn track(&self) -> Option<&Track> {
+        Some(&self.track)
+    }
+
+    fn reaper_target_type(&self) -> Option<ReaperTargetType> {
+        Some(ReaperTargetType::TrackDelete)
+    }
+}
+
+impl<'a> Target<'a> for TrackDeleteTarget {
+    type Context = ControlContext<'a>;
+
+    fn current_value(&self, _: Self::Context) -> Option<AbsoluteValue> {
+        None
+    }
+
+    fn control_type(&self, context: Self::Context) -> ControlType {
+        self.control_type_and_character(context).0
+    }
+}
+
+pub const TRACK_DELETE_TARGET: TargetTypeDef = TargetTypeDef {
+    name: "Track: Delete",
+    short_name: "Delete track",
+    supports_track: true,
+    supports_feedback: false,
+    ..DEFAULT_TARGET
+};
@@ -0,0 +1,243 @@
+use crate::domain::{
+    format_value_as_pan, get_effective_tracks, pan_unit_value, parse_value_from_pan,
+    CompoundChangeEvent, ControlContext, ExtendedProcessorContext, HitInstructionReturnValue,
+    MappingCompartment, MappingControlContext, RealearnTarget, ReaperTarget, ReaperTargetType,
+    TargetCharacter, TargetTypeDef, TrackDescriptor, UnresolvedReaperTargetDef, DEFAULT_TARGET,
+};
+use derive_more::Display;
+use enum_iterator::IntoEnumIterator;
+use helgoboss_learn::{AbsoluteValue, ControlType, ControlValue, NumericValue, Target, UnitValue};
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+use reaper_high::{AvailablePanValue, ChangeEvent, Pan, Project, Reaper, Track};
+use reaper_medium::{ReaperPanValue, TrackAttributeKey};
+use serde::{Deserialize, Serialize};
+
+/// Value of `I_PANMODE` which stands for "dual pan".
+const DUAL_PAN_MODE: f64 = 6.0;
+
+#[derive(
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    Debug,
+    Serialize,
+    Deserialize,
+    IntoEnumIterator,
+    TryFromPrimitive,
+    IntoPrimitive,
+    Display,
+)]
+#[repr(usize)]
+pub enum DualPanComponent {
+    #[serde(rename = "left")]
+    #[display(fmt = "Left")]
+    Left,
+    #[serde(rename = "right")]
+    #[display(fmt = "Right")]
+    Right,
+}
+
+impl Default for DualPanComponent {
+    fn default() -> Self {
+        DualPanComponent::Left
+    }
+}
+
+impl DualPanComponent {
+    fn attribute_key(self) -> TrackAttributeKey<'static> {
+        match self {
+            DualPanComponent::Left => TrackAttributeKey::DualPanL,
+            DualPanComponent::Right => TrackAttributeKey::DualPanR,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct UnresolvedTrackDualPanTarget {
+    pub track_descriptor: TrackDescriptor,
+    pub component: DualPanComponent,
+}
+
+impl UnresolvedReaperTargetDef for UnresolvedTrackDualPanTarget {
+    fn resolve(
+        &self,
+        context: ExtendedProcessorContext,
+        compartment: MappingCompartment,
+    ) -> Result<Vec<ReaperTarget>, &'static str> {
+        Ok(
+            get_effective_tracks(context, &self.track_descriptor.track, compartment)?
+                .into_iter()
+                .map(|track| {
+                    ReaperTarget::TrackDualPan(TrackDualPanTarget {
+                        track,
+                        component: self.component,
+                    })
+                })
+                .collect(),
+        )
+    }
+
+    fn track_descriptor(&self) -> Option<&TrackDescriptor> {
+        Some(&self.track_descriptor)
+    }
+}
+
+/// Controls the left or right pan of a track in REAPER's dual-pan mode.
+///
+/// Switches the track to dual-pan mode when hit.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TrackDualPanTarget {
+    pub track: Track,
+    pub component: DualPanComponent,
+}
+
+impl TrackDualPanTarget {
+    fn pan(&self) -> Pan {
+        let value = unsafe {
+            Reaper::get()
+                .medium_reaper()
+                .get_media_track_info_value(self.track.raw(), self.component.attribute_key())
+        };
+        Pan::from_reaper_value(ReaperPanValue::new(value))
+    }
+
+    fn set_pan(&self, pan: Pan) {
+        let reaper = Reaper::get().medium_reaper();
+        unsafe {
+            let mode =
+                reaper.get_media_track_info_value(self.track.raw(), TrackAttributeKey::PanMode);
+            if mode != DUAL_PAN_MODE {
+                let _ = reaper.set_media_track_info_value(
+                    self.track.raw(),
+                    TrackAttributeKey::PanMode,
+                    DUAL_PAN_MODE,
+                );
+            }
+            let _ = reaper.set_media_track_info_value(
+                self.track.raw(),
+                self.component.attribute_key(),
+                pan.reaper_value().get(),
+            );
+        }
+    }
+}
+
+impl RealearnTarget for TrackDualPanTarget {
+    fn control_type_and_character(&self, _: ControlContext) -> (ControlType, TargetCharacter) {
+        (ControlType::AbsoluteContinuous, TargetCharacter::Continuous)
+    }
+
+    fn parse_as_value(&self, text: &str, _: ControlContext) -> Result<UnitValue, &'static str> {
+        parse_value_from_pan(text)
+    }
+
+    fn format_value_without_unit(&self, value: UnitValue, _: ControlContext) -> String {
+        format_value_as_pan(value)
+    }
+
+    fn is_available(&self, _: ControlContext) -> bool {
+        self.track.is_available()
+    }
+
+    fn hide_formatted_value(&self, _: ControlContext) -> bool {
+        true
+    }
+
+    fn hide_formatted_step_size(&self, _: ControlContext) -> bool {
+        true
+    }
+
+    fn project(&self) -> Option<Project> {
+        Some(self.track.project())
+    }
+
+    fn track(&self) -> Option<&Track> {
+        Some(&self.track)
+    }
+
+    fn value_unit(&self, _: ControlContext) -> &'static str {
+        ""
+    }
+
+    fn step_size_unit(&self, _: ControlContext) -> &'static str {
+        ""
+    }
+
+    fn format_value(&self, value: UnitValue, _: ControlContext) -> String {
+        format_value_as_pan(value)
+    }
+
+    fn hit(
+        &mut self,
+        value: ControlValue,
+        _: MappingControlContext,
+    ) -> Result<HitInstructionReturnValue, &'static str> {
+        let pan = Pan::from_normalized_value(value.to_unit_value()?.get());
+        self.set_pan(pan);
+        Ok(None)
+    }
+
+    fn process_change_event(
+        &self,
+        evt: CompoundChangeEvent,
+        _: ControlContext,
+    ) -> (bool, Option<AbsoluteValue>) {
+        match evt {
+            CompoundChangeEvent::Reaper(ChangeEvent::TrackPanChanged(e))
+                if e.track == self.track =>
+            {
+                (true, {
+                    match e.new_value {
+                        AvailablePanValue::Complete(reaper_medium::Pan::DualPan {
+                            left,
+                            right,
+                        }) => {
+                            let pan = match self.component {
+                                DualPanComponent::Left => left,
+                                DualPanComponent::Right => right,
+                            };
+                            Some(AbsoluteValue::Continuous(pan_unit_value(
+                                Pan::from_reaper_value(pan),
+                            )))
+                        }
+                        _ => None,
+                    }
+                })
+            }
+            _ => (false, None),
+        }
+    }
+
+    fn text_value(&self, _: ControlContext) -> Option<String> {
+        Some(self.pan().to_string())
+    }
+
+    fn numeric_value(&self, _: ControlContext) -> Option<NumericValue> {
+        Some(NumericValue::Decimal(self.pan().reaper_value().get()))
+    }
+
+    fn reaper_target_type(&self) -> Option<ReaperTargetType> {
+        Some(ReaperTargetType::TrackDualPan)
+    }
+}
+
+impl<'a> Target<'a> for TrackDualPanTarget {
+    type Context = ControlContext<'a>;
+
+    fn current_value(&self, _: Self::Context) -> Option<AbsoluteValue> {
+        let val = pan_unit_value(self.pan());
+        Some(AbsoluteValue::Continuous(val))
+    }
+
+    fn control_type(&self, context: Self::Context) -> ControlType {
+        self.control_type_and_character(context).0
+    }
+}
+
+pub const TRACK_DUAL_PAN_TARGET: TargetTypeDef = TargetTypeDef {
+    name: "Track: Set dual pan",
+    short_name: "Track dual pan",
+    supports_track: true,
+    ..DEFAULT_TARGET
+};
@@ -0,0 +1,106 @@
+use crate::domain::{
+    get_effective_tracks, ControlContext, ExtendedProcessorContext, HitInstructionReturnValue,
+    MappingCompartment, MappingControlContext, RealearnTarget, ReaperTarget, ReaperTargetType,
+    TargetCharacter, TargetTypeDef, TrackDescriptor, UnresolvedReaperTargetDef, DEFAULT_TARGET,
+};
+use helgoboss_learn::{AbsoluteValue, ControlType, ControlValue, Target, UnitValue};
+use reaper_high::{Project, Reaper, Track};
+use reaper_medium::CommandId;
+
+#[derive(Debug)]
+pub struct UnresolvedTrackDuplicateTarget {
+    pub track_descriptor: TrackDescriptor,
+}
+
+impl UnresolvedReaperTargetDef for UnresolvedTrackDuplicateTarget {
+    fn resolve(
+        &self,
+        context: ExtendedProcessorContext,
+        compartment: MappingCompartment,
+    ) -> Result<Vec<ReaperTarget>, &'static str> {
+        Ok(
+            get_effective_tracks(context, &self.track_descriptor.track, compartment)?
+                .into_iter()
+                .map(|track| ReaperTarget::TrackDuplicate(TrackDuplicateTarget { track }))
+                .collect(),
+        )
+    }
+
+    fn track_descriptor(&self) -> Option<&TrackDescriptor> {
+        Some(&self.track_descriptor)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TrackDuplicateTarget {
+    pub track: Track,
+}
+
+impl RealearnTarget for TrackDuplicateTarget {
+    fn control_type_and_character(&self, _: ControlContext) -> (ControlType, TargetCharacter) {
+        (
+            ControlType::AbsoluteContinuousRetriggerable,
+            TargetCharacter::Trigger,
+        )
+    }
+
+    fn format_value(&self, _: UnitValue, _: ControlContext) -> String {
+        "".to_owned()
+    }
+
+    fn hit(
+        &mut self,
+        value: ControlValue,
+        _: MappingControlContext,
+    ) -> Result<HitInstructionReturnValue, &'static str> {
+        if !value.to_unit_value()?.is_zero() {
+            if self.track.is_master_track() {
+                return Err("master track can't be duplicated");
+            }
+            // There's no API function for duplicating a track (with items, FX, routing etc.), so
+            // we use the action. It works on the selected tracks and selects the duplicate.
+            self.track.select_exclusively();
+            Reaper::get()
+                .main_section()
+                .action_by_command_id(CommandId::new(40062))
+                .invoke_as_trigger(Some(self.track.project()));
+        }
+        Ok(None)
+    }
+
+    fn is_available(&self, _: ControlContext) -> bool {
+        self.track.is_available()
+    }
+
+    fn project(&self) -> Option<Project> {
+        Some(self.track.project())
+    }
+
+    fn track(&self) -> Option<&Track> {
+        Some(&self.track)
+    }
+
+    fn reaper_target_type(&self) -> Option<ReaperTargetType> {
+        Some(ReaperTargetType::TrackDuplicate)
+    }
+}
+
+impl<'a> Target<'a> for TrackDuplicateTarget {
+    type Context = ControlContext<'a>;
+
+    fn current_value(&self, _: Self::Context) -> Option<AbsoluteValue> {
+        None
+    }
+
+    fn control_type(&self, context: Self::Context) -> ControlType {
+        self.control_type_and_character(context).0
+    }
+}
+
+pub const TRACK_DUPLICATE_TARGET: TargetTypeDef = TargetTypeDef {
+    name: "Track: Duplicate",
+    short_name: "Duplicate track",
+    supports_track: true,
+    supports_feedback: false,
+    ..DEFAULT_TARGET
+};
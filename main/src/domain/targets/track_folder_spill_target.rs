@@ -0,0 +1,172 @@
+use crate::domain::{
+    format_value_as_on_off, get_effective_tracks, CompoundChangeEvent, ControlContext,
+    ExtendedProcessorContext, HitInstructionReturnValue, InstanceStateChanged, MappingCompartment,
+    MappingControlContext, RealearnTarget, ReaperTarget, ReaperTargetType, TargetCharacter,
+    TargetTypeDef, TrackDescriptor, UnresolvedReaperTargetDef, DEFAULT_TARGET,
+};
+use helgoboss_learn::{AbsoluteValue, ControlType, ControlValue, Target, UnitValue};
+use reaper_high::{Project, Track};
+
+#[derive(Debug)]
+pub struct UnresolvedTrackFolderSpillTarget {
+    pub track_descriptor: TrackDescriptor,
+}
+
+impl UnresolvedReaperTargetDef for UnresolvedTrackFolderSpillTarget {
+    fn resolve(
+        &self,
+        context: ExtendedProcessorContext,
+        compartment: MappingCompartment,
+    ) -> Result<Vec<ReaperTarget>, &'static str> {
+        Ok(
+            get_effective_tracks(context, &self.track_descriptor.track, compartment)?
+                .into_iter()
+                .map(|track| ReaperTarget::TrackFolderSpill(TrackFolderSpillTarget { track }))
+                .collect(),
+        )
+    }
+
+    fn track_descriptor(&self) -> Option<&TrackDescriptor> {
+        Some(&self.track_descriptor)
+    }
+}
+
+/// Spills the children of a folder track, that is, makes all tracks which are addressed by
+/// position resolve to the children of that folder (first child = track #1).
+///
+/// Switching off returns to normal track positions (if this folder is still the spilled one).
+#[derive(Clone, Debug, PartialEq)]
+pub struct TrackFolderSpillTarget {
+    pub track: Track,
+}
+
+impl RealearnTarget for TrackFolderSpillTarget {
+    fn control_type_and_character(&self, _: ControlContext) -> (ControlType, TargetCharacter) {
+        (
+            ControlType::AbsoluteContinuousRetriggerable,
+            TargetCharacter::Switch,
+        )
+    }
+
+    fn format_value(&self, value: UnitValue, _: ControlContext) -> String {
+        format_value_as_on_off(value).to_string()
+    }
+
+    fn hit(
+        &mut self,
+        value: ControlValue,
+        context: MappingControlContext,
+    ) -> Result<HitInstructionReturnValue, &'static str> {
+        let is_on = !value.to_unit_value()?.is_zero();
+        let mut instance_state = context.control_context.instance_state.borrow_mut();
+        if is_on {
+            if !is_folder_track(&self.track) {
+                return Err("track is not a folder");
+            }
+            instance_state.set_spilled_folder_track(Some(*self.track.guid()));
+        } else if instance_state.spilled_folder_track() == Some(*self.track.guid()) {
+            instance_state.set_spilled_folder_track(None);
+        }
+        Ok(None)
+    }
+
+    fn is_available(&self, _: ControlContext) -> bool {
+        self.track.is_available()
+    }
+
+    fn project(&self) -> Option<Project> {
+        Some(self.track.project())
+    }
+
+    fn track(&self) -> Option<&Track> {
+        Some(&self.track)
+    }
+
+    fn process_change_event(
+        &self,
+        evt: CompoundChangeEvent,
+        _: ControlContext,
+    ) -> (bool, Option<AbsoluteValue>) {
+        match evt {
+            CompoundChangeEvent::Instance(InstanceStateChanged::SpilledFolderTrack { .. }) => {
+                (true, None)
+            }
+            _ => (false, None),
+        }
+    }
+
+    fn text_value(&self, context: ControlContext) -> Option<String> {
+        Some(format_value_as_on_off(self.current_value(context)?.to_unit_value()).to_string())
+    }
+
+    fn reaper_target_type(&self) -> Option<ReaperTargetType> {
+        Some(ReaperTargetType::TrackFolderSpill)
+    }
+}
+
+impl<'a> Target<'a> for TrackFolderSpillTarget {
+    type Context = ControlContext<'a>;
+
+    fn current_value(&self, context: Self::Context) -> Option<AbsoluteValue> {
+        let is_spilled =
+            context.instance_state.borrow().spilled_folder_track() == Some(*self.track.guid());
+        let uv = if is_spilled {
+            UnitValue::MAX
+        } else {
+            UnitValue::MIN
+        };
+        Some(AbsoluteValue::Continuous(uv))
+    }
+
+    fn control_type(&self, context: Self::Context) -> ControlType {
+        self.control_type_and_character(context).0
+    }
+}
+
+pub const TRACK_FOLDER_SPILL_TARGET: TargetTypeDef = TargetTypeDef {
+    name: "Track: Spill folder",
+    short_name: "Spill folder",
+    hint: "Retargets tracks by position to folder children",
+    supports_track: true,
+    ..DEFAULT_TARGET
+};
+
+pub fn is_folder_track(track: &Track) -> bool {
+    track.folder_depth_change() > 0
+}
+
+/// Returns the nesting level of the given track (0 means the track is not within a folder).
+pub fn get_track_folder_depth(track: &Track) -> u32 {
+    let index = match track.index() {
+        // Master track
+        None => return 0,
+        Some(i) => i,
+    };
+    let level: i32 = track
+        .project()
+        .tracks()
+        .take(index as usize)
+        .map(|t| t.folder_depth_change())
+        .sum();
+    level.max(0) as u32
+}
+
+/// Returns the direct children of the given folder track (not the grandchildren).
+pub fn get_folder_children(folder: &Track) -> Vec<Track> {
+    let index = match folder.index() {
+        None => return vec![],
+        Some(i) => i,
+    };
+    let mut level = folder.folder_depth_change();
+    let mut children = vec![];
+    for track in folder.project().tracks().skip(index as usize + 1) {
+        if level <= 0 {
+            break;
+        }
+        if level == 1 {
+            children.push(track.clone());
+        }
+        level += track.folder_depth_change();
+    }
+    children
+}
@@ -0,0 +1,291 @@
+use crate::domain::ui_util::convert_bool_to_unit_value;
+use crate::domain::{
+    change_track_prop, convert_count_to_step_size, convert_discrete_to_unit_value,
+    convert_unit_to_discrete_value, format_value_as_on_off,
+    get_control_type_and_character_for_track_exclusivity, get_effective_tracks,
+    CompoundChangeEvent, ControlContext, ExtendedProcessorContext, HitInstructionReturnValue,
+    MappingCompartment, MappingControlContext, RealearnTarget, ReaperTarget, ReaperTargetType,
+    TargetCharacter, TargetTypeDef, TrackDescriptor, TrackExclusivity, UnresolvedReaperTargetDef,
+    DEFAULT_TARGET,
+};
+use helgoboss_learn::{
+    AbsoluteValue, ControlType, ControlValue, Fraction, NumericValue, Target, UnitValue,
+};
+use reaper_high::{ChangeEvent, Project, Reaper, Track};
+use reaper_medium::{InputMonitoringMode, TrackAttributeKey};
+
+#[derive(Debug)]
+pub struct UnresolvedTrackInputMonitoringTarget {
+    pub track_descriptor: TrackDescriptor,
+    pub exclusivity: TrackExclusivity,
+    /// `None` means switching between all modes (off, normal, tape style).
+    pub mode: Option<InputMonitoringMode>,
+}
+
+impl UnresolvedReaperTargetDef for UnresolvedTrackInputMonitoringTarget {
+    fn resolve(
+        &self,
+        context: ExtendedProcessorContext,
+        compartment: MappingCompartment,
+    ) -> Result<Vec<ReaperTarget>, &'static str> {
+        Ok(
+            get_effective_tracks(context, &self.track_descriptor.track, compartment)?
+                .into_iter()
+                .map(|track| {
+                    ReaperTarget::TrackInputMonitoring(TrackInputMonitoringTarget {
+                        track,
+                        exclusivity: self.exclusivity,
+                        mode: self.mode,
+                    })
+                })
+                .collect(),
+        )
+    }
+
+    fn track_descriptor(&self) -> Option<&TrackDescriptor> {
+        Some(&self.track_descriptor)
+    }
+}
+
+/// Switches the input monitoring of a track to the given mode (on) or turns it off (off).
+///
+/// Without a specific mode, it chooses between off, normal and tape style (discrete).
+#[derive(Clone, Debug, PartialEq)]
+pub struct TrackInputMonitoringTarget {
+    pub track: Track,
+    pub exclusivity: TrackExclusivity,
+    pub mode: Option<InputMonitoringMode>,
+}
+
+/// Modes in the order of their discrete values.
+const ALL_MODES: [InputMonitoringMode; 3] = [
+    InputMonitoringMode::Off,
+    InputMonitoringMode::Normal,
+    InputMonitoringMode::NotWhenPlaying,
+];
+
+impl TrackInputMonitoringTarget {
+    fn current_mode_index(&self) -> u32 {
+        let current_mode = get_input_monitoring_mode(&self.track);
+        ALL_MODES
+            .iter()
+            .position(|m| *m == current_mode)
+            .unwrap_or(0) as u32
+    }
+}
+
+impl RealearnTarget for TrackInputMonitoringTarget {
+    fn control_type_and_character(&self, _: ControlContext) -> (ControlType, TargetCharacter) {
+        if self.mode.is_some() {
+            get_control_type_and_character_for_track_exclusivity(self.exclusivity)
+        } else {
+            (
+                ControlType::AbsoluteDiscrete {
+                    atomic_step_size: convert_count_to_step_size(ALL_MODES.len() as u32),
+                },
+                TargetCharacter::Discrete,
+            )
+        }
+    }
+
+    fn parse_as_value(
+        &self,
+        text: &str,
+        context: ControlContext,
+    ) -> Result<UnitValue, &'static str> {
+        if self.mode.is_some() {
+            return Err("not supported");
+        }
+        self.parse_value_from_discrete_value(text, context)
+    }
+
+    fn parse_as_step_size(
+        &self,
+        text: &str,
+        context: ControlContext,
+    ) -> Result<UnitValue, &'static str> {
+        if self.mode.is_some() {
+            return Err("not supported");
+        }
+        self.parse_value_from_discrete_value(text, context)
+    }
+
+    fn convert_unit_value_to_discrete_value(
+        &self,
+        input: UnitValue,
+        _: ControlContext,
+    ) -> Result<u32, &'static str> {
+        if self.mode.is_some() {
+            return Err("not supported");
+        }
+        Ok(convert_unit_to_discrete_value(
+            input,
+            ALL_MODES.len() as u32,
+        ))
+    }
+
+    fn convert_discrete_value_to_unit_value(
+        &self,
+        value: u32,
+        _: ControlContext,
+    ) -> Result<UnitValue, &'static str> {
+        if self.mode.is_some() {
+            return Err("not supported");
+        }
+        Ok(convert_discrete_to_unit_value(
+            value,
+            ALL_MODES.len() as u32,
+        ))
+    }
+
+    fn format_value(&self, value: UnitValue, _: ControlContext) -> String {
+        if self.mode.is_some() {
+            return format_value_as_on_off(value).to_string();
+        }
+        let index = convert_unit_to_discrete_value(value, ALL_MODES.len() as u32);
+        format_input_monitoring_mode(ALL_MODES[index as usize]).to_string()
+    }
+
+    fn hit(
+        &mut self,
+        value: ControlValue,
+        _: MappingControlContext,
+    ) -> Result<HitInstructionReturnValue, &'static str> {
+        match self.mode {
+            Some(mode) => {
+                change_track_prop(
+                    &self.track,
+                    self.exclusivity,
+                    value.to_unit_value()?,
+                    |t| set_input_monitoring_mode(t, mode),
+                    |t| set_input_monitoring_mode(t, InputMonitoringMode::Off),
+                );
+            }
+            None => {
+                let count = ALL_MODES.len() as u32;
+                let index = match value.to_absolute_value()? {
+                    AbsoluteValue::Continuous(v) => convert_unit_to_discrete_value(v, count),
+                    AbsoluteValue::Discrete(f) => f.actual().min(count - 1),
+                };
+                set_input_monitoring_mode(&self.track, ALL_MODES[index as usize]);
+            }
+        }
+        Ok(None)
+    }
+
+    fn is_available(&self, _: ControlContext) -> bool {
+        self.track.is_available()
+    }
+
+    fn project(&self) -> Option<Project> {
+        Some(self.track.project())
+    }
+
+    fn track(&self) -> Option<&Track> {
+        Some(&self.track)
+    }
+
+    fn track_exclusivity(&self) -> Option<TrackExclusivity> {
+        // Exclusivity only applies when switching one specific mode on and off.
+        self.mode.map(|_| self.exclusivity)
+    }
+
+    fn process_change_event(
+        &self,
+        evt: CompoundChangeEvent,
+        _: ControlContext,
+    ) -> (bool, Option<AbsoluteValue>) {
+        match evt {
+            CompoundChangeEvent::Reaper(ChangeEvent::TrackInputMonitoringChanged(e))
+                if e.track == self.track =>
+            {
+                (true, None)
+            }
+            _ => (false, None),
+        }
+    }
+
+    fn text_value(&self, context: ControlContext) -> Option<String> {
+        if self.mode.is_none() {
+            let mode = get_input_monitoring_mode(&self.track);
+            return Some(format_input_monitoring_mode(mode).to_string());
+        }
+        Some(format_value_as_on_off(self.current_value(context)?.to_unit_value()).to_string())
+    }
+
+    fn numeric_value(&self, _: ControlContext) -> Option<NumericValue> {
+        if self.mode.is_some() {
+            return None;
+        }
+        Some(NumericValue::Discrete(self.current_mode_index() as i32))
+    }
+
+    fn reaper_target_type(&self) -> Option<ReaperTargetType> {
+        Some(ReaperTargetType::TrackInputMonitoring)
+    }
+}
+
+impl<'a> Target<'a> for TrackInputMonitoringTarget {
+    type Context = ControlContext<'a>;
+
+    fn current_value(&self, _: Self::Context) -> Option<AbsoluteValue> {
+        match self.mode {
+            Some(mode) => {
+                let is_on = get_input_monitoring_mode(&self.track) == mode;
+                Some(AbsoluteValue::Continuous(convert_bool_to_unit_value(is_on)))
+            }
+            None => {
+                let fraction = Fraction::new(self.current_mode_index(), ALL_MODES.len() as u32 - 1);
+                Some(AbsoluteValue::Discrete(fraction))
+            }
+        }
+    }
+
+    fn control_type(&self, context: Self::Context) -> ControlType {
+        self.control_type_and_character(context).0
+    }
+}
+
+pub const TRACK_INPUT_MONITORING_TARGET: TargetTypeDef = TargetTypeDef {
+    name: "Track: Set input monitoring",
+    short_name: "Input monitoring",
+    supports_track: true,
+    supports_track_exclusivity: true,
+    ..DEFAULT_TARGET
+};
+
+fn get_input_monitoring_mode(track: &Track) -> InputMonitoringMode {
+    let value = unsafe {
+        Reaper::get()
+            .medium_reaper()
+            .get_media_track_info_value(track.raw(), TrackAttributeKey::RecMon)
+    };
+    match value as i32 {
+        1 => InputMonitoringMode::Normal,
+        2 => InputMonitoringMode::NotWhenPlaying,
+        _ => InputMonitoringMode::Off,
+    }
+}
+
+fn set_input_monitoring_mode(track: &Track, mode: InputMonitoringMode) {
+    let value = match mode {
+        InputMonitoringMode::Normal => 1.0,
+        InputMonitoringMode::NotWhenPlaying => 2.0,
+        _ => 0.0,
+    };
+    let _ = unsafe {
+        Reaper::get().medium_reaper().set_media_track_info_value(
+            track.raw(),
+            TrackAttributeKey::RecMon,
+            value,
+        )
+    };
+}
+
+fn format_input_monitoring_mode(mode: InputMonitoringMode) -> &'static str {
+    match mode {
+        InputMonitoringMode::Normal => "Normal",
+        InputMonitoringMode::NotWhenPlaying => "Tape style",
+        _ => "Off",
+    }
+}
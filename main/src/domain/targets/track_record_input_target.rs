@@ -0,0 +1,295 @@
+use crate::domain::{
+    convert_count_to_step_size, convert_discrete_to_unit_value, convert_unit_to_discrete_value,
+    get_effective_tracks, CompoundChangeEvent, ControlContext, ExtendedProcessorContext,
+    HitInstructionReturnValue, MappingCompartment, MappingControlContext, RealearnTarget,
+    ReaperTarget, ReaperTargetType, TargetCharacter, TargetTypeDef, TrackDescriptor,
+    UnresolvedReaperTargetDef, DEFAULT_TARGET,
+};
+use derive_more::Display;
+use enum_iterator::IntoEnumIterator;
+use helgoboss_learn::{
+    AbsoluteValue, ControlType, ControlValue, Fraction, NumericValue, Target, UnitValue,
+};
+use helgoboss_midi::Channel;
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+use reaper_high::{ChangeEvent, Project, Reaper, Track};
+use reaper_medium::{RecordingInput, TrackAttributeKey};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug)]
+pub struct UnresolvedTrackRecordInputTarget {
+    pub track_descriptor: TrackDescriptor,
+    pub kind: RecordInputKind,
+}
+
+impl UnresolvedReaperTargetDef for UnresolvedTrackRecordInputTarget {
+    fn resolve(
+        &self,
+        context: ExtendedProcessorContext,
+        compartment: MappingCompartment,
+    ) -> Result<Vec<ReaperTarget>, &'static str> {
+        let inputs = available_record_inputs(self.kind);
+        Ok(
+            get_effective_tracks(context, &self.track_descriptor.track, compartment)?
+                .into_iter()
+                .map(|track| {
+                    ReaperTarget::TrackRecordInput(TrackRecordInputTarget {
+                        track,
+                        inputs: inputs.clone(),
+                    })
+                })
+                .collect(),
+        )
+    }
+
+    fn track_descriptor(&self) -> Option<&TrackDescriptor> {
+        Some(&self.track_descriptor)
+    }
+}
+
+/// Chooses the record input of a track from a list of possible inputs.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TrackRecordInputTarget {
+    pub track: Track,
+    /// Inputs which can be chosen, `None` means "no input". Never empty.
+    pub inputs: Vec<Option<RecordingInput>>,
+}
+
+impl TrackRecordInputTarget {
+    fn input_count(&self) -> u32 {
+        self.inputs.len() as u32
+    }
+
+    fn current_input_index(&self) -> Option<u32> {
+        let current_input = self.track.recording_input();
+        let index = self.inputs.iter().position(|i| *i == current_input)?;
+        Some(index as u32)
+    }
+
+    fn set_input(&self, input: Option<RecordingInput>) {
+        let raw_value = input.map(|i| i.to_raw()).unwrap_or(-1);
+        let _ = unsafe {
+            Reaper::get().medium_reaper().set_media_track_info_value(
+                self.track.raw(),
+                TrackAttributeKey::RecInput,
+                raw_value as f64,
+            )
+        };
+    }
+}
+
+impl RealearnTarget for TrackRecordInputTarget {
+    fn control_type_and_character(&self, _: ControlContext) -> (ControlType, TargetCharacter) {
+        (
+            ControlType::AbsoluteDiscrete {
+                atomic_step_size: convert_count_to_step_size(self.input_count()),
+            },
+            TargetCharacter::Discrete,
+        )
+    }
+
+    fn parse_as_value(
+        &self,
+        text: &str,
+        context: ControlContext,
+    ) -> Result<UnitValue, &'static str> {
+        self.parse_value_from_discrete_value(text, context)
+    }
+
+    fn parse_as_step_size(
+        &self,
+        text: &str,
+        context: ControlContext,
+    ) -> Result<UnitValue, &'static str> {
+        self.parse_value_from_discrete_value(text, context)
+    }
+
+    fn convert_unit_value_to_discrete_value(
+        &self,
+        input: UnitValue,
+        _: ControlContext,
+    ) -> Result<u32, &'static str> {
+        Ok(convert_unit_to_discrete_value(input, self.input_count()))
+    }
+
+    fn convert_discrete_value_to_unit_value(
+        &self,
+        value: u32,
+        _: ControlContext,
+    ) -> Result<UnitValue, &'static str> {
+        Ok(convert_discrete_to_unit_value(value, self.input_count()))
+    }
+
+    fn format_value(&self, value: UnitValue, _: ControlContext) -> String {
+        let index = convert_unit_to_discrete_value(value, self.input_count());
+        match self.inputs.get(index as usize) {
+            None => "<Invalid>".to_owned(),
+            Some(input) => format_record_input(*input),
+        }
+    }
+
+    fn hit(
+        &mut self,
+        value: ControlValue,
+        _: MappingControlContext,
+    ) -> Result<HitInstructionReturnValue, &'static str> {
+        let index = match value.to_absolute_value()? {
+            AbsoluteValue::Continuous(v) => convert_unit_to_discrete_value(v, self.input_count()),
+            AbsoluteValue::Discrete(f) => f.actual().min(self.input_count() - 1),
+        };
+        let input = *self
+            .inputs
+            .get(index as usize)
+            .ok_or("record input not available")?;
+        self.set_input(input);
+        Ok(None)
+    }
+
+    fn is_available(&self, _: ControlContext) -> bool {
+        self.track.is_available()
+    }
+
+    fn project(&self) -> Option<Project> {
+        Some(self.track.project())
+    }
+
+    fn track(&self) -> Option<&Track> {
+        Some(&self.track)
+    }
+
+    fn process_change_event(
+        &self,
+        evt: CompoundChangeEvent,
+        _: ControlContext,
+    ) -> (bool, Option<AbsoluteValue>) {
+        match evt {
+            CompoundChangeEvent::Reaper(ChangeEvent::TrackInputChanged(e))
+                if e.track == self.track =>
+            {
+                (true, None)
+            }
+            _ => (false, None),
+        }
+    }
+
+    fn text_value(&self, _: ControlContext) -> Option<String> {
+        Some(format_record_input(self.track.recording_input()))
+    }
+
+    fn numeric_value(&self, _: ControlContext) -> Option<NumericValue> {
+        Some(NumericValue::Discrete(self.current_input_index()? as i32))
+    }
+
+    fn reaper_target_type(&self) -> Option<ReaperTargetType> {
+        Some(ReaperTargetType::TrackRecordInput)
+    }
+}
+
+impl<'a> Target<'a> for TrackRecordInputTarget {
+    type Context = ControlContext<'a>;
+
+    fn current_value(&self, _: Self::Context) -> Option<AbsoluteValue> {
+        let index = self.current_input_index()?;
+        let fraction = Fraction::new(index, self.input_count() - 1);
+        Some(AbsoluteValue::Discrete(fraction))
+    }
+
+    fn control_type(&self, context: Self::Context) -> ControlType {
+        self.control_type_and_character(context).0
+    }
+}
+
+pub const TRACK_RECORD_INPUT_TARGET: TargetTypeDef = TargetTypeDef {
+    name: "Track: Set record input",
+    short_name: "Record input",
+    supports_track: true,
+    ..DEFAULT_TARGET
+};
+
+/// Which kind of record inputs the target switches between.
+#[derive(
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    Debug,
+    Serialize,
+    Deserialize,
+    IntoEnumIterator,
+    TryFromPrimitive,
+    IntoPrimitive,
+    Display,
+)]
+#[repr(usize)]
+pub enum RecordInputKind {
+    /// Mono inputs and stereo pairs of the audio device.
+    #[serde(rename = "audio")]
+    #[display(fmt = "Audio")]
+    Audio,
+    /// Channels of all available MIDI input devices.
+    #[serde(rename = "midi")]
+    #[display(fmt = "MIDI")]
+    Midi,
+}
+
+impl Default for RecordInputKind {
+    fn default() -> Self {
+        RecordInputKind::Audio
+    }
+}
+
+/// Returns the inputs that can be chosen for the given kind, starting with "no input".
+fn available_record_inputs(kind: RecordInputKind) -> Vec<Option<RecordingInput>> {
+    let none = std::iter::once(None);
+    match kind {
+        RecordInputKind::Audio => {
+            let channel_count = Reaper::get().medium_reaper().get_num_audio_inputs();
+            let mono_inputs = (0..channel_count).map(|i| Some(RecordingInput::Mono(i)));
+            // Stereo pairs can start at any input channel but the last one.
+            let stereo_inputs =
+                (0..channel_count.saturating_sub(1)).map(|i| Some(RecordingInput::Stereo(i)));
+            none.chain(mono_inputs).chain(stereo_inputs).collect()
+        }
+        RecordInputKind::Midi => {
+            let device_ids = std::iter::once(None).chain(
+                Reaper::get()
+                    .midi_input_devices()
+                    .filter(|d| d.is_available())
+                    .map(|d| Some(d.id())),
+            );
+            let midi_inputs = device_ids.flat_map(|device_id| {
+                std::iter::once(None)
+                    .chain((0..16).map(|ch| Some(Channel::new(ch))))
+                    .map(move |channel| Some(RecordingInput::Midi { device_id, channel }))
+            });
+            none.chain(midi_inputs).collect()
+        }
+    }
+}
+
+fn format_record_input(input: Option<RecordingInput>) -> String {
+    use RecordingInput::*;
+    match input {
+        None => "<None>".to_owned(),
+        Some(Mono(i)) => format!("Mono {}", i + 1),
+        Some(Stereo(i)) => format!("Stereo {}/{}", i + 1, i + 2),
+        Some(ReaRoute(i)) => format!("ReaRoute {}", i + 1),
+        Some(Midi { device_id, channel }) => {
+            let device_label = match device_id {
+                None => "All devices".to_owned(),
+                Some(id) => Reaper::get()
+                    .midi_input_device_by_id(id)
+                    .name()
+                    .into_inner()
+                    .to_string_lossy()
+                    .into_owned(),
+            };
+            let channel_label = match channel {
+                None => "all channels".to_owned(),
+                Some(ch) => format!("channel {}", ch.get() + 1),
+            };
+            format!("MIDI: {} ({})", device_label, channel_label)
+        }
+        Some(_) => "<Unknown>".to_owned(),
+    }
+}
@@ -2,13 +2,13 @@ use crate::domain::{
     change_track_prop, format_value_as_on_off,
     get_control_type_and_character_for_track_exclusivity, get_effective_tracks,
     track_solo_unit_value, CompoundChangeEvent, ControlContext, ExtendedProcessorContext,
-    HitInstructionReturnValue, MappingCompartment, MappingControlContext, RealearnTarget,
-    ReaperTarget, ReaperTargetType, SoloBehavior, TargetCharacter, TargetTypeDef, TrackDescriptor,
-    TrackExclusivity, UnresolvedReaperTargetDef, DEFAULT_TARGET,
+    FeedbackResolution, HitInstructionReturnValue, MappingCompartment, MappingControlContext,
+    RealearnTarget, ReaperTarget, ReaperTargetType, SoloBehavior, TargetCharacter, TargetTypeDef,
+    TrackDescriptor, TrackExclusivity, UnresolvedReaperTargetDef, DEFAULT_TARGET,
 };
 use helgoboss_learn::{AbsoluteValue, ControlType, ControlValue, Target, UnitValue};
-use reaper_high::{ChangeEvent, Project, Track};
-use reaper_medium::SoloMode;
+use reaper_high::{ChangeEvent, Project, Reaper, Track};
+use reaper_medium::{SoloMode, TrackAttributeKey};
 
 #[derive(Debug)]
 pub struct UnresolvedTrackSoloTarget {
@@ -31,6 +31,7 @@ impl UnresolvedReaperTargetDef for UnresolvedTrackSoloTarget {
                         track,
                         exclusivity: self.exclusivity,
                         behavior: self.behavior,
+                        previous_solos: vec![],
                     })
                 })
                 .collect(),
@@ -40,6 +41,15 @@ impl UnresolvedReaperTargetDef for UnresolvedTrackSoloTarget {
     fn track_descriptor(&self) -> Option<&TrackDescriptor> {
         Some(&self.track_descriptor)
     }
+
+    fn feedback_resolution(&self) -> Option<FeedbackResolution> {
+        if self.behavior == SoloBehavior::Defeat {
+            // There's no change event for solo defeat.
+            Some(FeedbackResolution::High)
+        } else {
+            None
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -47,6 +57,63 @@ pub struct TrackSoloTarget {
     pub track: Track,
     pub behavior: SoloBehavior,
     pub exclusivity: TrackExclusivity,
+    /// Tracks which were soloed before soloing exclusively (only used with
+    /// [`SoloBehavior::ExclusiveRestoring`]).
+    pub previous_solos: Vec<Track>,
+}
+
+impl TrackSoloTarget {
+    fn solo_exclusively(&mut self) {
+        if self.previous_solos.is_empty() {
+            self.previous_solos = self
+                .track
+                .project()
+                .tracks()
+                .filter(|t| t != &self.track && t.is_solo())
+                .collect();
+        }
+        for t in &self.previous_solos {
+            t.unsolo();
+        }
+        self.track.set_solo_mode(SoloMode::SoloInPlace);
+    }
+
+    fn unsolo_and_restore_previous_solos(&mut self) {
+        self.track.unsolo();
+        for t in self.previous_solos.drain(..) {
+            if t.is_available() {
+                t.solo();
+            }
+        }
+    }
+
+    fn is_on(&self) -> bool {
+        if self.behavior == SoloBehavior::Defeat {
+            solo_defeat_is_enabled(&self.track)
+        } else {
+            self.track.is_solo()
+        }
+    }
+}
+
+fn solo_defeat_is_enabled(track: &Track) -> bool {
+    let value = unsafe {
+        Reaper::get()
+            .medium_reaper()
+            .get_media_track_info_value(track.raw(), TrackAttributeKey::SoloDefeat)
+    };
+    value != 0.0
+}
+
+fn set_solo_defeat(track: &Track, enabled: bool) {
+    let value = if enabled { 1.0 } else { 0.0 };
+    let _ = unsafe {
+        Reaper::get().medium_reaper().set_media_track_info_value(
+            track.raw(),
+            TrackAttributeKey::SoloDefeat,
+            value,
+        )
+    };
 }
 
 impl RealearnTarget for TrackSoloTarget {
@@ -63,21 +130,41 @@ impl RealearnTarget for TrackSoloTarget {
         value: ControlValue,
         _: MappingControlContext,
     ) -> Result<HitInstructionReturnValue, &'static str> {
-        let solo_track = |t: &Track| {
-            use SoloBehavior::*;
-            match self.behavior {
-                InPlace => t.set_solo_mode(SoloMode::SoloInPlace),
-                IgnoreRouting => t.set_solo_mode(SoloMode::SoloIgnoreRouting),
-                ReaperPreference => t.solo(),
+        let value = value.to_unit_value()?;
+        use SoloBehavior::*;
+        match self.behavior {
+            ExclusiveRestoring => {
+                // Exclusive by definition, so the track exclusivity setting doesn't matter.
+                if value.is_zero() {
+                    self.unsolo_and_restore_previous_solos();
+                } else {
+                    self.solo_exclusively();
+                }
+            }
+            Defeat => {
+                change_track_prop(
+                    &self.track,
+                    self.exclusivity,
+                    value,
+                    |t| set_solo_defeat(t, true),
+                    |t| set_solo_defeat(t, false),
+                );
             }
-        };
-        change_track_prop(
-            &self.track,
-            self.exclusivity,
-            value.to_unit_value()?,
-            |t| solo_track(t),
-            |t| t.unsolo(),
-        );
+            InPlace | IgnoreRouting | ReaperPreference => {
+                let solo_track = |t: &Track| match self.behavior {
+                    IgnoreRouting => t.set_solo_mode(SoloMode::SoloIgnoreRouting),
+                    ReaperPreference => t.solo(),
+                    _ => t.set_solo_mode(SoloMode::SoloInPlace),
+                };
+                change_track_prop(
+                    &self.track,
+                    self.exclusivity,
+                    value,
+                    |t| solo_track(t),
+                    |t| t.unsolo(),
+                );
+            }
+        }
         Ok(None)
     }
 
@@ -103,8 +190,9 @@ impl RealearnTarget for TrackSoloTarget {
         _: ControlContext,
     ) -> (bool, Option<AbsoluteValue>) {
         match evt {
+            // Solo changes don't affect solo defeat.
             CompoundChangeEvent::Reaper(ChangeEvent::TrackSoloChanged(e))
-                if e.track == self.track =>
+                if e.track == self.track && self.behavior != SoloBehavior::Defeat =>
             {
                 (
                     true,
@@ -130,7 +218,7 @@ impl<'a> Target<'a> for TrackSoloTarget {
     type Context = ControlContext<'a>;
 
     fn current_value(&self, _: Self::Context) -> Option<AbsoluteValue> {
-        let val = track_solo_unit_value(self.track.is_solo());
+        let val = track_solo_unit_value(self.is_on());
         Some(AbsoluteValue::Continuous(val))
     }
 
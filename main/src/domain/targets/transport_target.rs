@@ -1,9 +1,9 @@
 use crate::domain::{
     format_value_as_on_off, transport_is_enabled_unit_value, AdditionalFeedbackEvent,
-    CompoundChangeEvent, ControlContext, ExtendedProcessorContext, FeedbackResolution,
-    HitInstructionReturnValue, MappingCompartment, MappingControlContext, RealearnTarget,
-    ReaperTarget, ReaperTargetType, TargetCharacter, TargetTypeDef, TransportAction,
-    UnresolvedReaperTargetDef, DEFAULT_TARGET,
+    CompoundChangeEvent, ControlContext, DomainEvent, ExtendedProcessorContext, FeedbackResolution,
+    HitInstruction, HitInstructionContext, HitInstructionReturnValue, MappingCompartment,
+    MappingControlContext, MappingControlResult, RealearnTarget, ReaperTarget, ReaperTargetType,
+    TargetCharacter, TargetTypeDef, TransportAction, UnresolvedReaperTargetDef, DEFAULT_TARGET,
 };
 use helgoboss_learn::{AbsoluteValue, ControlType, ControlValue, Target, UnitValue};
 use reaper_high::{ChangeEvent, Project, Reaper};
@@ -60,6 +60,18 @@ impl RealearnTarget for TransportTarget {
         value: ControlValue,
         _: MappingControlContext,
     ) -> Result<HitInstructionReturnValue, &'static str> {
+        struct RecordingStartedInstruction;
+        impl HitInstruction for RecordingStartedInstruction {
+            fn execute(
+                self: Box<Self>,
+                context: HitInstructionContext,
+            ) -> Vec<MappingControlResult> {
+                context
+                    .domain_event_handler
+                    .handle_event(DomainEvent::RecordingStarted);
+                vec![]
+            }
+        }
         use TransportAction::*;
         let on = !value.to_unit_value()?.is_zero();
         match self.action {
@@ -90,6 +102,8 @@ impl RealearnTarget for TransportTarget {
             Record => {
                 if on {
                     Reaper::get().enable_record_in_current_project();
+                    // Let the session know (e.g. for notifying webhooks)
+                    return Ok(Some(Box::new(RecordingStartedInstruction)));
                 } else {
                     Reaper::get().disable_record_in_current_project();
                 }
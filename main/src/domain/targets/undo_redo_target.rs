@@ -0,0 +1,157 @@
+use crate::domain::{
+    format_value_as_on_off, transport_is_enabled_unit_value, ControlContext,
+    ExtendedProcessorContext, FeedbackResolution, HitInstructionReturnValue, MappingCompartment,
+    MappingControlContext, RealearnTarget, ReaperTarget, ReaperTargetType, TargetCharacter,
+    TargetTypeDef, UnresolvedReaperTargetDef, DEFAULT_TARGET,
+};
+use derive_more::Display;
+use enum_iterator::IntoEnumIterator;
+use helgoboss_learn::{AbsoluteValue, ControlType, ControlValue, Target, UnitValue};
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+use reaper_high::Project;
+use serde::{Deserialize, Serialize};
+
+#[derive(
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    Debug,
+    Serialize,
+    Deserialize,
+    IntoEnumIterator,
+    TryFromPrimitive,
+    IntoPrimitive,
+    Display,
+)]
+#[repr(usize)]
+pub enum UndoRedoAction {
+    #[serde(rename = "undo")]
+    #[display(fmt = "Undo")]
+    Undo,
+    #[serde(rename = "redo")]
+    #[display(fmt = "Redo")]
+    Redo,
+}
+
+impl Default for UndoRedoAction {
+    fn default() -> Self {
+        UndoRedoAction::Undo
+    }
+}
+
+#[derive(Debug)]
+pub struct UnresolvedUndoRedoTarget {
+    pub action: UndoRedoAction,
+}
+
+impl UnresolvedReaperTargetDef for UnresolvedUndoRedoTarget {
+    fn resolve(
+        &self,
+        context: ExtendedProcessorContext,
+        _: MappingCompartment,
+    ) -> Result<Vec<ReaperTarget>, &'static str> {
+        Ok(vec![ReaperTarget::UndoRedo(UndoRedoTarget {
+            project: context.context().project_or_current_project(),
+            action: self.action,
+        })])
+    }
+
+    fn feedback_resolution(&self) -> Option<FeedbackResolution> {
+        // REAPER doesn't notify us when undo points are added, so we need to poll.
+        Some(FeedbackResolution::High)
+    }
+}
+
+/// Undoes or redoes the last action in the project.
+///
+/// Feedback is "on" as long as there's something to undo/redo. The textual feedback is the
+/// label of the undo point which would be undone/redone next.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UndoRedoTarget {
+    pub project: Project,
+    pub action: UndoRedoAction,
+}
+
+impl UndoRedoTarget {
+    fn next_label(&self) -> Option<String> {
+        let label = match self.action {
+            UndoRedoAction::Undo => self.project.label_of_last_undoable_action()?,
+            UndoRedoAction::Redo => self.project.label_of_last_redoable_action()?,
+        };
+        Some(label.to_str().to_owned())
+    }
+}
+
+impl RealearnTarget for UndoRedoTarget {
+    fn control_type_and_character(&self, _: ControlContext) -> (ControlType, TargetCharacter) {
+        (
+            ControlType::AbsoluteContinuousRetriggerable,
+            TargetCharacter::Trigger,
+        )
+    }
+
+    fn format_value(&self, value: UnitValue, _: ControlContext) -> String {
+        format_value_as_on_off(value).to_string()
+    }
+
+    fn hit(
+        &mut self,
+        value: ControlValue,
+        _: MappingControlContext,
+    ) -> Result<HitInstructionReturnValue, &'static str> {
+        if value.to_unit_value()?.is_zero() {
+            return Ok(None);
+        }
+        match self.action {
+            UndoRedoAction::Undo => {
+                if !self.project.undo() {
+                    return Err("nothing to undo");
+                }
+            }
+            UndoRedoAction::Redo => {
+                if !self.project.redo() {
+                    return Err("nothing to redo");
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    fn is_available(&self, _: ControlContext) -> bool {
+        self.project.is_available()
+    }
+
+    fn project(&self) -> Option<Project> {
+        Some(self.project)
+    }
+
+    fn text_value(&self, _: ControlContext) -> Option<String> {
+        self.next_label()
+    }
+
+    fn reaper_target_type(&self) -> Option<ReaperTargetType> {
+        Some(ReaperTargetType::UndoRedo)
+    }
+}
+
+impl<'a> Target<'a> for UndoRedoTarget {
+    type Context = ControlContext<'a>;
+
+    fn current_value(&self, _: Self::Context) -> Option<AbsoluteValue> {
+        let is_possible = self.next_label().is_some();
+        Some(AbsoluteValue::Continuous(transport_is_enabled_unit_value(
+            is_possible,
+        )))
+    }
+
+    fn control_type(&self, context: Self::Context) -> ControlType {
+        self.control_type_and_character(context).0
+    }
+}
+
+pub const UNDO_REDO_TARGET: TargetTypeDef = TargetTypeDef {
+    name: "Project: Undo/redo",
+    short_name: "Undo/redo",
+    ..DEFAULT_TARGET
+};
@@ -1,23 +1,34 @@
 use crate::application::BookmarkAnchorType;
 use crate::domain::realearn_target::RealearnTarget;
 use crate::domain::{
-    ExtendedProcessorContext, FeedbackResolution, MappingCompartment, ParameterSlice, ReaperTarget,
-    UnresolvedActionTarget, UnresolvedAllTrackFxEnableTarget, UnresolvedAnyOnTarget,
+    get_folder_children, ExtendedProcessorContext, FeedbackResolution, MappingCompartment,
+    ParameterSlice, ReaperTarget, UnresolvedActionTarget, UnresolvedAdjustMappingModeTarget,
+    UnresolvedAllTrackFxEnableTarget, UnresolvedAnyOnTarget,
     UnresolvedAutomationModeOverrideTarget, UnresolvedAutomationTouchStateTarget,
+    UnresolvedClipColumnStopTarget, UnresolvedClipCueTarget, UnresolvedClipSceneLaunchTarget,
     UnresolvedClipSeekTarget, UnresolvedClipTransportTarget, UnresolvedClipVolumeTarget,
-    UnresolvedEnableInstancesTarget, UnresolvedEnableMappingsTarget, UnresolvedFxEnableTarget,
-    UnresolvedFxNavigateTarget, UnresolvedFxOpenTarget, UnresolvedFxParameterTarget,
-    UnresolvedFxPresetTarget, UnresolvedGoToBookmarkTarget, UnresolvedLastTouchedTarget,
-    UnresolvedLoadFxSnapshotTarget, UnresolvedLoadMappingSnapshotTarget, UnresolvedMidiSendTarget,
-    UnresolvedNavigateWithinGroupTarget, UnresolvedOscSendTarget, UnresolvedPlayrateTarget,
+    UnresolvedEnableGroupTarget, UnresolvedEnableInstancesTarget, UnresolvedEnableMappingsTarget,
+    UnresolvedFxEnableTarget, UnresolvedFxNavigateTarget, UnresolvedFxOpenTarget,
+    UnresolvedFxParameterBankTarget, UnresolvedFxParameterTarget, UnresolvedFxPresetTarget,
+    UnresolvedGoToBookmarkTarget, UnresolvedInvokeInstanceTarget, UnresolvedLastTouchedTarget,
+    UnresolvedLoadFxSnapshotTarget, UnresolvedLoadMappingSnapshotTarget, UnresolvedMetronomeTarget,
+    UnresolvedMidiClockSendTarget, UnresolvedMidiMacroSendTarget, UnresolvedMidiSendTarget,
+    UnresolvedMonitoringVolumeTarget, UnresolvedMultiTarget, UnresolvedNavigateBookmarksTarget,
+    UnresolvedNavigateWithinGroupTarget, UnresolvedOscSendTarget, UnresolvedPanicTarget,
+    UnresolvedPlayrateTarget, UnresolvedProgramChangeSendTarget, UnresolvedPunchTarget,
     UnresolvedRouteAutomationModeTarget, UnresolvedRouteMonoTarget, UnresolvedRouteMuteTarget,
     UnresolvedRoutePanTarget, UnresolvedRoutePhaseTarget, UnresolvedRouteVolumeTarget,
-    UnresolvedSeekTarget, UnresolvedSelectedTrackTarget, UnresolvedTempoTarget,
-    UnresolvedTrackArmTarget, UnresolvedTrackAutomationModeTarget, UnresolvedTrackMuteTarget,
-    UnresolvedTrackPanTarget, UnresolvedTrackPeakTarget, UnresolvedTrackPhaseTarget,
+    UnresolvedRunScriptTarget, UnresolvedSaveMappingSnapshotTarget, UnresolvedSeekTarget,
+    UnresolvedSelectLayerTarget, UnresolvedSelectedTrackTarget, UnresolvedSetBankOffsetTarget,
+    UnresolvedSetExtStateTarget, UnresolvedTempoTarget, UnresolvedTimeSelectionTarget,
+    UnresolvedTrackArmTarget, UnresolvedTrackAutomationModeTarget, UnresolvedTrackCueTarget,
+    UnresolvedTrackDeleteTarget, UnresolvedTrackDualPanTarget, UnresolvedTrackDuplicateTarget,
+    UnresolvedTrackFolderSpillTarget, UnresolvedTrackInputMonitoringTarget,
+    UnresolvedTrackInsertTarget, UnresolvedTrackMuteTarget, UnresolvedTrackPanTarget,
+    UnresolvedTrackPeakTarget, UnresolvedTrackPhaseTarget, UnresolvedTrackRecordInputTarget,
     UnresolvedTrackSelectionTarget, UnresolvedTrackShowTarget, UnresolvedTrackSoloTarget,
     UnresolvedTrackToolTarget, UnresolvedTrackVolumeTarget, UnresolvedTrackWidthTarget,
-    UnresolvedTransportTarget, COMPARTMENT_PARAMETER_COUNT,
+    UnresolvedTransportTarget, UnresolvedUndoRedoTarget, COMPARTMENT_PARAMETER_COUNT,
 };
 use derive_more::{Display, Error};
 use enum_dispatch::enum_dispatch;
@@ -43,11 +54,13 @@ const MAX_MULTIPLE: usize = 1000;
 pub enum UnresolvedReaperTarget {
     Action(UnresolvedActionTarget),
     FxParameter(UnresolvedFxParameterTarget),
+    FxParameterBank(UnresolvedFxParameterBankTarget),
     TrackVolume(UnresolvedTrackVolumeTarget),
     TrackTool(UnresolvedTrackToolTarget),
     TrackPeak(UnresolvedTrackPeakTarget),
     TrackSendVolume(UnresolvedRouteVolumeTarget),
     TrackPan(UnresolvedTrackPanTarget),
+    TrackDualPan(UnresolvedTrackDualPanTarget),
     TrackWidth(UnresolvedTrackWidthTarget),
     TrackArm(UnresolvedTrackArmTarget),
     TrackSelection(UnresolvedTrackSelectionTarget),
@@ -55,6 +68,13 @@ pub enum UnresolvedReaperTarget {
     TrackPhase(UnresolvedTrackPhaseTarget),
     TrackShow(UnresolvedTrackShowTarget),
     TrackSolo(UnresolvedTrackSoloTarget),
+    TrackInsert(UnresolvedTrackInsertTarget),
+    TrackDuplicate(UnresolvedTrackDuplicateTarget),
+    TrackDelete(UnresolvedTrackDeleteTarget),
+    TrackFolderSpill(UnresolvedTrackFolderSpillTarget),
+    TrackRecordInput(UnresolvedTrackRecordInputTarget),
+    TrackInputMonitoring(UnresolvedTrackInputMonitoringTarget),
+    TrackCue(UnresolvedTrackCueTarget),
     TrackAutomationMode(UnresolvedTrackAutomationModeTarget),
     TrackSendPan(UnresolvedRoutePanTarget),
     TrackSendMute(UnresolvedRouteMuteTarget),
@@ -71,18 +91,40 @@ pub enum UnresolvedReaperTarget {
     FxNavigate(UnresolvedFxNavigateTarget),
     AllTrackFxEnable(UnresolvedAllTrackFxEnableTarget),
     Transport(UnresolvedTransportTarget),
+    Punch(UnresolvedPunchTarget),
+    UndoRedo(UnresolvedUndoRedoTarget),
+    TimeSelection(UnresolvedTimeSelectionTarget),
+    MonitoringVolume(UnresolvedMonitoringVolumeTarget),
+    Metronome(UnresolvedMetronomeTarget),
     LoadFxPreset(UnresolvedLoadFxSnapshotTarget),
     AutomationTouchState(UnresolvedAutomationTouchStateTarget),
     GoToBookmark(UnresolvedGoToBookmarkTarget),
+    NavigateBookmarks(UnresolvedNavigateBookmarksTarget),
     Seek(UnresolvedSeekTarget),
+    SetExtState(UnresolvedSetExtStateTarget),
+    RunScript(UnresolvedRunScriptTarget),
     SendMidi(UnresolvedMidiSendTarget),
+    SendMidiMacro(UnresolvedMidiMacroSendTarget),
+    SendProgramChange(UnresolvedProgramChangeSendTarget),
+    SendMidiClock(UnresolvedMidiClockSendTarget),
     SendOsc(UnresolvedOscSendTarget),
     ClipTransport(UnresolvedClipTransportTarget),
     ClipSeek(UnresolvedClipSeekTarget),
     ClipVolume(UnresolvedClipVolumeTarget),
+    ClipColumnStop(UnresolvedClipColumnStopTarget),
+    ClipSceneLaunch(UnresolvedClipSceneLaunchTarget),
+    ClipCue(UnresolvedClipCueTarget),
     LoadMappingSnapshot(UnresolvedLoadMappingSnapshotTarget),
+    SaveMappingSnapshot(UnresolvedSaveMappingSnapshotTarget),
     EnableMappings(UnresolvedEnableMappingsTarget),
+    EnableGroup(UnresolvedEnableGroupTarget),
     NavigateWithinGroup(UnresolvedNavigateWithinGroupTarget),
+    SelectLayer(UnresolvedSelectLayerTarget),
+    SetBankOffset(UnresolvedSetBankOffsetTarget),
+    AdjustMappingMode(UnresolvedAdjustMappingModeTarget),
+    Multi(UnresolvedMultiTarget),
+    Panic(UnresolvedPanicTarget),
+    InvokeInstance(UnresolvedInvokeInstanceTarget),
     EnableInstances(UnresolvedEnableInstancesTarget),
     AnyOn(UnresolvedAnyOnTarget),
     LastTouched(UnresolvedLastTouchedTarget),
@@ -122,6 +164,9 @@ impl UnresolvedReaperTarget {
     /// Should return true if the target should be refreshed (reresolved) on parameter changes.
     /// Usually true for all targets that use `<Dynamic>` selector.
     pub fn can_be_affected_by_parameters(&self) -> bool {
+        if let Self::Multi(t) = self {
+            return t.can_be_affected_by_parameters();
+        }
         let descriptors = self.unpack_descriptors();
         if let Some(desc) = descriptors.track {
             if matches!(&desc.track, VirtualTrack::Dynamic(_)) {
@@ -158,6 +203,30 @@ impl UnresolvedReaperTarget {
         false
     }
 
+    /// Should return true if the target might have to be refreshed on change events which concern
+    /// tracks other than the one it's currently resolved to, e.g. when using the `<Selected>` track
+    /// or selecting the track by name or position.
+    ///
+    /// FX selectors don't matter here because they only depend on the resolved track. Route
+    /// selectors usually don't matter either, with one exception: Selecting a route by name
+    /// depends on the name of the track on the other side of the route.
+    pub fn can_be_affected_by_foreign_change_events(&self) -> bool {
+        if let Self::Multi(t) = self {
+            return t.can_be_affected_by_foreign_change_events();
+        }
+        let descriptors = self.unpack_descriptors();
+        if let Some(desc) = descriptors.route {
+            if matches!(&desc.route.selector, TrackRouteSelector::ByName(_)) {
+                return true;
+            }
+        }
+        use VirtualTrack::*;
+        match descriptors.track {
+            None => false,
+            Some(desc) => !matches!(&desc.track, This | Master | ById(_)),
+        }
+    }
+
     fn unpack_descriptors(&self) -> Descriptors {
         if let Some(d) = self.fx_parameter_descriptor() {
             return Descriptors {
@@ -218,6 +287,29 @@ pub fn get_track_route(
         .map_err(|_| "route doesn't exist")
 }
 
+/// Resolves the route on each track (e.g. the "Reverb" send on all selected tracks).
+///
+/// Tracks which don't have such a route are skipped. Returns an error if not a single route
+/// exists.
+pub fn get_track_routes(
+    context: ExtendedProcessorContext,
+    descriptor: &TrackRouteDescriptor,
+    compartment: MappingCompartment,
+) -> Result<Vec<TrackRoute>, &'static str> {
+    let tracks = get_effective_tracks(context, &descriptor.track_descriptor.track, compartment)?;
+    if tracks.is_empty() {
+        return Err("no track resolved");
+    }
+    let routes: Vec<_> = tracks
+        .iter()
+        .filter_map(|track| descriptor.route.resolve(track, context, compartment).ok())
+        .collect();
+    if routes.is_empty() {
+        return Err("route doesn't exist");
+    }
+    Ok(routes)
+}
+
 #[derive(Debug)]
 pub struct TrackDescriptor {
     pub track: VirtualTrack,
@@ -324,7 +416,7 @@ impl TrackRouteSelector {
     ) -> Result<u32, TrackRouteResolveError> {
         let sliced_params = compartment.slice_params(context.params());
         let result = evaluator
-            .evaluate(sliced_params)
+            .evaluate_with_additional_vars(sliced_params, |name, _| context.osc_capture(name))
             .map_err(|_| TrackRouteResolveError::ExpressionFailed)?
             .round() as i32;
         if result < 0 {
@@ -479,7 +571,20 @@ impl VirtualFxParameter {
                     name: Some(name.clone()),
                     index: None,
                 }),
-            ByIndex(i) | ById(i) => resolve_parameter_by_index(fx, *i),
+            ByIndex(i) => {
+                // Paged by target "FX: Navigate between parameter banks"
+                let offset = context
+                    .control_context()
+                    .instance_state
+                    .borrow()
+                    .fx_parameter_bank_offset();
+                // The offset is restored from the session data, so it's not necessarily sane.
+                let i = i
+                    .checked_add(offset)
+                    .ok_or(FxParameterResolveError::OutOfRange)?;
+                resolve_parameter_by_index(fx, i)
+            }
+            ById(i) => resolve_parameter_by_index(fx, *i),
         }
     }
 
@@ -502,7 +607,7 @@ impl VirtualFxParameter {
     ) -> Result<u32, FxParameterResolveError> {
         let sliced_params = compartment.slice_params(context.params());
         let result = evaluator
-            .evaluate(sliced_params)
+            .evaluate_with_additional_vars(sliced_params, |name, _| context.osc_capture(name))
             .map_err(|_| FxParameterResolveError::ExpressionFailed)?
             .round() as i32;
         if result < 0 {
@@ -777,7 +882,18 @@ impl VirtualTrack {
                 .take(if *allow_multiple { MAX_MULTIPLE } else { 1 })
                 .collect(),
             ByIndex(index) => {
-                let single = resolve_track_by_index(project, *index as i32)?;
+                // Retargeted by target "Track: Spill folder"
+                let spilled_folder = context
+                    .control_context()
+                    .instance_state
+                    .borrow()
+                    .spilled_folder_track()
+                    .map(|id| project.track_by_guid(&id))
+                    .filter(|t| t.is_available());
+                let single = match spilled_folder {
+                    None => resolve_track_by_index(project, *index as i32)?,
+                    Some(folder) => resolve_folder_child_by_index(&folder, *index)?,
+                };
                 vec![single]
             }
         };
@@ -841,7 +957,7 @@ impl VirtualTrack {
                         None
                     }
                 }
-                _ => None,
+                _ => context.osc_capture(name),
             })
             .map_err(|_| TrackResolveError::ExpressionFailed)?
             .round() as i32;
@@ -1068,7 +1184,7 @@ impl VirtualChainFx {
     ) -> Result<u32, FxResolveError> {
         let sliced_params = compartment.slice_params(context.params());
         let result = evaluator
-            .evaluate(sliced_params)
+            .evaluate_with_additional_vars(sliced_params, |name, _| context.osc_capture(name))
             .map_err(|_| FxResolveError::ExpressionFailed)?
             .round() as i32;
         if result < 0 {
@@ -1252,6 +1368,17 @@ fn resolve_track_by_index(project: Project, index: i32) -> Result<Track, TrackRe
     }
 }
 
+fn resolve_folder_child_by_index(folder: &Track, index: u32) -> Result<Track, TrackResolveError> {
+    get_folder_children(folder)
+        .into_iter()
+        .nth(index as usize)
+        .ok_or(TrackResolveError::TrackNotFound {
+            guid: None,
+            name: None,
+            index: Some(index),
+        })
+}
+
 pub fn resolve_track_route_by_index(
     track: &Track,
     route_type: TrackRouteType,
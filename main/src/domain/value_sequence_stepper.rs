@@ -0,0 +1,255 @@
+use derive_more::Display;
+use enum_iterator::IntoEnumIterator;
+use helgoboss_learn::{ControlValue, UnitValue, ValueSequence};
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+use serde::{Deserialize, Serialize};
+
+/// Sequence values closer than this to the current target value are considered to be the current
+/// position within the sequence.
+const POSITION_TOLERANCE: f64 = 0.001;
+/// Step size used for ranges in the sequence which don't define one explicitly.
+const DEFAULT_RANGE_STEP_SIZE: f64 = 0.01;
+
+/// Decides what happens when a button press steps beyond the end of the target value sequence.
+#[derive(
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    Debug,
+    Serialize,
+    Deserialize,
+    IntoEnumIterator,
+    TryFromPrimitive,
+    IntoPrimitive,
+    Display,
+)]
+#[repr(usize)]
+pub enum ValueSequenceStepping {
+    #[serde(rename = "off")]
+    #[display(fmt = "Off")]
+    Off,
+    /// Stays at the last value.
+    #[serde(rename = "stop")]
+    #[display(fmt = "Stop at end")]
+    Stop,
+    /// Starts again with the first value.
+    #[serde(rename = "wrap")]
+    #[display(fmt = "Wrap")]
+    Wrap,
+    /// Reverses the direction at both ends.
+    #[serde(rename = "ping-pong")]
+    #[display(fmt = "Ping-pong")]
+    PingPong,
+}
+
+impl Default for ValueSequenceStepping {
+    fn default() -> Self {
+        ValueSequenceStepping::Off
+    }
+}
+
+/// Expands the ranges in the given sequence into single values.
+pub fn unpack_value_sequence(sequence: &ValueSequence) -> Vec<UnitValue> {
+    sequence.unpack(UnitValue::new(DEFAULT_RANGE_STEP_SIZE))
+}
+
+/// Keeps track of the position within the target value sequence when stepping through it with
+/// button presses.
+///
+/// Button presses don't reach the mode. Instead, each press is translated into the next value of
+/// the sequence, which is then sent to the target as is.
+#[derive(Clone, Debug, Default)]
+pub struct ValueSequenceStepper {
+    index: Option<usize>,
+    moving_backward: bool,
+}
+
+impl ValueSequenceStepper {
+    /// Returns the target value to which the given button press leads.
+    ///
+    /// If the target value has been changed from somewhere else, stepping continues from the
+    /// sequence value closest to the current target value.
+    ///
+    /// Returns `None` if the value is not a button value (e.g. a relative one) or if the sequence
+    /// is empty. Such values should be processed as usual. Returns `Some(None)` for button
+    /// releases, which should be ignored.
+    pub fn process_button(
+        &mut self,
+        stepping: ValueSequenceStepping,
+        sequence: &[UnitValue],
+        current_target_value: Option<UnitValue>,
+        value: ControlValue,
+    ) -> Option<Option<UnitValue>> {
+        if stepping == ValueSequenceStepping::Off || sequence.is_empty() {
+            return None;
+        }
+        if value.to_unit_value().ok()?.is_zero() {
+            return Some(None);
+        }
+        let next_index = match self.synchronized_index(sequence, current_target_value) {
+            // Not started yet. Begin with the first value.
+            None => 0,
+            Some(i) => self.advance(stepping, i, sequence.len()),
+        };
+        self.index = Some(next_index);
+        Some(Some(sequence[next_index]))
+    }
+
+    fn synchronized_index(
+        &self,
+        sequence: &[UnitValue],
+        current_target_value: Option<UnitValue>,
+    ) -> Option<usize> {
+        let current = match current_target_value {
+            None => return self.index.filter(|i| *i < sequence.len()),
+            Some(v) => v,
+        };
+        if let Some(i) = self.index {
+            if let Some(v) = sequence.get(i) {
+                if (v.get() - current.get()).abs() <= POSITION_TOLERANCE {
+                    return Some(i);
+                }
+            }
+        }
+        // Target has been changed from elsewhere (or we haven't stepped yet).
+        sequence
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| {
+                let distance_a = (a.get() - current.get()).abs();
+                let distance_b = (b.get() - current.get()).abs();
+                distance_a
+                    .partial_cmp(&distance_b)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+            .map(|(i, _)| i)
+    }
+
+    fn advance(&mut self, stepping: ValueSequenceStepping, index: usize, len: usize) -> usize {
+        use ValueSequenceStepping::*;
+        let last = len - 1;
+        match stepping {
+            Off | Stop => (index + 1).min(last),
+            Wrap => {
+                if index >= last {
+                    0
+                } else {
+                    index + 1
+                }
+            }
+            PingPong => {
+                if last == 0 {
+                    return 0;
+                }
+                if self.moving_backward && index == 0 {
+                    self.moving_backward = false;
+                } else if !self.moving_backward && index >= last {
+                    self.moving_backward = true;
+                }
+                if self.moving_backward {
+                    index - 1
+                } else {
+                    index + 1
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wrap() {
+        // Given
+        let sequence = sequence();
+        let mut stepper = ValueSequenceStepper::default();
+        // When
+        let values = press_n_times(&mut stepper, ValueSequenceStepping::Wrap, &sequence, 5);
+        // Then
+        assert_eq!(values, vec![0.0, 0.25, 0.5, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn stop_at_end() {
+        // Given
+        let sequence = sequence();
+        let mut stepper = ValueSequenceStepper::default();
+        // When
+        let values = press_n_times(&mut stepper, ValueSequenceStepping::Stop, &sequence, 5);
+        // Then
+        assert_eq!(values, vec![0.0, 0.25, 0.5, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn ping_pong() {
+        // Given
+        let sequence = sequence();
+        let mut stepper = ValueSequenceStepper::default();
+        // When
+        let values = press_n_times(&mut stepper, ValueSequenceStepping::PingPong, &sequence, 8);
+        // Then
+        assert_eq!(values, vec![0.0, 0.25, 0.5, 1.0, 0.5, 0.25, 0.0, 0.25]);
+    }
+
+    #[test]
+    fn continue_from_current_target_value() {
+        // Given
+        let sequence = sequence();
+        let mut stepper = ValueSequenceStepper::default();
+        // When
+        let value = stepper.process_button(
+            ValueSequenceStepping::Wrap,
+            &sequence,
+            Some(UnitValue::new(0.45)),
+            abs(1.0),
+        );
+        // Then
+        assert_eq!(value, Some(Some(UnitValue::MAX)));
+    }
+
+    #[test]
+    fn ignore_release() {
+        // Given
+        let sequence = sequence();
+        let mut stepper = ValueSequenceStepper::default();
+        // When
+        let value = stepper.process_button(ValueSequenceStepping::Wrap, &sequence, None, abs(0.0));
+        // Then
+        assert_eq!(value, Some(None));
+    }
+
+    fn press_n_times(
+        stepper: &mut ValueSequenceStepper,
+        stepping: ValueSequenceStepping,
+        sequence: &[UnitValue],
+        n: usize,
+    ) -> Vec<f64> {
+        let mut current = None;
+        (0..n)
+            .map(|_| {
+                let v = stepper
+                    .process_button(stepping, sequence, current, abs(1.0))
+                    .unwrap()
+                    .unwrap();
+                current = Some(v);
+                v.get()
+            })
+            .collect()
+    }
+
+    fn sequence() -> Vec<UnitValue> {
+        vec![
+            UnitValue::MIN,
+            UnitValue::new(0.25),
+            UnitValue::new(0.5),
+            UnitValue::MAX,
+        ]
+    }
+
+    fn abs(v: f64) -> ControlValue {
+        ControlValue::AbsoluteContinuous(UnitValue::new(v))
+    }
+}
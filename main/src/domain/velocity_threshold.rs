@@ -0,0 +1,141 @@
+use helgoboss_learn::{ControlValue, UnitValue};
+
+/// Distinguishes soft from hard button presses (e.g. pad hits) by comparing the incoming velocity
+/// with a threshold.
+///
+/// Each side can be mapped to a fixed control value or be ignored completely. The latter makes it
+/// possible to split one source into two mappings, one reacting to soft and one to hard presses.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct VelocityThreshold {
+    pub threshold: UnitValue,
+    /// Control value for presses softer than the threshold. `None` means these presses are ignored.
+    pub below_value: Option<UnitValue>,
+    /// Control value for presses at least as hard as the threshold. `None` means these presses
+    /// are ignored.
+    pub above_value: Option<UnitValue>,
+}
+
+/// Keeps track of whether the last press made it through, so the release can follow it.
+#[derive(Clone, Debug, Default)]
+pub struct VelocityThresholdFilter {
+    /// `None` if no press has been seen yet (e.g. because the mapping has just been replaced).
+    press_was_let_through: Option<bool>,
+}
+
+impl VelocityThresholdFilter {
+    /// Returns `None` if the control value should not be processed any further.
+    ///
+    /// A release (zero) is filtered like the press it belongs to: It passes unchanged only if that
+    /// press passed. Otherwise the mapping which ignores soft presses would see the release of a
+    /// soft press, for example, and switch off what the other mapping has switched on. If the
+    /// press is unknown, the release passes, so nothing stays switched on by accident. Relative
+    /// values are let through unchanged.
+    pub fn process(
+        &mut self,
+        settings: &VelocityThreshold,
+        value: ControlValue,
+    ) -> Option<ControlValue> {
+        let velocity = match value.to_unit_value() {
+            Ok(v) => v,
+            Err(_) => return Some(value),
+        };
+        if velocity.is_zero() {
+            return match self.press_was_let_through.take() {
+                Some(false) => None,
+                _ => Some(value),
+            };
+        }
+        let new_value = if velocity < settings.threshold {
+            settings.below_value
+        } else {
+            settings.above_value
+        };
+        self.press_was_let_through = Some(new_value.is_some());
+        Some(ControlValue::AbsoluteContinuous(new_value?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use helgoboss_learn::DiscreteIncrement;
+
+    #[test]
+    fn dual_values() {
+        // Given
+        let t = VelocityThreshold {
+            threshold: UnitValue::new(0.5),
+            below_value: Some(UnitValue::new(0.2)),
+            above_value: Some(UnitValue::MAX),
+        };
+        let mut f = VelocityThresholdFilter::default();
+        // Then
+        assert_eq!(f.process(&t, abs(0.3)), Some(abs(0.2)));
+        assert_eq!(f.process(&t, abs(0.5)), Some(abs(1.0)));
+        assert_eq!(f.process(&t, abs(0.9)), Some(abs(1.0)));
+    }
+
+    #[test]
+    fn ignore_one_side() {
+        // Given
+        let t = VelocityThreshold {
+            threshold: UnitValue::new(0.5),
+            below_value: None,
+            above_value: Some(UnitValue::MAX),
+        };
+        let mut f = VelocityThresholdFilter::default();
+        // Then
+        assert_eq!(f.process(&t, abs(0.3)), None);
+        assert_eq!(f.process(&t, abs(0.7)), Some(abs(1.0)));
+    }
+
+    #[test]
+    fn filter_release_like_its_press() {
+        // Given
+        let t = VelocityThreshold {
+            threshold: UnitValue::new(0.5),
+            below_value: None,
+            above_value: Some(UnitValue::MAX),
+        };
+        let mut f = VelocityThresholdFilter::default();
+        // Then
+        assert_eq!(f.process(&t, abs(0.3)), None);
+        assert_eq!(f.process(&t, abs(0.0)), None);
+        assert_eq!(f.process(&t, abs(0.7)), Some(abs(1.0)));
+        assert_eq!(f.process(&t, abs(0.0)), Some(abs(0.0)));
+    }
+
+    #[test]
+    fn let_through_release_of_unknown_press() {
+        // Given
+        let t = VelocityThreshold {
+            threshold: UnitValue::new(0.5),
+            below_value: None,
+            above_value: None,
+        };
+        let mut f = VelocityThresholdFilter::default();
+        // Then
+        assert_eq!(f.process(&t, abs(0.0)), Some(abs(0.0)));
+        assert_eq!(f.process(&t, abs(0.3)), None);
+        assert_eq!(f.process(&t, abs(0.0)), None);
+        assert_eq!(f.process(&t, abs(0.0)), Some(abs(0.0)));
+    }
+
+    #[test]
+    fn let_through_relative() {
+        // Given
+        let t = VelocityThreshold {
+            threshold: UnitValue::new(0.5),
+            below_value: None,
+            above_value: None,
+        };
+        let mut f = VelocityThresholdFilter::default();
+        let relative = ControlValue::Relative(DiscreteIncrement::new(1));
+        // Then
+        assert_eq!(f.process(&t, relative), Some(relative));
+    }
+
+    fn abs(v: f64) -> ControlValue {
+        ControlValue::AbsoluteContinuous(UnitValue::new(v))
+    }
+}
@@ -135,6 +135,15 @@ impl VirtualSourceValue {
     pub fn control_value(&self) -> ControlValue {
         self.control_value
     }
+
+    /// Shifts the control element by the given bank offset (see
+    /// [`VirtualControlElement::with_offset`]).
+    pub fn with_offset(self, offset: u32) -> Self {
+        VirtualSourceValue {
+            control_element: self.control_element.with_offset(offset),
+            ..self
+        }
+    }
 }
 
 #[derive(Clone, PartialEq, Debug)]
@@ -236,6 +245,26 @@ impl VirtualControlElement {
             Multi(i) | Button(i) => *i,
         }
     }
+
+    /// Shifts indexed control elements by the given bank offset, e.g. with an offset of 8,
+    /// "Multi 1" becomes "Multi 9". Named control elements are left untouched.
+    pub fn with_offset(self, offset: u32) -> Self {
+        use VirtualControlElement::*;
+        match self {
+            Multi(id) => Multi(id.with_offset(offset)),
+            Button(id) => Button(id.with_offset(offset)),
+        }
+    }
+}
+
+impl VirtualControlElementId {
+    fn with_offset(self, offset: u32) -> Self {
+        use VirtualControlElementId::*;
+        match self {
+            Indexed(index) => Indexed(index.saturating_add(offset)),
+            Named(_) => self,
+        }
+    }
 }
 
 pub mod control_element_domains {
@@ -9,6 +9,7 @@ pub const GROUP_CONTROL_ENABLED: bool = true;
 pub const GROUP_FEEDBACK_ENABLED: bool = true;
 
 pub const SOURCE_OSC_IS_RELATIVE: bool = false;
+pub const SOURCE_OSC_FEEDBACK_IS_RELATIVE: bool = false;
 
 const UNIT_INTERVAL: Interval<f64> = Interval(0.0, 1.0);
 pub const GLUE_STEP_SIZE_INTERVAL: Interval<f64> = Interval(0.01, 0.01);
@@ -34,6 +35,7 @@ pub const TARGET_BOOKMARK_SET_LOOP_POINTS: bool = false;
 pub const TARGET_POLL_FOR_FEEDBACK: bool = true;
 pub const TARGET_CLIP_NEXT_BAR: bool = false;
 pub const TARGET_CLIP_BUFFERED: bool = false;
+pub const TARGET_CUE_OUTPUT: u32 = 0;
 pub const TARGET_TRACK_SELECTION_SCROLL_ARRANGE_VIEW: bool = false;
 pub const TARGET_TRACK_SELECTION_SCROLL_MIXER: bool = false;
 pub const TARGET_SEEK_USE_TIME_SELECTION: bool = false;
@@ -43,5 +45,9 @@ pub const TARGET_SEEK_USE_PROJECT: bool = true;
 pub const TARGET_SEEK_MOVE_VIEW: bool = true;
 pub const TARGET_SEEK_SEEK_PLAY: bool = true;
 pub const TARGET_LOAD_MAPPING_SNAPSHOT_ACTIVE_MAPPINGS_ONLY: bool = false;
+pub const TARGET_SAVE_MAPPING_SNAPSHOT_ACTIVE_MAPPINGS_ONLY: bool = false;
+pub const TARGET_DELETE_TRACK_REQUIRE_CONFIRMATION: bool = true;
+pub const TARGET_SEND_MIDI_ARPEGGIATOR_STEPS_PER_BEAT: u32 = 4;
+pub const TARGET_FX_PARAMETER_BANK_SIZE: u32 = 8;
 
 pub const OSC_ARG_INDEX: u32 = 0;
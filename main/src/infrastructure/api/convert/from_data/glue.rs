@@ -1,3 +1,7 @@
+use crate::domain::{
+    CurveInterpolation, EncoderAccelerationProfile, MusicalScale, RampCurve, ValueSequenceStepping,
+    DEFAULT_ENCODER_ACCELERATION_CURVE,
+};
 use crate::infrastructure::api::convert::from_data::ConversionStyle;
 use crate::infrastructure::api::convert::{defaults, ConversionResult};
 use crate::infrastructure::data::ModeModelData;
@@ -105,7 +109,11 @@ pub fn convert_glue(data: ModeModelData, style: ConversionStyle) -> ConversionRe
                 InverseTargetValueOnOnly => Some(T::InverseTargetValueOnOnly),
             }
         },
+        takeover_catch_range: style.required_value(data.takeover_catch_range.get()),
         target_value_sequence: style.required_value(data.target_value_sequence.to_string()),
+        target_value_sequence_stepping: style.required_value(convert_value_sequence_stepping(
+            data.target_value_sequence_stepping,
+        )),
         feedback_kind: {
             use schema::FeedbackKind as T;
             use FeedbackType::*;
@@ -159,10 +167,121 @@ pub fn convert_glue(data: ModeModelData, style: ConversionStyle) -> ConversionRe
             };
             style.required_value(v)
         },
+        velocity_threshold: data
+            .velocity_threshold
+            .map(|threshold| schema::VelocityThreshold {
+                threshold: threshold.get(),
+                below_value: data.velocity_below_threshold_value.map(|v| v.get()),
+                above_value: data.velocity_above_threshold_value.map(|v| v.get()),
+            }),
+        scale_quantization: data
+            .quantization_scale
+            .map(|scale| schema::ScaleQuantization {
+                scale: convert_musical_scale(scale),
+                root: style.required_value(data.quantization_root),
+            }),
+        min_target_hit_interval: style.required_value(data.min_target_hit_interval as _),
+        dead_zone: style.required_value(data.dead_zone.get()),
+        hysteresis: match (
+            data.hysteresis_lower_threshold,
+            data.hysteresis_upper_threshold,
+        ) {
+            (Some(lower), Some(upper)) => Some(schema::Hysteresis {
+                lower_threshold: lower.get(),
+                upper_threshold: upper.get(),
+            }),
+            _ => None,
+        },
+        ramp: if data.ramp_duration > 0 {
+            Some(schema::Ramp {
+                duration: data.ramp_duration as _,
+                curve: style.required_value(convert_ramp_curve(data.ramp_curve)),
+                return_on_release: style.required_value(data.ramp_returns_on_release),
+            })
+        } else {
+            None
+        },
+        feedback_deadband: style.required_value(data.feedback_deadband.get()),
+        encoder_acceleration: if data.encoder_acceleration_profile
+            != EncoderAccelerationProfile::Off
+        {
+            Some(schema::EncoderAcceleration {
+                profile: convert_encoder_acceleration_profile(data.encoder_acceleration_profile),
+                curve: style.required_value_with_default(
+                    data.encoder_acceleration_curve,
+                    DEFAULT_ENCODER_ACCELERATION_CURVE,
+                ),
+            })
+        } else {
+            None
+        },
+        response_curve: if data.response_curve.is_empty() {
+            None
+        } else {
+            Some(schema::ResponseCurve {
+                points: data
+                    .response_curve
+                    .points
+                    .iter()
+                    .map(|p| schema::ResponseCurvePoint {
+                        x: p.x.get(),
+                        y: p.y.get(),
+                    })
+                    .collect(),
+                interpolation: style.required_value(convert_curve_interpolation(
+                    data.response_curve.interpolation,
+                )),
+            })
+        },
     };
     Ok(glue)
 }
 
+fn convert_curve_interpolation(interpolation: CurveInterpolation) -> schema::CurveInterpolation {
+    use schema::CurveInterpolation as T;
+    use CurveInterpolation::*;
+    match interpolation {
+        Linear => T::Linear,
+        Smooth => T::Smooth,
+        Step => T::Step,
+    }
+}
+
+fn convert_ramp_curve(curve: RampCurve) -> schema::RampCurve {
+    use schema::RampCurve as T;
+    use RampCurve::*;
+    match curve {
+        Linear => T::Linear,
+        Exponential => T::Exponential,
+        Logarithmic => T::Logarithmic,
+    }
+}
+
+fn convert_encoder_acceleration_profile(
+    profile: EncoderAccelerationProfile,
+) -> schema::EncoderAccelerationProfile {
+    use schema::EncoderAccelerationProfile as T;
+    use EncoderAccelerationProfile::*;
+    match profile {
+        Off => T::Off,
+        Linear => T::Linear,
+        Exponential => T::Exponential,
+    }
+}
+
+fn convert_value_sequence_stepping(
+    stepping: ValueSequenceStepping,
+) -> schema::ValueSequenceStepping {
+    use schema::ValueSequenceStepping as T;
+    use ValueSequenceStepping::*;
+    match stepping {
+        Off => T::Off,
+        Stop => T::Stop,
+        Wrap => T::Wrap,
+        PingPong => T::PingPong,
+    }
+}
+
 fn convert_absolute_mode(v: AbsoluteMode, style: ConversionStyle) -> Option<schema::AbsoluteMode> {
     use schema::AbsoluteMode as T;
     use AbsoluteMode::*;
@@ -174,6 +293,21 @@ fn convert_absolute_mode(v: AbsoluteMode, style: ConversionStyle) -> Option<sche
     style.required_value(mode)
 }
 
+fn convert_musical_scale(v: MusicalScale) -> schema::MusicalScale {
+    use schema::MusicalScale as T;
+    use MusicalScale::*;
+    match v {
+        Major => T::Major,
+        NaturalMinor => T::NaturalMinor,
+        HarmonicMinor => T::HarmonicMinor,
+        Dorian => T::Dorian,
+        Mixolydian => T::Mixolydian,
+        MajorPentatonic => T::MajorPentatonic,
+        MinorPentatonic => T::MinorPentatonic,
+        Blues => T::Blues,
+    }
+}
+
 fn convert_unit_interval(min: UnitValue, max: UnitValue) -> schema::Interval<f64> {
     schema::Interval(min.get(), max.get())
 }
@@ -27,6 +27,7 @@ pub fn convert_group(
             data.enabled_data.feedback_is_enabled,
             defaults::GROUP_FEEDBACK_ENABLED,
         ),
+        output_device_id: data.midi_output_device_id,
         activation_condition: convert_activation_condition(data.activation_condition_data),
     };
     Ok(group)
@@ -33,6 +33,10 @@ pub fn convert_mapping(
             data.enabled_data.feedback_is_enabled,
             defaults::MAPPING_FEEDBACK_ENABLED,
         ),
+        max_control_rate: style.optional_value(data.max_control_rate),
+        // Not using the conversion style here because base layer and "all layers" must stay
+        // distinguishable.
+        layer: data.layer.map(|l| l.get()),
         activation_condition: convert_activation_condition(data.activation_condition_data),
         on_activate: style.optional_value(advanced.extension_desc.on_activate),
         on_deactivate: style.optional_value(advanced.extension_desc.on_deactivate),
@@ -134,6 +138,7 @@ fn convert_lifecycle_midi_message_model(
             let message = convert_raw_midi_msg(msg)?;
             schema::SendMidiFeedbackAction::Raw { message }
         }
+        LifecycleMidiMessageModel::Macro(name) => schema::SendMidiFeedbackAction::Macro { name },
     };
     Ok(action)
 }
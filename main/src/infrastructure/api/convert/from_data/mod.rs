@@ -13,7 +13,7 @@ pub use compartment::*;
 mod target;
 
 use crate::application::{ActivationType, VirtualControlElementType};
-use crate::domain::Tag;
+use crate::domain::{ReaperStateCondition, Tag};
 use crate::infrastructure::api::convert::defaults;
 use crate::infrastructure::data::{ActivationConditionData, VirtualControlElementIdData};
 use helgoboss_learn::OscTypeTag;
@@ -185,5 +185,28 @@ fn convert_activation_condition(
             };
             Some(T::Eel(condition))
         }
+        Expression => {
+            let condition = schema::ExpressionActivationCondition {
+                condition: condition_data.expression_condition,
+            };
+            Some(T::Expression(condition))
+        }
+        ReaperState => {
+            let condition = schema::ReaperStateActivationCondition {
+                state: {
+                    use schema::ReaperState as T;
+                    use ReaperStateCondition::*;
+                    match condition_data.reaper_state_condition {
+                        Playing => T::Playing,
+                        Recording => T::Recording,
+                        Stopped => T::Stopped,
+                        AnyTrackArmed => T::AnyTrackArmed,
+                        AnyTrackSelected => T::AnyTrackSelected,
+                        AnyFxFocused => T::AnyFxFocused,
+                    }
+                },
+            };
+            Some(T::ReaperState(condition))
+        }
     }
 }
@@ -1,4 +1,5 @@
 use crate::application::{MidiSourceType, ReaperSourceType, SourceCategory};
+use crate::domain::{GameControllerElement, MmcCommand, OscDeltaMode};
 use crate::infrastructure::api::convert::from_data::{
     convert_control_element_id, convert_control_element_kind, convert_osc_argument, ConversionStyle,
 };
@@ -33,6 +34,7 @@ pub fn convert_source(
         };
         style.required_value(v)
     };
+    let output_device_id = data.midi_output_device_id;
     use SourceCategory::*;
     let source = match data.category {
         Never => schema::Source::NoneSource,
@@ -46,6 +48,7 @@ pub fn convert_source(
                         controller_number: convert_controller_number(data.number),
                         character: convert_character(data.character, style),
                         fourteen_bit: data.is_14_bit,
+                        output_device_id,
                     };
                     schema::Source::MidiControlChangeValue(s)
                 }
@@ -54,6 +57,7 @@ pub fn convert_source(
                         feedback_behavior,
                         channel: convert_channel(data.channel),
                         key_number: convert_key_number(data.number),
+                        output_device_id,
                     };
                     schema::Source::MidiNoteVelocity(s)
                 }
@@ -61,6 +65,7 @@ pub fn convert_source(
                     let s = schema::MidiNoteKeyNumberSource {
                         feedback_behavior,
                         channel: convert_channel(data.channel),
+                        output_device_id,
                     };
                     schema::Source::MidiNoteKeyNumber(s)
                 }
@@ -68,6 +73,7 @@ pub fn convert_source(
                     let s = schema::MidiPitchBendChangeValueSource {
                         feedback_behavior,
                         channel: convert_channel(data.channel),
+                        output_device_id,
                     };
                     schema::Source::MidiPitchBendChangeValue(s)
                 }
@@ -75,6 +81,7 @@ pub fn convert_source(
                     let s = schema::MidiChannelPressureAmountSource {
                         feedback_behavior,
                         channel: convert_channel(data.channel),
+                        output_device_id,
                     };
                     schema::Source::MidiChannelPressureAmount(s)
                 }
@@ -82,6 +89,7 @@ pub fn convert_source(
                     let s = schema::MidiProgramChangeNumberSource {
                         feedback_behavior,
                         channel: convert_channel(data.channel),
+                        output_device_id,
                     };
                     schema::Source::MidiProgramChangeNumber(s)
                 }
@@ -93,6 +101,7 @@ pub fn convert_source(
                         fourteen_bit: data.is_14_bit,
                         registered: data.is_registered,
                         character: convert_character(data.character, style),
+                        output_device_id,
                     };
                     schema::Source::MidiParameterNumberValue(s)
                 }
@@ -101,6 +110,7 @@ pub fn convert_source(
                         feedback_behavior,
                         channel: convert_channel(data.channel),
                         key_number: convert_key_number(data.number),
+                        output_device_id,
                     };
                     schema::Source::MidiPolyphonicKeyPressureAmount(s)
                 }
@@ -119,12 +129,14 @@ pub fn convert_source(
                         feedback_behavior,
                         pattern: style.required_value(data.raw_midi_pattern),
                         character: convert_character(data.character, style),
+                        output_device_id,
                     };
                     schema::Source::MidiRaw(s)
                 }
                 Script => {
                     let s = schema::MidiScriptSource {
                         script: style.required_value(data.midi_script),
+                        output_device_id,
                     };
                     schema::Source::MidiScript(s)
                 }
@@ -135,6 +147,7 @@ pub fn convert_source(
                             let s = schema::MackieLcdSource {
                                 channel: data.display_id,
                                 line: data.line,
+                                output_device_id,
                             };
                             schema::Source::MackieLcd(s)
                         }
@@ -145,6 +158,7 @@ pub fn convert_source(
                                         (id as usize).try_into().ok()?,
                                     )
                                 }),
+                                output_device_id,
                             };
                             schema::Source::MackieSevenSegmentDisplay(s)
                         }
@@ -152,11 +166,13 @@ pub fn convert_source(
                             let s = schema::SiniConE24DisplaySource {
                                 cell_index: data.display_id,
                                 item_index: data.line,
+                                output_device_id,
                             };
                             schema::Source::SiniConE24Display(s)
                         }
                         LaunchpadProScrollingText => {
-                            let s = schema::LaunchpadProScrollingTextDisplaySource;
+                            let s =
+                                schema::LaunchpadProScrollingTextDisplaySource { output_device_id };
                             schema::Source::LaunchpadProScrollingTextDisplay(s)
                         }
                     }
@@ -168,10 +184,24 @@ pub fn convert_source(
                 feedback_behavior,
                 address: style.required_value(data.osc_address_pattern),
                 argument: convert_osc_argument(data.osc_arg_index, data.osc_arg_type, style),
+                argument_filters: style.required_value(
+                    data.osc_arg_filters
+                        .into_iter()
+                        .map(|f| schema::OscArgumentFilter {
+                            index: f.index,
+                            value: f.value,
+                        })
+                        .collect(),
+                ),
                 relative: style.required_value_with_default(
                     data.osc_arg_is_relative,
                     defaults::SOURCE_OSC_IS_RELATIVE,
                 ),
+                delta_mode: convert_osc_delta_mode(data.osc_delta_mode, style),
+                relative_feedback: style.required_value_with_default(
+                    data.osc_feedback_is_relative,
+                    defaults::SOURCE_OSC_FEEDBACK_IS_RELATIVE,
+                ),
             };
             schema::Source::Osc(s)
         }
@@ -184,6 +214,29 @@ pub fn convert_source(
                 RealearnInstanceStart => {
                     schema::Source::RealearnInstanceStart(schema::RealearnInstanceStartSource)
                 }
+                GameController => {
+                    let s = schema::GameControllerSource {
+                        controller_index: data.game_controller_index,
+                        element: convert_game_controller_element(
+                            data.game_controller_element,
+                            style,
+                        ),
+                    };
+                    schema::Source::GameController(s)
+                }
+                InstanceMessage => {
+                    let s = schema::InstanceMessageSource {
+                        message: style.required_value(data.instance_message_name),
+                    };
+                    schema::Source::InstanceMessage(s)
+                }
+                Mmc => {
+                    let s = schema::MidiMachineControlSource {
+                        device_id: data.mmc_device_id,
+                        command: convert_mmc_command(data.mmc_command, style),
+                    };
+                    schema::Source::MidiMachineControl(s)
+                }
             }
         }
         Virtual => {
@@ -243,6 +296,73 @@ fn convert_transport_msg(
     Some(res)
 }
 
+fn convert_osc_delta_mode(v: OscDeltaMode, style: ConversionStyle) -> Option<schema::OscDeltaMode> {
+    use schema::OscDeltaMode as T;
+    use OscDeltaMode::*;
+    let res = match v {
+        Sign => T::Sign,
+        Steps => T::Steps,
+        Centered => T::Centered,
+    };
+    style.required_value(res)
+}
+
+fn convert_game_controller_element(
+    v: GameControllerElement,
+    style: ConversionStyle,
+) -> Option<schema::GameControllerElement> {
+    use schema::GameControllerElement as T;
+    use GameControllerElement::*;
+    let res = match v {
+        LeftStickX => T::LeftStickX,
+        LeftStickY => T::LeftStickY,
+        LeftZ => T::LeftZ,
+        RightStickX => T::RightStickX,
+        RightStickY => T::RightStickY,
+        RightZ => T::RightZ,
+        DPadX => T::DPadX,
+        DPadY => T::DPadY,
+        South => T::South,
+        East => T::East,
+        North => T::North,
+        West => T::West,
+        LeftTrigger => T::LeftTrigger,
+        LeftTrigger2 => T::LeftTrigger2,
+        RightTrigger => T::RightTrigger,
+        RightTrigger2 => T::RightTrigger2,
+        Select => T::Select,
+        Start => T::Start,
+        Mode => T::Mode,
+        LeftThumb => T::LeftThumb,
+        RightThumb => T::RightThumb,
+        DPadUp => T::DPadUp,
+        DPadDown => T::DPadDown,
+        DPadLeft => T::DPadLeft,
+        DPadRight => T::DPadRight,
+    };
+    style.required_value(res)
+}
+
+fn convert_mmc_command(
+    v: MmcCommand,
+    style: ConversionStyle,
+) -> Option<schema::MidiMachineControlCommand> {
+    use schema::MidiMachineControlCommand as T;
+    use MmcCommand::*;
+    let res = match v {
+        Stop => T::Stop,
+        Play => T::Play,
+        DeferredPlay => T::DeferredPlay,
+        FastForward => T::FastForward,
+        Rewind => T::Rewind,
+        RecordStrobe => T::RecordStrobe,
+        RecordExit => T::RecordExit,
+        Pause => T::Pause,
+        Locate => T::Locate,
+    };
+    style.required_value(res)
+}
+
 fn convert_mackie_seven_segment_display_scope(
     v: MackieSevenSegmentDisplayScope,
 ) -> Option<schema::MackieSevenSegmentDisplayScope> {
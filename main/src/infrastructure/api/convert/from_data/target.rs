@@ -1,36 +1,47 @@
 use crate::application::{
-    AutomationModeOverrideType, BookmarkAnchorType, RealearnAutomationMode, RealearnTrackArea,
-    TargetCategory, TargetUnit, TrackRouteSelectorType, VirtualFxParameterType, VirtualFxType,
-    VirtualTrackType,
+    AutomationModeOverrideType, BookmarkAnchorType, RealearnAutomationMode,
+    RealearnInputMonitoringMode, RealearnTrackArea, TargetCategory, TargetUnit,
+    TrackRouteSelectorType, VirtualFxParameterType, VirtualFxType, VirtualTrackType,
 };
 use crate::domain::{
-    ActionInvocationType, AnyOnParameter, Exclusivity, FeedbackResolution, FxDisplayType,
-    ReaperTargetType, SendMidiDestination, SoloBehavior, TouchedParameterType, TrackExclusivity,
-    TrackRouteType, TransportAction,
+    ActionInvocationType, AnyOnParameter, ArpeggiatorDirection, ArpeggiatorSettings,
+    BookmarkNavigationMode, ChordShape, ClipMatrixId, DualPanComponent, Exclusivity,
+    ExtStateValueFormat, FeedbackResolution, FxDisplayType, GroupEnableMode, MappingModeSetting,
+    MetronomeParameter, MtcFrameRate, PunchAction, ReaperTargetType, RecordInputKind,
+    SendMidiDestination, SlotStopQuantization, SoloBehavior, TimeRangeKind, TimeSelectionAction,
+    TouchedParameterType, TrackExclusivity, TrackRouteType, TransportAction, UndoRedoAction,
 };
 use crate::infrastructure::api::convert::from_data::{
     convert_control_element_id, convert_control_element_kind, convert_osc_argument, convert_tags,
     ConversionStyle,
 };
-use crate::infrastructure::api::convert::{defaults, ConversionResult};
+use crate::infrastructure::api::convert::{convert_multiple, defaults, ConversionResult};
 use crate::infrastructure::data::{
     deserialize_fx, deserialize_fx_parameter, deserialize_track, deserialize_track_route,
-    TargetModelData, TrackData,
+    MultiTargetChildData, TargetModelData, TrackData,
 };
 use realearn_api::schema;
 use realearn_api::schema::{
-    AllTrackFxOnOffStateTarget, AnyOnTarget, AutomationModeOverrideTarget, BookmarkDescriptor,
-    BookmarkRef, ClipDescriptor, ClipOutput, ClipSeekTarget, ClipTransportActionTarget,
-    ClipVolumeTarget, CycleThroughFxPresetsTarget, CycleThroughFxTarget,
-    CycleThroughGroupMappingsTarget, CycleThroughTracksTarget, EnableInstancesTarget,
-    EnableMappingsTarget, FxOnOffStateTarget, FxParameterValueTarget, FxVisibilityTarget,
-    GoToBookmarkTarget, LastTouchedTarget, LoadFxSnapshotTarget, LoadMappingSnapshotsTarget,
-    PlayRateTarget, ReaperActionTarget, RouteAutomationModeTarget, RouteMonoStateTarget,
-    RouteMuteStateTarget, RoutePanTarget, RoutePhaseTarget, RouteVolumeTarget, SeekTarget,
-    SendMidiTarget, SendOscTarget, TempoTarget, TrackArmStateTarget, TrackAutomationModeTarget,
-    TrackAutomationTouchStateTarget, TrackMuteStateTarget, TrackPanTarget, TrackPeakTarget,
-    TrackPhaseTarget, TrackSelectionStateTarget, TrackSoloStateTarget, TrackToolTarget,
-    TrackVisibilityTarget, TrackVolumeTarget, TrackWidthTarget, TransportActionTarget,
+    AdjustMappingModeTarget, AllTrackFxOnOffStateTarget, AnyOnTarget, AutomationModeOverrideTarget,
+    BookmarkDescriptor, BookmarkRef, ClipColumnStopTarget, ClipCueTarget, ClipDescriptor,
+    ClipOutput, ClipSceneLaunchTarget, ClipSeekTarget, ClipTransportActionTarget, ClipVolumeTarget,
+    CycleThroughFxPresetsTarget, CycleThroughFxTarget, CycleThroughGroupMappingsTarget,
+    CycleThroughTracksTarget, DeleteTrackTarget, DuplicateTrackTarget, EnableGroupTarget,
+    EnableInstancesTarget, EnableMappingsTarget, FxOnOffStateTarget, FxParameterBankTarget,
+    FxParameterValueTarget, FxVisibilityTarget, GoToBookmarkTarget, InsertTrackTarget,
+    InvokeInstanceTarget, LastTouchedTarget, LoadFxSnapshotTarget, LoadMappingSnapshotsTarget,
+    MetronomeTarget, MonitoringVolumeTarget, MultiTarget, MultiTargetChild,
+    NavigateBookmarksTarget, PanicTarget, PlayRateTarget, PunchActionTarget, ReaperActionTarget,
+    RouteAutomationModeTarget, RouteMonoStateTarget, RouteMuteStateTarget, RoutePanTarget,
+    RoutePhaseTarget, RouteVolumeTarget, RunScriptTarget, SaveMappingSnapshotTarget, SeekTarget,
+    SelectLayerTarget, SendMidiClockTarget, SendMidiMacroTarget, SendMidiTarget, SendOscTarget,
+    SendProgramChangeTarget, SetBankOffsetTarget, SetExtStateTarget, SpillFolderTarget,
+    TempoTarget, TimeSelectionTarget, TrackArmStateTarget, TrackAutomationModeTarget,
+    TrackAutomationTouchStateTarget, TrackCueTarget, TrackDualPanTarget,
+    TrackInputMonitoringTarget, TrackMuteStateTarget, TrackPanTarget, TrackPeakTarget,
+    TrackPhaseTarget, TrackRecordInputTarget, TrackSelectionStateTarget, TrackSoloStateTarget,
+    TrackToolTarget, TrackVisibilityTarget, TrackVolumeTarget, TrackWidthTarget,
+    TransportActionTarget, UndoRedoTarget,
 };
 
 pub fn convert_target(
@@ -100,6 +111,24 @@ fn convert_real_target(
             commons,
             action: convert_transport_action(data.transport_action),
         }),
+        Punch => T::PunchAction(PunchActionTarget {
+            commons,
+            action: convert_punch_action(data.punch_action),
+        }),
+        UndoRedo => T::UndoRedo(UndoRedoTarget {
+            commons,
+            action: convert_undo_redo_action(data.undo_redo_action),
+        }),
+        TimeSelection => T::TimeSelection(TimeSelectionTarget {
+            commons,
+            action: convert_time_selection_action(data.time_selection_action),
+            range: style.required_value(convert_time_range_kind(data.time_range_kind)),
+        }),
+        MonitoringVolume => T::MonitoringVolume(MonitoringVolumeTarget { commons }),
+        Metronome => T::Metronome(MetronomeTarget {
+            commons,
+            parameter: convert_metronome_parameter(data.metronome_parameter),
+        }),
         AnyOn => T::AnyOn(AnyOnTarget {
             commons,
             parameter: convert_any_on_parameter(data.any_on_parameter),
@@ -130,6 +159,17 @@ fn convert_real_target(
                 defaults::TARGET_BOOKMARK_SET_LOOP_POINTS,
             ),
         }),
+        NavigateBookmarks => T::NavigateBookmarks(NavigateBookmarksTarget {
+            commons,
+            bookmark_type: if data.bookmark_data.is_region {
+                schema::BookmarkType::Region
+            } else {
+                schema::BookmarkType::Marker
+            },
+            mode: style.required_value(convert_bookmark_navigation_mode(
+                data.bookmark_navigation_mode,
+            )),
+        }),
         TrackAutomationMode => T::TrackAutomationMode(TrackAutomationModeTarget {
             commons,
             track: convert_track_descriptor(
@@ -140,6 +180,16 @@ fn convert_real_target(
             exclusivity: convert_track_exclusivity(data.track_exclusivity),
             mode: convert_automation_mode(data.track_automation_mode),
         }),
+        TrackInputMonitoring => T::TrackInputMonitoring(TrackInputMonitoringTarget {
+            commons,
+            track: convert_track_descriptor(
+                data.track_data,
+                data.enable_only_if_track_is_selected,
+                style,
+            ),
+            exclusivity: convert_track_exclusivity(data.track_exclusivity),
+            mode: convert_input_monitoring_mode(data.track_input_monitoring_mode),
+        }),
         AutomationTouchState => T::TrackAutomationTouchState(TrackAutomationTouchStateTarget {
             commons,
             track: convert_track_descriptor(
@@ -190,6 +240,14 @@ fn convert_real_target(
             ),
             parameter: convert_fx_parameter_descriptor(data, style),
         }),
+        FxParameterBank => T::FxParameterBank(FxParameterBankTarget {
+            commons,
+            bank_size: style.required_value_with_default(
+                data.fx_parameter_bank_size
+                    .unwrap_or(defaults::TARGET_FX_PARAMETER_BANK_SIZE),
+                defaults::TARGET_FX_PARAMETER_BANK_SIZE,
+            ),
+        }),
         TrackSendAutomationMode => T::RouteAutomationMode(RouteAutomationModeTarget {
             commons,
             mode: convert_automation_mode(data.track_automation_mode),
@@ -240,16 +298,25 @@ fn convert_real_target(
                 };
                 style.required_value(output)
             },
-            clip: convert_clip_descriptor(data.slot_index),
+            clip: convert_clip_descriptor(
+                data.clip_matrix_id.as_ref(),
+                data.slot_row,
+                data.slot_index,
+            ),
             action: convert_transport_action(data.transport_action),
             next_bar: style
                 .required_value_with_default(data.next_bar, defaults::TARGET_CLIP_NEXT_BAR),
             buffered: style
                 .required_value_with_default(data.buffered, defaults::TARGET_CLIP_BUFFERED),
+            stop_quantization: data.stop_quantization.map(convert_stop_quantization),
         }),
         ClipSeek => T::ClipSeek(ClipSeekTarget {
             commons,
-            clip: convert_clip_descriptor(data.slot_index),
+            clip: convert_clip_descriptor(
+                data.clip_matrix_id.as_ref(),
+                data.slot_row,
+                data.slot_index,
+            ),
             feedback_resolution: convert_feedback_resolution(
                 data.seek_options.feedback_resolution,
                 style,
@@ -257,7 +324,39 @@ fn convert_real_target(
         }),
         ClipVolume => T::ClipVolume(ClipVolumeTarget {
             commons,
-            clip: convert_clip_descriptor(data.slot_index),
+            clip: convert_clip_descriptor(
+                data.clip_matrix_id.as_ref(),
+                data.slot_row,
+                data.slot_index,
+            ),
+        }),
+        ClipCue => T::ClipCue(ClipCueTarget {
+            commons,
+            clip: convert_clip_descriptor(
+                data.clip_matrix_id.as_ref(),
+                data.slot_row,
+                data.slot_index,
+            ),
+            cue_output: style
+                .required_value_with_default(data.cue_output, defaults::TARGET_CUE_OUTPUT),
+        }),
+        ClipColumnStop => T::ClipColumnStop(ClipColumnStopTarget {
+            commons,
+            track: convert_track_descriptor(
+                data.track_data,
+                data.enable_only_if_track_is_selected,
+                style,
+            ),
+            matrix: data.clip_matrix_id.map(|id| id.get().to_owned()),
+        }),
+        ClipSceneLaunch => T::ClipSceneLaunch(ClipSceneLaunchTarget {
+            commons,
+            row: data.slot_row as _,
+            matrix: data.clip_matrix_id.map(|id| id.get().to_owned()),
+            next_bar: style
+                .required_value_with_default(data.next_bar, defaults::TARGET_CLIP_NEXT_BAR),
+            buffered: style
+                .required_value_with_default(data.buffered, defaults::TARGET_CLIP_BUFFERED),
         }),
         SendMidi => T::SendMidi(SendMidiTarget {
             commons,
@@ -271,6 +370,49 @@ fn convert_real_target(
                 };
                 style.required_value(dest)
             },
+            chord: data.midi_chord.map(convert_chord_shape),
+            arpeggiator: data.midi_arpeggiator.map(|a| convert_arpeggiator(a, style)),
+        }),
+        SendMidiMacro => T::SendMidiMacro(SendMidiMacroTarget {
+            commons,
+            macro_name: style.required_value(data.midi_macro_name),
+        }),
+        SendProgramChange => T::SendProgramChange(SendProgramChangeTarget {
+            commons,
+            destination: {
+                use schema::MidiDeviceDestination as T;
+                let v = match data.midi_output_device_id {
+                    None => T::FeedbackOutput,
+                    Some(id) => T::Device { id },
+                };
+                style.required_value(v)
+            },
+            channel: style.required_value(data.midi_channel),
+            patches: {
+                let patches: Vec<_> = data
+                    .program_change_patches
+                    .into_iter()
+                    .map(|p| schema::ProgramChangePatch {
+                        name: p.name,
+                        bank_msb: p.bank_msb,
+                        bank_lsb: p.bank_lsb,
+                        program: p.program,
+                    })
+                    .collect();
+                style.required_value(patches)
+            },
+        }),
+        SendMidiClock => T::SendMidiClock(SendMidiClockTarget {
+            commons,
+            destination: {
+                use schema::MidiDeviceDestination as T;
+                let v = match data.midi_output_device_id {
+                    None => T::FeedbackOutput,
+                    Some(id) => T::Device { id },
+                };
+                style.required_value(v)
+            },
+            mtc_frame_rate: data.midi_clock_mtc_frame_rate.map(convert_mtc_frame_rate),
         }),
         SelectedTrack => T::CycleThroughTracks(CycleThroughTracksTarget {
             commons,
@@ -316,6 +458,20 @@ fn convert_real_target(
         }),
         Playrate => T::PlayRate(PlayRateTarget { commons }),
         Tempo => T::Tempo(TempoTarget { commons }),
+        SetExtState => T::SetExtState(SetExtStateTarget {
+            commons,
+            section: style.required_value(data.ext_state_section),
+            key: style.required_value(data.ext_state_key),
+            format: style.required_value(convert_ext_state_value_format(data.ext_state_format)),
+            feedback_resolution: convert_feedback_resolution(
+                data.seek_options.feedback_resolution,
+                style,
+            ),
+        }),
+        RunScript => T::RunScript(RunScriptTarget {
+            commons,
+            script: style.required_value(data.script_path),
+        }),
         TrackArm => T::TrackArmState(TrackArmStateTarget {
             commons,
             track: convert_track_descriptor(
@@ -393,6 +549,15 @@ fn convert_real_target(
                 style,
             ),
         }),
+        TrackDualPan => T::TrackDualPan(TrackDualPanTarget {
+            commons,
+            track: convert_track_descriptor(
+                data.track_data,
+                data.enable_only_if_track_is_selected,
+                style,
+            ),
+            component: style.required_value(convert_dual_pan_component(data.dual_pan_component)),
+        }),
         TrackWidth => T::TrackWidth(TrackWidthTarget {
             commons,
             track: convert_track_descriptor(
@@ -417,6 +582,61 @@ fn convert_real_target(
                 style,
             ),
         }),
+        TrackInsert => T::InsertTrack(InsertTrackTarget {
+            commons,
+            track: convert_track_descriptor(
+                data.track_data,
+                data.enable_only_if_track_is_selected,
+                style,
+            ),
+        }),
+        TrackDuplicate => T::DuplicateTrack(DuplicateTrackTarget {
+            commons,
+            track: convert_track_descriptor(
+                data.track_data,
+                data.enable_only_if_track_is_selected,
+                style,
+            ),
+        }),
+        TrackDelete => T::DeleteTrack(DeleteTrackTarget {
+            commons,
+            track: convert_track_descriptor(
+                data.track_data,
+                data.enable_only_if_track_is_selected,
+                style,
+            ),
+            require_confirmation: style.required_value_with_default(
+                data.require_confirmation,
+                defaults::TARGET_DELETE_TRACK_REQUIRE_CONFIRMATION,
+            ),
+        }),
+        TrackCue => T::TrackCue(TrackCueTarget {
+            commons,
+            track: convert_track_descriptor(
+                data.track_data,
+                data.enable_only_if_track_is_selected,
+                style,
+            ),
+            cue_output: style
+                .required_value_with_default(data.cue_output, defaults::TARGET_CUE_OUTPUT),
+        }),
+        TrackFolderSpill => T::SpillFolder(SpillFolderTarget {
+            commons,
+            track: convert_track_descriptor(
+                data.track_data,
+                data.enable_only_if_track_is_selected,
+                style,
+            ),
+        }),
+        TrackRecordInput => T::TrackRecordInput(TrackRecordInputTarget {
+            commons,
+            track: convert_track_descriptor(
+                data.track_data,
+                data.enable_only_if_track_is_selected,
+                style,
+            ),
+            input_kind: style.required_value(convert_record_input_kind(data.record_input_kind)),
+        }),
         TrackSolo => T::TrackSoloState(TrackSoloStateTarget {
             commons,
             track: convert_track_descriptor(
@@ -432,6 +652,8 @@ fn convert_real_target(
                     InPlace => T::InPlace,
                     IgnoreRouting => T::IgnoreRouting,
                     ReaperPreference => T::ReaperPreference,
+                    ExclusiveRestoring => T::ExclusiveRestoring,
+                    Defeat => T::Defeat,
                 });
                 style.optional_value(v)
             },
@@ -504,10 +726,23 @@ fn convert_real_target(
                 }
             },
         }),
+        EnableGroup => T::EnableGroup(EnableGroupTarget {
+            commons,
+            group: style.required_value(data.group_id.into()),
+            mode: style.required_value(convert_group_enable_mode(data.group_enable_mode)),
+        }),
         LoadMappingSnapshot => T::LoadMappingSnapshots(LoadMappingSnapshotsTarget {
             commons,
             tags: convert_tags(&data.tags, style),
             active_mappings_only: Some(data.active_mappings_only),
+            snapshot: style.required_value(data.mapping_snapshot_id),
+            fade_duration: style.required_value(data.mapping_snapshot_fade_millis as u32),
+        }),
+        SaveMappingSnapshot => T::SaveMappingSnapshot(SaveMappingSnapshotTarget {
+            commons,
+            tags: convert_tags(&data.tags, style),
+            active_mappings_only: Some(data.active_mappings_only),
+            snapshot: style.required_value(data.mapping_snapshot_id),
         }),
         NavigateWithinGroup => T::CycleThroughGroupMappings(CycleThroughGroupMappingsTarget {
             commons,
@@ -521,10 +756,47 @@ fn convert_real_target(
             },
             group: style.required_value(data.group_id.into()),
         }),
+        SelectLayer => T::SelectLayer(SelectLayerTarget {
+            commons,
+            layer: style.required_value(data.layer.get()),
+        }),
+        SetBankOffset => T::SetBankOffset(SetBankOffsetTarget {
+            commons,
+            offset: style.required_value(data.bank_offset),
+        }),
+        AdjustMappingMode => T::AdjustMappingMode(AdjustMappingModeTarget {
+            commons,
+            mapping: style.required_value(data.mapping_key),
+            setting: style.required_value(convert_mapping_mode_setting(data.mapping_mode_setting)),
+        }),
+        Multi => T::Multi(MultiTarget {
+            commons,
+            targets: convert_multiple(data.multi_target_children, |c| {
+                convert_multi_target_child(c, style)
+            })?,
+        }),
+        Panic => T::Panic(PanicTarget { commons }),
+        InvokeInstance => T::InvokeInstance(InvokeInstanceTarget {
+            commons,
+            tags: convert_tags(&data.tags, style),
+            session: Some(data.instance_session_id).filter(|id| !id.is_empty()),
+            message: style.required_value(data.instance_message_name),
+        }),
     };
     Ok(target)
 }
 
+fn convert_multi_target_child(
+    data: MultiTargetChildData,
+    style: ConversionStyle,
+) -> ConversionResult<MultiTargetChild> {
+    let child = MultiTargetChild {
+        target: convert_target(data.target, style)?,
+        scale: style.required_value_with_default(data.scale.get(), 1.0),
+    };
+    Ok(child)
+}
+
 fn convert_commons(
     unit: TargetUnit,
     style: ConversionStyle,
@@ -570,6 +842,104 @@ fn convert_transport_action(transport_action: TransportAction) -> schema::Transp
     }
 }
 
+fn convert_punch_action(punch_action: PunchAction) -> schema::PunchAction {
+    use schema::PunchAction as T;
+    use PunchAction::*;
+    match punch_action {
+        SetPunchIn => T::SetPunchIn,
+        SetPunchOut => T::SetPunchOut,
+        AutoPunch => T::AutoPunch,
+        RecordFromCursor => T::RecordFromCursor,
+    }
+}
+
+fn convert_undo_redo_action(action: UndoRedoAction) -> schema::UndoRedoAction {
+    use schema::UndoRedoAction as T;
+    use UndoRedoAction::*;
+    match action {
+        Undo => T::Undo,
+        Redo => T::Redo,
+    }
+}
+
+fn convert_time_selection_action(action: TimeSelectionAction) -> schema::TimeSelectionAction {
+    use schema::TimeSelectionAction as T;
+    use TimeSelectionAction::*;
+    match action {
+        SetStart => T::SetStart,
+        SetEnd => T::SetEnd,
+        Clear => T::Clear,
+        Repeat => T::Repeat,
+    }
+}
+
+fn convert_time_range_kind(kind: TimeRangeKind) -> schema::TimeRangeKind {
+    use schema::TimeRangeKind as T;
+    use TimeRangeKind::*;
+    match kind {
+        TimeSelection => T::TimeSelection,
+        LoopPoints => T::LoopPoints,
+    }
+}
+
+fn convert_bookmark_navigation_mode(
+    mode: BookmarkNavigationMode,
+) -> schema::BookmarkNavigationMode {
+    use schema::BookmarkNavigationMode as T;
+    use BookmarkNavigationMode::*;
+    match mode {
+        Relative => T::Relative,
+        Next => T::Next,
+        Previous => T::Previous,
+    }
+}
+
+fn convert_metronome_parameter(parameter: MetronomeParameter) -> schema::MetronomeParameter {
+    use schema::MetronomeParameter as T;
+    use MetronomeParameter::*;
+    match parameter {
+        Enable => T::Enable,
+        Volume => T::Volume,
+        CountIn => T::CountIn,
+        CountInLength => T::CountInLength,
+    }
+}
+
+fn convert_group_enable_mode(mode: GroupEnableMode) -> schema::GroupEnableMode {
+    use schema::GroupEnableMode as T;
+    use GroupEnableMode::*;
+    match mode {
+        Momentary => T::Momentary,
+        Toggle => T::Toggle,
+    }
+}
+
+fn convert_mapping_mode_setting(setting: MappingModeSetting) -> schema::MappingModeSetting {
+    use schema::MappingModeSetting as T;
+    use MappingModeSetting::*;
+    match setting {
+        SourceMin => T::SourceMin,
+        SourceMax => T::SourceMax,
+        TargetMin => T::TargetMin,
+        TargetMax => T::TargetMax,
+        StepSizeMin => T::StepSizeMin,
+        StepSizeMax => T::StepSizeMax,
+        JumpMin => T::JumpMin,
+        JumpMax => T::JumpMax,
+        DeadZone => T::DeadZone,
+    }
+}
+
+fn convert_mtc_frame_rate(rate: MtcFrameRate) -> schema::MtcFrameRate {
+    use schema::MtcFrameRate as T;
+    use MtcFrameRate::*;
+    match rate {
+        Fps24 => T::Fps24,
+        Fps25 => T::Fps25,
+        Fps30 => T::Fps30,
+    }
+}
+
 fn convert_any_on_parameter(parameter: AnyOnParameter) -> schema::AnyOnParameter {
     use schema::AnyOnParameter as T;
     use AnyOnParameter::*;
@@ -594,6 +964,16 @@ fn convert_automation_mode(mode: RealearnAutomationMode) -> schema::AutomationMo
     }
 }
 
+fn convert_input_monitoring_mode(mode: RealearnInputMonitoringMode) -> schema::InputMonitoringMode {
+    use schema::InputMonitoringMode as T;
+    use RealearnInputMonitoringMode::*;
+    match mode {
+        Normal => T::Normal,
+        NotWhenPlaying => T::NotWhenPlaying,
+        AllModes => T::AllModes,
+    }
+}
+
 fn convert_track_exclusivity(exclusivity: TrackExclusivity) -> Option<schema::TrackExclusivity> {
     use schema::TrackExclusivity as T;
     use TrackExclusivity::*;
@@ -809,9 +1189,19 @@ fn convert_fx_descriptor(
     style.required_value(v)
 }
 
-fn convert_clip_descriptor(slot_index: usize) -> schema::ClipDescriptor {
+fn convert_clip_descriptor(
+    matrix_id: Option<&ClipMatrixId>,
+    slot_row: usize,
+    slot_column: usize,
+) -> schema::ClipDescriptor {
     ClipDescriptor::Slot {
-        index: slot_index as _,
+        index: slot_column as _,
+        row: if slot_row == 0 {
+            None
+        } else {
+            Some(slot_row as _)
+        },
+        matrix: matrix_id.map(|id| id.get().to_owned()),
     }
 }
 
@@ -827,3 +1217,80 @@ fn convert_feedback_resolution(
     };
     style.required_value(v)
 }
+
+fn convert_record_input_kind(k: RecordInputKind) -> schema::RecordInputKind {
+    use schema::RecordInputKind as T;
+    use RecordInputKind::*;
+    match k {
+        Audio => T::Audio,
+        Midi => T::Midi,
+    }
+}
+
+fn convert_dual_pan_component(c: DualPanComponent) -> schema::DualPanComponent {
+    use schema::DualPanComponent as T;
+    use DualPanComponent::*;
+    match c {
+        Left => T::Left,
+        Right => T::Right,
+    }
+}
+
+fn convert_ext_state_value_format(f: ExtStateValueFormat) -> schema::ExtStateValueFormat {
+    use schema::ExtStateValueFormat as T;
+    use ExtStateValueFormat::*;
+    match f {
+        Normalized => T::Normalized,
+        Percent => T::Percent,
+    }
+}
+
+fn convert_stop_quantization(q: SlotStopQuantization) -> schema::ClipStopQuantization {
+    use schema::ClipStopQuantization as T;
+    use SlotStopQuantization::*;
+    match q {
+        Immediately => T::Immediately,
+        EndOfBar => T::EndOfBar,
+        EndOfClip => T::EndOfClip,
+    }
+}
+
+fn convert_chord_shape(shape: ChordShape) -> schema::ChordShape {
+    use schema::ChordShape as T;
+    use ChordShape::*;
+    match shape {
+        Major => T::Major,
+        Minor => T::Minor,
+        Diminished => T::Diminished,
+        Augmented => T::Augmented,
+        Sus2 => T::Sus2,
+        Sus4 => T::Sus4,
+        Major7 => T::Major7,
+        Minor7 => T::Minor7,
+        Dominant7 => T::Dominant7,
+        Power => T::Power,
+        Octave => T::Octave,
+    }
+}
+
+fn convert_arpeggiator(
+    settings: ArpeggiatorSettings,
+    style: ConversionStyle,
+) -> schema::Arpeggiator {
+    schema::Arpeggiator {
+        direction: {
+            use schema::ArpeggiatorDirection as T;
+            use ArpeggiatorDirection::*;
+            let v = match settings.direction {
+                Up => T::Up,
+                Down => T::Down,
+                UpDown => T::UpDown,
+            };
+            style.required_value(v)
+        },
+        steps_per_beat: style.required_value_with_default(
+            settings.steps_per_beat,
+            defaults::TARGET_SEND_MIDI_ARPEGGIATOR_STEPS_PER_BEAT,
+        ),
+    }
+}
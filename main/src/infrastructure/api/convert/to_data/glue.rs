@@ -1,5 +1,5 @@
 use crate::infrastructure::api::convert::defaults;
-use crate::infrastructure::api::convert::ConversionResult;
+use crate::infrastructure::api::convert::{convert_multiple, ConversionResult};
 use crate::infrastructure::data::ModeModelData;
 use helgoboss_learn::{SoftSymmetricUnitValue, UnitValue, BASE_EPSILON};
 use realearn_api::schema::*;
@@ -66,6 +66,45 @@ pub fn convert_glue(g: Glue) -> ConversionResult<ModeModelData> {
             OnDoublePress(_) => (0, 0),
         }
     };
+    let (velocity_threshold, velocity_below_threshold_value, velocity_above_threshold_value) =
+        if let Some(t) = g.velocity_threshold {
+            (
+                Some(t.threshold.try_into()?),
+                convert_optional_unit_value(t.below_value)?,
+                convert_optional_unit_value(t.above_value)?,
+            )
+        } else {
+            (None, None, None)
+        };
+    let (hysteresis_lower_threshold, hysteresis_upper_threshold) = if let Some(h) = g.hysteresis {
+        let interval = convert_unit_value_interval(Interval(h.lower_threshold, h.upper_threshold))?;
+        (Some(interval.min_val()), Some(interval.max_val()))
+    } else {
+        (None, None)
+    };
+    let encoder_acceleration_curve = g
+        .encoder_acceleration
+        .as_ref()
+        .and_then(|a| a.curve)
+        .unwrap_or(crate::domain::DEFAULT_ENCODER_ACCELERATION_CURVE);
+    if encoder_acceleration_curve <= 0.0 {
+        return Err("Encoder acceleration `curve` must be greater than zero".into());
+    }
+    let response_curve = if let Some(c) = g.response_curve {
+        let points = convert_multiple(c.points, |p| {
+            let point = crate::domain::ResponseCurvePoint {
+                x: p.x.try_into()?,
+                y: p.y.try_into()?,
+            };
+            Ok(point)
+        })?;
+        crate::domain::ResponseCurve {
+            points,
+            interpolation: convert_curve_interpolation(c.interpolation.unwrap_or_default()),
+        }
+    } else {
+        Default::default()
+    };
     let data = ModeModelData {
         r#type: {
             use helgoboss_learn::AbsoluteMode as T;
@@ -179,6 +218,9 @@ pub fn convert_glue(g: Glue) -> ConversionResult<ModeModelData> {
         } else {
             Default::default()
         },
+        target_value_sequence_stepping: convert_value_sequence_stepping(
+            g.target_value_sequence_stepping.unwrap_or_default(),
+        ),
         feedback_type: {
             use helgoboss_learn::FeedbackType as T;
             use FeedbackKind::*;
@@ -187,10 +229,85 @@ pub fn convert_glue(g: Glue) -> ConversionResult<ModeModelData> {
                 Text => T::Textual,
             }
         },
+        velocity_threshold,
+        velocity_below_threshold_value,
+        velocity_above_threshold_value,
+        quantization_scale: g
+            .scale_quantization
+            .as_ref()
+            .map(|q| convert_musical_scale(q.scale)),
+        quantization_root: g
+            .scale_quantization
+            .and_then(|q| q.root)
+            .unwrap_or_default(),
+        min_target_hit_interval: g.min_target_hit_interval.unwrap_or_default() as _,
+        dead_zone: g.dead_zone.unwrap_or_default().try_into()?,
+        hysteresis_lower_threshold,
+        hysteresis_upper_threshold,
+        ramp_duration: g.ramp.as_ref().map(|r| r.duration).unwrap_or_default() as _,
+        ramp_curve: convert_ramp_curve(g.ramp.as_ref().and_then(|r| r.curve).unwrap_or_default()),
+        ramp_returns_on_release: g.ramp.and_then(|r| r.return_on_release).unwrap_or_default(),
+        feedback_deadband: g.feedback_deadband.unwrap_or_default().try_into()?,
+        takeover_catch_range: g.takeover_catch_range.unwrap_or_default().try_into()?,
+        encoder_acceleration_profile: convert_encoder_acceleration_profile(
+            g.encoder_acceleration
+                .as_ref()
+                .map(|a| a.profile)
+                .unwrap_or_default(),
+        ),
+        encoder_acceleration_curve,
+        response_curve,
     };
     Ok(data)
 }
 
+fn convert_ramp_curve(curve: RampCurve) -> crate::domain::RampCurve {
+    use crate::domain::RampCurve as T;
+    use RampCurve::*;
+    match curve {
+        Linear => T::Linear,
+        Exponential => T::Exponential,
+        Logarithmic => T::Logarithmic,
+    }
+}
+
+fn convert_encoder_acceleration_profile(
+    profile: EncoderAccelerationProfile,
+) -> crate::domain::EncoderAccelerationProfile {
+    use crate::domain::EncoderAccelerationProfile as T;
+    use EncoderAccelerationProfile::*;
+    match profile {
+        Off => T::Off,
+        Linear => T::Linear,
+        Exponential => T::Exponential,
+    }
+}
+
+fn convert_curve_interpolation(
+    interpolation: CurveInterpolation,
+) -> crate::domain::CurveInterpolation {
+    use crate::domain::CurveInterpolation as T;
+    use CurveInterpolation::*;
+    match interpolation {
+        Linear => T::Linear,
+        Smooth => T::Smooth,
+        Step => T::Step,
+    }
+}
+
+fn convert_value_sequence_stepping(
+    stepping: ValueSequenceStepping,
+) -> crate::domain::ValueSequenceStepping {
+    use crate::domain::ValueSequenceStepping as T;
+    use ValueSequenceStepping::*;
+    match stepping {
+        Off => T::Off,
+        Stop => T::Stop,
+        Wrap => T::Wrap,
+        PingPong => T::PingPong,
+    }
+}
+
 fn convert_step_factor_interval(
     i: Interval<i32>,
 ) -> ConversionResult<helgoboss_learn::Interval<SoftSymmetricUnitValue>> {
@@ -220,6 +337,30 @@ fn convert_unit_value_interval(
     Ok(result)
 }
 
+fn convert_optional_unit_value(v: Option<f64>) -> ConversionResult<Option<UnitValue>> {
+    let result = if let Some(v) = v {
+        Some(v.try_into()?)
+    } else {
+        None
+    };
+    Ok(result)
+}
+
+fn convert_musical_scale(scale: MusicalScale) -> crate::domain::MusicalScale {
+    use crate::domain::MusicalScale as T;
+    use MusicalScale::*;
+    match scale {
+        Major => T::Major,
+        NaturalMinor => T::NaturalMinor,
+        HarmonicMinor => T::HarmonicMinor,
+        Dorian => T::Dorian,
+        Mixolydian => T::Mixolydian,
+        MajorPentatonic => T::MajorPentatonic,
+        MinorPentatonic => T::MinorPentatonic,
+        Blues => T::Blues,
+    }
+}
+
 fn convert_virtual_color(color: VirtualColor) -> helgoboss_learn::VirtualColor {
     use helgoboss_learn::VirtualColor as T;
     use VirtualColor::*;
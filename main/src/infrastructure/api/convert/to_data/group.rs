@@ -26,6 +26,7 @@ pub fn convert_group(
                     .unwrap_or(defaults::GROUP_FEEDBACK_ENABLED),
             }
         },
+        midi_output_device_id: g.output_device_id,
         activation_condition_data: if let Some(cond) = g.activation_condition {
             convert_activation(cond, &param_index_by_key)?
         } else {
@@ -3,7 +3,7 @@ use crate::application;
 use crate::application::{
     LifecycleMidiMessageModel, LifecycleModel, MappingExtensionModel, RawByteArrayMidiMessage,
 };
-use crate::domain::Tag;
+use crate::domain::{LayerId, Tag};
 use crate::infrastructure::api::convert::to_data::glue::convert_glue;
 use crate::infrastructure::api::convert::to_data::target::convert_target;
 use crate::infrastructure::api::convert::to_data::{
@@ -74,6 +74,8 @@ pub fn convert_mapping(
         visible_in_projection: m
             .visible_in_projection
             .unwrap_or(defaults::MAPPING_VISIBLE_IN_PROJECTION),
+        max_control_rate: m.max_control_rate,
+        layer: m.layer.map(LayerId::new),
     };
     Ok(v)
 }
@@ -105,6 +107,8 @@ fn convert_advanced(
     let extension_model = MappingExtensionModel {
         on_activate: convert_lifecycle_hook(on_activate)?,
         on_deactivate: convert_lifecycle_hook(on_deactivate)?,
+        // Not yet part of the API. Passed through as unprocessed property.
+        feedback: Default::default(),
     };
     let value = serde_yaml::to_value(&extension_model)?;
     let mut mapping = into_yaml_mapping(value);
@@ -141,6 +145,7 @@ fn convert_send_midi_feedback_action(
         SendMidiFeedbackAction::Raw { message } => {
             LifecycleMidiMessageModel::Raw(convert_raw_midi_message(message)?)
         }
+        SendMidiFeedbackAction::Macro { name } => LifecycleMidiMessageModel::Macro(name),
     };
     Ok(v)
 }
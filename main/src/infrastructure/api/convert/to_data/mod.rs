@@ -111,6 +111,27 @@ fn convert_activation(
             eel_condition: c.condition,
             ..Default::default()
         },
+        Expression(c) => ActivationConditionData {
+            activation_type: ActivationType::Expression,
+            expression_condition: c.condition,
+            ..Default::default()
+        },
+        ReaperState(c) => ActivationConditionData {
+            activation_type: ActivationType::ReaperState,
+            reaper_state_condition: {
+                use crate::domain::ReaperStateCondition as T;
+                use realearn_api::schema::ReaperState::*;
+                match c.state {
+                    Playing => T::Playing,
+                    Recording => T::Recording,
+                    Stopped => T::Stopped,
+                    AnyTrackArmed => T::AnyTrackArmed,
+                    AnyTrackSelected => T::AnyTrackSelected,
+                    AnyFxFocused => T::AnyFxFocused,
+                }
+            },
+            ..Default::default()
+        },
     };
     Ok(data)
 }
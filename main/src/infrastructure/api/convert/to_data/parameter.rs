@@ -6,6 +6,7 @@ pub fn convert_parameter(p: Parameter) -> ConversionResult<ParameterSetting> {
     let data = ParameterSetting {
         key: p.id,
         name: p.name.unwrap_or_default(),
+        link: None,
     };
     Ok(data)
 }
@@ -1,4 +1,5 @@
 use crate::application::{MidiSourceType, ReaperSourceType, SourceCategory};
+use crate::domain::OscArgFilter;
 use crate::infrastructure::api::convert::to_data::{
     convert_control_element_id, convert_control_element_type, convert_osc_arg_type,
 };
@@ -65,6 +66,25 @@ pub fn convert_source(s: Source) -> ConversionResult<SourceModelData> {
             SiniConE24Display(s) => s.item_index,
             _ => None,
         },
+        // Input device overrides are not exposed in the API yet.
+        midi_input_device_id: None,
+        midi_output_device_id: match &s {
+            MidiNoteVelocity(s) => s.output_device_id,
+            MidiNoteKeyNumber(s) => s.output_device_id,
+            MidiPolyphonicKeyPressureAmount(s) => s.output_device_id,
+            MidiControlChangeValue(s) => s.output_device_id,
+            MidiProgramChangeNumber(s) => s.output_device_id,
+            MidiChannelPressureAmount(s) => s.output_device_id,
+            MidiPitchBendChangeValue(s) => s.output_device_id,
+            MidiParameterNumberValue(s) => s.output_device_id,
+            MidiRaw(s) => s.output_device_id,
+            MidiScript(s) => s.output_device_id,
+            MackieLcd(s) => s.output_device_id,
+            MackieSevenSegmentDisplay(s) => s.output_device_id,
+            SiniConE24Display(s) => s.output_device_id,
+            LaunchpadProScrollingTextDisplay(s) => s.output_device_id,
+            _ => None,
+        },
         osc_address_pattern: match &s {
             Osc(s) => s.address.as_ref().cloned().unwrap_or_default(),
             _ => Default::default(),
@@ -84,6 +104,28 @@ pub fn convert_source(s: Source) -> ConversionResult<SourceModelData> {
             Osc(s) => s.relative.unwrap_or(defaults::SOURCE_OSC_IS_RELATIVE),
             _ => false,
         },
+        osc_delta_mode: match &s {
+            Osc(s) => convert_osc_delta_mode(s.delta_mode.unwrap_or_default()),
+            _ => Default::default(),
+        },
+        osc_arg_filters: match &s {
+            Osc(s) => s
+                .argument_filters
+                .iter()
+                .flatten()
+                .map(|f| OscArgFilter {
+                    index: f.index,
+                    value: f.value.clone(),
+                })
+                .collect(),
+            _ => vec![],
+        },
+        osc_feedback_is_relative: match &s {
+            Osc(s) => s
+                .relative_feedback
+                .unwrap_or(defaults::SOURCE_OSC_FEEDBACK_IS_RELATIVE),
+            _ => false,
+        },
         control_element_type: match &s {
             Virtual(s) => convert_control_element_type(s.character.unwrap_or_default()),
             _ => Default::default(),
@@ -95,8 +137,31 @@ pub fn convert_source(s: Source) -> ConversionResult<SourceModelData> {
         reaper_source_type: match &s {
             MidiDeviceChanges(_) => ReaperSourceType::MidiDeviceChanges,
             RealearnInstanceStart(_) => ReaperSourceType::RealearnInstanceStart,
+            GameController(_) => ReaperSourceType::GameController,
+            InstanceMessage(_) => ReaperSourceType::InstanceMessage,
+            MidiMachineControl(_) => ReaperSourceType::Mmc,
+            _ => Default::default(),
+        },
+        game_controller_index: match &s {
+            GameController(s) => s.controller_index,
+            _ => None,
+        },
+        game_controller_element: match &s {
+            GameController(s) => convert_game_controller_element(s.element.unwrap_or_default()),
+            _ => Default::default(),
+        },
+        instance_message_name: match &s {
+            InstanceMessage(s) => s.message.clone().unwrap_or_default(),
+            _ => Default::default(),
+        },
+        mmc_command: match &s {
+            MidiMachineControl(s) => convert_mmc_command(s.command.unwrap_or_default()),
             _ => Default::default(),
         },
+        mmc_device_id: match &s {
+            MidiMachineControl(s) => s.device_id,
+            _ => None,
+        },
     };
     Ok(data)
 }
@@ -105,7 +170,11 @@ fn convert_category(s: &Source) -> SourceCategory {
     use Source::*;
     match s {
         NoneSource => SourceCategory::Never,
-        MidiDeviceChanges(_) | RealearnInstanceStart(_) => SourceCategory::Reaper,
+        MidiDeviceChanges(_)
+        | RealearnInstanceStart(_)
+        | GameController(_)
+        | InstanceMessage(_)
+        | MidiMachineControl(_) => SourceCategory::Reaper,
         MidiNoteVelocity(_)
         | MidiNoteKeyNumber(_)
         | MidiPolyphonicKeyPressureAmount(_)
@@ -212,6 +281,66 @@ fn convert_midi_clock_transport_message(
     }
 }
 
+fn convert_game_controller_element(
+    s: GameControllerElement,
+) -> crate::domain::GameControllerElement {
+    use crate::domain::GameControllerElement as T;
+    use GameControllerElement::*;
+    match s {
+        LeftStickX => T::LeftStickX,
+        LeftStickY => T::LeftStickY,
+        LeftZ => T::LeftZ,
+        RightStickX => T::RightStickX,
+        RightStickY => T::RightStickY,
+        RightZ => T::RightZ,
+        DPadX => T::DPadX,
+        DPadY => T::DPadY,
+        South => T::South,
+        East => T::East,
+        North => T::North,
+        West => T::West,
+        LeftTrigger => T::LeftTrigger,
+        LeftTrigger2 => T::LeftTrigger2,
+        RightTrigger => T::RightTrigger,
+        RightTrigger2 => T::RightTrigger2,
+        Select => T::Select,
+        Start => T::Start,
+        Mode => T::Mode,
+        LeftThumb => T::LeftThumb,
+        RightThumb => T::RightThumb,
+        DPadUp => T::DPadUp,
+        DPadDown => T::DPadDown,
+        DPadLeft => T::DPadLeft,
+        DPadRight => T::DPadRight,
+    }
+}
+
+fn convert_mmc_command(s: MidiMachineControlCommand) -> crate::domain::MmcCommand {
+    use crate::domain::MmcCommand as T;
+    use MidiMachineControlCommand::*;
+    match s {
+        Stop => T::Stop,
+        Play => T::Play,
+        DeferredPlay => T::DeferredPlay,
+        FastForward => T::FastForward,
+        Rewind => T::Rewind,
+        RecordStrobe => T::RecordStrobe,
+        RecordExit => T::RecordExit,
+        Pause => T::Pause,
+        Locate => T::Locate,
+    }
+}
+
+fn convert_osc_delta_mode(s: OscDeltaMode) -> crate::domain::OscDeltaMode {
+    use crate::domain::OscDeltaMode as T;
+    use OscDeltaMode::*;
+    match s {
+        Sign => T::Sign,
+        Steps => T::Steps,
+        Centered => T::Centered,
+    }
+}
+
 fn convert_mackie_seven_segment_display_scope(
     s: MackieSevenSegmentDisplayScope,
 ) -> helgoboss_learn::MackieSevenSegmentDisplayScope {
@@ -1,24 +1,25 @@
 use crate::application::{
     AutomationModeOverrideType, BookmarkAnchorType, FxParameterPropValues, FxPropValues,
-    RealearnAutomationMode, RealearnTrackArea, TargetCategory, TrackPropValues,
-    TrackRoutePropValues, TrackRouteSelectorType, VirtualFxParameterType, VirtualFxType,
-    VirtualTrackType,
+    RealearnAutomationMode, RealearnInputMonitoringMode, RealearnTrackArea, TargetCategory,
+    TrackPropValues, TrackRoutePropValues, TrackRouteSelectorType, VirtualFxParameterType,
+    VirtualFxType, VirtualTrackType,
 };
 use crate::domain::{
-    ActionInvocationType, Exclusivity, FxDisplayType, ReaperTargetType, SeekOptions,
+    ActionInvocationType, ClipMatrixId, Exclusivity, FxDisplayType, ReaperTargetType, SeekOptions,
     SendMidiDestination, TrackRouteType,
 };
 use crate::infrastructure::api::convert::to_data::{
     convert_control_element_id, convert_control_element_type, convert_osc_arg_type, convert_tags,
 };
-use crate::infrastructure::api::convert::{defaults, ConversionResult};
+use crate::infrastructure::api::convert::{convert_multiple, defaults, ConversionResult};
 use crate::infrastructure::data::{
     serialize_fx, serialize_fx_parameter, serialize_track, serialize_track_route, BookmarkData,
-    FxData, FxParameterData, TargetModelData, TrackData, TrackRouteData,
+    FxData, FxParameterData, MultiTargetChildData, TargetModelData, TrackData, TrackRouteData,
 };
 use crate::{application, domain};
 use realearn_api::schema::*;
 use reaper_high::Guid;
+use std::convert::TryInto;
 use std::rc::Rc;
 
 pub fn convert_target(t: Target) -> ConversionResult<TargetModelData> {
@@ -86,6 +87,36 @@ pub fn convert_target(t: Target) -> ConversionResult<TargetModelData> {
             transport_action: convert_transport_action(d.action),
             ..init(d.commons)
         },
+        Target::PunchAction(d) => TargetModelData {
+            category: TargetCategory::Reaper,
+            r#type: ReaperTargetType::Punch,
+            punch_action: convert_punch_action(d.action),
+            ..init(d.commons)
+        },
+        Target::UndoRedo(d) => TargetModelData {
+            category: TargetCategory::Reaper,
+            r#type: ReaperTargetType::UndoRedo,
+            undo_redo_action: convert_undo_redo_action(d.action),
+            ..init(d.commons)
+        },
+        Target::TimeSelection(d) => TargetModelData {
+            category: TargetCategory::Reaper,
+            r#type: ReaperTargetType::TimeSelection,
+            time_selection_action: convert_time_selection_action(d.action),
+            time_range_kind: convert_time_range_kind(d.range.unwrap_or_default()),
+            ..init(d.commons)
+        },
+        Target::MonitoringVolume(d) => TargetModelData {
+            category: TargetCategory::Reaper,
+            r#type: ReaperTargetType::MonitoringVolume,
+            ..init(d.commons)
+        },
+        Target::Metronome(d) => TargetModelData {
+            category: TargetCategory::Reaper,
+            r#type: ReaperTargetType::Metronome,
+            metronome_parameter: convert_metronome_parameter(d.parameter),
+            ..init(d.commons)
+        },
         Target::AnyOn(d) => TargetModelData {
             category: TargetCategory::Reaper,
             r#type: ReaperTargetType::AnyOn,
@@ -133,6 +164,26 @@ pub fn convert_target(t: Target) -> ConversionResult<TargetModelData> {
             r#type: ReaperTargetType::Tempo,
             ..init(d.commons)
         },
+        Target::RunScript(d) => TargetModelData {
+            category: TargetCategory::Reaper,
+            r#type: ReaperTargetType::RunScript,
+            script_path: d.script.unwrap_or_default(),
+            ..init(d.commons)
+        },
+        Target::SetExtState(d) => TargetModelData {
+            category: TargetCategory::Reaper,
+            r#type: ReaperTargetType::SetExtState,
+            ext_state_section: d.section.unwrap_or_default(),
+            ext_state_key: d.key.unwrap_or_default(),
+            ext_state_format: convert_ext_state_value_format(d.format.unwrap_or_default()),
+            seek_options: SeekOptions {
+                feedback_resolution: convert_feedback_resolution(
+                    d.feedback_resolution.unwrap_or_default(),
+                ),
+                ..Default::default()
+            },
+            ..init(d.commons)
+        },
         Target::GoToBookmark(d) => TargetModelData {
             category: TargetCategory::Reaper,
             r#type: ReaperTargetType::GoToBookmark,
@@ -167,6 +218,16 @@ pub fn convert_target(t: Target) -> ConversionResult<TargetModelData> {
             },
             ..init(d.commons)
         },
+        Target::NavigateBookmarks(d) => TargetModelData {
+            category: TargetCategory::Reaper,
+            r#type: ReaperTargetType::NavigateBookmarks,
+            bookmark_data: BookmarkData {
+                is_region: d.bookmark_type == BookmarkType::Region,
+                ..Default::default()
+            },
+            bookmark_navigation_mode: convert_bookmark_navigation_mode(d.mode.unwrap_or_default()),
+            ..init(d.commons)
+        },
         Target::TrackArmState(d) => {
             let track_desc = convert_track_desc(d.track.unwrap_or_default())?;
             TargetModelData {
@@ -256,6 +317,18 @@ pub fn convert_target(t: Target) -> ConversionResult<TargetModelData> {
                 ..init(d.commons)
             }
         }
+        Target::TrackInputMonitoring(d) => {
+            let track_desc = convert_track_desc(d.track.unwrap_or_default())?;
+            TargetModelData {
+                category: TargetCategory::Reaper,
+                r#type: ReaperTargetType::TrackInputMonitoring,
+                track_data: track_desc.track_data,
+                enable_only_if_track_is_selected: track_desc.track_must_be_selected,
+                track_exclusivity: convert_track_exclusivity(d.exclusivity),
+                track_input_monitoring_mode: convert_input_monitoring_mode(d.mode),
+                ..init(d.commons)
+            }
+        }
         Target::TrackAutomationTouchState(d) => {
             let track_desc = convert_track_desc(d.track.unwrap_or_default())?;
             TargetModelData {
@@ -316,6 +389,82 @@ pub fn convert_target(t: Target) -> ConversionResult<TargetModelData> {
                 ..init(d.commons)
             }
         }
+        Target::InsertTrack(d) => {
+            let track_desc = convert_track_desc(d.track.unwrap_or_default())?;
+            TargetModelData {
+                category: TargetCategory::Reaper,
+                r#type: ReaperTargetType::TrackInsert,
+                track_data: track_desc.track_data,
+                enable_only_if_track_is_selected: track_desc.track_must_be_selected,
+                ..init(d.commons)
+            }
+        }
+        Target::DuplicateTrack(d) => {
+            let track_desc = convert_track_desc(d.track.unwrap_or_default())?;
+            TargetModelData {
+                category: TargetCategory::Reaper,
+                r#type: ReaperTargetType::TrackDuplicate,
+                track_data: track_desc.track_data,
+                enable_only_if_track_is_selected: track_desc.track_must_be_selected,
+                ..init(d.commons)
+            }
+        }
+        Target::DeleteTrack(d) => {
+            let track_desc = convert_track_desc(d.track.unwrap_or_default())?;
+            TargetModelData {
+                category: TargetCategory::Reaper,
+                r#type: ReaperTargetType::TrackDelete,
+                track_data: track_desc.track_data,
+                enable_only_if_track_is_selected: track_desc.track_must_be_selected,
+                require_confirmation: d
+                    .require_confirmation
+                    .unwrap_or(defaults::TARGET_DELETE_TRACK_REQUIRE_CONFIRMATION),
+                ..init(d.commons)
+            }
+        }
+        Target::TrackCue(d) => {
+            let track_desc = convert_track_desc(d.track.unwrap_or_default())?;
+            TargetModelData {
+                category: TargetCategory::Reaper,
+                r#type: ReaperTargetType::TrackCue,
+                track_data: track_desc.track_data,
+                enable_only_if_track_is_selected: track_desc.track_must_be_selected,
+                cue_output: d.cue_output.unwrap_or(defaults::TARGET_CUE_OUTPUT),
+                ..init(d.commons)
+            }
+        }
+        Target::SpillFolder(d) => {
+            let track_desc = convert_track_desc(d.track.unwrap_or_default())?;
+            TargetModelData {
+                category: TargetCategory::Reaper,
+                r#type: ReaperTargetType::TrackFolderSpill,
+                track_data: track_desc.track_data,
+                enable_only_if_track_is_selected: track_desc.track_must_be_selected,
+                ..init(d.commons)
+            }
+        }
+        Target::TrackDualPan(d) => {
+            let track_desc = convert_track_desc(d.track.unwrap_or_default())?;
+            TargetModelData {
+                category: TargetCategory::Reaper,
+                r#type: ReaperTargetType::TrackDualPan,
+                track_data: track_desc.track_data,
+                enable_only_if_track_is_selected: track_desc.track_must_be_selected,
+                dual_pan_component: convert_dual_pan_component(d.component.unwrap_or_default()),
+                ..init(d.commons)
+            }
+        }
+        Target::TrackRecordInput(d) => {
+            let track_desc = convert_track_desc(d.track.unwrap_or_default())?;
+            TargetModelData {
+                category: TargetCategory::Reaper,
+                r#type: ReaperTargetType::TrackRecordInput,
+                track_data: track_desc.track_data,
+                enable_only_if_track_is_selected: track_desc.track_must_be_selected,
+                record_input_kind: convert_record_input_kind(d.input_kind.unwrap_or_default()),
+                ..init(d.commons)
+            }
+        }
         Target::TrackVisibility(d) => {
             let track_desc = convert_track_desc(d.track.unwrap_or_default())?;
             TargetModelData {
@@ -351,6 +500,8 @@ pub fn convert_target(t: Target) -> ConversionResult<TargetModelData> {
                         InPlace => T::InPlace,
                         IgnoreRouting => T::IgnoreRouting,
                         ReaperPreference => T::ReaperPreference,
+                        ExclusiveRestoring => T::ExclusiveRestoring,
+                        Defeat => T::Defeat,
                     };
                     Some(v)
                 },
@@ -449,6 +600,12 @@ pub fn convert_target(t: Target) -> ConversionResult<TargetModelData> {
                 ..init(d.commons)
             }
         }
+        Target::FxParameterBank(d) => TargetModelData {
+            category: TargetCategory::Reaper,
+            r#type: ReaperTargetType::FxParameterBank,
+            fx_parameter_bank_size: d.bank_size,
+            ..init(d.commons)
+        },
         Target::RouteAutomationMode(d) => {
             let route_desc = convert_route_desc(d.route)?;
             let track_desc = route_desc.track_desc;
@@ -544,10 +701,13 @@ pub fn convert_target(t: Target) -> ConversionResult<TargetModelData> {
                 r#type: ReaperTargetType::ClipTransport,
                 track_data: track_desc.track_data,
                 enable_only_if_track_is_selected: track_desc.track_must_be_selected,
-                slot_index: clip_desc.slot_index,
+                clip_matrix_id: clip_desc.matrix_id,
+                slot_row: clip_desc.slot_row,
+                slot_index: clip_desc.slot_column,
                 transport_action: convert_transport_action(d.action),
                 next_bar: d.next_bar.unwrap_or(defaults::TARGET_CLIP_NEXT_BAR),
                 buffered: d.buffered.unwrap_or(defaults::TARGET_CLIP_BUFFERED),
+                stop_quantization: d.stop_quantization.map(convert_stop_quantization),
                 ..init(d.commons)
             }
         }
@@ -556,7 +716,9 @@ pub fn convert_target(t: Target) -> ConversionResult<TargetModelData> {
             TargetModelData {
                 category: TargetCategory::Reaper,
                 r#type: ReaperTargetType::ClipSeek,
-                slot_index: clip_desc.slot_index,
+                clip_matrix_id: clip_desc.matrix_id,
+                slot_row: clip_desc.slot_row,
+                slot_index: clip_desc.slot_column,
                 seek_options: SeekOptions {
                     feedback_resolution: convert_feedback_resolution(
                         d.feedback_resolution.unwrap_or_default(),
@@ -571,10 +733,44 @@ pub fn convert_target(t: Target) -> ConversionResult<TargetModelData> {
             TargetModelData {
                 category: TargetCategory::Reaper,
                 r#type: ReaperTargetType::ClipVolume,
-                slot_index: clip_desc.slot_index,
+                clip_matrix_id: clip_desc.matrix_id,
+                slot_row: clip_desc.slot_row,
+                slot_index: clip_desc.slot_column,
                 ..init(d.commons)
             }
         }
+        Target::ClipCue(d) => {
+            let clip_desc = convert_clip_desc(d.clip)?;
+            TargetModelData {
+                category: TargetCategory::Reaper,
+                r#type: ReaperTargetType::ClipCue,
+                clip_matrix_id: clip_desc.matrix_id,
+                slot_row: clip_desc.slot_row,
+                slot_index: clip_desc.slot_column,
+                cue_output: d.cue_output.unwrap_or(defaults::TARGET_CUE_OUTPUT),
+                ..init(d.commons)
+            }
+        }
+        Target::ClipColumnStop(d) => {
+            let track_desc = convert_track_desc(d.track.unwrap_or_default())?;
+            TargetModelData {
+                category: TargetCategory::Reaper,
+                r#type: ReaperTargetType::ClipColumnStop,
+                track_data: track_desc.track_data,
+                enable_only_if_track_is_selected: track_desc.track_must_be_selected,
+                clip_matrix_id: d.matrix.and_then(|m| ClipMatrixId::from_str_opt(&m)),
+                ..init(d.commons)
+            }
+        }
+        Target::ClipSceneLaunch(d) => TargetModelData {
+            category: TargetCategory::Reaper,
+            r#type: ReaperTargetType::ClipSceneLaunch,
+            clip_matrix_id: d.matrix.and_then(|m| ClipMatrixId::from_str_opt(&m)),
+            slot_row: d.row as _,
+            next_bar: d.next_bar.unwrap_or(defaults::TARGET_CLIP_NEXT_BAR),
+            buffered: d.buffered.unwrap_or(defaults::TARGET_CLIP_BUFFERED),
+            ..init(d.commons)
+        },
         Target::SendMidi(d) => TargetModelData {
             category: TargetCategory::Reaper,
             r#type: ReaperTargetType::SendMidi,
@@ -583,6 +779,45 @@ pub fn convert_target(t: Target) -> ConversionResult<TargetModelData> {
                 MidiDestination::FxOutput => SendMidiDestination::FxOutput,
                 MidiDestination::FeedbackOutput => SendMidiDestination::FeedbackOutput,
             },
+            midi_chord: d.chord.map(convert_chord_shape),
+            midi_arpeggiator: d.arpeggiator.map(convert_arpeggiator),
+            ..init(d.commons)
+        },
+        Target::SendMidiMacro(d) => TargetModelData {
+            category: TargetCategory::Reaper,
+            r#type: ReaperTargetType::SendMidiMacro,
+            midi_macro_name: d.macro_name.unwrap_or_default(),
+            ..init(d.commons)
+        },
+        Target::SendProgramChange(d) => TargetModelData {
+            category: TargetCategory::Reaper,
+            r#type: ReaperTargetType::SendProgramChange,
+            midi_output_device_id: match d.destination.unwrap_or_default() {
+                MidiDeviceDestination::FeedbackOutput => None,
+                MidiDeviceDestination::Device { id } => Some(id),
+            },
+            midi_channel: d.channel.unwrap_or_default(),
+            program_change_patches: d
+                .patches
+                .unwrap_or_default()
+                .into_iter()
+                .map(|p| domain::ProgramChangePatch {
+                    name: p.name,
+                    bank_msb: p.bank_msb,
+                    bank_lsb: p.bank_lsb,
+                    program: p.program,
+                })
+                .collect(),
+            ..init(d.commons)
+        },
+        Target::SendMidiClock(d) => TargetModelData {
+            category: TargetCategory::Reaper,
+            r#type: ReaperTargetType::SendMidiClock,
+            midi_output_device_id: match d.destination.unwrap_or_default() {
+                MidiDeviceDestination::FeedbackOutput => None,
+                MidiDeviceDestination::Device { id } => Some(id),
+            },
+            midi_clock_mtc_frame_rate: d.mtc_frame_rate.map(convert_mtc_frame_rate),
             ..init(d.commons)
         },
         Target::SendOsc(d) => {
@@ -637,6 +872,13 @@ pub fn convert_target(t: Target) -> ConversionResult<TargetModelData> {
             },
             ..init(d.commons)
         },
+        Target::EnableGroup(d) => TargetModelData {
+            category: TargetCategory::Reaper,
+            r#type: ReaperTargetType::EnableGroup,
+            group_id: d.group.map(|g| g.into()).unwrap_or_default(),
+            group_enable_mode: convert_group_enable_mode(d.mode.unwrap_or_default()),
+            ..init(d.commons)
+        },
         Target::LoadMappingSnapshots(d) => TargetModelData {
             category: TargetCategory::Reaper,
             r#type: ReaperTargetType::LoadMappingSnapshot,
@@ -644,6 +886,18 @@ pub fn convert_target(t: Target) -> ConversionResult<TargetModelData> {
             active_mappings_only: d
                 .active_mappings_only
                 .unwrap_or(defaults::TARGET_LOAD_MAPPING_SNAPSHOT_ACTIVE_MAPPINGS_ONLY),
+            mapping_snapshot_id: d.snapshot.unwrap_or_default(),
+            mapping_snapshot_fade_millis: d.fade_duration.unwrap_or_default() as _,
+            ..init(d.commons)
+        },
+        Target::SaveMappingSnapshot(d) => TargetModelData {
+            category: TargetCategory::Reaper,
+            r#type: ReaperTargetType::SaveMappingSnapshot,
+            tags: convert_tags(d.tags.unwrap_or_default())?,
+            active_mappings_only: d
+                .active_mappings_only
+                .unwrap_or(defaults::TARGET_SAVE_MAPPING_SNAPSHOT_ACTIVE_MAPPINGS_ONLY),
+            mapping_snapshot_id: d.snapshot.unwrap_or_default(),
             ..init(d.commons)
         },
         Target::CycleThroughGroupMappings(d) => TargetModelData {
@@ -660,6 +914,44 @@ pub fn convert_target(t: Target) -> ConversionResult<TargetModelData> {
             group_id: d.group.map(|g| g.into()).unwrap_or_default(),
             ..init(d.commons)
         },
+        Target::SelectLayer(d) => TargetModelData {
+            category: TargetCategory::Reaper,
+            r#type: ReaperTargetType::SelectLayer,
+            layer: domain::LayerId::new(d.layer.unwrap_or_default()),
+            ..init(d.commons)
+        },
+        Target::SetBankOffset(d) => TargetModelData {
+            category: TargetCategory::Reaper,
+            r#type: ReaperTargetType::SetBankOffset,
+            bank_offset: d.offset.unwrap_or_default(),
+            ..init(d.commons)
+        },
+        Target::AdjustMappingMode(d) => TargetModelData {
+            category: TargetCategory::Reaper,
+            r#type: ReaperTargetType::AdjustMappingMode,
+            mapping_key: d.mapping.unwrap_or_default(),
+            mapping_mode_setting: convert_mapping_mode_setting(d.setting.unwrap_or_default()),
+            ..init(d.commons)
+        },
+        Target::Multi(d) => TargetModelData {
+            category: TargetCategory::Reaper,
+            r#type: ReaperTargetType::Multi,
+            multi_target_children: convert_multiple(d.targets, convert_multi_target_child)?,
+            ..init(d.commons)
+        },
+        Target::Panic(d) => TargetModelData {
+            category: TargetCategory::Reaper,
+            r#type: ReaperTargetType::Panic,
+            ..init(d.commons)
+        },
+        Target::InvokeInstance(d) => TargetModelData {
+            category: TargetCategory::Reaper,
+            r#type: ReaperTargetType::InvokeInstance,
+            tags: convert_tags(d.tags.unwrap_or_default())?,
+            instance_session_id: d.session.unwrap_or_default(),
+            instance_message_name: d.message.unwrap_or_default(),
+            ..init(d.commons)
+        },
         Target::Virtual(d) => TargetModelData {
             category: TargetCategory::Virtual,
             control_element_type: convert_control_element_type(d.character.unwrap_or_default()),
@@ -670,6 +962,14 @@ pub fn convert_target(t: Target) -> ConversionResult<TargetModelData> {
     Ok(data)
 }
 
+fn convert_multi_target_child(c: MultiTargetChild) -> ConversionResult<MultiTargetChildData> {
+    let data = MultiTargetChildData {
+        target: convert_target(c.target)?,
+        scale: c.scale.unwrap_or(1.0).try_into()?,
+    };
+    Ok(data)
+}
+
 fn init(commons: TargetCommons) -> TargetModelData {
     TargetModelData {
         unit: {
@@ -697,6 +997,16 @@ fn convert_automation_mode(mode: AutomationMode) -> RealearnAutomationMode {
     }
 }
 
+fn convert_input_monitoring_mode(mode: InputMonitoringMode) -> RealearnInputMonitoringMode {
+    use InputMonitoringMode::*;
+    use RealearnInputMonitoringMode as T;
+    match mode {
+        Normal => T::Normal,
+        NotWhenPlaying => T::NotWhenPlaying,
+        AllModes => T::AllModes,
+    }
+}
+
 #[derive(Default)]
 struct TrackDesc {
     track_data: TrackData,
@@ -717,7 +1027,9 @@ struct RouteDesc {
 
 #[derive(Default)]
 struct ClipDesc {
-    slot_index: usize,
+    matrix_id: Option<ClipMatrixId>,
+    slot_row: usize,
+    slot_column: usize,
 }
 
 #[derive(Default)]
@@ -735,8 +1047,10 @@ struct FxParameterDesc {
 
 fn convert_clip_desc(t: ClipDescriptor) -> ConversionResult<ClipDesc> {
     let desc = match t {
-        ClipDescriptor::Slot { index } => ClipDesc {
-            slot_index: index as _,
+        ClipDescriptor::Slot { index, row, matrix } => ClipDesc {
+            matrix_id: matrix.and_then(|m| ClipMatrixId::from_str_opt(&m)),
+            slot_row: row.unwrap_or(0) as _,
+            slot_column: index as _,
         },
     };
     Ok(desc)
@@ -1064,6 +1378,104 @@ fn convert_transport_action(transport_action: TransportAction) -> domain::Transp
     }
 }
 
+fn convert_punch_action(punch_action: PunchAction) -> domain::PunchAction {
+    use domain::PunchAction as T;
+    use PunchAction::*;
+    match punch_action {
+        SetPunchIn => T::SetPunchIn,
+        SetPunchOut => T::SetPunchOut,
+        AutoPunch => T::AutoPunch,
+        RecordFromCursor => T::RecordFromCursor,
+    }
+}
+
+fn convert_undo_redo_action(action: UndoRedoAction) -> domain::UndoRedoAction {
+    use domain::UndoRedoAction as T;
+    use UndoRedoAction::*;
+    match action {
+        Undo => T::Undo,
+        Redo => T::Redo,
+    }
+}
+
+fn convert_time_selection_action(action: TimeSelectionAction) -> domain::TimeSelectionAction {
+    use domain::TimeSelectionAction as T;
+    use TimeSelectionAction::*;
+    match action {
+        SetStart => T::SetStart,
+        SetEnd => T::SetEnd,
+        Clear => T::Clear,
+        Repeat => T::Repeat,
+    }
+}
+
+fn convert_time_range_kind(kind: TimeRangeKind) -> domain::TimeRangeKind {
+    use domain::TimeRangeKind as T;
+    use TimeRangeKind::*;
+    match kind {
+        TimeSelection => T::TimeSelection,
+        LoopPoints => T::LoopPoints,
+    }
+}
+
+fn convert_bookmark_navigation_mode(
+    mode: BookmarkNavigationMode,
+) -> domain::BookmarkNavigationMode {
+    use domain::BookmarkNavigationMode as T;
+    use BookmarkNavigationMode::*;
+    match mode {
+        Relative => T::Relative,
+        Next => T::Next,
+        Previous => T::Previous,
+    }
+}
+
+fn convert_metronome_parameter(parameter: MetronomeParameter) -> domain::MetronomeParameter {
+    use domain::MetronomeParameter as T;
+    use MetronomeParameter::*;
+    match parameter {
+        Enable => T::Enable,
+        Volume => T::Volume,
+        CountIn => T::CountIn,
+        CountInLength => T::CountInLength,
+    }
+}
+
+fn convert_group_enable_mode(mode: GroupEnableMode) -> domain::GroupEnableMode {
+    use domain::GroupEnableMode as T;
+    use GroupEnableMode::*;
+    match mode {
+        Momentary => T::Momentary,
+        Toggle => T::Toggle,
+    }
+}
+
+fn convert_mapping_mode_setting(setting: MappingModeSetting) -> domain::MappingModeSetting {
+    use domain::MappingModeSetting as T;
+    use MappingModeSetting::*;
+    match setting {
+        SourceMin => T::SourceMin,
+        SourceMax => T::SourceMax,
+        TargetMin => T::TargetMin,
+        TargetMax => T::TargetMax,
+        StepSizeMin => T::StepSizeMin,
+        StepSizeMax => T::StepSizeMax,
+        JumpMin => T::JumpMin,
+        JumpMax => T::JumpMax,
+        DeadZone => T::DeadZone,
+    }
+}
+
+fn convert_mtc_frame_rate(rate: MtcFrameRate) -> domain::MtcFrameRate {
+    use domain::MtcFrameRate as T;
+    use MtcFrameRate::*;
+    match rate {
+        Fps24 => T::Fps24,
+        Fps25 => T::Fps25,
+        Fps30 => T::Fps30,
+    }
+}
+
 fn convert_any_on_parameter(parameter: AnyOnParameter) -> domain::AnyOnParameter {
     use domain::AnyOnParameter as T;
     use AnyOnParameter::*;
@@ -1075,6 +1487,16 @@ fn convert_any_on_parameter(parameter: AnyOnParameter) -> domain::AnyOnParameter
     }
 }
 
+fn convert_stop_quantization(q: ClipStopQuantization) -> domain::SlotStopQuantization {
+    use domain::SlotStopQuantization as T;
+    use ClipStopQuantization::*;
+    match q {
+        Immediately => T::Immediately,
+        EndOfBar => T::EndOfBar,
+        EndOfClip => T::EndOfClip,
+    }
+}
+
 fn convert_feedback_resolution(r: FeedbackResolution) -> domain::FeedbackResolution {
     use domain::FeedbackResolution as T;
     use FeedbackResolution::*;
@@ -1084,6 +1506,33 @@ fn convert_feedback_resolution(r: FeedbackResolution) -> domain::FeedbackResolut
     }
 }
 
+fn convert_record_input_kind(k: RecordInputKind) -> domain::RecordInputKind {
+    use domain::RecordInputKind as T;
+    use RecordInputKind::*;
+    match k {
+        Audio => T::Audio,
+        Midi => T::Midi,
+    }
+}
+
+fn convert_dual_pan_component(c: DualPanComponent) -> domain::DualPanComponent {
+    use domain::DualPanComponent as T;
+    use DualPanComponent::*;
+    match c {
+        Left => T::Left,
+        Right => T::Right,
+    }
+}
+
+fn convert_ext_state_value_format(f: ExtStateValueFormat) -> domain::ExtStateValueFormat {
+    use domain::ExtStateValueFormat as T;
+    use ExtStateValueFormat::*;
+    match f {
+        Normalized => T::Normalized,
+        Percent => T::Percent,
+    }
+}
+
 fn convert_bookmark_ref(r: BookmarkRef) -> (BookmarkAnchorType, u32) {
     use BookmarkAnchorType as T;
     match r {
@@ -1106,6 +1555,41 @@ fn convert_track_exclusivity(exclusivity: Option<TrackExclusivity>) -> domain::T
     }
 }
 
+fn convert_chord_shape(shape: ChordShape) -> domain::ChordShape {
+    use domain::ChordShape as T;
+    use ChordShape::*;
+    match shape {
+        Major => T::Major,
+        Minor => T::Minor,
+        Diminished => T::Diminished,
+        Augmented => T::Augmented,
+        Sus2 => T::Sus2,
+        Sus4 => T::Sus4,
+        Major7 => T::Major7,
+        Minor7 => T::Minor7,
+        Dominant7 => T::Dominant7,
+        Power => T::Power,
+        Octave => T::Octave,
+    }
+}
+
+fn convert_arpeggiator(arpeggiator: Arpeggiator) -> domain::ArpeggiatorSettings {
+    domain::ArpeggiatorSettings {
+        direction: {
+            use domain::ArpeggiatorDirection as T;
+            use ArpeggiatorDirection::*;
+            match arpeggiator.direction.unwrap_or_default() {
+                Up => T::Up,
+                Down => T::Down,
+                UpDown => T::UpDown,
+            }
+        },
+        steps_per_beat: arpeggiator
+            .steps_per_beat
+            .unwrap_or(defaults::TARGET_SEND_MIDI_ARPEGGIATOR_STEPS_PER_BEAT),
+    }
+}
+
 fn convert_fx_display_kind(display_kind: FxDisplayKind) -> FxDisplayType {
     use domain::FxDisplayType as T;
     use FxDisplayKind::*;
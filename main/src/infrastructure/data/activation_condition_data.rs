@@ -2,6 +2,7 @@ use crate::application::{
     ActivationConditionModel, ActivationType, BankConditionModel, ModifierConditionModel,
 };
 use crate::base::default_util::is_default;
+use crate::domain::ReaperStateCondition;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
@@ -17,6 +18,10 @@ pub struct ActivationConditionData {
     pub program_condition: BankConditionModel,
     #[serde(default, skip_serializing_if = "is_default")]
     pub eel_condition: String,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub expression_condition: String,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub reaper_state_condition: ReaperStateCondition,
 }
 
 impl ActivationConditionData {
@@ -27,6 +32,8 @@ impl ActivationConditionData {
             modifier_condition_2: model.modifier_condition_2.get(),
             program_condition: model.bank_condition.get(),
             eel_condition: model.eel_condition.get_ref().clone(),
+            expression_condition: model.expression_condition.get_ref().clone(),
+            reaper_state_condition: model.reaper_state_condition.get(),
         }
     }
 
@@ -46,5 +53,11 @@ impl ActivationConditionData {
         model
             .eel_condition
             .set_with_optional_notification(self.eel_condition.clone(), with_notification);
+        model
+            .expression_condition
+            .set_with_optional_notification(self.expression_condition.clone(), with_notification);
+        model
+            .reaper_state_condition
+            .set_with_optional_notification(self.reaper_state_condition, with_notification);
     }
 }
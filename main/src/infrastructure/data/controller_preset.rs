@@ -1,11 +1,15 @@
-use crate::application::{ControllerPreset, Preset, PresetManager};
+use crate::application::{
+    ControllerPreset, OrphanedCustomDataRef, Preset, PresetManager, RawMidiMessage,
+};
 use crate::domain::MappingCompartment;
 use crate::infrastructure::data::{
     CompartmentModelData, ExtendedPresetManager, FileBasedPresetManager, PresetData,
 };
 
 use crate::base::default_util::is_default;
+use crate::base::{apply_json_patch, JsonPatchOperation};
 use crate::infrastructure::plugin::App;
+use reaper_high::Reaper;
 use semver::Version;
 use serde::{Deserialize, Serialize};
 use std::cell::RefCell;
@@ -25,6 +29,56 @@ impl PresetManager for SharedControllerPresetManager {
     }
 }
 
+impl FileBasedControllerPresetManager {
+    /// Returns the IDs and orphaned custom data references of all controller presets that have
+    /// some.
+    pub fn orphaned_custom_data_refs(&self) -> Vec<(String, Vec<OrphanedCustomDataRef>)> {
+        self.presets()
+            .filter_map(|p| {
+                let refs = p.orphaned_custom_data_refs();
+                if refs.is_empty() {
+                    return None;
+                }
+                Some((p.id().to_string(), refs))
+            })
+            .collect()
+    }
+
+    /// Removes the orphaned custom data references from the given controller preset and saves it.
+    ///
+    /// Returns the removed references.
+    pub fn remove_orphaned_custom_data_refs(
+        &mut self,
+        id: &str,
+    ) -> Result<Vec<OrphanedCustomDataRef>, &'static str> {
+        let mut preset = self.find_by_id(id).ok_or("controller preset not found")?;
+        let removed = preset.remove_orphaned_custom_data_refs();
+        if !removed.is_empty() {
+            self.update_preset(preset)?;
+        }
+        Ok(removed)
+    }
+
+    pub fn log_orphaned_custom_data_refs(&self) {
+        let orphaned_refs = self.orphaned_custom_data_refs();
+        if orphaned_refs.is_empty() {
+            return;
+        }
+        let lines: Vec<_> = orphaned_refs
+            .iter()
+            .flat_map(|(id, refs)| refs.iter().map(move |r| format!("- {}: {}\n", id, r)))
+            .collect();
+        let msg = format!(
+            "\n\
+            # Orphaned controller custom data\n\
+            \n\
+            {}",
+            lines.concat()
+        );
+        Reaper::get().show_console_msg(msg);
+    }
+}
+
 impl ExtendedPresetManager for SharedControllerPresetManager {
     fn find_index_by_id(&self, id: &str) -> Option<usize> {
         self.borrow().find_index_by_id(id)
@@ -52,6 +106,38 @@ pub struct ControllerPresetData {
     data: CompartmentModelData,
     #[serde(default, skip_serializing_if = "is_default")]
     custom_data: HashMap<String, serde_json::Value>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    midi_macros: HashMap<String, Vec<RawMidiMessage>>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    state_requests: Vec<RawMidiMessage>,
+}
+
+/// Applies the given JSON patch to the representation of the preset as it's saved on disk.
+///
+/// Empty collections (e.g. `customData` or `mappings`) can be patched even though they are left
+/// out on disk. The patched preset must still be valid, otherwise an error is returned.
+pub fn patch_controller_preset(
+    preset: &ControllerPreset,
+    operations: &[JsonPatchOperation],
+) -> Result<ControllerPreset, String> {
+    let mut json = serde_json::to_value(ControllerPresetData::from_model(preset))
+        .map_err(|e| e.to_string())?;
+    if let Some(object) = json.as_object_mut() {
+        for key in &["customData", "parameters", "midiMacros"] {
+            object
+                .entry(*key)
+                .or_insert_with(|| serde_json::Value::Object(Default::default()));
+        }
+        for key in &["groups", "mappings", "stateRequests"] {
+            object
+                .entry(*key)
+                .or_insert_with(|| serde_json::Value::Array(Default::default()));
+        }
+    }
+    apply_json_patch(&mut json, operations)?;
+    let data: ControllerPresetData = serde_json::from_value(json)
+        .map_err(|e| format!("patched preset isn't valid. Details:\n\n{}", e))?;
+    data.to_model(preset.id().to_string())
 }
 
 impl PresetData for ControllerPresetData {
@@ -64,6 +150,8 @@ impl PresetData for ControllerPresetData {
             data: CompartmentModelData::from_model(preset.data()),
             name: preset.name().to_string(),
             custom_data: preset.custom_data().clone(),
+            midi_macros: preset.midi_macros().clone(),
+            state_requests: preset.state_requests().to_vec(),
         }
     }
 
@@ -76,6 +164,8 @@ impl PresetData for ControllerPresetData {
                 MappingCompartment::ControllerMappings,
             )?,
             self.custom_data.clone(),
+            self.midi_macros.clone(),
+            self.state_requests.clone(),
         );
         Ok(preset)
     }
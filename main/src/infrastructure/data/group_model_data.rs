@@ -2,6 +2,7 @@ use crate::application::GroupModel;
 use crate::base::default_util::is_default;
 use crate::domain::{GroupId, GroupKey, MappingCompartment, Tag};
 use crate::infrastructure::data::{ActivationConditionData, EnabledData};
+use reaper_medium::MidiOutputDeviceId;
 use serde::{Deserialize, Serialize};
 use std::borrow::BorrowMut;
 
@@ -23,6 +24,8 @@ pub struct GroupModelData {
     pub tags: Vec<Tag>,
     #[serde(flatten)]
     pub enabled_data: EnabledData,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub midi_output_device_id: Option<u8>,
     #[serde(flatten)]
     pub activation_condition_data: ActivationConditionData,
 }
@@ -38,6 +41,7 @@ impl GroupModelData {
                 control_is_enabled: model.control_is_enabled.get(),
                 feedback_is_enabled: model.feedback_is_enabled.get(),
             },
+            midi_output_device_id: model.midi_output_device.get().map(|id| id.get()),
             activation_condition_data: ActivationConditionData::from_model(
                 &model.activation_condition_model,
             ),
@@ -71,6 +75,9 @@ impl GroupModelData {
         model
             .feedback_is_enabled
             .set_without_notification(self.enabled_data.feedback_is_enabled);
+        model
+            .midi_output_device
+            .set_without_notification(self.midi_output_device_id.map(MidiOutputDeviceId::new));
         self.activation_condition_data
             .apply_to_model(model.activation_condition_model.borrow_mut(), false);
     }
@@ -1,7 +1,7 @@
 use crate::application::MappingModel;
 use crate::base::default_util::{bool_true, is_bool_true, is_default};
 use crate::domain::{
-    ExtendedProcessorContext, FeedbackSendBehavior, GroupId, GroupKey, MappingCompartment,
+    ExtendedProcessorContext, FeedbackSendBehavior, GroupId, GroupKey, LayerId, MappingCompartment,
     MappingKey, Tag,
 };
 use crate::infrastructure::data::{
@@ -47,6 +47,11 @@ pub struct MappingModelData {
     pub advanced: Option<serde_yaml::mapping::Mapping>,
     #[serde(default = "bool_true", skip_serializing_if = "is_bool_true")]
     pub visible_in_projection: bool,
+    /// Maximum number of control values per second.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub max_control_rate: Option<u32>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub layer: Option<LayerId>,
 }
 
 impl MappingModelData {
@@ -81,6 +86,8 @@ impl MappingModelData {
             ),
             advanced: model.advanced_settings().cloned(),
             visible_in_projection: model.visible_in_projection.get(),
+            max_control_rate: model.max_control_rate.get(),
+            layer: model.layer.get(),
         }
     }
 
@@ -240,5 +247,11 @@ impl MappingModelData {
         model
             .visible_in_projection
             .set_with_optional_notification(self.visible_in_projection, with_notification);
+        model
+            .max_control_rate
+            .set_with_optional_notification(self.max_control_rate, with_notification);
+        model
+            .layer
+            .set_with_optional_notification(self.layer, with_notification);
     }
 }
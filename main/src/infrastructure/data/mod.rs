@@ -13,6 +13,9 @@ pub use mode_model_data::*;
 mod session_data;
 pub use session_data::*;
 
+mod session_template;
+pub use session_template::*;
+
 mod source_model_data;
 pub use source_model_data::*;
 
@@ -49,5 +52,8 @@ pub use migration::*;
 mod osc_device_management;
 pub use osc_device_management::*;
 
+mod rtp_midi_device_management;
+pub use rtp_midi_device_management::*;
+
 mod virtual_control;
 pub use virtual_control::*;
@@ -1,5 +1,9 @@
 use crate::application::ModeModel;
 use crate::base::default_util::{is_default, is_unit_value_one, unit_value_one};
+use crate::domain::{
+    EncoderAccelerationProfile, MusicalScale, RampCurve, ResponseCurve, ValueSequenceStepping,
+    DEFAULT_ENCODER_ACCELERATION_CURVE,
+};
 use crate::infrastructure::data::MigrationDescriptor;
 use crate::infrastructure::plugin::App;
 use helgoboss_learn::{
@@ -83,6 +87,45 @@ pub struct ModeModelData {
     pub target_value_sequence: ValueSequence,
     #[serde(default, skip_serializing_if = "is_default")]
     pub feedback_type: FeedbackType,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub velocity_threshold: Option<UnitValue>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub velocity_below_threshold_value: Option<UnitValue>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub velocity_above_threshold_value: Option<UnitValue>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub quantization_scale: Option<MusicalScale>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub quantization_root: u8,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub min_target_hit_interval: u64,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub dead_zone: UnitValue,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub hysteresis_lower_threshold: Option<UnitValue>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub hysteresis_upper_threshold: Option<UnitValue>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub ramp_duration: u64,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub ramp_curve: RampCurve,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub ramp_returns_on_release: bool,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub feedback_deadband: UnitValue,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub encoder_acceleration_profile: EncoderAccelerationProfile,
+    #[serde(
+        default = "default_encoder_acceleration_curve",
+        skip_serializing_if = "is_default_encoder_acceleration_curve"
+    )]
+    pub encoder_acceleration_curve: f64,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub target_value_sequence_stepping: ValueSequenceStepping,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub response_curve: ResponseCurve,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub takeover_catch_range: UnitValue,
 }
 
 fn default_step_size() -> SoftSymmetricUnitValue {
@@ -93,6 +136,14 @@ fn is_default_step_size(v: &SoftSymmetricUnitValue) -> bool {
     *v == default_step_size()
 }
 
+fn default_encoder_acceleration_curve() -> f64 {
+    DEFAULT_ENCODER_ACCELERATION_CURVE
+}
+
+fn is_default_encoder_acceleration_curve(v: &f64) -> bool {
+    *v == DEFAULT_ENCODER_ACCELERATION_CURVE
+}
+
 impl ModeModelData {
     pub fn from_model(model: &ModeModel) -> Self {
         Self {
@@ -140,6 +191,24 @@ impl ModeModelData {
             group_interaction: model.group_interaction.get(),
             target_value_sequence: model.target_value_sequence.get_ref().clone(),
             feedback_type: model.feedback_type.get(),
+            velocity_threshold: model.velocity_threshold.get(),
+            velocity_below_threshold_value: model.velocity_below_threshold_value.get(),
+            velocity_above_threshold_value: model.velocity_above_threshold_value.get(),
+            quantization_scale: model.quantization_scale.get(),
+            quantization_root: model.quantization_root.get(),
+            min_target_hit_interval: model.min_target_hit_interval.get().as_millis() as _,
+            dead_zone: model.dead_zone.get(),
+            hysteresis_lower_threshold: model.hysteresis_thresholds.get().map(|i| i.min_val()),
+            hysteresis_upper_threshold: model.hysteresis_thresholds.get().map(|i| i.max_val()),
+            ramp_duration: model.ramp_duration.get().as_millis() as _,
+            ramp_curve: model.ramp_curve.get(),
+            ramp_returns_on_release: model.ramp_returns_on_release.get(),
+            feedback_deadband: model.feedback_deadband.get(),
+            encoder_acceleration_profile: model.encoder_acceleration_profile.get(),
+            encoder_acceleration_curve: model.encoder_acceleration_curve.get(),
+            target_value_sequence_stepping: model.target_value_sequence_stepping.get(),
+            response_curve: model.response_curve.get_ref().clone(),
+            takeover_catch_range: model.takeover_catch_range.get(),
         }
     }
 
@@ -275,5 +344,67 @@ impl ModeModelData {
         model
             .feedback_type
             .set_with_optional_notification(self.feedback_type, with_notification);
+        model
+            .velocity_threshold
+            .set_with_optional_notification(self.velocity_threshold, with_notification);
+        model
+            .velocity_below_threshold_value
+            .set_with_optional_notification(self.velocity_below_threshold_value, with_notification);
+        model
+            .velocity_above_threshold_value
+            .set_with_optional_notification(self.velocity_above_threshold_value, with_notification);
+        model
+            .quantization_scale
+            .set_with_optional_notification(self.quantization_scale, with_notification);
+        model
+            .quantization_root
+            .set_with_optional_notification(self.quantization_root, with_notification);
+        model
+            .min_target_hit_interval
+            .set_with_optional_notification(
+                Duration::from_millis(self.min_target_hit_interval),
+                with_notification,
+            );
+        model
+            .dead_zone
+            .set_with_optional_notification(self.dead_zone, with_notification);
+        let hysteresis_thresholds = match (
+            self.hysteresis_lower_threshold,
+            self.hysteresis_upper_threshold,
+        ) {
+            (Some(lower), Some(upper)) => Interval::try_new(lower, upper).ok(),
+            _ => None,
+        };
+        model
+            .hysteresis_thresholds
+            .set_with_optional_notification(hysteresis_thresholds, with_notification);
+        model.ramp_duration.set_with_optional_notification(
+            Duration::from_millis(self.ramp_duration),
+            with_notification,
+        );
+        model
+            .ramp_curve
+            .set_with_optional_notification(self.ramp_curve, with_notification);
+        model
+            .ramp_returns_on_release
+            .set_with_optional_notification(self.ramp_returns_on_release, with_notification);
+        model
+            .feedback_deadband
+            .set_with_optional_notification(self.feedback_deadband, with_notification);
+        model
+            .encoder_acceleration_profile
+            .set_with_optional_notification(self.encoder_acceleration_profile, with_notification);
+        model
+            .encoder_acceleration_curve
+            .set_with_optional_notification(self.encoder_acceleration_curve, with_notification);
+        model
+            .target_value_sequence_stepping
+            .set_with_optional_notification(self.target_value_sequence_stepping, with_notification);
+        model
+            .response_curve
+            .set_with_optional_notification(self.response_curve.clone(), with_notification);
+        model
+            .takeover_catch_range
+            .set_with_optional_notification(self.takeover_catch_range, with_notification);
     }
 }
@@ -1,6 +1,6 @@
 use crate::base::default_util::{bool_true, is_bool_true, is_default};
 use crate::base::AsyncNotifier;
-use crate::domain::{OscDeviceId, OscInputDevice, OscOutputDevice};
+use crate::domain::{OscDeviceId, OscInputDevice, OscOutputDevice, OscTransport};
 use crate::infrastructure::plugin::App;
 use derive_more::Display;
 use rx_util::Notifier;
@@ -148,6 +148,11 @@ pub struct OscDevice {
     device_port: Option<u16>,
     #[serde(default = "bool_true", skip_serializing_if = "is_bool_true")]
     can_deal_with_bundles: bool,
+    #[serde(default, skip_serializing_if = "is_default")]
+    transport: OscTransport,
+    /// Maximum number of feedback messages per second (for devices which can't keep up).
+    #[serde(default, skip_serializing_if = "is_default")]
+    feedback_max_rate: Option<u32>,
     #[serde(skip)]
     has_output_connection_problem: bool,
 }
@@ -163,6 +168,8 @@ impl Default for OscDevice {
             device_host: None,
             device_port: None,
             can_deal_with_bundles: true,
+            transport: Default::default(),
+            feedback_max_rate: None,
             has_input_connection_problem: false,
             has_output_connection_problem: false,
         }
@@ -184,6 +191,7 @@ impl OscDevice {
                 self.local_port.ok_or("local port not specified")?,
             ),
             App::logger().new(slog::o!("struct" => "OscInputDevice", "id" => self.id.to_string())),
+            self.transport,
         )
     }
 
@@ -202,6 +210,8 @@ impl OscDevice {
             ),
             App::logger().new(slog::o!("struct" => "OscOutputDevice", "id" => self.id.to_string())),
             self.can_deal_with_bundles,
+            self.transport,
+            self.feedback_max_rate,
         )
     }
 
@@ -245,6 +255,10 @@ impl OscDevice {
         self.can_deal_with_bundles
     }
 
+    pub fn transport(&self) -> OscTransport {
+        self.transport
+    }
+
     pub fn input_status(&self) -> OscDeviceStatus {
         use OscDeviceStatus::*;
         if !self.is_configured_for_input() {
@@ -301,10 +315,22 @@ impl OscDevice {
         self.can_deal_with_bundles = !self.can_deal_with_bundles;
     }
 
+    pub fn toggle_tcp(&mut self) {
+        self.transport = match self.transport {
+            OscTransport::Udp => OscTransport::Tcp,
+            OscTransport::Tcp => OscTransport::Udp,
+        };
+    }
+
     pub fn get_list_label(&self, is_output: bool) -> String {
         format!(
-            "{}{}",
+            "{}{}{}",
             self.name(),
+            if self.transport == OscTransport::Tcp {
+                " (TCP)"
+            } else {
+                ""
+            },
             if is_output {
                 self.output_status()
             } else {
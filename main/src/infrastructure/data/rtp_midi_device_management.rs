@@ -0,0 +1,235 @@
+use crate::base::default_util::{bool_true, is_bool_true, is_default};
+use crate::base::AsyncNotifier;
+use crate::domain::{RtpMidiDeviceId, RtpMidiSession};
+use crate::infrastructure::plugin::App;
+use derive_more::Display;
+use rx_util::Notifier;
+use rxrust::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+pub type SharedRtpMidiDeviceManager = Rc<RefCell<RtpMidiDeviceManager>>;
+
+#[derive(Debug)]
+pub struct RtpMidiDeviceManager {
+    config: RtpMidiDeviceConfig,
+    changed_subject: LocalSubject<'static, (), ()>,
+    rtp_midi_device_config_file_path: PathBuf,
+}
+
+impl RtpMidiDeviceManager {
+    pub fn new(rtp_midi_device_config_file_path: PathBuf) -> RtpMidiDeviceManager {
+        let mut manager = RtpMidiDeviceManager {
+            config: Default::default(),
+            rtp_midi_device_config_file_path,
+            changed_subject: Default::default(),
+        };
+        let _ = manager.load();
+        manager
+    }
+
+    fn load(&mut self) -> Result<(), String> {
+        let json = fs::read_to_string(&self.rtp_midi_device_config_file_path)
+            .map_err(|_| "couldn't read RTP-MIDI device config file".to_string())?;
+        let config: RtpMidiDeviceConfig = serde_json::from_str(&json)
+            .map_err(|e| format!("RTP-MIDI device config file isn't valid. Details:\n\n{}", e))?;
+        self.config = config;
+        Ok(())
+    }
+
+    fn save(&mut self) -> Result<(), String> {
+        fs::create_dir_all(&self.rtp_midi_device_config_file_path.parent().unwrap())
+            .map_err(|_| "couldn't create RTP-MIDI device config file parent directory")?;
+        let json = serde_json::to_string_pretty(&self.config)
+            .map_err(|_| "couldn't serialize RTP-MIDI device config")?;
+        fs::write(&self.rtp_midi_device_config_file_path, json)
+            .map_err(|_| "couldn't write RTP-MIDI device config file")?;
+        Ok(())
+    }
+
+    pub fn devices(&self) -> impl Iterator<Item = &RtpMidiDevice> + ExactSizeIterator {
+        self.config.devices.iter()
+    }
+
+    pub fn find_index_by_id(&self, id: &RtpMidiDeviceId) -> Option<usize> {
+        self.config.devices.iter().position(|dev| dev.id() == id)
+    }
+
+    pub fn find_device_by_id(&self, id: &RtpMidiDeviceId) -> Option<&RtpMidiDevice> {
+        self.config.devices.iter().find(|dev| dev.id() == id)
+    }
+
+    pub fn find_device_by_index(&self, index: usize) -> Option<&RtpMidiDevice> {
+        self.config.devices.get(index)
+    }
+
+    pub fn bind_all_enabled_sessions(&mut self) -> Vec<RtpMidiSession> {
+        self.config
+            .devices
+            .iter_mut()
+            .filter(|dev| dev.is_enabled())
+            .flat_map(|dev| dev.bind_session())
+            .collect()
+    }
+
+    pub fn changed(&self) -> impl LocalObservable<'static, Item = (), Err = ()> + 'static {
+        self.changed_subject.clone()
+    }
+
+    pub fn add_device(&mut self, dev: RtpMidiDevice) -> Result<(), &'static str> {
+        self.config.devices.push(dev);
+        self.save_and_notify_changed()?;
+        Ok(())
+    }
+
+    pub fn update_device(&mut self, dev: RtpMidiDevice) -> Result<(), &'static str> {
+        let old_dev = self
+            .config
+            .devices
+            .iter_mut()
+            .find(|d| d.id() == dev.id())
+            .ok_or("couldn't find RTP-MIDI device")?;
+        let _ = std::mem::replace(old_dev, dev);
+        self.save_and_notify_changed()?;
+        Ok(())
+    }
+
+    pub fn remove_device_by_id(&mut self, dev_id: RtpMidiDeviceId) -> Result<(), &'static str> {
+        self.config.devices.retain(|dev| dev.id != dev_id);
+        self.save_and_notify_changed()?;
+        Ok(())
+    }
+
+    fn save_and_notify_changed(&mut self) -> Result<(), &'static str> {
+        self.save()
+            .map_err(|_| "error when saving RTP-MIDI device configuration")?;
+        AsyncNotifier::notify(&mut self.changed_subject, &());
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RtpMidiDeviceConfig {
+    #[serde(default)]
+    devices: Vec<RtpMidiDevice>,
+}
+
+/// An RTP-MIDI session as configured by the user.
+///
+/// Acts as control input and feedback output at the same time, just like a MIDI device which
+/// has both an input and an output port.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RtpMidiDevice {
+    id: RtpMidiDeviceId,
+    name: String,
+    #[serde(default = "bool_true", skip_serializing_if = "is_bool_true")]
+    is_enabled: bool,
+    /// Session control port. The next port is used for MIDI data.
+    #[serde(default, skip_serializing_if = "is_default")]
+    local_port: Option<u16>,
+    #[serde(skip)]
+    has_connection_problem: bool,
+}
+
+impl Default for RtpMidiDevice {
+    fn default() -> Self {
+        Self {
+            id: RtpMidiDeviceId::random(),
+            name: "".to_string(),
+            is_enabled: true,
+            // Default port of Apple's network MIDI driver.
+            local_port: Some(5004),
+            has_connection_problem: false,
+        }
+    }
+}
+
+impl RtpMidiDevice {
+    pub fn bind_session(&mut self) -> Result<RtpMidiSession, Box<dyn Error>> {
+        let result = self.bind_session_internal();
+        self.has_connection_problem = result.is_err();
+        result
+    }
+
+    fn bind_session_internal(&self) -> Result<RtpMidiSession, Box<dyn Error>> {
+        RtpMidiSession::bind(
+            self.id,
+            self.name.clone(),
+            self.local_port.ok_or("local port not specified")?,
+            App::logger().new(slog::o!("struct" => "RtpMidiSession", "id" => self.id.to_string())),
+        )
+    }
+
+    pub fn id(&self) -> &RtpMidiDeviceId {
+        &self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn local_port(&self) -> Option<u16> {
+        self.local_port
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.is_enabled
+    }
+
+    pub fn status(&self) -> RtpMidiDeviceStatus {
+        use RtpMidiDeviceStatus::*;
+        if self.local_port.is_none() {
+            return Incomplete;
+        }
+        if !self.is_enabled {
+            return Disabled;
+        }
+        if self.has_connection_problem {
+            return UnableToBind;
+        }
+        Connected
+    }
+
+    pub fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+
+    pub fn set_local_port(&mut self, local_port: Option<u16>) {
+        self.local_port = local_port;
+    }
+
+    pub fn toggle_enabled(&mut self) {
+        self.is_enabled = !self.is_enabled;
+    }
+
+    /// Label which also shows the peers which are currently connected.
+    pub fn get_list_label(&self, peer_names: &[String]) -> String {
+        let status = self.status();
+        if !matches!(status, RtpMidiDeviceStatus::Connected) {
+            return format!("{}{}", self.name(), status);
+        }
+        if peer_names.is_empty() {
+            format!("{} <no peers>", self.name())
+        } else {
+            format!("{} ({})", self.name(), peer_names.join(", "))
+        }
+    }
+}
+
+#[derive(Display)]
+pub enum RtpMidiDeviceStatus {
+    #[display(fmt = " <needs config>")]
+    Incomplete,
+    #[display(fmt = " <disabled>")]
+    Disabled,
+    #[display(fmt = " <unable to connect>")]
+    UnableToBind,
+    #[display(fmt = "")]
+    Connected,
+}
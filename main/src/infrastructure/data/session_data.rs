@@ -4,17 +4,20 @@ use crate::application::{
 };
 use crate::base::default_util::{bool_true, is_bool_true, is_default};
 use crate::domain::{
-    GroupId, GroupKey, InstanceState, MappingCompartment, MappingId, MidiControlInput,
-    MidiDestination, OscDeviceId, ParameterArray, QualifiedSlotDescriptor, Tag,
-    COMPARTMENT_PARAMETER_COUNT, ZEROED_PLUGIN_PARAMETERS,
+    BackboneState, ClipMatrixContent, ClipMatrixId, ClipMatrixSize, ControlInput,
+    DeferredClipSlots, FeedbackOutput, GroupId, GroupKey, IdleAnimationType, InstancePriority,
+    InstanceState, LayerId, MappingCompartment, MappingId, MidiControlInput, MidiDestination,
+    OscDeviceId, ParameterArray, QualifiedSlotDescriptor, RtpMidiDeviceId, SlotStopQuantization,
+    Tag, COMPARTMENT_PARAMETER_COUNT, ZEROED_PLUGIN_PARAMETERS,
 };
 use crate::infrastructure::data::{
     ensure_no_duplicate_compartment_data, GroupModelData, MappingModelData, MigrationDescriptor,
     ParameterData,
 };
-use crate::infrastructure::plugin::App;
+use crate::infrastructure::plugin::{App, Subsystem};
 
 use crate::infrastructure::api::convert::to_data::ApiToDataConversionContext;
+use reaper_high::{Guid, Project};
 use reaper_medium::{MidiInputDeviceId, MidiOutputDeviceId};
 use semver::Version;
 use serde::{Deserialize, Serialize};
@@ -44,12 +47,25 @@ pub struct SessionData {
     let_unmatched_events_through: bool,
     #[serde(default = "bool_true", skip_serializing_if = "is_bool_true")]
     always_auto_detect_mode: bool,
-    #[serde(default, skip_serializing_if = "is_default")]
+    // Serialization skipped because this is deprecated in favor of instance_priority
+    // since ReaLearn v2.11.0-pre.15.
+    #[serde(default, skip_serializing)]
     lives_on_upper_floor: bool,
     #[serde(default, skip_serializing_if = "is_default")]
+    instance_priority: InstancePriority,
+    #[serde(default, skip_serializing_if = "is_default")]
     // false by default because in older versions, feedback was always sent no matter if armed or
     // not
     send_feedback_only_if_armed: bool,
+    #[serde(default, skip_serializing_if = "is_default")]
+    idle_animation_type: IdleAnimationType,
+    #[serde(
+        default = "default_idle_animation_timeout_minutes",
+        skip_serializing_if = "is_default_idle_animation_timeout_minutes"
+    )]
+    idle_animation_timeout_minutes: u32,
+    #[serde(default = "bool_true", skip_serializing_if = "is_bool_true")]
+    suppress_feedback_loops: bool,
     /// `None` means "<FX input>"
     #[serde(default, skip_serializing_if = "is_default")]
     control_device_id: Option<ControlDeviceId>,
@@ -86,7 +102,22 @@ pub struct SessionData {
     #[serde(default, skip_serializing_if = "is_default")]
     controller_parameters: HashMap<String, ParameterData>,
     #[serde(default, skip_serializing_if = "is_default")]
+    clip_matrix_size: ClipMatrixSize,
+    /// Slot indexes are relative to the clip matrix size.
+    #[serde(default, skip_serializing_if = "is_default")]
     clip_slots: Vec<QualifiedSlotDescriptor>,
+    /// Contents of the shared clip matrices.
+    ///
+    /// Every instance saves all shared clip matrices. When loading, an instance only fills a
+    /// shared clip matrix if no other instance has filled it already.
+    #[serde(default, skip_serializing_if = "is_default")]
+    shared_clip_matrices: HashMap<ClipMatrixId, Vec<QualifiedSlotDescriptor>>,
+    /// Sizes of the shared clip matrices which don't have the default size.
+    #[serde(default, skip_serializing_if = "is_default")]
+    shared_clip_matrix_sizes: HashMap<ClipMatrixId, ClipMatrixSize>,
+    /// Default stop quantization for clip targets which don't define their own.
+    #[serde(default, skip_serializing_if = "is_default")]
+    clip_stop_quantization: Option<SlotStopQuantization>,
     #[serde(default, skip_serializing_if = "is_default")]
     pub tags: Vec<Tag>,
     #[serde(default, skip_serializing_if = "is_default")]
@@ -95,6 +126,31 @@ pub struct SessionData {
     main: CompartmentState,
     #[serde(default, skip_serializing_if = "is_default")]
     active_instance_tags: HashSet<Tag>,
+    /// Set by target "ReaLearn: Set bank offset".
+    #[serde(default, skip_serializing_if = "is_default")]
+    virtual_control_element_offset: u32,
+    /// Set by target "FX: Navigate between parameter banks".
+    #[serde(default, skip_serializing_if = "is_default")]
+    fx_parameter_bank_offset: u32,
+    /// Set by target "Track: Spill folder".
+    #[serde(default, skip_serializing_if = "is_default")]
+    spilled_folder_track_id: Option<String>,
+    /// Cue sends which existed when saving.
+    ///
+    /// They are saved as normal hardware output sends along with the project, so they need to be
+    /// removed again when loading.
+    #[serde(default, skip_serializing_if = "is_default")]
+    cue_sends: Vec<CueSendData>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    instance_hooks: Option<serde_yaml::Mapping>,
+}
+
+fn default_idle_animation_timeout_minutes() -> u32 {
+    crate::application::session_defaults::IDLE_ANIMATION_TIMEOUT_MINUTES
+}
+
+fn is_default_idle_animation_timeout_minutes(v: &u32) -> bool {
+    *v == default_idle_animation_timeout_minutes()
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Default)]
@@ -104,6 +160,10 @@ struct CompartmentState {
     active_mapping_by_group: HashMap<GroupId, MappingId>,
     #[serde(default, skip_serializing_if = "is_default")]
     active_mapping_tags: HashSet<Tag>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    disabled_groups: HashSet<GroupId>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    active_layer: LayerId,
 }
 
 impl CompartmentState {
@@ -114,6 +174,55 @@ impl CompartmentState {
         CompartmentState {
             active_mapping_by_group: instance_state.active_mapping_by_group(compartment).clone(),
             active_mapping_tags: instance_state.active_mapping_tags(compartment).clone(),
+            disabled_groups: instance_state.disabled_groups(compartment).clone(),
+            active_layer: instance_state.active_layer(compartment),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CueSendData {
+    track_id: String,
+    cue_output: u32,
+}
+
+/// RTP-MIDI devices are persisted as MIDI device ID strings with this prefix (followed by the
+/// device UUID), so older ReaLearn versions just see an invalid MIDI device ID.
+const RTP_MIDI_DEVICE_ID_PREFIX: &str = "rtp-midi:";
+
+fn format_rtp_midi_device_id(dev_id: RtpMidiDeviceId) -> String {
+    format!("{}{}", RTP_MIDI_DEVICE_ID_PREFIX, dev_id)
+}
+
+fn parse_rtp_midi_device_id(s: &str) -> Result<RtpMidiDeviceId, &'static str> {
+    s.strip_prefix(RTP_MIDI_DEVICE_ID_PREFIX)
+        .ok_or("not an RTP-MIDI device ID")?
+        .parse()
+}
+
+fn convert_control_input(input: ControlInput) -> Option<ControlDeviceId> {
+    use MidiControlInput::*;
+    match input {
+        ControlInput::Osc(osc_dev_id) => Some(ControlDeviceId::Osc(osc_dev_id)),
+        ControlInput::Midi(FxInput) => None,
+        ControlInput::Midi(Device(dev_id)) => Some(ControlDeviceId::Midi(dev_id.to_string())),
+        ControlInput::Midi(RtpMidi(dev_id)) => {
+            Some(ControlDeviceId::Midi(format_rtp_midi_device_id(dev_id)))
+        }
+    }
+}
+
+fn convert_feedback_output(output: FeedbackOutput) -> FeedbackDeviceId {
+    use MidiDestination::*;
+    match output {
+        FeedbackOutput::Osc(osc_dev_id) => FeedbackDeviceId::Osc(osc_dev_id),
+        FeedbackOutput::Midi(Device(dev_id)) => {
+            FeedbackDeviceId::MidiOrFxOutput(dev_id.to_string())
+        }
+        FeedbackOutput::Midi(FxOutput) => FeedbackDeviceId::MidiOrFxOutput("fx-output".to_owned()),
+        FeedbackOutput::Midi(RtpMidi(dev_id)) => {
+            FeedbackDeviceId::MidiOrFxOutput(format_rtp_midi_device_id(dev_id))
         }
     }
 }
@@ -141,8 +250,12 @@ impl Default for SessionData {
             let_matched_events_through: session_defaults::LET_MATCHED_EVENTS_THROUGH,
             let_unmatched_events_through: session_defaults::LET_UNMATCHED_EVENTS_THROUGH,
             always_auto_detect_mode: session_defaults::AUTO_CORRECT_SETTINGS,
-            lives_on_upper_floor: session_defaults::LIVES_ON_UPPER_FLOOR,
+            lives_on_upper_floor: false,
+            instance_priority: session_defaults::INSTANCE_PRIORITY,
             send_feedback_only_if_armed: session_defaults::SEND_FEEDBACK_ONLY_IF_ARMED,
+            idle_animation_type: session_defaults::IDLE_ANIMATION_TYPE,
+            idle_animation_timeout_minutes: session_defaults::IDLE_ANIMATION_TIMEOUT_MINUTES,
+            suppress_feedback_loops: session_defaults::SUPPRESS_FEEDBACK_LOOPS,
             control_device_id: None,
             feedback_device_id: None,
             default_group: None,
@@ -156,11 +269,20 @@ impl Default for SessionData {
             main_preset_auto_load_mode: session_defaults::MAIN_PRESET_AUTO_LOAD_MODE,
             parameters: Default::default(),
             controller_parameters: Default::default(),
+            clip_matrix_size: Default::default(),
             clip_slots: vec![],
+            shared_clip_matrices: Default::default(),
+            shared_clip_matrix_sizes: Default::default(),
+            clip_stop_quantization: None,
             tags: vec![],
             controller: Default::default(),
             main: Default::default(),
             active_instance_tags: Default::default(),
+            virtual_control_element_offset: 0,
+            fx_parameter_bank_offset: 0,
+            spilled_folder_track_id: None,
+            cue_sends: vec![],
+            instance_hooks: None,
         }
     }
 }
@@ -189,32 +311,27 @@ impl SessionData {
             ))
         };
         let instance_state = session.instance_state().borrow();
+        let (own_clip_matrix, shared_clip_matrices) = match instance_state.deferred_clip_slots() {
+            None => (
+                instance_state.clip_matrix_content(),
+                BackboneState::get().shared_clip_matrix_contents(),
+            ),
+            Some(slots) => (slots.own.clone(), slots.shared.clone()),
+        };
         SessionData {
             version: Some(App::version().clone()),
             id: Some(session.id().to_string()),
             let_matched_events_through: session.let_matched_events_through.get(),
             let_unmatched_events_through: session.let_unmatched_events_through.get(),
             always_auto_detect_mode: session.auto_correct_settings.get(),
-            lives_on_upper_floor: session.lives_on_upper_floor.get(),
+            lives_on_upper_floor: false,
+            instance_priority: session.instance_priority.get(),
             send_feedback_only_if_armed: session.send_feedback_only_if_armed.get(),
-            control_device_id: if let Some(osc_dev_id) = session.osc_input_device_id.get() {
-                Some(ControlDeviceId::Osc(osc_dev_id))
-            } else {
-                use MidiControlInput::*;
-                match session.midi_control_input.get() {
-                    FxInput => None,
-                    Device(dev_id) => Some(ControlDeviceId::Midi(dev_id.to_string())),
-                }
-            },
-            feedback_device_id: if let Some(osc_dev_id) = session.osc_output_device_id.get() {
-                Some(FeedbackDeviceId::Osc(osc_dev_id))
-            } else {
-                use MidiDestination::*;
-                session.midi_feedback_output.get().map(|o| match o {
-                    Device(dev_id) => FeedbackDeviceId::MidiOrFxOutput(dev_id.to_string()),
-                    FxOutput => FeedbackDeviceId::MidiOrFxOutput("fx-output".to_owned()),
-                })
-            },
+            idle_animation_type: session.idle_animation_type.get(),
+            idle_animation_timeout_minutes: session.idle_animation_timeout_minutes.get(),
+            suppress_feedback_loops: session.suppress_feedback_loops.get(),
+            control_device_id: convert_control_input(session.control_input()),
+            feedback_device_id: session.feedback_output().map(convert_feedback_output),
             default_group: from_group(MappingCompartment::MainMappings),
             default_controller_group: from_group(MappingCompartment::ControllerMappings),
             groups: from_groups(MappingCompartment::MainMappings),
@@ -236,7 +353,19 @@ impl SessionData {
                 parameters,
                 MappingCompartment::ControllerMappings,
             ),
-            clip_slots: { instance_state.filled_slot_descriptors() },
+            clip_matrix_size: own_clip_matrix.size,
+            clip_slots: own_clip_matrix.slots,
+            shared_clip_matrices: shared_clip_matrices
+                .iter()
+                .filter(|(_, content)| !content.slots.is_empty())
+                .map(|(id, content)| (id.clone(), content.slots.clone()))
+                .collect(),
+            shared_clip_matrix_sizes: shared_clip_matrices
+                .into_iter()
+                .filter(|(_, content)| content.size != ClipMatrixSize::default())
+                .map(|(id, content)| (id, content.size))
+                .collect(),
+            clip_stop_quantization: instance_state.clip_stop_quantization(),
             tags: session.tags.get_ref().clone(),
             controller: CompartmentState::from_instance_state(
                 &instance_state,
@@ -247,6 +376,20 @@ impl SessionData {
                 MappingCompartment::MainMappings,
             ),
             active_instance_tags: instance_state.active_instance_tags().clone(),
+            virtual_control_element_offset: instance_state.virtual_control_element_offset(),
+            fx_parameter_bank_offset: instance_state.fx_parameter_bank_offset(),
+            spilled_folder_track_id: instance_state
+                .spilled_folder_track()
+                .map(|id| id.to_string_without_braces()),
+            cue_sends: instance_state
+                .cue_sends()
+                .cued_tracks()
+                .map(|(track, cue_output)| CueSendData {
+                    track_id: track.guid().to_string_without_braces(),
+                    cue_output,
+                })
+                .collect(),
+            instance_hooks: session.instance_hooks().cloned(),
         }
     }
 
@@ -273,6 +416,10 @@ impl SessionData {
             Some(dev_id) => {
                 use ControlDeviceId::*;
                 match dev_id {
+                    Midi(s) if s.starts_with(RTP_MIDI_DEVICE_ID_PREFIX) => {
+                        let rtp_midi_dev_id = parse_rtp_midi_device_id(s)?;
+                        (MidiControlInput::RtpMidi(rtp_midi_dev_id), None)
+                    }
                     Midi(midi_dev_id_string) => {
                         let raw_midi_dev_id = midi_dev_id_string
                             .parse::<u8>()
@@ -294,6 +441,10 @@ impl SessionData {
                     MidiOrFxOutput(s) if s == "fx-output" => {
                         (Some(MidiDestination::FxOutput), None)
                     }
+                    MidiOrFxOutput(s) if s.starts_with(RTP_MIDI_DEVICE_ID_PREFIX) => {
+                        let rtp_midi_dev_id = parse_rtp_midi_device_id(s)?;
+                        (Some(MidiDestination::RtpMidi(rtp_midi_dev_id)), None)
+                    }
                     MidiOrFxOutput(midi_dev_id_string) => {
                         let midi_dev_id = midi_dev_id_string
                             .parse::<u8>()
@@ -313,10 +464,25 @@ impl SessionData {
         session
             .auto_correct_settings
             .set(self.always_auto_detect_mode);
-        session.lives_on_upper_floor.set(self.lives_on_upper_floor);
+        let instance_priority = if self.lives_on_upper_floor && self.instance_priority == 0 {
+            // Superior instances of older versions just win against all others.
+            1
+        } else {
+            self.instance_priority
+        };
+        session.instance_priority.set(instance_priority);
         session
             .send_feedback_only_if_armed
             .set_without_notification(self.send_feedback_only_if_armed);
+        session
+            .idle_animation_type
+            .set_without_notification(self.idle_animation_type);
+        session
+            .idle_animation_timeout_minutes
+            .set_without_notification(self.idle_animation_timeout_minutes);
+        session
+            .suppress_feedback_loops
+            .set_without_notification(self.suppress_feedback_loops);
         session
             .midi_control_input
             .set_without_notification(midi_control_input);
@@ -432,13 +598,31 @@ impl SessionData {
         );
         // Instance state
         {
+            let project = Some(session.context().project_or_current_project());
             let mut instance_state = session.instance_state().borrow_mut();
-            instance_state.load_slots(
-                self.clip_slots.clone(),
-                Some(session.context().project_or_current_project()),
-            )?;
+            let clip_slots = self.clip_slots();
+            if App::get().subsystem_is_enabled(Subsystem::ClipEngine) {
+                load_clip_slots(&mut instance_state, clip_slots, project)?;
+            } else {
+                instance_state.defer_clip_slots(clip_slots);
+            }
             instance_state
                 .set_active_instance_tags_without_notification(self.active_instance_tags.clone());
+            instance_state.set_clip_stop_quantization(self.clip_stop_quantization);
+            instance_state.set_virtual_control_element_offset(self.virtual_control_element_offset);
+            instance_state.set_fx_parameter_bank_offset(self.fx_parameter_bank_offset);
+            instance_state.set_spilled_folder_track(
+                self.spilled_folder_track_id
+                    .as_ref()
+                    .and_then(|id| Guid::from_string_without_braces(id).ok()),
+            );
+            if let Some(project) = project {
+                let stale_cue_sends = self.cue_sends.iter().filter_map(|s| {
+                    let guid = Guid::from_string_without_braces(&s.track_id).ok()?;
+                    Some((project.track_by_guid(&guid), s.cue_output))
+                });
+                instance_state.cue_sends().remove_stale(stale_cue_sends);
+            }
             // Compartment-specific
             instance_state.set_active_mapping_by_group(
                 MappingCompartment::ControllerMappings,
@@ -456,10 +640,77 @@ impl SessionData {
                 MappingCompartment::MainMappings,
                 self.main.active_mapping_tags.clone(),
             );
+            instance_state.set_disabled_groups(
+                MappingCompartment::ControllerMappings,
+                self.controller.disabled_groups.clone(),
+            );
+            instance_state.set_disabled_groups(
+                MappingCompartment::MainMappings,
+                self.main.disabled_groups.clone(),
+            );
+            instance_state.set_active_layer(
+                MappingCompartment::ControllerMappings,
+                self.controller.active_layer,
+            );
+            instance_state
+                .set_active_layer(MappingCompartment::MainMappings, self.main.active_layer);
         }
+        // Instance hooks
+        session.set_instance_hooks(self.instance_hooks.clone(), false)?;
         Ok(())
     }
 
+    /// Removes the session ID so that applying this data keeps the ID of the target session.
+    pub fn clear_id(&mut self) {
+        self.id = None;
+    }
+
+    /// Replaces the control input, e.g. with one that has been resolved from a device alias.
+    pub fn set_control_input(&mut self, input: ControlInput) {
+        self.control_device_id = convert_control_input(input);
+    }
+
+    /// Replaces the feedback output, e.g. with one that has been resolved from a device alias.
+    pub fn set_feedback_output(&mut self, output: Option<FeedbackOutput>) {
+        self.feedback_device_id = output.map(convert_feedback_output);
+    }
+
+    pub fn clip_matrix_size(&self) -> ClipMatrixSize {
+        self.clip_matrix_size
+    }
+
+    fn clip_slots(&self) -> DeferredClipSlots {
+        let shared_ids: HashSet<_> = self
+            .shared_clip_matrices
+            .keys()
+            .chain(self.shared_clip_matrix_sizes.keys())
+            .collect();
+        DeferredClipSlots {
+            own: ClipMatrixContent {
+                size: self.clip_matrix_size,
+                slots: self.clip_slots.clone(),
+            },
+            shared: shared_ids
+                .into_iter()
+                .map(|id| {
+                    let content = ClipMatrixContent {
+                        size: self
+                            .shared_clip_matrix_sizes
+                            .get(id)
+                            .copied()
+                            .unwrap_or_default(),
+                        slots: self
+                            .shared_clip_matrices
+                            .get(id)
+                            .cloned()
+                            .unwrap_or_default(),
+                    };
+                    (id.clone(), content)
+                })
+                .collect(),
+        }
+    }
+
     pub fn parameters_as_array(&self) -> ParameterArray {
         let mut parameters = ZEROED_PLUGIN_PARAMETERS;
         for (i, p) in self.parameters.iter() {
@@ -552,3 +803,26 @@ pub trait DataToModelConversionContext {
 
     fn non_default_group_id_by_key(&self, key: &GroupKey) -> Option<GroupId>;
 }
+
+/// Loads the clip slots which have been deferred because the clip engine was disabled in safe
+/// mode.
+pub fn load_deferred_clip_slots(session: &Session) -> Result<(), &'static str> {
+    let project = Some(session.context().project_or_current_project());
+    let mut instance_state = session.instance_state().borrow_mut();
+    match instance_state.take_deferred_clip_slots() {
+        None => Ok(()),
+        Some(slots) => load_clip_slots(&mut instance_state, slots, project),
+    }
+}
+
+fn load_clip_slots(
+    instance_state: &mut InstanceState,
+    slots: DeferredClipSlots,
+    project: Option<Project>,
+) -> Result<(), &'static str> {
+    instance_state.load_clip_matrix(slots.own, project)?;
+    for (id, content) in slots.shared {
+        BackboneState::get().load_shared_clip_matrix_if_empty(&id, content, project)?;
+    }
+    Ok(())
+}
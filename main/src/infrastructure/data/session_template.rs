@@ -0,0 +1,166 @@
+use crate::domain::{
+    ControlInput, FeedbackOutput, MidiControlInput, MidiDestination, OscDeviceId, RtpMidiDeviceId,
+};
+use crate::infrastructure::data::SessionData;
+use crate::infrastructure::plugin::App;
+use reaper_high::Reaper;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+
+/// A complete session configuration which can be applied to an existing instance in one go.
+///
+/// In contrast to session data, devices are referenced by alias (their name) because device IDs
+/// differ from machine to machine.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionTemplate {
+    /// Name of the MIDI input, OSC or RTP-MIDI device to be used as control input.
+    ///
+    /// `"<FX input>"` selects the FX input. `None` means the control input contained in the
+    /// session data is kept.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub control_device: Option<String>,
+    /// Name of the MIDI output, OSC or RTP-MIDI device to be used as feedback output.
+    ///
+    /// `"<FX output>"` selects the FX output, `"<None>"` disables feedback. `None` means the
+    /// feedback output contained in the session data is kept.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub feedback_device: Option<String>,
+    /// Number of clip slots the template expects.
+    ///
+    /// This is only checked against the clip matrix size contained in the session data.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clip_slot_count: Option<usize>,
+    /// Both compartments, parameters and everything else, in the same format as the session data
+    /// that ReaLearn saves in the project.
+    pub session: SessionData,
+}
+
+const FX_INPUT_ALIAS: &str = "<FX input>";
+const FX_OUTPUT_ALIAS: &str = "<FX output>";
+const NONE_ALIAS: &str = "<None>";
+
+impl SessionTemplate {
+    /// Loads the template with the given ID from the session template directory.
+    ///
+    /// The ID is the file name without the `.json` extension. Returns `None` if there's no such
+    /// template.
+    pub fn load(id: &str) -> Result<Option<SessionTemplate>, String> {
+        if !is_valid_session_template_id(id) {
+            return Err("invalid session template ID".to_string());
+        }
+        let path = session_template_file_path(id);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let json = fs::read_to_string(path)
+            .map_err(|_| format!("couldn't read session template \"{}\"", id))?;
+        let template = serde_json::from_str(&json)
+            .map_err(|e| format!("session template isn't valid. Details:\n\n{}", e))?;
+        Ok(Some(template))
+    }
+
+    /// Resolves the device aliases and returns session data which can be applied to an instance.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a device alias doesn't match any device or if the clip matrix doesn't
+    /// have the expected size.
+    pub fn into_session_data(self) -> Result<SessionData, Box<dyn Error>> {
+        if let Some(count) = self.clip_slot_count {
+            let size = self.session.clip_matrix_size();
+            if count > size.slot_count() {
+                return Err(format!(
+                    "template needs {} clip slots but its {} clip matrix has only {}",
+                    count,
+                    size,
+                    size.slot_count()
+                )
+                .into());
+            }
+        }
+        let mut session_data = self.session;
+        // Session-specific, must not be taken over from the template
+        session_data.clear_id();
+        if let Some(alias) = &self.control_device {
+            session_data.set_control_input(resolve_control_input(alias)?);
+        }
+        if let Some(alias) = &self.feedback_device {
+            session_data.set_feedback_output(resolve_feedback_output(alias)?);
+        }
+        Ok(session_data)
+    }
+}
+
+/// Makes sure that the ID can't point outside the session template directory.
+///
+/// Colons are rejected because on Windows they can denote a drive (`C:templates`) or an
+/// alternate data stream.
+fn is_valid_session_template_id(id: &str) -> bool {
+    !id.is_empty() && !id.starts_with('.') && !id.contains(|c| matches!(c, '/' | '\\' | ':'))
+}
+
+fn session_template_file_path(id: &str) -> PathBuf {
+    App::realearn_session_template_dir_path().join(format!("{}.json", id))
+}
+
+fn resolve_control_input(alias: &str) -> Result<ControlInput, String> {
+    let name = alias.trim();
+    if name == FX_INPUT_ALIAS {
+        return Ok(ControlInput::Midi(MidiControlInput::FxInput));
+    }
+    if let Some(dev) = Reaper::get()
+        .midi_input_devices()
+        .find(|dev| dev.name().into_inner().to_string_lossy() == name)
+    {
+        return Ok(ControlInput::Midi(MidiControlInput::Device(dev.id())));
+    }
+    if let Some(dev_id) = find_osc_device_by_name(name) {
+        return Ok(ControlInput::Osc(dev_id));
+    }
+    if let Some(dev_id) = find_rtp_midi_device_by_name(name) {
+        return Ok(ControlInput::Midi(MidiControlInput::RtpMidi(dev_id)));
+    }
+    Err(format!("control device \"{}\" not found", name))
+}
+
+fn resolve_feedback_output(alias: &str) -> Result<Option<FeedbackOutput>, String> {
+    let name = alias.trim();
+    if name == NONE_ALIAS {
+        return Ok(None);
+    }
+    if name == FX_OUTPUT_ALIAS {
+        return Ok(Some(FeedbackOutput::Midi(MidiDestination::FxOutput)));
+    }
+    if let Some(dev) = Reaper::get()
+        .midi_output_devices()
+        .find(|dev| dev.name().into_inner().to_string_lossy() == name)
+    {
+        return Ok(Some(FeedbackOutput::Midi(MidiDestination::Device(
+            dev.id(),
+        ))));
+    }
+    if let Some(dev_id) = find_osc_device_by_name(name) {
+        return Ok(Some(FeedbackOutput::Osc(dev_id)));
+    }
+    if let Some(dev_id) = find_rtp_midi_device_by_name(name) {
+        return Ok(Some(FeedbackOutput::Midi(MidiDestination::RtpMidi(dev_id))));
+    }
+    Err(format!("feedback device \"{}\" not found", name))
+}
+
+fn find_osc_device_by_name(name: &str) -> Option<OscDeviceId> {
+    let manager = App::get().osc_device_manager();
+    let manager = manager.borrow();
+    let dev = manager.devices().find(|dev| dev.name() == name)?;
+    Some(*dev.id())
+}
+
+fn find_rtp_midi_device_by_name(name: &str) -> Option<RtpMidiDeviceId> {
+    let manager = App::get().rtp_midi_device_manager();
+    let manager = manager.borrow();
+    let dev = manager.devices().find(|dev| dev.name() == name)?;
+    Some(*dev.id())
+}
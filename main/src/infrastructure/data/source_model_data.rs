@@ -4,10 +4,13 @@ use crate::application::{
 };
 use crate::base::default_util::is_default;
 use crate::base::notification;
-use crate::domain::MappingCompartment;
+use crate::domain::{
+    GameControllerElement, MappingCompartment, MmcCommand, OscArgFilter, OscDeltaMode,
+};
 use crate::infrastructure::data::VirtualControlElementIdData;
 use helgoboss_learn::{DisplayType, MidiClockTransportMessage, OscTypeTag, SourceCharacter};
 use helgoboss_midi::{Channel, U14, U7};
+use reaper_medium::MidiOutputDeviceId;
 use semver::Version;
 use serde::{Deserialize, Serialize};
 use std::convert::TryInto;
@@ -53,6 +56,10 @@ pub struct SourceModelData {
     pub display_id: Option<u8>,
     #[serde(default, skip_serializing_if = "is_default")]
     pub line: Option<u8>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub midi_input_device_id: Option<u8>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub midi_output_device_id: Option<u8>,
     // OSC
     #[serde(default, skip_serializing_if = "is_default")]
     pub osc_address_pattern: String,
@@ -62,6 +69,12 @@ pub struct SourceModelData {
     pub osc_arg_type: OscTypeTag,
     #[serde(default, skip_serializing_if = "is_default")]
     pub osc_arg_is_relative: bool,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub osc_delta_mode: OscDeltaMode,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub osc_feedback_is_relative: bool,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub osc_arg_filters: Vec<OscArgFilter>,
     // Virtual
     #[serde(default, skip_serializing_if = "is_default")]
     pub control_element_type: VirtualControlElementType,
@@ -69,6 +82,16 @@ pub struct SourceModelData {
     pub control_element_index: VirtualControlElementIdData,
     #[serde(default, skip_serializing_if = "is_default")]
     pub reaper_source_type: ReaperSourceType,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub game_controller_index: Option<u32>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub game_controller_element: GameControllerElement,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub instance_message_name: String,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub mmc_command: MmcCommand,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub mmc_device_id: Option<u8>,
 }
 
 impl SourceModelData {
@@ -91,15 +114,25 @@ impl SourceModelData {
             display_type: model.display_type.get(),
             display_id: model.display_id.get(),
             line: model.line.get(),
+            midi_input_device_id: model.midi_input_device.get().map(|id| id.get()),
+            midi_output_device_id: model.midi_output_device.get().map(|id| id.get()),
             osc_address_pattern: model.osc_address_pattern.get_ref().clone(),
             osc_arg_index: model.osc_arg_index.get(),
             osc_arg_type: model.osc_arg_type_tag.get(),
             osc_arg_is_relative: model.osc_arg_is_relative.get(),
+            osc_delta_mode: model.osc_delta_mode.get(),
+            osc_feedback_is_relative: model.osc_feedback_is_relative.get(),
+            osc_arg_filters: model.osc_arg_filters.get_ref().clone(),
             control_element_type: model.control_element_type.get(),
             control_element_index: VirtualControlElementIdData::from_model(
                 model.control_element_id.get(),
             ),
             reaper_source_type: model.reaper_source_type.get(),
+            game_controller_index: model.game_controller_index.get(),
+            game_controller_element: model.game_controller_element.get(),
+            instance_message_name: model.instance_message_name.get_ref().clone(),
+            mmc_command: model.mmc_command.get(),
+            mmc_device_id: model.mmc_device_id.get(),
         }
     }
 
@@ -192,6 +225,14 @@ impl SourceModelData {
         model
             .line
             .set_with_optional_notification(self.line, with_notification);
+        model.midi_input_device.set_with_optional_notification(
+            self.midi_input_device_id.and_then(|id| id.try_into().ok()),
+            with_notification,
+        );
+        model.midi_output_device.set_with_optional_notification(
+            self.midi_output_device_id.map(MidiOutputDeviceId::new),
+            with_notification,
+        );
         model
             .osc_address_pattern
             .set_with_optional_notification(self.osc_address_pattern.clone(), with_notification);
@@ -204,6 +245,15 @@ impl SourceModelData {
         model
             .osc_arg_is_relative
             .set_with_optional_notification(self.osc_arg_is_relative, with_notification);
+        model
+            .osc_delta_mode
+            .set_with_optional_notification(self.osc_delta_mode, with_notification);
+        model
+            .osc_feedback_is_relative
+            .set_with_optional_notification(self.osc_feedback_is_relative, with_notification);
+        model
+            .osc_arg_filters
+            .set_with_optional_notification(self.osc_arg_filters.clone(), with_notification);
         model
             .control_element_type
             .set_with_optional_notification(self.control_element_type, with_notification);
@@ -214,6 +264,21 @@ impl SourceModelData {
         model
             .reaper_source_type
             .set_with_optional_notification(self.reaper_source_type, with_notification);
+        model
+            .game_controller_index
+            .set_with_optional_notification(self.game_controller_index, with_notification);
+        model
+            .game_controller_element
+            .set_with_optional_notification(self.game_controller_element, with_notification);
+        model
+            .instance_message_name
+            .set_with_optional_notification(self.instance_message_name.clone(), with_notification);
+        model
+            .mmc_command
+            .set_with_optional_notification(self.mmc_command, with_notification);
+        model
+            .mmc_device_id
+            .set_with_optional_notification(self.mmc_device_id, with_notification);
     }
 }
 
@@ -1,29 +1,38 @@
 use super::f32_as_u32;
 use super::none_if_minus_one;
 use reaper_high::{BookmarkType, Fx, Guid, Reaper};
+use reaper_medium::MidiOutputDeviceId;
 
 use crate::application::{
     AutomationModeOverrideType, BookmarkAnchorType, FxParameterPropValues, FxPropValues,
-    FxSnapshot, RealearnAutomationMode, RealearnTrackArea, TargetCategory, TargetModel, TargetUnit,
-    TrackPropValues, TrackRoutePropValues, TrackRouteSelectorType, VirtualControlElementType,
+    FxSnapshot, MultiTargetChildModel, RealearnAutomationMode, RealearnInputMonitoringMode,
+    RealearnTrackArea, TargetCategory, TargetModel, TargetUnit, TrackPropValues,
+    TrackRoutePropValues, TrackRouteSelectorType, VirtualControlElementType,
     VirtualFxParameterType, VirtualFxType, VirtualTrackType,
 };
-use crate::base::default_util::{bool_true, is_bool_true, is_default, is_none_or_some_default};
+use crate::base::default_util::{
+    bool_true, is_bool_true, is_default, is_none_or_some_default, is_unit_value_one, unit_value_one,
+};
 use crate::base::notification;
 use crate::domain::{
-    get_fx_chain, ActionInvocationType, AnyOnParameter, Exclusivity, ExtendedProcessorContext,
-    FxDisplayType, GroupKey, MappingCompartment, OscDeviceId, ReaperTargetType, SeekOptions,
-    SendMidiDestination, SoloBehavior, Tag, TouchedParameterType, TrackExclusivity, TrackRouteType,
-    TransportAction, VirtualTrack,
+    get_fx_chain, ActionInvocationType, AnyOnParameter, ArpeggiatorSettings,
+    BookmarkNavigationMode, ChordShape, ClipMatrixId, DualPanComponent, Exclusivity,
+    ExtStateValueFormat, ExtendedProcessorContext, FxDisplayType, GroupEnableMode, GroupKey,
+    LayerId, MappingCompartment, MappingModeSetting, MetronomeParameter, MtcFrameRate, OscDeviceId,
+    ProgramChangePatch, PunchAction, ReaperTargetType, RecordInputKind, SeekOptions,
+    SendMidiDestination, SlotStopQuantization, SoloBehavior, Tag, TimeRangeKind,
+    TimeSelectionAction, TouchedParameterType, TrackExclusivity, TrackRouteType, TransportAction,
+    UndoRedoAction, VirtualTrack, DEFAULT_FX_PARAMETER_BANK_SIZE,
 };
 use crate::infrastructure::data::{
     DataToModelConversionContext, ModelToDataConversionContext, VirtualControlElementIdData,
 };
 use crate::infrastructure::plugin::App;
-use helgoboss_learn::OscTypeTag;
+use helgoboss_learn::{OscTypeTag, UnitValue};
 use semver::Version;
 use serde::{Deserialize, Serialize};
 use std::convert::TryInto;
+use std::time::Duration;
 
 #[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -64,6 +73,9 @@ pub struct TargetModelData {
     // Track selection target (replaced with `track_exclusivity` since v2.4.0)
     #[serde(default, skip_serializing_if = "is_default")]
     pub select_exclusively: Option<bool>,
+    // Track dual pan target
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub dual_pan_component: DualPanComponent,
     // Track solo target (since v2.4.0, also changed default from "ignore routing" to "in place")
     #[serde(default, skip_serializing_if = "is_none_or_some_default")]
     pub solo_behavior: Option<SoloBehavior>,
@@ -73,6 +85,23 @@ pub struct TargetModelData {
     // Transport target
     #[serde(default, skip_serializing_if = "is_default")]
     pub transport_action: TransportAction,
+    // Punch target
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub punch_action: PunchAction,
+    // Undo/redo target
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub undo_redo_action: UndoRedoAction,
+    // Time selection target
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub time_selection_action: TimeSelectionAction,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub time_range_kind: TimeRangeKind,
+    // "Navigate bookmarks" target
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub bookmark_navigation_mode: BookmarkNavigationMode,
+    // Metronome target
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub metronome_parameter: MetronomeParameter,
     // Any-on target
     #[serde(default, skip_serializing_if = "is_default")]
     pub any_on_parameter: AnyOnParameter,
@@ -93,9 +122,15 @@ pub struct TargetModelData {
     // Track show target
     #[serde(default, skip_serializing_if = "is_default")]
     pub track_area: RealearnTrackArea,
+    // Track delete target
+    #[serde(default = "bool_true", skip_serializing_if = "is_bool_true")]
+    pub require_confirmation: bool,
     // Track automation mode target
     #[serde(default, skip_serializing_if = "is_default")]
     pub track_automation_mode: RealearnAutomationMode,
+    // Track input monitoring target
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub track_input_monitoring_mode: RealearnInputMonitoringMode,
     // Automation mode override target
     #[serde(default, skip_serializing_if = "is_default")]
     pub automation_mode_override_type: AutomationModeOverrideType,
@@ -112,6 +147,24 @@ pub struct TargetModelData {
     pub send_midi_destination: SendMidiDestination,
     #[serde(default, skip_serializing_if = "is_default")]
     pub raw_midi_pattern: String,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub midi_chord: Option<ChordShape>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub midi_arpeggiator: Option<ArpeggiatorSettings>,
+    // Send MIDI macro
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub midi_macro_name: String,
+    // Send program change
+    /// `None` means feedback output.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub midi_output_device_id: Option<u8>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub midi_channel: u8,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub program_change_patches: Vec<ProgramChangePatch>,
+    // Send MIDI clock (also uses `midi_output_device_id`)
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub midi_clock_mtc_frame_rate: Option<MtcFrameRate>,
     // Send OSC
     #[serde(default, skip_serializing_if = "is_default")]
     pub osc_address_pattern: String,
@@ -121,12 +174,34 @@ pub struct TargetModelData {
     pub osc_arg_type: OscTypeTag,
     #[serde(default, skip_serializing_if = "is_default")]
     pub osc_dev_id: Option<OscDeviceId>,
+    // Set ext state
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub ext_state_section: String,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub ext_state_key: String,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub ext_state_format: ExtStateValueFormat,
+    // Run script
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub script_path: String,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub clip_matrix_id: Option<ClipMatrixId>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub slot_row: usize,
+    /// The slot column. Called index because before matrices had rows, it was the index.
     #[serde(default, skip_serializing_if = "is_default")]
     pub slot_index: usize,
     #[serde(default, skip_serializing_if = "is_default")]
     pub next_bar: bool,
     #[serde(default, skip_serializing_if = "is_default")]
     pub buffered: bool,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub stop_quantization: Option<SlotStopQuantization>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub cue_output: u32,
+    // Track record input
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub record_input_kind: RecordInputKind,
     #[serde(default = "bool_true", skip_serializing_if = "is_bool_true")]
     pub poll_for_feedback: bool,
     #[serde(default, skip_serializing_if = "is_default")]
@@ -136,7 +211,42 @@ pub struct TargetModelData {
     #[serde(default, skip_serializing_if = "is_default")]
     pub group_id: GroupKey,
     #[serde(default, skip_serializing_if = "is_default")]
+    pub group_enable_mode: GroupEnableMode,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub layer: LayerId,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub bank_offset: u32,
+    /// `None` means the default bank size.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub fx_parameter_bank_size: Option<u32>,
+    #[serde(default, skip_serializing_if = "is_default")]
     pub active_mappings_only: bool,
+    // Mapping snapshots
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub mapping_snapshot_id: String,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub mapping_snapshot_fade_millis: u64,
+    // Adjust mapping mode
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub mapping_key: String,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub mapping_mode_setting: MappingModeSetting,
+    // Multi target
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub multi_target_children: Vec<MultiTargetChildData>,
+    // Invoke instance target
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub instance_message_name: String,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub instance_session_id: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MultiTargetChildData {
+    pub target: TargetModelData,
+    #[serde(default = "unit_value_one", skip_serializing_if = "is_unit_value_one")]
+    pub scale: UnitValue,
 }
 
 impl TargetModelData {
@@ -171,8 +281,15 @@ impl TargetModelData {
             fx_parameter_data: serialize_fx_parameter(model.fx_parameter()),
             select_exclusively: None,
             solo_behavior: Some(model.solo_behavior.get()),
+            dual_pan_component: model.dual_pan_component.get(),
             track_exclusivity: model.track_exclusivity.get(),
             transport_action: model.transport_action.get(),
+            punch_action: model.punch_action.get(),
+            undo_redo_action: model.undo_redo_action.get(),
+            time_selection_action: model.time_selection_action.get(),
+            time_range_kind: model.time_range_kind.get(),
+            bookmark_navigation_mode: model.bookmark_navigation_mode.get(),
+            metronome_parameter: model.metronome_parameter.get(),
             any_on_parameter: model.any_on_parameter.get(),
             control_element_type: model.control_element_type.get(),
             control_element_index: VirtualControlElementIdData::from_model(
@@ -187,27 +304,65 @@ impl TargetModelData {
             },
             seek_options: model.seek_options(),
             track_area: model.track_area.get(),
+            require_confirmation: model.require_confirmation.get(),
             track_automation_mode: model.automation_mode.get(),
+            track_input_monitoring_mode: model.input_monitoring_mode.get(),
             automation_mode_override_type: model.automation_mode_override_type.get(),
             fx_display_type: model.fx_display_type.get(),
             scroll_arrange_view: model.scroll_arrange_view.get(),
             scroll_mixer: model.scroll_mixer.get(),
             send_midi_destination: model.send_midi_destination.get(),
             raw_midi_pattern: model.raw_midi_pattern.get_ref().clone(),
+            midi_chord: model.midi_chord.get(),
+            midi_arpeggiator: model.midi_arpeggiator.get(),
+            midi_macro_name: model.midi_macro_name.get_ref().clone(),
+            midi_output_device_id: model.midi_output_device_id.get().map(|id| id.get()),
+            midi_channel: model.midi_channel.get(),
+            program_change_patches: model.program_change_patches.get_ref().clone(),
+            midi_clock_mtc_frame_rate: model.midi_clock_mtc_frame_rate.get(),
             osc_address_pattern: model.osc_address_pattern.get_ref().clone(),
             osc_arg_index: model.osc_arg_index.get(),
             osc_arg_type: model.osc_arg_type_tag.get(),
             osc_dev_id: model.osc_dev_id.get(),
-            slot_index: model.slot_index.get(),
+            ext_state_section: model.ext_state_section.get_ref().clone(),
+            ext_state_key: model.ext_state_key.get_ref().clone(),
+            ext_state_format: model.ext_state_format.get(),
+            script_path: model.script_path.get_ref().clone(),
+            clip_matrix_id: model.clip_matrix_id.get_ref().clone(),
+            slot_row: model.slot_row.get(),
+            slot_index: model.slot_column.get(),
             next_bar: model.next_bar.get(),
             buffered: model.buffered.get(),
+            stop_quantization: model.stop_quantization.get(),
+            cue_output: model.cue_output.get(),
+            record_input_kind: model.record_input_kind.get(),
             poll_for_feedback: model.poll_for_feedback.get(),
             tags: model.tags.get_ref().clone(),
             exclusivity: model.exclusivity.get(),
             group_id: conversion_context
                 .group_key_by_id(model.group_id.get())
                 .unwrap_or_default(),
+            group_enable_mode: model.group_enable_mode.get(),
+            layer: model.layer.get(),
+            bank_offset: model.bank_offset.get(),
+            fx_parameter_bank_size: Some(model.fx_parameter_bank_size.get())
+                .filter(|s| *s != DEFAULT_FX_PARAMETER_BANK_SIZE),
             active_mappings_only: model.active_mappings_only.get(),
+            mapping_snapshot_id: model.mapping_snapshot_id.get_ref().clone(),
+            mapping_snapshot_fade_millis: model.mapping_snapshot_fade_time.get().as_millis() as _,
+            mapping_key: model.mapping_key.get_ref().clone(),
+            mapping_mode_setting: model.mapping_mode_setting.get(),
+            multi_target_children: model
+                .multi_target_children
+                .get_ref()
+                .iter()
+                .map(|c| MultiTargetChildData {
+                    target: TargetModelData::from_model(&c.target, conversion_context),
+                    scale: c.scale,
+                })
+                .collect(),
+            instance_message_name: model.instance_message_name.get_ref().clone(),
+            instance_session_id: model.instance_session_id.get_ref().clone(),
         }
     }
 
@@ -340,9 +495,30 @@ impl TargetModelData {
         model
             .solo_behavior
             .set_with_optional_notification(solo_behavior, with_notification);
+        model
+            .dual_pan_component
+            .set_with_optional_notification(self.dual_pan_component, with_notification);
         model
             .transport_action
             .set_with_optional_notification(self.transport_action, with_notification);
+        model
+            .punch_action
+            .set_with_optional_notification(self.punch_action, with_notification);
+        model
+            .undo_redo_action
+            .set_with_optional_notification(self.undo_redo_action, with_notification);
+        model
+            .time_selection_action
+            .set_with_optional_notification(self.time_selection_action, with_notification);
+        model
+            .time_range_kind
+            .set_with_optional_notification(self.time_range_kind, with_notification);
+        model
+            .bookmark_navigation_mode
+            .set_with_optional_notification(self.bookmark_navigation_mode, with_notification);
+        model
+            .metronome_parameter
+            .set_with_optional_notification(self.metronome_parameter, with_notification);
         model
             .any_on_parameter
             .set_with_optional_notification(self.any_on_parameter, with_notification);
@@ -377,9 +553,15 @@ impl TargetModelData {
         model
             .track_area
             .set_with_optional_notification(self.track_area, with_notification);
+        model
+            .require_confirmation
+            .set_with_optional_notification(self.require_confirmation, with_notification);
         model
             .automation_mode
             .set_with_optional_notification(self.track_automation_mode, with_notification);
+        model
+            .input_monitoring_mode
+            .set_with_optional_notification(self.track_input_monitoring_mode, with_notification);
         model
             .automation_mode_override_type
             .set_with_optional_notification(self.automation_mode_override_type, with_notification);
@@ -412,6 +594,28 @@ impl TargetModelData {
         model
             .raw_midi_pattern
             .set_with_optional_notification(self.raw_midi_pattern.clone(), with_notification);
+        model
+            .midi_chord
+            .set_with_optional_notification(self.midi_chord, with_notification);
+        model
+            .midi_arpeggiator
+            .set_with_optional_notification(self.midi_arpeggiator, with_notification);
+        model
+            .midi_macro_name
+            .set_with_optional_notification(self.midi_macro_name.clone(), with_notification);
+        model.midi_output_device_id.set_with_optional_notification(
+            self.midi_output_device_id.map(MidiOutputDeviceId::new),
+            with_notification,
+        );
+        model
+            .midi_channel
+            .set_with_optional_notification(self.midi_channel, with_notification);
+        model
+            .program_change_patches
+            .set_with_optional_notification(self.program_change_patches.clone(), with_notification);
+        model
+            .midi_clock_mtc_frame_rate
+            .set_with_optional_notification(self.midi_clock_mtc_frame_rate, with_notification);
         model
             .osc_address_pattern
             .set_with_optional_notification(self.osc_address_pattern.clone(), with_notification);
@@ -425,7 +629,25 @@ impl TargetModelData {
             .osc_dev_id
             .set_with_optional_notification(self.osc_dev_id, with_notification);
         model
-            .slot_index
+            .ext_state_section
+            .set_with_optional_notification(self.ext_state_section.clone(), with_notification);
+        model
+            .ext_state_key
+            .set_with_optional_notification(self.ext_state_key.clone(), with_notification);
+        model
+            .ext_state_format
+            .set_with_optional_notification(self.ext_state_format, with_notification);
+        model
+            .script_path
+            .set_with_optional_notification(self.script_path.clone(), with_notification);
+        model
+            .clip_matrix_id
+            .set_with_optional_notification(self.clip_matrix_id.clone(), with_notification);
+        model
+            .slot_row
+            .set_with_optional_notification(self.slot_row, with_notification);
+        model
+            .slot_column
             .set_with_optional_notification(self.slot_index, with_notification);
         model
             .next_bar
@@ -433,6 +655,15 @@ impl TargetModelData {
         model
             .buffered
             .set_with_optional_notification(self.buffered, with_notification);
+        model
+            .stop_quantization
+            .set_with_optional_notification(self.stop_quantization, with_notification);
+        model
+            .cue_output
+            .set_with_optional_notification(self.cue_output, with_notification);
+        model
+            .record_input_kind
+            .set_with_optional_notification(self.record_input_kind, with_notification);
         model
             .poll_for_feedback
             .set_with_optional_notification(self.poll_for_feedback, with_notification);
@@ -448,9 +679,63 @@ impl TargetModelData {
         model
             .group_id
             .set_with_optional_notification(group_id, with_notification);
+        model
+            .group_enable_mode
+            .set_with_optional_notification(self.group_enable_mode, with_notification);
+        model
+            .layer
+            .set_with_optional_notification(self.layer, with_notification);
+        model
+            .bank_offset
+            .set_with_optional_notification(self.bank_offset, with_notification);
+        model.fx_parameter_bank_size.set_with_optional_notification(
+            self.fx_parameter_bank_size
+                .unwrap_or(DEFAULT_FX_PARAMETER_BANK_SIZE),
+            with_notification,
+        );
         model
             .active_mappings_only
             .set_with_optional_notification(self.active_mappings_only, with_notification);
+        model
+            .mapping_snapshot_id
+            .set_with_optional_notification(self.mapping_snapshot_id.clone(), with_notification);
+        model
+            .mapping_snapshot_fade_time
+            .set_with_optional_notification(
+                Duration::from_millis(self.mapping_snapshot_fade_millis),
+                with_notification,
+            );
+        model
+            .mapping_key
+            .set_with_optional_notification(self.mapping_key.clone(), with_notification);
+        model
+            .mapping_mode_setting
+            .set_with_optional_notification(self.mapping_mode_setting, with_notification);
+        let multi_target_children = self
+            .multi_target_children
+            .iter()
+            .map(|c| {
+                let mut child_model = TargetModel::default();
+                c.target.apply_to_model_flexible(
+                    &mut child_model,
+                    context,
+                    preset_version,
+                    false,
+                    compartment,
+                    conversion_context,
+                );
+                MultiTargetChildModel::new(child_model, c.scale)
+            })
+            .collect();
+        model
+            .multi_target_children
+            .set_with_optional_notification(multi_target_children, with_notification);
+        model
+            .instance_message_name
+            .set_with_optional_notification(self.instance_message_name.clone(), with_notification);
+        model
+            .instance_session_id
+            .set_with_optional_notification(self.instance_session_id.clone(), with_notification);
     }
 }
 
@@ -5,20 +5,26 @@ use crate::base::default_util::is_default;
 use crate::base::{notification, Global};
 use crate::domain::{
     ActionInvokedEvent, AdditionalFeedbackEvent, BackboneState, EnableInstancesArgs, Exclusivity,
-    FeedbackAudioHookTask, Garbage, GarbageBin, GroupId, InputDescriptor, InstanceContainer,
-    InstanceId, InstanceOrchestrationEvent, MainProcessor, MappingCompartment, MessageCaptureEvent,
-    MessageCaptureResult, MidiScanResult, NormalAudioHookTask, OscDeviceId, OscFeedbackProcessor,
-    OscFeedbackTask, OscScanResult, RealTimeSender, RealearnAudioHook,
-    RealearnControlSurfaceMainTask, RealearnControlSurfaceMiddleware,
-    RealearnControlSurfaceServerTask, RealearnTarget, RealearnTargetContext, ReaperTarget,
-    SharedRealTimeProcessor, Tag,
+    FeedbackAudioHookTask, FeedbackMaxRateMap, Garbage, GarbageBin, GroupId, InputDescriptor,
+    InstanceContainer, InstanceId, InstanceMessageRecipients, InstanceOrchestrationEvent,
+    MainProcessor, MappingCompartment, MessageCaptureEvent, MessageCaptureResult, MidiScanResult,
+    NormalAudioHookTask, OscDeviceId, OscFeedbackProcessor, OscFeedbackTask, OscScanResult,
+    RealTimeSender, RealearnAudioHook, RealearnControlSurfaceMainTask,
+    RealearnControlSurfaceMiddleware, RealearnControlSurfaceServerTask, RealearnTarget,
+    RealearnTargetContext, ReaperTarget, RtpMidiDeviceId, RtpMidiEvent, RtpMidiProcessor,
+    SendInstanceMessageArgs, SharedRealTimeProcessor, SysexChunkingSettings,
+    SysexChunkingSettingsMap, Tag,
 };
 use crate::infrastructure::data::{
-    FileBasedControllerPresetManager, FileBasedMainPresetManager, FileBasedPresetLinkManager,
-    OscDevice, OscDeviceManager, SharedControllerPresetManager, SharedMainPresetManager,
-    SharedOscDeviceManager, SharedPresetLinkManager,
+    load_deferred_clip_slots, FileBasedControllerPresetManager, FileBasedMainPresetManager,
+    FileBasedPresetLinkManager, OscDevice, OscDeviceManager, RtpMidiDevice, RtpMidiDeviceManager,
+    SharedControllerPresetManager, SharedMainPresetManager, SharedOscDeviceManager,
+    SharedPresetLinkManager, SharedRtpMidiDeviceManager,
 };
 use crate::infrastructure::plugin::debug_util;
+use crate::infrastructure::plugin::{
+    RealearnPluginParameters, SafeMode, Subsystem, SAFE_MODE_ENV_VAR,
+};
 use crate::infrastructure::server;
 use crate::infrastructure::server::{RealearnServer, SharedRealearnServer, COMPANION_WEB_APP_URL};
 use crate::infrastructure::ui::MessagePanel;
@@ -26,8 +32,8 @@ use crate::infrastructure::ui::MessagePanel;
 use reaper_high::{ActionKind, CrashInfo, Fx, MiddlewareControlSurface, Project, Reaper, Track};
 use reaper_low::{PluginContext, Swell};
 use reaper_medium::{
-    ActionValueChange, CommandId, HookPostCommand, HookPostCommand2, ReaProject,
-    RegistrationHandle, SectionContext, WindowContext,
+    ActionValueChange, CommandId, HookPostCommand, HookPostCommand2, MidiOutputDeviceId,
+    ReaProject, RegistrationHandle, SectionContext, WindowContext,
 };
 use reaper_rx::{ActionRxHookPostCommand, ActionRxHookPostCommand2};
 use rxrust::prelude::*;
@@ -35,10 +41,12 @@ use semver::Version;
 use serde::{Deserialize, Serialize};
 use slog::{debug, Drain, Logger};
 use std::cell::{Ref, RefCell};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::PathBuf;
 use std::rc::Rc;
+use std::sync;
+use std::time::Duration;
 use swell_ui::{SharedView, View};
 use url::Url;
 
@@ -56,6 +64,8 @@ const GARBAGE_QUEUE_SIZE: usize = 100_000;
 const INSTANCE_ORCHESTRATION_EVENT_QUEUE_SIZE: usize = 5000;
 const NORMAL_AUDIO_HOOK_TASK_QUEUE_SIZE: usize = 2000;
 const OSC_OUTGOING_QUEUE_SIZE: usize = 1000;
+const RTP_MIDI_INCOMING_QUEUE_SIZE: usize = 1000;
+const RTP_MIDI_OUTGOING_QUEUE_SIZE: usize = 1000;
 
 make_available_globally_in_main_thread!(App);
 
@@ -75,8 +85,10 @@ pub struct App {
     main_preset_manager: SharedMainPresetManager,
     preset_link_manager: SharedPresetLinkManager,
     osc_device_manager: SharedOscDeviceManager,
+    rtp_midi_device_manager: SharedRtpMidiDeviceManager,
     server: SharedRealearnServer,
     config: RefCell<AppConfig>,
+    safe_mode: RefCell<SafeMode>,
     changed_subject: RefCell<LocalSubject<'static, (), ()>>,
     list_of_recently_focused_fx: Rc<RefCell<ListOfRecentlyFocusedFx>>,
     party_is_over_subject: LocalSubject<'static, (), ()>,
@@ -87,9 +99,11 @@ pub struct App {
     instance_orchestration_event_sender: crossbeam_channel::Sender<InstanceOrchestrationEvent>,
     audio_hook_task_sender: crossbeam_channel::Sender<NormalAudioHookTask>,
     sessions: RefCell<Vec<WeakSession>>,
+    plugin_parameters: RefCell<HashMap<InstanceId, sync::Weak<RealearnPluginParameters>>>,
     sessions_changed_subject: RefCell<LocalSubject<'static, (), ()>>,
     message_panel: SharedView<MessagePanel>,
     osc_feedback_processor: Rc<RefCell<OscFeedbackProcessor>>,
+    rtp_midi_processor: Rc<RefCell<RtpMidiProcessor>>,
 }
 
 #[derive(Debug)]
@@ -128,6 +142,8 @@ struct UninitializedState {
     instance_orchestration_event_receiver: crossbeam_channel::Receiver<InstanceOrchestrationEvent>,
     normal_audio_hook_task_receiver: crossbeam_channel::Receiver<NormalAudioHookTask>,
     feedback_audio_hook_task_receiver: crossbeam_channel::Receiver<FeedbackAudioHookTask>,
+    rtp_midi_incoming_receiver: crossbeam_channel::Receiver<RtpMidiEvent>,
+    rtp_midi_outgoing_sender: crossbeam_channel::Sender<RtpMidiEvent>,
 }
 
 #[derive(Debug)]
@@ -182,7 +198,12 @@ impl App {
         &VALUE
     }
 
-    fn new(config: AppConfig) -> App {
+    fn new(mut config: AppConfig) -> App {
+        if config.ensure_server_auth_token() {
+            if let Err(e) = config.save() {
+                debug!(App::logger(), "{}", e);
+            }
+        }
         let (main_sender, main_receiver) =
             crossbeam_channel::bounded(CONTROL_SURFACE_MAIN_TASK_QUEUE_SIZE);
         let (server_sender, server_receiver) =
@@ -197,6 +218,10 @@ impl App {
             crossbeam_channel::bounded(FEEDBACK_AUDIO_HOOK_TASK_QUEUE_SIZE);
         let (audio_sender, audio_receiver) =
             crossbeam_channel::bounded(NORMAL_AUDIO_HOOK_TASK_QUEUE_SIZE);
+        let (rtp_midi_incoming_sender, rtp_midi_incoming_receiver) =
+            crossbeam_channel::bounded(RTP_MIDI_INCOMING_QUEUE_SIZE);
+        let (rtp_midi_outgoing_sender, rtp_midi_outgoing_receiver) =
+            crossbeam_channel::bounded(RTP_MIDI_OUTGOING_QUEUE_SIZE);
         let uninitialized_state = UninitializedState {
             control_surface_main_task_receiver: main_receiver,
             control_surface_server_task_receiver: server_receiver,
@@ -204,6 +229,8 @@ impl App {
             instance_orchestration_event_receiver,
             normal_audio_hook_task_receiver: audio_receiver,
             feedback_audio_hook_task_receiver,
+            rtp_midi_incoming_receiver,
+            rtp_midi_outgoing_sender,
         };
         App {
             state: RefCell::new(AppState::Uninitialized(uninitialized_state)),
@@ -221,12 +248,22 @@ impl App {
             osc_device_manager: Rc::new(RefCell::new(OscDeviceManager::new(
                 App::realearn_osc_device_config_file_path(),
             ))),
+            rtp_midi_device_manager: Rc::new(RefCell::new(RtpMidiDeviceManager::new(
+                App::realearn_rtp_midi_device_config_file_path(),
+            ))),
             server: Rc::new(RefCell::new(RealearnServer::new(
                 config.main.server_http_port,
                 config.main.server_https_port,
+                config.main.server_port_fallback_count,
                 App::server_resource_dir_path().join("certificates"),
                 server_sender,
+                App::realearn_webhook_config_file_path(),
+                config.server_auth_token().map(|t| t.to_string()),
             ))),
+            safe_mode: RefCell::new(SafeMode::determine(
+                std::env::var(SAFE_MODE_ENV_VAR).ok().as_deref(),
+                config.safe_mode_enabled_subsystems(),
+            )),
             config: RefCell::new(config),
             changed_subject: Default::default(),
             list_of_recently_focused_fx: Default::default(),
@@ -238,11 +275,16 @@ impl App {
             instance_orchestration_event_sender,
             audio_hook_task_sender: audio_sender,
             sessions: Default::default(),
+            plugin_parameters: Default::default(),
             sessions_changed_subject: Default::default(),
             message_panel: Default::default(),
             osc_feedback_processor: Rc::new(RefCell::new(OscFeedbackProcessor::new(
                 osc_feedback_task_receiver,
             ))),
+            rtp_midi_processor: Rc::new(RefCell::new(RtpMidiProcessor::new(
+                rtp_midi_incoming_sender,
+                rtp_midi_outgoing_receiver,
+            ))),
         }
     }
 
@@ -276,11 +318,16 @@ impl App {
         server::keep_informing_clients_about_sessions();
         debug_util::register_resolve_symbols_action();
         crate::infrastructure::test::register_test_action();
+        self.log_safe_mode();
         let list_of_recently_focused_fx = self.list_of_recently_focused_fx.clone();
         self.osc_device_manager
             .borrow()
             .changed()
             .subscribe(|_| App::get().reconnect_osc_devices());
+        self.rtp_midi_device_manager
+            .borrow()
+            .changed()
+            .subscribe(|_| App::get().rebind_rtp_midi_sessions());
         Global::control_surface_rx()
             .fx_focused()
             .take_until(self.party_is_over())
@@ -299,6 +346,8 @@ impl App {
         let audio_hook = RealearnAudioHook::new(
             uninit_state.normal_audio_hook_task_receiver,
             uninit_state.feedback_audio_hook_task_receiver,
+            uninit_state.rtp_midi_incoming_receiver,
+            uninit_state.rtp_midi_outgoing_sender,
             Self::garbage_bin().clone(),
         );
         let sleeping_state = SleepingState {
@@ -308,7 +357,27 @@ impl App {
         self.state.replace(AppState::Sleeping(sleeping_state));
     }
 
+    /// Resolves the device names in the config to device IDs (which REAPER keeps stable for each
+    /// device name) and hands the result to the audio hook.
+    fn sync_sysex_chunking_settings_to_audio_hook(&self) {
+        let settings = self.config.borrow().sysex_chunking_settings();
+        self.audio_hook_task_sender
+            .try_send(NormalAudioHookTask::SetSysexChunkingSettings(settings))
+            .unwrap();
+    }
+
+    /// Same as with sys-ex chunking settings.
+    fn sync_feedback_max_rates_to_audio_hook(&self) {
+        let max_rates = self.config.borrow().feedback_max_rates();
+        self.audio_hook_task_sender
+            .try_send(NormalAudioHookTask::SetFeedbackMaxRates(max_rates))
+            .unwrap();
+    }
+
     fn reconnect_osc_devices(&self) {
+        if !self.subsystem_is_enabled(Subsystem::OscEngine) {
+            return;
+        }
         // Control devices
         self.temporarily_reclaim_control_surface_ownership(|control_surface| {
             let middleware = control_surface.middleware_mut();
@@ -335,6 +404,22 @@ impl App {
         }
     }
 
+    fn rebind_rtp_midi_sessions(&self) {
+        // Only relevant while awake. Otherwise the sessions are bound when waking up.
+        if !matches!(&*self.state.borrow(), AppState::Awake(_)) {
+            return;
+        }
+        let mut processor = self.rtp_midi_processor.borrow_mut();
+        // Unbind
+        processor.stop();
+        // Rebind
+        let sessions = self
+            .rtp_midi_device_manager
+            .borrow_mut()
+            .bind_all_enabled_sessions();
+        processor.start(sessions);
+    }
+
     // Executed whenever the first ReaLearn instance is loaded.
     pub fn wake_up(&self) {
         let prev_state = self.state.replace(AppState::WakingUp);
@@ -343,10 +428,9 @@ impl App {
         } else {
             panic!("App was not sleeping");
         };
-        if self.config.borrow().server_is_enabled() {
-            self.server()
-                .borrow_mut()
-                .start()
+        if self.config.borrow().server_is_enabled() && self.subsystem_is_enabled(Subsystem::Server)
+        {
+            self.start_server()
                 .unwrap_or_else(warn_about_failed_server_start);
         }
         let mut session = Reaper::get().medium_session();
@@ -369,19 +453,31 @@ impl App {
         let audio_hook_handle = session
             .audio_reg_hardware_hook_add(sleeping_state.audio_hook)
             .expect("couldn't register ReaLearn audio hook");
+        self.sync_sysex_chunking_settings_to_audio_hook();
+        self.sync_feedback_max_rates_to_audio_hook();
         // OSC devices
-        let osc_input_devices = self
-            .osc_device_manager
-            .borrow_mut()
-            .connect_all_enabled_inputs();
-        let osc_output_devices = self
-            .osc_device_manager
-            .borrow_mut()
-            .connect_all_enabled_outputs();
+        let (osc_input_devices, osc_output_devices) =
+            if self.subsystem_is_enabled(Subsystem::OscEngine) {
+                let mut osc_device_manager = self.osc_device_manager.borrow_mut();
+                (
+                    osc_device_manager.connect_all_enabled_inputs(),
+                    osc_device_manager.connect_all_enabled_outputs(),
+                )
+            } else {
+                (vec![], vec![])
+            };
         // OSC processor
         self.osc_feedback_processor
             .borrow_mut()
             .start(osc_output_devices);
+        // RTP-MIDI processor
+        let rtp_midi_sessions = self
+            .rtp_midi_device_manager
+            .borrow_mut()
+            .bind_all_enabled_sessions();
+        self.rtp_midi_processor
+            .borrow_mut()
+            .start(rtp_midi_sessions);
         // Control surface
         let middleware = sleeping_state.control_surface.middleware_mut();
         middleware.set_osc_input_devices(osc_input_devices);
@@ -423,6 +519,8 @@ impl App {
         let middleware = control_surface.middleware_mut();
         middleware.clear_osc_input_devices();
         self.osc_feedback_processor.borrow_mut().stop();
+        // Close RTP-MIDI sessions
+        self.rtp_midi_processor.borrow_mut().stop();
         // Actions
         session.plugin_register_remove_hook_post_command_2::<Self>();
         session.plugin_register_remove_hook_post_command_2::<ActionRxHookPostCommand2<Global>>();
@@ -622,6 +720,34 @@ impl App {
             .unwrap();
     }
 
+    pub fn rtp_midi_device_manager(&self) -> SharedRtpMidiDeviceManager {
+        self.rtp_midi_device_manager.clone()
+    }
+
+    pub fn do_with_rtp_midi_device(
+        &self,
+        dev_id: RtpMidiDeviceId,
+        f: impl FnOnce(&mut RtpMidiDevice),
+    ) {
+        let mut dev = App::get()
+            .rtp_midi_device_manager()
+            .borrow()
+            .find_device_by_id(&dev_id)
+            .unwrap()
+            .clone();
+        f(&mut dev);
+        App::get()
+            .rtp_midi_device_manager()
+            .borrow_mut()
+            .update_device(dev)
+            .unwrap();
+    }
+
+    /// Returns the names of the network MIDI peers currently connected to the given session.
+    pub fn rtp_midi_peer_names(&self, dev_id: RtpMidiDeviceId) -> Vec<String> {
+        self.rtp_midi_processor.borrow().peer_names(dev_id)
+    }
+
     pub fn server(&self) -> &SharedRealearnServer {
         &self.server
     }
@@ -630,8 +756,24 @@ impl App {
         self.config.borrow()
     }
 
+    /// Starts the server and records the ports it actually uses in the config.
+    fn start_server(&self) -> Result<(), String> {
+        let (http_port, https_port) = {
+            let mut server = self.server.borrow_mut();
+            server.start()?;
+            (server.http_port(), server.https_port())
+        };
+        self.config
+            .borrow_mut()
+            .set_effective_server_ports(http_port, https_port);
+        Ok(())
+    }
+
     pub fn start_server_persistently(&self) -> Result<(), String> {
-        self.server.borrow_mut().start()?;
+        self.start_server()?;
+        self.safe_mode
+            .borrow_mut()
+            .enable_subsystem(Subsystem::Server);
         self.change_config(AppConfig::enable_server);
         Ok(())
     }
@@ -653,19 +795,89 @@ impl App {
         - State: {:#?}\n\
         - Session count: {}\n\
         - Module base address: {:?}\n\
+        - Safe mode: {}\n\
         ",
             self.state.borrow(),
             self.sessions.borrow().len(),
             determine_module_base_address().map(|addr| format!("0x{:x}", addr)),
+            self.safe_mode_label(),
         );
         Reaper::get().show_console_msg(msg);
         self.server.borrow().log_debug_info(session_id);
-        self.controller_preset_manager.borrow().log_debug_info();
+        {
+            let controller_preset_manager = self.controller_preset_manager.borrow();
+            controller_preset_manager.log_debug_info();
+            controller_preset_manager.log_orphaned_custom_data_refs();
+        }
         self.control_surface_main_task_sender
             .try_send(RealearnControlSurfaceMainTask::LogDebugInfo)
             .unwrap();
     }
 
+    pub fn safe_mode(&self) -> Ref<SafeMode> {
+        self.safe_mode.borrow()
+    }
+
+    pub fn subsystem_is_enabled(&self, subsystem: Subsystem) -> bool {
+        self.safe_mode.borrow().subsystem_is_enabled(subsystem)
+    }
+
+    /// Enables a subsystem which has been disabled by starting in safe mode.
+    pub fn enable_subsystem(&self, subsystem: Subsystem) {
+        if !self.safe_mode.borrow_mut().enable_subsystem(subsystem) {
+            return;
+        }
+        debug!(
+            App::logger(),
+            "Enabled subsystem in safe mode: {}", subsystem
+        );
+        let is_awake = matches!(&*self.state.borrow(), AppState::Awake(_));
+        match subsystem {
+            Subsystem::Server => {
+                if is_awake && self.config.borrow().server_is_enabled() {
+                    self.start_server()
+                        .unwrap_or_else(warn_about_failed_server_start);
+                }
+            }
+            Subsystem::OscEngine => {
+                if is_awake {
+                    self.reconnect_osc_devices();
+                }
+            }
+            Subsystem::ClipEngine => {
+                for session in self.sessions() {
+                    let session = session.borrow();
+                    if let Err(e) = load_deferred_clip_slots(&session) {
+                        notification::warn(format!("Couldn't load clip slots: {}", e));
+                    }
+                }
+            }
+        }
+        self.notify_changed();
+    }
+
+    fn safe_mode_label(&self) -> String {
+        let safe_mode = self.safe_mode.borrow();
+        if !safe_mode.is_active() {
+            return "Off".to_string();
+        }
+        let disabled: Vec<_> = safe_mode
+            .disabled_subsystems()
+            .map(|s| s.to_string())
+            .collect();
+        if disabled.is_empty() {
+            "On (all subsystems enabled)".to_string()
+        } else {
+            format!("On ({} disabled)", disabled.join(", "))
+        }
+    }
+
+    fn log_safe_mode(&self) {
+        if self.safe_mode.borrow().is_active() {
+            debug!(App::logger(), "Safe mode: {}", self.safe_mode_label());
+        }
+    }
+
     pub fn changed(&self) -> impl LocalObservable<'static, Item = (), Err = ()> + 'static {
         self.changed_subject.borrow().clone()
     }
@@ -695,6 +907,10 @@ impl App {
         Self::realearn_data_dir_path().join("presets")
     }
 
+    pub fn realearn_session_template_dir_path() -> PathBuf {
+        Self::realearn_data_dir_path().join("session-templates")
+    }
+
     pub fn realearn_auto_load_configs_dir_path() -> PathBuf {
         Self::realearn_data_dir_path().join("auto-load-configs")
     }
@@ -703,6 +919,14 @@ impl App {
         App::realearn_resource_dir_path().join("osc.json")
     }
 
+    pub fn realearn_rtp_midi_device_config_file_path() -> PathBuf {
+        App::realearn_resource_dir_path().join("rtp-midi.json")
+    }
+
+    pub fn realearn_webhook_config_file_path() -> PathBuf {
+        App::realearn_resource_dir_path().join("webhooks.json")
+    }
+
     // We need this to be static because we need it at plugin construction time, so we don't have
     // REAPER API access yet. App needs REAPER API to be constructed (e.g. in order to
     // know where's the resource directory that contains the app configuration).
@@ -739,6 +963,15 @@ impl App {
         self.find_session_by_id(session_id).is_some()
     }
 
+    /// Returns all sessions which are still alive.
+    pub fn sessions(&self) -> Vec<SharedSession> {
+        self.sessions
+            .borrow()
+            .iter()
+            .filter_map(|s| s.upgrade())
+            .collect()
+    }
+
     pub fn find_session_by_id(&self, session_id: &str) -> Option<SharedSession> {
         self.find_session(|session| {
             let session = session.borrow();
@@ -753,6 +986,30 @@ impl App {
         })
     }
 
+    /// The plug-in parameters are needed for creating and applying complete session data.
+    pub fn find_plugin_parameters_by_session_id(
+        &self,
+        session_id: &str,
+    ) -> Option<sync::Arc<RealearnPluginParameters>> {
+        let session = self.find_session_by_id(session_id)?;
+        let instance_id = *session.borrow().instance_id();
+        self.plugin_parameters.borrow().get(&instance_id)?.upgrade()
+    }
+
+    pub fn register_plugin_parameters(
+        &self,
+        instance_id: InstanceId,
+        plugin_parameters: sync::Weak<RealearnPluginParameters>,
+    ) {
+        self.plugin_parameters
+            .borrow_mut()
+            .insert(instance_id, plugin_parameters);
+    }
+
+    pub fn unregister_plugin_parameters(&self, instance_id: &InstanceId) {
+        self.plugin_parameters.borrow_mut().remove(instance_id);
+    }
+
     pub fn register_session(&self, session: WeakSession) {
         let mut sessions = self.sessions.borrow_mut();
         debug!(Reaper::get().logger(), "Registering new session...");
@@ -1275,6 +1532,21 @@ impl Drop for App {
 #[serde(default)]
 pub struct AppConfig {
     main: MainConfig,
+    /// HTTP and HTTPS port actually used by the running server.
+    ///
+    /// Differs from the configured ones if the server had to fall back to other ports. Not saved.
+    #[serde(skip)]
+    effective_server_ports: Option<(u16, u16)>,
+    /// Maximum sys-ex message size in bytes, keyed by MIDI output device name (as displayed by
+    /// REAPER, so device aliases are respected).
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    sysex_max_size: HashMap<String, u32>,
+    /// Delay between sys-ex chunks in milliseconds, keyed by MIDI output device name.
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    sysex_chunk_delay: HashMap<String, u64>,
+    /// Maximum number of feedback messages per second, keyed by MIDI output device name.
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    feedback_max_rate: HashMap<String, u32>,
 }
 
 impl AppConfig {
@@ -1306,10 +1578,96 @@ impl AppConfig {
         self.main.server_enabled > 0
     }
 
+    /// Returns the HTTP port actually used by the server (or the configured one if not started).
+    pub fn server_http_port(&self) -> u16 {
+        self.effective_server_ports
+            .map(|(http, _)| http)
+            .unwrap_or(self.main.server_http_port)
+    }
+
+    /// Returns the HTTPS port actually used by the server (or the configured one if not started).
+    pub fn server_https_port(&self) -> u16 {
+        self.effective_server_ports
+            .map(|(_, https)| https)
+            .unwrap_or(self.main.server_https_port)
+    }
+
+    fn set_effective_server_ports(&mut self, http_port: u16, https_port: u16) {
+        self.effective_server_ports = Some((http_port, https_port));
+    }
+
+    /// Returns the secret which clients must present if server authentication is enabled.
+    pub fn server_auth_token(&self) -> Option<&str> {
+        if self.main.server_auth_enabled > 0 && !self.main.server_auth_token.is_empty() {
+            Some(&self.main.server_auth_token)
+        } else {
+            None
+        }
+    }
+
+    /// Generates a random secret for this installation if server authentication is enabled but
+    /// no secret has been set yet.
+    ///
+    /// Returns `true` if the config has been changed.
+    pub fn ensure_server_auth_token(&mut self) -> bool {
+        if self.main.server_auth_enabled == 0 || !self.main.server_auth_token.is_empty() {
+            return false;
+        }
+        self.main.server_auth_token = nanoid::nanoid!(32);
+        true
+    }
+
+    /// Returns the comma-separated subsystems which should be enabled if safe mode is enabled
+    /// in the config.
+    pub fn safe_mode_enabled_subsystems(&self) -> Option<&str> {
+        if self.main.safe_mode > 0 {
+            Some(&self.main.safe_mode_enabled_subsystems)
+        } else {
+            None
+        }
+    }
+
     pub fn companion_web_app_url(&self) -> url::Url {
         Url::parse(&self.main.companion_web_app_url).expect("invalid companion web app URL")
     }
 
+    pub fn sysex_chunking_settings(&self) -> SysexChunkingSettingsMap {
+        if self.sysex_max_size.is_empty() {
+            return Default::default();
+        }
+        Reaper::get()
+            .midi_output_devices()
+            .filter_map(|dev| {
+                let name = dev.name().into_inner().to_string_lossy().to_string();
+                let max_size = *self.sysex_max_size.get(&name)?;
+                let settings = SysexChunkingSettings {
+                    max_size: max_size as usize,
+                    chunk_delay: Duration::from_millis(
+                        self.sysex_chunk_delay
+                            .get(&name)
+                            .copied()
+                            .unwrap_or_default(),
+                    ),
+                };
+                Some((dev.id(), settings))
+            })
+            .collect()
+    }
+
+    pub fn feedback_max_rates(&self) -> FeedbackMaxRateMap<MidiOutputDeviceId> {
+        if self.feedback_max_rate.is_empty() {
+            return Default::default();
+        }
+        Reaper::get()
+            .midi_output_devices()
+            .filter_map(|dev| {
+                let name = dev.name().into_inner().to_string_lossy().to_string();
+                let max_rate = *self.feedback_max_rate.get(&name)?;
+                Some((dev.id(), max_rate))
+            })
+            .collect()
+    }
+
     fn config_file_path() -> PathBuf {
         App::realearn_resource_dir_path().join("realearn.ini")
     }
@@ -1329,11 +1687,23 @@ struct MainConfig {
         skip_serializing_if = "is_default_server_https_port"
     )]
     server_https_port: u16,
+    /// Number of subsequent ports to try if the configured HTTP or HTTPS port is taken.
+    #[serde(default, skip_serializing_if = "is_default")]
+    server_port_fallback_count: u16,
     #[serde(
         default = "default_companion_web_app_url",
         skip_serializing_if = "is_default_companion_web_app_url"
     )]
     companion_web_app_url: String,
+    /// If enabled, clients must present `server_auth_token` in order to use the server.
+    #[serde(default, skip_serializing_if = "is_default")]
+    server_auth_enabled: u8,
+    #[serde(default, skip_serializing_if = "is_default")]
+    server_auth_token: String,
+    #[serde(default, skip_serializing_if = "is_default")]
+    safe_mode: u8,
+    #[serde(default, skip_serializing_if = "is_default")]
+    safe_mode_enabled_subsystems: String,
 }
 
 const DEFAULT_SERVER_HTTP_PORT: u16 = 39080;
@@ -1369,7 +1739,12 @@ impl Default for MainConfig {
             server_enabled: Default::default(),
             server_http_port: default_server_http_port(),
             server_https_port: default_server_https_port(),
+            server_port_fallback_count: Default::default(),
             companion_web_app_url: default_companion_web_app_url(),
+            server_auth_enabled: Default::default(),
+            server_auth_token: Default::default(),
+            safe_mode: Default::default(),
+            safe_mode_enabled_subsystems: Default::default(),
         }
     }
 }
@@ -1491,4 +1866,48 @@ impl InstanceContainer for App {
             None
         }
     }
+
+    fn panic(&self, instance_id: &InstanceId) -> Result<(), &'static str> {
+        let session = self
+            .find_session(|session| session.borrow().instance_id() == instance_id)
+            .ok_or("session not found")?;
+        session.borrow().panic();
+        Ok(())
+    }
+
+    fn send_instance_message(&self, args: SendInstanceMessageArgs) -> Result<(), &'static str> {
+        match args.recipients {
+            InstanceMessageRecipients::Session(session_id) => {
+                let session = self
+                    .find_session_by_id(session_id)
+                    .ok_or("session not found")?;
+                let session = session.borrow();
+                if *session.instance_id() == args.initiator_instance_id {
+                    return Err("instance can't send messages to itself");
+                }
+                session.receive_instance_message(args.message);
+            }
+            InstanceMessageRecipients::Tagged(scope) => {
+                for session in self.sessions.borrow().iter() {
+                    if let Some(session) = session.upgrade() {
+                        let session = session.borrow();
+                        // Don't send to ourselves.
+                        if *session.instance_id() == args.initiator_instance_id {
+                            continue;
+                        }
+                        // Don't leave the context (project if in project, FX chain if monitoring
+                        // FX).
+                        if session.context().project() != args.initiator_project {
+                            continue;
+                        }
+                        if !scope.matches(session.tags.get_ref()) {
+                            continue;
+                        }
+                        session.receive_instance_message(args.message.clone());
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
 }
@@ -6,6 +6,8 @@ pub use app::*;
 mod realearn_plugin;
 mod realearn_plugin_parameters;
 pub use realearn_plugin_parameters::*;
+mod safe_mode;
+pub use safe_mode::*;
 
 #[allow(unused)]
 mod built_info {
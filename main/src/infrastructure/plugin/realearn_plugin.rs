@@ -5,9 +5,10 @@ use vst::plugin::{CanDo, Category, HostCallback, Info, Plugin, PluginParameters}
 use super::RealearnEditor;
 use crate::base::Global;
 use crate::domain::{
-    ControlMainTask, Event, FeedbackRealTimeTask, InstanceId, InstanceState, MainProcessor,
-    NormalMainTask, NormalRealTimeToMainThreadTask, ParameterMainTask, ProcessorContext,
-    RealTimeProcessorLocker, RealTimeSender, SharedRealTimeProcessor, PLUGIN_PARAMETER_COUNT,
+    BackboneState, ControlMainTask, Event, FeedbackRealTimeTask, InstanceId, InstanceState,
+    MainProcessor, NormalMainTask, NormalRealTimeToMainThreadTask, ParameterMainTask,
+    ProcessorContext, RealTimeProcessorLocker, RealTimeSender, SharedRealTimeProcessor,
+    PLUGIN_PARAMETER_COUNT,
 };
 use crate::domain::{NormalRealTimeTask, RealTimeProcessor};
 use crate::infrastructure::plugin::realearn_plugin_parameters::RealearnPluginParameters;
@@ -335,6 +336,10 @@ impl RealearnPlugin {
                 // Instance state (domain - shared)
                 let (instance_feedback_event_sender, instance_feedback_event_receiver) =
                     crossbeam_channel::bounded(INSTANCE_FEEDBACK_EVENT_QUEUE_SIZE);
+                // Lets this instance receive changes of shared clip matrices
+                BackboneState::get().register_instance_feedback_event_sender(
+                    instance_feedback_event_sender.clone(),
+                );
                 let instance_state = Rc::new(RefCell::new(InstanceState::new(
                     instance_feedback_event_sender,
                 )));
@@ -363,6 +368,8 @@ impl RealearnPlugin {
                 let weak_session = Rc::downgrade(&shared_session);
                 server::keep_informing_clients_about_session_events(&shared_session);
                 App::get().register_session(weak_session.clone());
+                App::get()
+                    .register_plugin_parameters(instance_id, Arc::downgrade(&plugin_parameters));
                 // Main processor - (domain, owned by REAPER control surface)
                 // Register the main processor with the global ReaLearn control surface. We let it
                 // call by the control surface because it must be called regularly,
@@ -471,6 +478,7 @@ impl Drop for RealearnPlugin {
         debug!(self.logger, "Dropping plug-in...");
         if let Some(session) = self.session.borrow() {
             App::get().unregister_processor_couple(self.instance_id);
+            App::get().unregister_plugin_parameters(&self.instance_id);
             App::get().unregister_session(session.as_ptr());
             debug!(
                 self.logger,
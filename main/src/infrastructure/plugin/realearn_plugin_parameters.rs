@@ -1,4 +1,4 @@
-use crate::base::{notification, SendOrSyncWhatever};
+use crate::base::{notification, RealearnError, SendOrSyncWhatever};
 
 use lazycell::AtomicLazyCell;
 use reaper_high::Reaper;
@@ -7,7 +7,8 @@ use slog::debug;
 
 use crate::application::{SharedSession, WeakSession};
 use crate::domain::{
-    MappingCompartment, ParameterArray, ParameterMainTask, ZEROED_PLUGIN_PARAMETERS,
+    InstanceHookEvent, MappingCompartment, ParameterArray, ParameterMainTask,
+    ZEROED_PLUGIN_PARAMETERS,
 };
 use crate::infrastructure::data::SessionData;
 use crate::infrastructure::plugin::App;
@@ -55,16 +56,21 @@ impl RealearnPluginParameters {
         self.create_session_data_internal()
     }
 
-    pub fn apply_session_data(&self, session_data: &SessionData) {
+    /// Returns an error if the session data couldn't be applied completely.
+    ///
+    /// In that case, the session might be applied partially.
+    pub fn apply_session_data(&self, session_data: &SessionData) -> Result<(), RealearnError> {
         // TODO-medium This is called from ReaLearn itself so we should maybe automate host
         //  parameters otherwise host is not updated. New feature at some point I guess.
-        self.apply_session_data_internal(session_data);
+        self.apply_session_data_internal(session_data)
     }
 
     pub fn load_state(&self, json: &str) {
         let session_data: SessionData =
             serde_json::from_str(json).expect("couldn't deserialize session data");
-        self.apply_session_data_internal(&session_data);
+        if let Err(e) = self.apply_session_data_internal(&session_data) {
+            notification::warn(e.to_string());
+        }
     }
 
     fn create_session_data_internal(&self) -> SessionData {
@@ -74,9 +80,11 @@ impl RealearnPluginParameters {
         SessionData::from_model(&session, &parameters)
     }
 
-    fn apply_session_data_internal(&self, session_data: &SessionData) {
+    fn apply_session_data_internal(&self, session_data: &SessionData) -> Result<(), RealearnError> {
         // Update session
-        let shared_session = self.session().expect("session should exist already");
+        let shared_session = self
+            .session()
+            .ok_or_else(|| RealearnError::internal("session doesn't exist anymore"))?;
         let mut session = shared_session.borrow_mut();
         if let Some(v) = session_data.version.as_ref() {
             if App::version() < v {
@@ -92,9 +100,13 @@ impl RealearnPluginParameters {
             }
         }
         let parameters = session_data.parameters_as_array();
-        if let Err(e) = session_data.apply_to_model(&mut session, &parameters) {
-            notification::warn(e.to_string());
-        }
+        // Even if the data is partially invalid, the valid parts have been applied already, so
+        // we still need to notify.
+        let result = session_data
+            .apply_to_model(&mut session, &parameters)
+            .map_err(|e| {
+                RealearnError::invalid_input(e.to_string()).context("applying session data")
+            });
         // Update parameters
         self.parameter_main_task_sender
             .try_send(ParameterMainTask::UpdateAllParameters(Box::new(parameters)))
@@ -102,6 +114,9 @@ impl RealearnPluginParameters {
         *self.parameters_mut() = parameters;
         // Notify
         session.notify_everything_has_changed(Rc::downgrade(&shared_session));
+        session.fire_instance_hook(InstanceHookEvent::SessionLoad);
+        session.request_controller_state();
+        result
     }
 
     fn session(&self) -> Option<SharedSession> {
@@ -192,6 +207,26 @@ impl PluginParameters for RealearnPluginParameters {
         .unwrap_or_default()
     }
 
+    fn get_parameter_label(&self, _index: i32) -> String {
+        "%".to_string()
+    }
+
+    fn get_parameter_text(&self, index: i32) -> String {
+        firewall(|| format!("{:.1}", self.parameters()[index as usize] * 100.0)).unwrap_or_default()
+    }
+
+    fn string_to_parameter(&self, index: i32, text: String) -> bool {
+        firewall(|| {
+            let percent: f32 = match text.trim().trim_end_matches('%').trim().parse() {
+                Ok(v) => v,
+                Err(_) => return false,
+            };
+            self.set_parameter(index, (percent / 100.0).max(0.0).min(1.0));
+            true
+        })
+        .unwrap_or(false)
+    }
+
     fn get_parameter(&self, index: i32) -> f32 {
         firewall(|| {
             // It's super important that we don't get the parameter from the session because if
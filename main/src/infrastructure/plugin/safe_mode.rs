@@ -0,0 +1,115 @@
+use derive_more::Display;
+use enum_iterator::IntoEnumIterator;
+use serde::Serialize;
+use std::collections::HashSet;
+
+/// Environment variable which starts ReaLearn in safe mode.
+///
+/// Its value is a comma-separated list of subsystems which should be enabled nevertheless, e.g.
+/// `server,osc-engine`. Any other value (e.g. `1`) disables all subsystems.
+pub const SAFE_MODE_ENV_VAR: &str = "REALEARN_SAFE_MODE";
+
+/// Subsystems which can be disabled individually by starting in safe mode.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, IntoEnumIterator, Display)]
+#[serde(rename_all = "kebab-case")]
+pub enum Subsystem {
+    #[display(fmt = "Projection server")]
+    Server,
+    #[display(fmt = "OSC engine")]
+    OscEngine,
+    #[display(fmt = "Clip engine")]
+    ClipEngine,
+}
+
+impl Subsystem {
+    /// The ID used in the config file and in the environment variable.
+    pub fn id(self) -> &'static str {
+        use Subsystem::*;
+        match self {
+            Server => "server",
+            OscEngine => "osc-engine",
+            ClipEngine => "clip-engine",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::into_enum_iter().find(|s| s.id() == id)
+    }
+}
+
+/// Lets the user isolate crashes by starting ReaLearn with some subsystems disabled.
+///
+/// Disabled subsystems can be enabled one by one at runtime. They can't be disabled again
+/// without restarting REAPER.
+#[derive(Clone, Debug, Default)]
+pub struct SafeMode {
+    active: bool,
+    disabled_subsystems: HashSet<Subsystem>,
+}
+
+impl SafeMode {
+    /// Activates safe mode if the environment variable is set or if it's enabled in the config.
+    ///
+    /// The environment variable takes precedence.
+    pub fn determine(env_value: Option<&str>, config: Option<&str>) -> Self {
+        match env_value.or(config) {
+            None => Self::default(),
+            Some(enabled_subsystems) => Self::with_enabled_subsystems(enabled_subsystems),
+        }
+    }
+
+    fn with_enabled_subsystems(enabled_subsystems: &str) -> Self {
+        let enabled: HashSet<_> = enabled_subsystems
+            .split(',')
+            .filter_map(|id| Subsystem::from_id(id.trim()))
+            .collect();
+        Self {
+            active: true,
+            disabled_subsystems: Subsystem::into_enum_iter()
+                .filter(|s| !enabled.contains(s))
+                .collect(),
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    pub fn subsystem_is_enabled(&self, subsystem: Subsystem) -> bool {
+        !self.disabled_subsystems.contains(&subsystem)
+    }
+
+    /// Returns `false` if the subsystem was enabled already.
+    pub fn enable_subsystem(&mut self, subsystem: Subsystem) -> bool {
+        self.disabled_subsystems.remove(&subsystem)
+    }
+
+    pub fn disabled_subsystems(&self) -> impl Iterator<Item = Subsystem> + '_ {
+        Subsystem::into_enum_iter().filter(move |s| self.disabled_subsystems.contains(s))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn determine() {
+        // When
+        let inactive = SafeMode::determine(None, None);
+        let all_disabled = SafeMode::determine(Some("1"), None);
+        let from_config = SafeMode::determine(None, Some("server, clip-engine"));
+        let env_wins = SafeMode::determine(Some("osc-engine"), Some("server"));
+        // Then
+        assert!(!inactive.is_active());
+        assert!(inactive.subsystem_is_enabled(Subsystem::ClipEngine));
+        assert!(all_disabled.is_active());
+        assert_eq!(all_disabled.disabled_subsystems().count(), 3);
+        assert_eq!(
+            from_config.disabled_subsystems().collect::<Vec<_>>(),
+            vec![Subsystem::OscEngine]
+        );
+        assert!(env_wins.subsystem_is_enabled(Subsystem::OscEngine));
+        assert!(!env_wins.subsystem_is_enabled(Subsystem::Server));
+    }
+}
@@ -1,15 +1,26 @@
+mod webhook;
+use webhook::{dispatch_webhook_messages, WebhookConfig, WebhookMessage, WebhookSender};
+
 use crate::application::{
-    Preset, PresetManager, Session, SharedSession, SourceCategory, TargetCategory,
+    BinaryCustomData, GuidedMappingState, GuidedMappingStep, MappingModel, OrphanedCustomDataRef,
+    Preset, PresetManager, Session, SessionActivity, SharedSession, SourceCategory, TargetCategory,
+    TargetModelFormatVeryShort, VirtualControlElementType, DEFAULT_BINARY_CONTENT_TYPE,
 };
-use crate::base::when;
+use crate::base::{when, ErrorCode, JsonPatchOperation, RealearnError};
 use crate::domain::{
-    MappingCompartment, MappingKey, ProjectionFeedbackValue, RealearnControlSurfaceServerTask,
+    BackboneState, ControlLatencyStatisticsInMillis, DeviceControlInput, DeviceFeedbackOutput,
+    DeviceUser, InstanceId, InstancePriority, MappingCompartment, MappingKey, OscDeviceId,
+    ProjectionFeedbackValue, RealearnControlSurfaceServerTask, ReaperTarget, ReaperTargetType,
+    RtpMidiDeviceId, TargetValueChangeOrigin, TargetValueChangedEvent, VirtualControlElementId,
 };
 use maplit::hashmap;
 
 use crate::base::Global;
-use crate::infrastructure::data::{ControllerPresetData, PresetData};
-use crate::infrastructure::plugin::{App, RealearnControlSurfaceServerTaskSender};
+use crate::infrastructure::data::{
+    patch_controller_preset, ControllerPresetData, PresetData, SessionData, SessionTemplate,
+};
+use crate::infrastructure::plugin::{App, RealearnControlSurfaceServerTaskSender, Subsystem};
+use crate::infrastructure::ui::SearchExpression;
 
 use futures::StreamExt;
 use rcgen::{BasicConstraints, CertificateParams, DistinguishedName, DnType, IsCa, SanType};
@@ -31,8 +42,10 @@ use std::sync::Arc;
 use tokio::sync::{broadcast, mpsc};
 use url::Url;
 use warp::http::{Method, Response, StatusCode};
+use warp::hyper::body::Bytes;
 
-use helgoboss_learn::UnitValue;
+use enum_iterator::IntoEnumIterator;
+use helgoboss_learn::{AbsoluteValue, UnitValue};
 use std::thread::JoinHandle;
 use std::time::Duration;
 use warp::reply::Json;
@@ -43,13 +56,24 @@ pub type SharedRealearnServer = Rc<RefCell<RealearnServer>>;
 
 #[derive(Debug)]
 pub struct RealearnServer {
+    /// Configured port.
     http_port: u16,
+    /// Configured port.
     https_port: u16,
+    /// Number of subsequent ports to try if a configured port is taken.
+    port_fallback_count: u16,
+    /// Actually used port (differs from the configured one if we had to fall back).
+    effective_http_port: u16,
+    /// Actually used port (differs from the configured one if we had to fall back).
+    effective_https_port: u16,
     state: ServerState,
     certs_dir_path: PathBuf,
     changed_subject: LocalSubject<'static, (), ()>,
     local_ip: Option<IpAddr>,
     control_surface_task_sender: RealearnControlSurfaceServerTaskSender,
+    webhook_config_file_path: PathBuf,
+    /// If set, clients must present this token.
+    auth_token: Option<String>,
 }
 
 #[derive(Debug)]
@@ -63,6 +87,8 @@ enum ServerState {
 struct ServerRuntimeData {
     clients: ServerClients,
     shutdown_sender: broadcast::Sender<()>,
+    /// `None` if no webhooks are configured.
+    webhook_sender: Option<WebhookSender>,
     server_thread_join_handle: JoinHandle<()>,
 }
 
@@ -78,21 +104,36 @@ impl ServerState {
 
 pub const COMPANION_WEB_APP_URL: &str = "https://realearn.helgoboss.org/";
 
+/// Maximum size of uploaded binary custom data (before base64 encoding).
+const MAX_BINARY_CUSTOM_DATA_SIZE: u64 = 1024 * 1024;
+
+/// Number of mappings returned by the mapping list if the client doesn't specify a limit.
+const DEFAULT_MAPPING_PAGE_SIZE: usize = 100;
+const MAX_MAPPING_PAGE_SIZE: usize = 1000;
+
 impl RealearnServer {
     pub fn new(
         http_port: u16,
         https_port: u16,
+        port_fallback_count: u16,
         certs_dir_path: PathBuf,
         control_surface_task_sender: RealearnControlSurfaceServerTaskSender,
+        webhook_config_file_path: PathBuf,
+        auth_token: Option<String>,
     ) -> RealearnServer {
         RealearnServer {
             http_port,
             https_port,
+            port_fallback_count,
+            effective_http_port: http_port,
+            effective_https_port: https_port,
             state: ServerState::Stopped,
             certs_dir_path,
             changed_subject: Default::default(),
             local_ip: get_local_ip(),
             control_surface_task_sender,
+            webhook_config_file_path,
+            auth_token,
         }
     }
 
@@ -101,16 +142,40 @@ impl RealearnServer {
         if self.state.is_starting_or_running() {
             return Ok(());
         }
-        check_port(false, self.http_port)?;
-        check_port(true, self.https_port)?;
+        // The HTTP listener must not take the port reserved for HTTPS and vice versa.
+        self.effective_http_port = find_available_port(
+            false,
+            self.http_port,
+            self.port_fallback_count,
+            self.https_port,
+        )?;
+        self.effective_https_port = find_available_port(
+            true,
+            self.https_port,
+            self.port_fallback_count,
+            self.effective_http_port,
+        )?;
         let clients: ServerClients = Default::default();
         let clients_clone = clients.clone();
-        let http_port = self.http_port;
-        let https_port = self.https_port;
+        let http_port = self.effective_http_port;
+        let https_port = self.effective_https_port;
         let key_and_cert = self.key_and_cert();
         let control_surface_task_sender = self.control_surface_task_sender.clone();
+        let auth_token = self.auth_token.clone();
         let (shutdown_sender, http_shutdown_receiver) = broadcast::channel(5);
         let https_shutdown_receiver = shutdown_sender.subscribe();
+        // A broken webhook config shouldn't prevent the server from starting.
+        let webhook_config =
+            WebhookConfig::load(&self.webhook_config_file_path).unwrap_or_else(|e| {
+                eprintln!("{}", e);
+                Default::default()
+            });
+        let (webhook_sender, webhook_receiver) = if webhook_config.webhooks.is_empty() {
+            (None, None)
+        } else {
+            let (sender, receiver) = mpsc::unbounded_channel();
+            (Some(sender), Some(receiver))
+        };
         let server_thread_join_handle = std::thread::Builder::new()
             .name("ReaLearn server".to_string())
             .spawn(move || {
@@ -124,12 +189,16 @@ impl RealearnServer {
                     .enable_all()
                     .build()
                     .unwrap();
+                if let Some(receiver) = webhook_receiver {
+                    runtime.spawn(dispatch_webhook_messages(receiver, webhook_config));
+                }
                 runtime.block_on(start_server(
                     http_port,
                     https_port,
                     clients_clone,
                     key_and_cert,
                     control_surface_task_sender,
+                    auth_token,
                     http_shutdown_receiver,
                     https_shutdown_receiver,
                 ));
@@ -139,6 +208,7 @@ impl RealearnServer {
         let runtime_data = ServerRuntimeData {
             clients,
             shutdown_sender,
+            webhook_sender,
             server_thread_join_handle,
         };
         self.state = ServerState::Starting(runtime_data);
@@ -192,6 +262,17 @@ impl RealearnServer {
         }
     }
 
+    /// Posts the given session activity to all interested webhooks (if the server is running).
+    pub fn notify_webhooks(&self, session_id: &str, activity: SessionActivity) {
+        let runtime_data = match &self.state {
+            ServerState::Starting(d) | ServerState::Running(d) => d,
+            ServerState::Stopped => return,
+        };
+        if let Some(sender) = &runtime_data.webhook_sender {
+            let _ = sender.send(WebhookMessage::new(session_id.to_string(), activity));
+        }
+    }
+
     pub fn is_running(&self) -> bool {
         matches!(&self.state, ServerState::Running { .. })
     }
@@ -202,6 +283,19 @@ impl RealearnServer {
         } else {
             self.local_ip().map(|ip| ip.to_string())
         };
+        let mut params = vec![
+            ("host", host.unwrap_or_else(|| "localhost".to_string())),
+            ("http-port", self.http_port().to_string()),
+            ("https-port", self.https_port().to_string()),
+            ("session-id", session_id.to_string()),
+            // In order to indicate that the URL has not been entered manually and therefore
+            // typos are out of question (for a proper error message if connection is not
+            // possible).
+            ("generated", "true".to_string()),
+        ];
+        if let Some(token) = &self.auth_token {
+            params.push(("token", token.clone()));
+        }
         Url::parse_with_params(
             App::get()
                 .config()
@@ -209,16 +303,7 @@ impl RealearnServer {
                 .join("controller-routing")
                 .unwrap()
                 .as_str(),
-            &[
-                ("host", host.unwrap_or_else(|| "localhost".to_string())),
-                ("http-port", self.http_port().to_string()),
-                ("https-port", self.https_port().to_string()),
-                ("session-id", session_id.to_string()),
-                // In order to indicate that the URL has not been entered manually and therefore
-                // typos are out of question (for a proper error message if connection is not
-                // possible).
-                ("generated", "true".to_string()),
-            ],
+            &params,
         )
         .expect("invalid URL")
         .into()
@@ -238,12 +323,14 @@ impl RealearnServer {
         dns_lookup::lookup_addr(&ip).ok()
     }
 
+    /// Returns the port which is actually used.
     pub fn http_port(&self) -> u16 {
-        self.http_port
+        self.effective_http_port
     }
 
+    /// Returns the port which is actually used.
     pub fn https_port(&self) -> u16 {
-        self.https_port
+        self.effective_https_port
     }
 
     pub fn log_debug_info(&self, session_id: &str) {
@@ -296,14 +383,111 @@ async fn process_send_result<O: Reply + 'static, E: Reply + 'static, SE>(
     Ok(raw)
 }
 
-fn sender_dropped_response() -> Response<&'static str> {
-    Response::builder()
-        .status(500)
-        .body("sender dropped")
-        .unwrap()
+fn sender_dropped_response() -> RealearnError {
+    RealearnError::internal("sender dropped")
+}
+
+#[derive(Debug)]
+struct Unauthorized;
+
+impl warp::reject::Reject for Unauthorized {}
+
+/// Lets requests pass only if they carry the given token (if any).
+///
+/// The token can be presented as bearer token in the `Authorization` header or as `token` query
+/// parameter. The latter is necessary for WebSocket connections because browsers can't set
+/// headers for them.
+fn authenticate(
+    auth_token: Option<String>,
+) -> impl warp::Filter<Extract = (), Error = Rejection> + Clone {
+    use warp::Filter;
+    warp::header::optional::<String>("authorization")
+        .and(warp::query::<AuthRequest>())
+        .and_then(move |header: Option<String>, req: AuthRequest| {
+            let auth_token = auth_token.clone();
+            async move {
+                let expected_token = match auth_token {
+                    None => return Ok(()),
+                    Some(t) => t,
+                };
+                let presented_token = header
+                    .as_deref()
+                    .and_then(|h| h.strip_prefix("Bearer "))
+                    .or_else(|| req.token.as_deref());
+                let is_authorized = presented_token
+                    .map(|t| tokens_are_equal(t, &expected_token))
+                    .unwrap_or(false);
+                if is_authorized {
+                    Ok(())
+                } else {
+                    Err(warp::reject::custom(Unauthorized))
+                }
+            }
+        })
+        .untuple_one()
+}
+
+/// Takes the same time for all tokens of the same length, so response times don't reveal how
+/// many leading characters of a guessed token are correct.
+fn tokens_are_equal(presented: &str, expected: &str) -> bool {
+    let (presented, expected) = (presented.as_bytes(), expected.as_bytes());
+    if presented.len() != expected.len() {
+        return false;
+    }
+    let diff = presented
+        .iter()
+        .zip(expected)
+        .fold(0, |diff, (a, b)| diff | (a ^ b));
+    diff == 0
+}
+
+async fn handle_rejection(rejection: Rejection) -> Result<reply::Response, Rejection> {
+    if rejection.find::<Unauthorized>().is_some() {
+        Ok(RealearnError::unauthorized("missing or invalid token").into_response())
+    } else {
+        Err(rejection)
+    }
+}
+
+/// Error responses are sent as "problem details" (RFC 7807).
+impl Reply for RealearnError {
+    fn into_response(self) -> reply::Response {
+        let status = match self.code() {
+            ErrorCode::InvalidInput => StatusCode::BAD_REQUEST,
+            ErrorCode::NotFound => StatusCode::NOT_FOUND,
+            ErrorCode::NotSupported => StatusCode::METHOD_NOT_ALLOWED,
+            ErrorCode::Unauthorized => StatusCode::UNAUTHORIZED,
+            ErrorCode::Internal | ErrorCode::Unspecified => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        let problem = ProblemDetails {
+            r#type: "about:blank",
+            title: status.canonical_reason().unwrap_or_default(),
+            status: status.as_u16(),
+            detail: self.to_string(),
+            code: self.code(),
+            context: self.context_entries().iter().map(|c| c.as_ref()).collect(),
+        };
+        let body = serde_json::to_string(&problem).unwrap_or_default();
+        Response::builder()
+            .status(status)
+            .header("content-type", "application/problem+json")
+            .body(body.into())
+            .unwrap()
+    }
+}
+
+#[derive(Serialize)]
+struct ProblemDetails<'a> {
+    r#type: &'static str,
+    title: &'static str,
+    status: u16,
+    detail: String,
+    code: ErrorCode,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    context: Vec<&'a str>,
 }
 
-fn handle_controller_routing_route(session_id: String) -> Result<Json, Response<&'static str>> {
+fn handle_controller_routing_route(session_id: String) -> Result<Json, RealearnError> {
     let session = App::get()
         .find_session_by_id(&session_id)
         .ok_or_else(session_not_found)?;
@@ -311,64 +495,325 @@ fn handle_controller_routing_route(session_id: String) -> Result<Json, Response<
     Ok(reply::json(&routing))
 }
 
+/// Applies a JSON Patch (RFC 6902) to the controller preset.
+///
+/// For backward compatibility, a single operation is accepted as well.
 fn handle_patch_controller_route(
     controller_id: String,
     req: PatchRequest,
-) -> Result<StatusCode, Response<&'static str>> {
-    if req.op != PatchRequestOp::Replace {
-        return Err(Response::builder()
-            .status(StatusCode::METHOD_NOT_ALLOWED)
-            .body("only 'replace' is supported as op")
-            .unwrap());
-    }
-    let split_path: Vec<_> = req.path.split('/').collect();
-    let custom_data_key = if let ["", "customData", key] = split_path.as_slice() {
-        key
-    } else {
-        return Err(Response::builder()
-            .status(StatusCode::BAD_REQUEST)
-            .body("only '/customData/{key}' is supported as path")
-            .unwrap());
+) -> Result<StatusCode, RealearnError> {
+    let operations = match req {
+        PatchRequest::Single(op) => vec![op],
+        PatchRequest::Multiple(ops) => ops,
     };
+    let controller_manager = App::get().controller_preset_manager();
+    let mut controller_manager = controller_manager.borrow_mut();
+    let controller = controller_manager
+        .find_by_id(&controller_id)
+        .ok_or_else(controller_not_found)?;
+    let patched_controller = patch_controller_preset(&controller, &operations)
+        .map_err(|e| RealearnError::invalid_input(e).context("patching controller"))?;
+    controller_manager
+        .update_preset(patched_controller)
+        .map_err(|e| RealearnError::internal(e).context("updating controller"))?;
+    Ok(StatusCode::OK)
+}
+
+fn handle_binary_custom_data_route(
+    controller_id: String,
+    key: String,
+) -> Result<Response<Vec<u8>>, RealearnError> {
+    let controller = App::get()
+        .controller_preset_manager()
+        .find_by_id(&controller_id)
+        .ok_or_else(controller_not_found)?;
+    let data = controller
+        .binary_custom_data(&key)
+        .map_err(RealearnError::invalid_input)?
+        .ok_or_else(custom_data_not_found)?;
+    let response = Response::builder()
+        .header("Content-Type", data.content_type)
+        .body(data.bytes)
+        .map_err(|_| RealearnError::internal("couldn't build response"))?;
+    Ok(response)
+}
+
+fn handle_put_binary_custom_data_route(
+    controller_id: String,
+    key: String,
+    data: BinaryCustomData,
+) -> Result<StatusCode, RealearnError> {
     let controller_manager = App::get().controller_preset_manager();
     let mut controller_manager = controller_manager.borrow_mut();
     let mut controller = controller_manager
         .find_by_id(&controller_id)
         .ok_or_else(controller_not_found)?;
-    controller.update_custom_data(custom_data_key.to_string(), req.value);
+    controller
+        .update_binary_custom_data(key, data)
+        .map_err(RealearnError::invalid_input)?;
     controller_manager
         .update_preset(controller)
-        .map_err(|_| internal_server_error("couldn't update controller"))?;
+        .map_err(|e| RealearnError::internal(e).context("updating controller"))?;
+    Ok(StatusCode::OK)
+}
+
+fn handle_orphaned_custom_data_route(controller_id: String) -> Result<Json, RealearnError> {
+    let controller = App::get()
+        .controller_preset_manager()
+        .find_by_id(&controller_id)
+        .ok_or_else(controller_not_found)?;
+    let refs = OrphanedCustomDataRefData::from_refs(controller.orphaned_custom_data_refs());
+    Ok(reply::json(&refs))
+}
+
+/// Responds with the removed references.
+fn handle_delete_orphaned_custom_data_route(controller_id: String) -> Result<Json, RealearnError> {
+    let controller_manager = App::get().controller_preset_manager();
+    let mut controller_manager = controller_manager.borrow_mut();
+    if controller_manager.find_by_id(&controller_id).is_none() {
+        return Err(controller_not_found());
+    }
+    let removed = controller_manager
+        .remove_orphaned_custom_data_refs(&controller_id)
+        .map_err(|e| RealearnError::internal(e).context("updating controller"))?;
+    Ok(reply::json(&OrphanedCustomDataRefData::from_refs(removed)))
+}
+
+fn handle_post_target_value_route(
+    session_id: String,
+    mapping_key: String,
+    req: TargetValueRequest,
+) -> Result<StatusCode, RealearnError> {
+    if !(0.0..=1.0).contains(&req.value) {
+        return Err(RealearnError::invalid_input(
+            "value must be between 0.0 and 1.0",
+        ));
+    }
+    let session = App::get()
+        .find_session_by_id(&session_id)
+        .ok_or_else(session_not_found)?;
+    let session = session.borrow();
+    let mapping_key: MappingKey = mapping_key.into();
+    let id = MappingCompartment::enum_iter()
+        .find_map(|compartment| {
+            let mapping = session
+                .mappings(compartment)
+                .find(|m| m.borrow().key() == &mapping_key)?;
+            let id = mapping.borrow().qualified_id();
+            Some(id)
+        })
+        .ok_or_else(mapping_not_found)?;
+    let value = AbsoluteValue::Continuous(UnitValue::new(req.value));
+    session.hit_target(id, value);
     Ok(StatusCode::OK)
 }
 
-fn session_not_found() -> Response<&'static str> {
-    not_found("session not found")
+fn handle_mappings_route(session_id: String, req: MappingsRequest) -> Result<Json, RealearnError> {
+    let session = App::get()
+        .find_session_by_id(&session_id)
+        .ok_or_else(session_not_found)?;
+    let session = session.borrow();
+    let compartments: Vec<_> = match req.compartment {
+        None => MappingCompartment::enum_iter().collect(),
+        Some(c) => vec![c.to_compartment()],
+    };
+    let search_expression = SearchExpression::new(req.search.as_deref().unwrap_or_default());
+    let matching_mappings: Vec<_> = compartments
+        .into_iter()
+        .flat_map(|compartment| session.mappings(compartment))
+        .filter(|m| {
+            let m = m.borrow();
+            search_expression.is_empty()
+                || search_expression.matches(&m.effective_name())
+                || search_expression.matches_any_tag(m.tags.get_ref())
+                || search_expression.matches_any_tag_in_group(&m, &session)
+        })
+        .collect();
+    let limit = req
+        .limit
+        .unwrap_or(DEFAULT_MAPPING_PAGE_SIZE)
+        .min(MAX_MAPPING_PAGE_SIZE);
+    let data = MappingListData {
+        total: matching_mappings.len(),
+        offset: req.offset,
+        mappings: matching_mappings
+            .into_iter()
+            .skip(req.offset)
+            .take(limit)
+            .map(|m| MappingSummaryData::from_model(&m.borrow(), &session))
+            .collect(),
+    };
+    Ok(reply::json(&data))
 }
 
-fn session_has_no_active_controller() -> Response<&'static str> {
-    not_found("session doesn't have an active controller")
+fn handle_control_latency_route(session_id: String) -> Result<Json, RealearnError> {
+    let session = App::get()
+        .find_session_by_id(&session_id)
+        .ok_or_else(session_not_found)?;
+    let session = session.borrow();
+    let data = ControlLatencyData {
+        measuring: session.control_latency_measurement_is_running(),
+        statistics: session.control_latency_statistics().map(|s| s.in_millis()),
+    };
+    Ok(reply::json(&data))
 }
 
-fn controller_not_found() -> Response<&'static str> {
-    not_found("session has controller but controller not found")
+fn handle_full_session_route(session_id: String) -> Result<Json, RealearnError> {
+    let plugin_parameters = App::get()
+        .find_plugin_parameters_by_session_id(&session_id)
+        .ok_or_else(session_not_found)?;
+    let session_data = plugin_parameters.create_session_data();
+    Ok(reply::json(&session_data))
 }
 
-fn not_found(msg: &'static str) -> Response<&'static str> {
-    Response::builder()
-        .status(StatusCode::NOT_FOUND)
-        .body(msg)
-        .unwrap()
+fn handle_put_full_session_route(
+    session_id: String,
+    session_data: SessionData,
+) -> Result<StatusCode, RealearnError> {
+    let plugin_parameters = App::get()
+        .find_plugin_parameters_by_session_id(&session_id)
+        .ok_or_else(session_not_found)?;
+    plugin_parameters.apply_session_data(&session_data)?;
+    Ok(StatusCode::OK)
 }
 
-fn internal_server_error(msg: &'static str) -> Response<&'static str> {
-    Response::builder()
-        .status(StatusCode::INTERNAL_SERVER_ERROR)
-        .body(msg)
-        .unwrap()
+fn handle_post_session_template_route(
+    session_id: String,
+    template_id: String,
+) -> Result<StatusCode, RealearnError> {
+    let plugin_parameters = App::get()
+        .find_plugin_parameters_by_session_id(&session_id)
+        .ok_or_else(session_not_found)?;
+    let template = SessionTemplate::load(&template_id)
+        .map_err(|e| RealearnError::invalid_input(e).context("loading session template"))?
+        .ok_or_else(session_template_not_found)?;
+    let session_data = template.into_session_data().map_err(|e| {
+        RealearnError::invalid_input(e.to_string()).context("applying session template")
+    })?;
+    plugin_parameters.apply_session_data(&session_data)?;
+    Ok(StatusCode::OK)
 }
 
-fn handle_controller_route(session_id: String) -> Result<Json, Response<&'static str>> {
+fn handle_guided_mapping_route(session_id: String) -> Result<Json, RealearnError> {
+    let session = App::get()
+        .find_session_by_id(&session_id)
+        .ok_or_else(session_not_found)?;
+    let session = session.borrow();
+    let state = session
+        .guided_mapping_state()
+        .ok_or_else(guided_mapping_not_found)?;
+    Ok(reply::json(&GuidedMappingData::from_state(state)))
+}
+
+fn handle_post_guided_mapping_route(
+    session_id: String,
+    req: GuidedMappingRequest,
+) -> Result<StatusCode, RealearnError> {
+    let control_element_id: VirtualControlElementId = req
+        .control_element_id
+        .parse()
+        .map_err(RealearnError::invalid_input)?;
+    let control_element = req
+        .control_element_type
+        .create_control_element(control_element_id);
+    let session = App::get()
+        .find_session_by_id(&session_id)
+        .ok_or_else(session_not_found)?;
+    session
+        .borrow_mut()
+        .claim_control_element_for_guided_mapping(control_element)
+        .map_err(|e| RealearnError::invalid_input(e).context("claiming control element"))?;
+    Ok(StatusCode::OK)
+}
+
+fn handle_post_guided_mapping_step_route(
+    session_id: String,
+    step: String,
+) -> Result<Json, RealearnError> {
+    let session = App::get()
+        .find_session_by_id(&session_id)
+        .ok_or_else(session_not_found)?;
+    let mut session = session.borrow_mut();
+    let mapping_key = match step.as_str() {
+        "identify" => {
+            session
+                .identify_guided_mapping_control_element()
+                .map_err(RealearnError::not_found)?;
+            None
+        }
+        "capture-target" => {
+            session
+                .capture_guided_mapping_target()
+                .map_err(|e| RealearnError::invalid_input(e).context("capturing target"))?;
+            None
+        }
+        "commit" => {
+            let mapping = session
+                .commit_guided_mapping()
+                .map_err(|e| RealearnError::invalid_input(e).context("committing mapping"))?;
+            let key = mapping.borrow().key().clone();
+            Some(key)
+        }
+        _ => {
+            return Err(RealearnError::not_found(
+                "only 'identify', 'capture-target' and 'commit' are supported as steps",
+            ))
+        }
+    };
+    let data = GuidedMappingStepResponseData {
+        state: session
+            .guided_mapping_state()
+            .map(GuidedMappingData::from_state),
+        mapping_key,
+    };
+    Ok(reply::json(&data))
+}
+
+fn handle_delete_guided_mapping_route(session_id: String) -> Result<StatusCode, RealearnError> {
+    let session = App::get()
+        .find_session_by_id(&session_id)
+        .ok_or_else(session_not_found)?;
+    session.borrow_mut().cancel_guided_mapping();
+    Ok(StatusCode::OK)
+}
+
+fn handle_post_panic_route(session_id: String) -> Result<StatusCode, RealearnError> {
+    let session = App::get()
+        .find_session_by_id(&session_id)
+        .ok_or_else(session_not_found)?;
+    session.borrow().panic();
+    Ok(StatusCode::OK)
+}
+
+fn session_not_found() -> RealearnError {
+    RealearnError::not_found("session not found")
+}
+
+fn session_template_not_found() -> RealearnError {
+    RealearnError::not_found("session template not found")
+}
+
+fn guided_mapping_not_found() -> RealearnError {
+    RealearnError::not_found("session doesn't have a guided mapping in progress")
+}
+
+fn session_has_no_active_controller() -> RealearnError {
+    RealearnError::not_found("session doesn't have an active controller")
+}
+
+fn mapping_not_found() -> RealearnError {
+    RealearnError::not_found("session doesn't have a mapping with that key")
+}
+
+fn custom_data_not_found() -> RealearnError {
+    RealearnError::not_found("custom data not found")
+}
+
+fn controller_not_found() -> RealearnError {
+    RealearnError::not_found("session has controller but controller not found")
+}
+
+fn handle_controller_route(session_id: String) -> Result<Json, RealearnError> {
     let session = App::get()
         .find_session_by_id(&session_id)
         .ok_or_else(session_not_found)?;
@@ -385,7 +830,106 @@ fn handle_controller_route(session_id: String) -> Result<Json, Response<&'static
     Ok(reply::json(&controller_data))
 }
 
-fn handle_session_route(session_id: String) -> Result<Json, Response<&'static str>> {
+fn handle_info_route() -> Result<Json, RealearnError> {
+    let app = App::get();
+    let safe_mode = app.safe_mode();
+    let info = InfoData {
+        version: App::version().to_string(),
+        safe_mode: SafeModeData {
+            active: safe_mode.is_active(),
+            disabled_subsystems: safe_mode.disabled_subsystems().collect(),
+        },
+    };
+    Ok(reply::json(&info))
+}
+
+fn handle_sessions_route() -> Result<Json, RealearnError> {
+    let sessions: Vec<_> = App::get()
+        .sessions()
+        .iter()
+        .map(|session| {
+            let session = session.borrow();
+            LightSessionData {
+                id: session.id().to_string(),
+                instance_id: session.instance_id().to_string(),
+                project_name: session
+                    .context()
+                    .project()
+                    .and_then(|p| p.file())
+                    .and_then(|f| Some(f.file_stem()?.to_string_lossy().to_string())),
+                main_preset: session.active_main_preset().map(|mp| LightMainPresetData {
+                    id: mp.id().to_string(),
+                    name: mp.name().to_string(),
+                }),
+            }
+        })
+        .collect();
+    Ok(reply::json(&sessions))
+}
+
+fn handle_controller_presets_route() -> Result<Json, RealearnError> {
+    let manager = App::get().controller_preset_manager();
+    let presets: Vec<_> = manager
+        .borrow()
+        .presets()
+        .map(|p| PresetInfoData {
+            custom_data_keys: Some(p.custom_data().keys().cloned().collect()),
+            ..PresetInfoData::from_preset(p)
+        })
+        .collect();
+    Ok(reply::json(&presets))
+}
+
+fn handle_main_presets_route() -> Result<Json, RealearnError> {
+    let manager = App::get().main_preset_manager();
+    let presets: Vec<_> = manager
+        .borrow()
+        .presets()
+        .map(PresetInfoData::from_preset)
+        .collect();
+    Ok(reply::json(&presets))
+}
+
+fn handle_io_ownership_route() -> Result<Json, RealearnError> {
+    let session_ids: HashMap<InstanceId, String> = App::get()
+        .sessions()
+        .iter()
+        .map(|session| {
+            let session = session.borrow();
+            (*session.instance_id(), session.id().to_string())
+        })
+        .collect();
+    let backbone_state = BackboneState::get();
+    let ownership = IoOwnershipData {
+        control_inputs: backbone_state
+            .control_input_ownerships()
+            .into_iter()
+            .map(|o| {
+                let device = match o.device {
+                    DeviceControlInput::Midi(id) => DeviceData::midi(id.get()),
+                    DeviceControlInput::Osc(id) => DeviceData::osc(id),
+                    DeviceControlInput::RtpMidi(id) => DeviceData::rtp_midi(id),
+                };
+                DeviceOwnershipData::new(device, o.users, &session_ids)
+            })
+            .collect(),
+        feedback_outputs: backbone_state
+            .feedback_output_ownerships()
+            .into_iter()
+            .map(|o| {
+                let device = match o.device {
+                    DeviceFeedbackOutput::Midi(id) => DeviceData::midi(id.get()),
+                    DeviceFeedbackOutput::Osc(id) => DeviceData::osc(id),
+                    DeviceFeedbackOutput::RtpMidi(id) => DeviceData::rtp_midi(id),
+                };
+                DeviceOwnershipData::new(device, o.users, &session_ids)
+            })
+            .collect(),
+    };
+    Ok(reply::json(&ownership))
+}
+
+fn handle_session_route(session_id: String) -> Result<Json, RealearnError> {
     let _ = App::get()
         .find_session_by_id(&session_id)
         .ok_or_else(session_not_found)?;
@@ -402,7 +946,7 @@ async fn handle_metrics_route(
             sender,
         ))
         .unwrap();
-    let snapshot: Result<Result<String, String>, _> = receiver.await.map(Ok);
+    let snapshot: Result<Result<String, RealearnError>, _> = receiver.await.map(Ok);
     process_send_result(snapshot).await
 }
 
@@ -412,6 +956,7 @@ async fn start_server(
     clients: ServerClients,
     (key, cert): (String, String),
     control_surface_task_sender: RealearnControlSurfaceServerTaskSender,
+    auth_token: Option<String>,
     mut http_shutdown_receiver: broadcast::Receiver<()>,
     mut https_shutdown_receiver: broadcast::Receiver<()>,
 ) {
@@ -419,9 +964,100 @@ async fn start_server(
     let welcome_route = warp::path::end()
         .and(warp::head().or(warp::get()))
         .map(|_| warp::reply::html(include_str!("welcome_page.html")));
+    let info_route = warp::get()
+        .and(warp::path!("realearn" / "info"))
+        .and_then(|| in_main_thread(handle_info_route));
+    let sessions_route = warp::get()
+        .and(warp::path!("realearn" / "sessions"))
+        .and_then(|| in_main_thread(handle_sessions_route));
+    let controller_presets_route = warp::get()
+        .and(warp::path!("realearn" / "controller-presets"))
+        .and_then(|| in_main_thread(handle_controller_presets_route));
+    let main_presets_route = warp::get()
+        .and(warp::path!("realearn" / "main-presets"))
+        .and_then(|| in_main_thread(handle_main_presets_route));
+    let io_ownership_route = warp::get()
+        .and(warp::path!("realearn" / "io-ownership"))
+        .and_then(|| in_main_thread(handle_io_ownership_route));
     let session_route = warp::get()
         .and(warp::path!("realearn" / "session" / String))
         .and_then(|session_id| in_main_thread(|| handle_session_route(percent_decode(session_id))));
+    let full_session_route = warp::get()
+        .and(warp::path!("realearn" / "session" / String / "full"))
+        .and_then(|session_id| {
+            in_main_thread(|| handle_full_session_route(percent_decode(session_id)))
+        });
+    let put_full_session_route = warp::put()
+        .and(warp::path!("realearn" / "session" / String / "full"))
+        .and(warp::body::json())
+        .and_then(|session_id: String, session_data: SessionData| {
+            in_main_thread(move || {
+                handle_put_full_session_route(percent_decode(session_id), session_data)
+            })
+        });
+    let post_session_template_route = warp::post()
+        .and(warp::path!(
+            "realearn" / "session" / String / "template" / String
+        ))
+        .and_then(|session_id: String, template_id: String| {
+            in_main_thread(move || {
+                handle_post_session_template_route(
+                    percent_decode(session_id),
+                    percent_decode(template_id),
+                )
+            })
+        });
+    let guided_mapping_route = warp::get()
+        .and(warp::path!(
+            "realearn" / "session" / String / "guided-mapping"
+        ))
+        .and_then(|session_id: String| {
+            in_main_thread(|| handle_guided_mapping_route(percent_decode(session_id)))
+        });
+    let post_guided_mapping_route = warp::post()
+        .and(warp::path!(
+            "realearn" / "session" / String / "guided-mapping"
+        ))
+        .and(warp::body::json())
+        .and_then(|session_id: String, req: GuidedMappingRequest| {
+            in_main_thread(move || {
+                handle_post_guided_mapping_route(percent_decode(session_id), req)
+            })
+        });
+    let post_guided_mapping_step_route = warp::post()
+        .and(warp::path!(
+            "realearn" / "session" / String / "guided-mapping" / String
+        ))
+        .and_then(|session_id: String, step: String| {
+            in_main_thread(move || {
+                handle_post_guided_mapping_step_route(percent_decode(session_id), step)
+            })
+        });
+    let delete_guided_mapping_route = warp::delete()
+        .and(warp::path!(
+            "realearn" / "session" / String / "guided-mapping"
+        ))
+        .and_then(|session_id: String| {
+            in_main_thread(|| handle_delete_guided_mapping_route(percent_decode(session_id)))
+        });
+    let post_panic_route = warp::post()
+        .and(warp::path!("realearn" / "session" / String / "panic"))
+        .and_then(|session_id: String| {
+            in_main_thread(|| handle_post_panic_route(percent_decode(session_id)))
+        });
+    let mappings_route = warp::get()
+        .and(warp::path!("realearn" / "session" / String / "mappings"))
+        .and(warp::query::<MappingsRequest>())
+        .and_then(|session_id: String, req: MappingsRequest| {
+            in_main_thread(move || handle_mappings_route(percent_decode(session_id), req))
+        });
+    let control_latency_route = warp::get()
+        .and(warp::path!(
+            "realearn" / "session" / String / "control-latency"
+        ))
+        .and_then(|session_id| {
+            in_main_thread(|| handle_control_latency_route(percent_decode(session_id)))
+        });
     let controller_route = warp::get()
         .and(warp::path!("realearn" / "session" / String / "controller"))
         .and_then(|session_id| {
@@ -442,6 +1078,72 @@ async fn start_server(
                 handle_patch_controller_route(percent_decode(controller_id), req)
             })
         });
+    let binary_custom_data_route = warp::get()
+        .and(warp::path!(
+            "realearn" / "controller" / String / "custom-data" / String / "binary"
+        ))
+        .and_then(|controller_id: String, key: String| {
+            in_main_thread(move || {
+                handle_binary_custom_data_route(percent_decode(controller_id), percent_decode(key))
+            })
+        });
+    let put_binary_custom_data_route = warp::put()
+        .and(warp::path!(
+            "realearn" / "controller" / String / "custom-data" / String / "binary"
+        ))
+        .and(warp::header::optional::<String>("content-type"))
+        .and(warp::body::content_length_limit(
+            MAX_BINARY_CUSTOM_DATA_SIZE,
+        ))
+        .and(warp::body::bytes())
+        .and_then(
+            |controller_id: String, key: String, content_type: Option<String>, body: Bytes| {
+                let data = BinaryCustomData {
+                    content_type: content_type
+                        .unwrap_or_else(|| DEFAULT_BINARY_CONTENT_TYPE.to_string()),
+                    bytes: body.to_vec(),
+                };
+                in_main_thread(move || {
+                    handle_put_binary_custom_data_route(
+                        percent_decode(controller_id),
+                        percent_decode(key),
+                        data,
+                    )
+                })
+            },
+        );
+    let orphaned_custom_data_route = warp::get()
+        .and(warp::path!(
+            "realearn" / "controller" / String / "orphaned-custom-data"
+        ))
+        .and_then(|controller_id: String| {
+            in_main_thread(|| handle_orphaned_custom_data_route(percent_decode(controller_id)))
+        });
+    let delete_orphaned_custom_data_route = warp::delete()
+        .and(warp::path!(
+            "realearn" / "controller" / String / "orphaned-custom-data"
+        ))
+        .and_then(|controller_id: String| {
+            in_main_thread(|| {
+                handle_delete_orphaned_custom_data_route(percent_decode(controller_id))
+            })
+        });
+    let post_target_value_route = warp::post()
+        .and(warp::path!(
+            "realearn" / "session" / String / "mapping" / String / "target-value"
+        ))
+        .and(warp::body::json())
+        .and_then(
+            |session_id: String, mapping_key: String, req: TargetValueRequest| {
+                in_main_thread(move || {
+                    handle_post_target_value_route(
+                        percent_decode(session_id),
+                        percent_decode(mapping_key),
+                        req,
+                    )
+                })
+            },
+        );
 
     #[cfg(feature = "realearn-meter")]
     let metrics_route = warp::get()
@@ -488,17 +1190,41 @@ async fn start_server(
             Method::DELETE,
             Method::PATCH,
         ])
-        .allow_header("Content-Type");
-    let routes = welcome_route
-        .or(cert_route)
+        .allow_header("Content-Type")
+        .allow_header("Authorization");
+    // Everything except the welcome page and the certificate requires authentication (if enabled)
+    let protected_routes = info_route
+        .or(sessions_route)
+        .or(controller_presets_route)
+        .or(main_presets_route)
+        .or(io_ownership_route)
         .or(session_route)
+        .or(full_session_route)
+        .or(put_full_session_route)
+        .or(post_session_template_route)
+        .or(guided_mapping_route)
+        .or(post_guided_mapping_route)
+        .or(post_guided_mapping_step_route)
+        .or(delete_guided_mapping_route)
+        .or(mappings_route)
+        .or(control_latency_route)
         .or(controller_route)
         .or(controller_routing_route)
         .or(patch_controller_route)
+        .or(binary_custom_data_route)
+        .or(put_binary_custom_data_route)
+        .or(orphaned_custom_data_route)
+        .or(delete_orphaned_custom_data_route)
+        .or(post_target_value_route)
+        .or(post_panic_route)
         .or(ws_route);
     #[cfg(feature = "realearn-meter")]
-    let routes = routes.or(metrics_route);
-    let routes = routes.with(cors);
+    let protected_routes = protected_routes.or(metrics_route);
+    let routes = welcome_route
+        .or(cert_route)
+        .or(authenticate(auth_token).and(protected_routes))
+        .recover(handle_rejection)
+        .with(cors);
     let (_, http_future) = warp::serve(routes.clone())
         .bind_with_graceful_shutdown(([0, 0, 0, 0], http_port), async move {
             http_shutdown_receiver.recv().await.unwrap()
@@ -571,22 +1297,46 @@ fn get_key_and_cert_paths(ip: IpAddr, cert_dir_path: &Path) -> (PathBuf, PathBuf
     (key_file_path, cert_file_path)
 }
 
+#[derive(Deserialize)]
+struct AuthRequest {
+    token: Option<String>,
+}
+
 #[derive(Deserialize)]
 struct WebSocketRequest {
     topics: String,
 }
 
 #[derive(Deserialize)]
-struct PatchRequest {
-    op: PatchRequestOp,
-    path: String,
-    value: serde_json::value::Value,
+#[serde(untagged)]
+enum PatchRequest {
+    Single(JsonPatchOperation),
+    Multiple(Vec<JsonPatchOperation>),
 }
 
-#[derive(Copy, Clone, Eq, PartialEq, Debug, Deserialize)]
-#[serde(rename_all = "lowercase")]
-enum PatchRequestOp {
-    Replace,
+#[derive(Deserialize)]
+struct MappingsRequest {
+    /// `None` means both compartments.
+    compartment: Option<CompartmentData>,
+    /// Same syntax as the search field in the header panel, e.g. `#tag`.
+    search: Option<String>,
+    #[serde(default)]
+    offset: usize,
+    limit: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct TargetValueRequest {
+    /// Absolute target value between 0.0 and 1.0.
+    value: f64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GuidedMappingRequest {
+    control_element_type: VirtualControlElementType,
+    /// Position (1-based) or name of the control element.
+    control_element_id: String,
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug, Serialize)]
@@ -695,11 +1445,24 @@ pub fn keep_informing_clients_about_session_events(shared_session: &SharedSessio
     .do_async(|session, _| {
         let _ = send_updated_controller_routing(&session.borrow());
     });
+    when(instance_state.io_connection_status_changed())
+        .with(Rc::downgrade(shared_session))
+        .do_async(|session, _| {
+            let _ = send_updated_io_status(&session.borrow());
+        });
     when(App::get().controller_preset_manager().borrow().changed())
         .with(Rc::downgrade(shared_session))
         .do_async(|session, _| {
             let _ = send_updated_active_controller(&session.borrow());
         });
+    when(session.activity())
+        .with(Rc::downgrade(shared_session))
+        .do_sync(|session, activity| {
+            App::get()
+                .server()
+                .borrow()
+                .notify_webhooks(session.borrow().id(), activity);
+        });
     when(session.everything_changed().merge(session.id.changed()))
         .with(Rc::downgrade(shared_session))
         .do_async(|session, _| {
@@ -707,6 +1470,7 @@ pub fn keep_informing_clients_about_session_events(shared_session: &SharedSessio
             let session = session.borrow();
             let _ = send_updated_active_controller(&session);
             let _ = send_updated_controller_routing(&session);
+            let _ = send_updated_io_status(&session);
         });
 }
 
@@ -731,6 +1495,15 @@ fn send_initial_controller_routing(
     client.send(&event)
 }
 
+fn send_initial_io_status(client: &WebSocketClient, session_id: &str) -> Result<(), &'static str> {
+    let event = if let Some(session) = App::get().find_session_by_id(session_id) {
+        get_io_status_updated_event(session_id, Some(&session.borrow()))
+    } else {
+        get_io_status_updated_event(session_id, None)
+    };
+    client.send(&event)
+}
+
 fn send_initial_controller(client: &WebSocketClient, session_id: &str) -> Result<(), &'static str> {
     let event = if let Some(session) = App::get().find_session_by_id(session_id) {
         get_active_controller_updated_event(session_id, Some(&session.borrow()))
@@ -764,15 +1537,45 @@ fn send_updated_controller_routing(session: &Session) -> Result<(), &'static str
     )
 }
 
+fn send_updated_io_status(session: &Session) -> Result<(), &'static str> {
+    send_to_clients_subscribed_to(
+        &Topic::IoStatus {
+            session_id: session.id().to_string(),
+        },
+        || get_io_status_updated_event(session.id(), Some(session)),
+    )
+}
+
 pub fn send_projection_feedback_to_subscribed_clients(
     session_id: &str,
     value: ProjectionFeedbackValue,
 ) -> Result<(), &'static str> {
+    // Old clients only understand numeric values.
+    if let Some(numeric_value) = value.value {
+        send_to_clients_subscribed_to(
+            &Topic::Feedback {
+                session_id: session_id.to_string(),
+            },
+            || get_projection_feedback_event(session_id, value.mapping_key.clone(), numeric_value),
+        )?;
+    }
     send_to_clients_subscribed_to(
-        &Topic::Feedback {
+        &Topic::FeedbackDetails {
             session_id: session_id.to_string(),
         },
-        || get_projection_feedback_event(session_id, value),
+        || get_projection_feedback_details_event(session_id, value),
+    )
+}
+
+pub fn send_target_value_to_subscribed_clients(
+    session: &Session,
+    event: &TargetValueChangedEvent,
+) -> Result<(), &'static str> {
+    send_to_clients_subscribed_to(
+        &Topic::TargetValue {
+            session_id: session.id().to_string(),
+        },
+        || get_target_value_event(session, event),
     )
 }
 
@@ -827,10 +1630,13 @@ fn send_initial_events_for_topic(
         Session { session_id } => send_initial_session(client, session_id),
         ControllerRouting { session_id } => send_initial_controller_routing(client, session_id),
         ActiveController { session_id } => send_initial_controller(client, session_id),
-        Feedback { session_id } => {
+        IoStatus { session_id } => send_initial_io_status(client, session_id),
+        Feedback { session_id } | FeedbackDetails { session_id } => {
             send_initial_feedback(session_id);
             Ok(())
         }
+        // Target values are only sent on change.
+        TargetValue { .. } => Ok(()),
     }
 }
 
@@ -840,6 +1646,9 @@ enum Topic {
     ActiveController { session_id: String },
     ControllerRouting { session_id: String },
     Feedback { session_id: String },
+    FeedbackDetails { session_id: String },
+    TargetValue { session_id: String },
+    IoStatus { session_id: String },
 }
 
 impl TryFrom<&str> for Topic {
@@ -857,6 +1666,15 @@ impl TryFrom<&str> for Topic {
             ["realearn", "session", id, "feedback"] => Topic::Feedback {
                 session_id: id.to_string(),
             },
+            ["realearn", "session", id, "feedback-details"] => Topic::FeedbackDetails {
+                session_id: id.to_string(),
+            },
+            ["realearn", "session", id, "target-value"] => Topic::TargetValue {
+                session_id: id.to_string(),
+            },
+            ["realearn", "session", id, "io-status"] => Topic::IoStatus {
+                session_id: id.to_string(),
+            },
             ["realearn", "session", id] => Topic::Session {
                 session_id: id.to_string(),
             },
@@ -878,16 +1696,55 @@ fn get_active_controller_updated_event(
 
 fn get_projection_feedback_event(
     session_id: &str,
-    feedback_value: ProjectionFeedbackValue,
+    mapping_key: Rc<str>,
+    value: UnitValue,
 ) -> Event<HashMap<Rc<str>, UnitValue>> {
     Event::patch(
         format!("/realearn/session/{}/feedback", session_id),
         hashmap! {
-            feedback_value.mapping_key => feedback_value.value
+            mapping_key => value
+        },
+    )
+}
+
+fn get_projection_feedback_details_event(
+    session_id: &str,
+    feedback_value: ProjectionFeedbackValue,
+) -> Event<HashMap<Rc<str>, ProjectionFeedbackData>> {
+    let data = ProjectionFeedbackData {
+        value: feedback_value.value,
+        text: feedback_value.text,
+        step_count: feedback_value.step_count,
+    };
+    Event::patch(
+        format!("/realearn/session/{}/feedback-details", session_id),
+        hashmap! {
+            feedback_value.mapping_key => data
         },
     )
 }
 
+fn get_target_value_event(
+    session: &Session,
+    event: &TargetValueChangedEvent,
+) -> Event<HashMap<MappingKey, TargetValueData>> {
+    let values = session
+        .find_mapping_and_index_by_id(event.compartment, event.mapping_id)
+        .map(|(_, m)| {
+            let data = TargetValueData {
+                value: event.new_value.to_unit_value(),
+                origin: event.origin,
+            };
+            (m.borrow().key().clone(), data)
+        })
+        .into_iter()
+        .collect();
+    Event::patch(
+        format!("/realearn/session/{}/target-value", session.id()),
+        values,
+    )
+}
+
 fn get_session_updated_event(
     session_id: &str,
     session_data: Option<SessionResponseData>,
@@ -905,6 +1762,48 @@ fn get_controller_routing_updated_event(
     )
 }
 
+fn get_io_status_updated_event(
+    session_id: &str,
+    session: Option<&Session>,
+) -> Event<Option<IoStatusData>> {
+    Event::put(
+        format!("/realearn/session/{}/io-status", session_id),
+        session.map(get_io_status),
+    )
+}
+
+fn get_io_status(session: &Session) -> IoStatusData {
+    let instance_state = session.instance_state().borrow();
+    let status = instance_state.io_connection_status();
+    let mut missing_midi_input_devices: Vec<_> = status
+        .missing_midi_input_devices
+        .iter()
+        .map(|id| id.get())
+        .collect();
+    missing_midi_input_devices.sort_unstable();
+    let mut missing_midi_output_devices: Vec<_> = status
+        .missing_midi_output_devices
+        .iter()
+        .map(|id| id.get())
+        .collect();
+    missing_midi_output_devices.sort_unstable();
+    let mut suspended_mappings: Vec<_> = status
+        .suspended_mappings
+        .iter()
+        .filter_map(|id| {
+            let (_, m) = session.find_mapping_and_index_by_id(id.compartment, id.id)?;
+            let key = m.borrow().key().clone();
+            Some(key)
+        })
+        .collect();
+    suspended_mappings.sort_unstable();
+    IoStatusData {
+        missing_midi_input_devices,
+        missing_midi_output_devices,
+        suspended_mappings,
+    }
+}
+
 fn get_controller(session: &Session) -> Option<ControllerPresetData> {
     let controller = session.active_controller()?;
     Some(ControllerPresetData::from_model(&controller))
@@ -974,6 +1873,34 @@ struct ControllerRouting {
     routes: HashMap<MappingKey, Vec<TargetDescriptor>>,
 }
 
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct IoStatusData {
+    /// IDs of used MIDI input devices which are currently unplugged.
+    missing_midi_input_devices: Vec<u8>,
+    /// IDs of used MIDI output devices which are currently unplugged.
+    missing_midi_output_devices: Vec<u8>,
+    /// Keys of the mappings which are suspended because they use one of the missing devices.
+    suspended_mappings: Vec<MappingKey>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ProjectionFeedbackData {
+    /// `None` if the feedback is textual.
+    value: Option<UnitValue>,
+    text: Option<String>,
+    /// Number of possible values if the value is discrete.
+    step_count: Option<u32>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TargetValueData {
+    value: UnitValue,
+    origin: TargetValueChangeOrigin,
+}
+
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 struct LightMainPresetData {
@@ -981,11 +1908,265 @@ struct LightMainPresetData {
     name: String,
 }
 
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PresetInfoData {
+    id: String,
+    name: String,
+    mapping_count: usize,
+    group_count: usize,
+    /// Keys of the custom data sections (only set for controller presets), e.g. `companion`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    custom_data_keys: Option<Vec<String>>,
+}
+
+impl PresetInfoData {
+    fn from_preset(preset: &impl Preset) -> Self {
+        Self {
+            id: preset.id().to_string(),
+            name: preset.name().to_string(),
+            mapping_count: preset.data().mappings.len(),
+            group_count: preset.data().groups.len(),
+            custom_data_keys: None,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MappingListData {
+    /// Number of matching mappings, not just the ones on this page.
+    total: usize,
+    offset: usize,
+    mappings: Vec<MappingSummaryData>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MappingSummaryData {
+    id: String,
+    key: MappingKey,
+    compartment: CompartmentData,
+    name: String,
+    source_label: String,
+    target_label: String,
+    on: bool,
+}
+
+impl MappingSummaryData {
+    fn from_model(mapping: &MappingModel, session: &Session) -> Self {
+        Self {
+            id: mapping.id().to_string(),
+            key: mapping.key().clone(),
+            compartment: CompartmentData::from_compartment(mapping.compartment()),
+            name: mapping.effective_name(),
+            source_label: mapping.source_model.to_string(),
+            target_label: TargetModelFormatVeryShort(&mapping.target_model).to_string(),
+            on: session.mapping_is_on(mapping.qualified_id()),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum CompartmentData {
+    Controller,
+    Main,
+}
+
+impl CompartmentData {
+    fn from_compartment(compartment: MappingCompartment) -> Self {
+        match compartment {
+            MappingCompartment::ControllerMappings => Self::Controller,
+            MappingCompartment::MainMappings => Self::Main,
+        }
+    }
+
+    fn to_compartment(self) -> MappingCompartment {
+        match self {
+            Self::Controller => MappingCompartment::ControllerMappings,
+            Self::Main => MappingCompartment::MainMappings,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct InfoData {
+    version: String,
+    safe_mode: SafeModeData,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SafeModeData {
+    active: bool,
+    disabled_subsystems: Vec<Subsystem>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LightSessionData {
+    id: String,
+    instance_id: String,
+    /// `None` if the project is not saved yet or the session lives in the monitoring FX chain.
+    project_name: Option<String>,
+    main_preset: Option<LightMainPresetData>,
+}
+
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 // Right now just a placeholder
 struct SessionResponseData {}
 
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct IoOwnershipData {
+    control_inputs: Vec<DeviceOwnershipData>,
+    feedback_outputs: Vec<DeviceOwnershipData>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DeviceOwnershipData {
+    device: DeviceData,
+    /// Highest priority first.
+    users: Vec<DeviceUserData>,
+}
+
+impl DeviceOwnershipData {
+    fn new(
+        device: DeviceData,
+        users: Vec<DeviceUser>,
+        session_ids: &HashMap<InstanceId, String>,
+    ) -> Self {
+        Self {
+            device,
+            users: users
+                .into_iter()
+                .map(|u| DeviceUserData {
+                    session_id: session_ids.get(&u.instance_id).cloned(),
+                    instance_id: u.instance_id.to_string(),
+                    priority: u.priority,
+                    is_owner: u.is_owner,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DeviceData {
+    kind: &'static str,
+    id: String,
+}
+
+impl DeviceData {
+    fn midi(id: u8) -> Self {
+        Self {
+            kind: "midi",
+            id: id.to_string(),
+        }
+    }
+
+    fn osc(id: OscDeviceId) -> Self {
+        Self {
+            kind: "osc",
+            id: id.to_string(),
+        }
+    }
+
+    fn rtp_midi(id: RtpMidiDeviceId) -> Self {
+        Self {
+            kind: "rtpMidi",
+            id: id.to_string(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DeviceUserData {
+    /// `None` if the instance is about to be removed.
+    session_id: Option<String>,
+    instance_id: String,
+    priority: InstancePriority,
+    is_owner: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ControlLatencyData {
+    measuring: bool,
+    /// `None` if no control input has hit a target during the (last) measurement.
+    statistics: Option<ControlLatencyStatisticsInMillis>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct OrphanedCustomDataRefData {
+    control_id: String,
+    mapping_id: String,
+}
+
+impl OrphanedCustomDataRefData {
+    fn from_refs(refs: Vec<OrphanedCustomDataRef>) -> Vec<Self> {
+        refs.into_iter()
+            .map(|r| Self {
+                control_id: r.control_id,
+                mapping_id: r.mapping_id,
+            })
+            .collect()
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GuidedMappingData {
+    control_element: String,
+    step: GuidedMappingStepData,
+    /// `None` as long as no target has been captured.
+    target_label: Option<String>,
+}
+
+impl GuidedMappingData {
+    fn from_state(state: &GuidedMappingState) -> Self {
+        Self {
+            control_element: state.control_element.to_string(),
+            step: match state.step() {
+                GuidedMappingStep::CapturingTarget => GuidedMappingStepData::CapturingTarget,
+                GuidedMappingStep::ReadyToCommit => GuidedMappingStepData::ReadyToCommit,
+            },
+            target_label: state.target.as_ref().map(get_guided_mapping_target_label),
+        }
+    }
+}
+
+fn get_guided_mapping_target_label(target: &ReaperTarget) -> String {
+    let type_name = ReaperTargetType::from_target(target).short_name();
+    match target.track().and_then(|t| t.name()) {
+        None => type_name.to_string(),
+        Some(track_name) => format!("{} ({})", type_name, track_name.into_string()),
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+enum GuidedMappingStepData {
+    CapturingTarget,
+    ReadyToCommit,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GuidedMappingStepResponseData {
+    /// `None` after committing.
+    state: Option<GuidedMappingData>,
+    /// Key of the created mapping, only set after committing.
+    mapping_key: Option<MappingKey>,
+}
+
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 struct TargetDescriptor {
@@ -1040,10 +2221,24 @@ pub fn get_local_ip() -> Option<std::net::IpAddr> {
     }
 }
 
-fn check_port(is_https: bool, port: u16) -> Result<(), String> {
-    if !local_port_available(port) {
-        let msg = format!(
-            r#"{upper_case_port_label} port {port} is not available. Possible causes and solutions:
+/// Returns the given port if it's available, otherwise the first available one of the next
+/// `fallback_count` ports. Never returns `excluded_port`.
+fn find_available_port(
+    is_https: bool,
+    port: u16,
+    fallback_count: u16,
+    excluded_port: u16,
+) -> Result<u16, String> {
+    (0..=fallback_count)
+        .filter_map(|i| port.checked_add(i))
+        .filter(|p| *p != excluded_port)
+        .find(|p| local_port_available(*p))
+        .ok_or_else(|| port_not_available_msg(is_https, port))
+}
+
+fn port_not_available_msg(is_https: bool, port: u16) -> String {
+    format!(
+        r#"{upper_case_port_label} port {port} is not available. Possible causes and solutions:
 
 (1) You are already running another instance of REAPER with ReaLearn.
 
@@ -1058,15 +2253,16 @@ Restart REAPER and ReaLearn.
 Set another {upper_case_port_label} port in "realearn.ini", for example:
   
     server_{lower_case_port_label}_port = {alternate_port}
+
+Or let ReaLearn try the next few ports automatically:
+
+    server_port_fallback_count = 10
 "#,
-            lower_case_port_label = if is_https { "https" } else { "http" },
-            upper_case_port_label = if is_https { "HTTPS" } else { "HTTP" },
-            port = port,
-            alternate_port = if is_https { 40443 } else { 40080 },
-        );
-        return Err(msg);
-    }
-    Ok(())
+        lower_case_port_label = if is_https { "https" } else { "http" },
+        upper_case_port_label = if is_https { "HTTPS" } else { "HTTP" },
+        port = port,
+        alternate_port = if is_https { 40443 } else { 40080 },
+    )
 }
 
 fn local_port_available(port: u16) -> bool {
@@ -0,0 +1,266 @@
+use super::CompartmentData;
+use crate::application::SessionActivity;
+use crate::domain::MappingId;
+use hyper::client::HttpConnector;
+use hyper::header::CONTENT_TYPE;
+use hyper::{Body, Client, Request};
+use hyper_rustls::HttpsConnector;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// Maximum number of attempts to deliver one event to one webhook.
+const MAX_ATTEMPT_COUNT: u32 = 4;
+/// Delay before the first retry. Doubles with each further retry.
+const INITIAL_RETRY_DELAY: Duration = Duration::from_millis(500);
+
+pub type WebhookSender = mpsc::UnboundedSender<WebhookMessage>;
+
+type HttpClient = Client<HttpsConnector<HttpConnector>, Body>;
+
+/// Contents of the webhook config file.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    #[serde(default)]
+    pub webhooks: Vec<Webhook>,
+}
+
+impl WebhookConfig {
+    /// Returns an empty config if the file doesn't exist.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        if !path.exists() {
+            return Ok(Default::default());
+        }
+        let json = fs::read_to_string(path)
+            .map_err(|_| "couldn't read webhook config file".to_string())?;
+        serde_json::from_str(&json)
+            .map_err(|e| format!("webhook config file isn't valid. Details:\n\n{}", e))
+    }
+}
+
+/// A user-defined URL to which events are posted as JSON.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Webhook {
+    pub url: String,
+    /// Empty means all events.
+    #[serde(default)]
+    pub events: Vec<WebhookEventKind>,
+}
+
+impl Webhook {
+    fn is_interested_in(&self, kind: WebhookEventKind) -> bool {
+        self.events.is_empty() || self.events.contains(&kind)
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum WebhookEventKind {
+    PresetLoaded,
+    MappingEnabledChanged,
+    RecordingStarted,
+}
+
+/// The JSON body which is posted to the webhooks.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookMessage {
+    session_id: String,
+    #[serde(flatten)]
+    event: WebhookEvent,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+enum WebhookEvent {
+    #[serde(rename_all = "camelCase")]
+    PresetLoaded {
+        compartment: CompartmentData,
+        preset_id: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    MappingEnabledChanged {
+        compartment: CompartmentData,
+        mapping_id: MappingId,
+        mapping_name: String,
+        is_enabled: bool,
+    },
+    RecordingStarted,
+}
+
+impl WebhookMessage {
+    pub fn new(session_id: String, activity: SessionActivity) -> Self {
+        use SessionActivity::*;
+        let event = match activity {
+            PresetLoaded {
+                compartment,
+                preset_id,
+            } => WebhookEvent::PresetLoaded {
+                compartment: CompartmentData::from_compartment(compartment),
+                preset_id,
+            },
+            MappingEnabledChanged {
+                compartment,
+                mapping_id,
+                mapping_name,
+                is_enabled,
+            } => WebhookEvent::MappingEnabledChanged {
+                compartment: CompartmentData::from_compartment(compartment),
+                mapping_id,
+                mapping_name,
+                is_enabled,
+            },
+            RecordingStarted => WebhookEvent::RecordingStarted,
+        };
+        Self { session_id, event }
+    }
+
+    fn kind(&self) -> WebhookEventKind {
+        use WebhookEvent::*;
+        match &self.event {
+            PresetLoaded { .. } => WebhookEventKind::PresetLoaded,
+            MappingEnabledChanged { .. } => WebhookEventKind::MappingEnabledChanged,
+            RecordingStarted => WebhookEventKind::RecordingStarted,
+        }
+    }
+}
+
+/// Posts each received message to all interested webhooks until the sender is dropped.
+///
+/// Each delivery runs in its own task so a slow or unreachable webhook doesn't hold up the others.
+pub async fn dispatch_webhook_messages(
+    mut receiver: mpsc::UnboundedReceiver<WebhookMessage>,
+    config: WebhookConfig,
+) {
+    let client: HttpClient = Client::builder().build(HttpsConnector::new());
+    while let Some(msg) = receiver.recv().await {
+        let body = match serde_json::to_string(&msg) {
+            Ok(b) => b,
+            Err(e) => {
+                eprintln!("couldn't serialize webhook message: {}", e);
+                continue;
+            }
+        };
+        let kind = msg.kind();
+        for webhook in config.webhooks.iter().filter(|w| w.is_interested_in(kind)) {
+            tokio::spawn(post_with_retry(
+                client.clone(),
+                webhook.url.clone(),
+                body.clone(),
+            ));
+        }
+    }
+}
+
+async fn post_with_retry(client: HttpClient, url: String, body: String) {
+    for attempt in 1..=MAX_ATTEMPT_COUNT {
+        let error = match post(&client, &url, body.clone()).await {
+            Ok(_) => return,
+            Err(PostError::Permanent(msg)) => {
+                eprintln!("webhook {} rejected event: {}", url, msg);
+                return;
+            }
+            Err(PostError::Transient(msg)) => msg,
+        };
+        if attempt == MAX_ATTEMPT_COUNT {
+            eprintln!(
+                "giving up posting event to webhook {} after {} attempts: {}",
+                url, attempt, error
+            );
+            return;
+        }
+        tokio::time::delay_for(retry_delay(attempt)).await;
+    }
+}
+
+/// Returns how long to wait after the given (1-based) failed attempt.
+fn retry_delay(attempt: u32) -> Duration {
+    INITIAL_RETRY_DELAY * 2u32.pow(attempt - 1)
+}
+
+enum PostError {
+    /// Retrying won't help, e.g. invalid URL or client error response.
+    Permanent(String),
+    /// Network errors and server error responses.
+    Transient(String),
+}
+
+async fn post(client: &HttpClient, url: &str, body: String) -> Result<(), PostError> {
+    let request = Request::post(url)
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(body))
+        .map_err(|e| PostError::Permanent(e.to_string()))?;
+    let response = client
+        .request(request)
+        .await
+        .map_err(|e| PostError::Transient(e.to_string()))?;
+    let status = response.status();
+    if status.is_success() {
+        Ok(())
+    } else if status.is_client_error() {
+        Err(PostError::Permanent(status.to_string()))
+    } else {
+        Err(PostError::Transient(status.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_config() {
+        // Given
+        let json = r#"{
+            "webhooks": [
+                { "url": "http://localhost:1880/all" },
+                { "url": "http://localhost:1880/rec", "events": ["recording-started"] }
+            ]
+        }"#;
+        // When
+        let config: WebhookConfig = serde_json::from_str(json).unwrap();
+        // Then
+        let interested: Vec<_> = config
+            .webhooks
+            .iter()
+            .filter(|w| w.is_interested_in(WebhookEventKind::PresetLoaded))
+            .map(|w| w.url.as_str())
+            .collect();
+        assert_eq!(interested, vec!["http://localhost:1880/all"]);
+    }
+
+    #[test]
+    fn message_shape() {
+        // Given
+        let msg = WebhookMessage::new(
+            "abc".to_string(),
+            SessionActivity::PresetLoaded {
+                compartment: crate::domain::MappingCompartment::MainMappings,
+                preset_id: Some("my-preset".to_string()),
+            },
+        );
+        // When
+        let json = serde_json::to_value(&msg).unwrap();
+        // Then
+        assert_eq!(msg.kind(), WebhookEventKind::PresetLoaded);
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "sessionId": "abc",
+                "event": "preset-loaded",
+                "compartment": "main",
+                "presetId": "my-preset"
+            })
+        );
+    }
+
+    #[test]
+    fn retry_backoff() {
+        // Then
+        assert_eq!(retry_delay(1), Duration::from_millis(500));
+        assert_eq!(retry_delay(2), Duration::from_secs(1));
+        assert_eq!(retry_delay(3), Duration::from_secs(2));
+    }
+}
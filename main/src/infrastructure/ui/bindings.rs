@@ -223,4 +223,5 @@ pub mod root {
     pub const IDC_MAPPING_MATCHED_INDICATOR_TEXT: u32 = 40133;
     pub const IDC_MAPPING_ROW_MATCHED_INDICATOR_TEXT: u32 = 40134;
     pub const ID_TARGET_VALUE_OFF_BUTTON: u32 = 40136;
+    pub const ID_SETTINGS_MORE_BUTTON: u32 = 40137;
 }
@@ -80,8 +80,8 @@ impl CompanionAppPresenter {
                 .local_ip()
                 .map(|ip| ip.to_string())
                 .unwrap_or_else(|| "<could not be determined>".to_string()),
-            server_http_port: server.http_port(),
-            server_https_port: server.https_port(),
+            server_http_port: config.server_http_port(),
+            server_https_port: config.server_https_port(),
             session_id: session.id().to_string(),
             os: std::env::consts::OS,
         };
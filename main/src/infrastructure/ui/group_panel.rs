@@ -92,6 +92,26 @@ impl GroupPanel {
                     .invalidate_due_to_changed_prop(ItemProp::EelCondition, initiator);
             },
         );
+        self.when(
+            group
+                .activation_condition_model
+                .expression_condition
+                .changed_with_initiator(),
+            |view, initiator| {
+                view.mapping_header_panel
+                    .invalidate_due_to_changed_prop(ItemProp::ExpressionCondition, initiator);
+            },
+        );
+        self.when(
+            group
+                .activation_condition_model
+                .reaper_state_condition
+                .changed(),
+            |view, _| {
+                view.mapping_header_panel
+                    .invalidate_due_to_changed_prop(ItemProp::ReaperStateCondition, None);
+            },
+        );
     }
 
     fn when<I: Send + Sync + Clone + 'static>(
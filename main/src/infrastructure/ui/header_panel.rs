@@ -22,19 +22,23 @@ use crate::application::{
 };
 use crate::base::when;
 use crate::domain::{
-    ControlInput, GroupId, MappingCompartment, MessageCaptureEvent, OscDeviceId, ReaperTarget,
+    BackboneState, ClipMatrixId, ClipMatrixSize, ControlInput, GroupId, IdleAnimationType,
+    InstancePriority, MappingCompartment, MessageCaptureEvent, OscDeviceId, OscTransport,
+    ParameterLink, ReaperTarget, RtpMidiDeviceId, SlotStopQuantization,
     COMPARTMENT_PARAMETER_COUNT,
 };
 use crate::domain::{MidiControlInput, MidiDestination};
 use crate::infrastructure::data::{
     CompartmentInSession, CompartmentModelData, ExtendedPresetManager, MappingModelData, OscDevice,
+    RtpMidiDevice,
 };
 use crate::infrastructure::plugin::{
-    warn_about_failed_server_start, App, RealearnPluginParameters,
+    warn_about_failed_server_start, App, RealearnPluginParameters, Subsystem,
 };
 
 use crate::infrastructure::ui::bindings::root;
 
+use crate::base::notification;
 use crate::base::notification::notify_processing_result;
 use crate::infrastructure::api::convert::from_data::ConversionStyle;
 use crate::infrastructure::ui::dialog_util::add_group_via_dialog;
@@ -45,6 +49,7 @@ use crate::infrastructure::ui::{
     serialize_data_object, serialize_data_object_to_json, serialize_data_object_to_lua, DataObject,
     GroupFilter, GroupPanel, IndependentPanelManager, MappingRowsPanel, SearchExpression,
     SerializationFormat, SharedIndependentPanelManager, SharedMainState, SourceFilter,
+    YamlEditorPanel,
 };
 use crate::infrastructure::ui::{dialog_util, CompanionAppPresenter};
 use itertools::Itertools;
@@ -54,6 +59,7 @@ use std::error::Error;
 use std::net::Ipv4Addr;
 
 const OSC_INDEX_OFFSET: isize = 1000;
+const RTP_MIDI_INDEX_OFFSET: isize = 2000;
 const PARAM_BATCH_SIZE: u32 = 5;
 
 /// The upper part of the main panel, containing buttons such as "Add mapping".
@@ -66,6 +72,7 @@ pub struct HeaderPanel {
     plugin_parameters: sync::Weak<RealearnPluginParameters>,
     panel_manager: Weak<RefCell<IndependentPanelManager>>,
     group_panel: RefCell<Option<SharedView<GroupPanel>>>,
+    yaml_editor: RefCell<Option<SharedView<YamlEditorPanel>>>,
     is_invoked_programmatically: Cell<bool>,
 }
 
@@ -84,6 +91,7 @@ impl HeaderPanel {
             plugin_parameters,
             panel_manager,
             group_panel: Default::default(),
+            yaml_editor: Default::default(),
             is_invoked_programmatically: false.into(),
         }
     }
@@ -148,6 +156,15 @@ impl HeaderPanel {
         }
     }
 
+    fn learn_many_targets(&self) {
+        self.main_state.borrow_mut().stop_filter_learning();
+        let session = self.session();
+        session
+            .borrow_mut()
+            .start_learning_many_targets(&session, self.active_group_id().unwrap_or_default());
+        self.panel_manager().borrow().open_message_panel();
+    }
+
     fn prompt_for_control_element_type(&self) -> Option<VirtualControlElementType> {
         let menu_bar = MenuBar::load(root::IDR_HEADER_PANEL_ADD_MANY_CONTROLLER_MAPPINGS_MENU)
             .expect("menu bar couldn't be loaded");
@@ -195,14 +212,22 @@ impl HeaderPanel {
             AutoNameListedMappings,
             MakeTargetsOfListedMappingsSticky,
             MakeSourcesOfMainMappingsVirtual,
+            LearnManyTargets,
             MoveListedMappingsToGroup(Option<GroupId>),
+            SetGroupMidiOutputDevice(GroupId, Option<MidiOutputDeviceId>),
             PasteReplaceAllInGroup(Vec<MappingModelData>),
             PasteFromLuaReplaceAllInGroup(String),
             ToggleAutoCorrectSettings,
             ToggleInputLogging,
             ToggleOutputLogging,
             ToggleSendFeedbackOnlyIfTrackArmed,
-            ToggleUpperFloorMembership,
+            ChangeInstancePriority,
+            SetIdleAnimationType(IdleAnimationType),
+            ChangeIdleAnimationTimeout,
+            ResizeClipMatrix(Option<ClipMatrixId>),
+            SetClipStopQuantization(Option<SlotStopQuantization>),
+            ToggleFeedbackLoopSuppression,
+            EditInstanceHooks,
             ToggleServer,
             AddFirewallRule,
             ChangeSessionId,
@@ -216,15 +241,25 @@ impl HeaderPanel {
             OpenWebsite,
             Donate,
             ReloadAllPresets,
+            RemoveOrphanedCustomData,
             EditNewOscDevice,
             EditExistingOscDevice(OscDeviceId),
             RemoveOscDevice(OscDeviceId),
             ToggleOscDeviceControl(OscDeviceId),
             ToggleOscDeviceFeedback(OscDeviceId),
             ToggleOscDeviceBundles(OscDeviceId),
+            ToggleOscDeviceTcp(OscDeviceId),
+            EditNewRtpMidiDevice,
+            EditExistingRtpMidiDevice(RtpMidiDeviceId),
+            RemoveRtpMidiDevice(RtpMidiDeviceId),
+            ToggleRtpMidiDeviceEnabled(RtpMidiDeviceId),
             EditCompartmentParameter(MappingCompartment, u32),
+            EditParameterLink(MappingCompartment, u32),
             SendFeedbackNow,
+            RequestControllerState,
             LogDebugInfo,
+            ToggleControlLatencyMeasurement,
+            EnableSubsystem(Subsystem),
         }
         impl Default for MenuAction {
             fn default() -> Self {
@@ -236,6 +271,8 @@ impl HeaderPanel {
             use swell_ui::menu_tree::*;
             let dev_manager = App::get().osc_device_manager();
             let dev_manager = dev_manager.borrow();
+            let rtp_midi_dev_manager = App::get().rtp_midi_device_manager();
+            let rtp_midi_dev_manager = rtp_midi_dev_manager.borrow();
             let preset_link_manager = App::get().preset_link_manager();
             let preset_link_manager = preset_link_manager.borrow();
             let main_preset_manager = App::get().main_preset_manager();
@@ -250,6 +287,12 @@ impl HeaderPanel {
             let session = session.borrow();
             let compartment = self.active_compartment();
             let group_id = self.active_group_id();
+            // Only set if a particular group is displayed.
+            let group_midi_output_device = group_id.and_then(|id| {
+                let group = session.find_group_by_id_including_default_group(compartment, id)?;
+                let device = group.borrow().midi_output_device.get();
+                Some((id, device))
+            });
             let last_focused_fx_id = App::get().previously_focused_fx().and_then(|fx| {
                 if fx.is_available() {
                     FxId::from_fx(&fx, true).ok()
@@ -257,7 +300,7 @@ impl HeaderPanel {
                     None
                 }
             });
-            let entries = vec![
+            let mut entries = vec![
                 item("Copy listed mappings", || {
                     MenuAction::CopyListedMappingsAsJson
                 }),
@@ -280,6 +323,15 @@ impl HeaderPanel {
                 item("Make targets of listed mappings sticky", || {
                     MenuAction::MakeTargetsOfListedMappingsSticky
                 }),
+                item_with_opts(
+                    "Learn many targets (target first)",
+                    ItemOpts {
+                        enabled: compartment == MappingCompartment::MainMappings
+                            && !session.is_learning_many_mappings(),
+                        checked: false,
+                    },
+                    || MenuAction::LearnManyTargets,
+                ),
                 menu(
                     "Move listed mappings to group",
                     iter::once(item("<New group>", || {
@@ -299,6 +351,45 @@ impl HeaderPanel {
                     }))
                     .collect(),
                 ),
+                menu(
+                    "MIDI output device of group",
+                    iter::once(item_with_opts(
+                        "<Instance feedback output>",
+                        ItemOpts {
+                            enabled: group_midi_output_device.is_some(),
+                            checked: matches!(group_midi_output_device, Some((_, None))),
+                        },
+                        move || match group_midi_output_device {
+                            Some((id, _)) => MenuAction::SetGroupMidiOutputDevice(id, None),
+                            None => MenuAction::None,
+                        },
+                    ))
+                    .chain(
+                        Reaper::get()
+                            .midi_output_devices()
+                            .filter(|d| d.is_available())
+                            .map(|dev| {
+                                let dev_id = dev.id();
+                                item_with_opts(
+                                    get_midi_output_device_label(dev),
+                                    ItemOpts {
+                                        enabled: group_midi_output_device.is_some(),
+                                        checked: matches!(
+                                            group_midi_output_device,
+                                            Some((_, Some(id))) if id == dev_id
+                                        ),
+                                    },
+                                    move || match group_midi_output_device {
+                                        Some((id, _)) => {
+                                            MenuAction::SetGroupMidiOutputDevice(id, Some(dev_id))
+                                        }
+                                        None => MenuAction::None,
+                                    },
+                                )
+                            }),
+                    )
+                    .collect(),
+                ),
                 menu(
                     "Options",
                     vec![
@@ -325,14 +416,90 @@ impl HeaderPanel {
                             },
                             || MenuAction::ToggleSendFeedbackOnlyIfTrackArmed,
                         ),
+                        item(
+                            format!("Instance priority ({})...", session.instance_priority.get()),
+                            || MenuAction::ChangeInstancePriority,
+                        ),
+                        menu(
+                            "Idle animation",
+                            IdleAnimationType::into_enum_iter()
+                                .map(|t| {
+                                    item_with_opts(
+                                        t.to_string(),
+                                        ItemOpts {
+                                            enabled: true,
+                                            checked: session.idle_animation_type.get() == t,
+                                        },
+                                        move || MenuAction::SetIdleAnimationType(t),
+                                    )
+                                })
+                                .chain(once(separator()))
+                                .chain(once(item(
+                                    format!(
+                                        "Start after {} minutes without control input...",
+                                        session.idle_animation_timeout_minutes.get()
+                                    ),
+                                    || MenuAction::ChangeIdleAnimationTimeout,
+                                )))
+                                .collect(),
+                        ),
+                        menu(
+                            "Clip matrix size",
+                            once(item(
+                                format!(
+                                    "Own ({})...",
+                                    session.instance_state().borrow().clip_matrix().size()
+                                ),
+                                || MenuAction::ResizeClipMatrix(None),
+                            ))
+                            .chain(
+                                BackboneState::get()
+                                    .shared_clip_matrix_sizes()
+                                    .into_iter()
+                                    .map(|(id, size)| {
+                                        item(
+                                            format!("Shared \"{}\" ({})...", id, size),
+                                            move || MenuAction::ResizeClipMatrix(Some(id)),
+                                        )
+                                    }),
+                            )
+                            .collect(),
+                        ),
+                        {
+                            let current =
+                                session.instance_state().borrow().clip_stop_quantization();
+                            menu(
+                                "Clip stop quantization",
+                                once(None)
+                                    .chain(SlotStopQuantization::into_enum_iter().map(Some))
+                                    .map(|q| {
+                                        let label = match q {
+                                            None => "Derived from \"Next bar\"".to_string(),
+                                            Some(q) => q.to_string(),
+                                        };
+                                        item_with_opts(
+                                            label,
+                                            ItemOpts {
+                                                enabled: true,
+                                                checked: current == q,
+                                            },
+                                            move || MenuAction::SetClipStopQuantization(q),
+                                        )
+                                    })
+                                    .collect(),
+                            )
+                        },
                         item_with_opts(
-                            "Make instance superior",
+                            "Suppress feedback loops",
                             ItemOpts {
                                 enabled: true,
-                                checked: session.lives_on_upper_floor.get(),
+                                checked: session.suppress_feedback_loops.get(),
                             },
-                            || MenuAction::ToggleUpperFloorMembership,
+                            || MenuAction::ToggleFeedbackLoopSuppression,
                         ),
+                        item("Edit instance lifecycle hooks...", || {
+                            MenuAction::EditInstanceHooks
+                        }),
                     ],
                 ),
                 menu(
@@ -345,14 +512,37 @@ impl HeaderPanel {
                                 format!("Parameters {} - {}", range.start + 1, range.end),
                                 range
                                     .map(|i| {
-                                        item(
+                                        let is_linked = session
+                                            .get_parameter_settings(compartment, i)
+                                            .link
+                                            .is_some();
+                                        menu(
                                             format!(
-                                                "{}...",
-                                                session.get_parameter_name(compartment, i)
+                                                "{}{}",
+                                                session.get_parameter_name(compartment, i),
+                                                if is_linked { " (linked)" } else { "" }
                                             ),
-                                            move || {
-                                                MenuAction::EditCompartmentParameter(compartment, i)
-                                            },
+                                            vec![
+                                                item("Edit names...", move || {
+                                                    MenuAction::EditCompartmentParameter(
+                                                        compartment,
+                                                        i,
+                                                    )
+                                                }),
+                                                item_with_opts(
+                                                    "Follow parameter of other instance...",
+                                                    ItemOpts {
+                                                        enabled: true,
+                                                        checked: is_linked,
+                                                    },
+                                                    move || {
+                                                        MenuAction::EditParameterLink(
+                                                            compartment,
+                                                            i,
+                                                        )
+                                                    },
+                                                ),
+                                            ],
                                         )
                                     })
                                     .collect(),
@@ -440,6 +630,39 @@ impl HeaderPanel {
                                         },
                                         move || MenuAction::ToggleOscDeviceBundles(dev_id),
                                     ),
+                                    item_with_opts(
+                                        "Use TCP (OSC 1.1 SLIP framing)",
+                                        ItemOpts {
+                                            enabled: true,
+                                            checked: dev.transport() == OscTransport::Tcp,
+                                        },
+                                        move || MenuAction::ToggleOscDeviceTcp(dev_id),
+                                    ),
+                                ],
+                            )
+                        }))
+                        .collect(),
+                ),
+                menu(
+                    "RTP-MIDI devices",
+                    once(item("<New>", || MenuAction::EditNewRtpMidiDevice))
+                        .chain(rtp_midi_dev_manager.devices().map(|dev| {
+                            let dev_id = *dev.id();
+                            menu(
+                                dev.name(),
+                                vec![
+                                    item("Edit...", move || {
+                                        MenuAction::EditExistingRtpMidiDevice(dev_id)
+                                    }),
+                                    item("Remove", move || MenuAction::RemoveRtpMidiDevice(dev_id)),
+                                    item_with_opts(
+                                        "Enabled",
+                                        ItemOpts {
+                                            enabled: true,
+                                            checked: dev.is_enabled(),
+                                        },
+                                        move || MenuAction::ToggleRtpMidiDeviceEnabled(dev_id),
+                                    ),
                                 ],
                             )
                         }))
@@ -522,8 +745,24 @@ impl HeaderPanel {
                 item("Reload all presets from disk", || {
                     MenuAction::ReloadAllPresets
                 }),
+                item_with_opts(
+                    "Remove orphaned custom data from controller preset",
+                    ItemOpts {
+                        enabled: session.active_controller_preset_id().is_some(),
+                        checked: false,
+                    },
+                    || MenuAction::RemoveOrphanedCustomData,
+                ),
                 separator(),
                 item("Send feedback now", || MenuAction::SendFeedbackNow),
+                item_with_opts(
+                    "Request controller state",
+                    ItemOpts {
+                        enabled: session.controller_has_state_requests(),
+                        checked: false,
+                    },
+                    || MenuAction::RequestControllerState,
+                ),
                 item("Log debug info", || MenuAction::LogDebugInfo),
                 item_with_opts(
                     "Log incoming messages",
@@ -541,7 +780,34 @@ impl HeaderPanel {
                     },
                     || MenuAction::ToggleOutputLogging,
                 ),
+                item_with_opts(
+                    "Measure control latency",
+                    ItemOpts {
+                        enabled: true,
+                        checked: session.control_latency_measurement_is_running(),
+                    },
+                    || MenuAction::ToggleControlLatencyMeasurement,
+                ),
             ];
+            if App::get().safe_mode().is_active() {
+                entries.push(separator());
+                entries.push(menu(
+                    "Safe mode",
+                    Subsystem::into_enum_iter()
+                        .map(|subsystem| {
+                            let is_enabled = App::get().subsystem_is_enabled(subsystem);
+                            item_with_opts(
+                                subsystem.to_string(),
+                                ItemOpts {
+                                    enabled: !is_enabled,
+                                    checked: is_enabled,
+                                },
+                                move || MenuAction::EnableSubsystem(subsystem),
+                            )
+                        })
+                        .collect(),
+                ));
+            }
             let mut root_menu = root_menu(entries);
             root_menu.index(1);
             fill_menu(menu_bar.menu(), &root_menu);
@@ -570,9 +836,13 @@ impl HeaderPanel {
             MenuAction::MakeTargetsOfListedMappingsSticky => {
                 self.make_targets_of_listed_mappings_sticky()
             }
+            MenuAction::LearnManyTargets => self.learn_many_targets(),
             MenuAction::MoveListedMappingsToGroup(group_id) => {
                 let _ = self.move_listed_mappings_to_group(group_id);
             }
+            MenuAction::SetGroupMidiOutputDevice(group_id, dev_id) => {
+                self.set_group_midi_output_device(group_id, dev_id)
+            }
             MenuAction::PasteReplaceAllInGroup(mapping_datas) => {
                 self.paste_replace_all_in_group(mapping_datas)
             }
@@ -596,16 +866,52 @@ impl HeaderPanel {
             MenuAction::ToggleOscDeviceBundles(dev_id) => {
                 App::get().do_with_osc_device(dev_id, |d| d.toggle_can_deal_with_bundles())
             }
+            MenuAction::ToggleOscDeviceTcp(dev_id) => {
+                App::get().do_with_osc_device(dev_id, |d| d.toggle_tcp())
+            }
+            MenuAction::EditNewRtpMidiDevice => edit_new_rtp_midi_device(),
+            MenuAction::EditExistingRtpMidiDevice(dev_id) => edit_existing_rtp_midi_device(dev_id),
+            MenuAction::RemoveRtpMidiDevice(dev_id) => {
+                remove_rtp_midi_device(self.view.require_window(), dev_id)
+            }
+            MenuAction::ToggleRtpMidiDeviceEnabled(dev_id) => {
+                App::get().do_with_rtp_midi_device(dev_id, |d| d.toggle_enabled())
+            }
             MenuAction::EditCompartmentParameter(compartment, rel_index) => {
                 let _ = edit_compartment_parameter(self.session(), compartment, rel_index);
             }
+            MenuAction::EditParameterLink(compartment, rel_index) => {
+                if let Err(e) = edit_parameter_link(self.session(), compartment, rel_index) {
+                    self.view.require_window().alert("ReaLearn", e);
+                }
+            }
             MenuAction::ToggleAutoCorrectSettings => self.toggle_always_auto_detect(),
             MenuAction::ToggleInputLogging => self.toggle_input_logging(),
             MenuAction::ToggleOutputLogging => self.toggle_output_logging(),
+            MenuAction::ToggleControlLatencyMeasurement => {
+                self.session().borrow().toggle_control_latency_measurement()
+            }
             MenuAction::ToggleSendFeedbackOnlyIfTrackArmed => {
                 self.toggle_send_feedback_only_if_armed()
             }
-            MenuAction::ToggleUpperFloorMembership => self.toggle_upper_floor_membership(),
+            MenuAction::ChangeInstancePriority => self.change_instance_priority(),
+            MenuAction::SetIdleAnimationType(t) => {
+                self.session().borrow_mut().idle_animation_type.set(t)
+            }
+            MenuAction::ChangeIdleAnimationTimeout => self.change_idle_animation_timeout(),
+            MenuAction::ResizeClipMatrix(matrix_id) => {
+                if let Err(e) = self.resize_clip_matrix(matrix_id) {
+                    self.view.require_window().alert("ReaLearn", e);
+                }
+            }
+            MenuAction::SetClipStopQuantization(q) => self
+                .session()
+                .borrow()
+                .instance_state()
+                .borrow_mut()
+                .set_clip_stop_quantization(q),
+            MenuAction::ToggleFeedbackLoopSuppression => self.toggle_feedback_loop_suppression(),
+            MenuAction::EditInstanceHooks => self.edit_instance_hooks(),
             MenuAction::ToggleServer => {
                 enum ServerAction {
                     Start,
@@ -678,8 +984,17 @@ impl HeaderPanel {
             MenuAction::OpenWebsite => self.open_website(),
             MenuAction::Donate => self.donate(),
             MenuAction::ReloadAllPresets => self.reload_all_presets(),
+            MenuAction::RemoveOrphanedCustomData => {
+                if let Err(e) = self.remove_orphaned_custom_data() {
+                    self.view.require_window().alert("ReaLearn", e);
+                }
+            }
             MenuAction::SendFeedbackNow => self.session().borrow().send_all_feedback(),
+            MenuAction::RequestControllerState => {
+                self.session().borrow().request_controller_state()
+            }
             MenuAction::LogDebugInfo => self.log_debug_info(),
+            MenuAction::EnableSubsystem(subsystem) => app.enable_subsystem(subsystem),
             MenuAction::EditPresetLinkFxId(fx_id) => edit_preset_link_fx_id(fx_id),
             MenuAction::RemovePresetLink(fx_id) => remove_preset_link(fx_id),
             MenuAction::LinkToPreset(fx_id, preset_id) => link_to_preset(fx_id, preset_id),
@@ -826,6 +1141,17 @@ impl HeaderPanel {
             .collect()
     }
 
+    fn set_group_midi_output_device(&self, group_id: GroupId, dev_id: Option<MidiOutputDeviceId>) {
+        let group = self
+            .session()
+            .borrow()
+            .find_group_by_id_including_default_group(self.active_compartment(), group_id)
+            .cloned();
+        if let Some(group) = group {
+            group.borrow_mut().midi_output_device.set(dev_id);
+        }
+    }
+
     fn paste_from_lua_replace_all_in_group(&self, text: &str) {
         if let Err(e) = self.paste_from_lua_replace_all_in_group_internal(text) {
             self.view.require_window().alert("ReaLearn", e.to_string());
@@ -1009,6 +1335,37 @@ impl HeaderPanel {
             .set_with(|prev| !*prev);
     }
 
+    fn toggle_feedback_loop_suppression(&self) {
+        self.session()
+            .borrow_mut()
+            .suppress_feedback_loops
+            .set_with(|prev| !*prev);
+    }
+
+    fn edit_instance_hooks(&self) {
+        let initial_value = self.session().borrow().instance_hooks().cloned();
+        let weak_session = self.session.clone();
+        let editor = YamlEditorPanel::new(initial_value, move |yaml_mapping| {
+            let session = match weak_session.upgrade() {
+                None => return,
+                Some(s) => s,
+            };
+            let result = session.borrow_mut().set_instance_hooks(yaml_mapping, true);
+            if let Err(e) = result {
+                notification::alert(format!(
+                    "Your changes have been applied and saved but they contain the following error and therefore won't have any effect:\n\n{}",
+                    e
+                ));
+            };
+        });
+        let editor = SharedView::new(editor);
+        let editor_clone = editor.clone();
+        if let Some(existing_editor) = self.yaml_editor.replace(Some(editor)) {
+            existing_editor.close();
+        };
+        editor_clone.open(self.view.require_window());
+    }
+
     fn toggle_always_auto_detect(&self) {
         self.session()
             .borrow_mut()
@@ -1030,18 +1387,25 @@ impl HeaderPanel {
             .set_with(|prev| !*prev);
     }
 
-    fn toggle_upper_floor_membership(&self) {
-        let enabled = {
-            let session = self.session();
-            let mut session = session.borrow_mut();
-            let new_state = !session.lives_on_upper_floor.get();
-            session.lives_on_upper_floor.set(new_state);
-            new_state
+    fn change_instance_priority(&self) {
+        let current_priority = { self.session().borrow().instance_priority.get() };
+        let new_priority =
+            match dialog_util::prompt_for("Instance priority", &current_priority.to_string()) {
+                None => return,
+                Some(p) => p,
+            };
+        let new_priority: InstancePriority = match new_priority.trim().parse() {
+            Ok(p) => p,
+            _ => return,
         };
-        if enabled {
-            let msg = "This ReaLearn instance is now superior. When this instance is active (contains active main mappings), it will disable other ReaLearn instances with the same control input and/or feedback output that don't have this setting turned on.";
+        self.session()
+            .borrow_mut()
+            .instance_priority
+            .set(new_priority);
+        if new_priority > current_priority {
+            let msg = "When this instance is active (contains active main mappings), it will disable other ReaLearn instances with the same control input and/or feedback output that have a lower priority.";
             self.view.require_window().alert("ReaLearn", msg);
-        };
+        }
     }
 
     fn fill_all_controls(&self) {
@@ -1348,7 +1712,8 @@ impl HeaderPanel {
                 osc_devices
                     .enumerate()
                     .map(|(i, dev)| (OSC_INDEX_OFFSET + i as isize, dev.get_list_label(false))),
-            ),
+            )
+            .chain(generate_rtp_midi_device_entries()),
         )
     }
 
@@ -1381,6 +1746,7 @@ impl HeaderPanel {
                 .unwrap_or_else(|_| {
                     b.select_new_combo_box_item(format!("{}. <Unknown>", dev_id.get()));
                 }),
+            RtpMidi(dev_id) => select_rtp_midi_device_combo_box_item(b, dev_id),
         };
     }
 
@@ -1417,7 +1783,8 @@ impl HeaderPanel {
                 osc_devices
                     .enumerate()
                     .map(|(i, dev)| (OSC_INDEX_OFFSET + i as isize, dev.get_list_label(true))),
-            ),
+            )
+            .chain(generate_rtp_midi_device_entries()),
         )
     }
 
@@ -1456,6 +1823,7 @@ impl HeaderPanel {
                     .unwrap_or_else(|_| {
                         b.select_new_combo_box_item(format!("{}. <Unknown>", dev_id.get()));
                     }),
+                RtpMidi(dev_id) => select_rtp_midi_device_combo_box_item(b, dev_id),
             },
         };
     }
@@ -1498,6 +1866,21 @@ impl HeaderPanel {
                     session.midi_control_input.set(MidiControlInput::FxInput);
                     true
                 }
+                rtp_midi_dev_index if rtp_midi_dev_index >= RTP_MIDI_INDEX_OFFSET => {
+                    if let Some(dev) = App::get()
+                        .rtp_midi_device_manager()
+                        .borrow()
+                        .find_device_by_index((rtp_midi_dev_index - RTP_MIDI_INDEX_OFFSET) as usize)
+                    {
+                        session.osc_input_device_id.set(None);
+                        session
+                            .midi_control_input
+                            .set(MidiControlInput::RtpMidi(*dev.id()));
+                        true
+                    } else {
+                        false
+                    }
+                }
                 osc_dev_index if osc_dev_index >= OSC_INDEX_OFFSET => {
                     if let Some(dev) = App::get()
                         .osc_device_manager()
@@ -1549,6 +1932,21 @@ impl HeaderPanel {
                     session.midi_feedback_output.set(None);
                     true
                 }
+                rtp_midi_dev_index if rtp_midi_dev_index >= RTP_MIDI_INDEX_OFFSET => {
+                    if let Some(dev) = App::get()
+                        .rtp_midi_device_manager()
+                        .borrow()
+                        .find_device_by_index((rtp_midi_dev_index - RTP_MIDI_INDEX_OFFSET) as usize)
+                    {
+                        session.osc_output_device_id.set(None);
+                        session
+                            .midi_feedback_output
+                            .set(Some(MidiDestination::RtpMidi(*dev.id())));
+                        true
+                    } else {
+                        false
+                    }
+                }
                 osc_dev_index if osc_dev_index >= OSC_INDEX_OFFSET => {
                     if let Some(dev) = App::get()
                         .osc_device_manager()
@@ -1827,7 +2225,9 @@ impl HeaderPanel {
                     "ReaLearn",
                     "Do you want to continue replacing the complete ReaLearn session with the data in the clipboard?",
                 ) {
-                    plugin_parameters.apply_session_data(&*d);
+                    if let Err(e) = plugin_parameters.apply_session_data(&*d) {
+                        notification::warn(e.to_string());
+                    }
                 }
             }
             DataObject::MainCompartment(Envelope {value}) => {
@@ -2023,6 +2423,30 @@ impl HeaderPanel {
         let _ = App::get().main_preset_manager().borrow_mut().load_presets();
     }
 
+    fn remove_orphaned_custom_data(&self) -> Result<(), &'static str> {
+        let controller_id = self
+            .session()
+            .borrow()
+            .active_controller_preset_id()
+            .ok_or("no controller preset active")?
+            .to_string();
+        let removed = App::get()
+            .controller_preset_manager()
+            .borrow_mut()
+            .remove_orphaned_custom_data_refs(&controller_id)?;
+        let msg = if removed.is_empty() {
+            "The controller preset doesn't contain orphaned custom data.".to_string()
+        } else {
+            format!(
+                "Removed {} references to mappings which don't exist anymore:\n\n{}",
+                removed.len(),
+                removed.iter().join("\n")
+            )
+        };
+        self.view.require_window().alert("ReaLearn", msg);
+        Ok(())
+    }
+
     fn make_mappings_project_independent_if_desired(&self) {
         let session = self.session();
         let compartment = self.active_compartment();
@@ -2072,6 +2496,60 @@ impl HeaderPanel {
         Ok(())
     }
 
+    fn change_idle_animation_timeout(&self) {
+        let current_minutes = { self.session().borrow().idle_animation_timeout_minutes.get() };
+        let new_minutes = match dialog_util::prompt_for(
+            "Idle animation timeout (minutes)",
+            &current_minutes.to_string(),
+        ) {
+            None => return,
+            Some(n) => n,
+        };
+        let new_minutes: u32 = match new_minutes.trim().parse() {
+            Ok(m) if m > 0 => m,
+            _ => return,
+        };
+        self.session()
+            .borrow_mut()
+            .idle_animation_timeout_minutes
+            .set(new_minutes);
+    }
+
+    fn resize_clip_matrix(&self, matrix_id: Option<ClipMatrixId>) -> Result<(), &'static str> {
+        let shared_session = self.session();
+        let instance_state = shared_session.borrow().instance_state().clone();
+        let current_size = match &matrix_id {
+            None => instance_state.borrow().clip_matrix().size(),
+            Some(id) => BackboneState::get().with_shared_clip_matrix(id, |m| m.size()),
+        };
+        let input = match dialog_util::prompt_for(
+            "Clip matrix size (rows x columns)",
+            &current_size.to_string(),
+        ) {
+            None => return Ok(()),
+            Some(i) => i,
+        };
+        let new_size: ClipMatrixSize = input.parse()?;
+        // Clip targets address slots by row and column, so they need to be resolved again.
+        match &matrix_id {
+            None => {
+                instance_state.borrow_mut().resize_clip_matrix(new_size)?;
+                shared_session
+                    .borrow_mut()
+                    .notify_everything_has_changed(Rc::downgrade(&shared_session));
+            }
+            Some(id) => {
+                BackboneState::get().resize_shared_clip_matrix(id, new_size)?;
+                for session in App::get().sessions() {
+                    session
+                        .borrow_mut()
+                        .notify_everything_has_changed(Rc::downgrade(&session));
+                }
+            }
+        }
+        Ok(())
+    }
+
     fn change_session_id(&self) {
         let current_session_id = { self.session().borrow().id.get_ref().clone() };
         let new_session_id = match dialog_util::prompt_for("Session ID", &current_session_id) {
@@ -2122,15 +2600,26 @@ impl HeaderPanel {
         let compartment_model = session.extract_compartment_model(compartment);
         match compartment {
             MappingCompartment::ControllerMappings => {
-                let custom_data = session
-                    .active_controller()
+                let active_controller = session.active_controller();
+                let custom_data = active_controller
+                    .as_ref()
                     .map(|c| c.custom_data().clone())
                     .unwrap_or_default();
+                let midi_macros = active_controller
+                    .as_ref()
+                    .map(|c| c.midi_macros().clone())
+                    .unwrap_or_default();
+                let state_requests = active_controller
+                    .as_ref()
+                    .map(|c| c.state_requests().to_vec())
+                    .unwrap_or_default();
                 let controller = ControllerPreset::new(
                     preset_id.clone(),
                     preset_name,
                     compartment_model,
                     custom_data,
+                    midi_macros,
+                    state_requests,
                 );
                 App::get()
                     .controller_preset_manager()
@@ -2311,6 +2800,7 @@ impl HeaderPanel {
                 .osc_device_manager()
                 .borrow()
                 .changed()
+                .merge(App::get().rtp_midi_device_manager().borrow().changed())
                 .take_until(self.view.closed()),
         )
         .with(Rc::downgrade(&self))
@@ -2451,7 +2941,7 @@ impl View for HeaderPanel {
     }
 }
 
-fn get_midi_input_device_label(dev: MidiInputDevice) -> String {
+pub(crate) fn get_midi_input_device_label(dev: MidiInputDevice) -> String {
     get_midi_device_label(
         dev.name(),
         dev.id().get(),
@@ -2459,7 +2949,7 @@ fn get_midi_input_device_label(dev: MidiInputDevice) -> String {
     )
 }
 
-fn get_midi_output_device_label(dev: MidiOutputDevice) -> String {
+pub(crate) fn get_midi_output_device_label(dev: MidiOutputDevice) -> String {
     get_midi_device_label(
         dev.name(),
         dev.id().get(),
@@ -2523,6 +3013,47 @@ fn generate_osc_device_heading(device_count: usize) -> String {
     )
 }
 
+fn generate_rtp_midi_device_heading(device_count: usize) -> String {
+    format!(
+        "----  RTP-MIDI  ----{}",
+        if device_count == 0 {
+            " (add devices via right-click menu)"
+        } else {
+            ""
+        }
+    )
+}
+
+fn generate_rtp_midi_device_entries() -> Vec<(isize, String)> {
+    let dev_manager = App::get().rtp_midi_device_manager();
+    let dev_manager = dev_manager.borrow();
+    let devices = dev_manager.devices();
+    iter::once((-100isize, generate_rtp_midi_device_heading(devices.len())))
+        .chain(devices.enumerate().map(|(i, dev)| {
+            let peer_names = App::get().rtp_midi_peer_names(*dev.id());
+            (
+                RTP_MIDI_INDEX_OFFSET + i as isize,
+                dev.get_list_label(&peer_names),
+            )
+        }))
+        .collect()
+}
+
+fn select_rtp_midi_device_combo_box_item(b: Window, dev_id: RtpMidiDeviceId) {
+    match App::get()
+        .rtp_midi_device_manager()
+        .borrow()
+        .find_index_by_id(&dev_id)
+    {
+        None => {
+            b.select_new_combo_box_item(format!("<Not present> ({})", dev_id));
+        }
+        Some(i) => b
+            .select_combo_box_item_by_data(RTP_MIDI_INDEX_OFFSET + i as isize)
+            .unwrap(),
+    };
+}
+
 fn edit_preset_link_fx_id(old_fx_id: FxId) {
     let new_fx_id = match edit_fx_id(&old_fx_id) {
         Ok(d) => d,
@@ -2630,6 +3161,52 @@ fn remove_osc_device(parent_window: Window, dev_id: OscDeviceId) {
         .unwrap();
 }
 
+fn edit_new_rtp_midi_device() {
+    let dev = match edit_rtp_midi_device(RtpMidiDevice::default()) {
+        Ok(d) => d,
+        Err(EditRtpMidiDevError::Cancelled) => return,
+        res => res.unwrap(),
+    };
+    App::get()
+        .rtp_midi_device_manager()
+        .borrow_mut()
+        .add_device(dev)
+        .unwrap();
+}
+
+fn edit_existing_rtp_midi_device(dev_id: RtpMidiDeviceId) {
+    let dev = App::get()
+        .rtp_midi_device_manager()
+        .borrow()
+        .find_device_by_id(&dev_id)
+        .unwrap()
+        .clone();
+    let dev = match edit_rtp_midi_device(dev) {
+        Ok(d) => d,
+        Err(EditRtpMidiDevError::Cancelled) => return,
+        res => res.unwrap(),
+    };
+    App::get()
+        .rtp_midi_device_manager()
+        .borrow_mut()
+        .update_device(dev)
+        .unwrap();
+}
+
+fn remove_rtp_midi_device(parent_window: Window, dev_id: RtpMidiDeviceId) {
+    if !parent_window.confirm(
+        "ReaLearn",
+        "Do you really want to remove this RTP-MIDI device? This is a global action. As a consequence, all existing ReaLearn instances which use this device will point to a device that doesn't exist anymore.",
+    ) {
+        return;
+    }
+    App::get()
+        .rtp_midi_device_manager()
+        .borrow_mut()
+        .remove_device_by_id(dev_id)
+        .unwrap();
+}
+
 fn edit_compartment_parameter(
     session: SharedSession,
     compartment: MappingCompartment,
@@ -2653,6 +3230,58 @@ fn edit_compartment_parameter(
     Ok(())
 }
 
+fn edit_parameter_link(
+    session: SharedSession,
+    compartment: MappingCompartment,
+    rel_index: u32,
+) -> Result<(), &'static str> {
+    let current_setting = session
+        .borrow()
+        .get_parameter_settings(compartment, rel_index)
+        .clone();
+    let initial_csv = match &current_setting.link {
+        None => ";1;0;1".to_owned(),
+        Some(l) => format!("{};{};{};{}", l.session_id, l.index + 1, l.offset, l.scale),
+    };
+    let csv = Reaper::get()
+        .medium_reaper()
+        .get_user_inputs(
+            "ReaLearn",
+            4,
+            "Leader session ID (empty = none),Leader parameter (1-100),Offset,Scale,separator=;,extrawidth=80",
+            initial_csv,
+            512,
+        )
+        .ok_or("cancelled")?;
+    let parts: Vec<_> = csv.to_str().split(';').map(|p| p.trim()).collect();
+    if parts.len() != 4 {
+        return Err("unexpected number of values");
+    }
+    let link = if parts[0].is_empty() {
+        None
+    } else {
+        let number: u32 = parts[1].parse().map_err(|_| "invalid parameter number")?;
+        if number < 1 || number > COMPARTMENT_PARAMETER_COUNT {
+            return Err("parameter number out of range");
+        }
+        let link = ParameterLink {
+            session_id: parts[0].to_owned(),
+            index: number - 1,
+            offset: parts[2].parse().map_err(|_| "invalid offset")?,
+            scale: parts[3].parse().map_err(|_| "invalid scale")?,
+        };
+        Some(link)
+    };
+    let new_setting = ParameterSetting {
+        link,
+        ..current_setting
+    };
+    session
+        .borrow_mut()
+        .set_parameter_settings(compartment, iter::once((rel_index, new_setting)));
+    Ok(())
+}
+
 #[derive(Debug)]
 enum EditOscDevError {
     Cancelled,
@@ -2685,8 +3314,8 @@ fn edit_compartment_parameter_internal(
         .split(';')
         .zip(settings)
         .map(|(name, old_setting)| ParameterSetting {
-            key: old_setting.key.clone(),
             name: name.trim().to_owned(),
+            ..old_setting.clone()
         })
         .collect();
     if out_settings.len() != settings.len() {
@@ -2723,3 +3352,34 @@ fn edit_osc_device(mut dev: OscDevice) -> Result<OscDevice, EditOscDevError> {
         Err(EditOscDevError::Unexpected("couldn't split"))
     }
 }
+
+fn edit_rtp_midi_device(mut dev: RtpMidiDevice) -> Result<RtpMidiDevice, EditRtpMidiDevError> {
+    let csv = Reaper::get()
+        .medium_reaper()
+        .get_user_inputs(
+            "ReaLearn",
+            2,
+            "Name,Local port (e.g. 5004),separator=;,extrawidth=80",
+            format!(
+                "{};{}",
+                dev.name(),
+                dev.local_port().map(|p| p.to_string()).unwrap_or_default(),
+            ),
+            512,
+        )
+        .ok_or(EditRtpMidiDevError::Cancelled)?;
+    let splitted: Vec<_> = csv.to_str().split(';').collect();
+    if let [name, local_port] = splitted.as_slice() {
+        dev.set_name(name.to_string());
+        dev.set_local_port(local_port.parse::<u16>().ok());
+        Ok(dev)
+    } else {
+        Err(EditRtpMidiDevError::Unexpected("couldn't split"))
+    }
+}
+
+#[derive(Debug)]
+enum EditRtpMidiDevError {
+    Cancelled,
+    Unexpected(&'static str),
+}
@@ -29,7 +29,7 @@ impl IndependentPanelManager {
 
     pub fn handle_changed_target_value(&self, event: TargetValueChangedEvent) {
         self.do_with_mapping_panel(event.compartment, event.mapping_id, |p| {
-            p.handle_changed_target_value(event.targets, event.new_value)
+            p.handle_changed_target_value(event.targets, event.new_value, event.origin)
         });
     }
 
@@ -16,7 +16,9 @@ use crate::domain::{
     TargetValueChangedEvent,
 };
 use crate::infrastructure::plugin::{App, RealearnPluginParameters};
-use crate::infrastructure::server::send_projection_feedback_to_subscribed_clients;
+use crate::infrastructure::server::{
+    send_projection_feedback_to_subscribed_clients, send_target_value_to_subscribed_clients,
+};
 use crate::infrastructure::ui::util::{format_tags_as_csv, parse_tags_from_csv};
 use rxrust::prelude::*;
 use std::borrow::Cow;
@@ -286,7 +288,8 @@ impl SessionUi for Weak<MainPanel> {
         upgrade_panel(self).edit_mapping(compartment, mapping_id);
     }
 
-    fn target_value_changed(&self, event: TargetValueChangedEvent) {
+    fn target_value_changed(&self, session: &Session, event: TargetValueChangedEvent) {
+        let _ = send_target_value_to_subscribed_clients(session, &event);
         upgrade_panel(self).handle_changed_target_value(event);
     }
 
@@ -11,7 +11,7 @@ use crate::application::{
     ActivationType, BankConditionModel, GroupModel, MappingModel, ModifierConditionModel,
     SharedSession, WeakSession,
 };
-use crate::domain::{MappingCompartment, Tag, COMPARTMENT_PARAMETER_COUNT};
+use crate::domain::{MappingCompartment, ReaperStateCondition, Tag, COMPARTMENT_PARAMETER_COUNT};
 use std::fmt::Debug;
 use swell_ui::{DialogUnits, Point, SharedView, View, ViewContext, Window};
 
@@ -49,6 +49,10 @@ pub trait Item: Debug {
     fn set_bank_condition(&mut self, value: BankConditionModel);
     fn eel_condition(&self) -> &str;
     fn set_eel_condition(&mut self, value: String, initiator: u32);
+    fn expression_condition(&self) -> &str;
+    fn set_expression_condition(&mut self, value: String, initiator: u32);
+    fn reaper_state_condition(&self) -> ReaperStateCondition;
+    fn set_reaper_state_condition(&mut self, value: ReaperStateCondition);
 }
 
 pub enum ItemProp {
@@ -61,6 +65,8 @@ pub enum ItemProp {
     ModifierCondition2,
     BankCondition,
     EelCondition,
+    ExpressionCondition,
+    ReaperStateCondition,
 }
 
 impl MappingHeaderPanel {
@@ -167,7 +173,7 @@ impl MappingHeaderPanel {
         self.invalidate_activation_type_combo_box(item);
         self.invalidate_activation_setting_1_controls(item);
         self.invalidate_activation_setting_2_controls(item);
-        self.invalidate_activation_eel_condition_edit_control(item, None);
+        self.invalidate_activation_condition_edit_control(item, None);
     }
 
     fn invalidate_activation_control_appearance(&self, item: &dyn Item) {
@@ -182,7 +188,8 @@ impl MappingHeaderPanel {
             Always => None,
             Modifiers => Some(("Modifier A", "Modifier B")),
             Bank => Some(("Parameter", "Bank")),
-            Eel => None,
+            Eel | Expression => None,
+            ReaperState => Some(("State", "")),
         };
         if let Some((first, second)) = label {
             self.view
@@ -222,6 +229,11 @@ impl MappingHeaderPanel {
                         (0..=99).map(|i| (i as isize, i.to_string())).collect(),
                     )
             }
+            ReaperState => {
+                self.view
+                    .require_control(root::ID_MAPPING_ACTIVATION_SETTING_1_COMBO_BOX)
+                    .fill_combo_box_indexed(ReaperStateCondition::into_enum_iter());
+            }
             _ => {}
         };
     }
@@ -238,10 +250,17 @@ impl MappingHeaderPanel {
         );
         self.show_if(
             show && (activation_type == ActivationType::Modifiers
-                || activation_type == ActivationType::Bank),
+                || activation_type == ActivationType::Bank
+                || activation_type == ActivationType::ReaperState),
             &[
                 root::ID_MAPPING_ACTIVATION_SETTING_1_LABEL_TEXT,
                 root::ID_MAPPING_ACTIVATION_SETTING_1_COMBO_BOX,
+            ],
+        );
+        self.show_if(
+            show && (activation_type == ActivationType::Modifiers
+                || activation_type == ActivationType::Bank),
+            &[
                 root::ID_MAPPING_ACTIVATION_SETTING_2_LABEL_TEXT,
                 root::ID_MAPPING_ACTIVATION_SETTING_2_COMBO_BOX,
             ],
@@ -254,7 +273,8 @@ impl MappingHeaderPanel {
             ],
         );
         self.show_if(
-            show && activation_type == ActivationType::Eel,
+            show && (activation_type == ActivationType::Eel
+                || activation_type == ActivationType::Expression),
             &[
                 root::ID_MAPPING_ACTIVATION_EEL_LABEL_TEXT,
                 root::ID_MAPPING_ACTIVATION_EDIT_CONTROL,
@@ -286,6 +306,12 @@ impl MappingHeaderPanel {
                     .select_combo_box_item_by_index(param_index as _)
                     .unwrap();
             }
+            ReaperState => {
+                self.view
+                    .require_control(root::ID_MAPPING_ACTIVATION_SETTING_1_COMBO_BOX)
+                    .select_combo_box_item_by_index(item.reaper_state_condition().into())
+                    .unwrap();
+            }
             _ => {}
         };
     }
@@ -388,13 +414,18 @@ impl MappingHeaderPanel {
         );
     }
 
-    fn update_activation_eel_condition(&self, item: &mut dyn Item) {
+    fn update_activation_condition_text(&self, item: &mut dyn Item) {
         let value = self
             .view
             .require_control(root::ID_MAPPING_ACTIVATION_EDIT_CONTROL)
             .text()
             .unwrap_or_else(|_| "".to_string());
-        item.set_eel_condition(value, root::ID_MAPPING_ACTIVATION_EDIT_CONTROL);
+        let initiator = root::ID_MAPPING_ACTIVATION_EDIT_CONTROL;
+        if item.activation_type() == ActivationType::Expression {
+            item.set_expression_condition(value, initiator);
+        } else {
+            item.set_eel_condition(value, initiator);
+        }
     }
 
     fn update_activation_type(&self, item: &mut dyn Item) {
@@ -426,6 +457,16 @@ impl MappingHeaderPanel {
                 let value = b.selected_combo_box_item_index() as u32;
                 item.set_bank_condition(item.bank_condition().with_param_index(value));
             }
+            ReaperState => {
+                let b = self
+                    .view
+                    .require_control(root::ID_MAPPING_ACTIVATION_SETTING_1_COMBO_BOX);
+                let value = b
+                    .selected_combo_box_item_index()
+                    .try_into()
+                    .expect("invalid REAPER state condition");
+                item.set_reaper_state_condition(value);
+            }
             _ => {}
         };
     }
@@ -468,7 +509,7 @@ impl MappingHeaderPanel {
         set(item, current.with_param_index(value));
     }
 
-    fn invalidate_activation_eel_condition_edit_control(
+    fn invalidate_activation_condition_edit_control(
         &self,
         item: &dyn Item,
         initiator: Option<u32>,
@@ -476,9 +517,17 @@ impl MappingHeaderPanel {
         if initiator == Some(root::ID_MAPPING_ACTIVATION_EDIT_CONTROL) {
             return;
         }
+        let (label, text) = if item.activation_type() == ActivationType::Expression {
+            ("Expression (e.g. p1 > 0)", item.expression_condition())
+        } else {
+            ("EEL (e.g. y = p1 > 0)", item.eel_condition())
+        };
+        self.view
+            .require_control(root::ID_MAPPING_ACTIVATION_EEL_LABEL_TEXT)
+            .set_text(label);
         self.view
             .require_control(root::ID_MAPPING_ACTIVATION_EDIT_CONTROL)
-            .set_text(item.eel_condition());
+            .set_text(text);
     }
 
     fn show_if(&self, condition: bool, control_resource_ids: &[u32]) {
@@ -518,9 +567,10 @@ impl MappingHeaderPanel {
                         self.invalidate_activation_setting_1_controls(item);
                         self.invalidate_activation_setting_2_controls(item);
                     }
-                    EelCondition => {
-                        self.invalidate_activation_eel_condition_edit_control(item, initiator)
+                    EelCondition | ExpressionCondition => {
+                        self.invalidate_activation_condition_edit_control(item, initiator)
                     }
+                    ReaperStateCondition => self.invalidate_activation_setting_1_controls(item),
                 };
             });
         });
@@ -629,7 +679,7 @@ impl View for MappingHeaderPanel {
                 self.with_mutable_item(Self::update_tags);
             }
             ID_MAPPING_ACTIVATION_EDIT_CONTROL => {
-                self.with_mutable_item(Self::update_activation_eel_condition);
+                self.with_mutable_item(Self::update_activation_condition_text);
             }
             _ => return false,
         };
@@ -743,6 +793,28 @@ impl Item for MappingModel {
             .eel_condition
             .set_with_initiator(value, Some(initiator));
     }
+
+    fn expression_condition(&self) -> &str {
+        self.activation_condition_model
+            .expression_condition
+            .get_ref()
+    }
+
+    fn set_expression_condition(&mut self, value: String, initiator: u32) {
+        self.activation_condition_model
+            .expression_condition
+            .set_with_initiator(value, Some(initiator));
+    }
+
+    fn reaper_state_condition(&self) -> ReaperStateCondition {
+        self.activation_condition_model.reaper_state_condition.get()
+    }
+
+    fn set_reaper_state_condition(&mut self, value: ReaperStateCondition) {
+        self.activation_condition_model
+            .reaper_state_condition
+            .set(value);
+    }
 }
 
 impl Item for GroupModel {
@@ -835,4 +907,26 @@ impl Item for GroupModel {
             .eel_condition
             .set_with_initiator(value, Some(initiator));
     }
+
+    fn expression_condition(&self) -> &str {
+        self.activation_condition_model
+            .expression_condition
+            .get_ref()
+    }
+
+    fn set_expression_condition(&mut self, value: String, initiator: u32) {
+        self.activation_condition_model
+            .expression_condition
+            .set_with_initiator(value, Some(initiator));
+    }
+
+    fn reaper_state_condition(&self) -> ReaperStateCondition {
+        self.activation_condition_model.reaper_state_condition.get()
+    }
+
+    fn set_reaper_state_condition(&mut self, value: ReaperStateCondition) {
+        self.activation_condition_model
+            .reaper_state_condition
+            .set(value);
+    }
 }
@@ -1,7 +1,7 @@
 use crate::base::{notification, when, Prop};
 use crate::infrastructure::ui::bindings::root;
 use crate::infrastructure::ui::{
-    EelEditorPanel, ItemProp, MainPanel, MappingHeaderPanel, YamlEditorPanel,
+    dialog_util, EelEditorPanel, ItemProp, MainPanel, MappingHeaderPanel, YamlEditorPanel,
 };
 use derive_more::Display;
 
@@ -19,7 +19,9 @@ use reaper_high::{
     BookmarkType, Fx, FxChain, Project, Reaper, SendPartnerType, Track, TrackRoutePartner,
 };
 use reaper_low::raw;
-use reaper_medium::{InitialAction, PromptForActionResult, SectionId, WindowContext};
+use reaper_medium::{
+    InitialAction, MidiOutputDeviceId, PromptForActionResult, SectionId, WindowContext,
+};
 use rxrust::prelude::*;
 use std::cell::{Cell, RefCell};
 use std::convert::TryInto;
@@ -34,24 +36,29 @@ use crate::application::{
     get_fx_param_label, get_non_present_bookmark_label, get_optional_fx_label, get_route_label,
     AutomationModeOverrideType, BookmarkAnchorType, ConcreteFxInstruction,
     ConcreteTrackInstruction, MappingModel, MidiSourceType, ModeModel, RealearnAutomationMode,
-    RealearnTrackArea, ReaperSourceType, Session, SharedMapping, SharedSession, SourceCategory,
-    SourceModel, TargetCategory, TargetModel, TargetModelWithContext, TargetUnit,
-    TrackRouteSelectorType, VirtualControlElementType, VirtualFxParameterType, VirtualFxType,
-    VirtualTrackType, WeakSession,
+    RealearnInputMonitoringMode, RealearnTrackArea, ReaperSourceType, Session, SharedMapping,
+    SharedSession, SourceCategory, SourceModel, TargetCategory, TargetModel,
+    TargetModelWithContext, TargetUnit, TrackRouteSelectorType, VirtualControlElementType,
+    VirtualFxParameterType, VirtualFxType, VirtualTrackType, WeakSession,
 };
 use crate::base::Global;
 use crate::domain::{
-    control_element_domains, AnyOnParameter, ClipInfo, ControlContext, Exclusivity,
-    FeedbackSendBehavior, ReaperTargetType, SendMidiDestination, SimpleExclusivity, SlotContent,
-    WithControlContext, CLIP_SLOT_COUNT,
+    control_element_domains, with_clip_matrix, AnyOnParameter, BackboneState, ClipInfo,
+    ClipMatrixId, ControlContext, Exclusivity, FeedbackSendBehavior, ReaperTargetType,
+    SendMidiDestination, SimpleExclusivity, SlotContent, SlotFades, SlotFollowAction,
+    SlotStopQuantization, WithControlContext,
 };
 use crate::domain::{
-    get_non_present_virtual_route_label, get_non_present_virtual_track_label,
-    resolve_track_route_by_index, ActionInvocationType, CompoundMappingTarget,
-    ExtendedProcessorContext, FeedbackResolution, FxDisplayType, MappingCompartment,
-    QualifiedMappingId, RealearnTarget, ReaperTarget, SoloBehavior, TargetCharacter,
-    TouchedParameterType, TrackExclusivity, TrackRouteType, TransportAction, VirtualControlElement,
-    VirtualControlElementId, VirtualFx,
+    format_program_change_patches, get_non_present_virtual_route_label,
+    get_non_present_virtual_track_label, parse_program_change_patches,
+    resolve_track_route_by_index, ActionInvocationType, BookmarkNavigationMode,
+    CompoundMappingTarget, DualPanComponent, ExtStateValueFormat, ExtendedProcessorContext,
+    FeedbackResolution, FxDisplayType, GameControllerElement, GroupEnableMode, MappingCompartment,
+    MappingModeSetting, MetronomeParameter, MmcCommand, MtcFrameRate, MusicalScale, OscDeltaMode,
+    PunchAction, QualifiedMappingId, RealearnTarget, ReaperTarget, RecordInputKind, SoloBehavior,
+    TargetCharacter, TargetValueChangeOrigin, TimeRangeKind, TimeSelectionAction,
+    TouchedParameterType, TrackExclusivity, TrackRouteType, TransportAction, UndoRedoAction,
+    VirtualControlElement, VirtualControlElementId, VirtualFx,
 };
 use itertools::Itertools;
 
@@ -203,6 +210,9 @@ impl MappingPanel {
                     self.invoke_slot_menu_action(action)?;
                 }
             }
+            ReaperTargetType::ClipColumnStop | ReaperTargetType::ClipSceneLaunch => {
+                self.invoke_slot_menu_action(SlotMenuAction::ChooseSharedMatrix)?;
+            }
             _ => {}
         }
         Ok(())
@@ -214,6 +224,16 @@ impl MappingPanel {
             use swell_ui::menu_tree::*;
             let session = self.session();
             let session = session.borrow();
+            let matrix_label = match self
+                .mapping()
+                .borrow()
+                .target_model
+                .clip_matrix_id
+                .get_ref()
+            {
+                None => "Use shared clip matrix...".to_owned(),
+                Some(id) => format!("Use shared clip matrix... ({})", id),
+            };
             let entries = vec![
                 item("Show slot info", || SlotMenuAction::ShowSlotInfo),
                 item_with_opts(
@@ -228,6 +248,16 @@ impl MappingPanel {
                     },
                     || SlotMenuAction::FillWithItemSource,
                 ),
+                item("Set clip tempo...", || SlotMenuAction::SetTempo),
+                item("Set fade lengths...", || SlotMenuAction::SetFades),
+                item("Set follow action...", || SlotMenuAction::SetFollowAction),
+                item(matrix_label, || SlotMenuAction::ChooseSharedMatrix),
+                item("Embed all clip contents in project", || {
+                    SlotMenuAction::EmbedContents
+                }),
+                item("Store all clip contents as external files", || {
+                    SlotMenuAction::ExternalizeContents
+                }),
             ];
             let mut root_menu = root_menu(entries);
             root_menu.index(1);
@@ -251,36 +281,40 @@ impl MappingPanel {
                 }
                 let info = {
                     let instance_state = self.session().borrow().instance_state().clone();
-                    let instance_state = instance_state.borrow();
                     let mapping = self.mapping();
                     let mapping = mapping.borrow();
-                    let slot_index = mapping.target_model.slot_index.get();
-                    if let Ok(slot) = instance_state.get_slot(slot_index) {
-                        if let Some(content) = &slot.descriptor().content {
-                            let info = SlotInfo {
-                                file_name: content
-                                    .file()
-                                    .map(|p| p.to_string_lossy().to_string())
-                                    .unwrap_or_default(),
-                                clip_info: slot.clip_info(),
-                            };
-                            Some(info)
-                        } else {
-                            None
-                        }
-                    } else {
-                        None
-                    }
+                    let matrix_id = mapping.target_model.clip_matrix_id.get_ref().as_ref();
+                    let slot_index = mapping.target_model.resolve_slot_index(&instance_state)?;
+                    with_clip_matrix(&instance_state, matrix_id, |m| {
+                        let slot = m.get_slot(slot_index).ok()?;
+                        let content = slot.descriptor().content.as_ref()?;
+                        let info = SlotInfo {
+                            file_name: content
+                                .file()
+                                .map(|p| p.to_string_lossy().to_string())
+                                .unwrap_or_else(|| "<Embedded in project>".to_owned()),
+                            clip_info: slot.clip_info(),
+                        };
+                        Some(info)
+                    })
                 };
                 let msg = if let Some(info) = info {
                     let suffix = if let Some(clip_info) = info.clip_info {
                         format!(
-                            "Type: {}\n\nLength: {}",
+                            "Type: {}\n\nLength: {}\n\nTempo: {}",
                             clip_info.r#type,
                             clip_info
                                 .length
                                 .map(|l| format!("{} secs", l))
-                                .unwrap_or_default()
+                                .unwrap_or_default(),
+                            clip_info
+                                .tempo
+                                .map(|t| format!(
+                                    "{:.2} bpm ({:.2} beats)",
+                                    t.bpm(),
+                                    t.beat_count()
+                                ))
+                                .unwrap_or_else(|| "<unknown>".to_owned())
                         )
                     } else {
                         "<offline>".to_owned()
@@ -301,13 +335,190 @@ impl MappingPanel {
                         .project_or_current_project()
                         .first_selected_item()
                         .ok_or("no item selected")?;
-                    let slot_index = self.mapping().borrow().target_model.slot_index.get();
-                    let mut instance_state = session.instance_state().borrow_mut();
-                    instance_state.fill_slot_with_item_source(slot_index, item)
+                    let mapping = self.mapping();
+                    let mapping = mapping.borrow();
+                    let slot_index = mapping
+                        .target_model
+                        .resolve_slot_index(session.instance_state())?;
+                    match mapping.target_model.clip_matrix_id.get_ref() {
+                        None => {
+                            let mut instance_state = session.instance_state().borrow_mut();
+                            instance_state.fill_slot_with_item_source(slot_index, item)
+                        }
+                        Some(id) => BackboneState::get().modify_shared_clip_matrix(id, |m| {
+                            m.fill_slot_with_item_source(slot_index, item)
+                        }),
+                    }
                 };
                 if let Err(e) = result {
                     self.view.require_window().alert("ReaLearn", e.to_string());
                 }
+                self.invalidate_target_line_3(None);
+                Ok(())
+            }
+            SlotMenuAction::SetTempo => {
+                let instance_state = self.session().borrow().instance_state().clone();
+                let (matrix_id, slot_index) = {
+                    let mapping = self.mapping();
+                    let mapping = mapping.borrow();
+                    (
+                        mapping.target_model.clip_matrix_id.get_ref().clone(),
+                        mapping.target_model.resolve_slot_index(&instance_state)?,
+                    )
+                };
+                let current = with_clip_matrix(&instance_state, matrix_id.as_ref(), |m| {
+                    let tempo = m.get_slot(slot_index).ok()?.tempo()?;
+                    Some(format!("{:.2}", tempo.bpm()))
+                })
+                .unwrap_or_default();
+                let input = match dialog_util::prompt_for("Clip tempo (bpm)", &current) {
+                    None => return Ok(()),
+                    Some(i) => i,
+                };
+                let bpm: f64 = input.parse().map_err(|_| "invalid tempo")?;
+                let result = match matrix_id.as_ref() {
+                    None => instance_state.borrow_mut().set_slot_tempo(slot_index, bpm),
+                    Some(id) => BackboneState::get()
+                        .modify_shared_clip_matrix(id, |m| m.set_slot_tempo(slot_index, bpm)),
+                };
+                if let Err(e) = result {
+                    self.view.require_window().alert("ReaLearn", e);
+                }
+                Ok(())
+            }
+            SlotMenuAction::SetFades => {
+                let instance_state = self.session().borrow().instance_state().clone();
+                let (matrix_id, slot_index) = {
+                    let mapping = self.mapping();
+                    let mapping = mapping.borrow();
+                    (
+                        mapping.target_model.clip_matrix_id.get_ref().clone(),
+                        mapping.target_model.resolve_slot_index(&instance_state)?,
+                    )
+                };
+                let current = with_clip_matrix(&instance_state, matrix_id.as_ref(), |m| {
+                    let fades = m.get_slot(slot_index).ok()?.fades();
+                    Some(format!(
+                        "{}, {}",
+                        fades.fade_in_millis, fades.fade_out_millis
+                    ))
+                })
+                .unwrap_or_default();
+                let input = match dialog_util::prompt_for("Fade-in, fade-out length (ms)", &current)
+                {
+                    None => return Ok(()),
+                    Some(i) => i,
+                };
+                let fades = parse_slot_fades(&input).ok_or("invalid fade lengths")?;
+                let result = match matrix_id.as_ref() {
+                    None => instance_state
+                        .borrow_mut()
+                        .set_slot_fades(slot_index, fades),
+                    Some(id) => BackboneState::get()
+                        .modify_shared_clip_matrix(id, |m| m.set_slot_fades(slot_index, fades)),
+                };
+                if let Err(e) = result {
+                    self.view.require_window().alert("ReaLearn", e);
+                }
+                Ok(())
+            }
+            SlotMenuAction::SetFollowAction => {
+                let instance_state = self.session().borrow().instance_state().clone();
+                let (matrix_id, slot_index) = {
+                    let mapping = self.mapping();
+                    let mapping = mapping.borrow();
+                    (
+                        mapping.target_model.clip_matrix_id.get_ref().clone(),
+                        mapping.target_model.resolve_slot_index(&instance_state)?,
+                    )
+                };
+                let current = with_clip_matrix(&instance_state, matrix_id.as_ref(), |m| {
+                    let follow_action = m.get_slot(slot_index).ok()?.follow_action()?;
+                    Some(follow_action.to_string())
+                })
+                .unwrap_or_default();
+                let input = match dialog_util::prompt_for(
+                    "Follow action (next/previous/random/stop) and play count",
+                    &current,
+                ) {
+                    None => return Ok(()),
+                    Some(i) => i,
+                };
+                let follow_action: Option<SlotFollowAction> = if input.trim().is_empty() {
+                    None
+                } else {
+                    Some(input.parse()?)
+                };
+                let result = match matrix_id.as_ref() {
+                    None => instance_state
+                        .borrow_mut()
+                        .set_slot_follow_action(slot_index, follow_action),
+                    Some(id) => BackboneState::get().modify_shared_clip_matrix(id, |m| {
+                        m.set_slot_follow_action(slot_index, follow_action)
+                    }),
+                };
+                if let Err(e) = result {
+                    self.view.require_window().alert("ReaLearn", e);
+                }
+                Ok(())
+            }
+            SlotMenuAction::EmbedContents | SlotMenuAction::ExternalizeContents => {
+                let embed = matches!(action, SlotMenuAction::EmbedContents);
+                let session = self.session();
+                let session = session.borrow();
+                let project = session.context().project_or_current_project();
+                let matrix_id = self
+                    .mapping()
+                    .borrow()
+                    .target_model
+                    .clip_matrix_id
+                    .get_ref()
+                    .clone();
+                let result = match matrix_id.as_ref() {
+                    None => {
+                        let mut instance_state = session.instance_state().borrow_mut();
+                        if embed {
+                            instance_state.embed_clip_contents(Some(project))
+                        } else {
+                            instance_state.externalize_clip_contents(project)
+                        }
+                    }
+                    Some(id) => BackboneState::get().modify_shared_clip_matrix(id, |m| {
+                        if embed {
+                            m.embed_contents(Some(project))
+                        } else {
+                            m.externalize_contents(project)
+                        }
+                    }),
+                };
+                let msg = match result {
+                    Ok(count) => format!("Migrated {} clip(s).", count),
+                    Err(e) => e.to_owned(),
+                };
+                self.view.require_window().alert("ReaLearn", msg);
+                self.invalidate_target_line_3(None);
+                Ok(())
+            }
+            SlotMenuAction::ChooseSharedMatrix => {
+                let current = self
+                    .mapping()
+                    .borrow()
+                    .target_model
+                    .clip_matrix_id
+                    .get_ref()
+                    .as_ref()
+                    .map(|id| id.get().to_owned())
+                    .unwrap_or_default();
+                // Empty input means that the instance's own clip matrix should be used.
+                if let Some(input) =
+                    dialog_util::prompt_for("Shared clip matrix ID (empty = own)", &current)
+                {
+                    self.mapping()
+                        .borrow_mut()
+                        .target_model
+                        .clip_matrix_id
+                        .set(ClipMatrixId::from_str_opt(&input));
+                }
                 Ok(())
             }
         }
@@ -364,6 +575,124 @@ impl MappingPanel {
         Ok(())
     }
 
+    fn more_settings_button_pressed(&self) -> Result<(), &'static str> {
+        let mapping = self.displayed_mapping().ok_or("no mapping set")?;
+        let action = match self.prompt_for_more_settings_action(&mapping) {
+            None => return Ok(()),
+            Some(a) => a,
+        };
+        match action {
+            MoreSettingsMenuAction::SetVelocityThreshold => {
+                let current = format_velocity_threshold(&mapping.borrow().mode_model);
+                let input = match dialog_util::prompt_for(
+                    "Velocity threshold, soft value, hard value (%)",
+                    &current,
+                ) {
+                    None => return Ok(()),
+                    Some(i) => i,
+                };
+                let (threshold, below_value, above_value) = if input.trim().is_empty() {
+                    (None, None, None)
+                } else {
+                    let (t, b, a) =
+                        parse_velocity_threshold(&input).ok_or("invalid velocity threshold")?;
+                    (Some(t), b, a)
+                };
+                let mut mapping = mapping.borrow_mut();
+                mapping.mode_model.velocity_threshold.set(threshold);
+                mapping
+                    .mode_model
+                    .velocity_below_threshold_value
+                    .set(below_value);
+                mapping
+                    .mode_model
+                    .velocity_above_threshold_value
+                    .set(above_value);
+            }
+            MoreSettingsMenuAction::SetQuantizationScale(scale) => {
+                mapping
+                    .borrow_mut()
+                    .mode_model
+                    .quantization_scale
+                    .set(scale);
+            }
+            MoreSettingsMenuAction::SetQuantizationRoot(root) => {
+                mapping.borrow_mut().mode_model.quantization_root.set(root);
+            }
+        }
+        Ok(())
+    }
+
+    fn prompt_for_more_settings_action(
+        &self,
+        mapping: &SharedMapping,
+    ) -> Option<MoreSettingsMenuAction> {
+        let menu_bar = MenuBar::new_popup_menu();
+        let pure_menu = {
+            use swell_ui::menu_tree::*;
+            let mapping = mapping.borrow();
+            let current_scale = mapping.mode_model.quantization_scale.get();
+            let current_root = mapping.mode_model.quantization_root.get();
+            let entries = vec![
+                item_with_opts(
+                    "Velocity threshold...",
+                    ItemOpts {
+                        enabled: true,
+                        checked: mapping.mode_model.velocity_threshold.get().is_some(),
+                    },
+                    || MoreSettingsMenuAction::SetVelocityThreshold,
+                ),
+                menu(
+                    "Scale quantization",
+                    iter::once(item_with_opts(
+                        "<Off>",
+                        ItemOpts {
+                            enabled: true,
+                            checked: current_scale.is_none(),
+                        },
+                        || MoreSettingsMenuAction::SetQuantizationScale(None),
+                    ))
+                    .chain(MusicalScale::into_enum_iter().map(|scale| {
+                        item_with_opts(
+                            scale.to_string(),
+                            ItemOpts {
+                                enabled: true,
+                                checked: current_scale == Some(scale),
+                            },
+                            move || MoreSettingsMenuAction::SetQuantizationScale(Some(scale)),
+                        )
+                    }))
+                    .collect(),
+                ),
+                menu(
+                    "Scale root note",
+                    (0u8..12)
+                        .map(|root| {
+                            item_with_opts(
+                                PITCH_CLASS_NAMES[root as usize],
+                                ItemOpts {
+                                    enabled: current_scale.is_some(),
+                                    checked: current_root == root,
+                                },
+                                move || MoreSettingsMenuAction::SetQuantizationRoot(root),
+                            )
+                        })
+                        .collect(),
+                ),
+            ];
+            let mut root_menu = root_menu(entries);
+            root_menu.index(1);
+            fill_menu(menu_bar.menu(), &root_menu);
+            root_menu
+        };
+        let result_index = self
+            .view
+            .require_window()
+            .open_popup_menu(menu_bar.menu(), Window::cursor_pos())?;
+        let item = pure_menu.find_item_by_id(result_index)?;
+        Some(item.invoke_handler())
+    }
+
     fn handle_target_line_4_button_press(&self) -> Result<(), &'static str> {
         let mapping = self.displayed_mapping().ok_or("no mapping set")?;
         let target_type = mapping.borrow().target_model.r#type.get();
@@ -430,11 +759,62 @@ impl MappingPanel {
                     .fx_snapshot
                     .set(Some(fx_snapshot));
             }
+            ReaperTargetType::ClipTransport => {
+                let current = mapping.borrow().target_model.stop_quantization.get();
+                if let Some(quantization) = self.prompt_for_stop_quantization(current) {
+                    mapping
+                        .borrow_mut()
+                        .target_model
+                        .stop_quantization
+                        .set(quantization);
+                }
+            }
             _ => {}
         }
         Ok(())
     }
 
+    /// Returns `None` if the menu was cancelled and `Some(None)` if the instance default was
+    /// chosen.
+    fn prompt_for_stop_quantization(
+        &self,
+        current: Option<SlotStopQuantization>,
+    ) -> Option<Option<SlotStopQuantization>> {
+        let menu_bar = MenuBar::new_popup_menu();
+        let pure_menu = {
+            use swell_ui::menu_tree::*;
+            let entries = iter::once(item_with_opts(
+                "Stop quantization: <Instance default>",
+                ItemOpts {
+                    enabled: true,
+                    checked: current.is_none(),
+                },
+                || None,
+            ))
+            .chain(SlotStopQuantization::into_enum_iter().map(|q| {
+                item_with_opts(
+                    format!("Stop quantization: {}", q),
+                    ItemOpts {
+                        enabled: true,
+                        checked: current == Some(q),
+                    },
+                    move || Some(q),
+                )
+            }))
+            .collect();
+            let mut root_menu = root_menu(entries);
+            root_menu.index(1);
+            fill_menu(menu_bar.menu(), &root_menu);
+            root_menu
+        };
+        let result_index = self
+            .view
+            .require_window()
+            .open_popup_menu(menu_bar.menu(), Window::cursor_pos())?;
+        let item = pure_menu.find_item_by_id(result_index)?;
+        Some(item.invoke_handler())
+    }
+
     pub fn is_free(&self) -> bool {
         self.mapping.borrow().is_none()
     }
@@ -539,6 +919,7 @@ impl MappingPanel {
         self: SharedView<Self>,
         targets: &[CompoundMappingTarget],
         new_value: AbsoluteValue,
+        origin: TargetValueChangeOrigin,
     ) {
         self.invoke_programmatically(|| {
             let session = self.session();
@@ -554,6 +935,7 @@ impl MappingPanel {
                     .require_control(root::ID_TARGET_VALUE_EDIT_CONTROL),
                 self.view.require_control(root::ID_TARGET_VALUE_TEXT),
                 new_value,
+                Some(origin),
                 None,
                 root::ID_TARGET_VALUE_EDIT_CONTROL,
                 true,
@@ -894,11 +1276,23 @@ impl<'a> MutableMappingPanel<'a> {
     }
 
     fn handle_source_line_4_check_box_change(&mut self) {
-        self.mapping.source_model.is_registered.set(Some(
-            self.view
-                .require_control(root::ID_SOURCE_RPN_CHECK_BOX)
-                .is_checked(),
-        ));
+        let checked = self
+            .view
+            .require_control(root::ID_SOURCE_RPN_CHECK_BOX)
+            .is_checked();
+        use SourceCategory::*;
+        match self.mapping.source_model.category.get() {
+            Midi => {
+                self.mapping.source_model.is_registered.set(Some(checked));
+            }
+            Osc => {
+                self.mapping
+                    .source_model
+                    .osc_feedback_is_relative
+                    .set(checked);
+            }
+            Reaper | Virtual | Never => {}
+        };
     }
 
     fn handle_source_check_box_2_change(&mut self) {
@@ -1003,6 +1397,24 @@ impl<'a> MutableMappingPanel<'a> {
                     .osc_arg_type_tag
                     .set(i.try_into().expect("invalid OSC type tag"));
             }
+            Reaper => {
+                let i = b.selected_combo_box_item_index();
+                match self.mapping.source_model.reaper_source_type.get() {
+                    ReaperSourceType::GameController => {
+                        self.mapping
+                            .source_model
+                            .game_controller_element
+                            .set(i.try_into().expect("invalid game controller element"));
+                    }
+                    ReaperSourceType::Mmc => {
+                        self.mapping
+                            .source_model
+                            .mmc_command
+                            .set(i.try_into().expect("invalid MMC command"));
+                    }
+                    _ => {}
+                }
+            }
             _ => {}
         }
     }
@@ -1066,6 +1478,13 @@ impl<'a> MutableMappingPanel<'a> {
                 }
                 _ => {}
             },
+            Osc => {
+                let i = b.selected_combo_box_item_index();
+                self.mapping
+                    .source_model
+                    .osc_delta_mode
+                    .set(i.try_into().expect("invalid OSC delta mode"));
+            }
             _ => {}
         }
     }
@@ -1096,7 +1515,28 @@ impl<'a> MutableMappingPanel<'a> {
                     .control_element_id
                     .set_with_initiator(text.parse().unwrap_or_default(), Some(edit_control_id));
             }
-            Reaper | Never => {}
+            Reaper => match self.mapping.source_model.reaper_source_type.get() {
+                ReaperSourceType::GameController => {
+                    self.mapping
+                        .source_model
+                        .game_controller_index
+                        .set_with_initiator(text.parse().ok(), Some(edit_control_id));
+                }
+                ReaperSourceType::InstanceMessage => {
+                    self.mapping
+                        .source_model
+                        .instance_message_name
+                        .set_with_initiator(text, Some(edit_control_id));
+                }
+                ReaperSourceType::Mmc => {
+                    self.mapping
+                        .source_model
+                        .mmc_device_id
+                        .set_with_initiator(text.parse().ok(), Some(edit_control_id));
+                }
+                _ => {}
+            },
+            Never => {}
         };
     }
 
@@ -1633,7 +2073,7 @@ impl<'a> MutableMappingPanel<'a> {
                 ReaperTargetType::Action => {
                     self.mapping.target_model.with_track.set(is_checked);
                 }
-                ReaperTargetType::GoToBookmark => {
+                ReaperTargetType::GoToBookmark | ReaperTargetType::NavigateBookmarks => {
                     let bookmark_type = if is_checked {
                         BookmarkType::Region
                     } else {
@@ -1678,7 +2118,7 @@ impl<'a> MutableMappingPanel<'a> {
                 ReaperTargetType::Seek => {
                     self.mapping.target_model.move_view.set(is_checked);
                 }
-                ReaperTargetType::LoadMappingSnapshot => {
+                ReaperTargetType::LoadMappingSnapshot | ReaperTargetType::SaveMappingSnapshot => {
                     self.mapping
                         .target_model
                         .active_mappings_only
@@ -1706,6 +2146,12 @@ impl<'a> MutableMappingPanel<'a> {
                 ReaperTargetType::Seek => {
                     self.mapping.target_model.use_project.set(is_checked);
                 }
+                ReaperTargetType::TrackDelete => {
+                    self.mapping
+                        .target_model
+                        .require_confirmation
+                        .set(is_checked);
+                }
                 _ => {}
             },
             TargetCategory::Virtual => {}
@@ -1723,7 +2169,7 @@ impl<'a> MutableMappingPanel<'a> {
                 ReaperTargetType::Seek => {
                     self.mapping.target_model.use_regions.set(is_checked);
                 }
-                ReaperTargetType::ClipTransport => {
+                ReaperTargetType::ClipTransport | ReaperTargetType::ClipSceneLaunch => {
                     self.mapping.target_model.next_bar.set(is_checked);
                 }
                 t if t.supports_poll_for_feedback() => {
@@ -1746,7 +2192,7 @@ impl<'a> MutableMappingPanel<'a> {
                 ReaperTargetType::Seek | ReaperTargetType::GoToBookmark => {
                     self.mapping.target_model.use_loop_points.set(is_checked);
                 }
-                ReaperTargetType::ClipTransport => {
+                ReaperTargetType::ClipTransport | ReaperTargetType::ClipSceneLaunch => {
                     self.mapping.target_model.buffered.set(is_checked);
                 }
                 _ => {}
@@ -1857,8 +2303,20 @@ impl<'a> MutableMappingPanel<'a> {
         match self.target_category() {
             TargetCategory::Reaper => match self.reaper_target_type() {
                 t if t.supports_slot() => {
+                    let matrix_id = self.mapping.target_model.clip_matrix_id.get_ref().clone();
+                    let size =
+                        with_clip_matrix(self.session.instance_state(), matrix_id.as_ref(), |m| {
+                            m.size()
+                        });
                     let slot_index = combo.selected_combo_box_item_index();
-                    self.mapping.target_model.slot_index.set(slot_index);
+                    if let Some(address) = size.slot_address(slot_index) {
+                        self.mapping.target_model.slot_row.set(address.row);
+                        self.mapping.target_model.slot_column.set(address.column);
+                    }
+                }
+                ReaperTargetType::ClipSceneLaunch => {
+                    let row = combo.selected_combo_box_item_index();
+                    self.mapping.target_model.slot_row.set(row);
                 }
                 t if t.supports_fx() => {
                     let fx_type = combo
@@ -1897,6 +2355,20 @@ impl<'a> MutableMappingPanel<'a> {
                         .osc_arg_type_tag
                         .set(i.try_into().expect("invalid OSC type tag"));
                 }
+                ReaperTargetType::SetExtState => {
+                    let i = combo.selected_combo_box_item_index();
+                    self.mapping
+                        .target_model
+                        .ext_state_format
+                        .set(i.try_into().expect("invalid ext state value format"));
+                }
+                ReaperTargetType::TrackRecordInput => {
+                    let i = combo.selected_combo_box_item_index();
+                    self.mapping
+                        .target_model
+                        .record_input_kind
+                        .set(i.try_into().expect("invalid record input kind"));
+                }
                 ReaperTargetType::FxParameter => {
                     let param_type = combo
                         .selected_combo_box_item_index()
@@ -1904,6 +2376,17 @@ impl<'a> MutableMappingPanel<'a> {
                         .unwrap_or_default();
                     self.mapping.target_model.param_type.set(param_type);
                 }
+                t if t.supports_cue_output() => {
+                    let i = combo.selected_combo_box_item_index();
+                    self.mapping.target_model.cue_output.set(i as _);
+                }
+                ReaperTargetType::EnableGroup => {
+                    let i = combo.selected_combo_box_item_index();
+                    self.mapping
+                        .target_model
+                        .group_enable_mode
+                        .set(i.try_into().expect("invalid group enable mode"));
+                }
                 ReaperTargetType::NavigateWithinGroup => {
                     let exclusivity: SimpleExclusivity = combo
                         .selected_combo_box_item_index()
@@ -1963,6 +2446,41 @@ impl<'a> MutableMappingPanel<'a> {
                         .transport_action
                         .set(i.try_into().expect("invalid transport action"));
                 }
+                ReaperTargetType::Punch => {
+                    let i = combo.selected_combo_box_item_index();
+                    self.mapping
+                        .target_model
+                        .punch_action
+                        .set(i.try_into().expect("invalid punch action"));
+                }
+                ReaperTargetType::UndoRedo => {
+                    let i = combo.selected_combo_box_item_index();
+                    self.mapping
+                        .target_model
+                        .undo_redo_action
+                        .set(i.try_into().expect("invalid undo/redo action"));
+                }
+                ReaperTargetType::TimeSelection => {
+                    let i = combo.selected_combo_box_item_index();
+                    self.mapping
+                        .target_model
+                        .time_selection_action
+                        .set(i.try_into().expect("invalid time selection action"));
+                }
+                ReaperTargetType::NavigateBookmarks => {
+                    let i = combo.selected_combo_box_item_index();
+                    self.mapping
+                        .target_model
+                        .bookmark_navigation_mode
+                        .set(i.try_into().expect("invalid bookmark navigation mode"));
+                }
+                ReaperTargetType::Metronome => {
+                    let i = combo.selected_combo_box_item_index();
+                    self.mapping
+                        .target_model
+                        .metronome_parameter
+                        .set(i.try_into().expect("invalid metronome parameter"));
+                }
                 ReaperTargetType::AnyOn => {
                     let i = combo.selected_combo_box_item_index();
                     self.mapping
@@ -1970,7 +2488,7 @@ impl<'a> MutableMappingPanel<'a> {
                         .any_on_parameter
                         .set(i.try_into().expect("invalid any-on parameter"));
                 }
-                ReaperTargetType::NavigateWithinGroup => {
+                ReaperTargetType::NavigateWithinGroup | ReaperTargetType::EnableGroup => {
                     let i = combo.selected_combo_box_item_index();
                     let group_id = self
                         .session
@@ -1999,6 +2517,13 @@ impl<'a> MutableMappingPanel<'a> {
                     };
                     self.mapping.target_model.osc_dev_id.set(dev_id);
                 }
+                ReaperTargetType::SendProgramChange | ReaperTargetType::SendMidiClock => {
+                    let dev_id = match combo.selected_combo_box_item_data() {
+                        i if i >= 0 => Some(MidiOutputDeviceId::new(i as u8)),
+                        _ => None,
+                    };
+                    self.mapping.target_model.midi_output_device_id.set(dev_id);
+                }
                 _ if self.mapping.target_model.supports_track() => {
                     let project = self.session.context().project_or_current_project();
                     let i = combo.selected_combo_box_item_index();
@@ -2053,6 +2578,13 @@ impl<'a> MutableMappingPanel<'a> {
                         .solo_behavior
                         .set(i.try_into().expect("invalid solo behavior"));
                 }
+                ReaperTargetType::TrackDualPan => {
+                    let i = combo.selected_combo_box_item_index();
+                    self.mapping
+                        .target_model
+                        .dual_pan_component
+                        .set(i.try_into().expect("invalid dual pan component"));
+                }
                 ReaperTargetType::TrackShow => {
                     let i = combo.selected_combo_box_item_index();
                     self.mapping
@@ -2069,6 +2601,20 @@ impl<'a> MutableMappingPanel<'a> {
                         .automation_mode
                         .set(i.try_into().expect("invalid automation mode"));
                 }
+                ReaperTargetType::TimeSelection => {
+                    let i = combo.selected_combo_box_item_index();
+                    self.mapping
+                        .target_model
+                        .time_range_kind
+                        .set(i.try_into().expect("invalid time range kind"));
+                }
+                ReaperTargetType::TrackInputMonitoring => {
+                    let i = combo.selected_combo_box_item_index();
+                    self.mapping
+                        .target_model
+                        .input_monitoring_mode
+                        .set(i.try_into().expect("invalid input monitoring mode"));
+                }
                 ReaperTargetType::AutomationTouchState => {
                     let i = combo.selected_combo_box_item_index();
                     self.mapping
@@ -2076,6 +2622,23 @@ impl<'a> MutableMappingPanel<'a> {
                         .touched_parameter_type
                         .set(i.try_into().expect("invalid touched parameter type"));
                 }
+                ReaperTargetType::AdjustMappingMode => {
+                    let i = combo.selected_combo_box_item_index();
+                    self.mapping
+                        .target_model
+                        .mapping_mode_setting
+                        .set(i.try_into().expect("invalid mapping mode setting"));
+                }
+                ReaperTargetType::SendMidiClock => {
+                    let rate = match combo.selected_combo_box_item_data() {
+                        i if i >= 0 => MtcFrameRate::into_enum_iter().nth(i as usize),
+                        _ => None,
+                    };
+                    self.mapping
+                        .target_model
+                        .midi_clock_mtc_frame_rate
+                        .set(rate);
+                }
                 _ => {}
             },
             TargetCategory::Virtual => {}
@@ -2193,6 +2756,54 @@ impl<'a> MutableMappingPanel<'a> {
                         _ => {}
                     }
                 }
+                ReaperTargetType::LoadMappingSnapshot | ReaperTargetType::SaveMappingSnapshot => {
+                    let text = control.text().unwrap_or_default();
+                    self.mapping
+                        .target_model
+                        .mapping_snapshot_id
+                        .set_with_initiator(text.trim().to_owned(), Some(edit_control_id));
+                }
+                ReaperTargetType::AdjustMappingMode => {
+                    let text = control.text().unwrap_or_default();
+                    self.mapping
+                        .target_model
+                        .mapping_key
+                        .set_with_initiator(text.trim().to_owned(), Some(edit_control_id));
+                }
+                ReaperTargetType::InvokeInstance => {
+                    let text = control.text().unwrap_or_default();
+                    self.mapping
+                        .target_model
+                        .instance_session_id
+                        .set_with_initiator(text.trim().to_owned(), Some(edit_control_id));
+                }
+                ReaperTargetType::SelectLayer => {
+                    let text = control.text().unwrap_or_default();
+                    if let Ok(layer) = text.parse() {
+                        self.mapping
+                            .target_model
+                            .layer
+                            .set_with_initiator(layer, Some(edit_control_id));
+                    }
+                }
+                ReaperTargetType::SetBankOffset => {
+                    let text = control.text().unwrap_or_default();
+                    if let Ok(offset) = text.trim().parse() {
+                        self.mapping
+                            .target_model
+                            .bank_offset
+                            .set_with_initiator(offset, Some(edit_control_id));
+                    }
+                }
+                ReaperTargetType::FxParameterBank => {
+                    let text = control.text().unwrap_or_default();
+                    if let Ok(size) = text.trim().parse() {
+                        self.mapping
+                            .target_model
+                            .fx_parameter_bank_size
+                            .set_with_initiator(size, Some(edit_control_id));
+                    }
+                }
                 _ => {}
             },
             TargetCategory::Virtual => {
@@ -2217,6 +2828,36 @@ impl<'a> MutableMappingPanel<'a> {
                         .raw_midi_pattern
                         .set_with_initiator(text, Some(edit_control_id));
                 }
+                ReaperTargetType::SendMidiMacro => {
+                    let text = control.text().unwrap_or_default();
+                    self.mapping
+                        .target_model
+                        .midi_macro_name
+                        .set_with_initiator(text, Some(edit_control_id));
+                }
+                ReaperTargetType::InvokeInstance => {
+                    let text = control.text().unwrap_or_default();
+                    self.mapping
+                        .target_model
+                        .instance_message_name
+                        .set_with_initiator(text, Some(edit_control_id));
+                }
+                ReaperTargetType::SendProgramChange => {
+                    let channel = parse_position_as_index(control).min(15);
+                    self.mapping
+                        .target_model
+                        .midi_channel
+                        .set_with_initiator(channel as u8, Some(edit_control_id));
+                }
+                ReaperTargetType::LoadMappingSnapshot => {
+                    let fade_time = self
+                        .get_value_from_duration_edit_control(edit_control_id)
+                        .unwrap_or_default();
+                    self.mapping
+                        .target_model
+                        .mapping_snapshot_fade_time
+                        .set_with_initiator(fade_time, Some(edit_control_id));
+                }
                 ReaperTargetType::SendOsc => {
                     let pattern = control.text().unwrap_or_default();
                     self.mapping
@@ -2224,6 +2865,20 @@ impl<'a> MutableMappingPanel<'a> {
                         .osc_address_pattern
                         .set_with_initiator(pattern, Some(edit_control_id));
                 }
+                ReaperTargetType::SetExtState => {
+                    let section = control.text().unwrap_or_default();
+                    self.mapping
+                        .target_model
+                        .ext_state_section
+                        .set_with_initiator(section, Some(edit_control_id));
+                }
+                ReaperTargetType::RunScript => {
+                    let path = control.text().unwrap_or_default();
+                    self.mapping
+                        .target_model
+                        .script_path
+                        .set_with_initiator(path, Some(edit_control_id));
+                }
                 t if t.supports_fx() => match self.mapping.target_model.fx_type.get() {
                     VirtualFxType::Dynamic => {
                         let expression = control.text().unwrap_or_default();
@@ -2266,6 +2921,22 @@ impl<'a> MutableMappingPanel<'a> {
                         .osc_arg_index
                         .set_with_initiator(parse_osc_arg_index(&text), Some(edit_control_id));
                 }
+                ReaperTargetType::SetExtState => {
+                    let key = control.text().unwrap_or_default();
+                    self.mapping
+                        .target_model
+                        .ext_state_key
+                        .set_with_initiator(key, Some(edit_control_id));
+                }
+                ReaperTargetType::SendProgramChange => {
+                    let text = control.text().unwrap_or_default();
+                    if let Ok(patches) = parse_program_change_patches(&text) {
+                        self.mapping
+                            .target_model
+                            .program_change_patches
+                            .set_with_initiator(patches, Some(edit_control_id));
+                    }
+                }
                 ReaperTargetType::FxParameter => match self.mapping.target_model.param_type.get() {
                     VirtualFxParameterType::Dynamic => {
                         let expression = control.text().unwrap_or_default();
@@ -2679,6 +3350,7 @@ impl<'a> ImmutableMappingPanel<'a> {
                 MidiSourceType::Display => Some("Protocol"),
                 _ => None,
             },
+            Osc if self.source.osc_arg_is_relative.get() => Some("Delta"),
             _ => None,
         };
         self.view
@@ -2752,6 +3424,10 @@ impl<'a> ImmutableMappingPanel<'a> {
                     _ => None,
                 }
             }
+            Osc if self.source.osc_arg_is_relative.get() => Some((
+                "Relative feedback",
+                self.source.osc_feedback_is_relative.get(),
+            )),
             _ => None,
         };
         self.invalidate_check_box(root::ID_SOURCE_RPN_CHECK_BOX, state);
@@ -2800,6 +3476,9 @@ impl<'a> ImmutableMappingPanel<'a> {
             }
             Virtual => Some("ID"),
             Osc => Some("Argument"),
+            Reaper if self.source_is_game_controller() => Some("Controller"),
+            Reaper if self.source_is_instance_message() => Some("Message"),
+            Reaper if self.source_is_mmc() => Some("Device ID"),
             _ => None,
         };
         self.view
@@ -2890,6 +3569,19 @@ impl<'a> ImmutableMappingPanel<'a> {
             },
             Osc => Some(format_osc_arg_index(self.source.osc_arg_index.get())),
             Virtual => Some(self.source.control_element_id.get().to_string()),
+            Reaper if self.source_is_game_controller() => {
+                match self.source.game_controller_index.get() {
+                    None => Some("".to_owned()),
+                    Some(i) => Some(i.to_string()),
+                }
+            }
+            Reaper if self.source_is_instance_message() => {
+                Some(self.source.instance_message_name.get_ref().clone())
+            }
+            Reaper if self.source_is_mmc() => match self.source.mmc_device_id.get() {
+                None => Some("".to_owned()),
+                Some(id) => Some(id.to_string()),
+            },
             _ => None,
         };
         self.view
@@ -2985,6 +3677,8 @@ impl<'a> ImmutableMappingPanel<'a> {
                 }
             }
             Osc => Some("Type"),
+            Reaper if self.source_is_game_controller() => Some("Element"),
+            Reaper if self.source_is_mmc() => Some("Command"),
             _ => None,
         };
         self.view
@@ -3032,10 +3726,22 @@ impl<'a> ImmutableMappingPanel<'a> {
                     }
                 }
             }
-            Osc => {
+            Osc => {
+                b.show();
+                b.fill_combo_box_indexed(OscTypeTag::into_enum_iter());
+                b.select_combo_box_item_by_index(self.source.osc_arg_type_tag.get().into())
+                    .unwrap();
+            }
+            Reaper if self.source_is_game_controller() => {
+                b.show();
+                b.fill_combo_box_indexed(GameControllerElement::into_enum_iter());
+                b.select_combo_box_item_by_index(self.source.game_controller_element.get().into())
+                    .unwrap();
+            }
+            Reaper if self.source_is_mmc() => {
                 b.show();
-                b.fill_combo_box_indexed(OscTypeTag::into_enum_iter());
-                b.select_combo_box_item_by_index(self.source.osc_arg_type_tag.get().into())
+                b.fill_combo_box_indexed(MmcCommand::into_enum_iter());
+                b.select_combo_box_item_by_index(self.source.mmc_command.get().into())
                     .unwrap();
             }
             _ => {
@@ -3044,6 +3750,18 @@ impl<'a> ImmutableMappingPanel<'a> {
         };
     }
 
+    fn source_is_game_controller(&self) -> bool {
+        self.source.reaper_source_type.get() == ReaperSourceType::GameController
+    }
+
+    fn source_is_instance_message(&self) -> bool {
+        self.source.reaper_source_type.get() == ReaperSourceType::InstanceMessage
+    }
+
+    fn source_is_mmc(&self) -> bool {
+        self.source.reaper_source_type.get() == ReaperSourceType::Mmc
+    }
+
     fn invalidate_source_line_3_combo_box_2(&self) {
         let b = self
             .view
@@ -3069,6 +3787,12 @@ impl<'a> ImmutableMappingPanel<'a> {
                     b.hide();
                 }
             },
+            Osc if self.source.osc_arg_is_relative.get() => {
+                b.show();
+                b.fill_combo_box_indexed(OscDeltaMode::into_enum_iter());
+                b.select_combo_box_item_by_index(self.source.osc_delta_mode.get().into())
+                    .unwrap();
+            }
             _ => {
                 b.hide();
             }
@@ -3133,17 +3857,32 @@ impl<'a> ImmutableMappingPanel<'a> {
     fn invalidate_target_line_2_label_1(&self) {
         let text = match self.target_category() {
             TargetCategory::Reaper => match self.reaper_target_type() {
-                ReaperTargetType::Transport => Some("Action"),
-                ReaperTargetType::AnyOn => Some("Parameter"),
+                ReaperTargetType::Transport
+                | ReaperTargetType::Punch
+                | ReaperTargetType::UndoRedo
+                | ReaperTargetType::TimeSelection => Some("Action"),
+                ReaperTargetType::AnyOn | ReaperTargetType::Metronome => Some("Parameter"),
                 ReaperTargetType::AutomationModeOverride => Some("Behavior"),
                 ReaperTargetType::GoToBookmark => match self.target.bookmark_type.get() {
                     BookmarkType::Marker => Some("Marker"),
                     BookmarkType::Region => Some("Region"),
                 },
+                ReaperTargetType::NavigateBookmarks => Some("Mode"),
                 ReaperTargetType::SendMidi => Some("Output"),
                 ReaperTargetType::SendOsc => Some("Output"),
-                ReaperTargetType::LoadMappingSnapshot => Some("Snapshot"),
-                ReaperTargetType::NavigateWithinGroup => Some("Group"),
+                ReaperTargetType::SendProgramChange => Some("Output"),
+                ReaperTargetType::SendMidiClock => Some("Output"),
+                ReaperTargetType::LoadMappingSnapshot | ReaperTargetType::SaveMappingSnapshot => {
+                    Some("Snapshot")
+                }
+                ReaperTargetType::NavigateWithinGroup | ReaperTargetType::EnableGroup => {
+                    Some("Group")
+                }
+                ReaperTargetType::AdjustMappingMode => Some("Mapping"),
+                ReaperTargetType::InvokeInstance => Some("Session"),
+                ReaperTargetType::SelectLayer => Some("Layer"),
+                ReaperTargetType::SetBankOffset => Some("Offset"),
+                ReaperTargetType::FxParameterBank => Some("Bank size"),
                 t if t.supports_feedback_resolution() => Some("Feedback"),
                 _ if self.target.supports_track() => Some("Track"),
                 _ => None,
@@ -3193,10 +3932,6 @@ impl<'a> ImmutableMappingPanel<'a> {
                         )
                         .unwrap();
                 }
-                ReaperTargetType::LoadMappingSnapshot => {
-                    combo.show();
-                    combo.select_only_combo_box_item("Initial");
-                }
                 t if t.supports_feedback_resolution() => {
                     combo.show();
                     combo.fill_combo_box_indexed(FeedbackResolution::into_enum_iter());
@@ -3233,6 +3968,55 @@ impl<'a> ImmutableMappingPanel<'a> {
                         )
                         .unwrap();
                 }
+                ReaperTargetType::Punch => {
+                    combo.show();
+                    combo.fill_combo_box_indexed(PunchAction::into_enum_iter());
+                    combo
+                        .select_combo_box_item_by_index(
+                            self.mapping.target_model.punch_action.get().into(),
+                        )
+                        .unwrap();
+                }
+                ReaperTargetType::UndoRedo => {
+                    combo.show();
+                    combo.fill_combo_box_indexed(UndoRedoAction::into_enum_iter());
+                    combo
+                        .select_combo_box_item_by_index(
+                            self.mapping.target_model.undo_redo_action.get().into(),
+                        )
+                        .unwrap();
+                }
+                ReaperTargetType::TimeSelection => {
+                    combo.show();
+                    combo.fill_combo_box_indexed(TimeSelectionAction::into_enum_iter());
+                    combo
+                        .select_combo_box_item_by_index(
+                            self.mapping.target_model.time_selection_action.get().into(),
+                        )
+                        .unwrap();
+                }
+                ReaperTargetType::NavigateBookmarks => {
+                    combo.show();
+                    combo.fill_combo_box_indexed(BookmarkNavigationMode::into_enum_iter());
+                    combo
+                        .select_combo_box_item_by_index(
+                            self.mapping
+                                .target_model
+                                .bookmark_navigation_mode
+                                .get()
+                                .into(),
+                        )
+                        .unwrap();
+                }
+                ReaperTargetType::Metronome => {
+                    combo.show();
+                    combo.fill_combo_box_indexed(MetronomeParameter::into_enum_iter());
+                    combo
+                        .select_combo_box_item_by_index(
+                            self.mapping.target_model.metronome_parameter.get().into(),
+                        )
+                        .unwrap();
+                }
                 ReaperTargetType::AnyOn => {
                     combo.show();
                     combo.fill_combo_box_indexed(AnyOnParameter::into_enum_iter());
@@ -3267,7 +4051,7 @@ impl<'a> ImmutableMappingPanel<'a> {
                         self.target.bookmark_ref.get(),
                     );
                 }
-                ReaperTargetType::NavigateWithinGroup => {
+                ReaperTargetType::NavigateWithinGroup | ReaperTargetType::EnableGroup => {
                     combo.show();
                     let compartment = self.mapping.compartment();
                     // Fill box
@@ -3327,6 +4111,35 @@ impl<'a> ImmutableMappingPanel<'a> {
                         combo.select_combo_box_item_by_data(-1).unwrap();
                     };
                 }
+                ReaperTargetType::SendProgramChange | ReaperTargetType::SendMidiClock => {
+                    combo.show();
+                    combo.fill_combo_box_with_data_small(
+                        std::iter::once((-1isize, "<Feedback output>".to_string())).chain(
+                            Reaper::get()
+                                .midi_output_devices()
+                                .filter(|d| d.is_available())
+                                .map(|dev| {
+                                    let label = format!(
+                                        "{}. {}",
+                                        dev.id().get(),
+                                        dev.name().into_inner().to_string_lossy()
+                                    );
+                                    (dev.id().get() as isize, label)
+                                }),
+                        ),
+                    );
+                    if let Some(dev_id) = self.mapping.target_model.midi_output_device_id.get() {
+                        let data = dev_id.get() as isize;
+                        if combo.select_combo_box_item_by_data(data).is_err() {
+                            combo.select_new_combo_box_item(format!(
+                                "<Not present> ({})",
+                                dev_id.get()
+                            ));
+                        }
+                    } else {
+                        combo.select_combo_box_item_by_data(-1).unwrap();
+                    };
+                }
                 _ if self.target.supports_track() => {
                     if matches!(
                         self.target.track_type.get(),
@@ -3395,6 +4208,30 @@ impl<'a> ImmutableMappingPanel<'a> {
                     };
                     control.set_text(text);
                 }
+                ReaperTargetType::LoadMappingSnapshot | ReaperTargetType::SaveMappingSnapshot => {
+                    control.show();
+                    control.set_text(self.target.mapping_snapshot_id.get_ref().as_str());
+                }
+                ReaperTargetType::AdjustMappingMode => {
+                    control.show();
+                    control.set_text(self.target.mapping_key.get_ref().as_str());
+                }
+                ReaperTargetType::InvokeInstance => {
+                    control.show();
+                    control.set_text(self.target.instance_session_id.get_ref().as_str());
+                }
+                ReaperTargetType::SelectLayer => {
+                    control.show();
+                    control.set_text(self.target.layer.get().get().to_string());
+                }
+                ReaperTargetType::SetBankOffset => {
+                    control.show();
+                    control.set_text(self.target.bank_offset.get().to_string());
+                }
+                ReaperTargetType::FxParameterBank => {
+                    control.show();
+                    control.set_text(self.target.fx_parameter_bank_size.get().to_string());
+                }
                 _ => {
                     control.hide();
                 }
@@ -3460,6 +4297,7 @@ impl<'a> ImmutableMappingPanel<'a> {
         let text = match self.target_category() {
             TargetCategory::Reaper => match self.reaper_target_type() {
                 t if t.supports_slot() => Some("..."),
+                ReaperTargetType::ClipColumnStop | ReaperTargetType::ClipSceneLaunch => Some("..."),
                 ReaperTargetType::SendMidi => Some("Pick!"),
                 _ => None,
             },
@@ -3475,6 +4313,16 @@ impl<'a> ImmutableMappingPanel<'a> {
             TargetCategory::Reaper => match self.reaper_target_type() {
                 ReaperTargetType::Action => Some("Pick!"),
                 ReaperTargetType::LoadFxSnapshot => Some("Take!"),
+                ReaperTargetType::ClipTransport
+                    if matches!(
+                        self.target.transport_action.get(),
+                        TransportAction::PlayStop
+                            | TransportAction::PlayPause
+                            | TransportAction::Stop
+                    ) =>
+                {
+                    Some("Stop...")
+                }
                 _ => None,
             },
             TargetCategory::Virtual => None,
@@ -3516,6 +4364,17 @@ impl<'a> ImmutableMappingPanel<'a> {
                     let text = format_osc_arg_index(self.target.osc_arg_index.get());
                     control.set_text(text.as_str());
                 }
+                ReaperTargetType::SetExtState => {
+                    control.show();
+                    let text = self.target.ext_state_key.get_ref();
+                    control.set_text(text.as_str());
+                }
+                ReaperTargetType::SendProgramChange => {
+                    control.show();
+                    let text =
+                        format_program_change_patches(self.target.program_change_patches.get_ref());
+                    control.set_text(text);
+                }
                 ReaperTargetType::FxParameter => {
                     let text = match self.target.param_type.get() {
                         VirtualFxParameterType::Dynamic => {
@@ -3579,11 +4438,41 @@ impl<'a> ImmutableMappingPanel<'a> {
                     let text = self.target.raw_midi_pattern.get_ref();
                     control.set_text(text.as_str());
                 }
+                ReaperTargetType::SendMidiMacro => {
+                    control.show();
+                    let text = self.target.midi_macro_name.get_ref();
+                    control.set_text(text.as_str());
+                }
+                ReaperTargetType::InvokeInstance => {
+                    control.show();
+                    let text = self.target.instance_message_name.get_ref();
+                    control.set_text(text.as_str());
+                }
+                ReaperTargetType::SendProgramChange => {
+                    control.show();
+                    let channel = self.target.midi_channel.get();
+                    control.set_text((channel + 1).to_string());
+                }
+                ReaperTargetType::LoadMappingSnapshot => {
+                    control.show();
+                    let fade_time = self.target.mapping_snapshot_fade_time.get();
+                    control.set_text(fade_time.as_millis().to_string());
+                }
                 ReaperTargetType::SendOsc => {
                     control.show();
                     let text = self.target.osc_address_pattern.get_ref();
                     control.set_text(text.as_str());
                 }
+                ReaperTargetType::SetExtState => {
+                    control.show();
+                    let text = self.target.ext_state_section.get_ref();
+                    control.set_text(text.as_str());
+                }
+                ReaperTargetType::RunScript => {
+                    control.show();
+                    let text = self.target.script_path.get_ref();
+                    control.set_text(text.as_str());
+                }
                 t if t.supports_fx() => {
                     let text = match self.target.fx_type.get() {
                         VirtualFxType::Dynamic => self.target.fx_expression.get_ref().clone(),
@@ -3617,12 +4506,25 @@ impl<'a> ImmutableMappingPanel<'a> {
             TargetCategory::Reaper => match self.reaper_target_type() {
                 ReaperTargetType::Action => Some("Invoke"),
                 ReaperTargetType::TrackSolo => Some("Behavior"),
+                ReaperTargetType::TrackDualPan => Some("Channel"),
                 ReaperTargetType::TrackShow => Some("Area"),
                 ReaperTargetType::AutomationTouchState => Some("Type"),
                 ReaperTargetType::SendMidi => Some("Pattern"),
+                ReaperTargetType::SendMidiMacro => Some("Macro"),
+                ReaperTargetType::InvokeInstance => Some("Message"),
+                ReaperTargetType::SendProgramChange => Some("Channel"),
+                ReaperTargetType::SendMidiClock => Some("MTC"),
                 ReaperTargetType::SendOsc => Some("Address"),
+                ReaperTargetType::SetExtState => Some("Section"),
+                ReaperTargetType::RunScript => Some("Script"),
+                ReaperTargetType::LoadMappingSnapshot => Some("Fade (ms)"),
+                ReaperTargetType::AdjustMappingMode => Some("Setting"),
                 _ if self.target.supports_automation_mode() => Some("Mode"),
+                ReaperTargetType::TrackInputMonitoring => Some("Mode"),
+                ReaperTargetType::TimeSelection => Some("Range"),
                 t if t.supports_slot() => Some("Slot"),
+                ReaperTargetType::ClipColumnStop => Some("Matrix"),
+                ReaperTargetType::ClipSceneLaunch => Some("Scene"),
                 t if t.supports_fx() => Some("FX"),
                 t if t.supports_send() => Some("Kind"),
                 _ => None,
@@ -3641,7 +4543,12 @@ impl<'a> ImmutableMappingPanel<'a> {
                 ReaperTargetType::FxParameter => Some("Parameter"),
                 ReaperTargetType::LoadFxSnapshot => Some("Snapshot"),
                 ReaperTargetType::SendOsc => Some("Argument"),
+                ReaperTargetType::SetExtState => Some("Key"),
+                ReaperTargetType::TrackRecordInput => Some("Inputs"),
+                ReaperTargetType::SendProgramChange => Some("Patches"),
                 ReaperTargetType::ClipTransport => Some("Action"),
+                ReaperTargetType::EnableGroup => Some("Mode"),
+                t if t.supports_cue_output() => Some("Cue output"),
                 t if t.supports_track_exclusivity() => Some("Exclusive"),
                 t if t.supports_fx_display_type() => Some("Display"),
                 t if t.supports_tags() => Some("Tags"),
@@ -3664,24 +4571,39 @@ impl<'a> ImmutableMappingPanel<'a> {
         let state = match self.target_category() {
             TargetCategory::Reaper => match self.reaper_target_type() {
                 t if t.supports_slot() => {
-                    let instance_state = self.session.instance_state().borrow();
-                    let slot = instance_state.get_slot(self.target.slot_index.get()).ok();
-                    let (label, enabled) = if let Some(slot) = slot {
-                        if let Some(content) = &slot.descriptor().content {
-                            match content {
-                                SlotContent::File { file } => (
-                                    file.to_string_lossy().to_string(),
-                                    slot.clip_info().is_some(),
-                                ),
+                    let matrix_id = self.target.clip_matrix_id.get_ref().as_ref();
+                    let (label, enabled) =
+                        with_clip_matrix(self.session.instance_state(), matrix_id, |m| {
+                            let slot = match m
+                                .slot_index(self.target.slot_address())
+                                .and_then(|i| m.get_slot(i))
+                            {
+                                Ok(s) => s,
+                                Err(_) => return ("<Invalid slot>".to_owned(), false),
+                            };
+                            if let Some(content) = &slot.descriptor().content {
+                                match content {
+                                    SlotContent::File { file } => (
+                                        file.to_string_lossy().to_string(),
+                                        slot.clip_info().is_some(),
+                                    ),
+                                    SlotContent::Embedded { .. } => {
+                                        ("<Embedded>".to_owned(), slot.clip_info().is_some())
+                                    }
+                                }
+                            } else {
+                                ("<Slot empty>".to_owned(), false)
                             }
-                        } else {
-                            ("<Slot empty>".to_owned(), false)
-                        }
-                    } else {
-                        ("<Invalid slot>".to_owned(), false)
-                    };
+                        });
                     Some((label, enabled))
                 }
+                ReaperTargetType::ClipColumnStop | ReaperTargetType::ClipSceneLaunch => {
+                    let label = match self.target.clip_matrix_id.get_ref() {
+                        None => "<Own>".to_owned(),
+                        Some(id) => id.to_string(),
+                    };
+                    Some((label, true))
+                }
                 _ => None,
             },
             TargetCategory::Virtual => None,
@@ -3725,12 +4647,36 @@ impl<'a> ImmutableMappingPanel<'a> {
             TargetCategory::Reaper => match self.target.r#type.get() {
                 t if t.supports_slot() => {
                     combo.show();
+                    let matrix_id = self.target.clip_matrix_id.get_ref().as_ref();
+                    let size =
+                        with_clip_matrix(self.session.instance_state(), matrix_id, |m| m.size());
                     combo.fill_combo_box_indexed(
-                        (0..CLIP_SLOT_COUNT).map(|i| format!("Slot {}", i + 1)),
+                        size.slot_addresses().map(|a| format!("Slot {}", a)),
                     );
-                    combo
-                        .select_combo_box_item_by_index(self.target.slot_index.get())
-                        .unwrap();
+                    let address = self.target.slot_address();
+                    match size.slot_index(address) {
+                        None => {
+                            combo.select_new_combo_box_item(format!("<Not present> ({})", address));
+                        }
+                        Some(i) => {
+                            combo.select_combo_box_item_by_index(i).unwrap();
+                        }
+                    }
+                }
+                ReaperTargetType::ClipSceneLaunch => {
+                    combo.show();
+                    let matrix_id = self.target.clip_matrix_id.get_ref().as_ref();
+                    let size =
+                        with_clip_matrix(self.session.instance_state(), matrix_id, |m| m.size());
+                    combo.fill_combo_box_indexed(
+                        (0..size.rows).map(|row| format!("Row {}", row + 1)),
+                    );
+                    let row = self.target.slot_row.get();
+                    if row < size.rows {
+                        combo.select_combo_box_item_by_index(row).unwrap();
+                    } else {
+                        combo.select_new_combo_box_item(format!("<Not present> (Row {})", row + 1));
+                    }
                 }
                 t if t.supports_fx() => {
                     combo.show();
@@ -3766,6 +4712,20 @@ impl<'a> ImmutableMappingPanel<'a> {
                     let tag = self.target.osc_arg_type_tag.get();
                     combo.select_combo_box_item_by_index(tag.into()).unwrap();
                 }
+                ReaperTargetType::SetExtState => {
+                    combo.show();
+                    combo.fill_combo_box_indexed(ExtStateValueFormat::into_enum_iter());
+                    combo
+                        .select_combo_box_item_by_index(self.target.ext_state_format.get().into())
+                        .unwrap();
+                }
+                ReaperTargetType::TrackRecordInput => {
+                    combo.show();
+                    combo.fill_combo_box_indexed(RecordInputKind::into_enum_iter());
+                    combo
+                        .select_combo_box_item_by_index(self.target.record_input_kind.get().into())
+                        .unwrap();
+                }
                 ReaperTargetType::FxParameter => {
                     combo.show();
                     combo.fill_combo_box_indexed(VirtualFxParameterType::into_enum_iter());
@@ -3773,6 +4733,28 @@ impl<'a> ImmutableMappingPanel<'a> {
                         .select_combo_box_item_by_index(self.target.param_type.get().into())
                         .unwrap();
                 }
+                t if t.supports_cue_output() => {
+                    combo.show();
+                    // Stereo pairs can start at any hardware output channel but the last one.
+                    let pair_count = Reaper::get()
+                        .medium_reaper()
+                        .get_num_audio_outputs()
+                        .saturating_sub(1)
+                        .max(self.target.cue_output.get() + 1);
+                    combo.fill_combo_box_indexed(
+                        (0..pair_count).map(|i| format!("Output {}/{}", i + 1, i + 2)),
+                    );
+                    combo
+                        .select_combo_box_item_by_index(self.target.cue_output.get() as _)
+                        .unwrap();
+                }
+                ReaperTargetType::EnableGroup => {
+                    combo.show();
+                    combo.fill_combo_box_indexed(GroupEnableMode::into_enum_iter());
+                    combo
+                        .select_combo_box_item_by_index(self.target.group_enable_mode.get().into())
+                        .unwrap();
+                }
                 ReaperTargetType::NavigateWithinGroup => {
                     combo.show();
                     combo.fill_combo_box_indexed(SimpleExclusivity::into_enum_iter());
@@ -3880,6 +4862,13 @@ impl<'a> ImmutableMappingPanel<'a> {
                         .select_combo_box_item_by_index(self.target.solo_behavior.get().into())
                         .unwrap();
                 }
+                ReaperTargetType::TrackDualPan => {
+                    combo.show();
+                    combo.fill_combo_box_indexed(DualPanComponent::into_enum_iter());
+                    combo
+                        .select_combo_box_item_by_index(self.target.dual_pan_component.get().into())
+                        .unwrap();
+                }
                 ReaperTargetType::TrackShow => {
                     combo.show();
                     combo.fill_combo_box_indexed(RealearnTrackArea::into_enum_iter());
@@ -3894,6 +4883,22 @@ impl<'a> ImmutableMappingPanel<'a> {
                         .select_combo_box_item_by_index(self.target.automation_mode.get().into())
                         .unwrap();
                 }
+                ReaperTargetType::TimeSelection => {
+                    combo.show();
+                    combo.fill_combo_box_indexed(TimeRangeKind::into_enum_iter());
+                    combo
+                        .select_combo_box_item_by_index(self.target.time_range_kind.get().into())
+                        .unwrap();
+                }
+                ReaperTargetType::TrackInputMonitoring => {
+                    combo.show();
+                    combo.fill_combo_box_indexed(RealearnInputMonitoringMode::into_enum_iter());
+                    combo
+                        .select_combo_box_item_by_index(
+                            self.target.input_monitoring_mode.get().into(),
+                        )
+                        .unwrap();
+                }
                 ReaperTargetType::AutomationTouchState => {
                     combo.show();
                     combo.fill_combo_box_indexed(TouchedParameterType::into_enum_iter());
@@ -3903,6 +4908,33 @@ impl<'a> ImmutableMappingPanel<'a> {
                         )
                         .unwrap();
                 }
+                ReaperTargetType::AdjustMappingMode => {
+                    combo.show();
+                    combo.fill_combo_box_indexed(MappingModeSetting::into_enum_iter());
+                    combo
+                        .select_combo_box_item_by_index(
+                            self.target.mapping_mode_setting.get().into(),
+                        )
+                        .unwrap();
+                }
+                ReaperTargetType::SendMidiClock => {
+                    combo.show();
+                    combo.fill_combo_box_with_data_small(
+                        std::iter::once((-1isize, "Off".to_string())).chain(
+                            MtcFrameRate::into_enum_iter()
+                                .enumerate()
+                                .map(|(i, rate)| (i as isize, rate.to_string())),
+                        ),
+                    );
+                    let data = self
+                        .target
+                        .midi_clock_mtc_frame_rate
+                        .get()
+                        .and_then(|rate| MtcFrameRate::into_enum_iter().position(|r| r == rate))
+                        .map(|i| i as isize)
+                        .unwrap_or(-1);
+                    combo.select_combo_box_item_by_data(data).unwrap();
+                }
                 _ => {
                     combo.hide();
                 }
@@ -4027,7 +5059,7 @@ impl<'a> ImmutableMappingPanel<'a> {
         let state = match self.target.category.get() {
             TargetCategory::Reaper => match self.target.r#type.get() {
                 ReaperTargetType::Action => Some(("With track", self.target.with_track.get())),
-                ReaperTargetType::GoToBookmark => {
+                ReaperTargetType::GoToBookmark | ReaperTargetType::NavigateBookmarks => {
                     let is_regions = self.target.bookmark_type.get() == BookmarkType::Region;
                     Some(("Regions", is_regions))
                 }
@@ -4061,10 +5093,12 @@ impl<'a> ImmutableMappingPanel<'a> {
     fn invalidate_target_check_box_2(&self) {
         let state = match self.target.category.get() {
             TargetCategory::Reaper => match self.target.r#type.get() {
-                ReaperTargetType::LoadMappingSnapshot => Some((
-                    "Active mappings only",
-                    self.target.active_mappings_only.get(),
-                )),
+                ReaperTargetType::LoadMappingSnapshot | ReaperTargetType::SaveMappingSnapshot => {
+                    Some((
+                        "Active mappings only",
+                        self.target.active_mappings_only.get(),
+                    ))
+                }
                 _ if self.mapping.target_model.supports_track_must_be_selected() => {
                     if self
                         .target
@@ -4105,6 +5139,10 @@ impl<'a> ImmutableMappingPanel<'a> {
                     }
                 }
                 ReaperTargetType::Seek => Some(("Use project", self.target.use_project.get())),
+                ReaperTargetType::TrackDelete => Some((
+                    "Press twice to confirm",
+                    self.target.require_confirmation.get(),
+                )),
                 _ => None,
             },
             TargetCategory::Virtual => None,
@@ -4126,6 +5164,7 @@ impl<'a> ImmutableMappingPanel<'a> {
                 {
                     Some(("Next bar", self.target.next_bar.get()))
                 }
+                ReaperTargetType::ClipSceneLaunch => Some(("Next bar", self.target.next_bar.get())),
                 t if t.supports_poll_for_feedback() => {
                     Some(("Poll for feedback", self.target.poll_for_feedback.get()))
                 }
@@ -4152,15 +5191,9 @@ impl<'a> ImmutableMappingPanel<'a> {
                         TransportAction::PlayStop | TransportAction::PlayPause
                     ) =>
                 {
-                    let is_enabled = !self.target.next_bar.get();
-                    self.view
-                        .require_control(checkbox_id)
-                        .set_enabled(is_enabled);
-                    Some((
-                        "Buffered",
-                        self.target.slot_play_options().is_effectively_buffered(),
-                    ))
+                    self.buffered_check_box_state(checkbox_id)
                 }
+                ReaperTargetType::ClipSceneLaunch => self.buffered_check_box_state(checkbox_id),
                 _ => None,
             },
             TargetCategory::Virtual => None,
@@ -4168,6 +5201,17 @@ impl<'a> ImmutableMappingPanel<'a> {
         self.invalidate_check_box(checkbox_id, state);
     }
 
+    fn buffered_check_box_state(&self, checkbox_id: u32) -> Option<(&'static str, bool)> {
+        let is_enabled = !self.target.next_bar.get();
+        self.view
+            .require_control(checkbox_id)
+            .set_enabled(is_enabled);
+        Some((
+            "Buffered",
+            self.target.slot_play_options().is_effectively_buffered(),
+        ))
+    }
+
     fn invalidate_target_check_box_6(&self) {
         let state = match self.target.category.get() {
             TargetCategory::Reaper => match self.target.r#type.get() {
@@ -4495,6 +5539,28 @@ impl<'a> ImmutableMappingPanel<'a> {
                     .invalidate_due_to_changed_prop(ItemProp::EelCondition, initiator);
             },
         );
+        self.panel.when(
+            self.mapping
+                .activation_condition_model
+                .expression_condition
+                .changed_with_initiator(),
+            |view, initiator| {
+                view.panel
+                    .mapping_header_panel
+                    .invalidate_due_to_changed_prop(ItemProp::ExpressionCondition, initiator);
+            },
+        );
+        self.panel.when(
+            self.mapping
+                .activation_condition_model
+                .reaper_state_condition
+                .changed(),
+            |view, _| {
+                view.panel
+                    .mapping_header_panel
+                    .invalidate_due_to_changed_prop(ItemProp::ReaperStateCondition, None);
+            },
+        );
     }
 
     fn register_source_listeners(&self) {
@@ -4504,6 +5570,7 @@ impl<'a> ImmutableMappingPanel<'a> {
                 .category
                 .changed()
                 .merge(source.midi_source_type.changed())
+                .merge(source.reaper_source_type.changed())
                 .merge(source.control_element_type.changed()),
             |view, _| {
                 view.invalidate_source_controls();
@@ -4529,29 +5596,45 @@ impl<'a> ImmutableMappingPanel<'a> {
                 .parameter_number_message_number
                 .changed_with_initiator()
                 .merge(source.osc_arg_index.changed_with_initiator())
+                .merge(source.game_controller_index.changed_with_initiator())
+                .merge(source.instance_message_name.changed_with_initiator())
+                .merge(source.mmc_device_id.changed_with_initiator())
                 .merge(source.control_element_id.changed_with_initiator()),
             |view, initiator| {
                 view.invalidate_source_line_4_edit_control(initiator);
             },
         );
-        self.panel.when(source.is_registered.changed(), |view, _| {
-            view.invalidate_source_line_4_check_box();
-        });
+        self.panel.when(
+            source
+                .is_registered
+                .changed()
+                .merge(source.osc_feedback_is_relative.changed()),
+            |view, _| {
+                view.invalidate_source_line_4_check_box();
+            },
+        );
         self.panel.when(
             source
                 .custom_character
                 .changed()
-                .merge(source.osc_arg_type_tag.changed()),
+                .merge(source.osc_arg_type_tag.changed())
+                .merge(source.game_controller_element.changed())
+                .merge(source.mmc_command.changed()),
             |view, _| {
                 view.invalidate_source_line_5_combo_box();
                 view.invalidate_mode_controls();
                 view.invalidate_help();
             },
         );
-        self.panel
-            .when(source.midi_clock_transport_message.changed(), |view, _| {
+        self.panel.when(
+            source
+                .midi_clock_transport_message
+                .changed()
+                .merge(source.osc_delta_mode.changed()),
+            |view, _| {
                 view.invalidate_source_line_3_combo_box_2();
-            });
+            },
+        );
         self.panel.when(source.display_type.changed(), |view, _| {
             view.invalidate_source_controls();
         });
@@ -4943,6 +6026,7 @@ impl<'a> ImmutableMappingPanel<'a> {
             self.view.require_control(edit_control_id),
             self.view.require_control(value_text_control_id),
             value,
+            None,
             initiator,
             edit_control_id,
             false,
@@ -5321,6 +6405,11 @@ impl<'a> ImmutableMappingPanel<'a> {
                 .merge(target.bookmark_anchor_type.changed_with_initiator())
                 .merge(target.bookmark_ref.changed_with_initiator())
                 .merge(target.transport_action.changed_with_initiator())
+                .merge(target.punch_action.changed_with_initiator())
+                .merge(target.undo_redo_action.changed_with_initiator())
+                .merge(target.time_selection_action.changed_with_initiator())
+                .merge(target.bookmark_navigation_mode.changed_with_initiator())
+                .merge(target.metronome_parameter.changed_with_initiator())
                 .merge(target.any_on_parameter.changed_with_initiator())
                 .merge(target.action.changed_with_initiator()),
             |view, initiator| {
@@ -5390,10 +6479,17 @@ impl<'a> ImmutableMappingPanel<'a> {
                 .solo_behavior
                 .changed()
                 .merge(target.touched_parameter_type.changed())
+                .merge(target.mapping_mode_setting.changed())
+                .merge(target.midi_clock_mtc_frame_rate.changed())
                 .merge(target.automation_mode.changed())
+                .merge(target.input_monitoring_mode.changed())
+                .merge(target.time_range_kind.changed())
                 .merge(target.automation_mode_override_type.changed())
                 .merge(target.track_area.changed())
-                .merge(target.slot_index.changed()),
+                .merge(target.dual_pan_component.changed())
+                .merge(target.clip_matrix_id.changed())
+                .merge(target.slot_row.changed())
+                .merge(target.slot_column.changed()),
             |view, _| {
                 view.invalidate_target_line_3(None);
             },
@@ -5402,7 +6498,9 @@ impl<'a> ImmutableMappingPanel<'a> {
             target
                 .fx_snapshot
                 .changed()
-                .merge(target.fx_display_type.changed()),
+                .merge(target.fx_display_type.changed())
+                .merge(target.cue_output.changed())
+                .merge(target.record_input_kind.changed()),
             |view, _| {
                 view.invalidate_target_line_4(None);
                 view.invalidate_target_value_controls();
@@ -5428,7 +6526,10 @@ impl<'a> ImmutableMappingPanel<'a> {
             target
                 .osc_arg_type_tag
                 .changed_with_initiator()
-                .merge(target.osc_arg_index.changed_with_initiator()),
+                .merge(target.osc_arg_index.changed_with_initiator())
+                .merge(target.program_change_patches.changed_with_initiator())
+                .merge(target.ext_state_key.changed_with_initiator())
+                .merge(target.ext_state_format.changed_with_initiator()),
             |view, initiator| {
                 view.invalidate_target_line_4(initiator);
                 view.invalidate_target_value_controls();
@@ -5465,7 +6566,8 @@ impl<'a> ImmutableMappingPanel<'a> {
             target
                 .enable_only_if_fx_has_focus
                 .changed()
-                .merge(target.use_project.changed()),
+                .merge(target.use_project.changed())
+                .merge(target.require_confirmation.changed()),
             |view, _| {
                 view.invalidate_target_check_boxes();
             },
@@ -5479,6 +6581,10 @@ impl<'a> ImmutableMappingPanel<'a> {
                 view.invalidate_target_check_boxes();
             },
         );
+        self.panel
+            .when(target.stop_quantization.changed(), |view, _| {
+                view.invalidate_target_line_4_button();
+            });
         self.panel.when(
             target
                 .use_loop_points
@@ -5497,9 +6603,15 @@ impl<'a> ImmutableMappingPanel<'a> {
             .when(target.active_mappings_only.changed(), |view, _| {
                 view.invalidate_target_check_box_2();
             });
-        self.panel.when(target.exclusivity.changed(), |view, _| {
-            view.invalidate_target_line_4_combo_box_1();
-        });
+        self.panel.when(
+            target
+                .exclusivity
+                .changed()
+                .merge(target.group_enable_mode.changed()),
+            |view, _| {
+                view.invalidate_target_line_4_combo_box_1();
+            },
+        );
         self.panel
             .when(target.feedback_resolution.changed(), |view, _| {
                 view.invalidate_target_line_2_combo_box_1();
@@ -5517,7 +6629,8 @@ impl<'a> ImmutableMappingPanel<'a> {
             target
                 .send_midi_destination
                 .changed()
-                .merge(target.osc_dev_id.changed()),
+                .merge(target.osc_dev_id.changed())
+                .merge(target.midi_output_device_id.changed()),
             |view, _| {
                 view.invalidate_target_line_2(None);
             },
@@ -5526,12 +6639,32 @@ impl<'a> ImmutableMappingPanel<'a> {
             target
                 .raw_midi_pattern
                 .changed_with_initiator()
-                .merge(target.osc_address_pattern.changed_with_initiator()),
+                .merge(target.midi_macro_name.changed_with_initiator())
+                .merge(target.instance_message_name.changed_with_initiator())
+                .merge(target.midi_channel.changed_with_initiator())
+                .merge(target.osc_address_pattern.changed_with_initiator())
+                .merge(target.ext_state_section.changed_with_initiator())
+                .merge(target.script_path.changed_with_initiator())
+                .merge(target.mapping_snapshot_fade_time.changed_with_initiator()),
             |view, initiator| {
                 view.invalidate_target_line_3(initiator);
                 view.invalidate_mode_controls();
             },
         );
+        self.panel.when(
+            target
+                .mapping_snapshot_id
+                .changed_with_initiator()
+                .merge(target.mapping_key.changed_with_initiator())
+                .merge(target.instance_session_id.changed_with_initiator())
+                .merge(target.layer.changed_with_initiator())
+                .merge(target.bank_offset.changed_with_initiator())
+                .merge(target.fx_parameter_bank_size.changed_with_initiator()),
+            |view, initiator| {
+                view.invalidate_window_title();
+                view.invalidate_target_line_2_edit_control(initiator);
+            },
+        );
         self.panel
             .when(target.tags.changed_with_initiator(), |view, initiator| {
                 view.invalidate_target_line_4_edit_control(initiator);
@@ -5826,6 +6959,11 @@ impl View for MappingPanel {
             }
             root::ID_SETTINGS_REVERSE_CHECK_BOX => self.write(|p| p.update_mode_reverse()),
             root::ID_SETTINGS_RESET_BUTTON => self.write(|p| p.reset_mode()),
+            root::ID_SETTINGS_MORE_BUTTON => {
+                if let Err(e) = self.more_settings_button_pressed() {
+                    self.view.require_window().alert("ReaLearn", e);
+                }
+            }
             root::IDC_MODE_FEEDBACK_TYPE_BUTTON => {
                 let _ = self.feedback_type_button_pressed();
             }
@@ -6143,6 +7281,7 @@ fn invalidate_target_controls_free(
     edit_control: Window,
     value_text_control: Window,
     value: AbsoluteValue,
+    origin: Option<TargetValueChangeOrigin>,
     initiator: Option<u32>,
     edit_control_id: u32,
     set_text_only_if_edit_control_not_focused: bool,
@@ -6185,7 +7324,11 @@ fn invalidate_target_controls_free(
         edit_control.set_text(edit_text);
     }
     // Value label
-    value_text_control.set_text(value_text);
+    match origin {
+        Some(o) if value_text.is_empty() => value_text_control.set_text(format!("({})", o)),
+        Some(o) => value_text_control.set_text(format!("{} ({})", value_text, o)),
+        None => value_text_control.set_text(value_text),
+    }
 }
 
 fn get_text_right_to_target_edit_control(
@@ -6773,7 +7916,79 @@ fn format_osc_arg_index(index: Option<u32>) -> String {
     }
 }
 
+/// Accepts either one length for both fades or two comma-separated lengths (fade-in, fade-out).
+fn parse_slot_fades(text: &str) -> Option<SlotFades> {
+    let lengths: Vec<u32> = text
+        .split(',')
+        .map(|t| t.trim().parse().ok())
+        .collect::<Option<_>>()?;
+    match lengths.as_slice() {
+        [both] => Some(SlotFades::new(*both, *both)),
+        [fade_in, fade_out] => Some(SlotFades::new(*fade_in, *fade_out)),
+        _ => None,
+    }
+}
+
+/// Formats the velocity threshold settings as expected by [`parse_velocity_threshold`]. Empty if
+/// there's no threshold.
+fn format_velocity_threshold(mode_model: &ModeModel) -> String {
+    let threshold = match mode_model.velocity_threshold.get() {
+        None => return String::new(),
+        Some(t) => t,
+    };
+    let format_value = |v: Option<UnitValue>| {
+        v.map(|v| format_percentage_without_unit(v.get()))
+            .unwrap_or_default()
+    };
+    format!(
+        "{}, {}, {}",
+        format_percentage_without_unit(threshold.get()),
+        format_value(mode_model.velocity_below_threshold_value.get()),
+        format_value(mode_model.velocity_above_threshold_value.get())
+    )
+}
+
+/// Parses threshold, soft value and hard value in percent, e.g. `60, 50, 100`. An empty soft or
+/// hard value means that these presses are ignored.
+fn parse_velocity_threshold(
+    text: &str,
+) -> Option<(UnitValue, Option<UnitValue>, Option<UnitValue>)> {
+    let parse_optional_value = |t: &str| {
+        let t = t.trim();
+        if t.is_empty() {
+            Some(None)
+        } else {
+            parse_unit_value_from_percentage(t).ok().map(Some)
+        }
+    };
+    let parts: Vec<&str> = text.split(',').collect();
+    match parts.as_slice() {
+        [threshold, below_value, above_value] => Some((
+            parse_unit_value_from_percentage(threshold.trim()).ok()?,
+            parse_optional_value(below_value)?,
+            parse_optional_value(above_value)?,
+        )),
+        _ => None,
+    }
+}
+
+const PITCH_CLASS_NAMES: [&str; 12] = [
+    "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
+];
+
+enum MoreSettingsMenuAction {
+    SetVelocityThreshold,
+    SetQuantizationScale(Option<MusicalScale>),
+    SetQuantizationRoot(u8),
+}
+
 enum SlotMenuAction {
     ShowSlotInfo,
     FillWithItemSource,
+    SetTempo,
+    SetFades,
+    SetFollowAction,
+    ChooseSharedMatrix,
+    EmbedContents,
+    ExternalizeContents,
 }
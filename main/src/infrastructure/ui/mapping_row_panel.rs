@@ -20,13 +20,15 @@ use crate::infrastructure::ui::dialog_util::add_group_via_dialog;
 use crate::infrastructure::ui::util::{format_tags_as_csv, symbols};
 use crate::infrastructure::ui::{
     copy_text_to_clipboard, deserialize_api_object_from_lua, deserialize_data_object_from_json,
-    get_text_from_clipboard, serialize_data_object, util, DataObject, IndependentPanelManager,
-    SerializationFormat, SharedMainState,
+    get_midi_input_device_label, get_midi_output_device_label, get_text_from_clipboard,
+    serialize_data_object, util, DataObject, IndependentPanelManager, SerializationFormat,
+    SharedMainState,
 };
 use core::iter;
 use realearn_api::schema::{ApiObject, Envelope};
 use reaper_high::Reaper;
 use reaper_low::raw;
+use reaper_medium::{MidiInputDeviceId, MidiOutputDeviceId};
 use rxrust::prelude::*;
 use slog::debug;
 use std::cell::{Ref, RefCell};
@@ -204,6 +206,12 @@ impl MappingRowPanel {
                     )
                 }
             }
+        } else if self
+            .session()
+            .borrow()
+            .mapping_is_suspended(mapping.qualified_id())
+        {
+            format!("{}\n(device disconnected)", plain_label)
         } else {
             plain_label
         };
@@ -303,10 +311,12 @@ impl MappingRowPanel {
     }
 
     fn invalidate_on_indicator(&self, mapping: &MappingModel) {
-        let is_on = self
-            .session()
-            .borrow()
-            .mapping_is_on(mapping.qualified_id());
+        let is_on = {
+            let session = self.session();
+            let session = session.borrow();
+            let id = mapping.qualified_id();
+            session.mapping_is_on(id) && !session.mapping_is_suspended(id)
+        };
         self.view
             .require_control(root::ID_MAPPING_ROW_SOURCE_LABEL_TEXT)
             .set_enabled(is_on);
@@ -386,6 +396,10 @@ impl MappingRowPanel {
         self.when(instance_state.on_mappings_changed(), |view| {
             view.with_mapping(Self::invalidate_on_indicator);
         });
+        self.when(instance_state.io_connection_status_changed(), |view| {
+            view.with_mapping(Self::invalidate_on_indicator);
+            view.with_mapping(Self::invalidate_source_label);
+        });
         self.when(
             session
                 .main_preset_auto_load_mode
@@ -582,6 +596,9 @@ impl MappingRowPanel {
             PasteMappings(Vec<MappingModelData>),
             CopyPart(ObjectType),
             MoveMappingToGroup(Option<GroupId>),
+            ToggleSolo,
+            SetMidiInputDevice(Option<MidiInputDeviceId>),
+            SetMidiOutputDevice(Option<MidiOutputDeviceId>),
             CopyMappingAsLua(ConversionStyle),
             PasteFromLuaReplace(String),
             PasteFromLuaInsertBelow(String),
@@ -610,6 +627,10 @@ impl MappingRowPanel {
             let text_from_clipboard_clone = text_from_clipboard.clone();
             let data_object_from_clipboard_clone = data_object_from_clipboard.clone();
             let group_id = mapping.group_id.get();
+            let is_soloed = session.mapping_is_soloed(mapping.qualified_id());
+            let source_is_midi = mapping.source_model.category.get() == SourceCategory::Midi;
+            let midi_input_device = mapping.source_model.midi_input_device.get();
+            let midi_output_device = mapping.source_model.midi_output_device.get();
             let entries = vec![
                 item("Copy", || MenuAction::CopyPart(ObjectType::Mapping)),
                 {
@@ -680,6 +701,70 @@ impl MappingRowPanel {
                         }))
                         .collect(),
                 ),
+                item_with_opts(
+                    "Solo (mute control of all other mappings)",
+                    ItemOpts {
+                        enabled: true,
+                        checked: is_soloed,
+                    },
+                    || MenuAction::ToggleSolo,
+                ),
+                menu(
+                    "MIDI input device",
+                    iter::once(item_with_opts(
+                        "<Instance control input>",
+                        ItemOpts {
+                            enabled: source_is_midi,
+                            checked: midi_input_device.is_none(),
+                        },
+                        || MenuAction::SetMidiInputDevice(None),
+                    ))
+                    .chain(
+                        Reaper::get()
+                            .midi_input_devices()
+                            .filter(|d| d.is_available())
+                            .map(|dev| {
+                                let dev_id = dev.id();
+                                item_with_opts(
+                                    get_midi_input_device_label(dev),
+                                    ItemOpts {
+                                        enabled: source_is_midi,
+                                        checked: midi_input_device == Some(dev_id),
+                                    },
+                                    move || MenuAction::SetMidiInputDevice(Some(dev_id)),
+                                )
+                            }),
+                    )
+                    .collect(),
+                ),
+                menu(
+                    "MIDI output device",
+                    iter::once(item_with_opts(
+                        "<Instance feedback output>",
+                        ItemOpts {
+                            enabled: source_is_midi,
+                            checked: midi_output_device.is_none(),
+                        },
+                        || MenuAction::SetMidiOutputDevice(None),
+                    ))
+                    .chain(
+                        Reaper::get()
+                            .midi_output_devices()
+                            .filter(|d| d.is_available())
+                            .map(|dev| {
+                                let dev_id = dev.id();
+                                item_with_opts(
+                                    get_midi_output_device_label(dev),
+                                    ItemOpts {
+                                        enabled: source_is_midi,
+                                        checked: midi_output_device == Some(dev_id),
+                                    },
+                                    move || MenuAction::SetMidiOutputDevice(Some(dev_id)),
+                                )
+                            }),
+                    )
+                    .collect(),
+                ),
                 menu(
                     "Advanced",
                     vec![
@@ -777,6 +862,32 @@ impl MappingRowPanel {
                     group_id,
                 );
             }
+            MenuAction::ToggleSolo => {
+                self.session()
+                    .borrow_mut()
+                    .toggle_mapping_solo(QualifiedMappingId::new(
+                        triple.compartment,
+                        triple.mapping_id,
+                    ));
+            }
+            MenuAction::SetMidiInputDevice(dev_id) => {
+                if let Some(mapping) = self.mapping.borrow().as_ref() {
+                    mapping
+                        .borrow_mut()
+                        .source_model
+                        .midi_input_device
+                        .set(dev_id);
+                }
+            }
+            MenuAction::SetMidiOutputDevice(dev_id) => {
+                if let Some(mapping) = self.mapping.borrow().as_ref() {
+                    mapping
+                        .borrow_mut()
+                        .source_model
+                        .midi_output_device
+                        .set(dev_id);
+                }
+            }
             MenuAction::LogDebugInfo => self
                 .session()
                 .borrow()
@@ -221,6 +221,7 @@
 #define IDC_MAPPING_MATCHED_INDICATOR_TEXT 40133
 #define IDC_MAPPING_ROW_MATCHED_INDICATOR_TEXT 40134
 #define ID_TARGET_VALUE_OFF_BUTTON      40136
+#define ID_SETTINGS_MORE_BUTTON         40137
 
 // Next default values for new objects
 // 
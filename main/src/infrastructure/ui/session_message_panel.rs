@@ -31,8 +31,14 @@ impl SessionMessagePanel {
         let session = self.session();
         let session = session.borrow();
         let (title_addition, msg) = if let Some(state) = session.learn_many_state() {
-            if let Some((_, mapping)) =
-                session.find_mapping_and_index_by_id(state.compartment, state.current_mapping_id)
+            if state.sub_state == LearnManySubState::WaitingForTarget {
+                (
+                    "Learning many targets".to_string(),
+                    "Touch the next target which you want to control!".to_string(),
+                )
+            } else if let Some((_, mapping)) = state
+                .current_mapping_id
+                .and_then(|id| session.find_mapping_and_index_by_id(state.compartment, id))
             {
                 let mapping = mapping.borrow();
                 let mapping_label = format!("mapping {}", mapping.effective_name());
@@ -60,6 +66,7 @@ impl SessionMessagePanel {
                         format!("Learning target for {}", mapping_label),
                         "Now touch the target which you want to control!".to_string(),
                     ),
+                    LearnManySubState::WaitingForTarget => ("".to_string(), "".to_string()),
                 }
             } else {
                 ("".to_string(), "".to_string())